@@ -0,0 +1,158 @@
+use anyhow::Result;
+use aws_config::SdkConfig;
+use aws_sdk_applicationautoscaling::types::{ScalableTarget, ServiceNamespace, SuspendedState};
+use aws_sdk_applicationautoscaling::Client as AppAutoScalingClient;
+use tracing::info;
+
+/// Tag marking a scalable target whose scaling was suspended by the scheduler.
+const SUSPENDED_SCALING_TAG: &str = "scheduler:suspended-scaling";
+
+/// Suspends and resumes the Application Auto Scaling targets of stopped resources.
+///
+/// Without this, the scaling policies and scheduled actions of an ECS service or
+/// a DynamoDB table scale it back up right after it was stopped.
+/// On suspend: suspends dynamic and scheduled scaling of every scalable target of
+/// the resource, and marks the target with the `scheduler:suspended-scaling` tag.
+/// On resume: resumes scaling of the targets carrying that tag, so that targets
+/// suspended by hand stay suspended.
+pub struct AppAutoScaling {
+    client: AppAutoScalingClient,
+    dry_run: bool,
+}
+
+impl AppAutoScaling {
+    pub fn new(config: &SdkConfig, dry_run: bool) -> Self {
+        Self::from_client(AppAutoScalingClient::new(config), dry_run)
+    }
+
+    /// Create a new helper from an existing client, e.g. a client with mocked responses.
+    pub fn from_client(client: AppAutoScalingClient, dry_run: bool) -> Self {
+        Self { client, dry_run }
+    }
+
+    /// Suspend scaling of the scalable targets of the given resources.
+    ///
+    /// Resource IDs follow the Application Auto Scaling format,
+    /// e.g. `service/cluster/service` or `table/table-name`.
+    pub async fn suspend(
+        &self,
+        namespace: ServiceNamespace,
+        resource_ids: &[String],
+    ) -> Result<()> {
+        for target in self.scalable_targets(namespace, resource_ids).await? {
+            if target.suspended_state().is_some_and(is_fully_suspended) {
+                continue;
+            }
+            let Some(target_arn) = target.scalable_target_arn() else {
+                continue;
+            };
+            if self.dry_run {
+                info!(target = %target.resource_id(), dimension = %target.scalable_dimension(), "Dry run: would suspend Application Auto Scaling");
+                continue;
+            }
+
+            info!(target = %target.resource_id(), dimension = %target.scalable_dimension(), "Suspending Application Auto Scaling");
+            self.client
+                .tag_resource()
+                .resource_arn(target_arn)
+                .tags(SUSPENDED_SCALING_TAG, "true")
+                .send()
+                .await?;
+            self.set_suspended(&target, true).await?;
+        }
+        Ok(())
+    }
+
+    /// Resume scaling of the scalable targets of the given resources suspended by [`Self::suspend`].
+    pub async fn resume(&self, namespace: ServiceNamespace, resource_ids: &[String]) -> Result<()> {
+        for target in self.scalable_targets(namespace, resource_ids).await? {
+            let Some(target_arn) = target.scalable_target_arn() else {
+                continue;
+            };
+            let resp = self
+                .client
+                .list_tags_for_resource()
+                .resource_arn(target_arn)
+                .send()
+                .await?;
+            if !resp
+                .tags()
+                .is_some_and(|t| t.contains_key(SUSPENDED_SCALING_TAG))
+            {
+                continue;
+            }
+            if self.dry_run {
+                info!(target = %target.resource_id(), dimension = %target.scalable_dimension(), "Dry run: would resume Application Auto Scaling");
+                continue;
+            }
+
+            info!(target = %target.resource_id(), dimension = %target.scalable_dimension(), "Resuming Application Auto Scaling");
+            self.set_suspended(&target, false).await?;
+            self.client
+                .untag_resource()
+                .resource_arn(target_arn)
+                .tag_keys(SUSPENDED_SCALING_TAG)
+                .send()
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn scalable_targets(
+        &self,
+        namespace: ServiceNamespace,
+        resource_ids: &[String],
+    ) -> Result<Vec<ScalableTarget>> {
+        if resource_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut targets = Vec::new();
+        let mut next_token: Option<String> = None;
+
+        loop {
+            let mut req = self
+                .client
+                .describe_scalable_targets()
+                .service_namespace(namespace.clone())
+                .set_resource_ids(Some(resource_ids.to_vec()));
+            if let Some(ref token) = next_token {
+                req = req.next_token(token);
+            }
+
+            let resp = req.send().await?;
+            targets.extend(resp.scalable_targets().iter().cloned());
+
+            match resp.next_token() {
+                Some(token) if !token.is_empty() => next_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        Ok(targets)
+    }
+
+    async fn set_suspended(&self, target: &ScalableTarget, suspended: bool) -> Result<()> {
+        self.client
+            .register_scalable_target()
+            .service_namespace(target.service_namespace().clone())
+            .resource_id(target.resource_id())
+            .scalable_dimension(target.scalable_dimension().clone())
+            .suspended_state(
+                SuspendedState::builder()
+                    .dynamic_scaling_in_suspended(suspended)
+                    .dynamic_scaling_out_suspended(suspended)
+                    .scheduled_scaling_suspended(suspended)
+                    .build(),
+            )
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+fn is_fully_suspended(state: &SuspendedState) -> bool {
+    state.dynamic_scaling_in_suspended().unwrap_or_default()
+        && state.dynamic_scaling_out_suspended().unwrap_or_default()
+        && state.scheduled_scaling_suspended().unwrap_or_default()
+}
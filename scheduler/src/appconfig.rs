@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use aws_config::BehaviorVersion;
+use aws_sdk_appconfigdata::Client;
+
+use crate::config::{self, AppConfigSource};
+
+/// Session reading the configuration deployed to an AWS AppConfig profile, and
+/// polling it for new deployments.
+///
+/// AppConfig is called with the ambient credentials and region. A session whose
+/// token expired or was lost to an error is started again on the next poll.
+pub struct Poller {
+    client: Client,
+    source: AppConfigSource,
+    token: Option<String>,
+    content: Vec<u8>,
+}
+
+impl Poller {
+    /// Start a configuration session and fetch the deployed configuration.
+    pub async fn start(source: &AppConfigSource) -> Result<Self> {
+        let sdk_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+        let mut poller = Self::from_client(Client::new(&sdk_config), source);
+        poller.poll().await?;
+        Ok(poller)
+    }
+
+    /// Create a poller from an existing client, e.g. a client with mocked responses.
+    /// Nothing is fetched until the first poll.
+    pub fn from_client(client: Client, source: &AppConfigSource) -> Self {
+        Self {
+            client,
+            source: source.clone(),
+            token: None,
+            content: Vec::new(),
+        }
+    }
+
+    /// Options of the deployed configuration, keyed by environment variable name.
+    pub fn options(&self) -> Result<HashMap<String, String>> {
+        config::parse_options(&self.content)
+            .with_context(|| format!("Invalid AWS AppConfig configuration '{}'", self.source))
+    }
+
+    /// Fetch the latest deployed configuration. Returns whether it changed since the previous poll.
+    pub async fn poll(&mut self) -> Result<bool> {
+        let token = match self.token.take() {
+            Some(token) => token,
+            None => self.start_session().await?,
+        };
+        let resp = self
+            .client
+            .get_latest_configuration()
+            .configuration_token(token)
+            .send()
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to get AWS AppConfig configuration '{}'",
+                    self.source
+                )
+            })?;
+        self.token = resp.next_poll_configuration_token().map(str::to_string);
+
+        // The configuration is empty when it did not change since the previous call of the session.
+        match resp.configuration().map(|blob| blob.as_ref()) {
+            Some(content) if !content.is_empty() && content != self.content.as_slice() => {
+                self.content = content.to_vec();
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn start_session(&self) -> Result<String> {
+        let resp = self
+            .client
+            .start_configuration_session()
+            .application_identifier(&self.source.application)
+            .environment_identifier(&self.source.environment)
+            .configuration_profile_identifier(&self.source.profile)
+            .required_minimum_poll_interval_in_seconds(self.source.poll_interval.as_secs() as i32)
+            .send()
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to start AWS AppConfig session for '{}'",
+                    self.source
+                )
+            })?;
+        resp.initial_configuration_token()
+            .map(str::to_string)
+            .with_context(|| {
+                format!(
+                    "AWS AppConfig returned no configuration token for '{}'",
+                    self.source
+                )
+            })
+    }
+}
@@ -1,9 +1,17 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_apprunner::types::ServiceStatus;
 use aws_sdk_apprunner::Client as AppRunnerClient;
 use aws_sdk_resourcegroupstagging::Client as TaggingClient;
 use tracing::{error, info};
 
-use crate::filter_resources_by_tags;
+use crate::arn::Arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
 
 /// Stop/start handler for AWS App Runner services in a given AWS region.
 ///
@@ -12,82 +20,266 @@ use crate::filter_resources_by_tags;
 pub struct AppRunnerScheduler {
     apprunner: AppRunnerClient,
     tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
 }
 
 impl AppRunnerScheduler {
-    pub async fn new(region: &str) -> Self {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
-
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
         Self {
-            apprunner: AppRunnerClient::new(&config),
-            tagging: TaggingClient::new(&config),
+            apprunner: AppRunnerClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
         }
     }
 
-    pub async fn stop(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "apprunner:service", tag_key, tag_value).await?;
-        info!(count = arns.len(), "Found App Runner services to pause");
-
-        for arn in &arns {
-            let service_name = extract_service_name(arn);
-            if let Err(e) = self.pause_service(arn).await {
-                error!(service = %service_name, error = %e, "Failed to pause App Runner service");
-            }
-        }
-
-        Ok(())
-    }
-
-    pub async fn start(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "apprunner:service", tag_key, tag_value).await?;
-        info!(count = arns.len(), "Found App Runner services to resume");
-
-        for arn in &arns {
-            let service_name = extract_service_name(arn);
-            if let Err(e) = self.resume_service(arn).await {
-                error!(service = %service_name, error = %e, "Failed to resume App Runner service");
-            }
-        }
+    /// Get the current status of an App Runner service (e.g. `RUNNING`, `PAUSED`).
+    async fn service_status(&self, service_arn: &str) -> Result<ServiceStatus> {
+        let resp = self
+            .apprunner
+            .describe_service()
+            .service_arn(service_arn)
+            .send()
+            .await?;
 
-        Ok(())
+        resp.service()
+            .map(|s| s.status().clone())
+            .with_context(|| format!("App Runner service '{}' not found", service_arn))
     }
 
-    async fn pause_service(&self, service_arn: &str) -> Result<()> {
+    async fn pause_service(&self, service_arn: &str) -> Result<Outcome> {
         let service_name = extract_service_name(service_arn);
+        let status = self.service_status(service_arn).await?;
+        if status == ServiceStatus::Paused {
+            info!(service = %service_name, "App Runner service already paused, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(service = %service_name, "Dry run: would pause App Runner service");
+            return Ok(Outcome::Applied);
+        }
         info!(service = %service_name, "Pausing App Runner service");
         self.apprunner
             .pause_service()
             .service_arn(service_arn)
             .send()
             .await?;
-        Ok(())
+        Ok(Outcome::Applied)
     }
 
-    async fn resume_service(&self, service_arn: &str) -> Result<()> {
+    async fn resume_service(&self, service_arn: &str) -> Result<Outcome> {
         let service_name = extract_service_name(service_arn);
+        let status = self.service_status(service_arn).await?;
+        if status == ServiceStatus::Running {
+            info!(service = %service_name, "App Runner service already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(service = %service_name, "Dry run: would resume App Runner service");
+            return Ok(Outcome::Applied);
+        }
         info!(service = %service_name, "Resuming App Runner service");
         self.apprunner
             .resume_service()
             .service_arn(service_arn)
             .send()
             .await?;
-        Ok(())
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for AppRunnerScheduler {
+    fn name(&self) -> &'static str {
+        "apprunner"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "apprunner:service", filter)
+                .await?;
+        info!(count = arns.len(), "Found App Runner services to pause");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let service_name = extract_service_name(arn);
+            let result = self.pause_service(arn).await;
+            (service_name, result)
+        })
+        .await;
+        for (service_name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&service_name, outcome),
+                Err(e) => {
+                    counts.fail(&service_name, &e);
+                    error!(service = %service_name, error = %e, "Failed to pause App Runner service");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "apprunner:service", filter)
+                .await?;
+        info!(count = arns.len(), "Found App Runner services to resume");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let service_name = extract_service_name(arn);
+            let result = self.resume_service(arn).await;
+            (service_name, result)
+        })
+        .await;
+        for (service_name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&service_name, outcome),
+                Err(e) => {
+                    counts.fail(&service_name, &e);
+                    error!(service = %service_name, error = %e, "Failed to resume App Runner service");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the App Runner services matching the given filter with their current status.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "apprunner:service", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let status = self.service_status(&arn).await?;
+            items.push(InventoryItem::new(
+                arn,
+                "apprunner:service",
+                status.as_str(),
+            ));
+        }
+        Ok(items)
     }
 }
 
 /// Extract the service name from an App Runner service ARN.
 ///
-/// Expected ARN format: `arn:aws:apprunner:region:account:service/name/id`
+/// Expected ARN format: `arn:partition:apprunner:region:account:service/name/id`
 fn extract_service_name(arn: &str) -> String {
-    let parts: Vec<&str> = arn.split('/').collect();
-    if parts.len() >= 2 {
-        parts[parts.len() - 2].to_string()
-    } else {
-        arn.to_string()
+    match Arn::parse(arn).map(|a| a.resource_segments()).as_deref() {
+        Ok([_, name, ..]) => name.to_string(),
+        _ => arn.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_apprunner::operation::describe_service::DescribeServiceOutput;
+    use aws_sdk_apprunner::operation::pause_service::PauseServiceOutput;
+    use aws_sdk_apprunner::operation::resume_service::ResumeServiceOutput;
+    use aws_sdk_apprunner::primitives::DateTime;
+    use aws_sdk_apprunner::types::Service;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const API_ARN: &str =
+        "arn:aws:apprunner:eu-west-1:123456789012:service/api/8fe1e10304f84fd2b0df550fe98a71fa";
+    const WEB_ARN: &str =
+        "arn:aws:apprunner:eu-west-1:123456789012:service/web/2a1b0c9d8e7f46a5b4c3d2e1f0a9b8c7";
+
+    /// Rule describing the service `service_arn` with the given status.
+    fn describe_service_rule(service_arn: &'static str, status: ServiceStatus) -> Rule {
+        mock!(AppRunnerClient::describe_service)
+            .match_requests(move |req| req.service_arn() == Some(service_arn))
+            .then_output(move || {
+                DescribeServiceOutput::builder()
+                    .service(
+                        Service::builder()
+                            .service_arn(service_arn)
+                            .service_name(extract_service_name(service_arn))
+                            .service_id("id")
+                            .status(status.clone())
+                            .created_at(DateTime::from_secs(0))
+                            .updated_at(DateTime::from_secs(0))
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+            })
+    }
+
+    fn scheduler(apprunner: AppRunnerClient, tagging: TaggingClient) -> AppRunnerScheduler {
+        AppRunnerScheduler {
+            apprunner,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_pauses_running_services_only() {
+        let arns = get_resources_rule("apprunner:service", &[API_ARN, WEB_ARN]);
+        let api = describe_service_rule(API_ARN, ServiceStatus::Running);
+        let web = describe_service_rule(WEB_ARN, ServiceStatus::Paused);
+        let pause = mock!(AppRunnerClient::pause_service)
+            .match_requests(|req| req.service_arn() == Some(API_ARN))
+            .then_output(|| PauseServiceOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_apprunner, RuleMode::MatchAny, [&api, &web, &pause]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(counts.resources[0].resource, "api");
+        assert_eq!(pause.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_resumes_paused_services() {
+        let arns = get_resources_rule("apprunner:service", &[WEB_ARN]);
+        let web = describe_service_rule(WEB_ARN, ServiceStatus::Paused);
+        let resume = mock!(AppRunnerClient::resume_service)
+            .match_requests(|req| req.service_arn() == Some(WEB_ARN))
+            .then_output(|| ResumeServiceOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_apprunner, RuleMode::MatchAny, [&web, &resume]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(resume.num_calls(), 1);
     }
 }
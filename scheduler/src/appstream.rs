@@ -0,0 +1,265 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_appstream::types::FleetState;
+use aws_sdk_appstream::Client as AppStreamClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Stop/start handler for AppStream 2.0 fleets in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover fleets matching a tag,
+/// then performs the requested action on each one. Running fleets are billed
+/// per instance-hour, even without active streaming sessions.
+pub struct AppStreamScheduler {
+    appstream: AppStreamClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl AppStreamScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            appstream: AppStreamClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Get the current state of a fleet (e.g. `RUNNING`, `STOPPED`).
+    async fn fleet_state(&self, fleet: &str) -> Result<FleetState> {
+        let resp = self.appstream.describe_fleets().names(fleet).send().await?;
+
+        resp.fleets()
+            .first()
+            .and_then(|f| f.state().cloned())
+            .with_context(|| format!("AppStream fleet '{}' not found", fleet))
+    }
+
+    async fn stop_fleet(&self, fleet: &str) -> Result<Outcome> {
+        let state = self.fleet_state(fleet).await?;
+        if matches!(state, FleetState::Stopped | FleetState::Stopping) {
+            info!(fleet = %fleet, state = %state.as_str(), "AppStream fleet already stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(fleet = %fleet, "Dry run: would stop AppStream fleet");
+            return Ok(Outcome::Applied);
+        }
+        info!(fleet = %fleet, "Stopping AppStream fleet");
+        self.appstream.stop_fleet().name(fleet).send().await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn start_fleet(&self, fleet: &str) -> Result<Outcome> {
+        let state = self.fleet_state(fleet).await?;
+        if matches!(state, FleetState::Running | FleetState::Starting) {
+            info!(fleet = %fleet, state = %state.as_str(), "AppStream fleet already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(fleet = %fleet, "Dry run: would start AppStream fleet");
+            return Ok(Outcome::Applied);
+        }
+        info!(fleet = %fleet, "Starting AppStream fleet");
+        self.appstream.start_fleet().name(fleet).send().await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for AppStreamScheduler {
+    fn name(&self) -> &'static str {
+        "appstream"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "appstream:fleet", filter)
+                .await?;
+        info!(count = arns.len(), "Found AppStream fleets to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let fleet = extract_fleet_name(arn);
+            let result = self.stop_fleet(&fleet).await;
+            (fleet, result)
+        })
+        .await;
+        for (fleet, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&fleet, outcome),
+                Err(e) => {
+                    counts.fail(&fleet, &e);
+                    error!(fleet = %fleet, error = %e, "Failed to stop AppStream fleet");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "appstream:fleet", filter)
+                .await?;
+        info!(count = arns.len(), "Found AppStream fleets to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let fleet = extract_fleet_name(arn);
+            let result = self.start_fleet(&fleet).await;
+            (fleet, result)
+        })
+        .await;
+        for (fleet, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&fleet, outcome),
+                Err(e) => {
+                    counts.fail(&fleet, &e);
+                    error!(fleet = %fleet, error = %e, "Failed to start AppStream fleet");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the AppStream fleets matching the given filter with their current state.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "appstream:fleet", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let state = self.fleet_state(&extract_fleet_name(&arn)).await?;
+            items.push(InventoryItem::new(arn, "appstream:fleet", state.as_str()));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the fleet name from an AppStream fleet ARN.
+///
+/// Expected ARN format: `arn:partition:appstream:region:account:fleet/fleet-name`
+fn extract_fleet_name(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_appstream::operation::describe_fleets::DescribeFleetsOutput;
+    use aws_sdk_appstream::operation::start_fleet::StartFleetOutput;
+    use aws_sdk_appstream::operation::stop_fleet::StopFleetOutput;
+    use aws_sdk_appstream::types::Fleet;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const FLEET_ARN_PREFIX: &str = "arn:aws:appstream:eu-west-1:123456789012:fleet/";
+
+    /// Rule describing `fleet` in the given state.
+    fn describe_fleet_rule(fleet: &'static str, state: FleetState) -> Rule {
+        mock!(AppStreamClient::describe_fleets)
+            .match_requests(move |req| req.names() == [fleet.to_string()])
+            .then_output(move || {
+                DescribeFleetsOutput::builder()
+                    .fleets(Fleet::builder().name(fleet).state(state.clone()).build())
+                    .build()
+            })
+    }
+
+    fn scheduler(
+        appstream: AppStreamClient,
+        tagging: TaggingClient,
+        dry_run: bool,
+    ) -> AppStreamScheduler {
+        AppStreamScheduler {
+            appstream,
+            tagging,
+            dry_run,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_skips_fleets_already_stopping() {
+        let arns = get_resources_rule(
+            "appstream:fleet",
+            &[
+                &format!("{FLEET_ARN_PREFIX}training"),
+                &format!("{FLEET_ARN_PREFIX}support"),
+            ],
+        );
+        let training = describe_fleet_rule("training", FleetState::Running);
+        let support = describe_fleet_rule("support", FleetState::Stopping);
+        let stop = mock!(AppStreamClient::stop_fleet)
+            .match_requests(|req| req.name() == Some("training"))
+            .then_output(|| StopFleetOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_appstream,
+                RuleMode::MatchAny,
+                [&training, &support, &stop]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+            false,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(stop.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_in_dry_run_does_not_start_fleets() {
+        let arns = get_resources_rule("appstream:fleet", &[&format!("{FLEET_ARN_PREFIX}training")]);
+        let training = describe_fleet_rule("training", FleetState::Stopped);
+        let start =
+            mock!(AppStreamClient::start_fleet).then_output(|| StartFleetOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_appstream, RuleMode::MatchAny, [&training, &start]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+            true,
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(start.num_calls(), 0);
+    }
+}
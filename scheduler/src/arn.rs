@@ -0,0 +1,168 @@
+use anyhow::{bail, Context, Result};
+
+/// Partitions of the regions the scheduler can run in: the AWS regions, the
+/// AWS GovCloud (US) regions and the AWS China regions.
+const PARTITIONS: [&str; 3] = ["aws", "aws-us-gov", "aws-cn"];
+
+/// Amazon Resource Name, split into its fields.
+///
+/// `arn:partition:service:region:account:resource`, where the resource is written
+/// `type/id`, `type:id` or `id` depending on the service, and may hold several
+/// segments (e.g. `service/cluster-name/service-name` for ECS).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arn<'a> {
+    pub partition: &'a str,
+    pub service: &'a str,
+    /// Empty for global resources (CloudFront, Route 53...).
+    pub region: &'a str,
+    /// Empty for resources that do not belong to an account (S3 buckets...).
+    pub account: &'a str,
+    pub resource: &'a str,
+}
+
+impl<'a> Arn<'a> {
+    pub fn parse(arn: &'a str) -> Result<Self> {
+        let mut fields = arn.splitn(6, ':');
+        let (
+            Some("arn"),
+            Some(partition),
+            Some(service),
+            Some(region),
+            Some(account),
+            Some(resource),
+        ) = (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        )
+        else {
+            bail!(
+                "invalid ARN '{}': expected arn:partition:service:region:account:resource",
+                arn
+            );
+        };
+        if !PARTITIONS.contains(&partition) {
+            bail!("invalid ARN '{}': unknown partition '{}'", arn, partition);
+        }
+        if service.is_empty() || resource.is_empty() {
+            bail!("invalid ARN '{}': missing service or resource", arn);
+        }
+        Ok(Self {
+            partition,
+            service,
+            region,
+            account,
+            resource,
+        })
+    }
+
+    /// Segments of the resource, its type included: `["nodegroup", "cluster", "name", "uuid"]`
+    /// for `nodegroup/cluster/name/uuid`, `["db", "instance-id"]` for `db:instance-id`.
+    pub fn resource_segments(&self) -> Vec<&'a str> {
+        self.resource
+            .split(['/', ':'])
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    /// Identifier of the resource, its type left out (instance ID, DB name, table name...):
+    /// `i-1` for `instance/i-1`, `app` for `db:app`, `name/with/slashes` for `alarm:name/with/slashes`.
+    ///
+    /// Only the type is stripped, the rest is kept as is: identifiers such as alarm
+    /// names may contain `/` or `:`.
+    pub fn resource_id(&self) -> &'a str {
+        self.resource
+            .trim_start_matches('/')
+            .split_once(['/', ':'])
+            .map_or(self.resource, |(_, id)| id)
+    }
+}
+
+/// Identifier of the resource of an ARN, or `arn` itself when it cannot be parsed.
+pub fn resource_id(arn: &str) -> String {
+    Arn::parse(arn)
+        .map(|a| a.resource_id())
+        .unwrap_or(arn)
+        .to_string()
+}
+
+/// Partition of a region, for the ARNs built by the scheduler.
+pub fn partition(region: &str) -> &'static str {
+    if region.starts_with("us-gov-") {
+        "aws-us-gov"
+    } else if region.starts_with("cn-") {
+        "aws-cn"
+    } else {
+        "aws"
+    }
+}
+
+/// Account of an ARN, e.g. the account of an IAM role.
+pub fn account(arn: &str) -> Result<&str> {
+    let parsed = Arn::parse(arn)?;
+    Some(parsed.account)
+        .filter(|a| !a.is_empty())
+        .with_context(|| format!("invalid ARN '{}': missing account", arn))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_understands_govcloud_and_china_partitions() {
+        let gov = Arn::parse("arn:aws-us-gov:ec2:us-gov-west-1:123456789012:instance/i-1").unwrap();
+        assert_eq!(
+            (gov.partition, gov.region, gov.resource_id()),
+            ("aws-us-gov", "us-gov-west-1", "i-1")
+        );
+
+        let cn = Arn::parse("arn:aws-cn:rds:cn-north-1:123456789012:db:app").unwrap();
+        assert_eq!(
+            (cn.partition, cn.region, cn.resource_id()),
+            ("aws-cn", "cn-north-1", "app")
+        );
+
+        assert!(Arn::parse("arn:unknown:ec2:eu-west-1:123456789012:instance/i-1").is_err());
+        assert!(Arn::parse("i-1").is_err());
+    }
+
+    #[test]
+    fn resource_segments_split_on_slashes_and_colons() {
+        let arn = Arn::parse("arn:aws:ecs:eu-west-1:123456789012:service/cluster/web").unwrap();
+        assert_eq!(arn.resource_segments(), ["service", "cluster", "web"]);
+
+        let legacy = Arn::parse("arn:aws:ecs:eu-west-1:123456789012:service/web").unwrap();
+        assert_eq!(legacy.resource_segments(), ["service", "web"]);
+
+        let global = Arn::parse("arn:aws:route53:::healthcheck/abc").unwrap();
+        assert_eq!(
+            (global.region, global.account, global.resource_id()),
+            ("", "", "abc")
+        );
+    }
+
+    #[test]
+    fn resource_id_strips_only_the_resource_type() {
+        let cases = [
+            ("arn:aws:ec2:eu-west-1:123456789012:instance/i-1", "i-1"),
+            ("arn:aws:rds:eu-west-1:123456789012:db:app", "app"),
+            (
+                "arn:aws:cloudwatch:eu-west-1:123456789012:alarm:TargetTracking-table/MyTable-AlarmHigh-1a2b",
+                "TargetTracking-table/MyTable-AlarmHigh-1a2b",
+            ),
+            (
+                "arn:aws:emr-serverless:eu-west-1:123456789012:/applications/00f1",
+                "00f1",
+            ),
+            ("arn:aws:sns:eu-west-1:123456789012:topic", "topic"),
+            ("web", "web"),
+        ];
+        for (arn, id) in cases {
+            assert_eq!(resource_id(arn), id, "{}", arn);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use aws_sdk_dynamodb::types::{AttributeValue, PutRequest, WriteRequest};
+use aws_sdk_dynamodb::Client as DynamoDbClient;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client as S3Client;
+use aws_sdk_sts::Client as StsClient;
+use serde::Serialize;
+use tracing::info;
+
+use crate::arn;
+use crate::config::{AppConfig, StoreLocation};
+use crate::credentials;
+use crate::summary::{ExecutionSummary, ResourceStatus};
+
+/// Maximum number of items written by a single `BatchWriteItem` call.
+const BATCH_SIZE: usize = 25;
+
+/// Number of times the items left unprocessed by DynamoDB are written again.
+const MAX_UNPROCESSED_RETRIES: usize = 5;
+
+/// Audit record of the action performed on a single resource.
+#[derive(Debug, Serialize)]
+struct AuditRecord<'a> {
+    timestamp: &'a str,
+    account: &'a str,
+    region: &'a str,
+    service: &'a str,
+    resource: &'a str,
+    action: &'a str,
+    dry_run: bool,
+    outcome: ResourceStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+    run_started_at: &'a str,
+}
+
+/// Record every resource processed during a run in the audit store.
+///
+/// With `dynamodb://table`, each resource gets an item whose partition key is the
+/// string attribute `resource` and whose sort key is the string attribute
+/// `timestamp`, so that the history of a resource can be queried. With
+/// `s3://bucket/prefix`, the run is written as a JSON Lines object under
+/// `prefix/YYYY/MM/DD/`. The store is accessed in the first region of
+/// `AWS_REGIONS`, with the ambient credentials.
+pub async fn record(
+    config: &AppConfig,
+    store: &StoreLocation,
+    summary: &ExecutionSummary,
+) -> Result<()> {
+    let sdk_config = credentials::load_config(config, &config.aws_regions[0], None).await;
+    let accounts = accounts(&StsClient::new(&sdk_config), summary).await?;
+
+    let records: Vec<AuditRecord> = summary
+        .services
+        .iter()
+        .flat_map(|service| {
+            let account = accounts[&service.role_arn].as_str();
+            service
+                .counts
+                .resources
+                .iter()
+                .map(move |resource| AuditRecord {
+                    timestamp: &resource.timestamp,
+                    account,
+                    region: &service.region,
+                    service: &service.service,
+                    resource: &resource.resource,
+                    action: &summary.action,
+                    dry_run: summary.dry_run,
+                    outcome: resource.status,
+                    error: resource.error.as_deref(),
+                    run_started_at: &summary.started_at,
+                })
+        })
+        .collect();
+    if records.is_empty() {
+        return Ok(());
+    }
+
+    info!(store = %store, count = records.len(), "Writing audit records");
+    match store {
+        StoreLocation::DynamoDb { table } => {
+            write_items(&DynamoDbClient::new(&sdk_config), table, &records).await
+        }
+        StoreLocation::S3 { bucket, prefix } => {
+            write_object(
+                &S3Client::new(&sdk_config),
+                bucket,
+                prefix,
+                summary,
+                &records,
+            )
+            .await
+        }
+    }
+}
+
+/// Resolve the account ID of every role of the run: parsed from the role ARN,
+/// or looked up with STS for the ambient credentials.
+async fn accounts(
+    sts: &StsClient,
+    summary: &ExecutionSummary,
+) -> Result<HashMap<Option<String>, String>> {
+    let mut accounts = HashMap::new();
+    for service in &summary.services {
+        if accounts.contains_key(&service.role_arn) {
+            continue;
+        }
+        let account = match service
+            .role_arn
+            .as_deref()
+            .and_then(|role| arn::account(role).ok())
+        {
+            Some(account) => account.to_string(),
+            None => sts
+                .get_caller_identity()
+                .send()
+                .await
+                .context("Failed to look up the account of the ambient credentials")?
+                .account()
+                .unwrap_or_default()
+                .to_string(),
+        };
+        accounts.insert(service.role_arn.clone(), account);
+    }
+    Ok(accounts)
+}
+
+/// Write one item per record, retrying the items left unprocessed by DynamoDB.
+async fn write_items(
+    dynamodb: &DynamoDbClient,
+    table: &str,
+    records: &[AuditRecord<'_>],
+) -> Result<()> {
+    for batch in records.chunks(BATCH_SIZE) {
+        let mut requests: Vec<WriteRequest> =
+            batch.iter().map(write_request).collect::<Result<_>>()?;
+        for attempt in 0..=MAX_UNPROCESSED_RETRIES {
+            if attempt > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(100 << attempt)).await;
+            }
+            let resp = dynamodb
+                .batch_write_item()
+                .request_items(table, requests)
+                .send()
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to write audit records to DynamoDB table '{}'",
+                        table
+                    )
+                })?;
+            requests = resp
+                .unprocessed_items()
+                .and_then(|items| items.get(table))
+                .cloned()
+                .unwrap_or_default();
+            if requests.is_empty() {
+                break;
+            }
+        }
+        if !requests.is_empty() {
+            bail!(
+                "{} audit record(s) could not be written to DynamoDB table '{}'",
+                requests.len(),
+                table
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Build the DynamoDB put request of a record, with one string or boolean attribute per field.
+fn write_request(record: &AuditRecord<'_>) -> Result<WriteRequest> {
+    let mut item = HashMap::new();
+    for (name, value) in [
+        ("timestamp", Some(record.timestamp)),
+        ("account", Some(record.account)),
+        ("region", Some(record.region)),
+        ("service", Some(record.service)),
+        ("resource", Some(record.resource)),
+        ("action", Some(record.action)),
+        ("error", record.error),
+        ("run_started_at", Some(record.run_started_at)),
+    ] {
+        if let Some(value) = value {
+            item.insert(name.to_string(), AttributeValue::S(value.to_string()));
+        }
+    }
+    item.insert(
+        "outcome".to_string(),
+        AttributeValue::S(record.outcome.as_str().to_string()),
+    );
+    item.insert("dry_run".to_string(), AttributeValue::Bool(record.dry_run));
+
+    let put = PutRequest::builder()
+        .set_item(Some(item))
+        .build()
+        .context("Failed to build audit record")?;
+    Ok(WriteRequest::builder().put_request(put).build())
+}
+
+/// Write the records of a run as a single JSON Lines object.
+async fn write_object(
+    s3: &S3Client,
+    bucket: &str,
+    prefix: &str,
+    summary: &ExecutionSummary,
+    records: &[AuditRecord<'_>],
+) -> Result<()> {
+    let now = chrono::Utc::now();
+    let name = format!(
+        "{}-{}.jsonl",
+        now.format("%Y%m%dT%H%M%S%.3fZ"),
+        summary.action
+    );
+    let key = if prefix.is_empty() {
+        format!("{}/{}", now.format("%Y/%m/%d"), name)
+    } else {
+        format!("{}/{}/{}", prefix, now.format("%Y/%m/%d"), name)
+    };
+
+    let mut body = String::new();
+    for record in records {
+        body.push_str(&serde_json::to_string(record)?);
+        body.push('\n');
+    }
+
+    s3.put_object()
+        .bucket(bucket)
+        .key(&key)
+        .content_type("application/x-ndjson")
+        .body(ByteStream::from(body.into_bytes()))
+        .send()
+        .await
+        .with_context(|| format!("Failed to write audit records to 's3://{}/{}'", bucket, key))?;
+    Ok(())
+}
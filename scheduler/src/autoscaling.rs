@@ -1,34 +1,153 @@
-use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_autoscaling::primitives::DateTime;
+use aws_sdk_autoscaling::types::{AutoScalingGroup, ScheduledUpdateGroupAction, Tag};
 use aws_sdk_autoscaling::Client as AsgClient;
 use aws_sdk_ec2::Client as Ec2Client;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+use crate::concurrency::run_bounded;
+use crate::config::{AutoScalingMode, ScheduledActionsMode};
+use crate::ec2_batch::{self, InstanceCall};
+use crate::filter_resources_by_tags::ResourceFilter;
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::state::{State, StateStore};
+use crate::summary::{Outcome, ResourceCounts};
+use crate::waiters::{self, Waiter};
+
+/// State keys used to remember the capacity of a group before it was scaled to zero.
+const PREVIOUS_MIN_SIZE: &str = "previous-min-size";
+const PREVIOUS_MAX_SIZE: &str = "previous-max-size";
+const PREVIOUS_DESIRED_CAPACITY: &str = "previous-desired-capacity";
+
+/// Tag marking a group whose `ScheduledActions` process was suspended by the scheduler.
+const SUSPENDED_SCHEDULED_ACTIONS_TAG: &str = "scheduler:suspended-scheduled-actions";
+/// Prefix of the tags recording a deleted scheduled action, followed by the action name.
+const SCHEDULED_ACTION_TAG_PREFIX: &str = "scheduler:scheduled-action:";
 
-/// Suspend/resume handler for Auto Scaling Groups in a given AWS region.
+/// Auto Scaling process running the scheduled actions of a group.
+const SCHEDULED_ACTIONS_PROCESS: &str = "ScheduledActions";
+
+/// Maximum length of an Auto Scaling Group tag key and value.
+const MAX_TAG_KEY_LENGTH: usize = 128;
+const MAX_TAG_VALUE_LENGTH: usize = 256;
+
+/// Stop/start handler for Auto Scaling Groups in a given AWS region.
 ///
 /// Discovers ASGs by iterating through all groups and matching the given tag.
-/// On stop: suspends ASG processes, then stops instances.
-/// On start: starts instances, waits for them to be running, then resumes ASG processes.
+///
+/// In [`AutoScalingMode::Suspend`] mode:
+/// - On stop: suspends ASG processes, then stops instances.
+/// - On start: starts instances, waits for them to be running, then resumes ASG processes.
+///
+/// In [`AutoScalingMode::ScaleToZero`] mode:
+/// - On stop: records min/max/desired capacity in the state store (by default,
+///   `scheduler:previous-*` tags), then sets them to 0.
+/// - On start: restores the recorded capacity.
+///
+/// In [`AutoScalingMode::ScaleToZero`] mode, the scheduled actions of the group can
+/// also be neutralised on stop so that they do not scale it back up (see
+/// [`ScheduledActionsMode`]); they are restored on start.
+///
+/// On terminate, whatever the mode, groups are either deleted along with their
+/// instances, or scaled to 0 without recording their capacity.
 pub struct AutoScalingScheduler {
     ec2: Ec2Client,
     asg: AsgClient,
+    state: Arc<dyn StateStore>,
+    mode: AutoScalingMode,
+    scheduled_actions: ScheduledActionsMode,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+    /// Waiter for the started instances to be running before the group is resumed.
+    waiter: Waiter,
 }
 
-impl AutoScalingScheduler {
-    pub async fn new(region: &str) -> Self {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
+/// A scheduled action deleted by the scheduler, as recorded in a group tag.
+#[derive(Debug)]
+struct ScheduledActionRecord {
+    name: String,
+    recurrence: Option<String>,
+    /// Time of a one-time action, in seconds since the epoch.
+    time: Option<i64>,
+    /// End of a recurring action, in seconds since the epoch.
+    end_time: Option<i64>,
+    min_size: Option<i32>,
+    max_size: Option<i32>,
+    desired_capacity: Option<i32>,
+    time_zone: Option<String>,
+}
 
+impl AutoScalingScheduler {
+    pub fn new(
+        config: &SdkConfig,
+        state: Arc<dyn StateStore>,
+        mode: AutoScalingMode,
+        scheduled_actions: ScheduledActionsMode,
+        dry_run: bool,
+        max_concurrent_operations: usize,
+        waiter: Waiter,
+    ) -> Self {
         Self {
-            ec2: Ec2Client::new(&config),
-            asg: AsgClient::new(&config),
+            ec2: Ec2Client::new(config),
+            asg: AsgClient::new(config),
+            state,
+            mode,
+            scheduled_actions,
+            dry_run,
+            max_concurrent_operations,
+            waiter,
         }
     }
 
-    pub async fn stop(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let group_names = self.list_groups(tag_key, tag_value).await?;
-        let instance_ids = self.list_instances(&group_names).await?;
+    /// Terminate all ASGs matching the given filter.
+    ///
+    /// When `delete_groups` is set, groups are force-deleted along with their instances.
+    /// Otherwise, they are scaled to 0 and kept, without recording their capacity.
+    pub async fn terminate(
+        &self,
+        filter: &ResourceFilter,
+        delete_groups: bool,
+    ) -> Result<ResourceCounts> {
+        let group_names = self.list_groups(filter).await?;
+        info!(count = group_names.len(), "Found ASGs to terminate");
+
+        filter.check_discovered(group_names.len())?;
+        let mut counts = ResourceCounts::new(group_names.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &group_names,
+            |name| async move {
+                let result = self.terminate_group(name, delete_groups).await;
+                (name, result)
+            },
+        )
+        .await;
+        for (name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(name, outcome),
+                Err(e) => {
+                    counts.fail(name, &e);
+                    error!(group = %name, error = %e, "Failed to terminate ASG");
+                }
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Suspend ASG processes, then stop the instances of the given groups.
+    async fn suspend_and_stop(&self, group_names: &[String]) -> Result<ResourceCounts> {
+        let instance_ids: Vec<String> = self
+            .list_instances(group_names)
+            .await?
+            .into_iter()
+            .map(|(_, id)| id)
+            .collect();
 
         info!(
             groups = group_names.len(),
@@ -36,24 +155,51 @@ impl AutoScalingScheduler {
             "Found Auto Scaling resources to stop"
         );
 
-        for name in &group_names {
-            if let Err(e) = self.suspend_group(name).await {
-                error!(group = %name, error = %e, "Failed to suspend ASG");
+        let mut counts = ResourceCounts::new(group_names.len() + instance_ids.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            group_names,
+            |name| async move {
+                let result = self.suspend_group(name).await;
+                (name, result)
+            },
+        )
+        .await;
+        for (name, result) in results {
+            match result {
+                Ok(()) => counts.record(name, Outcome::Applied),
+                Err(e) => {
+                    counts.fail(name, &e);
+                    error!(group = %name, error = %e, "Failed to suspend ASG");
+                }
             }
         }
 
-        for id in &instance_ids {
-            if let Err(e) = self.stop_instance(id).await {
-                error!(instance = %id, error = %e, "Failed to stop ASG instance");
+        for (id, result) in self
+            .apply_to_instances(&instance_ids, InstanceCall::Stop)
+            .await
+        {
+            match result {
+                Ok(()) => counts.record(&id, Outcome::Applied),
+                Err(e) => {
+                    counts.fail(&id, &e);
+                    error!(instance = %id, error = %e, "Failed to stop ASG instance");
+                }
             }
         }
 
-        Ok(())
+        Ok(counts)
     }
 
-    pub async fn start(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let group_names = self.list_groups(tag_key, tag_value).await?;
-        let instance_ids = self.list_instances(&group_names).await?;
+    /// Start the instances of the given groups, wait for them to be running,
+    /// then resume ASG processes.
+    ///
+    /// Instances still not running once the waiter gives up are recorded as failed, and
+    /// the processes of their groups are left suspended, so that health checks do not
+    /// replace instances that are still pending.
+    async fn start_and_resume(&self, group_names: &[String]) -> Result<ResourceCounts> {
+        let instances = self.list_instances(group_names).await?;
+        let instance_ids: Vec<String> = instances.iter().map(|(_, id)| id.clone()).collect();
 
         info!(
             groups = group_names.len(),
@@ -61,32 +207,402 @@ impl AutoScalingScheduler {
             "Found Auto Scaling resources to start"
         );
 
+        let mut counts = ResourceCounts::new(group_names.len() + instance_ids.len());
         let mut started: Vec<String> = Vec::new();
-        for id in &instance_ids {
-            match self.start_instance(id).await {
-                Ok(()) => started.push(id.clone()),
-                Err(e) => error!(instance = %id, error = %e, "Failed to start ASG instance"),
+        let mut not_running: HashSet<String> = HashSet::new();
+        for (id, result) in self
+            .apply_to_instances(&instance_ids, InstanceCall::Start)
+            .await
+        {
+            match result {
+                Ok(()) => {
+                    counts.record(&id, Outcome::Applied);
+                    started.push(id);
+                }
+                Err(e) => {
+                    counts.fail(&id, &e);
+                    error!(instance = %id, error = %e, "Failed to start ASG instance");
+                    not_running.insert(id);
+                }
+            }
+        }
+
+        if !started.is_empty() && !self.dry_run {
+            match waiters::wait_instances_running(&self.ec2, &started, self.waiter).await {
+                Ok(pending) => {
+                    for id in pending {
+                        error!(instance = %id, "Timed out waiting for ASG instance to reach running state");
+                        counts.not_converged(
+                            &id,
+                            format!(
+                                "not running after {} attempts (WAITER_MAX_ATTEMPTS)",
+                                self.waiter.max_attempts
+                            ),
+                        );
+                        not_running.insert(id);
+                    }
+                }
+                Err(e) => {
+                    error!(error = %e, "Error while waiting for instances to be running");
+                    not_running.extend(started);
+                }
             }
         }
 
-        if !started.is_empty() {
-            if let Err(e) = self.wait_instances_running(&started).await {
-                error!(error = %e, "Error while waiting for instances to be running");
+        let pending_groups: HashSet<&str> = instances
+            .iter()
+            .filter(|(_, id)| not_running.contains(id))
+            .map(|(group, _)| group.as_str())
+            .collect();
+        let pending_groups = &pending_groups;
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            group_names,
+            |name| async move {
+                let result = if pending_groups.contains(name.as_str()) {
+                    Err(anyhow!(
+                        "instances not running, ASG processes left suspended"
+                    ))
+                } else {
+                    self.resume_group(name).await
+                };
+                (name, result)
+            },
+        )
+        .await;
+        for (name, result) in results {
+            match result {
+                Ok(()) => counts.record(name, Outcome::Applied),
+                Err(e) => {
+                    counts.fail(name, &e);
+                    error!(group = %name, error = %e, "Failed to resume ASG");
+                }
             }
         }
 
-        for name in &group_names {
-            if let Err(e) = self.resume_group(name).await {
-                error!(group = %name, error = %e, "Failed to resume ASG");
+        Ok(counts)
+    }
+
+    /// Record the current min/max/desired capacity of a group in the state store, then scale it to 0.
+    /// A group whose min, max and desired capacity are all 0 is skipped, so the state
+    /// store still holds the sizes that [`Self::restore_capacity`] sets back.
+    async fn scale_to_zero(&self, group_name: &str) -> Result<Outcome> {
+        let group = self.describe_group(group_name).await?;
+        let min_size = group.min_size().unwrap_or_default();
+        let max_size = group.max_size().unwrap_or_default();
+        let desired_capacity = group.desired_capacity().unwrap_or_default();
+
+        if min_size == 0 && max_size == 0 && desired_capacity == 0 {
+            info!(group = %group_name, "ASG already scaled to 0, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        if self.dry_run {
+            info!(
+                group = %group_name,
+                min_size,
+                max_size,
+                desired_capacity,
+                "Dry run: would record capacity and scale ASG to 0"
+            );
+            return Ok(Outcome::Applied);
+        }
+
+        info!(group = %group_name, min_size, max_size, desired_capacity, "Recording ASG capacity");
+        let state = State::from([
+            (PREVIOUS_MIN_SIZE.to_string(), min_size.to_string()),
+            (PREVIOUS_MAX_SIZE.to_string(), max_size.to_string()),
+            (
+                PREVIOUS_DESIRED_CAPACITY.to_string(),
+                desired_capacity.to_string(),
+            ),
+        ]);
+        self.state.save(group_arn(&group)?, &state).await?;
+
+        match self.scheduled_actions {
+            ScheduledActionsMode::Keep => {}
+            ScheduledActionsMode::Suspend => self.suspend_scheduled_actions(&group).await?,
+            ScheduledActionsMode::Delete => self.delete_scheduled_actions(group_name).await?,
+        }
+
+        info!(group = %group_name, "Scaling ASG to 0");
+        self.asg
+            .update_auto_scaling_group()
+            .auto_scaling_group_name(group_name)
+            .min_size(0)
+            .max_size(0)
+            .desired_capacity(0)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Delete a group and its instances, or scale it to 0 when `delete_group` is not set.
+    async fn terminate_group(&self, group_name: &str, delete_group: bool) -> Result<Outcome> {
+        let group = self.describe_group(group_name).await?;
+        if group
+            .status()
+            .is_some_and(|s| s.eq_ignore_ascii_case("Delete in progress"))
+        {
+            info!(group = %group_name, "ASG already being deleted, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        if delete_group {
+            if self.dry_run {
+                info!(group = %group_name, "Dry run: would delete ASG and its instances");
+                return Ok(Outcome::Applied);
+            }
+
+            info!(group = %group_name, "Deleting ASG and its instances");
+            self.asg
+                .delete_auto_scaling_group()
+                .auto_scaling_group_name(group_name)
+                .force_delete(true)
+                .send()
+                .await?;
+            return Ok(Outcome::Applied);
+        }
+
+        if group.min_size() == Some(0)
+            && group.max_size() == Some(0)
+            && group.desired_capacity() == Some(0)
+        {
+            info!(group = %group_name, "ASG already scaled to 0, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(group = %group_name, "Dry run: would scale ASG to 0");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(group = %group_name, "Scaling ASG to 0");
+        self.asg
+            .update_auto_scaling_group()
+            .auto_scaling_group_name(group_name)
+            .min_size(0)
+            .max_size(0)
+            .desired_capacity(0)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the min/max/desired capacity recorded by [`Self::scale_to_zero`].
+    async fn restore_capacity(&self, group_name: &str) -> Result<Outcome> {
+        let group = self.describe_group(group_name).await?;
+        let state = self.state.load(group_arn(&group)?).await?;
+
+        let (Some(min_size), Some(max_size), Some(desired_capacity)) = (
+            read_capacity(&state, PREVIOUS_MIN_SIZE),
+            read_capacity(&state, PREVIOUS_MAX_SIZE),
+            read_capacity(&state, PREVIOUS_DESIRED_CAPACITY),
+        ) else {
+            warn!(group = %group_name, "No recorded capacity found, skipping ASG");
+            return Ok(Outcome::Skipped);
+        };
+
+        if self.dry_run {
+            info!(
+                group = %group_name,
+                min_size,
+                max_size,
+                desired_capacity,
+                "Dry run: would restore ASG capacity"
+            );
+            return Ok(Outcome::Applied);
+        }
+
+        info!(group = %group_name, min_size, max_size, desired_capacity, "Restoring ASG capacity");
+        self.asg
+            .update_auto_scaling_group()
+            .auto_scaling_group_name(group_name)
+            .min_size(min_size)
+            .max_size(max_size)
+            .desired_capacity(desired_capacity)
+            .send()
+            .await?;
+
+        self.restore_scheduled_actions(&group).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Suspend the `ScheduledActions` process of a group and mark the group with a tag,
+    /// unless the process is already suspended.
+    async fn suspend_scheduled_actions(&self, group: &AutoScalingGroup) -> Result<()> {
+        let group_name = group.auto_scaling_group_name().unwrap_or_default();
+        let already_suspended = group
+            .suspended_processes()
+            .iter()
+            .any(|p| p.process_name() == Some(SCHEDULED_ACTIONS_PROCESS));
+        if already_suspended {
+            info!(group = %group_name, "ASG scheduled actions already suspended, skipping");
+            return Ok(());
+        }
+
+        info!(group = %group_name, "Suspending ASG scheduled actions");
+        self.asg
+            .create_or_update_tags()
+            .tags(group_tag(
+                group_name,
+                SUSPENDED_SCHEDULED_ACTIONS_TAG,
+                "true",
+            ))
+            .send()
+            .await?;
+        self.asg
+            .suspend_processes()
+            .auto_scaling_group_name(group_name)
+            .scaling_processes(SCHEDULED_ACTIONS_PROCESS)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Record the scheduled actions of a group in tags, then delete them.
+    async fn delete_scheduled_actions(&self, group_name: &str) -> Result<()> {
+        let actions = self.describe_scheduled_actions(group_name).await?;
+        if actions.is_empty() {
+            return Ok(());
+        }
+
+        let records = actions
+            .iter()
+            .map(ScheduledActionRecord::from_action)
+            .collect::<Result<Vec<_>>>()?;
+        let mut req = self.asg.create_or_update_tags();
+        for record in &records {
+            req = req.tags(group_tag(group_name, &record.key(), &record.value()?));
+        }
+        info!(group = %group_name, count = records.len(), "Recording ASG scheduled actions");
+        req.send().await?;
+
+        for record in &records {
+            info!(group = %group_name, action = %record.name, "Deleting ASG scheduled action");
+            self.asg
+                .delete_scheduled_action()
+                .auto_scaling_group_name(group_name)
+                .scheduled_action_name(&record.name)
+                .send()
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Resume the scheduled actions suspended and re-create the scheduled actions
+    /// deleted by the scheduler, as recorded in the tags of a group.
+    async fn restore_scheduled_actions(&self, group: &AutoScalingGroup) -> Result<()> {
+        let group_name = group.auto_scaling_group_name().unwrap_or_default();
+        let tags: HashMap<&str, &str> = group
+            .tags()
+            .iter()
+            .filter_map(|t| Some((t.key()?, t.value().unwrap_or_default())))
+            .collect();
+
+        if tags.contains_key(SUSPENDED_SCHEDULED_ACTIONS_TAG) {
+            info!(group = %group_name, "Resuming ASG scheduled actions");
+            self.asg
+                .resume_processes()
+                .auto_scaling_group_name(group_name)
+                .scaling_processes(SCHEDULED_ACTIONS_PROCESS)
+                .send()
+                .await?;
+            self.delete_group_tag(group_name, SUSPENDED_SCHEDULED_ACTIONS_TAG)
+                .await?;
+        }
+
+        let now = DateTime::from(std::time::SystemTime::now()).secs();
+        for (key, value) in &tags {
+            let Some(name) = key.strip_prefix(SCHEDULED_ACTION_TAG_PREFIX) else {
+                continue;
+            };
+            let record = ScheduledActionRecord::parse(name, value);
+            let expired = match record.recurrence {
+                Some(_) => record.end_time.is_some_and(|t| t <= now),
+                None => record.time.is_none_or(|t| t <= now),
+            };
+            if expired {
+                warn!(group = %group_name, action = %record.name, "ASG scheduled action expired while stopped, dropping it");
+            } else {
+                info!(group = %group_name, action = %record.name, "Re-creating ASG scheduled action");
+                self.asg
+                    .put_scheduled_update_group_action()
+                    .auto_scaling_group_name(group_name)
+                    .scheduled_action_name(&record.name)
+                    .set_recurrence(record.recurrence.clone())
+                    .set_start_time(record.time.map(DateTime::from_secs))
+                    .set_end_time(record.end_time.map(DateTime::from_secs))
+                    .set_min_size(record.min_size)
+                    .set_max_size(record.max_size)
+                    .set_desired_capacity(record.desired_capacity)
+                    .set_time_zone(record.time_zone.clone())
+                    .send()
+                    .await?;
+            }
+            self.delete_group_tag(group_name, key).await?;
+        }
+        Ok(())
+    }
+
+    async fn describe_scheduled_actions(
+        &self,
+        group_name: &str,
+    ) -> Result<Vec<ScheduledUpdateGroupAction>> {
+        let mut actions = Vec::new();
+        let mut next_token: Option<String> = None;
+
+        loop {
+            let mut req = self
+                .asg
+                .describe_scheduled_actions()
+                .auto_scaling_group_name(group_name);
+            if let Some(ref token) = next_token {
+                req = req.next_token(token);
+            }
+
+            let resp = req.send().await?;
+            actions.extend(resp.scheduled_update_group_actions().iter().cloned());
+
+            match resp.next_token() {
+                Some(token) if !token.is_empty() => next_token = Some(token.to_string()),
+                _ => break,
             }
         }
 
+        Ok(actions)
+    }
+
+    async fn delete_group_tag(&self, group_name: &str, key: &str) -> Result<()> {
+        let tag = Tag::builder()
+            .resource_id(group_name)
+            .resource_type("auto-scaling-group")
+            .key(key)
+            .build();
+        self.asg.delete_tags().tags(tag).send().await?;
         Ok(())
     }
 
+    /// Describe a single Auto Scaling Group, including its tags.
+    async fn describe_group(&self, group_name: &str) -> Result<AutoScalingGroup> {
+        let resp = self
+            .asg
+            .describe_auto_scaling_groups()
+            .auto_scaling_group_names(group_name)
+            .send()
+            .await?;
+
+        resp.auto_scaling_groups()
+            .first()
+            .cloned()
+            .with_context(|| format!("Auto Scaling group '{}' not found", group_name))
+    }
+
     /// List Auto Scaling Group names matching the given tag by paginating
     /// through all groups and filtering manually.
-    async fn list_groups(&self, tag_key: &str, tag_value: &str) -> Result<Vec<String>> {
+    ///
+    /// Excluded groups and groups whose schedule window tag does not allow
+    /// the action are left out.
+    async fn list_groups(&self, filter: &ResourceFilter) -> Result<Vec<String>> {
         let mut names = Vec::new();
         let mut next_token: Option<String> = None;
 
@@ -99,15 +615,16 @@ impl AutoScalingScheduler {
             let resp = req.send().await?;
 
             for group in resp.auto_scaling_groups() {
-                for tag in group.tags() {
-                    if tag.key().unwrap_or_default() == tag_key
-                        && tag.value().unwrap_or_default() == tag_value
-                    {
-                        if let Some(name) = group.auto_scaling_group_name() {
-                            names.push(name.to_string());
-                        }
-                        break;
-                    }
+                let Some(name) = group.auto_scaling_group_name() else {
+                    continue;
+                };
+                let tags = group
+                    .tags()
+                    .iter()
+                    .map(|t| (t.key().unwrap_or_default(), t.value().unwrap_or_default()));
+                let arn = group.auto_scaling_group_arn().unwrap_or(name);
+                if filter.matches(arn, name, tags) {
+                    names.push(name.to_string());
                 }
             }
 
@@ -117,12 +634,16 @@ impl AutoScalingScheduler {
             }
         }
 
-        info!(count = names.len(), "Found Auto Scaling groups with matching tag");
+        info!(
+            count = names.len(),
+            "Found Auto Scaling groups with matching tag"
+        );
         Ok(names)
     }
 
-    /// List all instance IDs belonging to the given Auto Scaling Groups.
-    async fn list_instances(&self, group_names: &[String]) -> Result<Vec<String>> {
+    /// List the instances belonging to the given Auto Scaling Groups, as
+    /// `(group name, instance ID)` pairs.
+    async fn list_instances(&self, group_names: &[String]) -> Result<Vec<(String, String)>> {
         if group_names.is_empty() {
             return Ok(Vec::new());
         }
@@ -142,9 +663,10 @@ impl AutoScalingScheduler {
             let resp = req.send().await?;
 
             for group in resp.auto_scaling_groups() {
+                let name = group.auto_scaling_group_name().unwrap_or_default();
                 for instance in group.instances() {
                     if let Some(id) = instance.instance_id() {
-                        ids.push(id.to_string());
+                        ids.push((name.to_string(), id.to_string()));
                     }
                 }
             }
@@ -159,6 +681,10 @@ impl AutoScalingScheduler {
     }
 
     async fn suspend_group(&self, group_name: &str) -> Result<()> {
+        if self.dry_run {
+            info!(group = %group_name, "Dry run: would suspend ASG processes");
+            return Ok(());
+        }
         info!(group = %group_name, "Suspending ASG processes");
         self.asg
             .suspend_processes()
@@ -169,6 +695,10 @@ impl AutoScalingScheduler {
     }
 
     async fn resume_group(&self, group_name: &str) -> Result<()> {
+        if self.dry_run {
+            info!(group = %group_name, "Dry run: would resume ASG processes");
+            return Ok(());
+        }
         info!(group = %group_name, "Resuming ASG processes");
         self.asg
             .resume_processes()
@@ -178,69 +708,417 @@ impl AutoScalingScheduler {
         Ok(())
     }
 
-    async fn stop_instance(&self, instance_id: &str) -> Result<()> {
-        info!(instance = %instance_id, "Stopping ASG instance");
-        self.ec2
-            .stop_instances()
-            .instance_ids(instance_id)
-            .send()
-            .await?;
-        Ok(())
+    /// Stop or start ASG instances in batched API calls, returning the result of every instance.
+    async fn apply_to_instances(
+        &self,
+        instance_ids: &[String],
+        call: InstanceCall,
+    ) -> Vec<(String, Result<()>)> {
+        if self.dry_run {
+            for id in instance_ids {
+                info!(instance = %id, "Dry run: would {} ASG instance", call.verb());
+            }
+            return instance_ids.iter().map(|id| (id.clone(), Ok(()))).collect();
+        }
+        ec2_batch::apply(&self.ec2, instance_ids, call).await
     }
+}
 
-    async fn start_instance(&self, instance_id: &str) -> Result<()> {
-        info!(instance = %instance_id, "Starting ASG instance");
-        self.ec2
-            .start_instances()
-            .instance_ids(instance_id)
-            .send()
-            .await?;
-        Ok(())
+#[async_trait]
+impl ResourceScheduler for AutoScalingScheduler {
+    fn name(&self) -> &'static str {
+        "autoscaling"
     }
 
-    /// Poll EC2 until all given instances are in the `running` state.
-    async fn wait_instances_running(&self, instance_ids: &[String]) -> Result<()> {
-        info!(count = instance_ids.len(), "Waiting for instances to reach running state");
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let group_names = self.list_groups(filter).await?;
+        filter.check_discovered(group_names.len())?;
 
-        let max_attempts = 40;
-        let delay = std::time::Duration::from_secs(15);
+        match self.mode {
+            AutoScalingMode::Suspend => self.suspend_and_stop(&group_names).await,
+            AutoScalingMode::ScaleToZero => {
+                let mut counts = ResourceCounts::new(group_names.len());
+                let results = run_bounded(
+                    self.max_concurrent_operations,
+                    &group_names,
+                    |name| async move {
+                        let result = self.scale_to_zero(name).await;
+                        (name, result)
+                    },
+                )
+                .await;
+                for (name, result) in results {
+                    match result {
+                        Ok(outcome) => counts.record(name, outcome),
+                        Err(e) => {
+                            counts.fail(name, &e);
+                            error!(group = %name, error = %e, "Failed to scale ASG to zero");
+                        }
+                    }
+                }
+                Ok(counts)
+            }
+        }
+    }
 
-        for attempt in 1..=max_attempts {
-            let mut req = self.ec2.describe_instance_status().include_all_instances(true);
-            for id in instance_ids {
-                req = req.instance_ids(id);
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let group_names = self.list_groups(filter).await?;
+        filter.check_discovered(group_names.len())?;
+
+        match self.mode {
+            AutoScalingMode::Suspend => self.start_and_resume(&group_names).await,
+            AutoScalingMode::ScaleToZero => {
+                let mut counts = ResourceCounts::new(group_names.len());
+                let results = run_bounded(
+                    self.max_concurrent_operations,
+                    &group_names,
+                    |name| async move {
+                        let result = self.restore_capacity(name).await;
+                        (name, result)
+                    },
+                )
+                .await;
+                for (name, result) in results {
+                    match result {
+                        Ok(outcome) => counts.record(name, outcome),
+                        Err(e) => {
+                            counts.fail(name, &e);
+                            error!(group = %name, error = %e, "Failed to restore ASG capacity");
+                        }
+                    }
+                }
+                Ok(counts)
             }
+        }
+    }
 
-            let resp = req.send().await?;
+    /// List the ASGs matching the given filter.
+    ///
+    /// Groups are reported as `suspended` when their processes are suspended,
+    /// `scaled-to-zero` when their capacity is 0, and `running` otherwise.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let group_names = self.list_groups(filter).await?;
 
-            let running_count = resp
-                .instance_statuses()
-                .iter()
-                .filter(|s| {
-                    s.instance_state()
-                        .and_then(|st| st.name())
-                        .map(|n| n.as_str() == "running")
-                        .unwrap_or(false)
-                })
-                .count();
+        let mut items = Vec::with_capacity(group_names.len());
+        for name in &group_names {
+            let group = self.describe_group(name).await?;
+            let state = if !group.suspended_processes().is_empty() {
+                "suspended"
+            } else if group.max_size() == Some(0) && group.desired_capacity() == Some(0) {
+                "scaled-to-zero"
+            } else {
+                "running"
+            };
+            let arn = group.auto_scaling_group_arn().unwrap_or(name);
+            items.push(InventoryItem::new(
+                arn,
+                "autoscaling:autoScalingGroup",
+                state,
+            ));
+        }
+        Ok(items)
+    }
+}
 
-            if running_count == instance_ids.len() {
-                info!("All instances are running");
-                return Ok(());
-            }
+impl ScheduledActionRecord {
+    fn from_action(action: &ScheduledUpdateGroupAction) -> Result<Self> {
+        let name = action
+            .scheduled_action_name()
+            .context("Scheduled action has no name")?
+            .to_string();
+        let recurrence = action.recurrence().map(str::to_string);
 
-            info!(
-                attempt,
-                running = running_count,
-                total = instance_ids.len(),
-                "Waiting for instances..."
+        Ok(Self {
+            time: if recurrence.is_some() {
+                None
+            } else {
+                action.start_time().or(action.time()).map(|t| t.secs())
+            },
+            end_time: action.end_time().map(|t| t.secs()),
+            min_size: action.min_size(),
+            max_size: action.max_size(),
+            desired_capacity: action.desired_capacity(),
+            time_zone: action.time_zone().map(str::to_string),
+            recurrence,
+            name,
+        })
+    }
+
+    /// Parse a record stored as `recurrence=0 8 * * 1-5;min=1;max=2;desired=1;tz=Europe/Paris`.
+    fn parse(name: &str, value: &str) -> Self {
+        let fields: HashMap<&str, &str> =
+            value.split(';').filter_map(|f| f.split_once('=')).collect();
+        let number = |field: &str| fields.get(field).and_then(|v| v.parse::<i64>().ok());
+
+        Self {
+            name: name.to_string(),
+            recurrence: fields.get("recurrence").map(|v| v.to_string()),
+            time: number("time"),
+            end_time: number("end"),
+            min_size: number("min").map(|v| v as i32),
+            max_size: number("max").map(|v| v as i32),
+            desired_capacity: number("desired").map(|v| v as i32),
+            time_zone: fields.get("tz").map(|v| v.to_string()),
+        }
+    }
+
+    /// Key of the group tag recording the action.
+    fn key(&self) -> String {
+        format!("{}{}", SCHEDULED_ACTION_TAG_PREFIX, self.name)
+    }
+
+    /// Value of the group tag recording the action.
+    fn value(&self) -> Result<String> {
+        let mut fields = Vec::new();
+        if let Some(recurrence) = &self.recurrence {
+            fields.push(format!("recurrence={}", recurrence));
+        }
+        if let Some(time) = self.time {
+            fields.push(format!("time={}", time));
+        }
+        if let Some(end_time) = self.end_time {
+            fields.push(format!("end={}", end_time));
+        }
+        if let Some(min_size) = self.min_size {
+            fields.push(format!("min={}", min_size));
+        }
+        if let Some(max_size) = self.max_size {
+            fields.push(format!("max={}", max_size));
+        }
+        if let Some(desired_capacity) = self.desired_capacity {
+            fields.push(format!("desired={}", desired_capacity));
+        }
+        if let Some(time_zone) = &self.time_zone {
+            fields.push(format!("tz={}", time_zone));
+        }
+
+        let value = fields.join(";");
+        if self.key().len() > MAX_TAG_KEY_LENGTH || value.len() > MAX_TAG_VALUE_LENGTH {
+            bail!(
+                "Scheduled action '{}' is too long to be recorded in a tag",
+                self.name
             );
-            tokio::time::sleep(delay).await;
         }
+        Ok(value)
+    }
+}
+
+/// Build an Auto Scaling Group tag that is not propagated to instances.
+fn group_tag(group_name: &str, key: &str, value: &str) -> Tag {
+    Tag::builder()
+        .resource_id(group_name)
+        .resource_type("auto-scaling-group")
+        .key(key)
+        .value(value)
+        .propagate_at_launch(false)
+        .build()
+}
+
+/// Read a capacity value recorded in the state of an Auto Scaling Group.
+fn read_capacity(state: &State, key: &str) -> Option<i32> {
+    state.get(key).and_then(|v| v.parse::<i32>().ok())
+}
+
+/// Get the ARN of an Auto Scaling Group, under which its state is recorded.
+fn group_arn(group: &AutoScalingGroup) -> Result<&str> {
+    group.auto_scaling_group_arn().with_context(|| {
+        format!(
+            "ASG '{}' has no ARN",
+            group.auto_scaling_group_name().unwrap_or_default()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use aws_sdk_autoscaling::operation::describe_auto_scaling_groups::DescribeAutoScalingGroupsOutput;
+    use aws_sdk_autoscaling::operation::resume_processes::ResumeProcessesOutput;
+    use aws_sdk_autoscaling::operation::update_auto_scaling_group::UpdateAutoScalingGroupOutput;
+    use aws_sdk_autoscaling::types::{Instance, TagDescription};
+    use aws_sdk_ec2::operation::describe_instance_status::DescribeInstanceStatusOutput;
+    use aws_sdk_ec2::operation::start_instances::StartInstancesOutput;
+    use aws_sdk_ec2::types::{InstanceState, InstanceStateName, InstanceStatus};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, MemoryStore, TAG_KEY, TAG_VALUE};
+
+    const WEB_ARN: &str =
+        "arn:aws:autoscaling:eu-west-1:123456789012:autoScalingGroup:1a2b:autoScalingGroupName/web";
+
+    /// Tagged group `name` with the given capacity and instances.
+    fn group(name: &str, capacity: i32, instance_ids: &[&str]) -> AutoScalingGroup {
+        let mut group = AutoScalingGroup::builder()
+            .auto_scaling_group_name(name)
+            .auto_scaling_group_arn(format!(
+                "arn:aws:autoscaling:eu-west-1:123456789012:autoScalingGroup:1a2b:autoScalingGroupName/{}",
+                name
+            ))
+            .min_size(capacity)
+            .max_size(capacity)
+            .desired_capacity(capacity)
+            .tags(TagDescription::builder().key(TAG_KEY).value(TAG_VALUE).build());
+        for instance_id in instance_ids {
+            group = group.instances(Instance::builder().instance_id(*instance_id).build());
+        }
+        group.build()
+    }
+
+    /// Rule describing the given groups, whatever the groups requested.
+    fn describe_groups_rule(groups: fn() -> Vec<AutoScalingGroup>) -> Rule {
+        mock!(AsgClient::describe_auto_scaling_groups).then_output(move || {
+            DescribeAutoScalingGroupsOutput::builder()
+                .set_auto_scaling_groups(Some(groups()))
+                .build()
+        })
+    }
+
+    fn scheduler(
+        ec2: Ec2Client,
+        asg: AsgClient,
+        state: Arc<dyn StateStore>,
+        mode: AutoScalingMode,
+    ) -> AutoScalingScheduler {
+        AutoScalingScheduler {
+            ec2,
+            asg,
+            state,
+            mode,
+            scheduled_actions: ScheduledActionsMode::Keep,
+            dry_run: false,
+            max_concurrent_operations: 10,
+            waiter: Waiter {
+                max_attempts: 1,
+                delay: Duration::ZERO,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn start_leaves_groups_with_pending_instances_suspended() {
+        let describe =
+            describe_groups_rule(|| vec![group("web", 1, &["i-web"]), group("api", 1, &["i-api"])]);
+        let start = mock!(Ec2Client::start_instances)
+            .then_output(|| StartInstancesOutput::builder().build());
+        let status = mock!(Ec2Client::describe_instance_status).then_output(|| {
+            let status = |id: &str, state: InstanceStateName| {
+                InstanceStatus::builder()
+                    .instance_id(id)
+                    .instance_state(InstanceState::builder().name(state).build())
+                    .build()
+            };
+            DescribeInstanceStatusOutput::builder()
+                .instance_statuses(status("i-web", InstanceStateName::Running))
+                .instance_statuses(status("i-api", InstanceStateName::Pending))
+                .build()
+        });
+        let resume = mock!(AsgClient::resume_processes)
+            .match_requests(|req| req.auto_scaling_group_name() == Some("web"))
+            .then_output(|| ResumeProcessesOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_ec2, RuleMode::MatchAny, [&start, &status]),
+            mock_client!(
+                aws_sdk_autoscaling,
+                RuleMode::MatchAny,
+                [&describe, &resume]
+            ),
+            Arc::new(MemoryStore::default()),
+            AutoScalingMode::Suspend,
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (4, 2, 0, 2)
+        );
+        assert_eq!(resume.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_restores_the_recorded_group_sizes() {
+        let describe = describe_groups_rule(|| vec![group("web", 0, &[])]);
+        let update = mock!(AsgClient::update_auto_scaling_group)
+            .match_requests(|req| {
+                req.auto_scaling_group_name() == Some("web")
+                    && req.min_size() == Some(1)
+                    && req.max_size() == Some(4)
+                    && req.desired_capacity() == Some(2)
+            })
+            .then_output(|| UpdateAutoScalingGroupOutput::builder().build());
+        let state = Arc::new(MemoryStore::with(
+            WEB_ARN,
+            &[
+                (PREVIOUS_MIN_SIZE, "1"),
+                (PREVIOUS_MAX_SIZE, "4"),
+                (PREVIOUS_DESIRED_CAPACITY, "2"),
+            ],
+        ));
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_ec2, Vec::<&Rule>::new()),
+            mock_client!(
+                aws_sdk_autoscaling,
+                RuleMode::MatchAny,
+                [&describe, &update]
+            ),
+            state,
+            AutoScalingMode::ScaleToZero,
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!((counts.discovered, counts.succeeded), (1, 1));
+        assert_eq!(update.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_records_the_group_sizes_then_scales_to_zero() {
+        let describe = describe_groups_rule(|| vec![group("web", 2, &[])]);
+        let update = mock!(AsgClient::update_auto_scaling_group)
+            .match_requests(|req| {
+                req.auto_scaling_group_name() == Some("web")
+                    && req.min_size() == Some(0)
+                    && req.max_size() == Some(0)
+                    && req.desired_capacity() == Some(0)
+            })
+            .then_output(|| UpdateAutoScalingGroupOutput::builder().build());
+        let state = Arc::new(MemoryStore::default());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_ec2, Vec::<&Rule>::new()),
+            mock_client!(
+                aws_sdk_autoscaling,
+                RuleMode::MatchAny,
+                [&describe, &update]
+            ),
+            state.clone(),
+            AutoScalingMode::ScaleToZero,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
 
-        anyhow::bail!(
-            "Timed out waiting for {} instances to reach running state",
-            instance_ids.len()
+        assert_eq!((counts.discovered, counts.succeeded), (1, 1));
+        assert_eq!(update.num_calls(), 1);
+        assert_eq!(
+            state.state(WEB_ARN),
+            Some(State::from([
+                (PREVIOUS_MIN_SIZE.to_string(), "2".to_string()),
+                (PREVIOUS_MAX_SIZE.to_string(), "2".to_string()),
+                (PREVIOUS_DESIRED_CAPACITY.to_string(), "2".to_string()),
+            ]))
         );
     }
 }
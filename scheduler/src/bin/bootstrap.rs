@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use lambda_runtime::{service_fn, Error, LambdaEvent};
+
+use scheduler::invocation::Invocation;
+use scheduler::telemetry::{self, Telemetry};
+
+/// Lambda entry point, built with the `lambda` feature.
+///
+/// Each invocation loads the configuration from environment variables (and
+/// `CONFIG_FILE`, `CONFIG_SSM_PREFIX`), applies the overrides of the event payload, then performs
+/// the action once.
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let telemetry = Arc::new(telemetry::init(true)?);
+
+    lambda_runtime::run(service_fn(move |event| {
+        handle(event, Arc::clone(&telemetry))
+    }))
+    .await
+}
+
+async fn handle(event: LambdaEvent<Invocation>, telemetry: Arc<Telemetry>) -> Result<(), Error> {
+    let config = event.payload.config().await?;
+    let result = scheduler::run(&config).await;
+    // The execution environment may be frozen as soon as the invocation returns.
+    telemetry.flush();
+    result?;
+    Ok(())
+}
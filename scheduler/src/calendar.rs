@@ -0,0 +1,351 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::str::FromStr;
+use tracing::{info, warn};
+
+/// Day or period of the calendar on which the scheduler does not run (`EXCLUDED_DATES`)
+/// or is allowed to run (`ACTIVE_DATE_RANGES`).
+///
+/// Parsed from an entry such as:
+/// - `MM-DD`: a day of every year (`12-25`)
+/// - `YYYY-MM-DD`: a single day (`2025-12-26`)
+/// - a weekday name (`sat`, `Sunday`)
+/// - `from:to`: an inclusive range of either form (`12-24:01-02`, `2025-12-24:2026-01-02`);
+///   yearly ranges wrap around the end of the year
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateSpec {
+    Yearly { month: u32, day: u32 },
+    Date(NaiveDate),
+    Weekday(Weekday),
+    YearlyRange { from: (u32, u32), to: (u32, u32) },
+    DateRange { from: NaiveDate, to: NaiveDate },
+}
+
+impl DateSpec {
+    /// Whether `date` falls on the day or within the period.
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        let day = (date.month(), date.day());
+        match self {
+            Self::Yearly { month, day: d } => day == (*month, *d),
+            Self::Date(excluded) => date == *excluded,
+            Self::Weekday(weekday) => date.weekday() == *weekday,
+            Self::YearlyRange { from, to } if from <= to => *from <= day && day <= *to,
+            Self::YearlyRange { from, to } => day >= *from || day <= *to,
+            Self::DateRange { from, to } => *from <= date && date <= *to,
+        }
+    }
+}
+
+impl FromStr for DateSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some((from, to)) = s.split_once(':') {
+            return match (parse_yearly(from), parse_yearly(to)) {
+                (Ok(from), Ok(to)) => Ok(Self::YearlyRange { from, to }),
+                _ => {
+                    let from = parse_date(from)?;
+                    let to = parse_date(to)?;
+                    if to < from {
+                        bail!("invalid range '{}': ends before it starts", s);
+                    }
+                    Ok(Self::DateRange { from, to })
+                }
+            };
+        }
+
+        if let Ok((month, day)) = parse_yearly(s) {
+            return Ok(Self::Yearly { month, day });
+        }
+        if let Ok(date) = parse_date(s) {
+            return Ok(Self::Date(date));
+        }
+        s.parse::<Weekday>().map(Self::Weekday).map_err(|_| {
+            anyhow!(
+                "invalid date '{}': expected MM-DD, YYYY-MM-DD, a weekday or a from:to range",
+                s
+            )
+        })
+    }
+}
+
+/// Parse a `MM-DD` day of the year, February 29 included.
+fn parse_yearly(value: &str) -> Result<(u32, u32)> {
+    let value = value.trim();
+    let (month, day) = value
+        .split_once('-')
+        .with_context(|| format!("invalid date '{}': expected MM-DD", value))?;
+    let month: u32 = month
+        .parse()
+        .with_context(|| format!("invalid date '{}': expected MM-DD", value))?;
+    let day: u32 = day
+        .parse()
+        .with_context(|| format!("invalid date '{}': expected MM-DD", value))?;
+    // 2000 is a leap year, so that 02-29 is accepted.
+    if NaiveDate::from_ymd_opt(2000, month, day).is_none() {
+        bail!("invalid date '{}': no such day", value);
+    }
+    Ok((month, day))
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .with_context(|| format!("invalid date '{}': expected MM-DD or YYYY-MM-DD", value))
+}
+
+/// Fetch an iCal feed (e.g. a company holiday calendar) and return the days of its events.
+pub async fn fetch_ical(url: &str) -> Result<Vec<DateSpec>> {
+    let body = reqwest::get(url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .context("Failed to fetch iCal calendar")?
+        .text()
+        .await
+        .context("Failed to read iCal calendar")?;
+    let dates = parse_ical(&body)?;
+    info!(
+        events = dates.len(),
+        "Loaded excluded dates from iCal calendar"
+    );
+    Ok(dates)
+}
+
+/// Parse the events of an iCal calendar into excluded days.
+///
+/// Only the dates of the events are considered, in the timezone they are
+/// written in: all-day events exclude the days they span (their end date is
+/// exclusive), other events the days they start and end on. Yearly recurring
+/// events exclude the same days every year; other recurrence rules are ignored,
+/// so only the first occurrence of such events is excluded.
+fn parse_ical(text: &str) -> Result<Vec<DateSpec>> {
+    // Long lines are folded into continuation lines starting with a space or a tab.
+    let text = text
+        .replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+
+    let mut dates = Vec::new();
+    let mut event: Option<(Option<&str>, Option<&str>, Option<&str>)> = None;
+    for line in text.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        match (name.as_str(), event.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some((None, None, None))
+            }
+            ("DTSTART", Some(event)) => event.0 = Some(value),
+            ("DTEND", Some(event)) => event.1 = Some(value),
+            ("RRULE", Some(event)) => event.2 = Some(value),
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let Some((Some(start), end, rrule)) = event.take() else {
+                    continue;
+                };
+                dates.push(ical_event(start, end, rrule)?);
+            }
+            _ => {}
+        }
+    }
+    Ok(dates)
+}
+
+/// Build the excluded days of an iCal event from its `DTSTART`, `DTEND` and `RRULE` values.
+fn ical_event(start: &str, end: Option<&str>, rrule: Option<&str>) -> Result<DateSpec> {
+    let from = parse_ical_date(start)?;
+    let to = match end {
+        // The end of an all-day event is the day after its last day.
+        Some(end) if end.trim().len() == 8 => parse_ical_date(end)?.pred_opt().unwrap_or(from),
+        Some(end) => parse_ical_date(end)?,
+        None => from,
+    }
+    .max(from);
+
+    let yearly = match rrule {
+        Some(rule)
+            if rule
+                .split(';')
+                .any(|p| p.eq_ignore_ascii_case("FREQ=YEARLY")) =>
+        {
+            true
+        }
+        Some(rule) => {
+            warn!(rrule = %rule, date = %from, "Unsupported iCal recurrence rule, only excluding the first occurrence");
+            false
+        }
+        None => false,
+    };
+
+    Ok(match (yearly, from == to) {
+        (true, true) => DateSpec::Yearly {
+            month: from.month(),
+            day: from.day(),
+        },
+        (true, false) => DateSpec::YearlyRange {
+            from: (from.month(), from.day()),
+            to: (to.month(), to.day()),
+        },
+        (false, true) => DateSpec::Date(from),
+        (false, false) => DateSpec::DateRange { from, to },
+    })
+}
+
+/// Parse the date of an iCal `DATE` (`YYYYMMDD`) or `DATE-TIME` (`YYYYMMDDTHHMMSS[Z]`) value.
+fn parse_ical_date(value: &str) -> Result<NaiveDate> {
+    let value = value.trim();
+    value
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .with_context(|| format!("invalid iCal date '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn from_str_parses_days_weekdays_and_ranges() {
+        let cases = [
+            ("12-25", DateSpec::Yearly { month: 12, day: 25 }),
+            ("02-29", DateSpec::Yearly { month: 2, day: 29 }),
+            ("2025-12-26", DateSpec::Date(date("2025-12-26"))),
+            ("sat", DateSpec::Weekday(Weekday::Sat)),
+            ("Sunday", DateSpec::Weekday(Weekday::Sun)),
+            (
+                "12-24:01-02",
+                DateSpec::YearlyRange {
+                    from: (12, 24),
+                    to: (1, 2),
+                },
+            ),
+            (
+                " 2025-12-24:2026-01-02 ",
+                DateSpec::DateRange {
+                    from: date("2025-12-24"),
+                    to: date("2026-01-02"),
+                },
+            ),
+        ];
+        for (value, spec) in cases {
+            assert_eq!(value.parse::<DateSpec>().unwrap(), spec, "{}", value);
+        }
+
+        for value in [
+            "13-01",
+            "02-30",
+            "someday",
+            "2026-01-02:2025-12-24",
+            "12-24:",
+        ] {
+            assert!(value.parse::<DateSpec>().is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn matches_days_within_the_period() {
+        let cases = [
+            ("12-25", "2030-12-25", true),
+            ("12-25", "2030-12-26", false),
+            ("2025-12-26", "2026-12-26", false),
+            ("sat", "2025-12-27", true),
+            ("sat", "2025-12-28", false),
+            ("06-01:06-30", "2025-06-15", true),
+            ("06-01:06-30", "2025-07-01", false),
+            ("12-24:01-02", "2025-12-31", true),
+            ("12-24:01-02", "2026-01-02", true),
+            ("12-24:01-02", "2026-01-03", false),
+            ("12-24:01-02", "2025-12-23", false),
+            ("2025-12-24:2026-01-02", "2026-01-01", true),
+            ("2025-12-24:2026-01-02", "2026-12-31", false),
+        ];
+        for (spec, day, expected) in cases {
+            let spec: DateSpec = spec.parse().unwrap();
+            assert_eq!(spec.matches(date(day)), expected, "{:?} on {}", spec, day);
+        }
+    }
+
+    #[test]
+    fn parse_ical_reads_the_days_of_every_event() {
+        let calendar = [
+            "BEGIN:VCALENDAR",
+            "BEGIN:VEVENT",
+            "SUMMARY:Christmas",
+            "DTSTART;VALUE=DATE:2025",
+            " 1225",
+            "DTEND;VALUE=DATE:20251226",
+            "RRULE:FREQ=YEARLY",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Office closed",
+            "DTSTART;VALUE=DATE:20251229",
+            "DTEND;VALUE=DATE:20260103",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:No start date",
+            "END:VEVENT",
+            "END:VCALENDAR",
+        ]
+        .join("\r\n");
+
+        assert_eq!(
+            parse_ical(&calendar).unwrap(),
+            [
+                DateSpec::Yearly { month: 12, day: 25 },
+                DateSpec::DateRange {
+                    from: date("2025-12-29"),
+                    to: date("2026-01-02"),
+                },
+            ]
+        );
+        assert!(parse_ical("BEGIN:VEVENT\nDTSTART:tomorrow\nEND:VEVENT").is_err());
+    }
+
+    #[test]
+    fn ical_event_builds_the_excluded_days() {
+        let cases = [
+            (
+                "20250101",
+                Some("20250102"),
+                None,
+                DateSpec::Date(date("2025-01-01")),
+            ),
+            ("20250101", None, None, DateSpec::Date(date("2025-01-01"))),
+            (
+                "20250310T220000",
+                Some("20250311T020000"),
+                None,
+                DateSpec::DateRange {
+                    from: date("2025-03-10"),
+                    to: date("2025-03-11"),
+                },
+            ),
+            (
+                "20251224",
+                Some("20251227"),
+                Some("FREQ=YEARLY;BYMONTH=12"),
+                DateSpec::YearlyRange {
+                    from: (12, 24),
+                    to: (12, 26),
+                },
+            ),
+            (
+                "20250707",
+                Some("20250708"),
+                Some("FREQ=WEEKLY"),
+                DateSpec::Date(date("2025-07-07")),
+            ),
+        ];
+        for (start, end, rrule, spec) in cases {
+            assert_eq!(ical_event(start, end, rrule).unwrap(), spec, "{}", start);
+        }
+    }
+}
@@ -0,0 +1,611 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_ec2::types::{AssociationStatusCode, Tag, TargetNetwork};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Tags used to remember the subnet associations and routes of an endpoint before they were removed.
+const PREVIOUS_SUBNET_IDS_TAG: &str = "scheduler:previous-subnet-ids";
+const PREVIOUS_ROUTES_TAG: &str = "scheduler:previous-routes";
+
+/// Maximum number of polls while waiting for subnet associations to complete.
+const MAX_ASSOCIATION_POLLS: u32 = 30;
+
+/// Delay between two polls while waiting for subnet associations to complete.
+const ASSOCIATION_POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Stop/start handler for Client VPN endpoints in a given AWS region.
+///
+/// Endpoints are billed per associated subnet, so their associations are removed instead.
+/// On stop: records the associated subnets and the manually added routes in tags,
+/// then disassociates every subnet (which also removes the routes).
+/// On start: re-associates the recorded subnets and re-creates the recorded routes.
+pub struct ClientVpnScheduler {
+    ec2: Ec2Client,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+/// A route added to an endpoint, towards a target subnet.
+#[derive(Debug)]
+struct EndpointRoute {
+    target_subnet: String,
+    destination_cidr: String,
+}
+
+impl ClientVpnScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            ec2: Ec2Client::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Record the subnet associations and routes of an endpoint in tags, then disassociate its subnets.
+    async fn disassociate(&self, endpoint_id: &str) -> Result<Outcome> {
+        let associations: Vec<TargetNetwork> = self
+            .target_networks(endpoint_id)
+            .await?
+            .into_iter()
+            .filter(is_associated)
+            .collect();
+        if associations.is_empty() {
+            info!(endpoint = %endpoint_id, "Client VPN endpoint has no associated subnet, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(endpoint = %endpoint_id, associations = associations.len(), "Dry run: would disassociate Client VPN endpoint subnets");
+            return Ok(Outcome::Applied);
+        }
+
+        let subnet_ids: Vec<&str> = associations
+            .iter()
+            .filter_map(|a| a.target_network_id())
+            .collect();
+        let routes = self.added_routes(endpoint_id).await?;
+        info!(endpoint = %endpoint_id, subnets = ?subnet_ids, routes = routes.len(), "Recording Client VPN endpoint associations");
+        self.ec2
+            .create_tags()
+            .resources(endpoint_id)
+            .tags(
+                Tag::builder()
+                    .key(PREVIOUS_SUBNET_IDS_TAG)
+                    .value(subnet_ids.join(" "))
+                    .build(),
+            )
+            .tags(
+                Tag::builder()
+                    .key(PREVIOUS_ROUTES_TAG)
+                    .value(
+                        routes
+                            .iter()
+                            .map(|r| format!("{}={}", r.target_subnet, r.destination_cidr))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    )
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        for association in &associations {
+            let Some(association_id) = association.association_id() else {
+                continue;
+            };
+            info!(endpoint = %endpoint_id, association = %association_id, "Disassociating Client VPN endpoint subnet");
+            self.ec2
+                .disassociate_client_vpn_target_network()
+                .client_vpn_endpoint_id(endpoint_id)
+                .association_id(association_id)
+                .send()
+                .await?;
+        }
+
+        Ok(Outcome::Applied)
+    }
+
+    /// Re-associate the subnets recorded on stop, then re-create the recorded routes.
+    async fn reassociate(&self, endpoint_id: &str) -> Result<Outcome> {
+        let tags = self.endpoint_tags(endpoint_id).await?;
+        let Some(subnet_ids) = tags.get(PREVIOUS_SUBNET_IDS_TAG) else {
+            warn!(endpoint = %endpoint_id, "No recorded subnet associations found, skipping Client VPN endpoint");
+            return Ok(Outcome::Skipped);
+        };
+        let routes = tags
+            .get(PREVIOUS_ROUTES_TAG)
+            .map(|v| parse_routes(v))
+            .transpose()
+            .with_context(|| {
+                format!(
+                    "Client VPN endpoint '{}' has an invalid {} tag",
+                    endpoint_id, PREVIOUS_ROUTES_TAG
+                )
+            })?
+            .unwrap_or_default();
+
+        let associated: Vec<String> = self
+            .target_networks(endpoint_id)
+            .await?
+            .iter()
+            .filter(|a| is_associated(a))
+            .filter_map(|a| a.target_network_id().map(str::to_string))
+            .collect();
+        let missing: Vec<&str> = subnet_ids
+            .split_whitespace()
+            .filter(|id| !associated.iter().any(|a| a == id))
+            .collect();
+        if missing.is_empty() {
+            info!(endpoint = %endpoint_id, "Client VPN endpoint subnets already associated, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(endpoint = %endpoint_id, subnets = ?missing, routes = routes.len(), "Dry run: would re-associate Client VPN endpoint subnets");
+            return Ok(Outcome::Applied);
+        }
+
+        for subnet_id in &missing {
+            info!(endpoint = %endpoint_id, subnet = %subnet_id, "Associating Client VPN endpoint subnet");
+            self.ec2
+                .associate_client_vpn_target_network()
+                .client_vpn_endpoint_id(endpoint_id)
+                .subnet_id(*subnet_id)
+                .send()
+                .await?;
+        }
+
+        if !routes.is_empty() {
+            self.wait_associated(endpoint_id).await?;
+            for route in &routes {
+                info!(endpoint = %endpoint_id, subnet = %route.target_subnet, destination = %route.destination_cidr, "Re-creating Client VPN route");
+                self.ec2
+                    .create_client_vpn_route()
+                    .client_vpn_endpoint_id(endpoint_id)
+                    .target_vpc_subnet_id(&route.target_subnet)
+                    .destination_cidr_block(&route.destination_cidr)
+                    .send()
+                    .await?;
+            }
+        }
+
+        self.ec2
+            .delete_tags()
+            .resources(endpoint_id)
+            .tags(Tag::builder().key(PREVIOUS_SUBNET_IDS_TAG).build())
+            .tags(Tag::builder().key(PREVIOUS_ROUTES_TAG).build())
+            .send()
+            .await?;
+
+        Ok(Outcome::Applied)
+    }
+
+    async fn target_networks(&self, endpoint_id: &str) -> Result<Vec<TargetNetwork>> {
+        let resp = self
+            .ec2
+            .describe_client_vpn_target_networks()
+            .client_vpn_endpoint_id(endpoint_id)
+            .send()
+            .await?;
+
+        Ok(resp.client_vpn_target_networks().to_vec())
+    }
+
+    /// List the routes added manually to an endpoint (routes created by an association are left out).
+    async fn added_routes(&self, endpoint_id: &str) -> Result<Vec<EndpointRoute>> {
+        let resp = self
+            .ec2
+            .describe_client_vpn_routes()
+            .client_vpn_endpoint_id(endpoint_id)
+            .send()
+            .await?;
+
+        Ok(resp
+            .routes()
+            .iter()
+            .filter(|r| r.origin() == Some("add-route"))
+            .filter_map(|r| {
+                Some(EndpointRoute {
+                    target_subnet: r.target_subnet()?.to_string(),
+                    destination_cidr: r.destination_cidr()?.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    async fn endpoint_tags(&self, endpoint_id: &str) -> Result<HashMap<String, String>> {
+        let resp = self
+            .ec2
+            .describe_client_vpn_endpoints()
+            .client_vpn_endpoint_ids(endpoint_id)
+            .send()
+            .await?;
+
+        let endpoint = resp
+            .client_vpn_endpoints()
+            .first()
+            .with_context(|| format!("Client VPN endpoint '{}' not found", endpoint_id))?;
+        Ok(endpoint
+            .tags()
+            .iter()
+            .filter_map(|t| {
+                Some((
+                    t.key()?.to_string(),
+                    t.value().unwrap_or_default().to_string(),
+                ))
+            })
+            .collect())
+    }
+
+    /// Poll the subnet associations of an endpoint until none is still associating.
+    ///
+    /// Routes can only be added towards an associated subnet.
+    async fn wait_associated(&self, endpoint_id: &str) -> Result<()> {
+        for _ in 0..MAX_ASSOCIATION_POLLS {
+            tokio::time::sleep(ASSOCIATION_POLL_INTERVAL).await;
+
+            let networks = self.target_networks(endpoint_id).await?;
+            let associating = networks
+                .iter()
+                .any(|n| association_code(n) == Some(&AssociationStatusCode::Associating));
+            if !associating {
+                return Ok(());
+            }
+        }
+
+        bail!(
+            "Timed out waiting for the subnets of Client VPN endpoint '{}' to associate",
+            endpoint_id
+        );
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for ClientVpnScheduler {
+    fn name(&self) -> &'static str {
+        "client_vpn"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "ec2:client-vpn-endpoint",
+            filter,
+        )
+        .await?;
+        info!(count = arns.len(), "Found Client VPN endpoints to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let endpoint_id = extract_endpoint_id(arn);
+            let result = self.disassociate(&endpoint_id).await;
+            (endpoint_id, result)
+        })
+        .await;
+        for (endpoint_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&endpoint_id, outcome),
+                Err(e) => {
+                    counts.fail(&endpoint_id, &e);
+                    error!(endpoint = %endpoint_id, error = %e, "Failed to stop Client VPN endpoint");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "ec2:client-vpn-endpoint",
+            filter,
+        )
+        .await?;
+        info!(count = arns.len(), "Found Client VPN endpoints to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let endpoint_id = extract_endpoint_id(arn);
+            let result = self.reassociate(&endpoint_id).await;
+            (endpoint_id, result)
+        })
+        .await;
+        for (endpoint_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&endpoint_id, outcome),
+                Err(e) => {
+                    counts.fail(&endpoint_id, &e);
+                    error!(endpoint = %endpoint_id, error = %e, "Failed to start Client VPN endpoint");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the Client VPN endpoints matching the given filter, `associated` when
+    /// at least one subnet is associated and `disassociated` otherwise.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "ec2:client-vpn-endpoint",
+            filter,
+        )
+        .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let networks = self.target_networks(&extract_endpoint_id(&arn)).await?;
+            let state = if networks.iter().any(is_associated) {
+                "associated"
+            } else {
+                "disassociated"
+            };
+            items.push(InventoryItem::new(arn, "ec2:client-vpn-endpoint", state));
+        }
+        Ok(items)
+    }
+}
+
+fn association_code(network: &TargetNetwork) -> Option<&AssociationStatusCode> {
+    network.status().and_then(|s| s.code())
+}
+
+/// Whether a target network is associated (or being associated) with its endpoint.
+fn is_associated(network: &TargetNetwork) -> bool {
+    matches!(
+        association_code(network),
+        Some(AssociationStatusCode::Associated | AssociationStatusCode::Associating)
+    )
+}
+
+/// Parse the routes recorded as space-separated `subnet-id=destination-cidr` entries.
+fn parse_routes(value: &str) -> Result<Vec<EndpointRoute>> {
+    value
+        .split_whitespace()
+        .map(|entry| {
+            let (target_subnet, destination_cidr) = entry
+                .split_once('=')
+                .with_context(|| format!("invalid route '{}'", entry))?;
+            Ok(EndpointRoute {
+                target_subnet: target_subnet.to_string(),
+                destination_cidr: destination_cidr.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Extract the endpoint ID from a Client VPN endpoint ARN.
+///
+/// Expected ARN format: `arn:partition:ec2:region:account:client-vpn-endpoint/cvpn-endpoint-0123456789abcdef0`
+fn extract_endpoint_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_ec2::operation::associate_client_vpn_target_network::AssociateClientVpnTargetNetworkOutput;
+    use aws_sdk_ec2::operation::create_client_vpn_route::CreateClientVpnRouteOutput;
+    use aws_sdk_ec2::operation::create_tags::CreateTagsOutput;
+    use aws_sdk_ec2::operation::delete_tags::DeleteTagsOutput;
+    use aws_sdk_ec2::operation::describe_client_vpn_endpoints::DescribeClientVpnEndpointsOutput;
+    use aws_sdk_ec2::operation::describe_client_vpn_routes::DescribeClientVpnRoutesOutput;
+    use aws_sdk_ec2::operation::describe_client_vpn_target_networks::DescribeClientVpnTargetNetworksOutput;
+    use aws_sdk_ec2::operation::disassociate_client_vpn_target_network::DisassociateClientVpnTargetNetworkOutput;
+    use aws_sdk_ec2::types::{AssociationStatus, ClientVpnEndpoint, ClientVpnRoute};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const ENDPOINT_ARN_PREFIX: &str = "arn:aws:ec2:eu-west-1:123456789012:client-vpn-endpoint/";
+
+    /// Target networks of an endpoint, as `(association_id, subnet_id, status)`.
+    type Networks = &'static [(&'static str, &'static str, &'static str)];
+
+    fn target_networks(networks: Networks) -> DescribeClientVpnTargetNetworksOutput {
+        let networks = networks.iter().map(|(association_id, subnet_id, status)| {
+            TargetNetwork::builder()
+                .association_id(*association_id)
+                .target_network_id(*subnet_id)
+                .status(
+                    AssociationStatus::builder()
+                        .code(AssociationStatusCode::from(*status))
+                        .build(),
+                )
+                .build()
+        });
+        DescribeClientVpnTargetNetworksOutput::builder()
+            .set_client_vpn_target_networks(Some(networks.collect()))
+            .build()
+    }
+
+    /// Rule answering the target networks of `endpoint_id`.
+    fn target_networks_rule(endpoint_id: &'static str, networks: Networks) -> Rule {
+        mock!(Ec2Client::describe_client_vpn_target_networks)
+            .match_requests(move |req| req.client_vpn_endpoint_id() == Some(endpoint_id))
+            .then_output(move || target_networks(networks))
+    }
+
+    fn scheduler(ec2: Ec2Client, tagging: TaggingClient) -> ClientVpnScheduler {
+        ClientVpnScheduler {
+            ec2,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_records_the_associations_then_disassociates_them() {
+        let arns = get_resources_rule(
+            "ec2:client-vpn-endpoint",
+            &[
+                &format!("{ENDPOINT_ARN_PREFIX}cvpn-endpoint-0a1b"),
+                &format!("{ENDPOINT_ARN_PREFIX}cvpn-endpoint-2c3d"),
+            ],
+        );
+        let associated = target_networks_rule(
+            "cvpn-endpoint-0a1b",
+            &[
+                ("cvpn-assoc-0001", "subnet-0a1b", "associated"),
+                ("cvpn-assoc-0002", "subnet-2c3d", "associated"),
+            ],
+        );
+        let idle = target_networks_rule(
+            "cvpn-endpoint-2c3d",
+            &[("cvpn-assoc-0003", "subnet-0a1b", "disassociated")],
+        );
+        let routes = mock!(Ec2Client::describe_client_vpn_routes).then_output(|| {
+            DescribeClientVpnRoutesOutput::builder()
+                .routes(
+                    ClientVpnRoute::builder()
+                        .target_subnet("subnet-0a1b")
+                        .destination_cidr("10.1.0.0/16")
+                        .origin("add-route")
+                        .build(),
+                )
+                .routes(
+                    ClientVpnRoute::builder()
+                        .target_subnet("subnet-0a1b")
+                        .destination_cidr("10.0.0.0/16")
+                        .origin("associate")
+                        .build(),
+                )
+                .build()
+        });
+        let record = mock!(Ec2Client::create_tags)
+            .match_requests(|req| {
+                let tag = |key: &str| {
+                    req.tags()
+                        .iter()
+                        .find(|t| t.key() == Some(key))
+                        .and_then(|t| t.value())
+                };
+                req.resources() == ["cvpn-endpoint-0a1b".to_string()]
+                    && tag(PREVIOUS_SUBNET_IDS_TAG) == Some("subnet-0a1b subnet-2c3d")
+                    && tag(PREVIOUS_ROUTES_TAG) == Some("subnet-0a1b=10.1.0.0/16")
+            })
+            .then_output(|| CreateTagsOutput::builder().build());
+        let disassociate = mock!(Ec2Client::disassociate_client_vpn_target_network)
+            .match_requests(|req| req.client_vpn_endpoint_id() == Some("cvpn-endpoint-0a1b"))
+            .then_output(|| DisassociateClientVpnTargetNetworkOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_ec2,
+                RuleMode::MatchAny,
+                [&associated, &idle, &routes, &record, &disassociate]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(record.num_calls(), 1);
+        assert_eq!(disassociate.num_calls(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn start_reassociates_the_subnets_then_recreates_the_routes() {
+        let arns = get_resources_rule(
+            "ec2:client-vpn-endpoint",
+            &[&format!("{ENDPOINT_ARN_PREFIX}cvpn-endpoint-0a1b")],
+        );
+        let endpoint = mock!(Ec2Client::describe_client_vpn_endpoints).then_output(|| {
+            DescribeClientVpnEndpointsOutput::builder()
+                .client_vpn_endpoints(
+                    ClientVpnEndpoint::builder()
+                        .client_vpn_endpoint_id("cvpn-endpoint-0a1b")
+                        .tags(
+                            Tag::builder()
+                                .key(PREVIOUS_SUBNET_IDS_TAG)
+                                .value("subnet-0a1b subnet-2c3d")
+                                .build(),
+                        )
+                        .tags(
+                            Tag::builder()
+                                .key(PREVIOUS_ROUTES_TAG)
+                                .value("subnet-0a1b=10.1.0.0/16")
+                                .build(),
+                        )
+                        .build(),
+                )
+                .build()
+        });
+        let networks = mock!(Ec2Client::describe_client_vpn_target_networks)
+            .sequence()
+            .output(|| target_networks(&[]))
+            .output(|| {
+                target_networks(&[
+                    ("cvpn-assoc-0004", "subnet-0a1b", "associated"),
+                    ("cvpn-assoc-0005", "subnet-2c3d", "associated"),
+                ])
+            })
+            .build();
+        let associate = mock!(Ec2Client::associate_client_vpn_target_network)
+            .match_requests(|req| req.client_vpn_endpoint_id() == Some("cvpn-endpoint-0a1b"))
+            .then_output(|| AssociateClientVpnTargetNetworkOutput::builder().build());
+        let route = mock!(Ec2Client::create_client_vpn_route)
+            .match_requests(|req| {
+                req.target_vpc_subnet_id() == Some("subnet-0a1b")
+                    && req.destination_cidr_block() == Some("10.1.0.0/16")
+            })
+            .then_output(|| CreateClientVpnRouteOutput::builder().build());
+        let cleanup = mock!(Ec2Client::delete_tags)
+            .match_requests(|req| req.resources() == ["cvpn-endpoint-0a1b".to_string()])
+            .then_output(|| DeleteTagsOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_ec2,
+                RuleMode::MatchAny,
+                [&endpoint, &networks, &associate, &route, &cleanup]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(associate.num_calls(), 2);
+        assert_eq!(networks.num_calls(), 2);
+        assert_eq!(route.num_calls(), 1);
+        assert_eq!(cleanup.num_calls(), 1);
+    }
+}
@@ -0,0 +1,338 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_ec2::types::{Filter, InstanceStateName};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Tag set by Cloud9 on the EC2 instance backing an environment.
+const ENVIRONMENT_TAG: &str = "aws:cloud9:environment";
+
+/// Stop/start handler for EC2-backed Cloud9 environments in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover environments matching a tag,
+/// resolves each one to its underlying EC2 instance and stops or starts it.
+/// This does not rely on Cloud9's own auto-hibernate setting, which users often disable.
+/// SSH environments have no instance and are skipped.
+pub struct Cloud9Scheduler {
+    ec2: Ec2Client,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl Cloud9Scheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            ec2: Ec2Client::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Find the EC2 instance backing an environment, with its current state.
+    async fn environment_instance(
+        &self,
+        environment_id: &str,
+    ) -> Result<Option<(String, InstanceStateName)>> {
+        let resp = self
+            .ec2
+            .describe_instances()
+            .filters(
+                Filter::builder()
+                    .name(format!("tag:{}", ENVIRONMENT_TAG))
+                    .values(environment_id)
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        Ok(resp
+            .reservations()
+            .iter()
+            .flat_map(|r| r.instances())
+            .filter_map(|i| Some((i.instance_id()?.to_string(), i.state()?.name()?.clone())))
+            .find(|(_, state)| *state != InstanceStateName::Terminated))
+    }
+
+    async fn stop_environment(&self, environment_id: &str) -> Result<Outcome> {
+        let Some((instance_id, state)) = self.environment_instance(environment_id).await? else {
+            info!(environment = %environment_id, "Cloud9 environment has no EC2 instance, skipping");
+            return Ok(Outcome::Skipped);
+        };
+        if matches!(
+            state,
+            InstanceStateName::Stopped | InstanceStateName::Stopping
+        ) {
+            info!(environment = %environment_id, instance_id = %instance_id, state = %state, "Cloud9 instance already stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(environment = %environment_id, instance_id = %instance_id, "Dry run: would stop Cloud9 instance");
+            return Ok(Outcome::Applied);
+        }
+        info!(environment = %environment_id, instance_id = %instance_id, "Stopping Cloud9 instance");
+        self.ec2
+            .stop_instances()
+            .instance_ids(&instance_id)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn start_environment(&self, environment_id: &str) -> Result<Outcome> {
+        let Some((instance_id, state)) = self.environment_instance(environment_id).await? else {
+            info!(environment = %environment_id, "Cloud9 environment has no EC2 instance, skipping");
+            return Ok(Outcome::Skipped);
+        };
+        if matches!(
+            state,
+            InstanceStateName::Running | InstanceStateName::Pending
+        ) {
+            info!(environment = %environment_id, instance_id = %instance_id, state = %state, "Cloud9 instance already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(environment = %environment_id, instance_id = %instance_id, "Dry run: would start Cloud9 instance");
+            return Ok(Outcome::Applied);
+        }
+        info!(environment = %environment_id, instance_id = %instance_id, "Starting Cloud9 instance");
+        self.ec2
+            .start_instances()
+            .instance_ids(&instance_id)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for Cloud9Scheduler {
+    fn name(&self) -> &'static str {
+        "cloud9"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "cloud9:environment", filter)
+                .await?;
+        info!(count = arns.len(), "Found Cloud9 environments to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let environment_id = extract_environment_id(arn);
+            let result = self.stop_environment(&environment_id).await;
+            (environment_id, result)
+        })
+        .await;
+        for (environment_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&environment_id, outcome),
+                Err(e) => {
+                    counts.fail(&environment_id, &e);
+                    error!(environment = %environment_id, error = %e, "Failed to stop Cloud9 environment");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "cloud9:environment", filter)
+                .await?;
+        info!(count = arns.len(), "Found Cloud9 environments to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let environment_id = extract_environment_id(arn);
+            let result = self.start_environment(&environment_id).await;
+            (environment_id, result)
+        })
+        .await;
+        for (environment_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&environment_id, outcome),
+                Err(e) => {
+                    counts.fail(&environment_id, &e);
+                    error!(environment = %environment_id, error = %e, "Failed to start Cloud9 environment");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the Cloud9 environments matching the given filter with the state of
+    /// their EC2 instance (`no-instance` for SSH environments).
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "cloud9:environment", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let state = match self
+                .environment_instance(&extract_environment_id(&arn))
+                .await?
+            {
+                Some((_, state)) => state.as_str().to_string(),
+                None => "no-instance".to_string(),
+            };
+            items.push(InventoryItem::new(arn, "cloud9:environment", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the environment ID from a Cloud9 environment ARN.
+///
+/// Expected ARN format: `arn:partition:cloud9:region:account:environment:environment-id`
+fn extract_environment_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_ec2::operation::describe_instances::DescribeInstancesOutput;
+    use aws_sdk_ec2::operation::start_instances::StartInstancesOutput;
+    use aws_sdk_ec2::operation::stop_instances::StopInstancesOutput;
+    use aws_sdk_ec2::types::{Instance, InstanceState, Reservation};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const ENVIRONMENT_ARN_PREFIX: &str = "arn:aws:cloud9:eu-west-1:123456789012:environment:";
+
+    /// Rule describing the instances of `environment_id`, as `(instance_id, state)`.
+    fn describe_instances_rule(
+        environment_id: &'static str,
+        instances: &'static [(&'static str, &'static str)],
+    ) -> Rule {
+        mock!(Ec2Client::describe_instances)
+            .match_requests(move |req| {
+                req.filters().first().is_some_and(|f| {
+                    f.name() == Some("tag:aws:cloud9:environment")
+                        && f.values() == [environment_id.to_string()]
+                })
+            })
+            .then_output(move || {
+                let instances = instances.iter().map(|(instance_id, state)| {
+                    Instance::builder()
+                        .instance_id(*instance_id)
+                        .state(
+                            InstanceState::builder()
+                                .name(InstanceStateName::from(*state))
+                                .build(),
+                        )
+                        .build()
+                });
+                DescribeInstancesOutput::builder()
+                    .reservations(
+                        Reservation::builder()
+                            .set_instances(Some(instances.collect()))
+                            .build(),
+                    )
+                    .build()
+            })
+    }
+
+    fn scheduler(ec2: Ec2Client, tagging: TaggingClient) -> Cloud9Scheduler {
+        Cloud9Scheduler {
+            ec2,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_skips_environments_without_a_live_instance() {
+        let arns = get_resources_rule(
+            "cloud9:environment",
+            &[
+                &format!("{ENVIRONMENT_ARN_PREFIX}0a1b2c3d"),
+                &format!("{ENVIRONMENT_ARN_PREFIX}4e5f6a7b"),
+            ],
+        );
+        let running = describe_instances_rule(
+            "0a1b2c3d",
+            &[
+                ("i-0000000000000000a", "terminated"),
+                ("i-0000000000000000b", "running"),
+            ],
+        );
+        let terminated =
+            describe_instances_rule("4e5f6a7b", &[("i-0000000000000000c", "terminated")]);
+        let stop = mock!(Ec2Client::stop_instances)
+            .match_requests(|req| req.instance_ids() == ["i-0000000000000000b".to_string()])
+            .then_output(|| StopInstancesOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_ec2,
+                RuleMode::MatchAny,
+                [&running, &terminated, &stop]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(stop.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_starts_the_stopped_instance_of_an_environment() {
+        let arns = get_resources_rule(
+            "cloud9:environment",
+            &[&format!("{ENVIRONMENT_ARN_PREFIX}0a1b2c3d")],
+        );
+        let stopped = describe_instances_rule("0a1b2c3d", &[("i-0000000000000000b", "stopped")]);
+        let start = mock!(Ec2Client::start_instances)
+            .match_requests(|req| req.instance_ids() == ["i-0000000000000000b".to_string()])
+            .then_output(|| StartInstancesOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_ec2, RuleMode::MatchAny, [&stopped, &start]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(start.num_calls(), 1);
+    }
+}
@@ -0,0 +1,390 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::{Region, SdkConfig};
+use aws_sdk_cloudfront::Client as CloudFrontClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// CloudFront is a global service whose resources are tagged and managed in `us-east-1`.
+const CLOUDFRONT_REGION: &str = "us-east-1";
+
+/// Distribution status reported once a configuration change has been propagated.
+const DEPLOYED_STATUS: &str = "Deployed";
+
+/// Maximum number of polls while waiting for updated distributions to be deployed.
+const MAX_DEPLOYMENT_POLLS: u32 = 60;
+
+/// Delay between two polls while waiting for updated distributions to be deployed.
+const DEPLOYMENT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Stop/start handler for CloudFront distributions.
+///
+/// Uses the Resource Groups Tagging API to discover distributions matching a tag.
+/// On stop: disables the distributions, so that they stop serving traffic.
+/// On start: re-enables them.
+/// In both cases the scheduler waits for the updated distributions to be deployed.
+///
+/// Distributions are global, so they are always looked up in `us-east-1`
+/// whatever the region being processed, and the service is only processed
+/// in one region (see [`AppConfig::service_regions`](crate::config::AppConfig::service_regions)).
+pub struct CloudFrontScheduler {
+    cloudfront: CloudFrontClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl CloudFrontScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        let config = config
+            .to_builder()
+            .region(Region::from_static(CLOUDFRONT_REGION))
+            .build();
+
+        Self {
+            cloudfront: CloudFrontClient::new(&config),
+            tagging: TaggingClient::new(&config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Enable or disable every distribution matching the filter, then wait for them to be deployed.
+    async fn process(&self, filter: &ResourceFilter, enabled: bool) -> Result<ResourceCounts> {
+        let action = if enabled { "start" } else { "stop" };
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "cloudfront:distribution",
+            filter,
+        )
+        .await?;
+        info!(
+            count = arns.len(),
+            "Found CloudFront distributions to {action}"
+        );
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let mut updated = Vec::new();
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let distribution_id = extract_distribution_id(arn);
+            let result = self.set_enabled(&distribution_id, enabled).await;
+            (distribution_id, result)
+        })
+        .await;
+        for (distribution_id, result) in results {
+            match result {
+                Ok(outcome) => {
+                    counts.record(&distribution_id, outcome);
+                    if outcome == Outcome::Applied && !self.dry_run {
+                        updated.push(distribution_id);
+                    }
+                }
+                Err(e) => {
+                    counts.fail(&distribution_id, &e);
+                    error!(distribution = %distribution_id, error = %e, "Failed to {action} CloudFront distribution");
+                }
+            }
+        }
+
+        if !updated.is_empty() {
+            if let Err(e) = self.wait_deployed(&updated).await {
+                warn!(error = %e, "CloudFront distributions not deployed yet, they will keep propagating");
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn set_enabled(&self, distribution_id: &str, enabled: bool) -> Result<Outcome> {
+        let resp = self
+            .cloudfront
+            .get_distribution_config()
+            .id(distribution_id)
+            .send()
+            .await?;
+        let mut config = resp.distribution_config().cloned().with_context(|| {
+            format!(
+                "CloudFront distribution '{}' has no configuration",
+                distribution_id
+            )
+        })?;
+        let e_tag = resp.e_tag().with_context(|| {
+            format!("CloudFront distribution '{}' has no ETag", distribution_id)
+        })?;
+
+        if config.enabled() == enabled {
+            let state = if enabled { "enabled" } else { "disabled" };
+            info!(distribution = %distribution_id, "CloudFront distribution already {state}, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            let action = if enabled { "enable" } else { "disable" };
+            info!(distribution = %distribution_id, "Dry run: would {action} CloudFront distribution");
+            return Ok(Outcome::Applied);
+        }
+
+        let action = if enabled { "Enabling" } else { "Disabling" };
+        info!(distribution = %distribution_id, "{action} CloudFront distribution");
+        config.enabled = enabled;
+        self.cloudfront
+            .update_distribution()
+            .id(distribution_id)
+            .if_match(e_tag)
+            .distribution_config(config)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Poll updated distributions until all of them are deployed.
+    async fn wait_deployed(&self, distribution_ids: &[String]) -> Result<()> {
+        for _ in 0..MAX_DEPLOYMENT_POLLS {
+            tokio::time::sleep(DEPLOYMENT_POLL_INTERVAL).await;
+
+            let mut pending = 0;
+            for distribution_id in distribution_ids {
+                let resp = self
+                    .cloudfront
+                    .get_distribution()
+                    .id(distribution_id)
+                    .send()
+                    .await?;
+                if resp
+                    .distribution()
+                    .is_none_or(|d| d.status() != DEPLOYED_STATUS)
+                {
+                    pending += 1;
+                }
+            }
+            if pending == 0 {
+                info!(
+                    count = distribution_ids.len(),
+                    "CloudFront distributions deployed"
+                );
+                return Ok(());
+            }
+            info!(
+                pending,
+                "Waiting for CloudFront distributions to be deployed"
+            );
+        }
+
+        bail!(
+            "Timed out waiting for {} CloudFront distributions to be deployed",
+            distribution_ids.len()
+        );
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for CloudFrontScheduler {
+    fn name(&self) -> &'static str {
+        "cloudfront"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        self.process(filter, false).await
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        self.process(filter, true).await
+    }
+
+    /// List the CloudFront distributions matching the given filter, `enabled` or `disabled`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "cloudfront:distribution",
+            filter,
+        )
+        .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let resp = self
+                .cloudfront
+                .get_distribution_config()
+                .id(extract_distribution_id(&arn))
+                .send()
+                .await?;
+            let state = if resp.distribution_config().is_some_and(|c| c.enabled()) {
+                "enabled"
+            } else {
+                "disabled"
+            };
+            items.push(InventoryItem::new(arn, "cloudfront:distribution", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the distribution ID from a CloudFront distribution ARN.
+///
+/// Expected ARN format: `arn:partition:cloudfront::account:distribution/distribution-id`
+fn extract_distribution_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_cloudfront::operation::get_distribution::GetDistributionOutput;
+    use aws_sdk_cloudfront::operation::get_distribution_config::GetDistributionConfigOutput;
+    use aws_sdk_cloudfront::operation::update_distribution::UpdateDistributionOutput;
+    use aws_sdk_cloudfront::primitives::DateTime;
+    use aws_sdk_cloudfront::types::{Distribution, DistributionConfig};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const DISTRIBUTION_ARN_PREFIX: &str = "arn:aws:cloudfront::123456789012:distribution/";
+    const E_TAG: &str = "E2QWRUHEXAMPLE";
+
+    /// Rule answering the configuration of `distribution_id`, enabled or not.
+    fn get_config_rule(distribution_id: &'static str, enabled: bool) -> Rule {
+        mock!(CloudFrontClient::get_distribution_config)
+            .match_requests(move |req| req.id() == Some(distribution_id))
+            .then_output(move || {
+                GetDistributionConfigOutput::builder()
+                    .distribution_config(
+                        DistributionConfig::builder()
+                            .caller_reference("terraform")
+                            .comment(distribution_id)
+                            .enabled(enabled)
+                            .build()
+                            .unwrap(),
+                    )
+                    .e_tag(E_TAG)
+                    .build()
+            })
+    }
+
+    /// Rule updating `distribution_id` to the given state, guarded by its ETag.
+    fn update_rule(distribution_id: &'static str, enabled: bool) -> Rule {
+        mock!(CloudFrontClient::update_distribution)
+            .match_requests(move |req| {
+                req.id() == Some(distribution_id)
+                    && req.if_match() == Some(E_TAG)
+                    && req
+                        .distribution_config()
+                        .is_some_and(|c| c.enabled() == enabled && c.comment() == distribution_id)
+            })
+            .then_output(|| UpdateDistributionOutput::builder().build())
+    }
+
+    fn distribution(distribution_id: &str, status: &str) -> GetDistributionOutput {
+        GetDistributionOutput::builder()
+            .distribution(
+                Distribution::builder()
+                    .id(distribution_id)
+                    .arn(format!("{DISTRIBUTION_ARN_PREFIX}{distribution_id}"))
+                    .status(status)
+                    .last_modified_time(DateTime::from_secs(0))
+                    .in_progress_invalidation_batches(0)
+                    .domain_name("d111111abcdef8.cloudfront.net")
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+    }
+
+    fn scheduler(cloudfront: CloudFrontClient, tagging: TaggingClient) -> CloudFrontScheduler {
+        CloudFrontScheduler {
+            cloudfront,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_disables_enabled_distributions_only() {
+        let arns = get_resources_rule(
+            "cloudfront:distribution",
+            &[
+                &format!("{DISTRIBUTION_ARN_PREFIX}EDFDVBD6EXAMPLE"),
+                &format!("{DISTRIBUTION_ARN_PREFIX}E1ZMV5EXAMPLE"),
+            ],
+        );
+        let enabled = get_config_rule("EDFDVBD6EXAMPLE", true);
+        let disabled = get_config_rule("E1ZMV5EXAMPLE", false);
+        let update = update_rule("EDFDVBD6EXAMPLE", false);
+        let deployed = mock!(CloudFrontClient::get_distribution)
+            .match_requests(|req| req.id() == Some("EDFDVBD6EXAMPLE"))
+            .then_output(|| distribution("EDFDVBD6EXAMPLE", DEPLOYED_STATUS));
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_cloudfront,
+                RuleMode::MatchAny,
+                [&enabled, &disabled, &update, &deployed]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+        assert_eq!(deployed.num_calls(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn start_waits_for_the_distributions_to_be_deployed() {
+        let arns = get_resources_rule(
+            "cloudfront:distribution",
+            &[&format!("{DISTRIBUTION_ARN_PREFIX}E1ZMV5EXAMPLE")],
+        );
+        let disabled = get_config_rule("E1ZMV5EXAMPLE", false);
+        let update = update_rule("E1ZMV5EXAMPLE", true);
+        let deployment = mock!(CloudFrontClient::get_distribution)
+            .match_requests(|req| req.id() == Some("E1ZMV5EXAMPLE"))
+            .sequence()
+            .output(|| distribution("E1ZMV5EXAMPLE", "InProgress"))
+            .output(|| distribution("E1ZMV5EXAMPLE", DEPLOYED_STATUS))
+            .build();
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_cloudfront,
+                RuleMode::MatchAny,
+                [&disabled, &update, &deployment]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+        assert_eq!(deployment.num_calls(), 2);
+    }
+}
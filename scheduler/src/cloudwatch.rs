@@ -1,9 +1,17 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_cloudwatch::types::AlarmType;
 use aws_sdk_cloudwatch::Client as CloudWatchClient;
 use aws_sdk_resourcegroupstagging::Client as TaggingClient;
 use tracing::{error, info};
 
-use crate::filter_resources_by_tags;
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
 
 /// Stop/start handler for CloudWatch alarm actions in a given AWS region.
 ///
@@ -12,75 +20,284 @@ use crate::filter_resources_by_tags;
 pub struct CloudWatchScheduler {
     cloudwatch: CloudWatchClient,
     tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
 }
 
 impl CloudWatchScheduler {
-    pub async fn new(region: &str) -> Self {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
-
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
         Self {
-            cloudwatch: CloudWatchClient::new(&config),
-            tagging: TaggingClient::new(&config),
+            cloudwatch: CloudWatchClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
         }
     }
 
-    pub async fn stop(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "cloudwatch:alarm", tag_key, tag_value).await?;
-        info!(count = arns.len(), "Found CloudWatch alarms to disable");
+    /// Check whether the actions of an alarm are currently enabled.
+    async fn actions_enabled(&self, alarm_name: &str) -> Result<bool> {
+        let resp = self
+            .cloudwatch
+            .describe_alarms()
+            .alarm_names(alarm_name)
+            .alarm_types(AlarmType::MetricAlarm)
+            .alarm_types(AlarmType::CompositeAlarm)
+            .send()
+            .await?;
 
-        for arn in &arns {
-            let alarm_name = extract_alarm_name(arn);
-            if let Err(e) = self.disable_alarm(&alarm_name).await {
-                error!(alarm = %alarm_name, error = %e, "Failed to disable alarm");
-            }
+        if let Some(alarm) = resp.metric_alarms().first() {
+            return Ok(alarm.actions_enabled().unwrap_or_default());
         }
-
-        Ok(())
+        resp.composite_alarms()
+            .first()
+            .map(|a| a.actions_enabled().unwrap_or_default())
+            .with_context(|| format!("CloudWatch alarm '{}' not found", alarm_name))
     }
 
-    pub async fn start(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "cloudwatch:alarm", tag_key, tag_value).await?;
-        info!(count = arns.len(), "Found CloudWatch alarms to enable");
-
-        for arn in &arns {
-            let alarm_name = extract_alarm_name(arn);
-            if let Err(e) = self.enable_alarm(&alarm_name).await {
-                error!(alarm = %alarm_name, error = %e, "Failed to enable alarm");
-            }
+    async fn disable_alarm(&self, alarm_name: &str) -> Result<Outcome> {
+        if !self.actions_enabled(alarm_name).await? {
+            info!(alarm = %alarm_name, "Alarm actions already disabled, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(alarm = %alarm_name, "Dry run: would disable alarm actions");
+            return Ok(Outcome::Applied);
         }
-
-        Ok(())
-    }
-
-    async fn disable_alarm(&self, alarm_name: &str) -> Result<()> {
         info!(alarm = %alarm_name, "Disabling alarm actions");
         self.cloudwatch
             .disable_alarm_actions()
             .alarm_names(alarm_name)
             .send()
             .await?;
-        Ok(())
+        Ok(Outcome::Applied)
     }
 
-    async fn enable_alarm(&self, alarm_name: &str) -> Result<()> {
+    async fn enable_alarm(&self, alarm_name: &str) -> Result<Outcome> {
+        if self.actions_enabled(alarm_name).await? {
+            info!(alarm = %alarm_name, "Alarm actions already enabled, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(alarm = %alarm_name, "Dry run: would enable alarm actions");
+            return Ok(Outcome::Applied);
+        }
         info!(alarm = %alarm_name, "Enabling alarm actions");
         self.cloudwatch
             .enable_alarm_actions()
             .alarm_names(alarm_name)
             .send()
             .await?;
-        Ok(())
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for CloudWatchScheduler {
+    fn name(&self) -> &'static str {
+        "cloudwatch"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "cloudwatch:alarm", filter)
+                .await?;
+        info!(count = arns.len(), "Found CloudWatch alarms to disable");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let alarm_name = extract_alarm_name(arn);
+            let result = self.disable_alarm(&alarm_name).await;
+            (alarm_name, result)
+        })
+        .await;
+        for (alarm_name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&alarm_name, outcome),
+                Err(e) => {
+                    counts.fail(&alarm_name, &e);
+                    error!(alarm = %alarm_name, error = %e, "Failed to disable alarm");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "cloudwatch:alarm", filter)
+                .await?;
+        info!(count = arns.len(), "Found CloudWatch alarms to enable");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let alarm_name = extract_alarm_name(arn);
+            let result = self.enable_alarm(&alarm_name).await;
+            (alarm_name, result)
+        })
+        .await;
+        for (alarm_name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&alarm_name, outcome),
+                Err(e) => {
+                    counts.fail(&alarm_name, &e);
+                    error!(alarm = %alarm_name, error = %e, "Failed to enable alarm");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the CloudWatch alarms matching the given filter with the state of their actions.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "cloudwatch:alarm", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let state = if self.actions_enabled(&extract_alarm_name(&arn)).await? {
+                "actions-enabled"
+            } else {
+                "actions-disabled"
+            };
+            items.push(InventoryItem::new(arn, "cloudwatch:alarm", state));
+        }
+        Ok(items)
     }
 }
 
 /// Extract the alarm name from a CloudWatch alarm ARN.
 ///
-/// Expected ARN format: `arn:aws:cloudwatch:region:account:alarm:name`
+/// Expected ARN format: `arn:partition:cloudwatch:region:account:alarm:name`
 fn extract_alarm_name(arn: &str) -> String {
-    arn.split(':').last().unwrap_or(arn).to_string()
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_cloudwatch::operation::describe_alarms::DescribeAlarmsOutput;
+    use aws_sdk_cloudwatch::operation::disable_alarm_actions::DisableAlarmActionsOutput;
+    use aws_sdk_cloudwatch::operation::enable_alarm_actions::EnableAlarmActionsOutput;
+    use aws_sdk_cloudwatch::types::{CompositeAlarm, MetricAlarm};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const ALARM_ARN_PREFIX: &str = "arn:aws:cloudwatch:eu-west-1:123456789012:alarm:";
+
+    /// Rule describing the metric alarm `alarm_name`.
+    fn metric_alarm_rule(alarm_name: &'static str, actions_enabled: bool) -> Rule {
+        mock!(CloudWatchClient::describe_alarms)
+            .match_requests(move |req| req.alarm_names() == [alarm_name.to_string()])
+            .then_output(move || {
+                DescribeAlarmsOutput::builder()
+                    .metric_alarms(
+                        MetricAlarm::builder()
+                            .alarm_name(alarm_name)
+                            .actions_enabled(actions_enabled)
+                            .build(),
+                    )
+                    .build()
+            })
+    }
+
+    /// Rule describing the composite alarm `alarm_name`.
+    fn composite_alarm_rule(alarm_name: &'static str, actions_enabled: bool) -> Rule {
+        mock!(CloudWatchClient::describe_alarms)
+            .match_requests(move |req| req.alarm_names() == [alarm_name.to_string()])
+            .then_output(move || {
+                DescribeAlarmsOutput::builder()
+                    .composite_alarms(
+                        CompositeAlarm::builder()
+                            .alarm_name(alarm_name)
+                            .actions_enabled(actions_enabled)
+                            .build(),
+                    )
+                    .build()
+            })
+    }
+
+    fn scheduler(cloudwatch: CloudWatchClient, tagging: TaggingClient) -> CloudWatchScheduler {
+        CloudWatchScheduler {
+            cloudwatch,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_disables_alarms_with_enabled_actions() {
+        let arns = get_resources_rule(
+            "cloudwatch:alarm",
+            &[
+                &format!("{ALARM_ARN_PREFIX}high-latency"),
+                &format!("{ALARM_ARN_PREFIX}service-health"),
+            ],
+        );
+        let latency = metric_alarm_rule("high-latency", true);
+        let health = composite_alarm_rule("service-health", false);
+        let disable = mock!(CloudWatchClient::disable_alarm_actions)
+            .match_requests(|req| req.alarm_names() == ["high-latency".to_string()])
+            .then_output(|| DisableAlarmActionsOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_cloudwatch,
+                RuleMode::MatchAny,
+                [&latency, &health, &disable]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(disable.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_enables_composite_alarms() {
+        let arns = get_resources_rule(
+            "cloudwatch:alarm",
+            &[&format!("{ALARM_ARN_PREFIX}service-health")],
+        );
+        let health = composite_alarm_rule("service-health", false);
+        let enable = mock!(CloudWatchClient::enable_alarm_actions)
+            .match_requests(|req| req.alarm_names() == ["service-health".to_string()])
+            .then_output(|| EnableAlarmActionsOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_cloudwatch, RuleMode::MatchAny, [&health, &enable]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(enable.num_calls(), 1);
+    }
 }
@@ -0,0 +1,235 @@
+use anyhow::{Context, Result};
+use aws_sdk_cloudwatch::primitives::DateTime;
+use aws_sdk_cloudwatch::types::{Dimension, MetricDatum, StandardUnit};
+use aws_sdk_cloudwatch::Client as CloudWatchClient;
+use serde_json::{json, Map, Value};
+use tracing::info;
+
+use crate::config::AppConfig;
+use crate::credentials;
+use crate::summary::ExecutionSummary;
+
+/// Maximum number of metric data points sent in a single `PutMetricData` call.
+const BATCH_SIZE: usize = 100;
+
+/// Metrics sharing the same dimensions.
+struct MetricSet<'a> {
+    dimensions: Vec<(&'static str, &'a str)>,
+    /// Name, value and CloudWatch unit of each metric.
+    values: Vec<(&'static str, f64, &'static str)>,
+}
+
+/// Publish the metrics of a run to CloudWatch under the given namespace.
+///
+/// Every service and region gets `ResourcesDiscovered`, `ResourcesStopped`
+/// (`ResourcesStarted`, `ResourcesTerminated` depending on the action),
+/// `ResourcesFailed`, `ResourcesSkipped`, `ServiceErrors` and `ServiceDurationSeconds`,
+/// with `Action`, `Service` and `Region` dimensions. The run as a whole gets
+/// `RunDurationSeconds` and `RunFailed`, with an `Action` dimension.
+///
+/// Metrics are put in the first region of `AWS_REGIONS`, with the ambient credentials.
+pub async fn publish(
+    config: &AppConfig,
+    namespace: &str,
+    summary: &ExecutionSummary,
+) -> Result<()> {
+    let sdk_config = credentials::load_config(config, &config.aws_regions[0], None).await;
+    let cloudwatch = CloudWatchClient::new(&sdk_config);
+
+    let data = metric_data(summary);
+    info!(namespace = %namespace, count = data.len(), "Publishing metrics to CloudWatch");
+    for batch in data.chunks(BATCH_SIZE) {
+        cloudwatch
+            .put_metric_data()
+            .namespace(namespace)
+            .set_metric_data(Some(batch.to_vec()))
+            .send()
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to put metrics in CloudWatch namespace '{}'",
+                    namespace
+                )
+            })?;
+    }
+    Ok(())
+}
+
+/// Build the CloudWatch Embedded Metric Format records of a run, one JSON line per
+/// metric set, holding the same metrics as [`publish`].
+///
+/// Once written to stdout by a Lambda function or an ECS task using the `awslogs`
+/// driver, the records are turned into CloudWatch metrics without any API call.
+pub fn emf_records(namespace: &str, summary: &ExecutionSummary) -> Vec<String> {
+    let timestamp = chrono::Utc::now().timestamp_millis();
+
+    metric_sets(summary)
+        .into_iter()
+        .map(|set| {
+            let dimension_names: Vec<&str> = set.dimensions.iter().map(|(name, _)| *name).collect();
+            let metrics: Vec<Value> = set
+                .values
+                .iter()
+                .map(|(name, _, unit)| json!({ "Name": name, "Unit": unit }))
+                .collect();
+
+            let mut record = Map::new();
+            record.insert(
+                "_aws".to_string(),
+                json!({
+                    "Timestamp": timestamp,
+                    "CloudWatchMetrics": [{
+                        "Namespace": namespace,
+                        "Dimensions": [dimension_names],
+                        "Metrics": metrics,
+                    }],
+                }),
+            );
+            for (name, value) in &set.dimensions {
+                record.insert(name.to_string(), json!(value));
+            }
+            for (name, value, _) in &set.values {
+                record.insert(name.to_string(), json!(value));
+            }
+            Value::Object(record).to_string()
+        })
+        .collect()
+}
+
+/// Build the metric data points of a run.
+fn metric_data(summary: &ExecutionSummary) -> Vec<MetricDatum> {
+    let timestamp = DateTime::from_secs(chrono::Utc::now().timestamp());
+    let sets = metric_sets(summary);
+
+    sets.iter()
+        .flat_map(|set| {
+            let dimensions: Vec<Dimension> = set
+                .dimensions
+                .iter()
+                .map(|(name, value)| Dimension::builder().name(*name).value(*value).build())
+                .collect();
+            set.values.iter().map(move |(name, value, unit)| {
+                MetricDatum::builder()
+                    .metric_name(*name)
+                    .value(*value)
+                    .unit(StandardUnit::from(*unit))
+                    .timestamp(timestamp)
+                    .set_dimensions(Some(dimensions.clone()))
+                    .build()
+            })
+        })
+        .collect()
+}
+
+/// Group the metrics of a run by dimensions: the run first, then each service and region.
+fn metric_sets(summary: &ExecutionSummary) -> Vec<MetricSet<'_>> {
+    let action = summary.action.as_str();
+    let succeeded_name = match action {
+        "start" => "ResourcesStarted",
+        "terminate" => "ResourcesTerminated",
+        _ => "ResourcesStopped",
+    };
+    let failed = !summary.failed_services().is_empty() || summary.interrupted;
+
+    let mut sets = vec![MetricSet {
+        dimensions: vec![("Action", action)],
+        values: vec![
+            ("RunDurationSeconds", summary.duration_seconds, "Seconds"),
+            ("RunFailed", if failed { 1.0 } else { 0.0 }, "Count"),
+        ],
+    }];
+
+    for service in &summary.services {
+        let counts = &service.counts;
+        sets.push(MetricSet {
+            dimensions: vec![
+                ("Action", action),
+                ("Service", service.service.as_str()),
+                ("Region", service.region.as_str()),
+            ],
+            values: vec![
+                ("ResourcesDiscovered", counts.discovered as f64, "Count"),
+                (succeeded_name, counts.succeeded as f64, "Count"),
+                ("ResourcesFailed", counts.failed as f64, "Count"),
+                ("ResourcesSkipped", counts.skipped as f64, "Count"),
+                (
+                    "ServiceErrors",
+                    if service.error.is_some() { 1.0 } else { 0.0 },
+                    "Count",
+                ),
+                (
+                    "ServiceDurationSeconds",
+                    service.duration_seconds,
+                    "Seconds",
+                ),
+            ],
+        });
+    }
+
+    sets
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::summary::{ResourceCounts, ServiceSummary};
+
+    #[test]
+    fn emf_records_hold_one_metric_set_per_line() {
+        let counts = ResourceCounts {
+            discovered: 3,
+            succeeded: 2,
+            failed: 1,
+            ..ResourceCounts::default()
+        };
+        let service = ServiceSummary::new("ec2", "eu-west-1", None, Ok(counts))
+            .with_duration(Duration::from_millis(1500));
+        let summary = ExecutionSummary::new(
+            "stop".to_string(),
+            false,
+            chrono::Utc::now(),
+            vec![service],
+            false,
+        );
+
+        let records: Vec<Value> = emf_records("Scheduler", &summary)
+            .iter()
+            .map(|record| serde_json::from_str(record).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        let run = &records[0];
+        assert_eq!(
+            run["_aws"]["CloudWatchMetrics"][0]["Namespace"],
+            "Scheduler"
+        );
+        assert_eq!(
+            run["_aws"]["CloudWatchMetrics"][0]["Dimensions"],
+            json!([["Action"]])
+        );
+        assert_eq!(run["Action"], "stop");
+        assert_eq!(run["RunFailed"], 1.0);
+
+        let service = &records[1];
+        let metrics = &service["_aws"]["CloudWatchMetrics"][0];
+        assert_eq!(metrics["Namespace"], "Scheduler");
+        assert_eq!(
+            metrics["Dimensions"],
+            json!([["Action", "Service", "Region"]])
+        );
+        assert_eq!(
+            metrics["Metrics"][1],
+            json!({ "Name": "ResourcesStopped", "Unit": "Count" })
+        );
+        assert_eq!(service["Service"], "ec2");
+        assert_eq!(service["Region"], "eu-west-1");
+        assert_eq!(service["ResourcesDiscovered"], 3.0);
+        assert_eq!(service["ResourcesStopped"], 2.0);
+        assert_eq!(service["ResourcesFailed"], 1.0);
+        assert_eq!(service["ResourcesSkipped"], 0.0);
+        assert_eq!(service["ServiceDurationSeconds"], 1.5);
+        assert!(service["_aws"]["Timestamp"].is_i64());
+    }
+}
@@ -0,0 +1,361 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_codepipeline::types::{StageState, StageTransitionType};
+use aws_sdk_codepipeline::Client as CodePipelineClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info};
+
+use crate::arn::Arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Reason set on the transitions disabled by the scheduler.
+///
+/// On start, only the transitions carrying this reason are re-enabled, so that
+/// transitions disabled by hand stay disabled.
+const DISABLED_REASON: &str = "Disabled by scheduler during the stop window";
+
+/// Stop/start handler for CodePipeline pipelines in a given AWS region.
+///
+/// On stop: disables the inbound transition of every stage but the first one,
+/// so that deployments do not run against stopped environments.
+/// On start: re-enables the transitions disabled by the scheduler.
+pub struct CodePipelineScheduler {
+    codepipeline: CodePipelineClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl CodePipelineScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            codepipeline: CodePipelineClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Discover the names of the pipelines matching the filter.
+    ///
+    /// The Tagging API reports webhooks and custom action types under the same
+    /// service, so only pipeline ARNs are kept.
+    async fn pipeline_names(&self, filter: &ResourceFilter) -> Result<Vec<String>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "codepipeline", filter).await?;
+
+        Ok(arns
+            .iter()
+            .filter_map(|arn| extract_pipeline_name(arn))
+            .collect())
+    }
+
+    /// List the stages of a pipeline after the first one, which has no inbound transition.
+    async fn stage_states(&self, pipeline: &str) -> Result<Vec<StageState>> {
+        let resp = self
+            .codepipeline
+            .get_pipeline_state()
+            .name(pipeline)
+            .send()
+            .await?;
+
+        Ok(resp.stage_states().iter().skip(1).cloned().collect())
+    }
+
+    async fn disable_transitions(&self, pipeline: &str) -> Result<Outcome> {
+        let stages: Vec<String> = self
+            .stage_states(pipeline)
+            .await?
+            .iter()
+            .filter(|s| s.inbound_transition_state().is_none_or(|t| t.enabled()))
+            .filter_map(|s| s.stage_name().map(str::to_string))
+            .collect();
+        if stages.is_empty() {
+            info!(pipeline = %pipeline, "CodePipeline transitions already disabled, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(pipeline = %pipeline, stages = ?stages, "Dry run: would disable CodePipeline transitions");
+            return Ok(Outcome::Applied);
+        }
+
+        for stage in &stages {
+            info!(pipeline = %pipeline, stage = %stage, "Disabling CodePipeline inbound transition");
+            self.codepipeline
+                .disable_stage_transition()
+                .pipeline_name(pipeline)
+                .stage_name(stage)
+                .transition_type(StageTransitionType::Inbound)
+                .reason(DISABLED_REASON)
+                .send()
+                .await?;
+        }
+        Ok(Outcome::Applied)
+    }
+
+    async fn enable_transitions(&self, pipeline: &str) -> Result<Outcome> {
+        let stages: Vec<String> = self
+            .stage_states(pipeline)
+            .await?
+            .iter()
+            .filter(|s| {
+                s.inbound_transition_state()
+                    .is_some_and(|t| !t.enabled() && t.disabled_reason() == Some(DISABLED_REASON))
+            })
+            .filter_map(|s| s.stage_name().map(str::to_string))
+            .collect();
+        if stages.is_empty() {
+            info!(pipeline = %pipeline, "No CodePipeline transition disabled by the scheduler, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(pipeline = %pipeline, stages = ?stages, "Dry run: would enable CodePipeline transitions");
+            return Ok(Outcome::Applied);
+        }
+
+        for stage in &stages {
+            info!(pipeline = %pipeline, stage = %stage, "Enabling CodePipeline inbound transition");
+            self.codepipeline
+                .enable_stage_transition()
+                .pipeline_name(pipeline)
+                .stage_name(stage)
+                .transition_type(StageTransitionType::Inbound)
+                .send()
+                .await?;
+        }
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for CodePipelineScheduler {
+    fn name(&self) -> &'static str {
+        "codepipeline"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let pipelines = self.pipeline_names(filter).await?;
+        info!(
+            count = pipelines.len(),
+            "Found CodePipeline pipelines to stop"
+        );
+
+        filter.check_discovered(pipelines.len())?;
+        let mut counts = ResourceCounts::new(pipelines.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &pipelines,
+            |pipeline| async move {
+                let result = self.disable_transitions(pipeline).await;
+                (pipeline, result)
+            },
+        )
+        .await;
+        for (pipeline, result) in results {
+            match result {
+                Ok(outcome) => counts.record(pipeline, outcome),
+                Err(e) => {
+                    counts.fail(pipeline, &e);
+                    error!(pipeline = %pipeline, error = %e, "Failed to disable CodePipeline transitions");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let pipelines = self.pipeline_names(filter).await?;
+        info!(
+            count = pipelines.len(),
+            "Found CodePipeline pipelines to start"
+        );
+
+        filter.check_discovered(pipelines.len())?;
+        let mut counts = ResourceCounts::new(pipelines.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &pipelines,
+            |pipeline| async move {
+                let result = self.enable_transitions(pipeline).await;
+                (pipeline, result)
+            },
+        )
+        .await;
+        for (pipeline, result) in results {
+            match result {
+                Ok(outcome) => counts.record(pipeline, outcome),
+                Err(e) => {
+                    counts.fail(pipeline, &e);
+                    error!(pipeline = %pipeline, error = %e, "Failed to enable CodePipeline transitions");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the CodePipeline pipelines matching the given filter with the state of
+    /// their inbound transitions (`transitions-enabled`, `transitions-disabled`
+    /// or `transitions-partially-disabled`).
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "codepipeline", filter).await?;
+
+        let mut items = Vec::new();
+        for arn in arns {
+            let Some(pipeline) = extract_pipeline_name(&arn) else {
+                continue;
+            };
+            let stages = self.stage_states(&pipeline).await?;
+            let disabled = stages
+                .iter()
+                .filter(|s| s.inbound_transition_state().is_some_and(|t| !t.enabled()))
+                .count();
+            let state = match disabled {
+                0 => "transitions-enabled",
+                n if n == stages.len() => "transitions-disabled",
+                _ => "transitions-partially-disabled",
+            };
+            items.push(InventoryItem::new(arn, "codepipeline:pipeline", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the pipeline name from a CodePipeline ARN, or `None` for other CodePipeline resources.
+///
+/// Expected ARN format: `arn:partition:codepipeline:region:account:pipeline-name`
+/// (webhooks and action types have an extra `webhook:`/`actiontype:` segment).
+fn extract_pipeline_name(arn: &str) -> Option<String> {
+    match Arn::parse(arn) {
+        Ok(parsed) if !parsed.resource.contains(':') => Some(parsed.resource.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_codepipeline::operation::disable_stage_transition::DisableStageTransitionOutput;
+    use aws_sdk_codepipeline::operation::enable_stage_transition::EnableStageTransitionOutput;
+    use aws_sdk_codepipeline::operation::get_pipeline_state::GetPipelineStateOutput;
+    use aws_sdk_codepipeline::types::TransitionState;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const PIPELINE_ARN: &str = "arn:aws:codepipeline:eu-west-1:123456789012:release";
+    const WEBHOOK_ARN: &str = "arn:aws:codepipeline:eu-west-1:123456789012:webhook:github-release";
+
+    /// Rule answering the stages of the `release` pipeline, as `(stage, inbound transition)`
+    /// where the transition is `None` when enabled, or disabled with the given reason.
+    fn pipeline_state_rule(stages: &'static [(&'static str, Option<&'static str>)]) -> Rule {
+        mock!(CodePipelineClient::get_pipeline_state)
+            .match_requests(|req| req.name() == Some("release"))
+            .then_output(move || {
+                let stages = stages.iter().map(|(stage, disabled_reason)| {
+                    StageState::builder()
+                        .stage_name(*stage)
+                        .inbound_transition_state(
+                            TransitionState::builder()
+                                .enabled(disabled_reason.is_none())
+                                .set_disabled_reason(disabled_reason.map(str::to_string))
+                                .build(),
+                        )
+                        .build()
+                });
+                GetPipelineStateOutput::builder()
+                    .pipeline_name("release")
+                    .set_stage_states(Some(stages.collect()))
+                    .build()
+            })
+    }
+
+    fn scheduler(
+        codepipeline: CodePipelineClient,
+        tagging: TaggingClient,
+    ) -> CodePipelineScheduler {
+        CodePipelineScheduler {
+            codepipeline,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_disables_the_enabled_transitions_of_pipelines() {
+        let arns = get_resources_rule("codepipeline", &[PIPELINE_ARN, WEBHOOK_ARN]);
+        let state = pipeline_state_rule(&[
+            ("Source", None),
+            ("Build", None),
+            ("Deploy", Some("Frozen until the release review")),
+        ]);
+        let disable = mock!(CodePipelineClient::disable_stage_transition)
+            .match_requests(|req| {
+                req.pipeline_name() == Some("release")
+                    && req.stage_name() == Some("Build")
+                    && req.reason() == Some(DISABLED_REASON)
+            })
+            .then_output(|| DisableStageTransitionOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_codepipeline, RuleMode::MatchAny, [&state, &disable]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(disable.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_enables_only_the_transitions_disabled_by_the_scheduler() {
+        let arns = get_resources_rule("codepipeline", &[PIPELINE_ARN]);
+        let state = pipeline_state_rule(&[
+            ("Source", None),
+            ("Build", Some(DISABLED_REASON)),
+            ("Deploy", Some("Frozen until the release review")),
+        ]);
+        let enable = mock!(CodePipelineClient::enable_stage_transition)
+            .match_requests(|req| {
+                req.pipeline_name() == Some("release") && req.stage_name() == Some("Build")
+            })
+            .then_output(|| EnableStageTransitionOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_codepipeline, RuleMode::MatchAny, [&state, &enable]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(enable.num_calls(), 1);
+    }
+}
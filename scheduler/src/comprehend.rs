@@ -0,0 +1,667 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_comprehend::types::{EndpointProperties, EndpointStatus, Tag};
+use aws_sdk_comprehend::Client as ComprehendClient;
+use tracing::{error, info, warn};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::ResourceFilter;
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Prefix of the model tags recording a deleted endpoint, followed by the endpoint name.
+const ENDPOINT_TAG_PREFIX: &str = "scheduler:comprehend-endpoint:";
+/// Infix of the model tags holding the tags of a deleted endpoint, followed by the tag key.
+const ENDPOINT_TAG_INFIX: &str = ":tag:";
+
+/// Maximum length of a Comprehend tag value.
+const MAX_TAG_VALUE_LENGTH: usize = 256;
+
+/// Stop/start handler for Amazon Comprehend custom endpoints in a given AWS region.
+///
+/// Endpoints cannot be stopped and are billed per second while provisioned,
+/// so they are deleted instead.
+/// On stop: records the inference units, data access role and tags of each endpoint
+/// in `scheduler:comprehend-endpoint:*` tags on its model, then deletes it.
+/// On start: re-creates the endpoints recorded on the models in the region whose
+/// recorded tags match the filter.
+///
+/// Comprehend resources are discovered with the Comprehend API and their tags
+/// matched directly.
+pub struct ComprehendScheduler {
+    comprehend: ComprehendClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+/// Settings of a deleted endpoint, as recorded on its model.
+#[derive(Debug)]
+struct EndpointRecord {
+    endpoint_name: String,
+    model_arn: String,
+    inference_units: i32,
+    data_access_role_arn: Option<String>,
+    tags: HashMap<String, String>,
+}
+
+impl ComprehendScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            comprehend: ComprehendClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// List the endpoints whose tags match the filter, along with their tags.
+    async fn list_endpoints(
+        &self,
+        filter: &ResourceFilter,
+    ) -> Result<Vec<(EndpointProperties, HashMap<String, String>)>> {
+        let mut endpoints = Vec::new();
+        let mut next_token: Option<String> = None;
+
+        loop {
+            let mut req = self.comprehend.list_endpoints();
+            if let Some(ref token) = next_token {
+                req = req.next_token(token);
+            }
+
+            let resp = req.send().await?;
+            for endpoint in resp.endpoint_properties_list() {
+                let Some(endpoint_arn) = endpoint.endpoint_arn() else {
+                    continue;
+                };
+                let tags = self.list_tags(endpoint_arn).await?;
+                if filter.matches(
+                    endpoint_arn,
+                    &extract_endpoint_name(endpoint_arn),
+                    tags.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                ) {
+                    endpoints.push((endpoint.clone(), tags));
+                }
+            }
+
+            match resp.next_token() {
+                Some(token) if !token.is_empty() => next_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        Ok(endpoints)
+    }
+
+    /// List the ARNs of the document classifiers and entity recognizers in the region.
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let mut models = Vec::new();
+
+        let mut next_token: Option<String> = None;
+        loop {
+            let mut req = self.comprehend.list_document_classifiers();
+            if let Some(ref token) = next_token {
+                req = req.next_token(token);
+            }
+
+            let resp = req.send().await?;
+            models.extend(
+                resp.document_classifier_properties_list()
+                    .iter()
+                    .filter_map(|c| c.document_classifier_arn().map(str::to_string)),
+            );
+
+            match resp.next_token() {
+                Some(token) if !token.is_empty() => next_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        let mut next_token: Option<String> = None;
+        loop {
+            let mut req = self.comprehend.list_entity_recognizers();
+            if let Some(ref token) = next_token {
+                req = req.next_token(token);
+            }
+
+            let resp = req.send().await?;
+            models.extend(
+                resp.entity_recognizer_properties_list()
+                    .iter()
+                    .filter_map(|r| r.entity_recognizer_arn().map(str::to_string)),
+            );
+
+            match resp.next_token() {
+                Some(token) if !token.is_empty() => next_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        Ok(models)
+    }
+
+    async fn list_tags(&self, arn: &str) -> Result<HashMap<String, String>> {
+        let resp = self
+            .comprehend
+            .list_tags_for_resource()
+            .resource_arn(arn)
+            .send()
+            .await?;
+
+        Ok(resp
+            .tags()
+            .iter()
+            .map(|t| {
+                (
+                    t.key().to_string(),
+                    t.value().unwrap_or_default().to_string(),
+                )
+            })
+            .collect())
+    }
+
+    /// Record the settings of an endpoint on its model, then delete it.
+    async fn record_and_delete(
+        &self,
+        endpoint: &EndpointProperties,
+        tags: &HashMap<String, String>,
+    ) -> Result<Outcome> {
+        let endpoint_arn = endpoint.endpoint_arn().unwrap_or_default();
+        if endpoint.status() != Some(&EndpointStatus::InService) {
+            info!(endpoint = %endpoint_arn, status = ?endpoint.status(), "Comprehend endpoint not in service, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if endpoint.flywheel_arn().is_some() {
+            warn!(endpoint = %endpoint_arn, "Comprehend endpoint is attached to a flywheel, it will be re-created from its current model only");
+        }
+
+        let record = EndpointRecord::from_endpoint(endpoint, tags)?;
+        let model_tags = record.model_tags()?;
+        if self.dry_run {
+            info!(endpoint = %endpoint_arn, units = record.inference_units, "Dry run: would delete Comprehend endpoint");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(endpoint = %endpoint_arn, model = %record.model_arn, units = record.inference_units, "Recording Comprehend endpoint settings on its model");
+        self.comprehend
+            .tag_resource()
+            .resource_arn(&record.model_arn)
+            .set_tags(Some(model_tags))
+            .send()
+            .await?;
+
+        info!(endpoint = %endpoint_arn, "Deleting Comprehend endpoint");
+        self.comprehend
+            .delete_endpoint()
+            .endpoint_arn(endpoint_arn)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Re-create an endpoint from its record, then remove the record from the model.
+    async fn recreate(&self, record: &EndpointRecord) -> Result<Outcome> {
+        if self.dry_run {
+            info!(endpoint = %record.endpoint_name, model = %record.model_arn, units = record.inference_units, "Dry run: would re-create Comprehend endpoint");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(endpoint = %record.endpoint_name, model = %record.model_arn, units = record.inference_units, "Re-creating Comprehend endpoint");
+        let tags = record
+            .tags
+            .iter()
+            .map(|(k, v)| comprehend_tag(k, v))
+            .collect::<Result<Vec<_>>>()?;
+        self.comprehend
+            .create_endpoint()
+            .endpoint_name(&record.endpoint_name)
+            .model_arn(&record.model_arn)
+            .desired_inference_units(record.inference_units)
+            .set_data_access_role_arn(record.data_access_role_arn.clone())
+            .set_tags(Some(tags))
+            .send()
+            .await?;
+
+        let record_keys: Vec<String> = std::iter::once(record.key())
+            .chain(record.tags.keys().map(|k| record.tag_key(k)))
+            .collect();
+        self.comprehend
+            .untag_resource()
+            .resource_arn(&record.model_arn)
+            .set_tag_keys(Some(record_keys))
+            .send()
+            .await?;
+
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for ComprehendScheduler {
+    fn name(&self) -> &'static str {
+        "comprehend"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let endpoints = self.list_endpoints(filter).await?;
+        info!(
+            count = endpoints.len(),
+            "Found Comprehend endpoints to stop"
+        );
+
+        filter.check_discovered(endpoints.len())?;
+        let mut counts = ResourceCounts::new(endpoints.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &endpoints,
+            |(endpoint, tags)| async move {
+                let endpoint_arn = endpoint.endpoint_arn().unwrap_or_default();
+                let result = self.record_and_delete(endpoint, tags).await;
+                (endpoint_arn, result)
+            },
+        )
+        .await;
+        for (endpoint_arn, result) in results {
+            match result {
+                Ok(outcome) => counts.record(endpoint_arn, outcome),
+                Err(e) => {
+                    counts.fail(endpoint_arn, &e);
+                    error!(endpoint = %endpoint_arn, error = %e, "Failed to stop Comprehend endpoint");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let mut records = Vec::new();
+        for model_arn in self.list_models().await? {
+            let tags = self.list_tags(&model_arn).await?;
+            records.extend(
+                EndpointRecord::from_model_tags(&model_arn, &tags)
+                    .into_iter()
+                    .filter(|r| {
+                        filter.matches(
+                            &r.endpoint_arn(),
+                            &r.endpoint_name,
+                            r.tags.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                        )
+                    }),
+            );
+        }
+        info!(
+            count = records.len(),
+            "Found Comprehend endpoints to re-create"
+        );
+
+        filter.check_discovered(records.len())?;
+        let mut counts = ResourceCounts::new(records.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &records,
+            |record| async move {
+                let result = self.recreate(record).await;
+                (record, result)
+            },
+        )
+        .await;
+        for (record, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&record.endpoint_name, outcome),
+                Err(e) => {
+                    counts.fail(&record.endpoint_name, &e);
+                    error!(endpoint = %record.endpoint_name, error = %e, "Failed to re-create Comprehend endpoint");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the Comprehend endpoints matching the given filter with their current status,
+    /// including the endpoints deleted on stop, reported as `DELETED`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let mut items: Vec<InventoryItem> = self
+            .list_endpoints(filter)
+            .await?
+            .into_iter()
+            .filter_map(|(endpoint, _)| {
+                let status = endpoint.status().map(|s| s.as_str()).unwrap_or("UNKNOWN");
+                Some(InventoryItem::new(
+                    endpoint.endpoint_arn()?,
+                    "comprehend:endpoint",
+                    status,
+                ))
+            })
+            .collect();
+
+        for model_arn in self.list_models().await? {
+            let tags = self.list_tags(&model_arn).await?;
+            items.extend(
+                EndpointRecord::from_model_tags(&model_arn, &tags)
+                    .into_iter()
+                    .filter(|r| {
+                        filter.matches(
+                            &r.endpoint_arn(),
+                            &r.endpoint_name,
+                            r.tags.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                        )
+                    })
+                    .map(|r| {
+                        InventoryItem::new(r.endpoint_arn(), "comprehend:endpoint", "DELETED")
+                    }),
+            );
+        }
+        Ok(items)
+    }
+}
+
+impl EndpointRecord {
+    fn from_endpoint(
+        endpoint: &EndpointProperties,
+        tags: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let endpoint_arn = endpoint.endpoint_arn().unwrap_or_default();
+
+        Ok(Self {
+            endpoint_name: extract_endpoint_name(endpoint_arn),
+            model_arn: endpoint
+                .model_arn()
+                .with_context(|| format!("Comprehend endpoint '{}' has no model", endpoint_arn))?
+                .to_string(),
+            inference_units: endpoint
+                .desired_inference_units()
+                .or(endpoint.current_inference_units())
+                .with_context(|| {
+                    format!(
+                        "Comprehend endpoint '{}' has no inference units",
+                        endpoint_arn
+                    )
+                })?,
+            data_access_role_arn: endpoint.data_access_role_arn().map(str::to_string),
+            tags: tags
+                .iter()
+                .filter(|(k, _)| !k.starts_with("aws:"))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        })
+    }
+
+    /// Read the endpoint records from the tags of a model.
+    ///
+    /// Settings are recorded as `units=1 role=arn` under
+    /// `scheduler:comprehend-endpoint:<endpoint-name>`, and the endpoint tags under
+    /// `scheduler:comprehend-endpoint:<endpoint-name>:tag:<key>`.
+    fn from_model_tags(model_arn: &str, tags: &HashMap<String, String>) -> Vec<Self> {
+        let mut records: Vec<Self> = tags
+            .iter()
+            .filter_map(|(key, value)| {
+                let endpoint_name = key.strip_prefix(ENDPOINT_TAG_PREFIX)?;
+                if endpoint_name.contains(ENDPOINT_TAG_INFIX) {
+                    return None;
+                }
+                let settings: HashMap<&str, &str> = value.split_whitespace().filter_map(|s| s.split_once('=')).collect();
+                let Some(inference_units) = settings.get("units").and_then(|v| v.parse::<i32>().ok()) else {
+                    warn!(model = %model_arn, endpoint = %endpoint_name, "Invalid Comprehend endpoint record, ignoring");
+                    return None;
+                };
+                Some(Self {
+                    endpoint_name: endpoint_name.to_string(),
+                    model_arn: model_arn.to_string(),
+                    inference_units,
+                    data_access_role_arn: settings.get("role").map(|v| v.to_string()),
+                    tags: HashMap::new(),
+                })
+            })
+            .collect();
+
+        for record in &mut records {
+            let prefix = record.tag_key("");
+            record.tags = tags
+                .iter()
+                .filter_map(|(k, v)| Some((k.strip_prefix(&prefix)?.to_string(), v.clone())))
+                .collect();
+        }
+        records
+    }
+
+    /// ARN of the endpoint, derived from the ARN of its model.
+    ///
+    /// e.g. `arn:aws:comprehend:region:account:document-classifier/name` gives
+    /// `arn:aws:comprehend:region:account:document-classifier-endpoint/<endpoint-name>`.
+    fn endpoint_arn(&self) -> String {
+        let model_type = self.model_arn.split('/').next().unwrap_or(&self.model_arn);
+        format!("{}-endpoint/{}", model_type, self.endpoint_name)
+    }
+
+    /// Key of the model tag holding the settings of the endpoint.
+    fn key(&self) -> String {
+        format!("{}{}", ENDPOINT_TAG_PREFIX, self.endpoint_name)
+    }
+
+    /// Key of the model tag holding one of the endpoint's own tags.
+    fn tag_key(&self, key: &str) -> String {
+        format!("{}{}{}", self.key(), ENDPOINT_TAG_INFIX, key)
+    }
+
+    /// Build the model tags recording the endpoint.
+    fn model_tags(&self) -> Result<Vec<Tag>> {
+        let mut settings = format!("units={}", self.inference_units);
+        if let Some(role) = &self.data_access_role_arn {
+            settings.push_str(&format!(" role={}", role));
+        }
+        if settings.len() > MAX_TAG_VALUE_LENGTH {
+            bail!(
+                "Comprehend endpoint '{}' has a data access role ARN too long to be recorded in a tag",
+                self.endpoint_name
+            );
+        }
+
+        let mut tags = vec![comprehend_tag(&self.key(), &settings)?];
+        for (k, v) in &self.tags {
+            tags.push(comprehend_tag(&self.tag_key(k), v)?);
+        }
+        Ok(tags)
+    }
+}
+
+fn comprehend_tag(key: &str, value: &str) -> Result<Tag> {
+    Ok(Tag::builder().key(key).value(value).build()?)
+}
+
+/// Extract the endpoint name from a Comprehend endpoint ARN.
+///
+/// Expected ARN formats:
+/// - `arn:partition:comprehend:region:account:document-classifier-endpoint/endpoint-name`
+/// - `arn:partition:comprehend:region:account:entity-recognizer-endpoint/endpoint-name`
+fn extract_endpoint_name(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_comprehend::operation::create_endpoint::CreateEndpointOutput;
+    use aws_sdk_comprehend::operation::delete_endpoint::DeleteEndpointOutput;
+    use aws_sdk_comprehend::operation::list_document_classifiers::ListDocumentClassifiersOutput;
+    use aws_sdk_comprehend::operation::list_endpoints::ListEndpointsOutput;
+    use aws_sdk_comprehend::operation::list_entity_recognizers::ListEntityRecognizersOutput;
+    use aws_sdk_comprehend::operation::list_tags_for_resource::ListTagsForResourceOutput;
+    use aws_sdk_comprehend::operation::tag_resource::TagResourceOutput;
+    use aws_sdk_comprehend::operation::untag_resource::UntagResourceOutput;
+    use aws_sdk_comprehend::types::DocumentClassifierProperties;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, TAG_KEY, TAG_VALUE};
+
+    const ENDPOINT_ARN_PREFIX: &str =
+        "arn:aws:comprehend:eu-west-1:123456789012:document-classifier-endpoint/";
+    const MODEL_ARN: &str =
+        "arn:aws:comprehend:eu-west-1:123456789012:document-classifier/support-tickets";
+    const ROLE_ARN: &str = "arn:aws:iam::123456789012:role/comprehend-data-access";
+
+    fn endpoint(name: &str, status: EndpointStatus) -> EndpointProperties {
+        EndpointProperties::builder()
+            .endpoint_arn(format!("{ENDPOINT_ARN_PREFIX}{name}"))
+            .status(status)
+            .model_arn(MODEL_ARN)
+            .desired_inference_units(2)
+            .current_inference_units(2)
+            .data_access_role_arn(ROLE_ARN)
+            .build()
+    }
+
+    /// Rule answering the tags of the resource `arn`.
+    fn list_tags_rule(arn: String, tags: &'static [(&'static str, &'static str)]) -> Rule {
+        mock!(ComprehendClient::list_tags_for_resource)
+            .match_requests(move |req| req.resource_arn() == Some(arn.as_str()))
+            .then_output(move || {
+                let tags = tags.iter().map(|(k, v)| comprehend_tag(k, v).unwrap());
+                ListTagsForResourceOutput::builder()
+                    .set_tags(Some(tags.collect()))
+                    .build()
+            })
+    }
+
+    fn scheduler(comprehend: ComprehendClient) -> ComprehendScheduler {
+        ComprehendScheduler {
+            comprehend,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_records_endpoints_in_service_on_their_model_then_deletes_them() {
+        let endpoints = mock!(ComprehendClient::list_endpoints).then_output(|| {
+            ListEndpointsOutput::builder()
+                .endpoint_properties_list(endpoint("tickets", EndpointStatus::InService))
+                .endpoint_properties_list(endpoint("tickets-canary", EndpointStatus::Creating))
+                .endpoint_properties_list(endpoint("tickets-prod", EndpointStatus::InService))
+                .build()
+        });
+        let tickets = list_tags_rule(
+            format!("{ENDPOINT_ARN_PREFIX}tickets"),
+            &[(TAG_KEY, TAG_VALUE), ("team", "support")],
+        );
+        let canary = list_tags_rule(
+            format!("{ENDPOINT_ARN_PREFIX}tickets-canary"),
+            &[(TAG_KEY, TAG_VALUE)],
+        );
+        let prod = list_tags_rule(
+            format!("{ENDPOINT_ARN_PREFIX}tickets-prod"),
+            &[(TAG_KEY, "production")],
+        );
+        let record = mock!(ComprehendClient::tag_resource)
+            .match_requests(|req| {
+                let tags: HashMap<&str, &str> = req
+                    .tags()
+                    .iter()
+                    .map(|t| (t.key(), t.value().unwrap_or_default()))
+                    .collect();
+                req.resource_arn() == Some(MODEL_ARN)
+                    && tags == HashMap::from([
+                        (
+                            "scheduler:comprehend-endpoint:tickets",
+                            "units=2 role=arn:aws:iam::123456789012:role/comprehend-data-access",
+                        ),
+                        ("scheduler:comprehend-endpoint:tickets:tag:env", TAG_VALUE),
+                        ("scheduler:comprehend-endpoint:tickets:tag:team", "support"),
+                    ])
+            })
+            .then_output(|| TagResourceOutput::builder().build());
+        let delete = mock!(ComprehendClient::delete_endpoint)
+            .match_requests(|req| {
+                req.endpoint_arn() == Some(format!("{ENDPOINT_ARN_PREFIX}tickets").as_str())
+            })
+            .then_output(|| DeleteEndpointOutput::builder().build());
+        let scheduler = scheduler(mock_client!(
+            aws_sdk_comprehend,
+            RuleMode::MatchAny,
+            [&endpoints, &tickets, &canary, &prod, &record, &delete]
+        ));
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(record.num_calls(), 1);
+        assert_eq!(delete.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_recreates_the_endpoints_recorded_on_models() {
+        let classifiers = mock!(ComprehendClient::list_document_classifiers).then_output(|| {
+            ListDocumentClassifiersOutput::builder()
+                .document_classifier_properties_list(
+                    DocumentClassifierProperties::builder()
+                        .document_classifier_arn(MODEL_ARN)
+                        .build(),
+                )
+                .build()
+        });
+        let recognizers = mock!(ComprehendClient::list_entity_recognizers)
+            .then_output(|| ListEntityRecognizersOutput::builder().build());
+        let model_tags = list_tags_rule(
+            MODEL_ARN.to_string(),
+            &[
+                ("scheduler:comprehend-endpoint:tickets", "units=2"),
+                ("scheduler:comprehend-endpoint:tickets:tag:env", TAG_VALUE),
+                ("scheduler:comprehend-endpoint:tickets:tag:team", "support"),
+            ],
+        );
+        let create = mock!(ComprehendClient::create_endpoint)
+            .match_requests(|req| {
+                let tags: HashMap<&str, &str> = req
+                    .tags()
+                    .iter()
+                    .map(|t| (t.key(), t.value().unwrap_or_default()))
+                    .collect();
+                req.endpoint_name() == Some("tickets")
+                    && req.model_arn() == Some(MODEL_ARN)
+                    && req.desired_inference_units() == Some(2)
+                    && req.data_access_role_arn().is_none()
+                    && tags == HashMap::from([(TAG_KEY, TAG_VALUE), ("team", "support")])
+            })
+            .then_output(|| CreateEndpointOutput::builder().build());
+        let cleanup = mock!(ComprehendClient::untag_resource)
+            .match_requests(|req| {
+                req.resource_arn() == Some(MODEL_ARN) && req.tag_keys().len() == 3
+            })
+            .then_output(|| UntagResourceOutput::builder().build());
+        let scheduler = scheduler(mock_client!(
+            aws_sdk_comprehend,
+            RuleMode::MatchAny,
+            [&classifiers, &recognizers, &model_tags, &create, &cleanup]
+        ));
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(create.num_calls(), 1);
+        assert_eq!(cleanup.num_calls(), 1);
+    }
+}
@@ -0,0 +1,36 @@
+use std::future::Future;
+
+use futures::future::join_all;
+use tokio::sync::Semaphore;
+
+use crate::shutdown;
+
+/// Run `operation` on every item, with at most `limit` operations in flight at once.
+///
+/// Results are returned in the order of `items`, so that callers can record
+/// and log them exactly as they would in a sequential loop.
+///
+/// Once a shutdown is requested, the items not started yet are left out of the results.
+pub async fn run_bounded<I, F, Fut>(limit: usize, items: I, operation: F) -> Vec<Fut::Output>
+where
+    I: IntoIterator,
+    F: Fn(I::Item) -> Fut,
+    Fut: Future,
+{
+    let semaphore = Semaphore::new(limit.max(1));
+    let semaphore = &semaphore;
+    let operation = &operation;
+
+    join_all(items.into_iter().map(|item| async move {
+        // The semaphore is never closed, so acquiring a permit cannot fail.
+        let _permit = semaphore.acquire().await.expect("semaphore closed");
+        if shutdown::requested() {
+            return None;
+        }
+        Some(operation(item).await)
+    }))
+    .await
+    .into_iter()
+    .flatten()
+    .collect()
+}
@@ -1,12 +1,266 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Weekday;
+use chrono_tz::Tz;
+use cron::Schedule;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
 
-/// Read a boolean from an environment variable (case-insensitive "true"/"false").
-/// Returns `default` when the variable is not set.
-fn env_bool(name: &str, default: bool) -> bool {
-    env::var(name)
-        .map(|v| v.to_lowercase() == "true")
-        .unwrap_or(default)
+use crate::appconfig;
+use crate::calendar::DateSpec;
+use crate::window::parse_days;
+
+/// Configuration values, read from environment variables and, when `CONFIG_FILE`
+/// is set, from a YAML or TOML file. Environment variables override file values.
+///
+/// File keys are named after the environment variables, in any case. Nested
+/// sections are flattened by joining their keys with `_`, so that
+/// `ec2: { schedule: true, stop_mode: hibernate }` sets `EC2_SCHEDULE` and
+/// `EC2_STOP_MODE`. Lists are joined with `,`.
+///
+/// When `CONFIG_SSM_PREFIX` is set and the configuration is loaded with
+/// [`AppConfig::load`], the parameters under that SSM Parameter Store path come
+/// between the two: environment variables override them, and they override file values.
+/// The options of the AWS AppConfig profile of `APPCONFIG_APPLICATION` come between
+/// environment variables and SSM.
+///
+/// Overrides (e.g. from a Lambda event) take precedence over all of them.
+struct Vars {
+    overrides: HashMap<String, String>,
+    appconfig: HashMap<String, String>,
+    ssm: HashMap<String, String>,
+    file: HashMap<String, String>,
+}
+
+impl Vars {
+    /// Load the file pointed to by `CONFIG_FILE`, if any.
+    fn load(overrides: HashMap<String, String>) -> Result<Self> {
+        let file = match env::var("CONFIG_FILE") {
+            Ok(path) => read_config_file(&path)?,
+            Err(_) => HashMap::new(),
+        };
+        Ok(Self {
+            overrides,
+            appconfig: HashMap::new(),
+            ssm: HashMap::new(),
+            file,
+        })
+    }
+
+    /// Load the parameters under the SSM Parameter Store path of `CONFIG_SSM_PREFIX`, if set.
+    ///
+    /// Parameter names are relative to the prefix, with `/` standing for `_` like
+    /// nested file sections: `/scheduler/prod/ec2/schedule` sets `EC2_SCHEDULE`.
+    /// SecureString parameters are decrypted. SSM is called with the ambient
+    /// credentials and region.
+    async fn load_ssm(&mut self) -> Result<()> {
+        let Ok(prefix) = self.var("CONFIG_SSM_PREFIX") else {
+            return Ok(());
+        };
+        let prefix = format!("/{}", prefix.trim().trim_matches('/'));
+        let sdk_config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        let ssm = aws_sdk_ssm::Client::new(&sdk_config);
+
+        let mut pages = ssm
+            .get_parameters_by_path()
+            .path(&prefix)
+            .recursive(true)
+            .with_decryption(true)
+            .into_paginator()
+            .send();
+        while let Some(page) = pages.next().await {
+            let page = page.with_context(|| {
+                format!(
+                    "Failed to read SSM parameters under CONFIG_SSM_PREFIX '{}'",
+                    prefix
+                )
+            })?;
+            for parameter in page.parameters() {
+                let (Some(name), Some(value)) = (parameter.name(), parameter.value()) else {
+                    continue;
+                };
+                let name = name
+                    .strip_prefix(&prefix)
+                    .unwrap_or(name)
+                    .trim_matches('/')
+                    .replace(['/', '-'], "_")
+                    .to_uppercase();
+                self.ssm.insert(name, value.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a value, from the overrides first, then the environment, then AppConfig,
+    /// then SSM, then the file.
+    fn var(&self, name: &str) -> Result<String, env::VarError> {
+        if let Some(value) = self.overrides.get(name) {
+            return Ok(value.clone());
+        }
+        env::var(name).or_else(|e| {
+            self.appconfig
+                .get(name)
+                .or_else(|| self.ssm.get(name))
+                .or_else(|| self.file.get(name))
+                .cloned()
+                .ok_or(e)
+        })
+    }
+
+    /// Read a boolean (case-insensitive "true"/"false").
+    /// Returns `default` when the variable is not set.
+    fn bool(&self, name: &str, default: bool) -> bool {
+        self.var(name)
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(default)
+    }
+
+    /// Read a positive integer.
+    /// Returns `default` when the variable is not set.
+    fn usize(&self, name: &str, default: usize) -> Result<usize> {
+        match self.var(name) {
+            Ok(v) => match v.trim().parse::<usize>() {
+                Ok(n) if n > 0 => Ok(n),
+                _ => bail!("Invalid {} '{}': must be a positive integer", name, v),
+            },
+            Err(_) => Ok(default),
+        }
+    }
+
+    /// Read a non-negative integer.
+    /// Returns `default` when the variable is not set.
+    fn u64(&self, name: &str, default: u64) -> Result<u64> {
+        match self.var(name) {
+            Ok(v) => v
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| anyhow!("Invalid {} '{}': must be a non-negative integer", name, v)),
+            Err(_) => Ok(default),
+        }
+    }
+
+    /// Read the AWS AppConfig profile of `APPCONFIG_APPLICATION`, `APPCONFIG_ENVIRONMENT`
+    /// and `APPCONFIG_PROFILE`. Returns `None` when `APPCONFIG_APPLICATION` is not set.
+    fn appconfig_source(&self) -> Result<Option<AppConfigSource>> {
+        let Some(application) = self
+            .var("APPCONFIG_APPLICATION")
+            .ok()
+            .filter(|v| !v.is_empty())
+        else {
+            return Ok(None);
+        };
+        let environment = self
+            .var("APPCONFIG_ENVIRONMENT")
+            .context("APPCONFIG_ENVIRONMENT is required when APPCONFIG_APPLICATION is set")?;
+        let profile = self
+            .var("APPCONFIG_PROFILE")
+            .context("APPCONFIG_PROFILE is required when APPCONFIG_APPLICATION is set")?;
+        let poll_interval = self.u64("APPCONFIG_POLL_INTERVAL_SECONDS", 60)?;
+        // Bounds of the minimum poll interval of an AppConfig session.
+        if !(15..=86400).contains(&poll_interval) {
+            bail!(
+                "Invalid APPCONFIG_POLL_INTERVAL_SECONDS '{}': must be between 15 and 86400",
+                poll_interval
+            );
+        }
+        Ok(Some(AppConfigSource {
+            application,
+            environment,
+            profile,
+            poll_interval: Duration::from_secs(poll_interval),
+        }))
+    }
+
+    /// Read a cron expression.
+    ///
+    /// Accepts the standard 5-field format (`min hour day month weekday`)
+    /// as well as the 6/7-field format with seconds (and years).
+    /// Returns `None` when the variable is not set.
+    fn cron(&self, name: &str) -> Result<Option<Schedule>> {
+        let Ok(expr) = self.var(name) else {
+            return Ok(None);
+        };
+        let expr = expr.trim();
+        let normalized = if expr.split_whitespace().count() == 5 {
+            format!("0 {}", expr)
+        } else {
+            expr.to_string()
+        };
+        let schedule = Schedule::from_str(&normalized)
+            .with_context(|| format!("Invalid {} '{}': not a valid cron expression", name, expr))?;
+        Ok(Some(schedule))
+    }
+}
+
+/// Flatten a configuration file into variables named like the environment variables.
+fn flatten(prefix: &str, value: &serde_json::Value, vars: &mut HashMap<String, String>) {
+    let value = match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                let key = key.to_uppercase().replace('-', "_");
+                let name = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}_{}", prefix, key)
+                };
+                flatten(&name, value, vars);
+            }
+            return;
+        }
+        serde_json::Value::Null => return,
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+        other => other.to_string(),
+    };
+    vars.insert(prefix.to_string(), value);
+}
+
+/// Read the options of a YAML or TOML configuration file, keyed by environment variable name.
+fn read_config_file(path: &str) -> Result<HashMap<String, String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read CONFIG_FILE '{}'", path))?;
+    let value: serde_json::Value = match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("yaml" | "yml") => serde_yaml::from_str(&content)
+            .with_context(|| format!("Invalid CONFIG_FILE '{}': not a valid YAML file", path))?,
+        Some("toml") => toml::from_str(&content)
+            .with_context(|| format!("Invalid CONFIG_FILE '{}': not a valid TOML file", path))?,
+        _ => bail!(
+            "Invalid CONFIG_FILE '{}': must be a .yaml, .yml or .toml file",
+            path
+        ),
+    };
+    if !value.is_object() {
+        bail!("Invalid CONFIG_FILE '{}': must hold a map of options", path);
+    }
+
+    let mut file = HashMap::new();
+    flatten("", &value, &mut file);
+    Ok(file)
+}
+
+/// Parse a YAML or JSON document holding options like `CONFIG_FILE`, such as the
+/// configuration deployed to an AWS AppConfig profile. An empty document holds no option.
+pub(crate) fn parse_options(content: &[u8]) -> Result<HashMap<String, String>> {
+    let mut options = HashMap::new();
+    match serde_yaml::from_slice::<serde_json::Value>(content)
+        .context("not a valid YAML or JSON document")?
+    {
+        serde_json::Value::Null => {}
+        value @ serde_json::Value::Object(_) => flatten("", &value, &mut options),
+        _ => bail!("must hold a map of options"),
+    }
+    Ok(options)
 }
 
 /// Action to perform on AWS resources.
@@ -16,6 +270,12 @@ pub enum ScheduleAction {
     Stop,
     /// Start the resources.
     Start,
+    /// Terminate the resources (EC2 instances and Auto Scaling Groups only).
+    Terminate,
+    /// List the matched resources with their current state, without changing them.
+    List,
+    /// Check that the matched resources are in the state implied by the most recent scheduled run.
+    Status,
 }
 
 impl std::fmt::Display for ScheduleAction {
@@ -23,111 +283,1583 @@ impl std::fmt::Display for ScheduleAction {
         match self {
             ScheduleAction::Stop => write!(f, "stop"),
             ScheduleAction::Start => write!(f, "start"),
+            ScheduleAction::Terminate => write!(f, "terminate"),
+            ScheduleAction::List => write!(f, "list"),
+            ScheduleAction::Status => write!(f, "status"),
+        }
+    }
+}
+
+/// Parse a `SCHEDULE_ACTION` value.
+fn parse_schedule_action(value: &str) -> Result<ScheduleAction> {
+    match value.to_lowercase().as_str() {
+        "stop" => Ok(ScheduleAction::Stop),
+        "start" => Ok(ScheduleAction::Start),
+        "terminate" => Ok(ScheduleAction::Terminate),
+        "list" => Ok(ScheduleAction::List),
+        "status" => Ok(ScheduleAction::Status),
+        other => bail!(
+            "Invalid SCHEDULE_ACTION '{}': must be 'stop', 'start', 'terminate', 'list' or 'status'",
+            other
+        ),
+    }
+}
+
+/// Parse the value of `name` (`SERVICE_TIMEOUT_SECONDS`, `SERVICE_WAIT_TIMEOUT_SECONDS`),
+/// e.g. `ec2=600,autoscaling=900`.
+fn parse_service_timeouts(name: &str, value: &str) -> Result<HashMap<String, u64>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|entry| {
+            let (service, seconds) = entry.split_once('=').with_context(|| {
+                format!(
+                    "Invalid {} entry '{}': must be 'service=seconds'",
+                    name, entry
+                )
+            })?;
+            match seconds.trim().parse::<u64>() {
+                Ok(seconds) if seconds > 0 => Ok((service.trim().to_lowercase(), seconds)),
+                _ => bail!(
+                    "Invalid {} entry '{}': timeout must be a positive integer",
+                    name,
+                    entry
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Names of the services, as reported in the execution summary, whose regions
+/// can be overridden with `<SERVICE>_REGIONS` (e.g. `RDS_REGIONS`).
+const SERVICE_NAMES: [&str; 36] = [
+    "apprunner",
+    "appstream",
+    "autoscaling",
+    "client_vpn",
+    "cloud9",
+    "cloudfront",
+    "cloudwatch",
+    "codepipeline",
+    "comprehend",
+    "dlm",
+    "dms",
+    "documentdb",
+    "dynamodb",
+    "ec2",
+    "ec2_fleet",
+    "ecs",
+    "eks",
+    "elasticache",
+    "emr_serverless",
+    "eventbridge",
+    "flink",
+    "gamelift",
+    "lambda",
+    "lightsail",
+    "mwaa",
+    "nat_gateway",
+    "opensearch",
+    "rds",
+    "redshift",
+    "redshift_serverless",
+    "route53_healthcheck",
+    "sagemaker",
+    "ssm_maintenance_window",
+    "transfer",
+    "vpc_endpoints",
+    "workspaces",
+];
+
+/// Services whose resources are global (managed from `us-east-1` whatever the region),
+/// processed in a single region so that each resource is acted upon once.
+const GLOBAL_SERVICES: [&str; 2] = ["cloudfront", "route53_healthcheck"];
+
+/// Order services are started in (`SERVICE_ORDER`), stopped in reverse.
+///
+/// Services are listed in groups separated by barriers: each group but the last
+/// one is waited for until its resources reach the target state before the next
+/// group is processed. Services left out are processed in registration order,
+/// after the ordered ones on start and before them on stop.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceOrder {
+    groups: Vec<Vec<String>>,
+}
+
+impl ServiceOrder {
+    /// Parse a `SERVICE_ORDER` value: comma-separated services, with `|` between the
+    /// groups of a barrier (e.g. `rds,documentdb|ec2,ecs,apprunner`).
+    pub(crate) fn parse(value: &str) -> Result<Self> {
+        if value.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let mut seen = HashSet::new();
+        let groups = value
+            .split('|')
+            .map(|group| {
+                let services: Vec<String> = group
+                    .split(',')
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                if services.is_empty() {
+                    bail!("Invalid SERVICE_ORDER '{}': empty group of services", value);
+                }
+                for service in &services {
+                    if !SERVICE_NAMES.contains(&service.as_str()) {
+                        bail!(
+                            "Invalid SERVICE_ORDER '{}': unknown service '{}'",
+                            value,
+                            service
+                        );
+                    }
+                    if !seen.insert(service.clone()) {
+                        bail!(
+                            "Invalid SERVICE_ORDER '{}': service '{}' listed twice",
+                            value,
+                            service
+                        );
+                    }
+                }
+                Ok(services)
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { groups })
+    }
+
+    /// Groups of the ordered services for `action`: as configured on start, in
+    /// reverse (groups and services within them) on stop.
+    pub fn groups(&self, action: &ScheduleAction) -> Vec<Vec<&str>> {
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| group.iter().map(String::as_str).collect::<Vec<_>>());
+        match action {
+            ScheduleAction::Stop => groups
+                .rev()
+                .map(|mut group| {
+                    group.reverse();
+                    group
+                })
+                .collect(),
+            _ => groups.collect(),
+        }
+    }
+}
+
+/// Parse comma-separated tag values; `*` or no value matches any value, and yields no value.
+fn parse_tag_values(value: &str) -> Vec<String> {
+    let values: Vec<String> = value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if values.iter().any(|v| v == "*") {
+        return Vec::new();
+    }
+    values
+}
+
+/// Parse the tag values of `STOP_TAG_VALUE` or `START_TAG_VALUE`, if set.
+fn parse_action_tag_values(vars: &Vars, name: &str) -> Result<Option<Vec<String>>> {
+    let Ok(value) = vars.var(name) else {
+        return Ok(None);
+    };
+    if value.trim().is_empty() {
+        bail!("{} must contain at least one value, or '*'", name);
+    }
+    Ok(Some(parse_tag_values(&value)))
+}
+
+/// Parse a resource name regular expression, anchored so that it must match the whole name.
+fn parse_name_regex(vars: &Vars, name: &str) -> Result<Option<Regex>> {
+    match vars.var(name).ok().filter(|v| !v.trim().is_empty()) {
+        Some(pattern) => Regex::new(&format!("^(?:{})$", pattern.trim()))
+            .map(Some)
+            .with_context(|| format!("Invalid {} '{}'", name, pattern)),
+        None => Ok(None),
+    }
+}
+
+/// Parse tag filters such as `PROTECTED_TAG_FILTERS`: comma-separated `key=value` tags,
+/// or `key` for any value.
+fn parse_tag_filters(vars: &Vars, name: &str) -> Result<Vec<(String, Option<String>)>> {
+    vars.var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|entry| {
+            let (key, value) = match entry.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim().to_string())),
+                None => (entry, None),
+            };
+            if key.is_empty() {
+                bail!(
+                    "Invalid {} entry '{}': must be 'key=value' or 'key'",
+                    name,
+                    entry
+                );
+            }
+            Ok((key.to_string(), value))
+        })
+        .collect()
+}
+
+/// Parse a comma-separated list of regions.
+fn parse_regions(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// How the scheduler is run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    /// Perform `SCHEDULE_ACTION` once, then exit.
+    Once,
+    /// Run continuously, performing stop/start actions on `STOP_CRON`/`START_CRON`.
+    Daemon,
+    /// Serve an HTTP API performing stop/start actions on request.
+    Server,
+}
+
+impl std::fmt::Display for RunMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunMode::Once => write!(f, "once"),
+            RunMode::Daemon => write!(f, "daemon"),
+            RunMode::Server => write!(f, "server"),
+        }
+    }
+}
+
+/// How EC2 instances are stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ec2StopMode {
+    /// Regular stop.
+    Stop,
+    /// Hibernate instances configured for hibernation, regular stop for the others.
+    Hibernate,
+}
+
+impl std::fmt::Display for Ec2StopMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ec2StopMode::Stop => write!(f, "stop"),
+            Ec2StopMode::Hibernate => write!(f, "hibernate"),
+        }
+    }
+}
+
+/// Payload format of the webhook receiving the summary of each run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// Slack incoming webhook message.
+    Slack,
+    /// Microsoft Teams incoming webhook message card.
+    Teams,
+    /// Execution summary as JSON.
+    Generic,
+}
+
+impl std::fmt::Display for WebhookFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookFormat::Slack => write!(f, "slack"),
+            WebhookFormat::Teams => write!(f, "teams"),
+            WebhookFormat::Generic => write!(f, "generic"),
+        }
+    }
+}
+
+/// Location of the audit trail or of the state store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreLocation {
+    /// DynamoDB table.
+    DynamoDb { table: String },
+    /// S3 bucket, under a key prefix.
+    S3 { bucket: String, prefix: String },
+}
+
+impl std::fmt::Display for StoreLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreLocation::DynamoDb { table } => write!(f, "dynamodb://{}", table),
+            StoreLocation::S3 { bucket, prefix } => write!(f, "s3://{}/{}", bucket, prefix),
+        }
+    }
+}
+
+/// Parse a store location (`AUDIT_STORE`, `STATE_STORE`): `dynamodb://table` or `s3://bucket[/prefix]`.
+fn parse_store_location(name: &str, value: &str) -> Result<StoreLocation> {
+    let value = value.trim();
+    if let Some(table) = value.strip_prefix("dynamodb://") {
+        if table.is_empty() || table.contains('/') {
+            bail!("Invalid {} '{}': must be 'dynamodb://table'", name, value);
+        }
+        return Ok(StoreLocation::DynamoDb {
+            table: table.to_string(),
+        });
+    }
+    if let Some(location) = value.strip_prefix("s3://") {
+        let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+        if bucket.is_empty() {
+            bail!("Invalid {} '{}': must be 's3://bucket/prefix'", name, value);
+        }
+        return Ok(StoreLocation::S3 {
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+        });
+    }
+    bail!(
+        "Invalid {} '{}': must be 'dynamodb://table' or 's3://bucket/prefix'",
+        name,
+        value
+    )
+}
+
+/// Hook run in each region before a stop (`PRE_STOP_HOOK`) or after a start (`POST_START_HOOK`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hook {
+    /// HTTPS endpoint receiving a POST of the event.
+    Webhook(String),
+    /// SSM Automation runbook, started once.
+    Automation(String),
+    /// SSM Run Command document, sent to the instances carrying the scheduler tag.
+    RunCommand(String),
+}
+
+impl std::fmt::Display for Hook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hook::Webhook(url) => write!(f, "{}", url),
+            Hook::Automation(document) => write!(f, "ssm-automation:{}", document),
+            Hook::RunCommand(document) => write!(f, "ssm-command:{}", document),
+        }
+    }
+}
+
+/// Parse a hook (`PRE_STOP_HOOK`, `POST_START_HOOK`): `https://...`,
+/// `ssm-automation:document` or `ssm-command:document`.
+fn parse_hook(name: &str, value: &str) -> Result<Hook> {
+    let value = value.trim();
+    if value.starts_with("https://") {
+        return Ok(Hook::Webhook(value.to_string()));
+    }
+    let document = |prefix: &str| {
+        value
+            .strip_prefix(prefix)
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+    };
+    if let Some(document) = document("ssm-automation:") {
+        return Ok(Hook::Automation(document.to_string()));
+    }
+    if let Some(document) = document("ssm-command:") {
+        return Ok(Hook::RunCommand(document.to_string()));
+    }
+    bail!(
+        "Invalid {} '{}': must be 'https://...', 'ssm-automation:document' or 'ssm-command:document'",
+        name,
+        value
+    )
+}
+
+/// Strategy used to stop and start Auto Scaling Groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoScalingMode {
+    /// Suspend the group processes, then stop its instances.
+    Suspend,
+    /// Record min/max/desired capacity in tags, then scale the group to 0.
+    ScaleToZero,
+}
+
+impl std::fmt::Display for AutoScalingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AutoScalingMode::Suspend => write!(f, "suspend"),
+            AutoScalingMode::ScaleToZero => write!(f, "scale-to-zero"),
+        }
+    }
+}
+
+/// What to do with the scheduled actions of Auto Scaling Groups scaled to zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduledActionsMode {
+    /// Leave scheduled actions untouched.
+    Keep,
+    /// Suspend the `ScheduledActions` process of the group.
+    Suspend,
+    /// Record scheduled actions in tags, then delete them.
+    Delete,
+}
+
+impl std::fmt::Display for ScheduledActionsMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScheduledActionsMode::Keep => write!(f, "keep"),
+            ScheduledActionsMode::Suspend => write!(f, "suspend"),
+            ScheduledActionsMode::Delete => write!(f, "delete"),
+        }
+    }
+}
+
+/// Strategy used to stop and start Aurora Serverless v2 clusters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerlessV2Mode {
+    /// Stop the cluster like any other Aurora cluster.
+    Stop,
+    /// Record min/max ACU in tags, then lower them to the minimum.
+    ScaleDown,
+}
+
+impl std::fmt::Display for ServerlessV2Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerlessV2Mode::Stop => write!(f, "stop"),
+            ServerlessV2Mode::ScaleDown => write!(f, "scale-down"),
+        }
+    }
+}
+
+/// Regions enumerated at startup when `AWS_REGIONS` is `all` or `all-opted-in`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionDiscovery {
+    /// Regions enabled by default in every account.
+    All,
+    /// Regions enabled by default, and the opt-in regions the account opted in to.
+    AllOptedIn,
+}
+
+impl std::fmt::Display for RegionDiscovery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegionDiscovery::All => write!(f, "all"),
+            RegionDiscovery::AllOptedIn => write!(f, "all-opted-in"),
         }
     }
 }
 
-/// Application configuration loaded from environment variables.
+/// AWS AppConfig configuration profile holding options, read at startup and polled
+/// for new deployments in daemon mode.
+#[derive(Debug, Clone)]
+pub struct AppConfigSource {
+    pub application: String,
+    pub environment: String,
+    pub profile: String,
+    /// Time between two polls of the profile in daemon mode.
+    pub poll_interval: Duration,
+}
+
+impl std::fmt::Display for AppConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}",
+            self.application, self.environment, self.profile
+        )
+    }
+}
+
+/// Application configuration loaded from environment variables and, optionally,
+/// from the YAML or TOML file pointed to by `CONFIG_FILE`.
 ///
 /// Required variables:
-/// - `SCHEDULE_ACTION`: `stop` or `start`
-/// - `AWS_REGIONS`: comma-separated list of AWS regions
+/// - `SCHEDULE_ACTION`: `stop`, `start`, `terminate`, `list` or `status` (not required when `RUN_MODE=daemon` or `server`)
+/// - `AWS_REGIONS`: comma-separated list of AWS regions, or `all`/`all-opted-in` to enumerate
+///   the enabled regions at startup
 /// - `TAG_KEY`: tag key to filter resources
-/// - `TAG_VALUE`: tag value to filter resources
 ///
 /// Optional variables (each defaults to `false` unless noted):
+/// - `CONFIG_FILE`: path to a YAML or TOML file holding the same options; environment variables override it
+/// - `CONFIG_SSM_PREFIX`: SSM Parameter Store path (e.g. `/scheduler/prod`) whose parameters hold the same options,
+///   read at startup; environment variables override them, and they override `CONFIG_FILE`
+/// - `APPCONFIG_APPLICATION`/`APPCONFIG_ENVIRONMENT`/`APPCONFIG_PROFILE`: AWS AppConfig configuration profile
+///   holding a YAML or JSON document of the same options, read at startup and, in daemon mode, polled so that
+///   new deployments apply to the next runs; environment variables override it, and it overrides SSM parameters
+/// - `APPCONFIG_POLL_INTERVAL_SECONDS`: time between two polls of the AppConfig profile in daemon mode (default: `60`)
+/// - `RUN_MODE`: `once` (default), `daemon` or `server`
+/// - `STOP_CRON`/`START_CRON`: cron expressions triggering stop/start in daemon mode
+///   (also required when `SCHEDULE_ACTION=status`, to find the most recent run)
+/// - `SCHEDULER_TIMEZONE`: IANA timezone used to evaluate cron expressions and excluded dates (default: `UTC`)
+/// - `TAG_VALUE`: comma-separated tag values to filter resources (any of them matches); any value when unset or `*`
+/// - `STOP_TAG_VALUE`/`START_TAG_VALUE`: comma-separated tag values replacing `TAG_VALUE` for the stop/start action
+/// - `STOP_TAG_FILTER`/`START_TAG_FILTER`: comma-separated `key=value` (or `key` for any value) tags a resource
+///   must carry, besides `TAG_KEY`, to be stopped/started (e.g. `autostart=true`)
+/// - `SCHEDULE_WINDOW_TAG`: tag key holding a per-resource schedule window (e.g. `Mon-Fri 08:00-19:00 Europe/Paris`)
+/// - `PRIORITY_TAG`: tag key holding the integer priority of a resource; within each service, resources are
+///   started in waves of ascending priority, those without one last, and stopped in reverse
+/// - `RESOURCE_NAME_INCLUDE_REGEX`/`RESOURCE_NAME_EXCLUDE_REGEX`: regular expressions the identifier of a
+///   resource in its ARN, without the resource type (e.g. the instance ID or DB name), must/must not fully match
+/// - `PROTECTED_TAG_FILTERS`: comma-separated `key=value` (or `key` for any value) tags of resources that are
+///   never acted upon, whatever the other filters (e.g. `env=prod`)
+/// - `EXCLUSION_TAG_KEY`/`EXCLUSION_TAG_VALUE`: tag opting individual resources out (any value when no value is set)
+/// - `TERMINATE_CONFIRM_TAG`: tag key that must be set to `true` on a resource for it to be terminated
+///   (required when `SCHEDULE_ACTION=terminate`)
+/// - `<SERVICE>_REGIONS`: comma-separated regions replacing `AWS_REGIONS` for one service, named as in the
+///   execution summary (e.g. `RDS_REGIONS`, `NAT_GATEWAY_REGIONS`)
+/// - `ASSUME_ROLE_ARNS`: comma-separated IAM role ARNs to assume, one per target account
+/// - `AWS_ENDPOINT_URL`: endpoint used by every AWS client instead of the AWS endpoints (e.g. LocalStack)
+/// - `AWS_USE_FIPS_ENDPOINT`: use the FIPS endpoints of the AWS services (e.g. in GovCloud)
+/// - `AWS_USE_DUALSTACK_ENDPOINT`: use the dual-stack (IPv4 and IPv6) endpoints of the AWS services
+/// - `AWS_PROFILE`: named profile of the shared AWS config and credentials files providing the credentials
 /// - `EC2_SCHEDULE`: enable EC2 processing (default: `true`)
+/// - `EC2_STOP_MODE`: `stop` (default) or `hibernate` (hibernation-enabled instances only)
+/// - `EC2_GRACEFUL_SHUTDOWN`: shut down the OS of the SSM-managed Linux instances through SSM Run Command
+///   (`AWS-RunShellScript`) before stopping them; instances that are hibernated are not shut down
+/// - `EC2_SHUTDOWN_COMMAND`: shell command of the graceful shutdown (default: `shutdown -h now`)
+/// - `EC2_SHUTDOWN_WAIT_SECONDS`: time the graceful shutdown is waited for before the instances are stopped (default: `60`)
 /// - `APPRUNNER_SCHEDULE`: enable App Runner processing
 /// - `AUTOSCALING_SCHEDULE`: enable Auto Scaling Group processing
+/// - `AUTOSCALING_MODE`: `suspend` (default) or `scale-to-zero`
+/// - `AUTOSCALING_DELETE_ON_TERMINATE`: delete Auto Scaling Groups on terminate instead of scaling them to 0
+/// - `AUTOSCALING_SCHEDULED_ACTIONS`: `keep` (default), `suspend` or `delete` scheduled actions of groups scaled to zero
 /// - `CLOUDWATCH_ALARM_SCHEDULE`: enable CloudWatch alarm processing
 /// - `DOCUMENTDB_SCHEDULE`: enable DocumentDB processing
 /// - `ECS_SCHEDULE`: enable ECS service processing
+/// - `ECS_DRAIN_CONTAINER_INSTANCES`: also drain tagged ECS container instances on stop (default: `false`)
 /// - `RDS_SCHEDULE`: enable RDS instance/cluster processing
+/// - `RDS_SERVERLESS_V2_MODE`: `stop` (default) or `scale-down` for Aurora Serverless v2 clusters
 /// - `REDSHIFT_SCHEDULE`: enable Redshift cluster processing
 /// - `TRANSFER_SCHEDULE`: enable Transfer Family server processing
-/// - `EXCLUDED_DATES`: comma-separated dates in `MM-DD` format to skip execution
+/// - `LAMBDA_SCHEDULE`: enable Lambda event source mapping processing
+/// - `SAGEMAKER_SCHEDULE`: enable SageMaker notebook instance processing
+/// - `EKS_SCHEDULE`: enable EKS managed node group processing
+/// - `REDSHIFT_SERVERLESS_SCHEDULE`: enable Redshift Serverless workgroup processing
+/// - `WORKSPACES_SCHEDULE`: enable WorkSpaces processing
+/// - `OPENSEARCH_SCHEDULE`: enable OpenSearch domain processing
+/// - `OPENSEARCH_STOP_INSTANCE_TYPE`: data node type used while stopped (default: `t3.small.search`)
+/// - `ELASTICACHE_SCHEDULE`: enable ElastiCache replication group processing (snapshot and delete)
+/// - `EMR_SERVERLESS_SCHEDULE`: enable EMR Serverless application processing
+/// - `MWAA_SCHEDULE`: enable MWAA environment processing
+/// - `EVENTBRIDGE_SCHEDULE`: enable EventBridge rule processing
+/// - `DMS_SCHEDULE`: enable DMS replication task/instance processing
+/// - `DMS_STOP_INSTANCE_CLASS`: replication instance class used while stopped (default: `dms.t3.micro`)
+/// - `NAT_GATEWAY_SCHEDULE`: enable NAT gateway processing
+/// - `APPSTREAM_SCHEDULE`: enable AppStream fleet processing
+/// - `LIGHTSAIL_SCHEDULE`: enable Lightsail instance/database processing
+/// - `FLINK_SCHEDULE`: enable Managed Flink application processing
+/// - `CLOUD9_SCHEDULE`: enable Cloud9 environment processing
+/// - `CLIENT_VPN_SCHEDULE`: enable Client VPN endpoint processing
+/// - `DYNAMODB_SCHEDULE`: enable DynamoDB provisioned table processing
+/// - `CODEPIPELINE_SCHEDULE`: enable CodePipeline transition processing
+/// - `GAMELIFT_SCHEDULE`: enable GameLift fleet processing
+/// - `VPC_ENDPOINTS_SCHEDULE`: enable VPC interface endpoint processing
+/// - `EC2_FLEET_SCHEDULE`: enable EC2 Fleet and Spot Fleet request processing
+/// - `CLOUDFRONT_SCHEDULE`: enable CloudFront distribution processing
+/// - `ROUTE53_HEALTHCHECK_SCHEDULE`: enable Route 53 health check processing
+/// - `COMPREHEND_SCHEDULE`: enable Amazon Comprehend endpoint processing
+/// - `SSM_MW_SCHEDULE`: enable SSM maintenance window processing
+/// - `DLM_SCHEDULE`: enable Data Lifecycle Manager policy processing
+/// - `EXCLUDED_DATES`: comma-separated days to skip execution (in `SCHEDULER_TIMEZONE`): `MM-DD`, `YYYY-MM-DD`,
+///   weekday names (`sat,sun`) or inclusive `from:to` ranges (`12-24:01-02`)
+/// - `EXCLUDED_DATES_ICAL_URL`: iCal feed (e.g. a holiday calendar) fetched at startup, whose event days are also excluded
+/// - `ACTIVE_DAYS`: days and day ranges the scheduler only runs on (e.g. `Mon-Fri`), every day when unset
+/// - `ACTIVE_DATE_RANGES`: comma-separated `from:to` ranges, in the same formats as `EXCLUDED_DATES`, the
+///   scheduler only runs within, always when unset
+/// - `MAX_CONCURRENT_REGIONS`: number of regions processed in parallel (default: `4`)
+/// - `MAX_CONCURRENT_OPERATIONS`: number of resources processed in parallel by each service (default: `10`)
+/// - `MAX_RESOURCES_PER_SERVICE`: number of discovered resources above which a service fails without acting (default: unlimited)
+/// - `EXECUTION_TIMEOUT_SECONDS`: time after which services still running are aborted and reported as failed
+/// - `SERVICE_TIMEOUT_SECONDS`: comma-separated `service=seconds` timeouts of individual services in each region (e.g. `autoscaling=900`)
+/// - `SERVICE_ORDER`: comma-separated services processed first on start and last on stop, in reverse, with `|`
+///   as a barrier waiting for the previous services to reach the target state (e.g. `rds,documentdb|ec2,ecs`)
+/// - `WAIT_FOR_COMPLETION`: after stop/start, wait for the resources of each service to reach the target state
+///   (e.g. `stopped`, `paused`, `OFFLINE`), reporting those that do not as failed
+/// - `WAIT_TIMEOUT_SECONDS`: time the resources of each service are waited for (default: `900`)
+/// - `SERVICE_WAIT_TIMEOUT_SECONDS`: comma-separated `service=seconds` wait timeouts of individual services (e.g. `rds=1800`)
+/// - `WAIT_POLL_INTERVAL_SECONDS`: time between two checks of the resource states while waiting (default: `15`)
+/// - `WAITER_MAX_ATTEMPTS`/`WAITER_DELAY_SECONDS`: attempts (default: `40`) and delay between them (default: `15`)
+///   of the waiters for started instances: Auto Scaling Group instances before resuming the group, EC2 and RDS
+///   instances with `WAIT_FOR_COMPLETION`
+/// - `PRE_STOP_HOOK`/`POST_START_HOOK`: hook run in each region before a stop/after a start: an HTTPS webhook
+///   (`https://...`), an SSM Automation runbook (`ssm-automation:document`) or an SSM Run Command document sent
+///   to the instances carrying the scheduler tag (`ssm-command:document`)
+/// - `HOOK_TIMEOUT_SECONDS`: time a hook is waited for before it is reported as failed (default: `300`)
+/// - `ABORT_ON_HOOK_FAILURE`: skip the stop of a region when its `PRE_STOP_HOOK` fails
+/// - `RETRY_MAX_ATTEMPTS`: attempts per AWS request, retrying throttling and transient errors with backoff (default: `5`)
+/// - `SNS_TOPIC_ARN`: SNS topic receiving the summary of each run
+/// - `WEBHOOK_URL`: webhook receiving the summary of each run
+/// - `WEBHOOK_FORMAT`: `slack`, `teams` or `generic` (default) payload posted to `WEBHOOK_URL`
+/// - `REPORT_EMAIL_TO`: comma-separated addresses receiving an HTML report of each run through SES
+/// - `REPORT_EMAIL_FROM`: SES verified sender of the report, required with `REPORT_EMAIL_TO`
+/// - `EVENT_BUS_NAME`: EventBridge event bus (name or ARN) receiving an event per processed resource
+/// - `ESTIMATE_SAVINGS`: estimate the hourly savings of the EC2 and RDS instances stopped by each stop run
+/// - `STATE_STORE`: `tags` (default), `dynamodb://table` or `s3://bucket/prefix` holding the state recorded on stop and restored on start
+/// - `AUDIT_STORE`: `dynamodb://table` or `s3://bucket/prefix` receiving an audit record per processed resource
+/// - `LOCK_TABLE`: DynamoDB table holding a lock that prevents overlapping runs of the same action and tag filter
+/// - `LOCK_TTL_SECONDS`: time after which a lock left by a killed run expires (default: `3600`)
+/// - `LOCK_WAIT_SECONDS`: time to wait for a lock held by another run before skipping the run (default: `0`)
+/// - `CLOUDWATCH_METRICS_NAMESPACE`: CloudWatch namespace receiving the metrics of each run
+/// - `EMF_NAMESPACE`: CloudWatch namespace of the Embedded Metric Format records written to stdout after each run
+/// - `METRICS_PORT`: port of a Prometheus `/metrics` listener in daemon mode (server mode serves `/metrics` on `SERVER_PORT`)
+/// - `PUSHGATEWAY_URL`: Prometheus Pushgateway receiving the metrics of each run
+/// - `SERVER_PORT`: port the HTTP API listens on in server mode (default: `8080`)
+/// - `SERVER_API_TOKEN`: bearer token required by the HTTP API, except for `/healthz`
+/// - `DRY_RUN`: only discover resources and log the actions that would be performed
 #[derive(Debug, Clone)]
 pub struct AppConfig {
+    pub run_mode: RunMode,
     pub schedule_action: ScheduleAction,
+    pub stop_cron: Option<Schedule>,
+    pub start_cron: Option<Schedule>,
+    pub timezone: Tz,
     pub aws_regions: Vec<String>,
+    pub region_discovery: Option<RegionDiscovery>,
+    pub service_regions: HashMap<String, Vec<String>>,
+    pub assume_role_arns: Vec<String>,
+    pub endpoint_url: Option<String>,
+    pub use_fips: bool,
+    pub use_dual_stack: bool,
+    pub aws_profile: Option<String>,
     pub tag_key: String,
-    pub tag_value: String,
+    pub tag_values: Vec<String>,
+    pub stop_tag_values: Option<Vec<String>>,
+    pub start_tag_values: Option<Vec<String>>,
+    pub stop_tag_filters: Vec<(String, Option<String>)>,
+    pub start_tag_filters: Vec<(String, Option<String>)>,
+    pub schedule_window_tag: Option<String>,
+    pub priority_tag: Option<String>,
+    pub name_include_regex: Option<Regex>,
+    pub name_exclude_regex: Option<Regex>,
+    pub protected_tags: Vec<(String, Option<String>)>,
+    pub exclusion_tag_key: Option<String>,
+    pub exclusion_tag_value: Option<String>,
+    pub terminate_confirm_tag: Option<String>,
     pub ec2_schedule: bool,
+    pub ec2_stop_mode: Ec2StopMode,
+    pub ec2_graceful_shutdown: bool,
+    pub ec2_shutdown_command: String,
+    pub ec2_shutdown_wait_seconds: u64,
     pub apprunner_schedule: bool,
     pub autoscaling_schedule: bool,
+    pub autoscaling_mode: AutoScalingMode,
+    pub autoscaling_delete_on_terminate: bool,
+    pub autoscaling_scheduled_actions: ScheduledActionsMode,
     pub cloudwatch_alarm_schedule: bool,
     pub documentdb_schedule: bool,
     pub ecs_schedule: bool,
+    pub ecs_drain_container_instances: bool,
     pub rds_schedule: bool,
+    pub rds_serverless_v2_mode: ServerlessV2Mode,
     pub redshift_schedule: bool,
     pub transfer_schedule: bool,
-    pub excluded_dates: Vec<String>,
+    pub lambda_schedule: bool,
+    pub sagemaker_schedule: bool,
+    pub eks_schedule: bool,
+    pub redshift_serverless_schedule: bool,
+    pub workspaces_schedule: bool,
+    pub opensearch_schedule: bool,
+    pub opensearch_stop_instance_type: String,
+    pub elasticache_schedule: bool,
+    pub emr_serverless_schedule: bool,
+    pub mwaa_schedule: bool,
+    pub eventbridge_schedule: bool,
+    pub dms_schedule: bool,
+    pub dms_stop_instance_class: String,
+    pub nat_gateway_schedule: bool,
+    pub appstream_schedule: bool,
+    pub lightsail_schedule: bool,
+    pub flink_schedule: bool,
+    pub cloud9_schedule: bool,
+    pub client_vpn_schedule: bool,
+    pub dynamodb_schedule: bool,
+    pub codepipeline_schedule: bool,
+    pub gamelift_schedule: bool,
+    pub vpc_endpoints_schedule: bool,
+    pub ec2_fleet_schedule: bool,
+    pub cloudfront_schedule: bool,
+    pub route53_healthcheck_schedule: bool,
+    pub comprehend_schedule: bool,
+    pub ssm_mw_schedule: bool,
+    pub dlm_schedule: bool,
+    pub excluded_dates: Vec<DateSpec>,
+    pub excluded_dates_ical_url: Option<String>,
+    pub active_days: Vec<Weekday>,
+    pub active_date_ranges: Vec<DateSpec>,
+    pub max_concurrent_regions: usize,
+    pub max_concurrent_operations: usize,
+    pub max_resources_per_service: Option<usize>,
+    pub execution_timeout_seconds: Option<u64>,
+    pub service_timeout_seconds: HashMap<String, u64>,
+    pub service_order: ServiceOrder,
+    pub wait_for_completion: bool,
+    pub wait_timeout_seconds: u64,
+    pub service_wait_timeout_seconds: HashMap<String, u64>,
+    pub wait_poll_interval_seconds: u64,
+    pub waiter_max_attempts: u32,
+    pub waiter_delay_seconds: u64,
+    pub pre_stop_hook: Option<Hook>,
+    pub post_start_hook: Option<Hook>,
+    pub hook_timeout_seconds: u64,
+    pub abort_on_hook_failure: bool,
+    pub retry_max_attempts: u32,
+    pub sns_topic_arn: Option<String>,
+    pub webhook_url: Option<String>,
+    pub webhook_format: WebhookFormat,
+    pub report_email_to: Vec<String>,
+    pub report_email_from: Option<String>,
+    pub event_bus_name: Option<String>,
+    pub audit_store: Option<StoreLocation>,
+    /// Store of the state recorded on stop, `None` for resource tags.
+    pub state_store: Option<StoreLocation>,
+    pub estimate_savings: bool,
+    pub lock_table: Option<String>,
+    pub lock_ttl_seconds: u64,
+    pub lock_wait_seconds: u64,
+    pub cloudwatch_metrics_namespace: Option<String>,
+    pub emf_namespace: Option<String>,
+    pub metrics_port: Option<u16>,
+    pub pushgateway_url: Option<String>,
+    pub server_port: u16,
+    pub server_api_token: Option<String>,
+    pub dry_run: bool,
+    /// AWS AppConfig profile the options are read from, polled in daemon mode.
+    pub appconfig: Option<AppConfigSource>,
+    /// ARNs of the resources of the plan applied by `scheduler apply`, the only ones acted upon.
+    pub planned_resources: Option<HashSet<String>>,
 }
 
 impl AppConfig {
-    /// Load configuration from environment variables.
+    /// Timeout of a service in each region, as named in the execution summary (e.g. `ec2`).
+    pub fn service_timeout(&self, service: &str) -> Option<Duration> {
+        self.service_timeout_seconds
+            .get(service)
+            .map(|s| Duration::from_secs(*s))
+    }
+
+    /// Time the resources of a service are waited for with `WAIT_FOR_COMPLETION`:
+    /// its `SERVICE_WAIT_TIMEOUT_SECONDS` when set, `WAIT_TIMEOUT_SECONDS` otherwise.
+    pub fn wait_timeout(&self, service: &str) -> Duration {
+        Duration::from_secs(
+            self.service_wait_timeout_seconds
+                .get(service)
+                .copied()
+                .unwrap_or(self.wait_timeout_seconds),
+        )
+    }
+
+    /// Tag values selecting the resources of an action: `STOP_TAG_VALUE` or
+    /// `START_TAG_VALUE` when set for the action, `TAG_VALUE` otherwise.
+    pub fn tag_values_for(&self, action: &ScheduleAction) -> &[String] {
+        let values = match action {
+            ScheduleAction::Stop => self.stop_tag_values.as_ref(),
+            ScheduleAction::Start => self.start_tag_values.as_ref(),
+            _ => None,
+        };
+        values.unwrap_or(&self.tag_values)
+    }
+
+    /// Tags the resources of an action must carry besides `TAG_KEY`:
+    /// `STOP_TAG_FILTER` or `START_TAG_FILTER` for the action, none otherwise.
+    pub fn tag_filters_for(&self, action: &ScheduleAction) -> &[(String, Option<String>)] {
+        match action {
+            ScheduleAction::Stop => &self.stop_tag_filters,
+            ScheduleAction::Start => &self.start_tag_filters,
+            _ => &[],
+        }
+    }
+
+    /// Regions a service is processed in, as named in the execution summary (e.g. `rds`):
+    /// its `<SERVICE>_REGIONS` when set, `AWS_REGIONS` otherwise.
+    /// Global services are only processed in the first of these regions.
+    pub fn service_regions(&self, service: &str) -> &[String] {
+        let regions = self
+            .service_regions
+            .get(service)
+            .unwrap_or(&self.aws_regions);
+        if GLOBAL_SERVICES.contains(&service) {
+            &regions[..regions.len().min(1)]
+        } else {
+            regions
+        }
+    }
+
+    /// Every region processed by a run: `AWS_REGIONS`, followed by the other
+    /// regions of the `<SERVICE>_REGIONS` overrides.
+    pub fn all_regions(&self) -> Vec<String> {
+        let mut others: Vec<String> = self
+            .service_regions
+            .values()
+            .flatten()
+            .filter(|r| !self.aws_regions.contains(r))
+            .cloned()
+            .collect();
+        others.sort();
+        others.dedup();
+        [self.aws_regions.clone(), others].concat()
+    }
+
+    /// Load configuration from environment variables and `CONFIG_FILE`.
     ///
     /// Returns an error if required variables are missing or invalid.
     pub fn from_env() -> Result<Self> {
-        let schedule_action = match env::var("SCHEDULE_ACTION")
-            .context("SCHEDULE_ACTION env var is required (stop|start)")?
+        Self::from_env_with(HashMap::new())
+    }
+
+    /// Load configuration like [`AppConfig::from_env`], with `overrides` (keyed by
+    /// environment variable name) taking precedence over the environment and `CONFIG_FILE`.
+    pub fn from_env_with(overrides: HashMap<String, String>) -> Result<Self> {
+        Self::from_vars(Vars::load(overrides)?)
+    }
+
+    /// Load configuration like [`AppConfig::from_env`], complemented by the SSM
+    /// parameters under `CONFIG_SSM_PREFIX` and the AWS AppConfig profile of
+    /// `APPCONFIG_APPLICATION` when they are set.
+    pub async fn load() -> Result<Self> {
+        Self::load_with(HashMap::new()).await
+    }
+
+    /// Load configuration like [`AppConfig::load`], with `overrides` taking precedence
+    /// over the environment, AppConfig, SSM and `CONFIG_FILE`.
+    pub async fn load_with(overrides: HashMap<String, String>) -> Result<Self> {
+        let mut vars = Vars::load(overrides)?;
+        vars.load_ssm().await?;
+        if let Some(source) = vars.appconfig_source()? {
+            vars.appconfig = appconfig::Poller::start(&source).await?.options()?;
+        }
+        Self::from_vars(vars)
+    }
+
+    /// Load configuration like [`AppConfig::load`], with the options of an AWS AppConfig
+    /// document already fetched (e.g. by the daemon polling for new deployments).
+    pub(crate) async fn load_with_appconfig(options: HashMap<String, String>) -> Result<Self> {
+        let mut vars = Vars::load(HashMap::new())?;
+        vars.load_ssm().await?;
+        vars.appconfig = options;
+        Self::from_vars(vars)
+    }
+
+    fn from_vars(vars: Vars) -> Result<Self> {
+        let run_mode = match vars
+            .var("RUN_MODE")
+            .unwrap_or_else(|_| "once".to_string())
             .to_lowercase()
             .as_str()
         {
-            "stop" => ScheduleAction::Stop,
-            "start" => ScheduleAction::Start,
+            "once" => RunMode::Once,
+            "daemon" => RunMode::Daemon,
+            "server" => RunMode::Server,
             other => bail!(
-                "Invalid SCHEDULE_ACTION '{}': must be 'stop' or 'start'",
+                "Invalid RUN_MODE '{}': must be 'once', 'daemon' or 'server'",
                 other
             ),
         };
 
-        let aws_regions: Vec<String> = env::var("AWS_REGIONS")
-            .context("AWS_REGIONS env var is required (comma-separated)")?
+        // In daemon mode the action is chosen by whichever cron expression fires,
+        // and in server mode by each request.
+        let schedule_action = match vars.var("SCHEDULE_ACTION") {
+            Ok(value) => parse_schedule_action(&value)?,
+            Err(_) if run_mode != RunMode::Once => ScheduleAction::Stop,
+            Err(_) => bail!("SCHEDULE_ACTION is required (stop|start)"),
+        };
+
+        let stop_cron = vars.cron("STOP_CRON")?;
+        let start_cron = vars.cron("START_CRON")?;
+        if run_mode == RunMode::Daemon && stop_cron.is_none() && start_cron.is_none() {
+            bail!("RUN_MODE=daemon requires STOP_CRON and/or START_CRON");
+        }
+
+        let timezone: Tz = match vars.var("SCHEDULER_TIMEZONE") {
+            Ok(name) => name.trim().parse().map_err(|_| {
+                anyhow!(
+                    "Invalid SCHEDULER_TIMEZONE '{}': unknown IANA timezone",
+                    name
+                )
+            })?,
+            Err(_) => Tz::UTC,
+        };
+
+        let aws_regions_var = vars
+            .var("AWS_REGIONS")
+            .context("AWS_REGIONS is required (comma-separated)")?;
+        let region_discovery = match aws_regions_var.trim().to_lowercase().as_str() {
+            "all" => Some(RegionDiscovery::All),
+            "all-opted-in" => Some(RegionDiscovery::AllOptedIn),
+            _ => None,
+        };
+        let aws_regions: Vec<String> = match region_discovery {
+            Some(_) => Vec::new(),
+            None => parse_regions(&aws_regions_var),
+        };
+
+        if aws_regions.is_empty() && region_discovery.is_none() {
+            bail!("AWS_REGIONS must contain at least one region");
+        }
+
+        let mut service_regions = HashMap::new();
+        for service in SERVICE_NAMES {
+            let name = format!("{}_REGIONS", service.to_uppercase());
+            if let Ok(value) = vars.var(&name) {
+                let regions = parse_regions(&value);
+                if regions.is_empty() {
+                    bail!("{} must contain at least one region", name);
+                }
+                service_regions.insert(service.to_string(), regions);
+            }
+        }
+
+        let assume_role_arns: Vec<String> = vars
+            .var("ASSUME_ROLE_ARNS")
+            .unwrap_or_default()
             .split(',')
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .collect();
 
-        if aws_regions.is_empty() {
-            bail!("AWS_REGIONS must contain at least one region");
+        let endpoint_url = vars.var("AWS_ENDPOINT_URL").ok().filter(|v| !v.is_empty());
+        let use_fips = vars.bool("AWS_USE_FIPS_ENDPOINT", false);
+        let use_dual_stack = vars.bool("AWS_USE_DUALSTACK_ENDPOINT", false);
+        let aws_profile = vars.var("AWS_PROFILE").ok().filter(|v| !v.is_empty());
+
+        let tag_key = vars.var("TAG_KEY").context("TAG_KEY is required")?;
+        let tag_values = parse_tag_values(&vars.var("TAG_VALUE").unwrap_or_default());
+        let stop_tag_values = parse_action_tag_values(&vars, "STOP_TAG_VALUE")?;
+        let start_tag_values = parse_action_tag_values(&vars, "START_TAG_VALUE")?;
+        let stop_tag_filters = parse_tag_filters(&vars, "STOP_TAG_FILTER")?;
+        let start_tag_filters = parse_tag_filters(&vars, "START_TAG_FILTER")?;
+        let schedule_window_tag = vars
+            .var("SCHEDULE_WINDOW_TAG")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        let priority_tag = vars
+            .var("PRIORITY_TAG")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        let name_include_regex = parse_name_regex(&vars, "RESOURCE_NAME_INCLUDE_REGEX")?;
+        let name_exclude_regex = parse_name_regex(&vars, "RESOURCE_NAME_EXCLUDE_REGEX")?;
+        let protected_tags = parse_tag_filters(&vars, "PROTECTED_TAG_FILTERS")?;
+        let exclusion_tag_key = vars.var("EXCLUSION_TAG_KEY").ok().filter(|v| !v.is_empty());
+        let exclusion_tag_value = vars
+            .var("EXCLUSION_TAG_VALUE")
+            .ok()
+            .filter(|v| !v.is_empty());
+        if exclusion_tag_value.is_some() && exclusion_tag_key.is_none() {
+            bail!("EXCLUSION_TAG_VALUE requires EXCLUSION_TAG_KEY");
+        }
+        let terminate_confirm_tag = vars
+            .var("TERMINATE_CONFIRM_TAG")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        if matches!(schedule_action, ScheduleAction::Terminate) {
+            if run_mode != RunMode::Once {
+                bail!(
+                    "SCHEDULE_ACTION=terminate is not supported with RUN_MODE={}",
+                    run_mode
+                );
+            }
+            if terminate_confirm_tag.is_none() {
+                bail!("SCHEDULE_ACTION=terminate requires TERMINATE_CONFIRM_TAG");
+            }
+        }
+        if matches!(
+            schedule_action,
+            ScheduleAction::List | ScheduleAction::Status
+        ) && run_mode != RunMode::Once
+        {
+            bail!(
+                "SCHEDULE_ACTION={} is not supported with RUN_MODE={}",
+                schedule_action,
+                run_mode
+            );
+        }
+        if matches!(schedule_action, ScheduleAction::Status)
+            && stop_cron.is_none()
+            && start_cron.is_none()
+        {
+            bail!("SCHEDULE_ACTION=status requires STOP_CRON and/or START_CRON");
         }
 
-        let tag_key = env::var("TAG_KEY").context("TAG_KEY env var is required")?;
-        let tag_value = env::var("TAG_VALUE").context("TAG_VALUE env var is required")?;
+        let ec2_schedule = vars.bool("EC2_SCHEDULE", true);
+        let ec2_stop_mode = match vars
+            .var("EC2_STOP_MODE")
+            .unwrap_or_else(|_| "stop".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "stop" => Ec2StopMode::Stop,
+            "hibernate" => Ec2StopMode::Hibernate,
+            other => bail!(
+                "Invalid EC2_STOP_MODE '{}': must be 'stop' or 'hibernate'",
+                other
+            ),
+        };
+        let ec2_graceful_shutdown = vars.bool("EC2_GRACEFUL_SHUTDOWN", false);
+        let ec2_shutdown_command = vars
+            .var("EC2_SHUTDOWN_COMMAND")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "shutdown -h now".to_string());
+        let ec2_shutdown_wait_seconds = vars.usize("EC2_SHUTDOWN_WAIT_SECONDS", 60)? as u64;
+        let apprunner_schedule = vars.bool("APPRUNNER_SCHEDULE", false);
+        let autoscaling_schedule = vars.bool("AUTOSCALING_SCHEDULE", false);
+        let autoscaling_mode = match vars
+            .var("AUTOSCALING_MODE")
+            .unwrap_or_else(|_| "suspend".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "suspend" => AutoScalingMode::Suspend,
+            "scale-to-zero" => AutoScalingMode::ScaleToZero,
+            other => bail!(
+                "Invalid AUTOSCALING_MODE '{}': must be 'suspend' or 'scale-to-zero'",
+                other
+            ),
+        };
+        let autoscaling_delete_on_terminate = vars.bool("AUTOSCALING_DELETE_ON_TERMINATE", false);
+        let autoscaling_scheduled_actions = match vars
+            .var("AUTOSCALING_SCHEDULED_ACTIONS")
+            .unwrap_or_else(|_| "keep".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "keep" => ScheduledActionsMode::Keep,
+            "suspend" => ScheduledActionsMode::Suspend,
+            "delete" => ScheduledActionsMode::Delete,
+            other => bail!(
+                "Invalid AUTOSCALING_SCHEDULED_ACTIONS '{}': must be 'keep', 'suspend' or 'delete'",
+                other
+            ),
+        };
+        let cloudwatch_alarm_schedule = vars.bool("CLOUDWATCH_ALARM_SCHEDULE", false);
+        let documentdb_schedule = vars.bool("DOCUMENTDB_SCHEDULE", false);
+        let ecs_schedule = vars.bool("ECS_SCHEDULE", false);
+        let ecs_drain_container_instances = vars.bool("ECS_DRAIN_CONTAINER_INSTANCES", false);
+        let rds_schedule = vars.bool("RDS_SCHEDULE", false);
+        let rds_serverless_v2_mode = match vars
+            .var("RDS_SERVERLESS_V2_MODE")
+            .unwrap_or_else(|_| "stop".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "stop" => ServerlessV2Mode::Stop,
+            "scale-down" => ServerlessV2Mode::ScaleDown,
+            other => bail!(
+                "Invalid RDS_SERVERLESS_V2_MODE '{}': must be 'stop' or 'scale-down'",
+                other
+            ),
+        };
+        let redshift_schedule = vars.bool("REDSHIFT_SCHEDULE", false);
+        let transfer_schedule = vars.bool("TRANSFER_SCHEDULE", false);
+        let lambda_schedule = vars.bool("LAMBDA_SCHEDULE", false);
+        let sagemaker_schedule = vars.bool("SAGEMAKER_SCHEDULE", false);
+        let eks_schedule = vars.bool("EKS_SCHEDULE", false);
+        let redshift_serverless_schedule = vars.bool("REDSHIFT_SERVERLESS_SCHEDULE", false);
+        let workspaces_schedule = vars.bool("WORKSPACES_SCHEDULE", false);
+        let opensearch_schedule = vars.bool("OPENSEARCH_SCHEDULE", false);
+        let opensearch_stop_instance_type = vars
+            .var("OPENSEARCH_STOP_INSTANCE_TYPE")
+            .unwrap_or_else(|_| "t3.small.search".to_string());
+        let elasticache_schedule = vars.bool("ELASTICACHE_SCHEDULE", false);
+        let emr_serverless_schedule = vars.bool("EMR_SERVERLESS_SCHEDULE", false);
+        let mwaa_schedule = vars.bool("MWAA_SCHEDULE", false);
+        let eventbridge_schedule = vars.bool("EVENTBRIDGE_SCHEDULE", false);
+        let dms_schedule = vars.bool("DMS_SCHEDULE", false);
+        let dms_stop_instance_class = vars
+            .var("DMS_STOP_INSTANCE_CLASS")
+            .unwrap_or_else(|_| "dms.t3.micro".to_string());
+        let nat_gateway_schedule = vars.bool("NAT_GATEWAY_SCHEDULE", false);
+        let appstream_schedule = vars.bool("APPSTREAM_SCHEDULE", false);
+        let lightsail_schedule = vars.bool("LIGHTSAIL_SCHEDULE", false);
+        let flink_schedule = vars.bool("FLINK_SCHEDULE", false);
+        let cloud9_schedule = vars.bool("CLOUD9_SCHEDULE", false);
+        let client_vpn_schedule = vars.bool("CLIENT_VPN_SCHEDULE", false);
+        let dynamodb_schedule = vars.bool("DYNAMODB_SCHEDULE", false);
+        let codepipeline_schedule = vars.bool("CODEPIPELINE_SCHEDULE", false);
+        let gamelift_schedule = vars.bool("GAMELIFT_SCHEDULE", false);
+        let vpc_endpoints_schedule = vars.bool("VPC_ENDPOINTS_SCHEDULE", false);
+        let ec2_fleet_schedule = vars.bool("EC2_FLEET_SCHEDULE", false);
+        let cloudfront_schedule = vars.bool("CLOUDFRONT_SCHEDULE", false);
+        let route53_healthcheck_schedule = vars.bool("ROUTE53_HEALTHCHECK_SCHEDULE", false);
+        let comprehend_schedule = vars.bool("COMPREHEND_SCHEDULE", false);
+        let ssm_mw_schedule = vars.bool("SSM_MW_SCHEDULE", false);
+        let dlm_schedule = vars.bool("DLM_SCHEDULE", false);
 
-        let ec2_schedule = env_bool("EC2_SCHEDULE", true);
-        let apprunner_schedule = env_bool("APPRUNNER_SCHEDULE", false);
-        let autoscaling_schedule = env_bool("AUTOSCALING_SCHEDULE", false);
-        let cloudwatch_alarm_schedule = env_bool("CLOUDWATCH_ALARM_SCHEDULE", false);
-        let documentdb_schedule = env_bool("DOCUMENTDB_SCHEDULE", false);
-        let ecs_schedule = env_bool("ECS_SCHEDULE", false);
-        let rds_schedule = env_bool("RDS_SCHEDULE", false);
-        let redshift_schedule = env_bool("REDSHIFT_SCHEDULE", false);
-        let transfer_schedule = env_bool("TRANSFER_SCHEDULE", false);
+        let excluded_dates: Vec<DateSpec> = vars
+            .var("EXCLUDED_DATES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().context("Invalid EXCLUDED_DATES"))
+            .collect::<Result<_>>()?;
+        let excluded_dates_ical_url = vars
+            .var("EXCLUDED_DATES_ICAL_URL")
+            .ok()
+            .filter(|v| !v.is_empty());
+        let active_days = match vars.var("ACTIVE_DAYS").ok().filter(|v| !v.is_empty()) {
+            Some(days) => parse_days(&days).context("Invalid ACTIVE_DAYS")?,
+            None => Vec::new(),
+        };
+        let active_date_ranges: Vec<DateSpec> = vars
+            .var("ACTIVE_DATE_RANGES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().context("Invalid ACTIVE_DATE_RANGES"))
+            .collect::<Result<_>>()?;
 
-        let excluded_dates: Vec<String> = env::var("EXCLUDED_DATES")
+        let max_concurrent_regions = vars.usize("MAX_CONCURRENT_REGIONS", 4)?;
+        let max_concurrent_operations = vars.usize("MAX_CONCURRENT_OPERATIONS", 10)?;
+        let max_resources_per_service = match vars.var("MAX_RESOURCES_PER_SERVICE") {
+            Ok(_) => Some(vars.usize("MAX_RESOURCES_PER_SERVICE", 0)?),
+            Err(_) => None,
+        };
+        let execution_timeout_seconds = match vars.var("EXECUTION_TIMEOUT_SECONDS") {
+            Ok(_) => Some(vars.usize("EXECUTION_TIMEOUT_SECONDS", 0)? as u64),
+            Err(_) => None,
+        };
+        let service_timeout_seconds = parse_service_timeouts(
+            "SERVICE_TIMEOUT_SECONDS",
+            &vars.var("SERVICE_TIMEOUT_SECONDS").unwrap_or_default(),
+        )?;
+        let service_order = ServiceOrder::parse(&vars.var("SERVICE_ORDER").unwrap_or_default())?;
+        let wait_for_completion = vars.bool("WAIT_FOR_COMPLETION", false);
+        let wait_timeout_seconds = vars.usize("WAIT_TIMEOUT_SECONDS", 900)? as u64;
+        let service_wait_timeout_seconds = parse_service_timeouts(
+            "SERVICE_WAIT_TIMEOUT_SECONDS",
+            &vars.var("SERVICE_WAIT_TIMEOUT_SECONDS").unwrap_or_default(),
+        )?;
+        let wait_poll_interval_seconds = vars.usize("WAIT_POLL_INTERVAL_SECONDS", 15)? as u64;
+        let waiter_max_attempts = u32::try_from(vars.usize("WAITER_MAX_ATTEMPTS", 40)?)
+            .context("Invalid WAITER_MAX_ATTEMPTS: too large")?;
+        let waiter_delay_seconds = vars.usize("WAITER_DELAY_SECONDS", 15)? as u64;
+        let pre_stop_hook = match vars.var("PRE_STOP_HOOK") {
+            Ok(value) if !value.trim().is_empty() => Some(parse_hook("PRE_STOP_HOOK", &value)?),
+            _ => None,
+        };
+        let post_start_hook = match vars.var("POST_START_HOOK") {
+            Ok(value) if !value.trim().is_empty() => Some(parse_hook("POST_START_HOOK", &value)?),
+            _ => None,
+        };
+        let hook_timeout_seconds = vars.usize("HOOK_TIMEOUT_SECONDS", 300)? as u64;
+        let abort_on_hook_failure = vars.bool("ABORT_ON_HOOK_FAILURE", false);
+        let retry_max_attempts = u32::try_from(vars.usize("RETRY_MAX_ATTEMPTS", 5)?)
+            .context("Invalid RETRY_MAX_ATTEMPTS: too large")?;
+        let sns_topic_arn = vars.var("SNS_TOPIC_ARN").ok().filter(|v| !v.is_empty());
+        let webhook_url = vars.var("WEBHOOK_URL").ok().filter(|v| !v.is_empty());
+        let webhook_format = match vars
+            .var("WEBHOOK_FORMAT")
+            .unwrap_or_else(|_| "generic".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "slack" => WebhookFormat::Slack,
+            "teams" => WebhookFormat::Teams,
+            "generic" => WebhookFormat::Generic,
+            other => bail!(
+                "Invalid WEBHOOK_FORMAT '{}': must be 'slack', 'teams' or 'generic'",
+                other
+            ),
+        };
+        let report_email_to: Vec<String> = vars
+            .var("REPORT_EMAIL_TO")
             .unwrap_or_default()
             .split(',')
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .collect();
+        let report_email_from = vars.var("REPORT_EMAIL_FROM").ok().filter(|v| !v.is_empty());
+        if !report_email_to.is_empty() && report_email_from.is_none() {
+            bail!("REPORT_EMAIL_FROM is required when REPORT_EMAIL_TO is set");
+        }
+        let event_bus_name = vars.var("EVENT_BUS_NAME").ok().filter(|v| !v.is_empty());
+        let audit_store = match vars.var("AUDIT_STORE") {
+            Ok(value) if !value.trim().is_empty() => {
+                Some(parse_store_location("AUDIT_STORE", &value)?)
+            }
+            _ => None,
+        };
+        let state_store = match vars.var("STATE_STORE") {
+            Ok(value) if !value.trim().is_empty() && !value.trim().eq_ignore_ascii_case("tags") => {
+                Some(parse_store_location("STATE_STORE", &value)?)
+            }
+            _ => None,
+        };
+        let estimate_savings = vars.bool("ESTIMATE_SAVINGS", false);
+        let lock_table = vars.var("LOCK_TABLE").ok().filter(|v| !v.is_empty());
+        let lock_ttl_seconds = vars.usize("LOCK_TTL_SECONDS", 3600)? as u64;
+        let lock_wait_seconds = vars.u64("LOCK_WAIT_SECONDS", 0)?;
+        let cloudwatch_metrics_namespace = vars
+            .var("CLOUDWATCH_METRICS_NAMESPACE")
+            .ok()
+            .filter(|v| !v.is_empty());
+        let emf_namespace = vars.var("EMF_NAMESPACE").ok().filter(|v| !v.is_empty());
+        let metrics_port =
+            match vars.var("METRICS_PORT") {
+                Ok(port) => Some(port.trim().parse::<u16>().map_err(|_| {
+                    anyhow!("Invalid METRICS_PORT '{}': must be a port number", port)
+                })?),
+                Err(_) => None,
+            };
+        let pushgateway_url = vars.var("PUSHGATEWAY_URL").ok().filter(|v| !v.is_empty());
+        let server_port = vars
+            .var("SERVER_PORT")
+            .unwrap_or_else(|_| "8080".to_string());
+        let server_port: u16 = server_port.trim().parse().map_err(|_| {
+            anyhow!(
+                "Invalid SERVER_PORT '{}': must be a port number",
+                server_port
+            )
+        })?;
+        let server_api_token = vars.var("SERVER_API_TOKEN").ok().filter(|v| !v.is_empty());
+        let dry_run = vars.bool("DRY_RUN", false);
+        let appconfig = vars.appconfig_source()?;
 
         Ok(Self {
+            run_mode,
             schedule_action,
+            stop_cron,
+            start_cron,
+            timezone,
             aws_regions,
+            region_discovery,
+            service_regions,
+            assume_role_arns,
+            endpoint_url,
+            use_fips,
+            use_dual_stack,
+            aws_profile,
             tag_key,
-            tag_value,
+            tag_values,
+            stop_tag_values,
+            start_tag_values,
+            stop_tag_filters,
+            start_tag_filters,
+            schedule_window_tag,
+            priority_tag,
+            name_include_regex,
+            name_exclude_regex,
+            protected_tags,
+            exclusion_tag_key,
+            exclusion_tag_value,
+            terminate_confirm_tag,
             ec2_schedule,
+            ec2_stop_mode,
+            ec2_graceful_shutdown,
+            ec2_shutdown_command,
+            ec2_shutdown_wait_seconds,
             apprunner_schedule,
             autoscaling_schedule,
+            autoscaling_mode,
+            autoscaling_delete_on_terminate,
+            autoscaling_scheduled_actions,
             cloudwatch_alarm_schedule,
             documentdb_schedule,
             ecs_schedule,
+            ecs_drain_container_instances,
             rds_schedule,
+            rds_serverless_v2_mode,
             redshift_schedule,
             transfer_schedule,
+            lambda_schedule,
+            sagemaker_schedule,
+            eks_schedule,
+            redshift_serverless_schedule,
+            workspaces_schedule,
+            opensearch_schedule,
+            opensearch_stop_instance_type,
+            elasticache_schedule,
+            emr_serverless_schedule,
+            mwaa_schedule,
+            eventbridge_schedule,
+            dms_schedule,
+            dms_stop_instance_class,
+            nat_gateway_schedule,
+            appstream_schedule,
+            lightsail_schedule,
+            flink_schedule,
+            cloud9_schedule,
+            client_vpn_schedule,
+            dynamodb_schedule,
+            codepipeline_schedule,
+            gamelift_schedule,
+            vpc_endpoints_schedule,
+            ec2_fleet_schedule,
+            cloudfront_schedule,
+            route53_healthcheck_schedule,
+            comprehend_schedule,
+            ssm_mw_schedule,
+            dlm_schedule,
             excluded_dates,
+            excluded_dates_ical_url,
+            active_days,
+            active_date_ranges,
+            max_concurrent_regions,
+            max_concurrent_operations,
+            max_resources_per_service,
+            execution_timeout_seconds,
+            service_timeout_seconds,
+            service_order,
+            wait_for_completion,
+            wait_timeout_seconds,
+            service_wait_timeout_seconds,
+            wait_poll_interval_seconds,
+            waiter_max_attempts,
+            waiter_delay_seconds,
+            pre_stop_hook,
+            post_start_hook,
+            hook_timeout_seconds,
+            abort_on_hook_failure,
+            retry_max_attempts,
+            sns_topic_arn,
+            webhook_url,
+            webhook_format,
+            report_email_to,
+            report_email_from,
+            event_bus_name,
+            audit_store,
+            state_store,
+            estimate_savings,
+            lock_table,
+            lock_ttl_seconds,
+            lock_wait_seconds,
+            cloudwatch_metrics_namespace,
+            emf_namespace,
+            metrics_port,
+            pushgateway_url,
+            server_port,
+            server_api_token,
+            dry_run,
+            appconfig,
+            planned_resources: None,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `content` to a configuration file named `name`, unique to this test process.
+    fn config_file(name: &str, content: &str) -> String {
+        let path = env::temp_dir().join(format!("scheduler-{}-{}", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn vars(file: HashMap<String, String>, overrides: &[(&str, &str)]) -> Vars {
+        Vars {
+            overrides: overrides
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            appconfig: HashMap::new(),
+            ssm: HashMap::new(),
+            file,
+        }
+    }
+
+    const YAML_CONFIG: &str = "
+schedule_action: start
+aws_regions: [eu-west-1, eu-west-3]
+tag:
+  key: env
+  value: staging
+rds:
+  regions: us-east-1
+service_wait_timeout_seconds: rds=1800
+";
+
+    #[test]
+    fn file_values_apply_without_overrides() {
+        let file = read_config_file(&config_file("file-only.yaml", YAML_CONFIG)).unwrap();
+
+        let config = AppConfig::from_vars(vars(file, &[])).unwrap();
+
+        assert!(matches!(config.schedule_action, ScheduleAction::Start));
+        assert_eq!(config.aws_regions, ["eu-west-1", "eu-west-3"]);
+        assert_eq!(config.tag_key, "env");
+        assert_eq!(config.tag_values, ["staging"]);
+    }
+
+    #[test]
+    fn toml_sections_are_flattened_like_yaml() {
+        let path = config_file(
+            "file-only.toml",
+            "schedule_action = \"stop\"\n\n[ec2]\nstop_mode = \"hibernate\"\n",
+        );
+
+        let file = read_config_file(&path).unwrap();
+
+        assert_eq!(file["SCHEDULE_ACTION"], "stop");
+        assert_eq!(file["EC2_STOP_MODE"], "hibernate");
+    }
+
+    #[test]
+    fn environment_overrides_file() {
+        let name = "SCHEDULER_CONFIG_TEST_ENV_OVER_FILE";
+        env::set_var(name, "env");
+        let file = HashMap::from([
+            (name.to_string(), "file".to_string()),
+            ("TAG_KEY".to_string(), "file".to_string()),
+        ]);
+
+        let vars = vars(file, &[("TAG_KEY", "override")]);
+
+        assert_eq!(vars.var(name).unwrap(), "env");
+        assert_eq!(vars.var("TAG_KEY").unwrap(), "override");
+        env::remove_var(name);
+    }
+
+    #[test]
+    fn service_overrides_replace_the_global_values() {
+        let file = read_config_file(&config_file("per-service.yaml", YAML_CONFIG)).unwrap();
+
+        let config = AppConfig::from_vars(vars(file, &[])).unwrap();
+
+        assert_eq!(config.service_regions("rds"), ["us-east-1"]);
+        assert_eq!(config.service_regions("ec2"), ["eu-west-1", "eu-west-3"]);
+        assert_eq!(
+            config.all_regions(),
+            ["eu-west-1", "eu-west-3", "us-east-1"]
+        );
+        assert_eq!(config.wait_timeout("rds"), Duration::from_secs(1800));
+        assert_eq!(
+            config.wait_timeout("ec2"),
+            Duration::from_secs(config.wait_timeout_seconds)
+        );
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        for (name, content, error) in [
+            ("invalid.yaml", "tag: [", "not a valid YAML file"),
+            ("invalid.toml", "tag = ", "not a valid TOML file"),
+            (
+                "list.yaml",
+                "- stop\n- start\n",
+                "must hold a map of options",
+            ),
+            ("config.json", "{}", "must be a .yaml, .yml or .toml file"),
+        ] {
+            let e = read_config_file(&config_file(name, content)).unwrap_err();
+            assert!(e.to_string().contains(error), "{}: {}", name, e);
+        }
+        assert!(read_config_file("/nonexistent/config.yaml")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Failed to read CONFIG_FILE"));
+    }
+
+    #[test]
+    fn service_order_is_reversed_on_stop() {
+        let order = ServiceOrder::parse("RDS, documentdb | ec2,ecs").unwrap();
+
+        assert_eq!(
+            order.groups(&ScheduleAction::Start),
+            [vec!["rds", "documentdb"], vec!["ec2", "ecs"]]
+        );
+        assert_eq!(
+            order.groups(&ScheduleAction::Stop),
+            [vec!["ecs", "ec2"], vec!["documentdb", "rds"]]
+        );
+        assert_eq!(ServiceOrder::parse(" ").unwrap(), ServiceOrder::default());
+    }
+
+    #[test]
+    fn service_order_rejects_invalid_groups() {
+        for (value, error) in [
+            ("rds||ec2", "empty group of services"),
+            ("rds,mainframe", "unknown service 'mainframe'"),
+            ("rds|ec2,rds", "service 'rds' listed twice"),
+        ] {
+            let e = ServiceOrder::parse(value).unwrap_err();
+            assert!(e.to_string().contains(error), "{}: {}", value, e);
+        }
+    }
+
+    #[test]
+    fn parse_tag_filters_accepts_keys_with_or_without_value() {
+        let vars = vars(
+            HashMap::new(),
+            &[("PROTECTED_TAG_FILTERS", "env=prod, critical ,,team = data")],
+        );
+
+        assert_eq!(
+            parse_tag_filters(&vars, "PROTECTED_TAG_FILTERS").unwrap(),
+            [
+                ("env".to_string(), Some("prod".to_string())),
+                ("critical".to_string(), None),
+                ("team".to_string(), Some("data".to_string())),
+            ]
+        );
+        assert!(parse_tag_filters(&vars, "STOP_TAG_FILTERS")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_tag_filters_rejects_entries_without_key() {
+        let vars = vars(
+            HashMap::new(),
+            &[("PROTECTED_TAG_FILTERS", "env=prod,=dev")],
+        );
+
+        assert!(parse_tag_filters(&vars, "PROTECTED_TAG_FILTERS").is_err());
+    }
+
+    #[test]
+    fn parse_store_location_accepts_dynamodb_and_s3() {
+        assert_eq!(
+            parse_store_location("STATE_STORE", "dynamodb://scheduler-state").unwrap(),
+            StoreLocation::DynamoDb {
+                table: "scheduler-state".to_string()
+            }
+        );
+        assert_eq!(
+            parse_store_location("STATE_STORE", "s3://bucket/scheduler/state/").unwrap(),
+            StoreLocation::S3 {
+                bucket: "bucket".to_string(),
+                prefix: "scheduler/state".to_string()
+            }
+        );
+        assert_eq!(
+            parse_store_location("STATE_STORE", "s3://bucket").unwrap(),
+            StoreLocation::S3 {
+                bucket: "bucket".to_string(),
+                prefix: String::new()
+            }
+        );
+        for value in [
+            "dynamodb://",
+            "dynamodb://table/key",
+            "s3:///prefix",
+            "file:///tmp",
+        ] {
+            assert!(
+                parse_store_location("STATE_STORE", value).is_err(),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn parse_hook_accepts_webhooks_and_ssm_documents() {
+        assert_eq!(
+            parse_hook("PRE_STOP_HOOK", "https://hooks.example.com/stop").unwrap(),
+            Hook::Webhook("https://hooks.example.com/stop".to_string())
+        );
+        assert_eq!(
+            parse_hook("PRE_STOP_HOOK", "ssm-automation:DrainNodes").unwrap(),
+            Hook::Automation("DrainNodes".to_string())
+        );
+        assert_eq!(
+            parse_hook("POST_START_HOOK", " ssm-command: WarmCaches ").unwrap(),
+            Hook::RunCommand("WarmCaches".to_string())
+        );
+        for value in ["http://hooks.example.com", "ssm-command:", "DrainNodes"] {
+            assert!(parse_hook("PRE_STOP_HOOK", value).is_err(), "{}", value);
+        }
+    }
+}
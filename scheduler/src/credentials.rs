@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use aws_config::retry::RetryConfig;
+use aws_config::sts::AssumeRoleProvider;
+use aws_config::{BehaviorVersion, ConfigLoader, Region, SdkConfig};
+
+use crate::config::AppConfig;
+
+/// Session name used when assuming a role in a target account.
+const SESSION_NAME: &str = "aws-scheduler-stop-start";
+
+/// Maximum delay between two attempts of a throttled or failed request.
+const MAX_BACKOFF: Duration = Duration::from_secs(20);
+
+/// Load the AWS SDK configuration for the given region.
+///
+/// Credentials are resolved automatically by the SDK, from the named profile of
+/// `AWS_PROFILE` when it is set. When `role_arn` is set, those credentials are
+/// used to assume the role, and the resulting temporary credentials are used by
+/// every client built from this configuration. When `endpoint_url` is configured,
+/// every client sends its requests to that endpoint, STS included. Otherwise,
+/// `AWS_USE_FIPS_ENDPOINT` and `AWS_USE_DUALSTACK_ENDPOINT` select the FIPS and
+/// dual-stack (IPv4 and IPv6) variants of the AWS endpoints.
+///
+/// Every client retries throttling errors (`Throttling`, `RequestLimitExceeded`, ...),
+/// transient 5xx errors and timeouts with jittered exponential backoff, up to
+/// `retry_max_attempts` attempts per request.
+pub async fn load_config(config: &AppConfig, region: &str, role_arn: Option<&str>) -> SdkConfig {
+    let region = Region::new(region.to_string());
+    let Some(role_arn) = role_arn else {
+        return loader(config, region).load().await;
+    };
+
+    let source = loader(config, region.clone()).load().await;
+    let provider = AssumeRoleProvider::builder(role_arn)
+        .configure(&source)
+        .region(region.clone())
+        .session_name(SESSION_NAME)
+        .build()
+        .await;
+    loader(config, region)
+        .credentials_provider(provider)
+        .load()
+        .await
+}
+
+/// SDK configuration loader of a region, with the retry settings, endpoints and profile of `config`.
+fn loader(config: &AppConfig, region: Region) -> ConfigLoader {
+    let retry_config = RetryConfig::standard()
+        .with_max_attempts(config.retry_max_attempts)
+        .with_max_backoff(MAX_BACKOFF);
+    let mut loader = aws_config::defaults(BehaviorVersion::latest())
+        .region(region)
+        .retry_config(retry_config);
+    if let Some(endpoint_url) = &config.endpoint_url {
+        loader = loader.endpoint_url(endpoint_url);
+    }
+    if config.use_fips {
+        loader = loader.use_fips(true);
+    }
+    if config.use_dual_stack {
+        loader = loader.use_dual_stack(true);
+    }
+    if let Some(profile) = &config.aws_profile {
+        loader = loader.profile_name(profile);
+    }
+    loader
+}
@@ -0,0 +1,294 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use tracing::{error, info, warn};
+
+use crate::appconfig::Poller;
+use crate::config::{AppConfig, ScheduleAction};
+use crate::metrics;
+use crate::shutdown;
+
+/// Run the scheduler continuously.
+///
+/// Sleeps until the next occurrence of `STOP_CRON` or `START_CRON`
+/// (evaluated in the configured timezone), then performs the matching action.
+/// Errors on individual runs are logged without stopping the daemon.
+/// When `METRICS_PORT` is set, metrics are served on `/metrics` meanwhile.
+/// On SIGTERM/SIGINT, the daemon exits once the current run, if any, is complete.
+///
+/// When the options come from an AWS AppConfig profile, it is polled while waiting,
+/// and the next runs use the configuration of each new deployment.
+pub async fn run(config: &AppConfig) -> Result<()> {
+    info!(
+        stop_cron = ?config.stop_cron.as_ref().map(|s| s.to_string()),
+        start_cron = ?config.start_cron.as_ref().map(|s| s.to_string()),
+        timezone = %config.timezone,
+        "Daemon started"
+    );
+
+    if let Some(port) = config.metrics_port {
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(port).await {
+                error!(error = %e, "Metrics listener stopped");
+            }
+        });
+    }
+
+    let mut config = config.clone();
+    let mut poller = match &config.appconfig {
+        Some(source) => Some(Poller::start(source).await?),
+        None => None,
+    };
+
+    loop {
+        let now = Utc::now().with_timezone(&config.timezone);
+
+        let Some((at, action)) = next_run(&config, &now) else {
+            info!("No upcoming scheduled run, exiting daemon");
+            return Ok(());
+        };
+
+        info!(action = %action, at = %at, "Waiting for next scheduled run");
+        let delay = (at.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default();
+        let poll_interval = config.appconfig.as_ref().map(|s| s.poll_interval);
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown::wait() => {}
+            reloaded = reload(poller.as_mut(), poll_interval) => {
+                config = reloaded;
+                continue;
+            }
+        }
+        if shutdown::requested() {
+            info!("Shutdown requested, exiting daemon");
+            return Ok(());
+        }
+
+        let run_config = AppConfig {
+            schedule_action: action,
+            ..config.clone()
+        };
+        if let Err(e) = crate::execute(&run_config).await {
+            error!(action = %run_config.schedule_action, error = %e, "Scheduled run failed");
+        }
+    }
+}
+
+/// Poll the AppConfig profile every `poll_interval` until a new deployment is found,
+/// and return the configuration loaded with it. Never returns without a poller.
+///
+/// A deployment that cannot be fetched or holds an invalid configuration is
+/// logged, and the current configuration is kept.
+async fn reload(poller: Option<&mut Poller>, poll_interval: Option<Duration>) -> AppConfig {
+    let (Some(poller), Some(poll_interval)) = (poller, poll_interval) else {
+        return std::future::pending().await;
+    };
+    loop {
+        tokio::time::sleep(poll_interval).await;
+        match poller.poll().await {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(e) => {
+                warn!(error = %e, "Failed to poll AWS AppConfig, keeping the current configuration");
+                continue;
+            }
+        }
+        match load_deployment(poller).await {
+            Ok(config) => {
+                info!(
+                    stop_cron = ?config.stop_cron.as_ref().map(|s| s.to_string()),
+                    start_cron = ?config.start_cron.as_ref().map(|s| s.to_string()),
+                    timezone = %config.timezone,
+                    "Configuration reloaded from AWS AppConfig"
+                );
+                return config;
+            }
+            Err(e) => error!(
+                error = %e,
+                "Failed to load the new AWS AppConfig deployment, keeping the current configuration"
+            ),
+        }
+    }
+}
+
+/// Load the configuration with the options of the deployment last fetched by `poller`.
+async fn load_deployment(poller: &Poller) -> Result<AppConfig> {
+    let config = AppConfig::load_with_appconfig(poller.options()?).await?;
+    crate::prepare(&config).await
+}
+
+/// Find the earliest upcoming run after `now`, along with the action to perform.
+///
+/// When stop and start are due at the same time, stop wins.
+fn next_run(config: &AppConfig, now: &DateTime<Tz>) -> Option<(DateTime<Tz>, ScheduleAction)> {
+    let next_stop = next_occurrence(config.stop_cron.as_ref(), now);
+    let next_start = next_occurrence(config.start_cron.as_ref(), now);
+
+    match (next_stop, next_start) {
+        (Some(stop), Some(start)) if start < stop => Some((start, ScheduleAction::Start)),
+        (Some(stop), _) => Some((stop, ScheduleAction::Stop)),
+        (None, Some(start)) => Some((start, ScheduleAction::Start)),
+        (None, None) => None,
+    }
+}
+
+/// Find the most recent run before `now`, along with the action that was performed.
+///
+/// When stop and start were due at the same time, stop wins, as in [`next_run`].
+pub fn last_run(config: &AppConfig, now: &DateTime<Tz>) -> Option<(DateTime<Tz>, ScheduleAction)> {
+    let last_stop = previous_occurrence(config.stop_cron.as_ref(), now);
+    let last_start = previous_occurrence(config.start_cron.as_ref(), now);
+
+    match (last_stop, last_start) {
+        (Some(stop), Some(start)) if start > stop => Some((start, ScheduleAction::Start)),
+        (Some(stop), _) => Some((stop, ScheduleAction::Stop)),
+        (None, Some(start)) => Some((start, ScheduleAction::Start)),
+        (None, None) => None,
+    }
+}
+
+fn next_occurrence(schedule: Option<&Schedule>, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    schedule.and_then(|s| s.after(now).next())
+}
+
+fn previous_occurrence(schedule: Option<&Schedule>, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    schedule.and_then(|s| s.after(now).next_back())
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_appconfigdata::operation::get_latest_configuration::GetLatestConfigurationOutput;
+    use aws_sdk_appconfigdata::operation::start_configuration_session::StartConfigurationSessionOutput;
+    use aws_sdk_appconfigdata::primitives::Blob;
+    use aws_sdk_appconfigdata::Client;
+    use aws_smithy_mocks::{mock, mock_client, Rule};
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::config::AppConfigSource;
+    use crate::test_util::app_config;
+
+    /// Stop at 19:00 and start at 08:00 on weekdays.
+    fn weekday_config() -> AppConfig {
+        app_config(&[("STOP_CRON", "0 19 * * 1-5"), ("START_CRON", "0 8 * * 1-5")])
+    }
+
+    /// Monday 4 March 2024 at the given time, in UTC.
+    fn monday(hour: u32, min: u32, sec: u32, millis: i64) -> DateTime<Tz> {
+        chrono_tz::UTC
+            .with_ymd_and_hms(2024, 3, 4, hour, min, sec)
+            .unwrap()
+            + chrono::Duration::milliseconds(millis)
+    }
+
+    #[test]
+    fn next_run_picks_the_earliest_action() {
+        let config = weekday_config();
+
+        assert!(matches!(
+            next_run(&config, &monday(7, 0, 0, 0)),
+            Some((at, ScheduleAction::Start)) if at == monday(8, 0, 0, 0)
+        ));
+        assert!(matches!(
+            next_run(&config, &monday(12, 0, 0, 0)),
+            Some((at, ScheduleAction::Stop)) if at == monday(19, 0, 0, 0)
+        ));
+    }
+
+    #[test]
+    fn next_run_skips_the_run_that_just_ended_within_its_second() {
+        let config = weekday_config();
+
+        // Woken up slightly early, the run of 19:00:00 is still ahead.
+        assert!(matches!(
+            next_run(&config, &monday(18, 59, 59, 999)),
+            Some((at, ScheduleAction::Stop)) if at == monday(19, 0, 0, 0)
+        ));
+        // A run that completed within 19:00:00 is not performed again.
+        let tuesday_start = monday(8, 0, 0, 0) + chrono::Duration::days(1);
+        assert!(matches!(
+            next_run(&config, &monday(19, 0, 0, 500)),
+            Some((at, ScheduleAction::Start)) if at == tuesday_start
+        ));
+    }
+
+    #[test]
+    fn next_run_prefers_stop_when_both_are_due() {
+        let config = app_config(&[("STOP_CRON", "0 19 * * *"), ("START_CRON", "0 19 * * *")]);
+
+        assert!(matches!(
+            next_run(&config, &monday(12, 0, 0, 0)),
+            Some((_, ScheduleAction::Stop))
+        ));
+        assert!(next_run(&app_config(&[]), &monday(12, 0, 0, 0)).is_none());
+    }
+
+    #[test]
+    fn last_run_includes_the_run_of_the_current_second() {
+        let config = weekday_config();
+
+        assert!(matches!(
+            last_run(&config, &monday(12, 0, 0, 0)),
+            Some((at, ScheduleAction::Start)) if at == monday(8, 0, 0, 0)
+        ));
+        assert!(matches!(
+            last_run(&config, &monday(19, 0, 0, 500)),
+            Some((at, ScheduleAction::Stop)) if at == monday(19, 0, 0, 0)
+        ));
+        // Exactly at 19:00:00, the stop is not due yet.
+        assert!(matches!(
+            last_run(&config, &monday(19, 0, 0, 0)),
+            Some((at, ScheduleAction::Start)) if at == monday(8, 0, 0, 0)
+        ));
+    }
+
+    /// Rule returning `content` from the next poll of the configuration.
+    fn configuration_rule(content: &'static str) -> Rule {
+        mock!(Client::get_latest_configuration).then_output(move || {
+            GetLatestConfigurationOutput::builder()
+                .next_poll_configuration_token("next")
+                .configuration(Blob::new(content))
+                .build()
+        })
+    }
+
+    #[tokio::test]
+    async fn reload_keeps_polling_until_a_valid_deployment() {
+        let session = mock!(Client::start_configuration_session).then_output(|| {
+            StartConfigurationSessionOutput::builder()
+                .initial_configuration_token("initial")
+                .build()
+        });
+        let unchanged = configuration_rule("");
+        let invalid = configuration_rule("stop_cron: [");
+        let valid = configuration_rule(
+            "run_mode: daemon\naws_regions: eu-west-1\ntag_key: env\ntag_value: staging\nstop_cron: 0 20 * * *\n",
+        );
+        let source = AppConfigSource {
+            application: "scheduler".to_string(),
+            environment: "staging".to_string(),
+            profile: "default".to_string(),
+            poll_interval: Duration::from_secs(60),
+        };
+        let mut poller = Poller::from_client(
+            mock_client!(
+                aws_sdk_appconfigdata,
+                [&session, &unchanged, &invalid, &valid]
+            ),
+            &source,
+        );
+
+        let config = reload(Some(&mut poller), Some(Duration::ZERO)).await;
+
+        assert_eq!(
+            config.stop_cron.map(|s| s.to_string()),
+            Some("0 0 20 * * *".to_string())
+        );
+        assert_eq!(valid.num_calls(), 1);
+    }
+}
@@ -0,0 +1,308 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_dlm::types::{GettablePolicyStateValues, SettablePolicyStateValues};
+use aws_sdk_dlm::Client as DlmClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Stop/start handler for Data Lifecycle Manager policies in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover lifecycle policies matching a tag.
+/// On stop: disables the policies, so that no snapshots are taken of stopped resources.
+/// On start: re-enables them.
+pub struct DlmScheduler {
+    dlm: DlmClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl DlmScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            dlm: DlmClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    async fn set_enabled(&self, policy_id: &str, enabled: bool) -> Result<Outcome> {
+        let resp = self
+            .dlm
+            .get_lifecycle_policy()
+            .policy_id(policy_id)
+            .send()
+            .await?;
+        let state = resp
+            .policy()
+            .and_then(|p| p.state())
+            .with_context(|| format!("DLM lifecycle policy '{}' has no state", policy_id))?;
+
+        // Policies in error are left untouched, whatever the action.
+        let (expected, target) = if enabled {
+            (
+                GettablePolicyStateValues::Disabled,
+                SettablePolicyStateValues::Enabled,
+            )
+        } else {
+            (
+                GettablePolicyStateValues::Enabled,
+                SettablePolicyStateValues::Disabled,
+            )
+        };
+        if *state != expected {
+            info!(policy = %policy_id, state = %state, "DLM lifecycle policy not {}, skipping", expected.as_str().to_lowercase());
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            let action = if enabled { "enable" } else { "disable" };
+            info!(policy = %policy_id, "Dry run: would {action} DLM lifecycle policy");
+            return Ok(Outcome::Applied);
+        }
+
+        let action = if enabled { "Enabling" } else { "Disabling" };
+        info!(policy = %policy_id, "{action} DLM lifecycle policy");
+        self.dlm
+            .update_lifecycle_policy()
+            .policy_id(policy_id)
+            .state(target)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for DlmScheduler {
+    fn name(&self) -> &'static str {
+        "dlm"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "dlm:policy", filter).await?;
+        info!(count = arns.len(), "Found DLM lifecycle policies to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let policy_id = extract_policy_id(arn);
+            let result = self.set_enabled(&policy_id, false).await;
+            (policy_id, result)
+        })
+        .await;
+        for (policy_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&policy_id, outcome),
+                Err(e) => {
+                    counts.fail(&policy_id, &e);
+                    error!(policy = %policy_id, error = %e, "Failed to disable DLM lifecycle policy");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "dlm:policy", filter).await?;
+        info!(count = arns.len(), "Found DLM lifecycle policies to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let policy_id = extract_policy_id(arn);
+            let result = self.set_enabled(&policy_id, true).await;
+            (policy_id, result)
+        })
+        .await;
+        for (policy_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&policy_id, outcome),
+                Err(e) => {
+                    counts.fail(&policy_id, &e);
+                    error!(policy = %policy_id, error = %e, "Failed to enable DLM lifecycle policy");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the DLM lifecycle policies matching the given tag with their current state.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "dlm:policy", filter).await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let resp = self
+                .dlm
+                .get_lifecycle_policy()
+                .policy_id(extract_policy_id(&arn))
+                .send()
+                .await?;
+            let state = resp
+                .policy()
+                .and_then(|p| p.state())
+                .map(|s| s.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            items.push(InventoryItem::new(arn, "dlm:policy", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the policy ID from a DLM lifecycle policy ARN.
+///
+/// Expected ARN format: `arn:partition:dlm:region:account:policy/policy-0123456789abcdef0`
+fn extract_policy_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dlm::operation::get_lifecycle_policy::GetLifecyclePolicyOutput;
+    use aws_sdk_dlm::operation::update_lifecycle_policy::UpdateLifecyclePolicyOutput;
+    use aws_sdk_dlm::types::LifecyclePolicy;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const POLICY_ARN_PREFIX: &str = "arn:aws:dlm:eu-west-1:123456789012:policy/";
+
+    /// Rule answering `policy_id` in the given state.
+    fn get_policy_rule(policy_id: &'static str, state: GettablePolicyStateValues) -> Rule {
+        mock!(DlmClient::get_lifecycle_policy)
+            .match_requests(move |req| req.policy_id() == Some(policy_id))
+            .then_output(move || {
+                GetLifecyclePolicyOutput::builder()
+                    .policy(
+                        LifecyclePolicy::builder()
+                            .policy_id(policy_id)
+                            .state(state.clone())
+                            .build(),
+                    )
+                    .build()
+            })
+    }
+
+    /// Rule setting `policy_id` to the given state.
+    fn update_policy_rule(policy_id: &'static str, state: SettablePolicyStateValues) -> Rule {
+        mock!(DlmClient::update_lifecycle_policy)
+            .match_requests(move |req| {
+                req.policy_id() == Some(policy_id) && req.state() == Some(&state)
+            })
+            .then_output(|| UpdateLifecyclePolicyOutput::builder().build())
+    }
+
+    fn scheduler(dlm: DlmClient, tagging: TaggingClient) -> DlmScheduler {
+        DlmScheduler {
+            dlm,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_disables_enabled_policies_only() {
+        let arns = get_resources_rule(
+            "dlm:policy",
+            &[
+                &format!("{POLICY_ARN_PREFIX}policy-0a1b2c3d4e5f60001"),
+                &format!("{POLICY_ARN_PREFIX}policy-0a1b2c3d4e5f60002"),
+            ],
+        );
+        let enabled = get_policy_rule(
+            "policy-0a1b2c3d4e5f60001",
+            GettablePolicyStateValues::Enabled,
+        );
+        let disabled = get_policy_rule(
+            "policy-0a1b2c3d4e5f60002",
+            GettablePolicyStateValues::Disabled,
+        );
+        let update = update_policy_rule(
+            "policy-0a1b2c3d4e5f60001",
+            SettablePolicyStateValues::Disabled,
+        );
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_dlm,
+                RuleMode::MatchAny,
+                [&enabled, &disabled, &update]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_leaves_policies_in_error_untouched() {
+        let arns = get_resources_rule(
+            "dlm:policy",
+            &[
+                &format!("{POLICY_ARN_PREFIX}policy-0a1b2c3d4e5f60002"),
+                &format!("{POLICY_ARN_PREFIX}policy-0a1b2c3d4e5f60003"),
+            ],
+        );
+        let disabled = get_policy_rule(
+            "policy-0a1b2c3d4e5f60002",
+            GettablePolicyStateValues::Disabled,
+        );
+        let error = get_policy_rule("policy-0a1b2c3d4e5f60003", GettablePolicyStateValues::Error);
+        let update = update_policy_rule(
+            "policy-0a1b2c3d4e5f60002",
+            SettablePolicyStateValues::Enabled,
+        );
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_dlm,
+                RuleMode::MatchAny,
+                [&disabled, &error, &update]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+}
@@ -0,0 +1,522 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_databasemigration::types::{
+    Filter, ReplicationInstance, StartReplicationTaskTypeValue, Tag,
+};
+use aws_sdk_databasemigration::Client as DmsClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Tag used to remember the class of a replication instance before it was downsized.
+const PREVIOUS_INSTANCE_CLASS_TAG: &str = "scheduler:previous-instance-class";
+
+/// Stop/start handler for DMS (Database Migration Service) resources in a given AWS region.
+///
+/// On stop: stops running replication tasks, then downsizes replication instances
+/// to the configured class (instances cannot be stopped), recording the previous
+/// class in a tag.
+/// On start: restores the recorded instance classes, then resumes stopped tasks.
+pub struct DmsScheduler {
+    dms: DmsClient,
+    tagging: TaggingClient,
+    stop_instance_class: String,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl DmsScheduler {
+    pub fn new(
+        config: &SdkConfig,
+        stop_instance_class: &str,
+        dry_run: bool,
+        max_concurrent_operations: usize,
+    ) -> Self {
+        Self {
+            dms: DmsClient::new(config),
+            tagging: TaggingClient::new(config),
+            stop_instance_class: stop_instance_class.to_string(),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Get the current status of a replication task (e.g. `running`, `stopped`).
+    async fn task_status(&self, arn: &str) -> Result<String> {
+        let resp = self
+            .dms
+            .describe_replication_tasks()
+            .filters(arn_filter("replication-task-arn", arn)?)
+            .send()
+            .await?;
+
+        resp.replication_tasks()
+            .first()
+            .map(|t| t.status().unwrap_or_default().to_string())
+            .with_context(|| format!("DMS replication task '{}' not found", arn))
+    }
+
+    async fn stop_task(&self, arn: &str) -> Result<Outcome> {
+        let status = self.task_status(arn).await?;
+        if status != "running" {
+            info!(task = %arn, status = %status, "DMS replication task not running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(task = %arn, "Dry run: would stop DMS replication task");
+            return Ok(Outcome::Applied);
+        }
+        info!(task = %arn, "Stopping DMS replication task");
+        self.dms
+            .stop_replication_task()
+            .replication_task_arn(arn)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Resume a stopped replication task from where it left off.
+    ///
+    /// Tasks that were never started (`ready`) or that failed are left alone,
+    /// as resuming them is not what a schedule should decide.
+    async fn resume_task(&self, arn: &str) -> Result<Outcome> {
+        let status = self.task_status(arn).await?;
+        if status != "stopped" {
+            info!(task = %arn, status = %status, "DMS replication task not stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(task = %arn, "Dry run: would resume DMS replication task");
+            return Ok(Outcome::Applied);
+        }
+        info!(task = %arn, "Resuming DMS replication task");
+        self.dms
+            .start_replication_task()
+            .replication_task_arn(arn)
+            .start_replication_task_type(StartReplicationTaskTypeValue::ResumeProcessing)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Describe a single replication instance.
+    async fn describe_instance(&self, arn: &str) -> Result<ReplicationInstance> {
+        let resp = self
+            .dms
+            .describe_replication_instances()
+            .filters(arn_filter("replication-instance-arn", arn)?)
+            .send()
+            .await?;
+
+        resp.replication_instances()
+            .first()
+            .cloned()
+            .with_context(|| format!("DMS replication instance '{}' not found", arn))
+    }
+
+    /// Record the class of a replication instance in a tag, then downsize it.
+    ///
+    /// Instances already on the stop class are skipped, so that their tag still
+    /// names the class to move back to on start.
+    async fn downsize_instance(&self, arn: &str) -> Result<Outcome> {
+        let instance = self.describe_instance(arn).await?;
+        let class = instance.replication_instance_class().unwrap_or_default();
+
+        if class == self.stop_instance_class {
+            info!(instance = %arn, class = %class, "DMS replication instance already downsized, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if !is_available(&instance) {
+            info!(instance = %arn, status = ?instance.replication_instance_status(), "DMS replication instance not available, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(instance = %arn, class = %class, "Dry run: would downsize DMS replication instance");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(instance = %arn, class = %class, "Recording previous replication instance class");
+        self.dms
+            .add_tags_to_resource()
+            .resource_arn(arn)
+            .tags(
+                Tag::builder()
+                    .key(PREVIOUS_INSTANCE_CLASS_TAG)
+                    .value(class)
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        info!(instance = %arn, class = %self.stop_instance_class, "Downsizing DMS replication instance");
+        self.modify_instance_class(arn, &self.stop_instance_class)
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the replication instance class recorded on stop.
+    async fn restore_instance(&self, arn: &str) -> Result<Outcome> {
+        let Some(previous_class) = self.recorded_class(arn).await? else {
+            warn!(instance = %arn, "No recorded instance class found, skipping DMS replication instance");
+            return Ok(Outcome::Skipped);
+        };
+
+        let instance = self.describe_instance(arn).await?;
+        if instance.replication_instance_class() == Some(previous_class.as_str()) {
+            info!(instance = %arn, class = %previous_class, "DMS replication instance already restored, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if !is_available(&instance) {
+            info!(instance = %arn, status = ?instance.replication_instance_status(), "DMS replication instance not available, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(instance = %arn, class = %previous_class, "Dry run: would restore DMS replication instance");
+            return Ok(Outcome::Applied);
+        }
+        info!(instance = %arn, class = %previous_class, "Restoring DMS replication instance");
+        self.modify_instance_class(arn, &previous_class).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Read the instance class recorded in the tags of a replication instance.
+    async fn recorded_class(&self, arn: &str) -> Result<Option<String>> {
+        let resp = self
+            .dms
+            .list_tags_for_resource()
+            .resource_arn(arn)
+            .send()
+            .await?;
+
+        Ok(resp
+            .tag_list()
+            .iter()
+            .find(|t| t.key() == Some(PREVIOUS_INSTANCE_CLASS_TAG))
+            .and_then(|t| t.value())
+            .map(str::to_string))
+    }
+
+    async fn modify_instance_class(&self, arn: &str, class: &str) -> Result<()> {
+        self.dms
+            .modify_replication_instance()
+            .replication_instance_arn(arn)
+            .replication_instance_class(class)
+            .apply_immediately(true)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for DmsScheduler {
+    fn name(&self) -> &'static str {
+        "dms"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let task_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "dms:task", filter).await?;
+        let instance_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "dms:rep", filter).await?;
+
+        info!(
+            tasks = task_arns.len(),
+            instances = instance_arns.len(),
+            "Found DMS resources to stop"
+        );
+
+        filter.check_discovered(task_arns.len() + instance_arns.len())?;
+        let mut counts = ResourceCounts::new(task_arns.len() + instance_arns.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &task_arns,
+            |arn| async move {
+                let result = self.stop_task(arn).await;
+                (arn, result)
+            },
+        )
+        .await;
+        for (arn, result) in results {
+            match result {
+                Ok(outcome) => counts.record(arn, outcome),
+                Err(e) => {
+                    counts.fail(arn, &e);
+                    error!(task = %arn, error = %e, "Failed to stop DMS replication task");
+                }
+            }
+        }
+
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &instance_arns,
+            |arn| async move {
+                let result = self.downsize_instance(arn).await;
+                (arn, result)
+            },
+        )
+        .await;
+        for (arn, result) in results {
+            match result {
+                Ok(outcome) => counts.record(arn, outcome),
+                Err(e) => {
+                    counts.fail(arn, &e);
+                    error!(instance = %arn, error = %e, "Failed to stop DMS replication instance");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let task_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "dms:task", filter).await?;
+        let instance_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "dms:rep", filter).await?;
+
+        info!(
+            tasks = task_arns.len(),
+            instances = instance_arns.len(),
+            "Found DMS resources to start"
+        );
+
+        // Instances are restored first so that resumed tasks run on the full-size instance.
+        filter.check_discovered(task_arns.len() + instance_arns.len())?;
+        let mut counts = ResourceCounts::new(task_arns.len() + instance_arns.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &instance_arns,
+            |arn| async move {
+                let result = self.restore_instance(arn).await;
+                (arn, result)
+            },
+        )
+        .await;
+        for (arn, result) in results {
+            match result {
+                Ok(outcome) => counts.record(arn, outcome),
+                Err(e) => {
+                    counts.fail(arn, &e);
+                    error!(instance = %arn, error = %e, "Failed to start DMS replication instance");
+                }
+            }
+        }
+
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &task_arns,
+            |arn| async move {
+                let result = self.resume_task(arn).await;
+                (arn, result)
+            },
+        )
+        .await;
+        for (arn, result) in results {
+            match result {
+                Ok(outcome) => counts.record(arn, outcome),
+                Err(e) => {
+                    counts.fail(arn, &e);
+                    error!(task = %arn, error = %e, "Failed to start DMS replication task");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the DMS replication tasks and instances matching the given filter with their
+    /// current status. Replication instances running the stop class are reported as `downsized`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let task_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "dms:task", filter).await?;
+        let instance_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "dms:rep", filter).await?;
+
+        let mut items = Vec::with_capacity(task_arns.len() + instance_arns.len());
+        for arn in task_arns {
+            let status = self.task_status(&arn).await?;
+            items.push(InventoryItem::new(arn, "dms:task", status));
+        }
+        for arn in instance_arns {
+            let instance = self.describe_instance(&arn).await?;
+            let state = if instance.replication_instance_class()
+                == Some(self.stop_instance_class.as_str())
+            {
+                "downsized"
+            } else {
+                instance.replication_instance_status().unwrap_or("unknown")
+            };
+            items.push(InventoryItem::new(arn, "dms:rep", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Whether a replication instance accepts modifications.
+fn is_available(instance: &ReplicationInstance) -> bool {
+    instance.replication_instance_status() == Some("available")
+}
+
+/// Build a DMS describe filter matching a single ARN.
+fn arn_filter(name: &str, arn: &str) -> Result<Filter> {
+    Ok(Filter::builder().name(name).values(arn).build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_databasemigration::operation::add_tags_to_resource::AddTagsToResourceOutput;
+    use aws_sdk_databasemigration::operation::describe_replication_instances::DescribeReplicationInstancesOutput;
+    use aws_sdk_databasemigration::operation::describe_replication_tasks::DescribeReplicationTasksOutput;
+    use aws_sdk_databasemigration::operation::list_tags_for_resource::ListTagsForResourceOutput;
+    use aws_sdk_databasemigration::operation::modify_replication_instance::ModifyReplicationInstanceOutput;
+    use aws_sdk_databasemigration::operation::start_replication_task::StartReplicationTaskOutput;
+    use aws_sdk_databasemigration::types::ReplicationTask;
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const INSTANCE_ARN: &str = "arn:aws:dms:eu-west-1:123456789012:rep:K3XQ7ZP2LMNB4VRT";
+    const TASK_ARN: &str = "arn:aws:dms:eu-west-1:123456789012:task:W9CJ4HQ2XBVN7DSA";
+    const STOP_INSTANCE_CLASS: &str = "dms.t3.micro";
+
+    #[tokio::test]
+    async fn stop_skips_instances_already_downsized() {
+        let tasks = get_resources_rule("dms:task", &[]);
+        let instances = get_resources_rule("dms:rep", &[INSTANCE_ARN]);
+        let describe = mock!(DmsClient::describe_replication_instances).then_output(|| {
+            DescribeReplicationInstancesOutput::builder()
+                .replication_instances(
+                    ReplicationInstance::builder()
+                        .replication_instance_arn(INSTANCE_ARN)
+                        .replication_instance_class(STOP_INSTANCE_CLASS)
+                        .replication_instance_status("available")
+                        .build(),
+                )
+                .build()
+        });
+        let record = mock!(DmsClient::add_tags_to_resource)
+            .then_output(|| AddTagsToResourceOutput::builder().build());
+        let modify = mock!(DmsClient::modify_replication_instance)
+            .then_output(|| ModifyReplicationInstanceOutput::builder().build());
+        let scheduler = DmsScheduler {
+            dms: mock_client!(
+                aws_sdk_databasemigration,
+                RuleMode::MatchAny,
+                [&describe, &record, &modify]
+            ),
+            tagging: mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&tasks, &instances]
+            ),
+            stop_instance_class: STOP_INSTANCE_CLASS.to_string(),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 0, 1, 0)
+        );
+        assert_eq!(record.num_calls(), 0);
+        assert_eq!(modify.num_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn start_restores_instances_then_resumes_stopped_tasks() {
+        let tasks = get_resources_rule("dms:task", &[TASK_ARN]);
+        let instances = get_resources_rule("dms:rep", &[INSTANCE_ARN]);
+        let tags = mock!(DmsClient::list_tags_for_resource).then_output(|| {
+            ListTagsForResourceOutput::builder()
+                .tag_list(
+                    Tag::builder()
+                        .key(PREVIOUS_INSTANCE_CLASS_TAG)
+                        .value("dms.r5.large")
+                        .build(),
+                )
+                .build()
+        });
+        let describe = mock!(DmsClient::describe_replication_instances).then_output(|| {
+            DescribeReplicationInstancesOutput::builder()
+                .replication_instances(
+                    ReplicationInstance::builder()
+                        .replication_instance_arn(INSTANCE_ARN)
+                        .replication_instance_class(STOP_INSTANCE_CLASS)
+                        .replication_instance_status("available")
+                        .build(),
+                )
+                .build()
+        });
+        let modify = mock!(DmsClient::modify_replication_instance)
+            .match_requests(|req| {
+                req.replication_instance_arn() == Some(INSTANCE_ARN)
+                    && req.replication_instance_class() == Some("dms.r5.large")
+            })
+            .then_output(|| ModifyReplicationInstanceOutput::builder().build());
+        let describe_task = mock!(DmsClient::describe_replication_tasks).then_output(|| {
+            DescribeReplicationTasksOutput::builder()
+                .replication_tasks(
+                    ReplicationTask::builder()
+                        .replication_task_arn(TASK_ARN)
+                        .status("stopped")
+                        .build(),
+                )
+                .build()
+        });
+        let resume = mock!(DmsClient::start_replication_task)
+            .match_requests(|req| {
+                req.replication_task_arn() == Some(TASK_ARN)
+                    && req.start_replication_task_type()
+                        == Some(&StartReplicationTaskTypeValue::ResumeProcessing)
+            })
+            .then_output(|| StartReplicationTaskOutput::builder().build());
+        let scheduler = DmsScheduler {
+            dms: mock_client!(
+                aws_sdk_databasemigration,
+                RuleMode::MatchAny,
+                [&tags, &describe, &modify, &describe_task, &resume]
+            ),
+            tagging: mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&tasks, &instances]
+            ),
+            stop_instance_class: STOP_INSTANCE_CLASS.to_string(),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 2, 0, 0)
+        );
+        assert_eq!(modify.num_calls(), 1);
+        assert_eq!(resume.num_calls(), 1);
+    }
+}
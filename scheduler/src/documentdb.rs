@@ -1,86 +1,493 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_docdb::types::DbCluster;
 use aws_sdk_docdb::Client as DocDbClient;
+use aws_sdk_docdbelastic::types::Status as ElasticStatus;
+use aws_sdk_docdbelastic::Client as DocDbElasticClient;
 use aws_sdk_resourcegroupstagging::Client as TaggingClient;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
-use crate::filter_resources_by_tags;
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Engine name reported by DocumentDB clusters.
+const DOCDB_ENGINE: &str = "docdb";
 
 /// Stop/start handler for DocumentDB clusters in a given AWS region.
 ///
 /// Uses the Resource Groups Tagging API to discover clusters matching a tag,
 /// then performs the requested action on each one.
+/// Aurora clusters, reported under the same `rds:cluster` type, are ignored.
+/// Elastic clusters are handled through the DocumentDB Elastic API.
 pub struct DocumentDbScheduler {
     docdb: DocDbClient,
+    elastic: DocDbElasticClient,
     tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
 }
 
 impl DocumentDbScheduler {
-    pub async fn new(region: &str) -> Self {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
-
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
         Self {
-            docdb: DocDbClient::new(&config),
-            tagging: TaggingClient::new(&config),
+            docdb: DocDbClient::new(config),
+            elastic: DocDbElasticClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
         }
     }
 
-    pub async fn stop(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "rds:cluster", tag_key, tag_value).await?;
-        info!(count = arns.len(), "Found DocumentDB clusters to stop");
-
-        for arn in &arns {
+    /// Keep only the DocumentDB clusters among the `rds:cluster` resources.
+    ///
+    /// The Tagging API reports Aurora and DocumentDB clusters under the same
+    /// resource type, so the engine of each cluster is checked.
+    async fn docdb_cluster_ids(&self, arns: &[String]) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for arn in arns {
             let cluster_id = extract_cluster_id(arn);
-            if let Err(e) = self.stop_cluster(&cluster_id).await {
-                error!(cluster = %cluster_id, error = %e, "Failed to stop DocumentDB cluster");
+            let cluster = self.describe_cluster(&cluster_id).await?;
+            match cluster.engine() {
+                Some(DOCDB_ENGINE) => ids.push(cluster_id),
+                engine => {
+                    debug!(cluster = %cluster_id, engine = ?engine, "Not a DocumentDB cluster, ignoring")
+                }
             }
         }
-
-        Ok(())
+        Ok(ids)
     }
 
-    pub async fn start(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "rds:cluster", tag_key, tag_value).await?;
-        info!(count = arns.len(), "Found DocumentDB clusters to start");
-
-        for arn in &arns {
-            let cluster_id = extract_cluster_id(arn);
-            if let Err(e) = self.start_cluster(&cluster_id).await {
-                error!(cluster = %cluster_id, error = %e, "Failed to start DocumentDB cluster");
-            }
-        }
+    /// Describe a single DocumentDB cluster.
+    async fn describe_cluster(&self, cluster_id: &str) -> Result<DbCluster> {
+        let resp = self
+            .docdb
+            .describe_db_clusters()
+            .db_cluster_identifier(cluster_id)
+            .send()
+            .await?;
 
-        Ok(())
+        resp.db_clusters()
+            .first()
+            .cloned()
+            .with_context(|| format!("DocumentDB cluster '{}' not found", cluster_id))
     }
 
-    async fn stop_cluster(&self, cluster_id: &str) -> Result<()> {
+    async fn stop_cluster(&self, cluster_id: &str) -> Result<Outcome> {
+        let cluster = self.describe_cluster(cluster_id).await?;
+        let status = cluster.status().unwrap_or_default();
+        if matches!(status, "stopped" | "stopping") {
+            info!(cluster = %cluster_id, status = %status, "DocumentDB cluster already stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(cluster = %cluster_id, "Dry run: would stop DocumentDB cluster");
+            return Ok(Outcome::Applied);
+        }
         info!(cluster = %cluster_id, "Stopping DocumentDB cluster");
         self.docdb
             .stop_db_cluster()
             .db_cluster_identifier(cluster_id)
             .send()
             .await?;
-        Ok(())
+        Ok(Outcome::Applied)
     }
 
-    async fn start_cluster(&self, cluster_id: &str) -> Result<()> {
+    async fn start_cluster(&self, cluster_id: &str) -> Result<Outcome> {
+        let cluster = self.describe_cluster(cluster_id).await?;
+        let status = cluster.status().unwrap_or_default();
+        if matches!(status, "available" | "starting") {
+            info!(cluster = %cluster_id, status = %status, "DocumentDB cluster already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(cluster = %cluster_id, "Dry run: would start DocumentDB cluster");
+            return Ok(Outcome::Applied);
+        }
         info!(cluster = %cluster_id, "Starting DocumentDB cluster");
         self.docdb
             .start_db_cluster()
             .db_cluster_identifier(cluster_id)
             .send()
             .await?;
-        Ok(())
+        Ok(Outcome::Applied)
+    }
+
+    /// Get the current status of a DocumentDB Elastic cluster (e.g. `ACTIVE`, `STOPPED`).
+    async fn elastic_cluster_status(&self, arn: &str) -> Result<ElasticStatus> {
+        let resp = self.elastic.get_cluster().cluster_arn(arn).send().await?;
+
+        resp.cluster()
+            .map(|c| c.status().clone())
+            .with_context(|| format!("DocumentDB Elastic cluster '{}' not found", arn))
+    }
+
+    async fn stop_elastic_cluster(&self, arn: &str) -> Result<Outcome> {
+        let status = self.elastic_cluster_status(arn).await?;
+        if status != ElasticStatus::Active {
+            info!(cluster = %arn, status = %status.as_str(), "DocumentDB Elastic cluster not active, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(cluster = %arn, "Dry run: would stop DocumentDB Elastic cluster");
+            return Ok(Outcome::Applied);
+        }
+        info!(cluster = %arn, "Stopping DocumentDB Elastic cluster");
+        self.elastic.stop_cluster().cluster_arn(arn).send().await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn start_elastic_cluster(&self, arn: &str) -> Result<Outcome> {
+        let status = self.elastic_cluster_status(arn).await?;
+        if status != ElasticStatus::Stopped {
+            info!(cluster = %arn, status = %status.as_str(), "DocumentDB Elastic cluster not stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(cluster = %arn, "Dry run: would start DocumentDB Elastic cluster");
+            return Ok(Outcome::Applied);
+        }
+        info!(cluster = %arn, "Starting DocumentDB Elastic cluster");
+        self.elastic.start_cluster().cluster_arn(arn).send().await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for DocumentDbScheduler {
+    fn name(&self) -> &'static str {
+        "documentdb"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "rds:cluster", filter).await?;
+        let cluster_ids = self.docdb_cluster_ids(&arns).await?;
+        let elastic_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "docdb-elastic:cluster", filter)
+                .await?;
+        info!(
+            clusters = cluster_ids.len(),
+            elastic_clusters = elastic_arns.len(),
+            "Found DocumentDB clusters to stop"
+        );
+
+        filter.check_discovered(cluster_ids.len() + elastic_arns.len())?;
+        let mut counts = ResourceCounts::new(cluster_ids.len() + elastic_arns.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &cluster_ids,
+            |cluster_id| async move {
+                let result = self.stop_cluster(cluster_id).await;
+                (cluster_id, result)
+            },
+        )
+        .await;
+        for (cluster_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(cluster_id, outcome),
+                Err(e) => {
+                    counts.fail(cluster_id, &e);
+                    error!(cluster = %cluster_id, error = %e, "Failed to stop DocumentDB cluster");
+                }
+            }
+        }
+
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &elastic_arns,
+            |arn| async move {
+                let result = self.stop_elastic_cluster(arn).await;
+                (arn, result)
+            },
+        )
+        .await;
+        for (arn, result) in results {
+            match result {
+                Ok(outcome) => counts.record(arn, outcome),
+                Err(e) => {
+                    counts.fail(arn, &e);
+                    error!(cluster = %arn, error = %e, "Failed to stop DocumentDB Elastic cluster");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "rds:cluster", filter).await?;
+        let cluster_ids = self.docdb_cluster_ids(&arns).await?;
+        let elastic_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "docdb-elastic:cluster", filter)
+                .await?;
+        info!(
+            clusters = cluster_ids.len(),
+            elastic_clusters = elastic_arns.len(),
+            "Found DocumentDB clusters to start"
+        );
+
+        filter.check_discovered(cluster_ids.len() + elastic_arns.len())?;
+        let mut counts = ResourceCounts::new(cluster_ids.len() + elastic_arns.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &cluster_ids,
+            |cluster_id| async move {
+                let result = self.start_cluster(cluster_id).await;
+                (cluster_id, result)
+            },
+        )
+        .await;
+        for (cluster_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(cluster_id, outcome),
+                Err(e) => {
+                    counts.fail(cluster_id, &e);
+                    error!(cluster = %cluster_id, error = %e, "Failed to start DocumentDB cluster");
+                }
+            }
+        }
+
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &elastic_arns,
+            |arn| async move {
+                let result = self.start_elastic_cluster(arn).await;
+                (arn, result)
+            },
+        )
+        .await;
+        for (arn, result) in results {
+            match result {
+                Ok(outcome) => counts.record(arn, outcome),
+                Err(e) => {
+                    counts.fail(arn, &e);
+                    error!(cluster = %arn, error = %e, "Failed to start DocumentDB Elastic cluster");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the DocumentDB clusters and Elastic clusters matching the given filter with their current status.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "rds:cluster", filter).await?;
+        let elastic_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "docdb-elastic:cluster", filter)
+                .await?;
+
+        let mut items = Vec::new();
+        for arn in arns {
+            let cluster = self.describe_cluster(&extract_cluster_id(&arn)).await?;
+            if cluster.engine() == Some(DOCDB_ENGINE) {
+                items.push(InventoryItem::new(
+                    arn,
+                    "rds:cluster",
+                    cluster.status().unwrap_or("unknown"),
+                ));
+            }
+        }
+        for arn in elastic_arns {
+            let status = self.elastic_cluster_status(&arn).await?;
+            items.push(InventoryItem::new(
+                arn,
+                "docdb-elastic:cluster",
+                status.as_str(),
+            ));
+        }
+        Ok(items)
     }
 }
 
 /// Extract the cluster identifier from an RDS cluster ARN.
 ///
-/// Expected ARN format: `arn:aws:rds:region:account:cluster:cluster-id`
+/// Expected ARN format: `arn:partition:rds:region:account:cluster:cluster-id`
 fn extract_cluster_id(arn: &str) -> String {
-    arn.split(':').last().unwrap_or(arn).to_string()
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_docdb::operation::describe_db_clusters::DescribeDbClustersOutput;
+    use aws_sdk_docdb::operation::start_db_cluster::StartDbClusterOutput;
+    use aws_sdk_docdb::operation::stop_db_cluster::StopDbClusterOutput;
+    use aws_sdk_docdbelastic::operation::get_cluster::GetClusterOutput;
+    use aws_sdk_docdbelastic::operation::start_cluster::StartClusterOutput;
+    use aws_sdk_docdbelastic::operation::stop_cluster::StopClusterOutput;
+    use aws_sdk_docdbelastic::types::{Auth, Cluster as ElasticCluster};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const CLUSTER_ARN_PREFIX: &str = "arn:aws:rds:eu-west-1:123456789012:cluster:";
+    const ELASTIC_ARN: &str =
+        "arn:aws:docdb-elastic:eu-west-1:123456789012:cluster/5e1f6a0c-7b2d-4c8e-9f3a-1b2c3d4e5f60";
+
+    /// Rule describing the cluster `cluster_id` of the given engine and status.
+    fn describe_cluster_rule(
+        cluster_id: &'static str,
+        engine: &'static str,
+        status: &'static str,
+    ) -> Rule {
+        mock!(DocDbClient::describe_db_clusters)
+            .match_requests(move |req| req.db_cluster_identifier() == Some(cluster_id))
+            .then_output(move || {
+                DescribeDbClustersOutput::builder()
+                    .db_clusters(
+                        DbCluster::builder()
+                            .db_cluster_identifier(cluster_id)
+                            .engine(engine)
+                            .status(status)
+                            .build(),
+                    )
+                    .build()
+            })
+    }
+
+    /// Rule answering the Elastic cluster in the given status.
+    fn get_elastic_cluster_rule(status: ElasticStatus) -> Rule {
+        mock!(DocDbElasticClient::get_cluster)
+            .match_requests(|req| req.cluster_arn() == Some(ELASTIC_ARN))
+            .then_output(move || {
+                GetClusterOutput::builder()
+                    .cluster(
+                        ElasticCluster::builder()
+                            .cluster_name("sessions")
+                            .cluster_arn(ELASTIC_ARN)
+                            .status(status.clone())
+                            .cluster_endpoint("sessions.eu-west-1.docdb-elastic.amazonaws.com")
+                            .create_time("2024-01-01T00:00:00Z")
+                            .admin_user_name("admin")
+                            .auth_type(Auth::SecretArn)
+                            .shard_capacity(2)
+                            .shard_count(1)
+                            .vpc_security_group_ids("sg-0a1b2c3d")
+                            .subnet_ids("subnet-0a1b2c3d")
+                            .preferred_maintenance_window("sun:03:00-sun:04:00")
+                            .kms_key_id("AWS_OWNED_KMS_KEY")
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+            })
+    }
+
+    fn scheduler(
+        docdb: DocDbClient,
+        elastic: DocDbElasticClient,
+        tagging: TaggingClient,
+    ) -> DocumentDbScheduler {
+        DocumentDbScheduler {
+            docdb,
+            elastic,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_ignores_clusters_of_other_engines() {
+        let clusters = get_resources_rule(
+            "rds:cluster",
+            &[
+                &format!("{CLUSTER_ARN_PREFIX}catalog"),
+                &format!("{CLUSTER_ARN_PREFIX}orders"),
+            ],
+        );
+        let elastic_clusters = get_resources_rule("docdb-elastic:cluster", &[ELASTIC_ARN]);
+        let catalog = describe_cluster_rule("catalog", DOCDB_ENGINE, "available");
+        let orders = describe_cluster_rule("orders", "aurora-postgresql", "available");
+        let stop = mock!(DocDbClient::stop_db_cluster)
+            .match_requests(|req| req.db_cluster_identifier() == Some("catalog"))
+            .then_output(|| StopDbClusterOutput::builder().build());
+        let sessions = get_elastic_cluster_rule(ElasticStatus::Active);
+        let stop_elastic = mock!(DocDbElasticClient::stop_cluster)
+            .match_requests(|req| req.cluster_arn() == Some(ELASTIC_ARN))
+            .then_output(|| StopClusterOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_docdb,
+                RuleMode::MatchAny,
+                [&catalog, &orders, &stop]
+            ),
+            mock_client!(
+                aws_sdk_docdbelastic,
+                RuleMode::MatchAny,
+                [&sessions, &stop_elastic]
+            ),
+            mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&clusters, &elastic_clusters]
+            ),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 2, 0, 0)
+        );
+        assert_eq!(stop.num_calls(), 1);
+        assert_eq!(stop_elastic.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_skips_elastic_clusters_not_stopped() {
+        let clusters =
+            get_resources_rule("rds:cluster", &[&format!("{CLUSTER_ARN_PREFIX}catalog")]);
+        let elastic_clusters = get_resources_rule("docdb-elastic:cluster", &[ELASTIC_ARN]);
+        let catalog = describe_cluster_rule("catalog", DOCDB_ENGINE, "stopped");
+        let start = mock!(DocDbClient::start_db_cluster)
+            .match_requests(|req| req.db_cluster_identifier() == Some("catalog"))
+            .then_output(|| StartDbClusterOutput::builder().build());
+        let sessions = get_elastic_cluster_rule(ElasticStatus::Updating);
+        let start_elastic = mock!(DocDbElasticClient::start_cluster)
+            .then_output(|| StartClusterOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_docdb, RuleMode::MatchAny, [&catalog, &start]),
+            mock_client!(
+                aws_sdk_docdbelastic,
+                RuleMode::MatchAny,
+                [&sessions, &start_elastic]
+            ),
+            mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&clusters, &elastic_clusters]
+            ),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(start.num_calls(), 1);
+        assert_eq!(start_elastic.num_calls(), 0);
+    }
 }
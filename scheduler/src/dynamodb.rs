@@ -0,0 +1,590 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_applicationautoscaling::types::ServiceNamespace;
+use aws_sdk_dynamodb::types::{
+    BillingMode, GlobalSecondaryIndexUpdate, ProvisionedThroughput,
+    ProvisionedThroughputDescription, TableDescription, TableStatus,
+    UpdateGlobalSecondaryIndexAction,
+};
+use aws_sdk_dynamodb::Client as DynamoDbClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::app_autoscaling::AppAutoScaling;
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::state::{State, StateStore};
+use crate::summary::{Outcome, ResourceCounts};
+
+/// State key used to remember the provisioned capacity of a table before it was scaled down.
+const PREVIOUS_CAPACITY: &str = "previous-capacity";
+/// Prefix of the state keys used to remember the provisioned capacity of each global secondary index.
+const PREVIOUS_INDEX_CAPACITY_PREFIX: &str = "previous-capacity:";
+
+/// Read and write capacity units applied to tables and indexes while stopped.
+const MIN_CAPACITY: Capacity = Capacity { read: 1, write: 1 };
+
+/// Stop/start handler for provisioned DynamoDB tables in a given AWS region.
+///
+/// Tables cannot be stopped, so their provisioned capacity is lowered instead.
+/// On stop: records the RCU/WCU of the table and of its global secondary indexes
+/// in the state store (by default, `scheduler:previous-capacity*` tags), then scales
+/// them all down to 1 RCU/1 WCU.
+/// On start: restores the recorded capacities.
+/// On-demand tables are skipped.
+/// Application Auto Scaling of the table and its indexes is suspended while they are scaled down.
+pub struct DynamoDbScheduler {
+    dynamodb: DynamoDbClient,
+    tagging: TaggingClient,
+    state: Arc<dyn StateStore>,
+    app_autoscaling: AppAutoScaling,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+/// Provisioned read/write capacity units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Capacity {
+    read: i64,
+    write: i64,
+}
+
+/// Provisioned capacity of a table and of each of its global secondary indexes.
+#[derive(Debug, PartialEq)]
+struct TableCapacity {
+    table: Capacity,
+    indexes: HashMap<String, Capacity>,
+}
+
+impl DynamoDbScheduler {
+    pub fn new(
+        config: &SdkConfig,
+        state: Arc<dyn StateStore>,
+        dry_run: bool,
+        max_concurrent_operations: usize,
+    ) -> Self {
+        Self {
+            dynamodb: DynamoDbClient::new(config),
+            tagging: TaggingClient::new(config),
+            state,
+            app_autoscaling: AppAutoScaling::new(config, dry_run),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Record the provisioned capacity of a table in the state store, then lower it to the minimum.
+    /// A table whose table and index throughput already sit at the minimum is skipped,
+    /// so the state store still holds the throughput that the next start restores.
+    async fn scale_down(&self, arn: &str, table: &str) -> Result<Outcome> {
+        let description = self.describe_table(table).await?;
+        if is_on_demand(&description) {
+            info!(table = %table, "DynamoDB table uses on-demand capacity, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let current = table_capacity(&description);
+        let target = TableCapacity {
+            table: MIN_CAPACITY,
+            indexes: current
+                .indexes
+                .keys()
+                .map(|name| (name.clone(), MIN_CAPACITY))
+                .collect(),
+        };
+        if current == target {
+            info!(table = %table, "DynamoDB table already scaled down, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if !is_active(&description) {
+            info!(table = %table, status = ?description.table_status(), "DynamoDB table not active, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(table = %table, current = ?current, "Dry run: would scale down DynamoDB table");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(table = %table, current = ?current, "Recording previous provisioned capacity");
+        let mut state = State::from([(PREVIOUS_CAPACITY.to_string(), current.table.to_string())]);
+        for (index, capacity) in &current.indexes {
+            state.insert(
+                format!("{}{}", PREVIOUS_INDEX_CAPACITY_PREFIX, index),
+                capacity.to_string(),
+            );
+        }
+        self.state.save(arn, &state).await?;
+
+        self.app_autoscaling
+            .suspend(
+                ServiceNamespace::Dynamodb,
+                &scalable_resource_ids(table, &current),
+            )
+            .await?;
+        info!(table = %table, "Scaling down DynamoDB table");
+        self.update_capacity(table, &current, &target).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the provisioned capacity recorded on stop.
+    async fn restore(&self, arn: &str, table: &str) -> Result<Outcome> {
+        let state = self.state.load(arn).await?;
+        let Some(recorded_table) = state
+            .get(PREVIOUS_CAPACITY)
+            .and_then(|v| Capacity::parse(v))
+        else {
+            warn!(table = %table, "No recorded provisioned capacity found, skipping DynamoDB table");
+            return Ok(Outcome::Skipped);
+        };
+
+        let description = self.describe_table(table).await?;
+        let current = table_capacity(&description);
+        // Indexes created since the stop have no recorded capacity and keep their current one.
+        let previous = TableCapacity {
+            table: recorded_table,
+            indexes: current
+                .indexes
+                .iter()
+                .map(|(index, capacity)| {
+                    let key = format!("{}{}", PREVIOUS_INDEX_CAPACITY_PREFIX, index);
+                    let recorded = state.get(&key).and_then(|v| Capacity::parse(v));
+                    (index.clone(), recorded.unwrap_or(*capacity))
+                })
+                .collect(),
+        };
+
+        if current == previous {
+            info!(table = %table, "DynamoDB table already restored, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if !is_active(&description) {
+            info!(table = %table, status = ?description.table_status(), "DynamoDB table not active, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(table = %table, previous = ?previous, "Dry run: would restore DynamoDB table");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(table = %table, previous = ?previous, "Restoring DynamoDB table");
+        self.update_capacity(table, &current, &previous).await?;
+        self.app_autoscaling
+            .resume(
+                ServiceNamespace::Dynamodb,
+                &scalable_resource_ids(table, &current),
+            )
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn describe_table(&self, table: &str) -> Result<TableDescription> {
+        let resp = self
+            .dynamodb
+            .describe_table()
+            .table_name(table)
+            .send()
+            .await?;
+
+        resp.table()
+            .cloned()
+            .with_context(|| format!("DynamoDB table '{}' not found", table))
+    }
+
+    /// Apply the given capacity to a table and its indexes.
+    ///
+    /// Only the table and indexes whose capacity changes are updated, as DynamoDB
+    /// rejects updates that do not change anything.
+    async fn update_capacity(
+        &self,
+        table: &str,
+        current: &TableCapacity,
+        capacity: &TableCapacity,
+    ) -> Result<()> {
+        let mut req = self.dynamodb.update_table().table_name(table);
+        if current.table != capacity.table {
+            req = req.provisioned_throughput(capacity.table.throughput()?);
+        }
+        for (index, index_capacity) in &capacity.indexes {
+            if current.indexes.get(index) == Some(index_capacity) {
+                continue;
+            }
+            req = req.global_secondary_index_updates(
+                GlobalSecondaryIndexUpdate::builder()
+                    .update(
+                        UpdateGlobalSecondaryIndexAction::builder()
+                            .index_name(index)
+                            .provisioned_throughput(index_capacity.throughput()?)
+                            .build()?,
+                    )
+                    .build(),
+            );
+        }
+        req.send().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for DynamoDbScheduler {
+    fn name(&self) -> &'static str {
+        "dynamodb"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(&self.tagging, "dynamodb:table", filter)
+            .await?;
+        info!(count = arns.len(), "Found DynamoDB tables to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let table = extract_table_name(arn);
+            let result = self.scale_down(arn, &table).await;
+            (table, result)
+        })
+        .await;
+        for (table, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&table, outcome),
+                Err(e) => {
+                    counts.fail(&table, &e);
+                    error!(table = %table, error = %e, "Failed to stop DynamoDB table");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(&self.tagging, "dynamodb:table", filter)
+            .await?;
+        info!(count = arns.len(), "Found DynamoDB tables to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let table = extract_table_name(arn);
+            let result = self.restore(arn, &table).await;
+            (table, result)
+        })
+        .await;
+        for (table, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&table, outcome),
+                Err(e) => {
+                    counts.fail(&table, &e);
+                    error!(table = %table, error = %e, "Failed to start DynamoDB table");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the DynamoDB tables matching the given filter: `on-demand`, `scaled-down`
+    /// when the table and its indexes run at the minimum capacity, or `provisioned`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns = filter_resources_by_tags::get_resources(&self.tagging, "dynamodb:table", filter)
+            .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let description = self.describe_table(&extract_table_name(&arn)).await?;
+            let state = if is_on_demand(&description) {
+                "on-demand"
+            } else {
+                let capacity = table_capacity(&description);
+                if capacity.table == MIN_CAPACITY
+                    && capacity.indexes.values().all(|c| *c == MIN_CAPACITY)
+                {
+                    "scaled-down"
+                } else {
+                    "provisioned"
+                }
+            };
+            items.push(InventoryItem::new(arn, "dynamodb:table", state));
+        }
+        Ok(items)
+    }
+}
+
+impl Capacity {
+    fn from_description(description: Option<&ProvisionedThroughputDescription>) -> Self {
+        Self {
+            read: description
+                .and_then(|d| d.read_capacity_units())
+                .unwrap_or_default(),
+            write: description
+                .and_then(|d| d.write_capacity_units())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Parse a capacity recorded as `read/write`.
+    fn parse(value: &str) -> Option<Self> {
+        let (read, write) = value.split_once('/')?;
+        Some(Self {
+            read: read.parse().ok()?,
+            write: write.parse().ok()?,
+        })
+    }
+
+    fn throughput(&self) -> Result<ProvisionedThroughput> {
+        Ok(ProvisionedThroughput::builder()
+            .read_capacity_units(self.read)
+            .write_capacity_units(self.write)
+            .build()?)
+    }
+}
+
+impl std::fmt::Display for Capacity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.read, self.write)
+    }
+}
+
+fn table_capacity(description: &TableDescription) -> TableCapacity {
+    TableCapacity {
+        table: Capacity::from_description(description.provisioned_throughput()),
+        indexes: description
+            .global_secondary_indexes()
+            .iter()
+            .filter_map(|index| {
+                Some((
+                    index.index_name()?.to_string(),
+                    Capacity::from_description(index.provisioned_throughput()),
+                ))
+            })
+            .collect(),
+    }
+}
+
+fn is_on_demand(description: &TableDescription) -> bool {
+    description
+        .billing_mode_summary()
+        .and_then(|b| b.billing_mode())
+        == Some(&BillingMode::PayPerRequest)
+}
+
+fn is_active(description: &TableDescription) -> bool {
+    description.table_status() == Some(&TableStatus::Active)
+}
+
+/// Build the Application Auto Scaling resource IDs of a table and of its global secondary indexes.
+fn scalable_resource_ids(table: &str, capacity: &TableCapacity) -> Vec<String> {
+    std::iter::once(format!("table/{}", table))
+        .chain(
+            capacity
+                .indexes
+                .keys()
+                .map(|index| format!("table/{}/index/{}", table, index)),
+        )
+        .collect()
+}
+
+/// Extract the table name from a DynamoDB table ARN.
+///
+/// Expected ARN format: `arn:partition:dynamodb:region:account:table/table-name`
+fn extract_table_name(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_applicationautoscaling::operation::describe_scalable_targets::DescribeScalableTargetsOutput;
+    use aws_sdk_applicationautoscaling::Client as AppAutoScalingClient;
+    use aws_sdk_dynamodb::operation::describe_table::DescribeTableOutput;
+    use aws_sdk_dynamodb::operation::update_table::UpdateTableOutput;
+    use aws_sdk_dynamodb::types::{BillingModeSummary, GlobalSecondaryIndexDescription};
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule, MemoryStore};
+
+    const TABLE_ARN: &str = "arn:aws:dynamodb:eu-west-1:123456789012:table/orders";
+
+    fn min_throughput() -> ProvisionedThroughputDescription {
+        ProvisionedThroughputDescription::builder()
+            .read_capacity_units(MIN_CAPACITY.read)
+            .write_capacity_units(MIN_CAPACITY.write)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn start_restores_the_recorded_table_and_index_capacity() {
+        let tables = get_resources_rule("dynamodb:table", &[TABLE_ARN]);
+        let describe = mock!(DynamoDbClient::describe_table)
+            .match_requests(|req| req.table_name() == Some("orders"))
+            .then_output(|| {
+                DescribeTableOutput::builder()
+                    .table(
+                        TableDescription::builder()
+                            .table_name("orders")
+                            .table_status(TableStatus::Active)
+                            .billing_mode_summary(
+                                BillingModeSummary::builder()
+                                    .billing_mode(BillingMode::Provisioned)
+                                    .build(),
+                            )
+                            .provisioned_throughput(min_throughput())
+                            .global_secondary_indexes(
+                                GlobalSecondaryIndexDescription::builder()
+                                    .index_name("by-customer")
+                                    .provisioned_throughput(min_throughput())
+                                    .build(),
+                            )
+                            .build(),
+                    )
+                    .build()
+            });
+        let update = mock!(DynamoDbClient::update_table)
+            .match_requests(|req| {
+                let table = req.provisioned_throughput();
+                let index = req
+                    .global_secondary_index_updates()
+                    .first()
+                    .and_then(|u| u.update());
+                table.map(|t| (t.read_capacity_units(), t.write_capacity_units())) == Some((10, 5))
+                    && index.map(|i| i.index_name()) == Some("by-customer")
+                    && index
+                        .and_then(|i| i.provisioned_throughput())
+                        .map(|t| (t.read_capacity_units(), t.write_capacity_units()))
+                        == Some((4, 2))
+            })
+            .then_output(|| UpdateTableOutput::builder().build());
+        let targets = mock!(AppAutoScalingClient::describe_scalable_targets)
+            .then_output(|| DescribeScalableTargetsOutput::builder().build());
+        let state = Arc::new(MemoryStore::with(
+            TABLE_ARN,
+            &[
+                (PREVIOUS_CAPACITY, "10/5"),
+                ("previous-capacity:by-customer", "4/2"),
+            ],
+        ));
+        let scheduler = DynamoDbScheduler {
+            dynamodb: mock_client!(aws_sdk_dynamodb, RuleMode::MatchAny, [&describe, &update]),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&tables]),
+            state,
+            app_autoscaling: AppAutoScaling::from_client(
+                mock_client!(aws_sdk_applicationautoscaling, [&targets]),
+                false,
+            ),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_records_the_table_and_index_capacity_then_scales_down() {
+        let tables = get_resources_rule("dynamodb:table", &[TABLE_ARN]);
+        let describe = mock!(DynamoDbClient::describe_table).then_output(|| {
+            DescribeTableOutput::builder()
+                .table(
+                    TableDescription::builder()
+                        .table_name("orders")
+                        .table_status(TableStatus::Active)
+                        .billing_mode_summary(
+                            BillingModeSummary::builder()
+                                .billing_mode(BillingMode::Provisioned)
+                                .build(),
+                        )
+                        .provisioned_throughput(
+                            ProvisionedThroughputDescription::builder()
+                                .read_capacity_units(10)
+                                .write_capacity_units(5)
+                                .build(),
+                        )
+                        .global_secondary_indexes(
+                            GlobalSecondaryIndexDescription::builder()
+                                .index_name("by-customer")
+                                .provisioned_throughput(
+                                    ProvisionedThroughputDescription::builder()
+                                        .read_capacity_units(4)
+                                        .write_capacity_units(2)
+                                        .build(),
+                                )
+                                .build(),
+                        )
+                        .build(),
+                )
+                .build()
+        });
+        let update = mock!(DynamoDbClient::update_table)
+            .match_requests(|req| {
+                let table = req.provisioned_throughput();
+                let index = req
+                    .global_secondary_index_updates()
+                    .first()
+                    .and_then(|u| u.update());
+                table.map(|t| (t.read_capacity_units(), t.write_capacity_units()))
+                    == Some((MIN_CAPACITY.read, MIN_CAPACITY.write))
+                    && index
+                        .and_then(|i| i.provisioned_throughput())
+                        .map(|t| (t.read_capacity_units(), t.write_capacity_units()))
+                        == Some((MIN_CAPACITY.read, MIN_CAPACITY.write))
+            })
+            .then_output(|| UpdateTableOutput::builder().build());
+        let targets = mock!(AppAutoScalingClient::describe_scalable_targets)
+            .then_output(|| DescribeScalableTargetsOutput::builder().build());
+        let state = Arc::new(MemoryStore::default());
+        let scheduler = DynamoDbScheduler {
+            dynamodb: mock_client!(aws_sdk_dynamodb, RuleMode::MatchAny, [&describe, &update]),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&tables]),
+            state: state.clone(),
+            app_autoscaling: AppAutoScaling::from_client(
+                mock_client!(aws_sdk_applicationautoscaling, [&targets]),
+                false,
+            ),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+        assert_eq!(
+            state.state(TABLE_ARN),
+            Some(State::from([
+                (PREVIOUS_CAPACITY.to_string(), "10/5".to_string()),
+                (
+                    "previous-capacity:by-customer".to_string(),
+                    "4/2".to_string()
+                ),
+            ]))
+        );
+    }
+}
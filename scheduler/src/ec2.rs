@@ -1,130 +1,796 @@
-use anyhow::Result;
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
 use aws_sdk_autoscaling::Client as AsgClient;
+use aws_sdk_ec2::types::{Instance, InstanceStateName};
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use aws_sdk_ssm::Client as SsmClient;
 use tracing::{error, info};
 
-use crate::filter_resources_by_tags;
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::config::Ec2StopMode;
+use crate::ec2_batch::{self, InstanceCall};
+use crate::ec2_shutdown::GracefulShutdown;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+use crate::waiters::{self, Waiter};
 
 /// Stop/start handler for EC2 instances in a given AWS region.
 ///
 /// Uses the Resource Groups Tagging API to discover instances matching a tag,
 /// then performs the requested action on each one.
 /// Instances belonging to an Auto Scaling Group are automatically skipped.
+///
+/// In [`Ec2StopMode::Hibernate`] mode, instances configured for hibernation are
+/// hibernated on stop, preserving their in-memory state; the others are stopped.
+///
+/// With a start waiter, the started instances are waited for until they are running.
+/// With a graceful shutdown, the OS of the instances is shut down through SSM before they are stopped.
 pub struct Ec2Scheduler {
     ec2: Ec2Client,
     asg: AsgClient,
     tagging: TaggingClient,
+    stop_mode: Ec2StopMode,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+    start_waiter: Option<Waiter>,
+    graceful_shutdown: Option<(SsmClient, GracefulShutdown)>,
 }
 
-/// Action to perform on an individual EC2 instance.
+/// Action to perform on the EC2 instances.
+#[derive(Clone, Copy)]
 enum Action {
     Stop,
     Start,
+    Terminate,
+}
+
+impl Action {
+    fn verb(self) -> &'static str {
+        match self {
+            Action::Stop => "stop",
+            Action::Start => "start",
+            Action::Terminate => "terminate",
+        }
+    }
 }
 
 impl Ec2Scheduler {
-    /// Create a new EC2 scheduler for the given region.
+    /// Create a new EC2 scheduler for the region of the given SDK configuration.
     ///
     /// Initializes AWS clients (EC2, Auto Scaling, Resource Groups Tagging)
-    /// with credentials resolved automatically by the SDK.
-    pub async fn new(region: &str) -> Self {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
+    /// sharing the credentials of `config`.
+    /// When `dry_run` is set, no mutating API call is made.
+    pub fn new(
+        config: &SdkConfig,
+        stop_mode: Ec2StopMode,
+        dry_run: bool,
+        max_concurrent_operations: usize,
+    ) -> Self {
+        Self::from_clients(
+            Ec2Client::new(config),
+            AsgClient::new(config),
+            TaggingClient::new(config),
+            stop_mode,
+            dry_run,
+            max_concurrent_operations,
+        )
+    }
 
+    /// Create a new EC2 scheduler from existing clients, e.g. clients with mocked responses.
+    pub fn from_clients(
+        ec2: Ec2Client,
+        asg: AsgClient,
+        tagging: TaggingClient,
+        stop_mode: Ec2StopMode,
+        dry_run: bool,
+        max_concurrent_operations: usize,
+    ) -> Self {
         Self {
-            ec2: Ec2Client::new(&config),
-            asg: AsgClient::new(&config),
-            tagging: TaggingClient::new(&config),
+            ec2,
+            asg,
+            tagging,
+            stop_mode,
+            dry_run,
+            max_concurrent_operations,
+            start_waiter: None,
+            graceful_shutdown: None,
         }
     }
 
-    /// Stop all EC2 instances matching the given tag.
+    /// Shut down the OS of the SSM-managed instances before stopping them (`EC2_GRACEFUL_SHUTDOWN`).
     ///
+    /// Hibernated instances are left out: shutting their OS down would lose the memory
+    /// that hibernation saves.
+    pub fn with_graceful_shutdown(mut self, ssm: SsmClient, shutdown: GracefulShutdown) -> Self {
+        self.graceful_shutdown = Some((ssm, shutdown));
+        self
+    }
+
+    /// Wait for the started instances to be running, recording those that are
+    /// still not running once `waiter` gives up as failed.
+    pub fn with_start_waiter(mut self, waiter: Waiter) -> Self {
+        self.start_waiter = Some(waiter);
+        self
+    }
+
+    /// Terminate all EC2 instances matching the given tag.
+    ///
+    /// Only instances carrying the termination confirmation tag are terminated.
     /// Instances belonging to an Auto Scaling Group are skipped.
     /// Errors on individual instances are logged without interrupting the processing.
-    pub async fn stop(&self, tag_key: &str, tag_value: &str) -> Result<()> {
+    pub async fn terminate(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
         let arns: Vec<String> =
-            filter_resources_by_tags::get_resources(&self.tagging, "ec2:instance", tag_key, tag_value).await?;
-        info!(count = arns.len(), "Found EC2 instances to stop");
+            filter_resources_by_tags::get_resources(&self.tagging, "ec2:instance", filter).await?;
+        info!(count = arns.len(), "Found EC2 instances to terminate");
+        filter.check_discovered(arns.len())?;
+
+        self.process(&arns, Action::Terminate).await
+    }
 
-        for arn in &arns {
-            let instance_id = extract_instance_id(arn);
-            if let Err(e) = self.process_instance(&instance_id, Action::Stop).await {
-                error!(instance_id = %instance_id, error = %e, "Failed to stop instance");
+    /// Perform an action on the instances of the given ARNs.
+    ///
+    /// Auto Scaling Group members are looked up for all instances at once and
+    /// skipped. The other instances are checked individually, then the ones left
+    /// to process are stopped/started/terminated in batched API calls.
+    async fn process(&self, arns: &[String], action: Action) -> Result<ResourceCounts> {
+        let mut counts = ResourceCounts::new(arns.len());
+        let instance_ids: Vec<String> = arns.iter().map(|arn| extract_instance_id(arn)).collect();
+        let asg_members = self.asg_members(&instance_ids).await?;
+
+        let mut candidates: Vec<String> = Vec::with_capacity(instance_ids.len());
+        for instance_id in instance_ids {
+            if asg_members.contains(&instance_id) {
+                info!(
+                    instance_id = %instance_id,
+                    "Skipping instance (belongs to Auto Scaling Group)"
+                );
+                counts.record(&instance_id, Outcome::Skipped);
+            } else {
+                candidates.push(instance_id);
+            }
+        }
+
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &candidates,
+            |instance_id| async move {
+                let result = self.check_instance(instance_id, action).await;
+                (instance_id.clone(), result)
+            },
+        )
+        .await;
+
+        let mut pending: Vec<(String, InstanceCall)> = Vec::new();
+        for (instance_id, result) in results {
+            match result {
+                Ok(Some(call)) => pending.push((instance_id, call)),
+                Ok(None) => counts.record(&instance_id, Outcome::Skipped),
+                Err(e) => {
+                    counts.fail(&instance_id, &e);
+                    error!(instance_id = %instance_id, error = %e, "Failed to {} instance", action.verb());
+                }
+            }
+        }
+
+        if self.dry_run {
+            for (instance_id, call) in &pending {
+                info!(instance_id = %instance_id, "Dry run: would {} instance", call.verb());
+                counts.record(instance_id, Outcome::Applied);
+            }
+            return Ok(counts);
+        }
+
+        let mut started: Vec<String> = Vec::new();
+        for call in [
+            InstanceCall::Hibernate,
+            InstanceCall::Stop,
+            InstanceCall::Start,
+            InstanceCall::Terminate,
+        ] {
+            let instance_ids: Vec<String> = pending
+                .iter()
+                .filter(|(_, c)| *c == call)
+                .map(|(id, _)| id.clone())
+                .collect();
+            // Only stopped instances are shut down first: hibernation saves the memory of the OS.
+            if let (InstanceCall::Stop, Some((ssm, shutdown))) = (call, &self.graceful_shutdown) {
+                if !instance_ids.is_empty() {
+                    shutdown.run(&self.ec2, ssm, &instance_ids).await;
+                }
             }
+            for (instance_id, result) in ec2_batch::apply(&self.ec2, &instance_ids, call).await {
+                match result {
+                    Ok(()) => {
+                        counts.record(&instance_id, Outcome::Applied);
+                        if call == InstanceCall::Start {
+                            started.push(instance_id);
+                        }
+                    }
+                    Err(e) => {
+                        counts.fail(&instance_id, &e);
+                        error!(instance_id = %instance_id, error = %e, "Failed to {} instance", call.verb());
+                    }
+                }
+            }
+        }
+
+        if let Some(waiter) = self.start_waiter.filter(|_| !started.is_empty()) {
+            self.wait_running(&started, waiter, &mut counts).await;
         }
 
-        Ok(())
+        Ok(counts)
     }
 
-    /// Start all EC2 instances matching the given tag.
+    /// Wait for the started instances to be running. Instances still not running
+    /// once the waiter gives up are recorded as failed; a failure to poll them is only logged.
+    async fn wait_running(
+        &self,
+        instance_ids: &[String],
+        waiter: Waiter,
+        counts: &mut ResourceCounts,
+    ) {
+        match waiters::wait_instances_running(&self.ec2, instance_ids, waiter).await {
+            Ok(pending) => {
+                for instance_id in pending {
+                    error!(instance_id = %instance_id, "Timed out waiting for instance to reach running state");
+                    counts.not_converged(
+                        &instance_id,
+                        format!(
+                            "not running after {} attempts (WAITER_MAX_ATTEMPTS)",
+                            waiter.max_attempts
+                        ),
+                    );
+                }
+            }
+            Err(e) => error!(error = %e, "Error while waiting for instances to be running"),
+        }
+    }
+
+    /// Return the IDs of the given instances that belong to an Auto Scaling Group.
     ///
-    /// Instances belonging to an Auto Scaling Group are skipped.
-    /// Errors on individual instances are logged without interrupting the processing.
-    pub async fn start(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let arns: Vec<String> =
-            filter_resources_by_tags::get_resources(&self.tagging, "ec2:instance", tag_key, tag_value).await?;
-        info!(count = arns.len(), "Found EC2 instances to start");
+    /// Instances are looked up in batches of [`ec2_batch::BATCH_SIZE`], the
+    /// maximum accepted by `DescribeAutoScalingInstances`.
+    async fn asg_members(&self, instance_ids: &[String]) -> Result<HashSet<String>> {
+        let mut members = HashSet::new();
 
-        for arn in &arns {
-            let instance_id = extract_instance_id(arn);
-            if let Err(e) = self.process_instance(&instance_id, Action::Start).await {
-                error!(instance_id = %instance_id, error = %e, "Failed to start instance");
+        for batch in instance_ids.chunks(ec2_batch::BATCH_SIZE) {
+            let mut next_token: Option<String> = None;
+            loop {
+                let mut req = self
+                    .asg
+                    .describe_auto_scaling_instances()
+                    .set_instance_ids(Some(batch.to_vec()));
+                if let Some(ref token) = next_token {
+                    req = req.next_token(token);
+                }
+                let resp = req
+                    .send()
+                    .await
+                    .context("Failed to look up Auto Scaling Group instances")?;
+
+                members.extend(
+                    resp.auto_scaling_instances()
+                        .iter()
+                        .filter_map(|i| i.instance_id())
+                        .map(String::from),
+                );
+
+                match resp.next_token() {
+                    Some(token) if !token.is_empty() => next_token = Some(token.to_string()),
+                    _ => break,
+                }
             }
         }
 
-        Ok(())
+        Ok(members)
     }
 
-    /// Process a single EC2 instance.
+    /// Check whether an action applies to a single EC2 instance.
     ///
-    /// Checks whether the instance belongs to an Auto Scaling Group first.
-    /// If so, the instance is skipped. Otherwise, the stop/start action is performed.
-    async fn process_instance(&self, instance_id: &str, action: Action) -> Result<()> {
-        let asg_response = self
-            .asg
-            .describe_auto_scaling_instances()
+    /// Instances already in the target state are skipped (`None`). Otherwise,
+    /// returns the API call to perform on the instance.
+    async fn check_instance(
+        &self,
+        instance_id: &str,
+        action: Action,
+    ) -> Result<Option<InstanceCall>> {
+        let instance = self.describe_instance(instance_id).await?;
+        let state = instance
+            .state()
+            .and_then(|s| s.name())
+            .cloned()
+            .with_context(|| format!("EC2 instance '{}' has no state", instance_id))?;
+        let already_done = match action {
+            Action::Stop => matches!(
+                state,
+                InstanceStateName::Stopped
+                    | InstanceStateName::Stopping
+                    | InstanceStateName::ShuttingDown
+                    | InstanceStateName::Terminated
+            ),
+            Action::Start => matches!(
+                state,
+                InstanceStateName::Running | InstanceStateName::Pending
+            ),
+            Action::Terminate => matches!(
+                state,
+                InstanceStateName::ShuttingDown | InstanceStateName::Terminated
+            ),
+        };
+        if already_done {
+            info!(instance_id = %instance_id, state = %state, "Instance already in target state, skipping");
+            return Ok(None);
+        }
+
+        let call = match action {
+            Action::Stop if self.stop_mode == Ec2StopMode::Hibernate => {
+                if hibernation_configured(&instance) {
+                    InstanceCall::Hibernate
+                } else {
+                    info!(instance_id = %instance_id, "Instance not configured for hibernation, stopping it instead");
+                    InstanceCall::Stop
+                }
+            }
+            Action::Stop => InstanceCall::Stop,
+            Action::Start => InstanceCall::Start,
+            Action::Terminate => InstanceCall::Terminate,
+        };
+        Ok(Some(call))
+    }
+
+    /// Describe a single EC2 instance.
+    async fn describe_instance(&self, instance_id: &str) -> Result<Instance> {
+        let resp = self
+            .ec2
+            .describe_instances()
             .instance_ids(instance_id)
             .send()
             .await?;
 
-        if !asg_response.auto_scaling_instances().is_empty() {
-            info!(
-                instance_id = %instance_id,
-                "Skipping instance (belongs to Auto Scaling Group)"
-            );
-            return Ok(());
-        }
+        resp.reservations()
+            .iter()
+            .flat_map(|r| r.instances())
+            .next()
+            .cloned()
+            .with_context(|| format!("EC2 instance '{}' not found", instance_id))
+    }
+}
 
-        match action {
-            Action::Stop => {
-                info!(instance_id = %instance_id, "Stopping instance");
-                self.ec2
-                    .stop_instances()
-                    .instance_ids(instance_id)
-                    .send()
-                    .await?;
-            }
-            Action::Start => {
-                info!(instance_id = %instance_id, "Starting instance");
-                self.ec2
-                    .start_instances()
-                    .instance_ids(instance_id)
-                    .send()
-                    .await?;
-            }
-        }
+#[async_trait]
+impl ResourceScheduler for Ec2Scheduler {
+    fn name(&self) -> &'static str {
+        "ec2"
+    }
 
-        Ok(())
+    /// Stop all EC2 instances matching the given tag.
+    ///
+    /// Instances belonging to an Auto Scaling Group are skipped.
+    /// Errors on individual instances are logged without interrupting the processing.
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns: Vec<String> =
+            filter_resources_by_tags::get_resources(&self.tagging, "ec2:instance", filter).await?;
+        info!(count = arns.len(), "Found EC2 instances to stop");
+        filter.check_discovered(arns.len())?;
+
+        self.process(&arns, Action::Stop).await
+    }
+
+    /// Start all EC2 instances matching the given tag.
+    ///
+    /// Instances belonging to an Auto Scaling Group are skipped.
+    /// Errors on individual instances are logged without interrupting the processing.
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns: Vec<String> =
+            filter_resources_by_tags::get_resources(&self.tagging, "ec2:instance", filter).await?;
+        info!(count = arns.len(), "Found EC2 instances to start");
+        filter.check_discovered(arns.len())?;
+
+        self.process(&arns, Action::Start).await
+    }
+
+    /// List the EC2 instances matching the given tag with their current state.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns: Vec<String> =
+            filter_resources_by_tags::get_resources(&self.tagging, "ec2:instance", filter).await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let instance = self.describe_instance(&extract_instance_id(&arn)).await?;
+            let state = instance
+                .state()
+                .and_then(|s| s.name())
+                .map(|n| n.as_str())
+                .unwrap_or("unknown");
+            items.push(InventoryItem::new(arn, "ec2:instance", state));
+        }
+        Ok(items)
     }
 }
 
+/// Whether an instance was launched with hibernation enabled.
+fn hibernation_configured(instance: &Instance) -> bool {
+    instance
+        .hibernation_options()
+        .and_then(|o| o.configured())
+        .unwrap_or_default()
+}
+
 /// Extract the instance ID from an EC2 ARN.
 ///
-/// Expected ARN format: `arn:aws:ec2:region:account:instance/i-xxxxx`
+/// Expected ARN format: `arn:partition:ec2:region:account:instance/i-xxxxx`
 fn extract_instance_id(arn: &str) -> String {
-    arn.split('/').last().unwrap_or(arn).to_string()
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use aws_sdk_autoscaling::operation::describe_auto_scaling_instances::DescribeAutoScalingInstancesOutput;
+    use aws_sdk_autoscaling::types::AutoScalingInstanceDetails;
+    use aws_sdk_ec2::error::ErrorMetadata;
+    use aws_sdk_ec2::operation::describe_instances::DescribeInstancesOutput;
+    use aws_sdk_ec2::operation::start_instances::StartInstancesOutput;
+    use aws_sdk_ec2::operation::stop_instances::{StopInstancesError, StopInstancesOutput};
+    use aws_sdk_ec2::types::{HibernationOptions, InstanceState, Reservation};
+    use aws_sdk_ssm::operation::describe_instance_information::{
+        DescribeInstanceInformationError, DescribeInstanceInformationOutput,
+    };
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    fn arn(instance_id: &str) -> String {
+        format!(
+            "arn:aws:ec2:eu-west-1:123456789012:instance/{}",
+            instance_id
+        )
+    }
+
+    /// Rule answering that the `members` instances belong to an Auto Scaling Group.
+    fn asg_rule(members: &'static [&'static str]) -> Rule {
+        mock!(AsgClient::describe_auto_scaling_instances).then_output(move || {
+            let mut output = DescribeAutoScalingInstancesOutput::builder();
+            for instance_id in members {
+                output = output.auto_scaling_instances(
+                    AutoScalingInstanceDetails::builder()
+                        .instance_id(*instance_id)
+                        .auto_scaling_group_name("web")
+                        .build(),
+                );
+            }
+            output.build()
+        })
+    }
+
+    /// Rule describing `instance_id` in the given state.
+    fn describe_rule(
+        instance_id: &'static str,
+        state: InstanceStateName,
+        hibernation: bool,
+    ) -> Rule {
+        mock!(Ec2Client::describe_instances)
+            .match_requests(move |req| req.instance_ids() == [instance_id])
+            .then_output(move || {
+                let instance = Instance::builder()
+                    .instance_id(instance_id)
+                    .state(InstanceState::builder().name(state.clone()).build())
+                    .hibernation_options(
+                        HibernationOptions::builder()
+                            .configured(hibernation)
+                            .build(),
+                    )
+                    .build();
+                DescribeInstancesOutput::builder()
+                    .reservations(Reservation::builder().instances(instance).build())
+                    .build()
+            })
+    }
+
+    fn stop_rule(instance_id: &'static str) -> Rule {
+        mock!(Ec2Client::stop_instances)
+            .match_requests(move |req| req.instance_ids() == [instance_id])
+            .then_output(|| StopInstancesOutput::builder().build())
+    }
+
+    #[tokio::test]
+    async fn stop_skips_asg_members_and_stopped_instances() {
+        let tagging = get_resources_rule(
+            "ec2:instance",
+            &[&arn("i-asg"), &arn("i-stopped"), &arn("i-running")],
+        );
+        let asg = asg_rule(&["i-asg"]);
+        let describe_stopped = describe_rule("i-stopped", InstanceStateName::Stopped, false);
+        let describe_running = describe_rule("i-running", InstanceStateName::Running, false);
+        let stop_running = stop_rule("i-running");
+        let scheduler = Ec2Scheduler::from_clients(
+            mock_client!(
+                aws_sdk_ec2,
+                RuleMode::MatchAny,
+                [&describe_stopped, &describe_running, &stop_running]
+            ),
+            mock_client!(aws_sdk_autoscaling, [&asg]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            Ec2StopMode::Stop,
+            false,
+            10,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (3, 1, 2, 0)
+        );
+        assert_eq!(asg.num_calls(), 1);
+        assert_eq!(stop_running.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_batches_instances() {
+        let arns: Vec<String> = (0..120).map(|i| arn(&format!("i-{:03}", i))).collect();
+        let tagging = get_resources_rule(
+            "ec2:instance",
+            &arns.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+        let asg = asg_rule(&[]);
+        let describe = mock!(Ec2Client::describe_instances).then_output(|| {
+            let instance = Instance::builder()
+                .state(
+                    InstanceState::builder()
+                        .name(InstanceStateName::Running)
+                        .build(),
+                )
+                .build();
+            DescribeInstancesOutput::builder()
+                .reservations(Reservation::builder().instances(instance).build())
+                .build()
+        });
+        let stop = mock!(Ec2Client::stop_instances)
+            .match_requests(|req| req.instance_ids().len() <= ec2_batch::BATCH_SIZE)
+            .then_output(|| StopInstancesOutput::builder().build());
+        let scheduler = Ec2Scheduler::from_clients(
+            mock_client!(aws_sdk_ec2, RuleMode::MatchAny, [&describe, &stop]),
+            mock_client!(aws_sdk_autoscaling, RuleMode::MatchAny, [&asg]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            Ec2StopMode::Stop,
+            false,
+            10,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (counts.discovered, counts.succeeded, counts.failed),
+            (120, 120, 0)
+        );
+        assert_eq!(asg.num_calls(), 3);
+        assert_eq!(stop.num_calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn stop_records_failures_without_interrupting_processing() {
+        let tagging = get_resources_rule("ec2:instance", &[&arn("i-failing"), &arn("i-running")]);
+        let asg = asg_rule(&[]);
+        let describe_failing = describe_rule("i-failing", InstanceStateName::Running, false);
+        let describe_running = describe_rule("i-running", InstanceStateName::Running, false);
+        let stop_failing = mock!(Ec2Client::stop_instances)
+            .match_requests(|req| req.instance_ids() == ["i-failing"])
+            .then_error(|| {
+                StopInstancesError::generic(
+                    ErrorMetadata::builder()
+                        .code("UnsupportedOperation")
+                        .build(),
+                )
+            });
+        let stop_batch = mock!(Ec2Client::stop_instances)
+            .match_requests(|req| req.instance_ids().len() > 1)
+            .then_error(|| {
+                StopInstancesError::generic(
+                    ErrorMetadata::builder()
+                        .code("UnsupportedOperation")
+                        .build(),
+                )
+            });
+        let stop_running = stop_rule("i-running");
+        let scheduler = Ec2Scheduler::from_clients(
+            mock_client!(
+                aws_sdk_ec2,
+                RuleMode::MatchAny,
+                [
+                    &describe_failing,
+                    &describe_running,
+                    &stop_batch,
+                    &stop_failing,
+                    &stop_running
+                ]
+            ),
+            mock_client!(aws_sdk_autoscaling, [&asg]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            Ec2StopMode::Stop,
+            false,
+            10,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 0, 1)
+        );
+        assert_eq!(stop_batch.num_calls(), 1);
+        assert_eq!(stop_running.num_calls(), 1);
+    }
+
+    fn graceful_shutdown() -> GracefulShutdown {
+        GracefulShutdown {
+            command: "shutdown -h now".to_string(),
+            wait: Duration::from_secs(600),
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_stops_instances_when_the_graceful_shutdown_fails() {
+        let tagging = get_resources_rule("ec2:instance", &[&arn("i-running")]);
+        let asg = asg_rule(&[]);
+        let describe = describe_rule("i-running", InstanceStateName::Running, false);
+        let stop = stop_rule("i-running");
+        let information = mock!(SsmClient::describe_instance_information).then_error(|| {
+            DescribeInstanceInformationError::generic(
+                ErrorMetadata::builder()
+                    .code("AccessDeniedException")
+                    .build(),
+            )
+        });
+        let scheduler = Ec2Scheduler::from_clients(
+            mock_client!(aws_sdk_ec2, RuleMode::MatchAny, [&describe, &stop]),
+            mock_client!(aws_sdk_autoscaling, [&asg]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            Ec2StopMode::Stop,
+            false,
+            10,
+        )
+        .with_graceful_shutdown(
+            mock_client!(aws_sdk_ssm, [&information]),
+            graceful_shutdown(),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!((counts.succeeded, counts.failed), (1, 0));
+        assert_eq!(information.num_calls(), 1);
+        assert_eq!(stop.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_does_not_shut_down_hibernated_instances() {
+        let tagging = get_resources_rule("ec2:instance", &[&arn("i-hibernate")]);
+        let asg = asg_rule(&[]);
+        let describe = describe_rule("i-hibernate", InstanceStateName::Running, true);
+        let hibernate = mock!(Ec2Client::stop_instances)
+            .match_requests(|req| {
+                req.instance_ids() == ["i-hibernate"] && req.hibernate() == Some(true)
+            })
+            .then_output(|| StopInstancesOutput::builder().build());
+        let information = mock!(SsmClient::describe_instance_information)
+            .then_output(|| DescribeInstanceInformationOutput::builder().build());
+        let scheduler = Ec2Scheduler::from_clients(
+            mock_client!(aws_sdk_ec2, RuleMode::MatchAny, [&describe, &hibernate]),
+            mock_client!(aws_sdk_autoscaling, [&asg]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            Ec2StopMode::Hibernate,
+            false,
+            10,
+        )
+        .with_graceful_shutdown(
+            mock_client!(aws_sdk_ssm, [&information]),
+            graceful_shutdown(),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!((counts.succeeded, counts.failed), (1, 0));
+        assert_eq!(information.num_calls(), 0);
+        assert_eq!(hibernate.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_refuses_more_instances_than_max_resources() {
+        let tagging = get_resources_rule("ec2:instance", &[&arn("i-1"), &arn("i-2")]);
+        let asg = asg_rule(&[]);
+        let stop = stop_rule("i-1");
+        let scheduler = Ec2Scheduler::from_clients(
+            mock_client!(aws_sdk_ec2, [&stop]),
+            mock_client!(aws_sdk_autoscaling, [&asg]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            Ec2StopMode::Stop,
+            false,
+            10,
+        );
+        let filter = ResourceFilter {
+            max_resources: Some(1),
+            ..filter(ScheduleAction::Stop)
+        };
+
+        let result = scheduler.stop(&filter).await;
+
+        assert!(result.is_err());
+        assert_eq!(asg.num_calls(), 0);
+        assert_eq!(stop.num_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn stop_hibernates_configured_instances() {
+        let tagging = get_resources_rule("ec2:instance", &[&arn("i-hibernating")]);
+        let asg = asg_rule(&[]);
+        let describe = describe_rule("i-hibernating", InstanceStateName::Running, true);
+        let hibernate = mock!(Ec2Client::stop_instances)
+            .match_requests(|req| req.hibernate() == Some(true))
+            .then_output(|| StopInstancesOutput::builder().build());
+        let scheduler = Ec2Scheduler::from_clients(
+            mock_client!(aws_sdk_ec2, RuleMode::MatchAny, [&describe, &hibernate]),
+            mock_client!(aws_sdk_autoscaling, [&asg]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            Ec2StopMode::Hibernate,
+            false,
+            10,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(counts.succeeded, 1);
+        assert_eq!(hibernate.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_makes_no_call_in_dry_run() {
+        let tagging = get_resources_rule("ec2:instance", &[&arn("i-stopped")]);
+        let asg = asg_rule(&[]);
+        let describe = describe_rule("i-stopped", InstanceStateName::Stopped, false);
+        let start = mock!(Ec2Client::start_instances)
+            .then_output(|| StartInstancesOutput::builder().build());
+        let scheduler = Ec2Scheduler::from_clients(
+            mock_client!(aws_sdk_ec2, RuleMode::MatchAny, [&describe, &start]),
+            mock_client!(aws_sdk_autoscaling, [&asg]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            Ec2StopMode::Stop,
+            true,
+            10,
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(counts.succeeded, 1);
+        assert_eq!(start.num_calls(), 0);
+    }
+
+    #[test]
+    fn extract_instance_id_reads_the_last_arn_segment() {
+        assert_eq!(
+            extract_instance_id(&arn("i-0123456789abcdef0")),
+            "i-0123456789abcdef0"
+        );
+    }
 }
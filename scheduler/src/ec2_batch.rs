@@ -0,0 +1,85 @@
+use anyhow::Result;
+use aws_sdk_ec2::Client as Ec2Client;
+use tracing::{info, warn};
+
+/// Maximum number of instance IDs sent in a single EC2 API call.
+pub const BATCH_SIZE: usize = 50;
+
+/// EC2 API call applied to a batch of instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceCall {
+    Stop,
+    Hibernate,
+    Start,
+    Terminate,
+}
+
+impl InstanceCall {
+    /// Verb describing the call, e.g. `stop`.
+    pub fn verb(self) -> &'static str {
+        match self {
+            InstanceCall::Stop => "stop",
+            InstanceCall::Hibernate => "hibernate",
+            InstanceCall::Start => "start",
+            InstanceCall::Terminate => "terminate",
+        }
+    }
+}
+
+/// Stop, hibernate, start or terminate instances with one API call per batch of [`BATCH_SIZE`].
+///
+/// EC2 rejects a whole call when any of its instances is in an incompatible
+/// state, so the instances of a failed batch are retried one at a time: the
+/// error is then reported only for the faulty ones.
+///
+/// Returns the result of every instance, in the order of `instance_ids`.
+pub async fn apply(
+    ec2: &Ec2Client,
+    instance_ids: &[String],
+    call: InstanceCall,
+) -> Vec<(String, Result<()>)> {
+    let mut results = Vec::with_capacity(instance_ids.len());
+
+    for batch in instance_ids.chunks(BATCH_SIZE) {
+        info!(count = batch.len(), instances = ?batch, "Requesting instances to {}", call.verb());
+        match send(ec2, batch, call).await {
+            Ok(()) => results.extend(batch.iter().map(|id| (id.clone(), Ok(())))),
+            Err(e) if batch.len() == 1 => results.push((batch[0].clone(), Err(e))),
+            Err(e) => {
+                warn!(count = batch.len(), error = %e, "Batch {} failed, retrying instances one at a time", call.verb());
+                for id in batch {
+                    let result = send(ec2, std::slice::from_ref(id), call).await;
+                    results.push((id.clone(), result));
+                }
+            }
+        }
+    }
+
+    results
+}
+
+async fn send(ec2: &Ec2Client, instance_ids: &[String], call: InstanceCall) -> Result<()> {
+    let ids = Some(instance_ids.to_vec());
+    match call {
+        InstanceCall::Stop => {
+            ec2.stop_instances().set_instance_ids(ids).send().await?;
+        }
+        InstanceCall::Hibernate => {
+            ec2.stop_instances()
+                .set_instance_ids(ids)
+                .hibernate(true)
+                .send()
+                .await?;
+        }
+        InstanceCall::Start => {
+            ec2.start_instances().set_instance_ids(ids).send().await?;
+        }
+        InstanceCall::Terminate => {
+            ec2.terminate_instances()
+                .set_instance_ids(ids)
+                .send()
+                .await?;
+        }
+    }
+    Ok(())
+}
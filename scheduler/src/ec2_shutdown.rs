@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use aws_sdk_ec2::types::InstanceStateName;
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_ssm::types::{
+    CommandStatus, InstanceInformationStringFilter, PingStatus, PlatformType,
+};
+use aws_sdk_ssm::Client as SsmClient;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::config::AppConfig;
+use crate::ec2_batch::BATCH_SIZE;
+
+/// SSM document running the shutdown command.
+const DOCUMENT: &str = "AWS-RunShellScript";
+/// Time between two checks of the shutdown command and of the instance states.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Graceful OS shutdown sent through SSM Run Command to the instances about to be
+/// stopped (`EC2_GRACEFUL_SHUTDOWN`), so that the applications they run, such as
+/// databases, are stopped cleanly before EC2 stops the instances.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GracefulShutdown {
+    /// Shell command run on each instance (`EC2_SHUTDOWN_COMMAND`).
+    pub command: String,
+    /// Longest time the command is waited for (`EC2_SHUTDOWN_WAIT_SECONDS`).
+    pub wait: Duration,
+}
+
+impl GracefulShutdown {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            command: config.ec2_shutdown_command.clone(),
+            wait: Duration::from_secs(config.ec2_shutdown_wait_seconds),
+        }
+    }
+
+    /// Run the shutdown command on the SSM-managed Linux instances among `instance_ids`,
+    /// then wait until it completed or the instances are no longer running, up to `wait`.
+    ///
+    /// Instances that are not managed by SSM are left to the EC2 stop. Errors are only
+    /// logged: the instances are stopped whatever the outcome of the command.
+    pub async fn run(&self, ec2: &Ec2Client, ssm: &SsmClient, instance_ids: &[String]) {
+        if let Err(e) = self.shutdown(ec2, ssm, instance_ids).await {
+            warn!(error = %e, "Graceful OS shutdown failed, stopping instances anyway");
+        }
+    }
+
+    async fn shutdown(
+        &self,
+        ec2: &Ec2Client,
+        ssm: &SsmClient,
+        instance_ids: &[String],
+    ) -> Result<()> {
+        let managed = managed_instances(ssm, instance_ids).await?;
+        if managed.is_empty() {
+            info!("No SSM-managed Linux instance to shut down gracefully");
+            return Ok(());
+        }
+
+        let mut command_ids = Vec::new();
+        for batch in managed.chunks(BATCH_SIZE) {
+            let resp = ssm
+                .send_command()
+                .document_name(DOCUMENT)
+                .set_instance_ids(Some(batch.to_vec()))
+                .parameters("commands", vec![self.command.clone()])
+                .comment("Graceful OS shutdown before scheduled stop")
+                .send()
+                .await
+                .context("Failed to send graceful OS shutdown command")?;
+            command_ids.extend(
+                resp.command()
+                    .and_then(|c| c.command_id())
+                    .map(String::from),
+            );
+        }
+        info!(
+            instances = managed.len(),
+            wait = self.wait.as_secs(),
+            "Sent graceful OS shutdown command, waiting before stopping instances"
+        );
+
+        let deadline = Instant::now() + self.wait;
+        loop {
+            if !commands_pending(ssm, &command_ids).await? {
+                info!("Graceful OS shutdown command completed");
+                return Ok(());
+            }
+            if running_instances(ec2, &managed).await?.is_empty() {
+                info!("Instances shut down gracefully");
+                return Ok(());
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                warn!(
+                    wait = self.wait.as_secs(),
+                    "Graceful OS shutdown still running after EC2_SHUTDOWN_WAIT_SECONDS, stopping instances"
+                );
+                return Ok(());
+            }
+            tokio::time::sleep(POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+}
+
+/// Instances among `instance_ids` whose SSM agent is online on a Linux (or macOS)
+/// platform, able to run [`DOCUMENT`].
+async fn managed_instances(ssm: &SsmClient, instance_ids: &[String]) -> Result<Vec<String>> {
+    let mut managed = Vec::new();
+    for batch in instance_ids.chunks(BATCH_SIZE) {
+        let filter = InstanceInformationStringFilter::builder()
+            .key("InstanceIds")
+            .set_values(Some(batch.to_vec()))
+            .build()?;
+        let mut next_token: Option<String> = None;
+        loop {
+            let resp = ssm
+                .describe_instance_information()
+                .filters(filter.clone())
+                .set_next_token(next_token.take())
+                .send()
+                .await
+                .context("Failed to look up SSM-managed instances")?;
+            for info in resp.instance_information_list() {
+                let Some(instance_id) = info.instance_id() else {
+                    continue;
+                };
+                match (info.ping_status(), info.platform_type()) {
+                    (Some(PingStatus::Online), Some(PlatformType::Linux | PlatformType::Macos)) => {
+                        managed.push(instance_id.to_string());
+                    }
+                    (Some(PingStatus::Online), _) => {
+                        info!(instance_id = %instance_id, "Not a Linux instance, skipping graceful OS shutdown");
+                    }
+                    _ => {
+                        info!(instance_id = %instance_id, "SSM agent is not online, skipping graceful OS shutdown")
+                    }
+                }
+            }
+            match resp.next_token() {
+                Some(token) if !token.is_empty() => next_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+    }
+    Ok(managed)
+}
+
+/// Whether any of the commands is still pending or in progress. Commands that
+/// ended on an error are logged.
+async fn commands_pending(ssm: &SsmClient, command_ids: &[String]) -> Result<bool> {
+    let mut pending = false;
+    for command_id in command_ids {
+        let resp = ssm.list_commands().command_id(command_id).send().await?;
+        match resp.commands().first().and_then(|c| c.status()) {
+            Some(CommandStatus::Pending | CommandStatus::InProgress) => pending = true,
+            Some(CommandStatus::Success) | None => {}
+            Some(status) => warn!(
+                command_id = %command_id,
+                status = status.as_str(),
+                "Graceful OS shutdown command did not succeed on every instance"
+            ),
+        }
+    }
+    Ok(pending)
+}
+
+/// Instances among `instance_ids` that are still in the `running` state.
+async fn running_instances(ec2: &Ec2Client, instance_ids: &[String]) -> Result<HashSet<String>> {
+    let mut running = HashSet::new();
+    for batch in instance_ids.chunks(BATCH_SIZE) {
+        let resp = ec2
+            .describe_instance_status()
+            .include_all_instances(true)
+            .set_instance_ids(Some(batch.to_vec()))
+            .send()
+            .await?;
+        running.extend(
+            resp.instance_statuses()
+                .iter()
+                .filter(|s| {
+                    s.instance_state().and_then(|st| st.name()) == Some(&InstanceStateName::Running)
+                })
+                .filter_map(|s| s.instance_id())
+                .map(String::from),
+        );
+    }
+    Ok(running)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_ec2::operation::describe_instance_status::DescribeInstanceStatusOutput;
+    use aws_sdk_ec2::types::{InstanceState, InstanceStatus};
+    use aws_sdk_ssm::operation::describe_instance_information::DescribeInstanceInformationOutput;
+    use aws_sdk_ssm::operation::list_commands::ListCommandsOutput;
+    use aws_sdk_ssm::operation::send_command::SendCommandOutput;
+    use aws_sdk_ssm::types::{Command, InstanceInformation};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+
+    fn graceful_shutdown() -> GracefulShutdown {
+        GracefulShutdown {
+            command: "shutdown -h now".to_string(),
+            wait: Duration::from_secs(600),
+        }
+    }
+
+    fn instance_ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    /// Rule describing the SSM agent of each instance.
+    fn information_rule(instances: Vec<(&'static str, PingStatus, PlatformType)>) -> Rule {
+        mock!(SsmClient::describe_instance_information).then_output(move || {
+            let information = instances.iter().map(|(instance_id, ping, platform)| {
+                InstanceInformation::builder()
+                    .instance_id(*instance_id)
+                    .ping_status(ping.clone())
+                    .platform_type(platform.clone())
+                    .build()
+            });
+            DescribeInstanceInformationOutput::builder()
+                .set_instance_information_list(Some(information.collect()))
+                .build()
+        })
+    }
+
+    /// Rule describing `instance_id` in the given state.
+    fn status_rule(instance_id: &'static str, state: InstanceStateName) -> Rule {
+        mock!(Ec2Client::describe_instance_status)
+            .match_requests(move |req| req.instance_ids() == [instance_id])
+            .then_output(move || {
+                DescribeInstanceStatusOutput::builder()
+                    .instance_statuses(
+                        InstanceStatus::builder()
+                            .instance_id(instance_id)
+                            .instance_state(InstanceState::builder().name(state.clone()).build())
+                            .build(),
+                    )
+                    .build()
+            })
+    }
+
+    #[tokio::test]
+    async fn managed_instances_keeps_online_linux_and_macos_instances() {
+        let information = information_rule(vec![
+            ("i-linux", PingStatus::Online, PlatformType::Linux),
+            ("i-macos", PingStatus::Online, PlatformType::Macos),
+            ("i-windows", PingStatus::Online, PlatformType::Windows),
+            ("i-offline", PingStatus::ConnectionLost, PlatformType::Macos),
+        ]);
+
+        let managed = managed_instances(
+            &mock_client!(aws_sdk_ssm, [&information]),
+            &instance_ids(&["i-linux", "i-macos", "i-windows", "i-offline"]),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(managed, ["i-linux", "i-macos"]);
+    }
+
+    #[tokio::test]
+    async fn shutdown_skips_unmanaged_and_windows_instances() {
+        let information = information_rule(vec![
+            ("i-windows", PingStatus::Online, PlatformType::Windows),
+            ("i-offline", PingStatus::ConnectionLost, PlatformType::Linux),
+        ]);
+        let send =
+            mock!(SsmClient::send_command).then_output(|| SendCommandOutput::builder().build());
+        let status = status_rule("i-windows", InstanceStateName::Running);
+
+        graceful_shutdown()
+            .shutdown(
+                &mock_client!(aws_sdk_ec2, [&status]),
+                &mock_client!(aws_sdk_ssm, RuleMode::MatchAny, [&information, &send]),
+                &instance_ids(&["i-windows", "i-offline"]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(send.num_calls(), 0);
+        assert_eq!(status.num_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn shutdown_returns_once_the_instances_are_no_longer_running() {
+        let information = information_rule(vec![
+            ("i-linux", PingStatus::Online, PlatformType::Linux),
+            ("i-windows", PingStatus::Online, PlatformType::Windows),
+        ]);
+        let send = mock!(SsmClient::send_command)
+            .match_requests(|req| req.instance_ids() == ["i-linux"])
+            .then_output(|| {
+                SendCommandOutput::builder()
+                    .command(Command::builder().command_id("cmd-1").build())
+                    .build()
+            });
+        let list = mock!(SsmClient::list_commands)
+            .match_requests(|req| req.command_id() == Some("cmd-1"))
+            .then_output(|| {
+                ListCommandsOutput::builder()
+                    .commands(
+                        Command::builder()
+                            .command_id("cmd-1")
+                            .status(CommandStatus::InProgress)
+                            .build(),
+                    )
+                    .build()
+            });
+        let status = status_rule("i-linux", InstanceStateName::Stopping);
+
+        graceful_shutdown()
+            .shutdown(
+                &mock_client!(aws_sdk_ec2, [&status]),
+                &mock_client!(
+                    aws_sdk_ssm,
+                    RuleMode::MatchAny,
+                    [&information, &send, &list]
+                ),
+                &instance_ids(&["i-linux", "i-windows"]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(send.num_calls(), 1);
+        assert_eq!(list.num_calls(), 1);
+        assert_eq!(status.num_calls(), 1);
+    }
+}
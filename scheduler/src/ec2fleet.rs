@@ -0,0 +1,531 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_ec2::types::{
+    BatchState, FleetStateCode, FleetType, Tag, TargetCapacitySpecificationRequest,
+};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Tags used to remember the target capacity of a fleet before it was stopped.
+const PREVIOUS_TARGET_CAPACITY_TAG: &str = "scheduler:previous-target-capacity";
+const PREVIOUS_ON_DEMAND_CAPACITY_TAG: &str = "scheduler:previous-on-demand-target-capacity";
+
+/// Stop/start handler for EC2 Fleets and Spot Fleet requests in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover fleets matching a tag.
+/// On stop: records the total and On-Demand target capacity in tags, then sets both to 0.
+/// On start: restores the recorded target capacity.
+/// Only fleets of type `maintain` can be modified; other fleets are skipped.
+pub struct Ec2FleetScheduler {
+    ec2: Ec2Client,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+/// Kind of fleet, as both are modified through different APIs.
+#[derive(Debug, Clone, Copy)]
+enum FleetKind {
+    Ec2Fleet,
+    SpotFleet,
+}
+
+/// Current state of a fleet.
+struct Fleet {
+    /// Whether the fleet is active and of type `maintain`, i.e. can be modified.
+    modifiable: bool,
+    capacity: Capacity,
+    tags: HashMap<String, String>,
+}
+
+/// Total and On-Demand target capacity of a fleet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Capacity {
+    total: i32,
+    on_demand: i32,
+}
+
+impl Ec2FleetScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            ec2: Ec2Client::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Discover the EC2 Fleets and Spot Fleet requests matching the filter.
+    async fn discover(&self, filter: &ResourceFilter) -> Result<Vec<(FleetKind, String)>> {
+        let fleet_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ec2:fleet", filter).await?;
+        let spot_fleet_arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "ec2:spot-fleet-request",
+            filter,
+        )
+        .await?;
+
+        Ok(fleet_arns
+            .iter()
+            .map(|arn| (FleetKind::Ec2Fleet, extract_fleet_id(arn)))
+            .chain(
+                spot_fleet_arns
+                    .iter()
+                    .map(|arn| (FleetKind::SpotFleet, extract_fleet_id(arn))),
+            )
+            .collect())
+    }
+
+    /// Record the target capacity of a fleet in tags, then set it to 0.
+    /// A fleet with a target capacity of 0 is skipped, as tagging it again would
+    /// replace the capacity that the next start sets back.
+    async fn scale_to_zero(&self, kind: FleetKind, fleet_id: &str) -> Result<Outcome> {
+        let fleet = self.describe(kind, fleet_id).await?;
+        let current = fleet.capacity;
+
+        if current.total == 0 {
+            info!(fleet = %fleet_id, "EC2 fleet already scaled to 0, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if !fleet.modifiable {
+            info!(fleet = %fleet_id, "EC2 fleet is not an active fleet of type maintain, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(fleet = %fleet_id, capacity = ?current, "Dry run: would scale EC2 fleet to 0");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(fleet = %fleet_id, capacity = ?current, "Recording previous target capacity");
+        self.ec2
+            .create_tags()
+            .resources(fleet_id)
+            .tags(ec2_tag(
+                PREVIOUS_TARGET_CAPACITY_TAG,
+                &current.total.to_string(),
+            ))
+            .tags(ec2_tag(
+                PREVIOUS_ON_DEMAND_CAPACITY_TAG,
+                &current.on_demand.to_string(),
+            ))
+            .send()
+            .await?;
+
+        info!(fleet = %fleet_id, "Scaling EC2 fleet to 0");
+        self.modify(
+            kind,
+            fleet_id,
+            Capacity {
+                total: 0,
+                on_demand: 0,
+            },
+        )
+        .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the target capacity recorded on stop.
+    ///
+    /// Fleets with a non-zero target capacity, or without a recorded
+    /// capacity, are left untouched.
+    async fn restore_capacity(&self, kind: FleetKind, fleet_id: &str) -> Result<Outcome> {
+        let fleet = self.describe(kind, fleet_id).await?;
+        if fleet.capacity.total > 0 {
+            info!(fleet = %fleet_id, capacity = ?fleet.capacity, "EC2 fleet already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let Some(total) = fleet
+            .tags
+            .get(PREVIOUS_TARGET_CAPACITY_TAG)
+            .and_then(|v| v.parse::<i32>().ok())
+        else {
+            warn!(fleet = %fleet_id, "No recorded target capacity found, skipping EC2 fleet");
+            return Ok(Outcome::Skipped);
+        };
+        let previous = Capacity {
+            total,
+            on_demand: fleet
+                .tags
+                .get(PREVIOUS_ON_DEMAND_CAPACITY_TAG)
+                .and_then(|v| v.parse::<i32>().ok())
+                .unwrap_or_default(),
+        };
+
+        if !fleet.modifiable {
+            info!(fleet = %fleet_id, "EC2 fleet is not an active fleet of type maintain, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(fleet = %fleet_id, capacity = ?previous, "Dry run: would restore EC2 fleet target capacity");
+            return Ok(Outcome::Applied);
+        }
+        info!(fleet = %fleet_id, capacity = ?previous, "Restoring EC2 fleet target capacity");
+        self.modify(kind, fleet_id, previous).await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn describe(&self, kind: FleetKind, fleet_id: &str) -> Result<Fleet> {
+        match kind {
+            FleetKind::Ec2Fleet => {
+                let resp = self
+                    .ec2
+                    .describe_fleets()
+                    .fleet_ids(fleet_id)
+                    .send()
+                    .await?;
+                let fleet = resp
+                    .fleets()
+                    .first()
+                    .with_context(|| format!("EC2 Fleet '{}' not found", fleet_id))?;
+                let target = fleet.target_capacity_specification();
+
+                Ok(Fleet {
+                    modifiable: fleet.fleet_state() == Some(&FleetStateCode::Active)
+                        && fleet.r#type() == Some(&FleetType::Maintain),
+                    capacity: Capacity {
+                        total: target
+                            .and_then(|t| t.total_target_capacity())
+                            .unwrap_or_default(),
+                        on_demand: target
+                            .and_then(|t| t.on_demand_target_capacity())
+                            .unwrap_or_default(),
+                    },
+                    tags: tag_map(fleet.tags()),
+                })
+            }
+            FleetKind::SpotFleet => {
+                let resp = self
+                    .ec2
+                    .describe_spot_fleet_requests()
+                    .spot_fleet_request_ids(fleet_id)
+                    .send()
+                    .await?;
+                let request = resp
+                    .spot_fleet_request_configs()
+                    .first()
+                    .with_context(|| format!("Spot Fleet request '{}' not found", fleet_id))?;
+                let config = request.spot_fleet_request_config();
+
+                Ok(Fleet {
+                    modifiable: request.spot_fleet_request_state() == Some(&BatchState::Active)
+                        && config.and_then(|c| c.r#type()) == Some(&FleetType::Maintain),
+                    capacity: Capacity {
+                        total: config.and_then(|c| c.target_capacity()).unwrap_or_default(),
+                        on_demand: config
+                            .and_then(|c| c.on_demand_target_capacity())
+                            .unwrap_or_default(),
+                    },
+                    tags: tag_map(request.tags()),
+                })
+            }
+        }
+    }
+
+    async fn modify(&self, kind: FleetKind, fleet_id: &str, capacity: Capacity) -> Result<()> {
+        match kind {
+            FleetKind::Ec2Fleet => {
+                self.ec2
+                    .modify_fleet()
+                    .fleet_id(fleet_id)
+                    .target_capacity_specification(
+                        TargetCapacitySpecificationRequest::builder()
+                            .total_target_capacity(capacity.total)
+                            .on_demand_target_capacity(capacity.on_demand)
+                            .build(),
+                    )
+                    .send()
+                    .await?;
+            }
+            FleetKind::SpotFleet => {
+                self.ec2
+                    .modify_spot_fleet_request()
+                    .spot_fleet_request_id(fleet_id)
+                    .target_capacity(capacity.total)
+                    .on_demand_target_capacity(capacity.on_demand)
+                    .send()
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for Ec2FleetScheduler {
+    fn name(&self) -> &'static str {
+        "ec2_fleet"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let fleets = self.discover(filter).await?;
+        info!(count = fleets.len(), "Found EC2 fleets to stop");
+
+        filter.check_discovered(fleets.len())?;
+        let mut counts = ResourceCounts::new(fleets.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &fleets,
+            |(kind, fleet_id)| async move {
+                let result = self.scale_to_zero(*kind, fleet_id).await;
+                (fleet_id, result)
+            },
+        )
+        .await;
+        for (fleet_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(fleet_id, outcome),
+                Err(e) => {
+                    counts.fail(fleet_id, &e);
+                    error!(fleet = %fleet_id, error = %e, "Failed to stop EC2 fleet");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let fleets = self.discover(filter).await?;
+        info!(count = fleets.len(), "Found EC2 fleets to start");
+
+        filter.check_discovered(fleets.len())?;
+        let mut counts = ResourceCounts::new(fleets.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &fleets,
+            |(kind, fleet_id)| async move {
+                let result = self.restore_capacity(*kind, fleet_id).await;
+                (fleet_id, result)
+            },
+        )
+        .await;
+        for (fleet_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(fleet_id, outcome),
+                Err(e) => {
+                    counts.fail(fleet_id, &e);
+                    error!(fleet = %fleet_id, error = %e, "Failed to start EC2 fleet");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the EC2 Fleets and Spot Fleet requests matching the given filter,
+    /// `scaled-to-zero` when their target capacity is 0 and `running` otherwise.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let fleet_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ec2:fleet", filter).await?;
+        let spot_fleet_arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "ec2:spot-fleet-request",
+            filter,
+        )
+        .await?;
+
+        let mut items = Vec::with_capacity(fleet_arns.len() + spot_fleet_arns.len());
+        let fleets = fleet_arns
+            .into_iter()
+            .map(|arn| (FleetKind::Ec2Fleet, "ec2:fleet", arn))
+            .chain(
+                spot_fleet_arns
+                    .into_iter()
+                    .map(|arn| (FleetKind::SpotFleet, "ec2:spot-fleet-request", arn)),
+            );
+        for (kind, resource_type, arn) in fleets {
+            let fleet = self.describe(kind, &extract_fleet_id(&arn)).await?;
+            let state = if fleet.capacity.total == 0 {
+                "scaled-to-zero"
+            } else {
+                "running"
+            };
+            items.push(InventoryItem::new(arn, resource_type, state));
+        }
+        Ok(items)
+    }
+}
+
+fn tag_map(tags: &[Tag]) -> HashMap<String, String> {
+    tags.iter()
+        .filter_map(|t| {
+            Some((
+                t.key()?.to_string(),
+                t.value().unwrap_or_default().to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn ec2_tag(key: &str, value: &str) -> Tag {
+    Tag::builder().key(key).value(value).build()
+}
+
+/// Extract the fleet ID from an EC2 Fleet or Spot Fleet request ARN.
+///
+/// Expected ARN formats:
+/// - EC2 Fleet: `arn:partition:ec2:region:account:fleet/fleet-id`
+/// - Spot Fleet request: `arn:partition:ec2:region:account:spot-fleet-request/sfr-id`
+fn extract_fleet_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_ec2::operation::create_tags::CreateTagsOutput;
+    use aws_sdk_ec2::operation::describe_fleets::DescribeFleetsOutput;
+    use aws_sdk_ec2::operation::modify_fleet::ModifyFleetOutput;
+    use aws_sdk_ec2::types::{FleetData, TargetCapacitySpecification};
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const FLEET_ARN: &str =
+        "arn:aws:ec2:eu-west-1:123456789012:fleet/fleet-12345678-90ab-cdef-1234-567890abcdef";
+
+    #[tokio::test]
+    async fn start_restores_the_tagged_target_capacity() {
+        let fleets = get_resources_rule("ec2:fleet", &[FLEET_ARN]);
+        let spot_fleets = get_resources_rule("ec2:spot-fleet-request", &[]);
+        let describe = mock!(Ec2Client::describe_fleets).then_output(|| {
+            DescribeFleetsOutput::builder()
+                .fleets(
+                    FleetData::builder()
+                        .fleet_id("fleet-12345678-90ab-cdef-1234-567890abcdef")
+                        .fleet_state(FleetStateCode::Active)
+                        .r#type(FleetType::Maintain)
+                        .target_capacity_specification(
+                            TargetCapacitySpecification::builder()
+                                .total_target_capacity(0)
+                                .on_demand_target_capacity(0)
+                                .build(),
+                        )
+                        .tags(ec2_tag(PREVIOUS_TARGET_CAPACITY_TAG, "4"))
+                        .tags(ec2_tag(PREVIOUS_ON_DEMAND_CAPACITY_TAG, "1"))
+                        .build(),
+                )
+                .build()
+        });
+        let modify = mock!(Ec2Client::modify_fleet)
+            .match_requests(|req| {
+                let target = req.target_capacity_specification();
+                target.and_then(|t| t.total_target_capacity()) == Some(4)
+                    && target.and_then(|t| t.on_demand_target_capacity()) == Some(1)
+            })
+            .then_output(|| ModifyFleetOutput::builder().build());
+        let scheduler = Ec2FleetScheduler {
+            ec2: mock_client!(aws_sdk_ec2, RuleMode::MatchAny, [&describe, &modify]),
+            tagging: mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&fleets, &spot_fleets]
+            ),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(modify.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_records_the_target_capacity_then_scales_to_zero() {
+        let fleets = get_resources_rule("ec2:fleet", &[FLEET_ARN]);
+        let spot_fleets = get_resources_rule("ec2:spot-fleet-request", &[]);
+        let describe = mock!(Ec2Client::describe_fleets).then_output(|| {
+            DescribeFleetsOutput::builder()
+                .fleets(
+                    FleetData::builder()
+                        .fleet_id("fleet-12345678-90ab-cdef-1234-567890abcdef")
+                        .fleet_state(FleetStateCode::Active)
+                        .r#type(FleetType::Maintain)
+                        .target_capacity_specification(
+                            TargetCapacitySpecification::builder()
+                                .total_target_capacity(4)
+                                .on_demand_target_capacity(1)
+                                .build(),
+                        )
+                        .build(),
+                )
+                .build()
+        });
+        let record = mock!(Ec2Client::create_tags)
+            .match_requests(|req| {
+                let tags: Vec<(&str, &str)> = req
+                    .tags()
+                    .iter()
+                    .map(|t| (t.key().unwrap_or_default(), t.value().unwrap_or_default()))
+                    .collect();
+                req.resources() == ["fleet-12345678-90ab-cdef-1234-567890abcdef"]
+                    && tags
+                        == [
+                            (PREVIOUS_TARGET_CAPACITY_TAG, "4"),
+                            (PREVIOUS_ON_DEMAND_CAPACITY_TAG, "1"),
+                        ]
+            })
+            .then_output(|| CreateTagsOutput::builder().build());
+        let modify = mock!(Ec2Client::modify_fleet)
+            .match_requests(|req| {
+                let target = req.target_capacity_specification();
+                target.and_then(|t| t.total_target_capacity()) == Some(0)
+                    && target.and_then(|t| t.on_demand_target_capacity()) == Some(0)
+            })
+            .then_output(|| ModifyFleetOutput::builder().build());
+        let scheduler = Ec2FleetScheduler {
+            ec2: mock_client!(
+                aws_sdk_ec2,
+                RuleMode::MatchAny,
+                [&describe, &record, &modify]
+            ),
+            tagging: mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&fleets, &spot_fleets]
+            ),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(record.num_calls(), 1);
+        assert_eq!(modify.num_calls(), 1);
+    }
+}
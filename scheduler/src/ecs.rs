@@ -1,64 +1,350 @@
-use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_applicationautoscaling::types::ServiceNamespace;
+use aws_sdk_ecs::types::{ContainerInstance, ContainerInstanceStatus, Service};
 use aws_sdk_ecs::Client as EcsClient;
 use aws_sdk_resourcegroupstagging::Client as TaggingClient;
-use tracing::{error, info};
+use tokio::sync::OnceCell;
+use tracing::{error, info, warn};
+
+use crate::app_autoscaling::AppAutoScaling;
+use crate::arn::{self, Arn};
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::state::{State, StateStore};
+use crate::summary::{Outcome, ResourceCounts};
+
+/// State key used to remember the desired count of a service before it was stopped.
+const PREVIOUS_DESIRED_COUNT: &str = "previous-desired-count";
+
+/// Desired count applied on start when no previous value was recorded.
+const DEFAULT_DESIRED_COUNT: i32 = 1;
+
+/// Maximum number of polls while waiting for drained container instances to stop their tasks.
+const MAX_DRAIN_POLLS: u32 = 20;
 
-use crate::filter_resources_by_tags;
+/// Delay between two polls while waiting for drained container instances to stop their tasks.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_secs(15);
 
 /// Stop/start handler for ECS services in a given AWS region.
 ///
-/// Uses the Resource Groups Tagging API to discover services matching a tag,
-/// then sets the desired count to 0 (stop) or 1 (start).
+/// Uses the Resource Groups Tagging API to discover services matching a tag.
+/// On stop: records the current desired count as `previous-desired-count` in the
+/// state store (by default, the `scheduler:previous-desired-count` tag), then sets
+/// the desired count to 0.
+/// On start: restores the recorded desired count (1 if none was recorded).
+/// Application Auto Scaling of the services is suspended while they are stopped.
+///
+/// When `drain_container_instances` is set, tagged container instances of EC2-backed
+/// clusters are also set to `DRAINING` on stop, waiting for their tasks to stop
+/// gracefully, and back to `ACTIVE` on start.
+///
+/// Services and container instances with a legacy ARN, which does not hold the
+/// cluster name, are looked up in every cluster of the region.
 pub struct EcsScheduler {
     ecs: EcsClient,
     tagging: TaggingClient,
+    state: Arc<dyn StateStore>,
+    app_autoscaling: AppAutoScaling,
+    drain_container_instances: bool,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+    /// Names of the clusters of the region, listed once when a legacy ARN is met.
+    clusters: OnceCell<Vec<String>>,
 }
 
 impl EcsScheduler {
-    pub async fn new(region: &str) -> Self {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
-
+    pub fn new(
+        config: &SdkConfig,
+        state: Arc<dyn StateStore>,
+        drain_container_instances: bool,
+        dry_run: bool,
+        max_concurrent_operations: usize,
+    ) -> Self {
         Self {
-            ecs: EcsClient::new(&config),
-            tagging: TaggingClient::new(&config),
+            ecs: EcsClient::new(config),
+            tagging: TaggingClient::new(config),
+            state,
+            app_autoscaling: AppAutoScaling::new(config, dry_run),
+            drain_container_instances,
+            dry_run,
+            max_concurrent_operations,
+            clusters: OnceCell::new(),
         }
     }
 
-    pub async fn stop(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "ecs:service", tag_key, tag_value).await?;
-        info!(count = arns.len(), "Found ECS services to stop");
+    /// Names of the clusters of the region.
+    async fn clusters(&self) -> Result<&[String]> {
+        let clusters = self
+            .clusters
+            .get_or_try_init(|| async {
+                let mut names = Vec::new();
+                let mut pages = self.ecs.list_clusters().into_paginator().send();
+                while let Some(page) = pages.next().await {
+                    let page = page.context("Failed to list ECS clusters")?;
+                    names.extend(
+                        page.cluster_arns()
+                            .iter()
+                            .map(|cluster| arn::resource_id(cluster)),
+                    );
+                }
+                Ok::<_, anyhow::Error>(names)
+            })
+            .await?;
+        Ok(clusters)
+    }
 
-        for arn in &arns {
-            let (cluster, service) = extract_ecs_names(arn);
-            if let Err(e) = self.update_service(&cluster, &service, 0).await {
-                error!(service = %service, cluster = %cluster, error = %e, "Failed to stop ECS service");
+    /// Cluster and name of a service, its cluster being looked up when its ARN
+    /// is in the legacy format.
+    async fn service_names(&self, arn: &str) -> Result<(String, String)> {
+        let (cluster, service) = extract_ecs_names(arn);
+        if let Some(cluster) = cluster {
+            return Ok((cluster, service));
+        }
+
+        for cluster in self.clusters().await? {
+            let resp = self
+                .ecs
+                .describe_services()
+                .cluster(cluster)
+                .services(arn)
+                .send()
+                .await?;
+            if resp
+                .services()
+                .iter()
+                .any(|s| s.status() != Some("INACTIVE"))
+            {
+                info!(service = %service, cluster = %cluster, "Resolved the cluster of a legacy ECS service ARN");
+                return Ok((cluster.clone(), service));
             }
         }
+        bail!("ECS service '{}' not found in any cluster", arn)
+    }
 
-        Ok(())
+    /// Cluster of a container instance, looked up when its ARN is in the legacy format.
+    async fn instance_cluster(&self, arn: &str) -> Result<String> {
+        if let (Some(cluster), _) = extract_ecs_names(arn) {
+            return Ok(cluster);
+        }
+
+        for cluster in self.clusters().await? {
+            let resp = self
+                .ecs
+                .describe_container_instances()
+                .cluster(cluster)
+                .container_instances(arn)
+                .send()
+                .await?;
+            if !resp.container_instances().is_empty() {
+                return Ok(cluster.clone());
+            }
+        }
+        bail!("ECS container instance '{}' not found in any cluster", arn)
     }
 
-    pub async fn start(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "ecs:service", tag_key, tag_value).await?;
-        info!(count = arns.len(), "Found ECS services to start");
+    /// Record the current desired count of a service, then scale it to 0.
+    ///
+    /// A service with a desired count of 0 is skipped: recording that 0 would make
+    /// the next start fall back to the default desired count.
+    async fn stop_service(&self, arn: &str, cluster: &str, service: &str) -> Result<Outcome> {
+        let current = self.describe_service(cluster, service).await?;
+        let desired_count = current.desired_count();
+
+        if desired_count == 0 {
+            info!(service = %service, cluster = %cluster, "ECS service already scaled to 0, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        self.save_desired_count(arn, service, desired_count).await?;
+        self.app_autoscaling
+            .suspend(
+                ServiceNamespace::Ecs,
+                &[scalable_resource_id(cluster, service)],
+            )
+            .await?;
+        self.update_service(cluster, service, 0).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the desired count recorded on stop.
+    ///
+    /// Services with a non-zero desired count are left untouched.
+    /// Falls back to a desired count of 1 when no valid desired count was recorded.
+    async fn start_service(&self, arn: &str, cluster: &str, service: &str) -> Result<Outcome> {
+        let current = self.describe_service(cluster, service).await?;
+
+        if current.desired_count() > 0 {
+            info!(service = %service, cluster = %cluster, "ECS service already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let desired_count = match previous_desired_count(&self.state.load(arn).await?) {
+            Some(count) => count,
+            None => {
+                warn!(
+                    service = %service,
+                    cluster = %cluster,
+                    default = DEFAULT_DESIRED_COUNT,
+                    "No previous desired count recorded, using default"
+                );
+                DEFAULT_DESIRED_COUNT
+            }
+        };
+
+        self.update_service(cluster, service, desired_count).await?;
+        self.app_autoscaling
+            .resume(
+                ServiceNamespace::Ecs,
+                &[scalable_resource_id(cluster, service)],
+            )
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Describe a single ECS service.
+    async fn describe_service(&self, cluster: &str, service: &str) -> Result<Service> {
+        let resp = self
+            .ecs
+            .describe_services()
+            .cluster(cluster)
+            .services(service)
+            .send()
+            .await?;
+
+        resp.services().first().cloned().with_context(|| {
+            format!(
+                "ECS service '{}' not found in cluster '{}'",
+                service, cluster
+            )
+        })
+    }
+
+    /// Record the desired count of the service in the state store.
+    async fn save_desired_count(&self, arn: &str, service: &str, desired_count: i32) -> Result<()> {
+        if self.dry_run {
+            info!(service = %service, desired_count, "Dry run: would record previous desired count");
+            return Ok(());
+        }
+        info!(service = %service, desired_count, "Recording previous desired count");
+        let state = State::from([(
+            PREVIOUS_DESIRED_COUNT.to_string(),
+            desired_count.to_string(),
+        )]);
+        self.state.save(arn, &state).await
+    }
+
+    /// Describe a single ECS container instance.
+    async fn describe_container_instance(
+        &self,
+        cluster: &str,
+        arn: &str,
+    ) -> Result<ContainerInstance> {
+        let resp = self
+            .ecs
+            .describe_container_instances()
+            .cluster(cluster)
+            .container_instances(arn)
+            .send()
+            .await?;
 
-        for arn in &arns {
-            let (cluster, service) = extract_ecs_names(arn);
-            if let Err(e) = self.update_service(&cluster, &service, 1).await {
-                error!(service = %service, cluster = %cluster, error = %e, "Failed to start ECS service");
+        resp.container_instances()
+            .first()
+            .cloned()
+            .with_context(|| format!("ECS container instance '{}' not found", arn))
+    }
+
+    /// Set a container instance to `DRAINING` or `ACTIVE`.
+    async fn set_container_instance_status(
+        &self,
+        arn: &str,
+        status: ContainerInstanceStatus,
+    ) -> Result<Outcome> {
+        let cluster = self.instance_cluster(arn).await?;
+        let instance = self.describe_container_instance(&cluster, arn).await?;
+        let current = instance.status().unwrap_or_default();
+        if current == status.as_str() {
+            info!(container_instance = %arn, status = %current, "ECS container instance already in target status, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(container_instance = %arn, status = %status.as_str(), "Dry run: would update ECS container instance status");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(container_instance = %arn, status = %status.as_str(), "Updating ECS container instance status");
+        let resp = self
+            .ecs
+            .update_container_instances_state()
+            .cluster(&cluster)
+            .container_instances(arn)
+            .status(status)
+            .send()
+            .await?;
+        if let Some(failure) = resp.failures().first() {
+            bail!(
+                "ECS rejected the status update: {}",
+                failure.reason().unwrap_or("unknown reason")
+            );
+        }
+        Ok(Outcome::Applied)
+    }
+
+    /// List the tagged container instances, when they are drained with their services.
+    async fn container_instances(&self, filter: &ResourceFilter) -> Result<Vec<String>> {
+        if !self.drain_container_instances {
+            return Ok(Vec::new());
+        }
+        filter_resources_by_tags::get_resources(&self.tagging, "ecs:container-instance", filter)
+            .await
+    }
+
+    /// Poll drained container instances until none of them runs a task anymore.
+    async fn wait_drained(&self, arns: &[String]) -> Result<()> {
+        for _ in 0..MAX_DRAIN_POLLS {
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+
+            let mut running_tasks = 0;
+            for arn in arns {
+                let cluster = self.instance_cluster(arn).await?;
+                running_tasks += self
+                    .describe_container_instance(&cluster, arn)
+                    .await?
+                    .running_tasks_count();
+            }
+            if running_tasks == 0 {
+                info!(count = arns.len(), "ECS container instances drained");
+                return Ok(());
             }
+            info!(
+                running_tasks,
+                "Waiting for ECS container instances to drain"
+            );
         }
 
-        Ok(())
+        bail!(
+            "Timed out waiting for {} ECS container instances to drain",
+            arns.len()
+        );
     }
 
     async fn update_service(&self, cluster: &str, service: &str, desired_count: i32) -> Result<()> {
-        let action = if desired_count == 0 { "Stopping" } else { "Starting" };
+        if self.dry_run {
+            let action = if desired_count == 0 { "stop" } else { "start" };
+            info!(service = %service, cluster = %cluster, desired_count, "Dry run: would {action} ECS service");
+            return Ok(());
+        }
+        let action = if desired_count == 0 {
+            "Stopping"
+        } else {
+            "Starting"
+        };
         info!(service = %service, cluster = %cluster, desired_count, "{action} ECS service");
         self.ecs
             .update_service()
@@ -71,14 +357,484 @@ impl EcsScheduler {
     }
 }
 
-/// Extract the cluster name and service name from an ECS service ARN.
+#[async_trait]
+impl ResourceScheduler for EcsScheduler {
+    fn name(&self) -> &'static str {
+        "ecs"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ecs:service", filter).await?;
+        info!(count = arns.len(), "Found ECS services to stop");
+        let instance_arns = self.container_instances(filter).await?;
+
+        filter.check_discovered(arns.len() + instance_arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            match self.service_names(arn).await {
+                Ok((cluster, service)) => {
+                    let result = self.stop_service(arn, &cluster, &service).await;
+                    (cluster, service, result)
+                }
+                Err(e) => ("unknown".to_string(), extract_ecs_names(arn).1, Err(e)),
+            }
+        })
+        .await;
+        for (cluster, service, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&format!("{}/{}", cluster, service), outcome),
+                Err(e) => {
+                    counts.fail(&format!("{}/{}", cluster, service), &e);
+                    error!(service = %service, cluster = %cluster, error = %e, "Failed to stop ECS service");
+                }
+            }
+        }
+
+        if self.drain_container_instances {
+            info!(
+                count = instance_arns.len(),
+                "Found ECS container instances to drain"
+            );
+
+            counts.discovered += instance_arns.len();
+            let mut drained = Vec::new();
+            let results = run_bounded(
+                self.max_concurrent_operations,
+                &instance_arns,
+                |arn| async move {
+                    let result = self
+                        .set_container_instance_status(arn, ContainerInstanceStatus::Draining)
+                        .await;
+                    (arn, result)
+                },
+            )
+            .await;
+            for (arn, result) in results {
+                match result {
+                    Ok(outcome) => {
+                        if outcome == Outcome::Applied {
+                            drained.push(arn.clone());
+                        }
+                        counts.record(arn, outcome);
+                    }
+                    Err(e) => {
+                        counts.fail(arn, &e);
+                        error!(container_instance = %arn, error = %e, "Failed to drain ECS container instance");
+                    }
+                }
+            }
+
+            if !drained.is_empty() && !self.dry_run {
+                if let Err(e) = self.wait_drained(&drained).await {
+                    warn!(error = %e, "ECS container instances still run tasks, they may be killed with their host");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ecs:service", filter).await?;
+        info!(count = arns.len(), "Found ECS services to start");
+        let instance_arns = self.container_instances(filter).await?;
+
+        filter.check_discovered(arns.len() + instance_arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            match self.service_names(arn).await {
+                Ok((cluster, service)) => {
+                    let result = self.start_service(arn, &cluster, &service).await;
+                    (cluster, service, result)
+                }
+                Err(e) => ("unknown".to_string(), extract_ecs_names(arn).1, Err(e)),
+            }
+        })
+        .await;
+        for (cluster, service, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&format!("{}/{}", cluster, service), outcome),
+                Err(e) => {
+                    counts.fail(&format!("{}/{}", cluster, service), &e);
+                    error!(service = %service, cluster = %cluster, error = %e, "Failed to start ECS service");
+                }
+            }
+        }
+
+        if self.drain_container_instances {
+            info!(
+                count = instance_arns.len(),
+                "Found ECS container instances to activate"
+            );
+
+            counts.discovered += instance_arns.len();
+            let results = run_bounded(
+                self.max_concurrent_operations,
+                &instance_arns,
+                |arn| async move {
+                    let result = self
+                        .set_container_instance_status(arn, ContainerInstanceStatus::Active)
+                        .await;
+                    (arn, result)
+                },
+            )
+            .await;
+            for (arn, result) in results {
+                match result {
+                    Ok(outcome) => counts.record(arn, outcome),
+                    Err(e) => {
+                        counts.fail(arn, &e);
+                        error!(container_instance = %arn, error = %e, "Failed to activate ECS container instance");
+                    }
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the ECS services matching the given filter, `scaled-to-zero` when their
+    /// desired count is 0 and `running` otherwise, along with the status of the tagged
+    /// container instances when `drain_container_instances` is set.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ecs:service", filter).await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let (cluster, service) = self.service_names(&arn).await?;
+            let current = self.describe_service(&cluster, &service).await?;
+            let state = if current.desired_count() == 0 {
+                "scaled-to-zero"
+            } else {
+                "running"
+            };
+            items.push(InventoryItem::new(arn, "ecs:service", state));
+        }
+
+        for arn in self.container_instances(filter).await? {
+            let cluster = self.instance_cluster(&arn).await?;
+            let instance = self.describe_container_instance(&cluster, &arn).await?;
+            let status = instance.status().unwrap_or("UNKNOWN").to_string();
+            items.push(InventoryItem::new(arn, "ecs:container-instance", status));
+        }
+        Ok(items)
+    }
+}
+
+/// Read the desired count recorded on stop.
 ///
-/// Expected ARN format: `arn:aws:ecs:region:account:service/cluster-name/service-name`
-fn extract_ecs_names(arn: &str) -> (String, String) {
-    let parts: Vec<&str> = arn.split('/').collect();
-    if parts.len() >= 3 {
-        (parts[parts.len() - 2].to_string(), parts[parts.len() - 1].to_string())
-    } else {
-        (String::new(), arn.to_string())
+/// Returns `None` when none was recorded or it is not a positive integer.
+fn previous_desired_count(state: &State) -> Option<i32> {
+    state
+        .get(PREVIOUS_DESIRED_COUNT)
+        .and_then(|v| v.parse::<i32>().ok())
+        .filter(|count| *count > 0)
+}
+
+/// Build the Application Auto Scaling resource ID of a service: `service/cluster-name/service-name`.
+fn scalable_resource_id(cluster: &str, service: &str) -> String {
+    format!("service/{}/{}", cluster, service)
+}
+
+/// Extract the cluster name (`None` for a legacy ARN) and resource name from an ECS
+/// service or container instance ARN.
+///
+/// Expected ARN formats:
+/// - Service: `arn:partition:ecs:region:account:service/cluster-name/service-name`
+/// - Container instance: `arn:partition:ecs:region:account:container-instance/cluster-name/instance-id`
+/// - Legacy (short) format, used by accounts that did not opt in to long ARNs:
+///   `arn:partition:ecs:region:account:service/service-name`, without the cluster name
+fn extract_ecs_names(arn: &str) -> (Option<String>, String) {
+    match Arn::parse(arn).map(|a| a.resource_segments()).as_deref() {
+        Ok([_, cluster, name]) => (Some(cluster.to_string()), name.to_string()),
+        Ok([_, name]) => (None, name.to_string()),
+        _ => (None, arn.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_applicationautoscaling::operation::describe_scalable_targets::DescribeScalableTargetsOutput;
+    use aws_sdk_ecs::operation::describe_container_instances::DescribeContainerInstancesOutput;
+    use aws_sdk_ecs::operation::describe_services::DescribeServicesOutput;
+    use aws_sdk_ecs::operation::list_clusters::ListClustersOutput;
+    use aws_sdk_ecs::operation::update_container_instances_state::UpdateContainerInstancesStateOutput;
+    use aws_sdk_ecs::operation::update_service::UpdateServiceOutput;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule, MemoryStore};
+
+    const SERVICE_ARN: &str = "arn:aws:ecs:eu-west-1:123456789012:service/prod/web";
+
+    /// Rule describing `service` (a name or an ARN) in `cluster`, answering that the
+    /// service does not exist there when `desired_count` is `None`.
+    fn describe_service_rule(
+        cluster: &'static str,
+        service: &'static str,
+        desired_count: Option<i32>,
+    ) -> Rule {
+        mock!(EcsClient::describe_services)
+            .match_requests(move |req| {
+                req.cluster() == Some(cluster) && req.services() == [service.to_string()]
+            })
+            .then_output(move || {
+                let services = desired_count.map(|count| {
+                    vec![Service::builder()
+                        .service_name(extract_ecs_names(service).1)
+                        .status("ACTIVE")
+                        .desired_count(count)
+                        .build()]
+                });
+                DescribeServicesOutput::builder()
+                    .set_services(services)
+                    .build()
+            })
+    }
+
+    /// Rule scaling `service` of `cluster` to `desired_count`.
+    fn update_service_rule(
+        cluster: &'static str,
+        service: &'static str,
+        desired_count: i32,
+    ) -> Rule {
+        mock!(EcsClient::update_service)
+            .match_requests(move |req| {
+                req.cluster() == Some(cluster)
+                    && req.service() == Some(service)
+                    && req.desired_count() == Some(desired_count)
+            })
+            .then_output(|| UpdateServiceOutput::builder().build())
+    }
+
+    fn scheduler(
+        ecs: EcsClient,
+        tagging: TaggingClient,
+        state: Arc<dyn StateStore>,
+        drain_container_instances: bool,
+    ) -> EcsScheduler {
+        let targets = mock!(aws_sdk_applicationautoscaling::Client::describe_scalable_targets)
+            .then_output(|| DescribeScalableTargetsOutput::builder().build());
+        EcsScheduler {
+            ecs,
+            tagging,
+            state,
+            app_autoscaling: AppAutoScaling::from_client(
+                mock_client!(
+                    aws_sdk_applicationautoscaling,
+                    RuleMode::MatchAny,
+                    [&targets]
+                ),
+                false,
+            ),
+            drain_container_instances,
+            dry_run: false,
+            max_concurrent_operations: 10,
+            clusters: OnceCell::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_skips_services_already_scaled_to_zero() {
+        let services = get_resources_rule("ecs:service", &[SERVICE_ARN]);
+        let describe = describe_service_rule("prod", "web", Some(0));
+        let update = update_service_rule("prod", "web", 0);
+        let state = Arc::new(MemoryStore::with(
+            SERVICE_ARN,
+            &[(PREVIOUS_DESIRED_COUNT, "3")],
+        ));
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_ecs, RuleMode::MatchAny, [&describe, &update]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&services]),
+            state.clone(),
+            false,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 0, 1, 0)
+        );
+        assert_eq!(update.num_calls(), 0);
+        assert_eq!(
+            previous_desired_count(&state.state(SERVICE_ARN).unwrap()),
+            Some(3)
+        );
+    }
+
+    #[tokio::test]
+    async fn stop_resolves_the_cluster_of_legacy_service_arns() {
+        const WEB_ARN: &str = "arn:aws:ecs:eu-west-1:123456789012:service/web";
+        const API_ARN: &str = "arn:aws:ecs:eu-west-1:123456789012:service/api";
+        let services = get_resources_rule("ecs:service", &[WEB_ARN, API_ARN]);
+        let clusters = mock!(EcsClient::list_clusters).then_output(|| {
+            ListClustersOutput::builder()
+                .cluster_arns("arn:aws:ecs:eu-west-1:123456789012:cluster/dev")
+                .cluster_arns("arn:aws:ecs:eu-west-1:123456789012:cluster/prod")
+                .build()
+        });
+        let lookups = [
+            describe_service_rule("dev", WEB_ARN, None),
+            describe_service_rule("prod", WEB_ARN, Some(2)),
+            describe_service_rule("dev", API_ARN, Some(1)),
+            describe_service_rule("prod", API_ARN, None),
+            describe_service_rule("prod", "web", Some(2)),
+            describe_service_rule("dev", "api", Some(1)),
+        ];
+        let stop_web = update_service_rule("prod", "web", 0);
+        let stop_api = update_service_rule("dev", "api", 0);
+        let state = Arc::new(MemoryStore::default());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_ecs,
+                RuleMode::MatchAny,
+                [
+                    &clusters,
+                    &lookups[0],
+                    &lookups[1],
+                    &lookups[2],
+                    &lookups[3],
+                    &lookups[4],
+                    &lookups[5],
+                    &stop_web,
+                    &stop_api
+                ]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&services]),
+            state.clone(),
+            false,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!((counts.succeeded, counts.failed), (2, 0));
+        let mut resources: Vec<_> = counts
+            .resources
+            .iter()
+            .map(|r| r.resource.as_str())
+            .collect();
+        resources.sort();
+        assert_eq!(resources, ["dev/api", "prod/web"]);
+        assert_eq!((stop_web.num_calls(), stop_api.num_calls()), (1, 1));
+        // The clusters are listed once, however many legacy ARNs are resolved.
+        assert_eq!(clusters.num_calls(), 1);
+        assert_eq!(
+            previous_desired_count(&state.state(WEB_ARN).unwrap()),
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn stop_fails_legacy_service_arns_found_in_no_cluster() {
+        const GHOST_ARN: &str = "arn:aws:ecs:eu-west-1:123456789012:service/ghost";
+        let services = get_resources_rule("ecs:service", &[GHOST_ARN]);
+        let clusters = mock!(EcsClient::list_clusters).then_output(|| {
+            ListClustersOutput::builder()
+                .cluster_arns("arn:aws:ecs:eu-west-1:123456789012:cluster/prod")
+                .build()
+        });
+        let lookup = describe_service_rule("prod", GHOST_ARN, None);
+        let update =
+            mock!(EcsClient::update_service).then_output(|| UpdateServiceOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_ecs,
+                RuleMode::MatchAny,
+                [&clusters, &lookup, &update]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&services]),
+            Arc::new(MemoryStore::default()),
+            false,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!((counts.discovered, counts.failed), (1, 1));
+        let failure = &counts.resources[0];
+        assert_eq!(failure.resource, "unknown/ghost");
+        assert!(failure
+            .error
+            .as_deref()
+            .is_some_and(|e| e.contains("not found in any cluster")));
+        assert_eq!(update.num_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn start_resolves_the_cluster_of_legacy_container_instance_arns() {
+        const INSTANCE_ARN: &str =
+            "arn:aws:ecs:eu-west-1:123456789012:container-instance/4f2d1c3b5a6e";
+        let services = get_resources_rule("ecs:service", &[]);
+        let instances = get_resources_rule("ecs:container-instance", &[INSTANCE_ARN]);
+        let clusters = mock!(EcsClient::list_clusters).then_output(|| {
+            ListClustersOutput::builder()
+                .cluster_arns("arn:aws:ecs:eu-west-1:123456789012:cluster/dev")
+                .cluster_arns("arn:aws:ecs:eu-west-1:123456789012:cluster/prod")
+                .build()
+        });
+        let elsewhere = mock!(EcsClient::describe_container_instances)
+            .match_requests(|req| req.cluster() == Some("dev"))
+            .then_output(|| DescribeContainerInstancesOutput::builder().build());
+        let found = mock!(EcsClient::describe_container_instances)
+            .match_requests(|req| req.cluster() == Some("prod"))
+            .then_output(|| {
+                DescribeContainerInstancesOutput::builder()
+                    .container_instances(
+                        ContainerInstance::builder()
+                            .container_instance_arn(INSTANCE_ARN)
+                            .status("DRAINING")
+                            .build(),
+                    )
+                    .build()
+            });
+        let activate = mock!(EcsClient::update_container_instances_state)
+            .match_requests(|req| {
+                req.cluster() == Some("prod")
+                    && req.status() == Some(&ContainerInstanceStatus::Active)
+            })
+            .then_output(|| UpdateContainerInstancesStateOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_ecs,
+                RuleMode::MatchAny,
+                [&clusters, &elsewhere, &found, &activate]
+            ),
+            mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&services, &instances]
+            ),
+            Arc::new(MemoryStore::default()),
+            true,
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!((counts.discovered, counts.succeeded), (1, 1));
+        assert_eq!(activate.num_calls(), 1);
+    }
+
+    #[test]
+    fn extract_ecs_names_leaves_the_cluster_of_legacy_arns_unknown() {
+        assert_eq!(
+            extract_ecs_names("arn:aws:ecs:eu-west-1:123456789012:service/prod/web"),
+            (Some("prod".to_string()), "web".to_string())
+        );
+        assert_eq!(
+            extract_ecs_names("arn:aws:ecs:eu-west-1:123456789012:service/web"),
+            (None, "web".to_string())
+        );
     }
 }
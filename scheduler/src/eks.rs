@@ -0,0 +1,405 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_eks::types::{Nodegroup, NodegroupScalingConfig};
+use aws_sdk_eks::Client as EksClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::arn::Arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Tags used to remember the scaling configuration of a node group before it was stopped.
+const PREVIOUS_MIN_SIZE_TAG: &str = "scheduler:previous-min-size";
+const PREVIOUS_MAX_SIZE_TAG: &str = "scheduler:previous-max-size";
+const PREVIOUS_DESIRED_SIZE_TAG: &str = "scheduler:previous-desired-size";
+
+/// Stop/start handler for EKS managed node groups in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover node groups matching a tag.
+/// On stop: records the min/max/desired size in tags, then scales the node group
+/// to 0 (EKS requires a max size of at least 1, so the max size is kept).
+/// On start: restores the recorded scaling configuration.
+pub struct EksScheduler {
+    eks: EksClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl EksScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            eks: EksClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Record the scaling configuration of a node group in tags, then scale it to 0.
+    ///
+    /// Node groups at a min and desired size of 0 are skipped: tagging them again
+    /// would record 0 nodes as the size to restore on start.
+    async fn scale_to_zero(&self, arn: &str, cluster: &str, nodegroup: &str) -> Result<Outcome> {
+        let current = self.describe_nodegroup(cluster, nodegroup).await?;
+        let scaling = current.scaling_config().with_context(|| {
+            format!(
+                "EKS node group '{}' has no scaling configuration",
+                nodegroup
+            )
+        })?;
+        let min_size = scaling.min_size().unwrap_or_default();
+        let max_size = scaling.max_size().unwrap_or_default();
+        let desired_size = scaling.desired_size().unwrap_or_default();
+
+        if min_size == 0 && desired_size == 0 {
+            info!(nodegroup = %nodegroup, cluster = %cluster, "EKS node group already scaled to 0, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        if self.dry_run {
+            info!(
+                nodegroup = %nodegroup,
+                cluster = %cluster,
+                min_size,
+                max_size,
+                desired_size,
+                "Dry run: would scale EKS node group to 0"
+            );
+            return Ok(Outcome::Applied);
+        }
+
+        info!(nodegroup = %nodegroup, min_size, max_size, desired_size, "Recording previous scaling configuration");
+        self.eks
+            .tag_resource()
+            .resource_arn(arn)
+            .tags(PREVIOUS_MIN_SIZE_TAG, min_size.to_string())
+            .tags(PREVIOUS_MAX_SIZE_TAG, max_size.to_string())
+            .tags(PREVIOUS_DESIRED_SIZE_TAG, desired_size.to_string())
+            .send()
+            .await?;
+
+        info!(nodegroup = %nodegroup, cluster = %cluster, "Scaling EKS node group to 0");
+        self.update_scaling(cluster, nodegroup, 0, max_size, 0)
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the scaling configuration recorded on stop.
+    ///
+    /// Node groups with a non-zero desired size, or without a recorded
+    /// scaling configuration, are left untouched.
+    async fn restore_scaling(&self, cluster: &str, nodegroup: &str) -> Result<Outcome> {
+        let current = self.describe_nodegroup(cluster, nodegroup).await?;
+
+        let desired_size = current
+            .scaling_config()
+            .and_then(|s| s.desired_size())
+            .unwrap_or_default();
+        if desired_size > 0 {
+            info!(nodegroup = %nodegroup, cluster = %cluster, desired_size, "EKS node group already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let (Some(min_size), Some(max_size), Some(desired_size)) = (
+            read_size_tag(&current, PREVIOUS_MIN_SIZE_TAG),
+            read_size_tag(&current, PREVIOUS_MAX_SIZE_TAG),
+            read_size_tag(&current, PREVIOUS_DESIRED_SIZE_TAG),
+        ) else {
+            warn!(nodegroup = %nodegroup, cluster = %cluster, "No recorded scaling configuration found, skipping EKS node group");
+            return Ok(Outcome::Skipped);
+        };
+
+        if self.dry_run {
+            info!(
+                nodegroup = %nodegroup,
+                cluster = %cluster,
+                min_size,
+                max_size,
+                desired_size,
+                "Dry run: would restore EKS node group scaling configuration"
+            );
+            return Ok(Outcome::Applied);
+        }
+
+        info!(nodegroup = %nodegroup, cluster = %cluster, min_size, max_size, desired_size, "Restoring EKS node group scaling configuration");
+        self.update_scaling(cluster, nodegroup, min_size, max_size, desired_size)
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Describe a single EKS node group, including its tags.
+    async fn describe_nodegroup(&self, cluster: &str, nodegroup: &str) -> Result<Nodegroup> {
+        let resp = self
+            .eks
+            .describe_nodegroup()
+            .cluster_name(cluster)
+            .nodegroup_name(nodegroup)
+            .send()
+            .await?;
+
+        resp.nodegroup().cloned().with_context(|| {
+            format!(
+                "EKS node group '{}' not found in cluster '{}'",
+                nodegroup, cluster
+            )
+        })
+    }
+
+    async fn update_scaling(
+        &self,
+        cluster: &str,
+        nodegroup: &str,
+        min_size: i32,
+        max_size: i32,
+        desired_size: i32,
+    ) -> Result<()> {
+        self.eks
+            .update_nodegroup_config()
+            .cluster_name(cluster)
+            .nodegroup_name(nodegroup)
+            .scaling_config(
+                NodegroupScalingConfig::builder()
+                    .min_size(min_size)
+                    .max_size(max_size)
+                    .desired_size(desired_size)
+                    .build(),
+            )
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for EksScheduler {
+    fn name(&self) -> &'static str {
+        "eks"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "eks:nodegroup", filter).await?;
+        info!(count = arns.len(), "Found EKS node groups to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let (cluster, nodegroup) = extract_nodegroup_names(arn);
+            let result = self.scale_to_zero(arn, &cluster, &nodegroup).await;
+            (cluster, nodegroup, result)
+        })
+        .await;
+        for (cluster, nodegroup, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&format!("{}/{}", cluster, nodegroup), outcome),
+                Err(e) => {
+                    counts.fail(&format!("{}/{}", cluster, nodegroup), &e);
+                    error!(nodegroup = %nodegroup, cluster = %cluster, error = %e, "Failed to stop EKS node group");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "eks:nodegroup", filter).await?;
+        info!(count = arns.len(), "Found EKS node groups to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let (cluster, nodegroup) = extract_nodegroup_names(arn);
+            let result = self.restore_scaling(&cluster, &nodegroup).await;
+            (cluster, nodegroup, result)
+        })
+        .await;
+        for (cluster, nodegroup, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&format!("{}/{}", cluster, nodegroup), outcome),
+                Err(e) => {
+                    counts.fail(&format!("{}/{}", cluster, nodegroup), &e);
+                    error!(nodegroup = %nodegroup, cluster = %cluster, error = %e, "Failed to start EKS node group");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the EKS node groups matching the given filter, `scaled-to-zero` when their
+    /// desired size is 0 and `running` otherwise.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "eks:nodegroup", filter).await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let (cluster, nodegroup) = extract_nodegroup_names(&arn);
+            let current = self.describe_nodegroup(&cluster, &nodegroup).await?;
+            let desired_size = current
+                .scaling_config()
+                .and_then(|s| s.desired_size())
+                .unwrap_or_default();
+            let state = if desired_size == 0 {
+                "scaled-to-zero"
+            } else {
+                "running"
+            };
+            items.push(InventoryItem::new(arn, "eks:nodegroup", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Read a size recorded in the tags of a node group.
+fn read_size_tag(nodegroup: &Nodegroup, key: &str) -> Option<i32> {
+    nodegroup
+        .tags()
+        .and_then(|tags| tags.get(key))
+        .and_then(|v| v.parse::<i32>().ok())
+}
+
+/// Extract the cluster name and node group name from an EKS node group ARN.
+///
+/// Expected ARN format: `arn:partition:eks:region:account:nodegroup/cluster-name/nodegroup-name/uuid`
+fn extract_nodegroup_names(arn: &str) -> (String, String) {
+    match Arn::parse(arn).map(|a| a.resource_segments()).as_deref() {
+        Ok([_, cluster, nodegroup, ..]) => (cluster.to_string(), nodegroup.to_string()),
+        _ => (String::new(), arn.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_eks::operation::describe_nodegroup::DescribeNodegroupOutput;
+    use aws_sdk_eks::operation::tag_resource::TagResourceOutput;
+    use aws_sdk_eks::operation::update_nodegroup_config::UpdateNodegroupConfigOutput;
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const NODEGROUP_ARN: &str =
+        "arn:aws:eks:eu-west-1:123456789012:nodegroup/prod/workers/0ac4f2b6-1f2e-4c3d-9a8b-7c6d5e4f3a2b";
+
+    #[tokio::test]
+    async fn stop_skips_node_groups_already_scaled_to_zero() {
+        let nodegroups = get_resources_rule("eks:nodegroup", &[NODEGROUP_ARN]);
+        let describe = mock!(EksClient::describe_nodegroup)
+            .match_requests(|req| {
+                req.cluster_name() == Some("prod") && req.nodegroup_name() == Some("workers")
+            })
+            .then_output(|| {
+                DescribeNodegroupOutput::builder()
+                    .nodegroup(
+                        Nodegroup::builder()
+                            .nodegroup_name("workers")
+                            .scaling_config(
+                                NodegroupScalingConfig::builder()
+                                    .min_size(0)
+                                    .max_size(5)
+                                    .desired_size(0)
+                                    .build(),
+                            )
+                            .tags(PREVIOUS_DESIRED_SIZE_TAG, "3")
+                            .build(),
+                    )
+                    .build()
+            });
+        let record =
+            mock!(EksClient::tag_resource).then_output(|| TagResourceOutput::builder().build());
+        let update = mock!(EksClient::update_nodegroup_config)
+            .then_output(|| UpdateNodegroupConfigOutput::builder().build());
+        let scheduler = EksScheduler {
+            eks: mock_client!(
+                aws_sdk_eks,
+                RuleMode::MatchAny,
+                [&describe, &record, &update]
+            ),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&nodegroups]),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 0, 1, 0)
+        );
+        assert_eq!(record.num_calls(), 0);
+        assert_eq!(update.num_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn start_restores_the_recorded_scaling_configuration() {
+        let nodegroups = get_resources_rule("eks:nodegroup", &[NODEGROUP_ARN]);
+        let describe = mock!(EksClient::describe_nodegroup).then_output(|| {
+            DescribeNodegroupOutput::builder()
+                .nodegroup(
+                    Nodegroup::builder()
+                        .nodegroup_name("workers")
+                        .scaling_config(
+                            NodegroupScalingConfig::builder()
+                                .min_size(0)
+                                .max_size(5)
+                                .desired_size(0)
+                                .build(),
+                        )
+                        .tags(PREVIOUS_MIN_SIZE_TAG, "1")
+                        .tags(PREVIOUS_MAX_SIZE_TAG, "5")
+                        .tags(PREVIOUS_DESIRED_SIZE_TAG, "3")
+                        .build(),
+                )
+                .build()
+        });
+        let update = mock!(EksClient::update_nodegroup_config)
+            .match_requests(|req| {
+                let scaling = req.scaling_config().unwrap();
+                req.cluster_name() == Some("prod")
+                    && req.nodegroup_name() == Some("workers")
+                    && (
+                        scaling.min_size(),
+                        scaling.max_size(),
+                        scaling.desired_size(),
+                    ) == (Some(1), Some(5), Some(3))
+            })
+            .then_output(|| UpdateNodegroupConfigOutput::builder().build());
+        let scheduler = EksScheduler {
+            eks: mock_client!(aws_sdk_eks, RuleMode::MatchAny, [&describe, &update]),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&nodegroups]),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+}
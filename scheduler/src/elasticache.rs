@@ -0,0 +1,893 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_elasticache::types::{AutomaticFailoverStatus, MultiAzStatus, ReplicationGroup, Tag};
+use aws_sdk_elasticache::Client as ElastiCacheClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use chrono::Utc;
+use tracing::{error, info, warn};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Prefix of the snapshots taken by the scheduler.
+const SNAPSHOT_PREFIX: &str = "scheduler-";
+
+/// Snapshot tags holding the settings needed to re-create a replication group.
+const REPLICATION_GROUP_ID_TAG: &str = "scheduler:replication-group-id";
+const NODE_TYPE_TAG: &str = "scheduler:node-type";
+const ENGINE_TAG: &str = "scheduler:engine";
+const ENGINE_VERSION_TAG: &str = "scheduler:engine-version";
+const CLUSTER_MODE_TAG: &str = "scheduler:cluster-mode";
+const NUM_NODE_GROUPS_TAG: &str = "scheduler:num-node-groups";
+const REPLICAS_PER_NODE_GROUP_TAG: &str = "scheduler:replicas-per-node-group";
+const AUTOMATIC_FAILOVER_TAG: &str = "scheduler:automatic-failover";
+const SUBNET_GROUP_TAG: &str = "scheduler:subnet-group";
+const SECURITY_GROUP_IDS_TAG: &str = "scheduler:security-group-ids";
+const PARAMETER_GROUP_TAG: &str = "scheduler:parameter-group";
+const AT_REST_ENCRYPTION_TAG: &str = "scheduler:at-rest-encryption";
+const TRANSIT_ENCRYPTION_TAG: &str = "scheduler:transit-encryption";
+const KMS_KEY_ID_TAG: &str = "scheduler:kms-key-id";
+const USER_GROUP_IDS_TAG: &str = "scheduler:user-group-ids";
+const PORT_TAG: &str = "scheduler:port";
+const MULTI_AZ_TAG: &str = "scheduler:multi-az";
+
+/// Maximum number of polls while waiting for a snapshot to become available.
+const MAX_SNAPSHOT_POLLS: u32 = 60;
+
+/// Delay between two polls while waiting for a snapshot to become available.
+const SNAPSHOT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Stop/start handler for ElastiCache (Redis OSS/Valkey) replication groups in a given AWS region.
+///
+/// Replication groups cannot be stopped, so they are deleted instead.
+/// On stop: takes a snapshot of each tagged replication group, tagged with the group
+/// settings and the group's own tags, waits for it to complete, then deletes the group.
+/// On start: discovers the scheduler snapshots through the same tag filter and
+/// re-creates each replication group from its most recent snapshot.
+///
+/// Groups protected by an AUTH token are skipped, as the token cannot be read back
+/// to re-create them.
+pub struct ElastiCacheScheduler {
+    elasticache: ElastiCacheClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl ElastiCacheScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            elasticache: ElastiCacheClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Snapshot a replication group with its settings in tags, then delete it.
+    ///
+    /// Older scheduler snapshots of the group are deleted once the new one is available.
+    async fn snapshot_and_delete(&self, arn: &str, group_id: &str) -> Result<Outcome> {
+        let group = self.describe_replication_group(group_id).await?;
+        let status = group.status().unwrap_or_default();
+        if status != "available" {
+            info!(replication_group = %group_id, status = %status, "ElastiCache replication group not available, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if group.auth_token_enabled().unwrap_or_default() {
+            warn!(replication_group = %group_id, "ElastiCache replication group uses an AUTH token, which cannot be restored, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let snapshot = format!(
+            "{}{}-{}",
+            SNAPSHOT_PREFIX,
+            group_id,
+            Utc::now().format("%Y%m%d-%H%M")
+        );
+        if self.dry_run {
+            info!(replication_group = %group_id, snapshot = %snapshot, "Dry run: would snapshot and delete ElastiCache replication group");
+            return Ok(Outcome::Applied);
+        }
+
+        let mut tags = self.replication_group_settings(&group, group_id).await?;
+        tags.extend(
+            self.list_tags(arn)
+                .await?
+                .into_iter()
+                .filter(|(k, _)| !k.starts_with("aws:")),
+        );
+
+        info!(replication_group = %group_id, snapshot = %snapshot, "Taking ElastiCache snapshot");
+        let previous_snapshots = self.scheduler_snapshots(group_id).await?;
+        self.elasticache
+            .create_snapshot()
+            .replication_group_id(group_id)
+            .snapshot_name(&snapshot)
+            .set_tags(Some(
+                tags.iter()
+                    .map(|(k, v)| Tag::builder().key(k).value(v).build())
+                    .collect(),
+            ))
+            .send()
+            .await?;
+        self.wait_snapshot_available(&snapshot).await?;
+
+        info!(replication_group = %group_id, "Deleting ElastiCache replication group");
+        self.elasticache
+            .delete_replication_group()
+            .replication_group_id(group_id)
+            .retain_primary_cluster(false)
+            .send()
+            .await?;
+
+        for previous in &previous_snapshots {
+            info!(snapshot = %previous, "Deleting previous ElastiCache scheduler snapshot");
+            if let Err(e) = self
+                .elasticache
+                .delete_snapshot()
+                .snapshot_name(previous)
+                .send()
+                .await
+            {
+                warn!(snapshot = %previous, error = %e, "Failed to delete previous ElastiCache snapshot");
+            }
+        }
+
+        Ok(Outcome::Applied)
+    }
+
+    /// Re-create a replication group from a scheduler snapshot and its tags.
+    ///
+    /// Groups that already exist are left untouched.
+    async fn restore_from_snapshot(
+        &self,
+        snapshot: &str,
+        group_id: &str,
+        tags: &HashMap<String, String>,
+    ) -> Result<Outcome> {
+        if self.replication_group_exists(group_id).await? {
+            info!(replication_group = %group_id, snapshot = %snapshot, "ElastiCache replication group already exists, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let setting = |key: &str| {
+            tags.get(key)
+                .cloned()
+                .with_context(|| format!("Snapshot '{}' has no {} tag", snapshot, key))
+        };
+        let node_type = setting(NODE_TYPE_TAG)?;
+        let engine = setting(ENGINE_TAG)?;
+        let engine_version = setting(ENGINE_VERSION_TAG)?;
+        let cluster_mode = setting(CLUSTER_MODE_TAG)? == "true";
+        let num_node_groups: i32 = setting(NUM_NODE_GROUPS_TAG)?.parse()?;
+        let replicas: i32 = setting(REPLICAS_PER_NODE_GROUP_TAG)?.parse()?;
+        let automatic_failover = setting(AUTOMATIC_FAILOVER_TAG)? == "true";
+        let subnet_group = setting(SUBNET_GROUP_TAG).ok();
+        let security_group_ids = setting(SECURITY_GROUP_IDS_TAG).ok();
+        let parameter_group = setting(PARAMETER_GROUP_TAG).ok();
+        let flag = |key: &str| setting(key).ok().map(|v| v == "true");
+        let at_rest_encryption = flag(AT_REST_ENCRYPTION_TAG);
+        let transit_encryption = flag(TRANSIT_ENCRYPTION_TAG);
+        let multi_az = flag(MULTI_AZ_TAG);
+        let kms_key_id = setting(KMS_KEY_ID_TAG).ok();
+        let user_group_ids = setting(USER_GROUP_IDS_TAG).ok();
+        let port = setting(PORT_TAG)
+            .ok()
+            .map(|p| p.parse::<i32>())
+            .transpose()?;
+
+        if self.dry_run {
+            info!(replication_group = %group_id, snapshot = %snapshot, "Dry run: would re-create ElastiCache replication group");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(replication_group = %group_id, snapshot = %snapshot, "Re-creating ElastiCache replication group");
+        let mut req = self
+            .elasticache
+            .create_replication_group()
+            .replication_group_id(group_id)
+            .replication_group_description(format!("Restored from {}", snapshot))
+            .snapshot_name(snapshot)
+            .cache_node_type(node_type)
+            .engine(engine)
+            .engine_version(engine_version)
+            .automatic_failover_enabled(automatic_failover)
+            .set_cache_subnet_group_name(subnet_group)
+            .set_cache_parameter_group_name(parameter_group)
+            .set_security_group_ids(
+                security_group_ids.map(|ids| ids.split_whitespace().map(str::to_string).collect()),
+            )
+            .set_at_rest_encryption_enabled(at_rest_encryption)
+            .set_transit_encryption_enabled(transit_encryption)
+            .set_kms_key_id(kms_key_id)
+            .set_user_group_ids(
+                user_group_ids.map(|ids| ids.split_whitespace().map(str::to_string).collect()),
+            )
+            .set_port(port)
+            .set_multi_az_enabled(multi_az)
+            .set_tags(Some(
+                tags.iter()
+                    .filter(|(k, _)| !k.starts_with("scheduler:"))
+                    .map(|(k, v)| Tag::builder().key(k).value(v).build())
+                    .collect(),
+            ));
+        req = if cluster_mode {
+            req.num_node_groups(num_node_groups)
+                .replicas_per_node_group(replicas)
+        } else {
+            req.num_cache_clusters(replicas + 1)
+        };
+        req.send().await?;
+
+        Ok(Outcome::Applied)
+    }
+
+    async fn describe_replication_group(&self, group_id: &str) -> Result<ReplicationGroup> {
+        let resp = self
+            .elasticache
+            .describe_replication_groups()
+            .replication_group_id(group_id)
+            .send()
+            .await?;
+
+        resp.replication_groups()
+            .first()
+            .cloned()
+            .with_context(|| format!("ElastiCache replication group '{}' not found", group_id))
+    }
+
+    async fn replication_group_exists(&self, group_id: &str) -> Result<bool> {
+        match self
+            .elasticache
+            .describe_replication_groups()
+            .replication_group_id(group_id)
+            .send()
+            .await
+        {
+            Ok(resp) => Ok(!resp.replication_groups().is_empty()),
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_replication_group_not_found_fault()) =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Collect the settings needed to re-create a replication group, as snapshot tags.
+    ///
+    /// Engine, subnet, security and parameter groups are read from the first member cluster,
+    /// encryption, user groups, port and Multi-AZ from the group itself.
+    async fn replication_group_settings(
+        &self,
+        group: &ReplicationGroup,
+        group_id: &str,
+    ) -> Result<HashMap<String, String>> {
+        let member = group.member_clusters().first().with_context(|| {
+            format!(
+                "ElastiCache replication group '{}' has no member cluster",
+                group_id
+            )
+        })?;
+        let resp = self
+            .elasticache
+            .describe_cache_clusters()
+            .cache_cluster_id(member)
+            .send()
+            .await?;
+        let cluster = resp
+            .cache_clusters()
+            .first()
+            .with_context(|| format!("ElastiCache cluster '{}' not found", member))?;
+
+        let node_groups = group.node_groups();
+        let replicas = node_groups
+            .first()
+            .map(|n| n.node_group_members().len().saturating_sub(1))
+            .unwrap_or_default();
+        let automatic_failover = matches!(
+            group.automatic_failover(),
+            Some(AutomaticFailoverStatus::Enabled | AutomaticFailoverStatus::Enabling)
+        );
+
+        let mut settings = HashMap::from([
+            (REPLICATION_GROUP_ID_TAG.to_string(), group_id.to_string()),
+            (
+                NODE_TYPE_TAG.to_string(),
+                group.cache_node_type().unwrap_or_default().to_string(),
+            ),
+            (
+                ENGINE_TAG.to_string(),
+                cluster.engine().unwrap_or("redis").to_string(),
+            ),
+            (
+                ENGINE_VERSION_TAG.to_string(),
+                cluster.engine_version().unwrap_or_default().to_string(),
+            ),
+            (
+                CLUSTER_MODE_TAG.to_string(),
+                group.cluster_enabled().unwrap_or_default().to_string(),
+            ),
+            (
+                NUM_NODE_GROUPS_TAG.to_string(),
+                node_groups.len().max(1).to_string(),
+            ),
+            (
+                REPLICAS_PER_NODE_GROUP_TAG.to_string(),
+                replicas.to_string(),
+            ),
+            (
+                AUTOMATIC_FAILOVER_TAG.to_string(),
+                automatic_failover.to_string(),
+            ),
+            (
+                AT_REST_ENCRYPTION_TAG.to_string(),
+                group
+                    .at_rest_encryption_enabled()
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+            (
+                TRANSIT_ENCRYPTION_TAG.to_string(),
+                group
+                    .transit_encryption_enabled()
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+            (
+                MULTI_AZ_TAG.to_string(),
+                (group.multi_az() == Some(&MultiAzStatus::Enabled)).to_string(),
+            ),
+        ]);
+        if let Some(kms_key_id) = group.kms_key_id() {
+            settings.insert(KMS_KEY_ID_TAG.to_string(), kms_key_id.to_string());
+        }
+        if !group.user_group_ids().is_empty() {
+            settings.insert(
+                USER_GROUP_IDS_TAG.to_string(),
+                group.user_group_ids().join(" "),
+            );
+        }
+        // Cluster mode groups expose their port on the configuration endpoint,
+        // other groups on the primary endpoint of their single node group.
+        let port = group
+            .configuration_endpoint()
+            .or_else(|| node_groups.first().and_then(|n| n.primary_endpoint()))
+            .and_then(|e| e.port());
+        if let Some(port) = port {
+            settings.insert(PORT_TAG.to_string(), port.to_string());
+        }
+        if let Some(subnet_group) = cluster.cache_subnet_group_name() {
+            settings.insert(SUBNET_GROUP_TAG.to_string(), subnet_group.to_string());
+        }
+        let security_group_ids: Vec<&str> = cluster
+            .security_groups()
+            .iter()
+            .filter_map(|g| g.security_group_id())
+            .collect();
+        if !security_group_ids.is_empty() {
+            settings.insert(
+                SECURITY_GROUP_IDS_TAG.to_string(),
+                security_group_ids.join(" "),
+            );
+        }
+        if let Some(parameter_group) = cluster
+            .cache_parameter_group()
+            .and_then(|p| p.cache_parameter_group_name())
+        {
+            settings.insert(PARAMETER_GROUP_TAG.to_string(), parameter_group.to_string());
+        }
+
+        Ok(settings)
+    }
+
+    /// List the names of the snapshots previously taken by the scheduler for a replication group.
+    async fn scheduler_snapshots(&self, group_id: &str) -> Result<Vec<String>> {
+        let prefix = format!("{}{}-", SNAPSHOT_PREFIX, group_id);
+        let mut names = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut req = self
+                .elasticache
+                .describe_snapshots()
+                .replication_group_id(group_id);
+            if let Some(ref token) = marker {
+                req = req.marker(token);
+            }
+
+            let resp = req.send().await?;
+            names.extend(
+                resp.snapshots()
+                    .iter()
+                    .filter_map(|s| s.snapshot_name())
+                    .filter(|name| name.starts_with(&prefix))
+                    .map(str::to_string),
+            );
+
+            match resp.marker() {
+                Some(token) if !token.is_empty() => marker = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Poll a snapshot until it reaches the `available` status.
+    async fn wait_snapshot_available(&self, snapshot: &str) -> Result<()> {
+        for _ in 0..MAX_SNAPSHOT_POLLS {
+            tokio::time::sleep(SNAPSHOT_POLL_INTERVAL).await;
+
+            let resp = self
+                .elasticache
+                .describe_snapshots()
+                .snapshot_name(snapshot)
+                .send()
+                .await?;
+            match resp.snapshots().first().and_then(|s| s.snapshot_status()) {
+                Some("available") => return Ok(()),
+                Some("failed") => bail!("ElastiCache snapshot '{}' failed", snapshot),
+                _ => {}
+            }
+        }
+
+        bail!(
+            "Timed out waiting for ElastiCache snapshot '{}' to become available",
+            snapshot
+        );
+    }
+
+    async fn list_tags(&self, arn: &str) -> Result<HashMap<String, String>> {
+        let resp = self
+            .elasticache
+            .list_tags_for_resource()
+            .resource_name(arn)
+            .send()
+            .await?;
+
+        Ok(resp
+            .tag_list()
+            .iter()
+            .filter_map(|t| {
+                Some((
+                    t.key()?.to_string(),
+                    t.value().unwrap_or_default().to_string(),
+                ))
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for ElastiCacheScheduler {
+    fn name(&self) -> &'static str {
+        "elasticache"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "elasticache:replicationgroup",
+            filter,
+        )
+        .await?;
+        info!(
+            count = arns.len(),
+            "Found ElastiCache replication groups to stop"
+        );
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let group_id = extract_resource_name(arn);
+            let result = self.snapshot_and_delete(arn, &group_id).await;
+            (group_id, result)
+        })
+        .await;
+        for (group_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&group_id, outcome),
+                Err(e) => {
+                    counts.fail(&group_id, &e);
+                    error!(replication_group = %group_id, error = %e, "Failed to stop ElastiCache replication group");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let mut arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "elasticache:snapshot", filter)
+                .await?
+                .into_iter()
+                .filter(|arn| extract_resource_name(arn).starts_with(SNAPSHOT_PREFIX))
+                .collect::<Vec<_>>();
+        // Snapshot names end with a timestamp: the most recent snapshot of a group comes first.
+        arns.sort_unstable_by(|a, b| b.cmp(a));
+        info!(
+            count = arns.len(),
+            "Found ElastiCache scheduler snapshots to restore"
+        );
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let tagged = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            (extract_resource_name(arn), self.list_tags(arn).await)
+        })
+        .await;
+
+        // Only the most recent snapshot of a group is restored: restoring older ones
+        // would race with it and bring back stale data.
+        let mut latest: Vec<(String, String, HashMap<String, String>)> = Vec::new();
+        for (snapshot, tags) in tagged {
+            let group_id = tags.and_then(|tags| {
+                let group_id = tags.get(REPLICATION_GROUP_ID_TAG).cloned();
+                group_id.map(|id| (id, tags)).with_context(|| {
+                    format!(
+                        "Snapshot '{}' has no {} tag",
+                        snapshot, REPLICATION_GROUP_ID_TAG
+                    )
+                })
+            });
+            match group_id {
+                Ok((group_id, _)) if latest.iter().any(|(id, _, _)| *id == group_id) => {
+                    info!(replication_group = %group_id, snapshot = %snapshot, "Newer ElastiCache scheduler snapshot found, skipping");
+                    counts.record(&snapshot, Outcome::Skipped);
+                }
+                Ok((group_id, tags)) => latest.push((group_id, snapshot, tags)),
+                Err(e) => {
+                    counts.fail(&snapshot, &e);
+                    error!(snapshot = %snapshot, error = %e, "Failed to restore ElastiCache replication group");
+                }
+            }
+        }
+
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &latest,
+            |(group_id, snapshot, tags)| async move {
+                let result = self.restore_from_snapshot(snapshot, group_id, tags).await;
+                (snapshot, result)
+            },
+        )
+        .await;
+        for (snapshot, result) in results {
+            match result {
+                Ok(outcome) => counts.record(snapshot, outcome),
+                Err(e) => {
+                    counts.fail(snapshot, &e);
+                    error!(snapshot = %snapshot, error = %e, "Failed to restore ElastiCache replication group");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the ElastiCache replication groups matching the given filter with their current
+    /// status, along with the scheduler snapshots of deleted groups, reported as `stored`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "elasticache:replicationgroup",
+            filter,
+        )
+        .await?;
+        let snapshot_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "elasticache:snapshot", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let group = self
+                .describe_replication_group(&extract_resource_name(&arn))
+                .await?;
+            let status = group.status().unwrap_or("unknown").to_string();
+            items.push(InventoryItem::new(
+                arn,
+                "elasticache:replicationgroup",
+                status,
+            ));
+        }
+        items.extend(
+            snapshot_arns
+                .into_iter()
+                .filter(|arn| extract_resource_name(arn).starts_with(SNAPSHOT_PREFIX))
+                .map(|arn| InventoryItem::new(arn, "elasticache:snapshot", "stored")),
+        );
+        Ok(items)
+    }
+}
+
+/// Extract the resource name from an ElastiCache ARN.
+///
+/// Expected ARN formats:
+/// - Replication group: `arn:partition:elasticache:region:account:replicationgroup:group-id`
+/// - Snapshot: `arn:partition:elasticache:region:account:snapshot:snapshot-name`
+fn extract_resource_name(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_elasticache::operation::create_replication_group::CreateReplicationGroupOutput;
+    use aws_sdk_elasticache::operation::create_snapshot::CreateSnapshotOutput;
+    use aws_sdk_elasticache::operation::delete_replication_group::DeleteReplicationGroupOutput;
+    use aws_sdk_elasticache::operation::delete_snapshot::DeleteSnapshotOutput;
+    use aws_sdk_elasticache::operation::describe_cache_clusters::DescribeCacheClustersOutput;
+    use aws_sdk_elasticache::operation::describe_replication_groups::{
+        DescribeReplicationGroupsError, DescribeReplicationGroupsOutput,
+    };
+    use aws_sdk_elasticache::operation::describe_snapshots::DescribeSnapshotsOutput;
+    use aws_sdk_elasticache::operation::list_tags_for_resource::ListTagsForResourceOutput;
+    use aws_sdk_elasticache::types::error::ReplicationGroupNotFoundFault;
+    use aws_sdk_elasticache::types::{
+        CacheCluster, Endpoint, NodeGroup, NodeGroupMember, Snapshot,
+    };
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::summary::ResourceStatus;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const GROUP_ARN: &str = "arn:aws:elasticache:eu-west-1:123456789012:replicationgroup:orders";
+    const OLD_SNAPSHOT: &str = "scheduler-orders-20240101-1900";
+    const NEW_SNAPSHOT: &str = "scheduler-orders-20240102-1900";
+
+    fn snapshot_arn(name: &str) -> String {
+        format!("arn:aws:elasticache:eu-west-1:123456789012:snapshot:{name}")
+    }
+
+    /// Rule describing the `orders` group, encrypted, Multi-AZ and with a replica.
+    fn describe_group_rule(auth_token_enabled: bool) -> Rule {
+        mock!(ElastiCacheClient::describe_replication_groups).then_output(move || {
+            let member = |id: &str| NodeGroupMember::builder().cache_cluster_id(id).build();
+            DescribeReplicationGroupsOutput::builder()
+                .replication_groups(
+                    ReplicationGroup::builder()
+                        .replication_group_id("orders")
+                        .status("available")
+                        .member_clusters("orders-001")
+                        .member_clusters("orders-002")
+                        .cache_node_type("cache.r6g.large")
+                        .cluster_enabled(false)
+                        .node_groups(
+                            NodeGroup::builder()
+                                .node_group_id("0001")
+                                .primary_endpoint(Endpoint::builder().port(6380).build())
+                                .node_group_members(member("orders-001"))
+                                .node_group_members(member("orders-002"))
+                                .build(),
+                        )
+                        .automatic_failover(AutomaticFailoverStatus::Enabled)
+                        .multi_az(MultiAzStatus::Enabled)
+                        .at_rest_encryption_enabled(true)
+                        .transit_encryption_enabled(true)
+                        .kms_key_id("alias/cache")
+                        .user_group_ids("app-users")
+                        .auth_token_enabled(auth_token_enabled)
+                        .build(),
+                )
+                .build()
+        })
+    }
+
+    /// Rule listing `tags` on the resource `arn`.
+    fn list_tags_rule(arn: String, tags: &'static [(&'static str, &'static str)]) -> Rule {
+        mock!(ElastiCacheClient::list_tags_for_resource)
+            .match_requests(move |req| req.resource_name() == Some(arn.as_str()))
+            .then_output(move || {
+                ListTagsForResourceOutput::builder()
+                    .set_tag_list(Some(
+                        tags.iter()
+                            .map(|(k, v)| Tag::builder().key(*k).value(*v).build())
+                            .collect(),
+                    ))
+                    .build()
+            })
+    }
+
+    fn scheduler(elasticache: ElastiCacheClient, tagging: TaggingClient) -> ElastiCacheScheduler {
+        ElastiCacheScheduler {
+            elasticache,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stop_snapshots_the_group_settings_then_deletes_it() {
+        let groups = get_resources_rule("elasticache:replicationgroup", &[GROUP_ARN]);
+        let describe = describe_group_rule(false);
+        let cluster = mock!(ElastiCacheClient::describe_cache_clusters).then_output(|| {
+            DescribeCacheClustersOutput::builder()
+                .cache_clusters(
+                    CacheCluster::builder()
+                        .cache_cluster_id("orders-001")
+                        .engine("valkey")
+                        .engine_version("8.0")
+                        .cache_subnet_group_name("private")
+                        .build(),
+                )
+                .build()
+        });
+        let tags = list_tags_rule(GROUP_ARN.to_string(), &[("team", "data")]);
+        let previous = mock!(ElastiCacheClient::describe_snapshots)
+            .match_requests(|req| req.replication_group_id() == Some("orders"))
+            .then_output(|| {
+                DescribeSnapshotsOutput::builder()
+                    .snapshots(Snapshot::builder().snapshot_name(OLD_SNAPSHOT).build())
+                    .build()
+            });
+        let available = mock!(ElastiCacheClient::describe_snapshots)
+            .match_requests(|req| req.snapshot_name().is_some())
+            .then_output(|| {
+                DescribeSnapshotsOutput::builder()
+                    .snapshots(Snapshot::builder().snapshot_status("available").build())
+                    .build()
+            });
+        let snapshot = mock!(ElastiCacheClient::create_snapshot)
+            .match_requests(|req| {
+                let tag = |key: &str| {
+                    req.tags()
+                        .iter()
+                        .find(|t| t.key() == Some(key))
+                        .and_then(|t| t.value())
+                };
+                tag(REPLICATION_GROUP_ID_TAG) == Some("orders")
+                    && tag(ENGINE_TAG) == Some("valkey")
+                    && tag(REPLICAS_PER_NODE_GROUP_TAG) == Some("1")
+                    && tag(AT_REST_ENCRYPTION_TAG) == Some("true")
+                    && tag(TRANSIT_ENCRYPTION_TAG) == Some("true")
+                    && tag(KMS_KEY_ID_TAG) == Some("alias/cache")
+                    && tag(USER_GROUP_IDS_TAG) == Some("app-users")
+                    && tag(PORT_TAG) == Some("6380")
+                    && tag(MULTI_AZ_TAG) == Some("true")
+                    && tag("team") == Some("data")
+            })
+            .then_output(|| CreateSnapshotOutput::builder().build());
+        let delete = mock!(ElastiCacheClient::delete_replication_group)
+            .match_requests(|req| req.replication_group_id() == Some("orders"))
+            .then_output(|| DeleteReplicationGroupOutput::builder().build());
+        let cleanup = mock!(ElastiCacheClient::delete_snapshot)
+            .match_requests(|req| req.snapshot_name() == Some(OLD_SNAPSHOT))
+            .then_output(|| DeleteSnapshotOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_elasticache,
+                RuleMode::MatchAny,
+                [&describe, &cluster, &tags, &previous, &available, &snapshot, &delete, &cleanup]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&groups]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(snapshot.num_calls(), 1);
+        assert_eq!(delete.num_calls(), 1);
+        assert_eq!(cleanup.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_skips_groups_protected_by_an_auth_token() {
+        let groups = get_resources_rule("elasticache:replicationgroup", &[GROUP_ARN]);
+        let describe = describe_group_rule(true);
+        let snapshot = mock!(ElastiCacheClient::create_snapshot)
+            .then_output(|| CreateSnapshotOutput::builder().build());
+        let delete = mock!(ElastiCacheClient::delete_replication_group)
+            .then_output(|| DeleteReplicationGroupOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_elasticache,
+                RuleMode::MatchAny,
+                [&describe, &snapshot, &delete]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&groups]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!((counts.succeeded, counts.skipped), (0, 1));
+        assert_eq!(snapshot.num_calls(), 0);
+        assert_eq!(delete.num_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn start_restores_only_the_latest_snapshot_of_a_group() {
+        const SETTINGS: &[(&str, &str)] = &[
+            (REPLICATION_GROUP_ID_TAG, "orders"),
+            (NODE_TYPE_TAG, "cache.r6g.large"),
+            (ENGINE_TAG, "valkey"),
+            (ENGINE_VERSION_TAG, "8.0"),
+            (CLUSTER_MODE_TAG, "false"),
+            (NUM_NODE_GROUPS_TAG, "1"),
+            (REPLICAS_PER_NODE_GROUP_TAG, "1"),
+            (AUTOMATIC_FAILOVER_TAG, "true"),
+            (AT_REST_ENCRYPTION_TAG, "true"),
+            (TRANSIT_ENCRYPTION_TAG, "true"),
+            (KMS_KEY_ID_TAG, "alias/cache"),
+            (USER_GROUP_IDS_TAG, "app-users"),
+            (PORT_TAG, "6380"),
+            (MULTI_AZ_TAG, "true"),
+            ("team", "data"),
+        ];
+        let snapshots = get_resources_rule(
+            "elasticache:snapshot",
+            &[&snapshot_arn(OLD_SNAPSHOT), &snapshot_arn(NEW_SNAPSHOT)],
+        );
+        let old_tags = list_tags_rule(snapshot_arn(OLD_SNAPSHOT), SETTINGS);
+        let new_tags = list_tags_rule(snapshot_arn(NEW_SNAPSHOT), SETTINGS);
+        let missing = mock!(ElastiCacheClient::describe_replication_groups).then_error(|| {
+            DescribeReplicationGroupsError::ReplicationGroupNotFoundFault(
+                ReplicationGroupNotFoundFault::builder().build(),
+            )
+        });
+        let create = mock!(ElastiCacheClient::create_replication_group)
+            .match_requests(|req| {
+                req.snapshot_name() == Some(NEW_SNAPSHOT)
+                    && req.num_cache_clusters() == Some(2)
+                    && req.at_rest_encryption_enabled() == Some(true)
+                    && req.transit_encryption_enabled() == Some(true)
+                    && req.kms_key_id() == Some("alias/cache")
+                    && req.user_group_ids() == ["app-users".to_string()]
+                    && req.port() == Some(6380)
+                    && req.multi_az_enabled() == Some(true)
+                    && req.tags().iter().all(|t| t.key() == Some("team"))
+            })
+            .then_output(|| CreateReplicationGroupOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_elasticache,
+                RuleMode::MatchAny,
+                [&old_tags, &new_tags, &missing, &create]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&snapshots]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(create.num_calls(), 1);
+        let skipped = counts
+            .resources
+            .iter()
+            .find(|r| r.status == ResourceStatus::Skipped)
+            .unwrap();
+        assert_eq!(skipped.resource, OLD_SNAPSHOT);
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use aws_sdk_sesv2::types::{Body, Content, Destination, EmailContent, Message};
+use aws_sdk_sesv2::Client as SesClient;
+use tracing::info;
+
+use crate::config::AppConfig;
+use crate::credentials;
+use crate::sns;
+use crate::summary::{ExecutionSummary, ResourceStatus};
+
+/// Send the report of a run by email through SES.
+///
+/// The message holds an HTML table of the services and of every processed
+/// resource with its outcome, failures first, along with the plain text
+/// notification body for clients that do not render HTML. SES is called in
+/// the first region of `AWS_REGIONS`, with the ambient credentials.
+pub async fn send_report(
+    config: &AppConfig,
+    to: &[String],
+    from: &str,
+    summary: &ExecutionSummary,
+) -> Result<()> {
+    let sdk_config = credentials::load_config(config, &config.aws_regions[0], None).await;
+    let ses = SesClient::new(&sdk_config);
+
+    let message = Message::builder()
+        .subject(content(&sns::subject(summary))?)
+        .body(
+            Body::builder()
+                .html(content(&html(summary))?)
+                .text(content(&sns::message(summary))?)
+                .build(),
+        )
+        .build();
+
+    info!(to = ?to, "Sending execution report by email");
+    ses.send_email()
+        .from_email_address(from)
+        .destination(
+            Destination::builder()
+                .set_to_addresses(Some(to.to_vec()))
+                .build(),
+        )
+        .content(EmailContent::builder().simple(message).build())
+        .send()
+        .await
+        .context("Failed to send execution report through SES")?;
+    Ok(())
+}
+
+/// Build a UTF-8 SES message part.
+fn content(data: &str) -> Result<Content> {
+    Content::builder()
+        .data(data)
+        .charset("UTF-8")
+        .build()
+        .context("Failed to build email content")
+}
+
+/// Build the HTML body of the report.
+fn html(summary: &ExecutionSummary) -> String {
+    let mut body = format!(
+        "<h2>{}</h2>\n<p>Started at {}, took {:.1}s{}. Resources: {} discovered, {} succeeded, {} failed, {} skipped.</p>\n",
+        escape(&sns::subject(summary)),
+        escape(&summary.started_at),
+        summary.duration_seconds,
+        if summary.dry_run { " (dry run)" } else { "" },
+        summary.totals.discovered,
+        summary.totals.succeeded,
+        summary.totals.failed,
+        summary.totals.skipped,
+    );
+    if let Some(savings) = &summary.estimated_savings {
+        body.push_str(&format!(
+            "<p>Estimated savings: ${:.2}/hour</p>\n",
+            savings.hourly_usd
+        ));
+    }
+
+    body.push_str("<h3>Services</h3>\n<table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\n");
+    body.push_str("<tr><th>Service</th><th>Region</th><th>Discovered</th><th>Succeeded</th><th>Failed</th><th>Skipped</th><th>Error</th></tr>\n");
+    for s in &summary.services {
+        body.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&s.service),
+            escape(&s.region),
+            s.counts.discovered,
+            s.counts.succeeded,
+            s.counts.failed,
+            s.counts.skipped,
+            escape(s.error.as_deref().unwrap_or_default()),
+        ));
+    }
+    body.push_str("</table>\n");
+
+    let mut resources: Vec<_> = summary
+        .services
+        .iter()
+        .flat_map(|s| s.counts.resources.iter().map(move |r| (s, r)))
+        .collect();
+    if resources.is_empty() {
+        return body;
+    }
+    resources.sort_by_key(|(_, r)| r.status != ResourceStatus::Failed);
+
+    body.push_str("<h3>Resources</h3>\n<table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\n");
+    body.push_str(
+        "<tr><th>Service</th><th>Region</th><th>Resource</th><th>Outcome</th><th>Error</th></tr>\n",
+    );
+    for (s, r) in resources {
+        let color = match r.status {
+            ResourceStatus::Succeeded => "#2EB886",
+            ResourceStatus::Skipped => "#777777",
+            ResourceStatus::Failed => "#D9534F",
+        };
+        body.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td style=\"color:{}\">{}</td><td>{}</td></tr>\n",
+            escape(&s.service),
+            escape(&s.region),
+            escape(&r.resource),
+            color,
+            r.status.as_str(),
+            escape(r.error.as_deref().unwrap_or_default()),
+        ));
+    }
+    body.push_str("</table>\n");
+    body
+}
+
+/// Escape the characters with a special meaning in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
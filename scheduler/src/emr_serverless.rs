@@ -0,0 +1,322 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_emrserverless::types::ApplicationState;
+use aws_sdk_emrserverless::Client as EmrServerlessClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Stop/start handler for EMR Serverless applications in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover applications matching a tag,
+/// then performs the requested action on each one. Stopping an application
+/// releases its pre-initialized capacity.
+pub struct EmrServerlessScheduler {
+    emr: EmrServerlessClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl EmrServerlessScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            emr: EmrServerlessClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Get the current state of an application (e.g. `STARTED`, `STOPPED`).
+    async fn application_state(&self, application_id: &str) -> Result<ApplicationState> {
+        let resp = self
+            .emr
+            .get_application()
+            .application_id(application_id)
+            .send()
+            .await?;
+
+        resp.application()
+            .map(|a| a.state().clone())
+            .with_context(|| format!("EMR Serverless application '{}' not found", application_id))
+    }
+
+    async fn stop_application(&self, application_id: &str) -> Result<Outcome> {
+        let state = self.application_state(application_id).await?;
+        if matches!(
+            state,
+            ApplicationState::Stopped
+                | ApplicationState::Stopping
+                | ApplicationState::Created
+                | ApplicationState::Terminated
+        ) {
+            info!(application = %application_id, state = %state.as_str(), "EMR Serverless application not running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(application = %application_id, "Dry run: would stop EMR Serverless application");
+            return Ok(Outcome::Applied);
+        }
+        info!(application = %application_id, "Stopping EMR Serverless application");
+        self.emr
+            .stop_application()
+            .application_id(application_id)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn start_application(&self, application_id: &str) -> Result<Outcome> {
+        let state = self.application_state(application_id).await?;
+        if matches!(
+            state,
+            ApplicationState::Started | ApplicationState::Starting
+        ) {
+            info!(application = %application_id, state = %state.as_str(), "EMR Serverless application already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(application = %application_id, "Dry run: would start EMR Serverless application");
+            return Ok(Outcome::Applied);
+        }
+        info!(application = %application_id, "Starting EMR Serverless application");
+        self.emr
+            .start_application()
+            .application_id(application_id)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for EmrServerlessScheduler {
+    fn name(&self) -> &'static str {
+        "emr_serverless"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "emr-serverless:applications",
+            filter,
+        )
+        .await?;
+        info!(
+            count = arns.len(),
+            "Found EMR Serverless applications to stop"
+        );
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let application_id = extract_application_id(arn);
+            let result = self.stop_application(&application_id).await;
+            (application_id, result)
+        })
+        .await;
+        for (application_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&application_id, outcome),
+                Err(e) => {
+                    counts.fail(&application_id, &e);
+                    error!(application = %application_id, error = %e, "Failed to stop EMR Serverless application");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "emr-serverless:applications",
+            filter,
+        )
+        .await?;
+        info!(
+            count = arns.len(),
+            "Found EMR Serverless applications to start"
+        );
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let application_id = extract_application_id(arn);
+            let result = self.start_application(&application_id).await;
+            (application_id, result)
+        })
+        .await;
+        for (application_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&application_id, outcome),
+                Err(e) => {
+                    counts.fail(&application_id, &e);
+                    error!(application = %application_id, error = %e, "Failed to start EMR Serverless application");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the EMR Serverless applications matching the given filter with their current state.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "emr-serverless:applications",
+            filter,
+        )
+        .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let state = self
+                .application_state(&extract_application_id(&arn))
+                .await?;
+            items.push(InventoryItem::new(
+                arn,
+                "emr-serverless:applications",
+                state.as_str(),
+            ));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the application ID from an EMR Serverless application ARN.
+///
+/// Expected ARN format: `arn:partition:emr-serverless:region:account:/applications/application-id`
+fn extract_application_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_emrserverless::operation::get_application::GetApplicationOutput;
+    use aws_sdk_emrserverless::operation::start_application::StartApplicationOutput;
+    use aws_sdk_emrserverless::operation::stop_application::StopApplicationOutput;
+    use aws_sdk_emrserverless::primitives::DateTime;
+    use aws_sdk_emrserverless::types::Application;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const APPLICATION_ARN_PREFIX: &str =
+        "arn:aws:emr-serverless:eu-west-1:123456789012:/applications/";
+
+    /// Rule answering `application_id` in the given state.
+    fn get_application_rule(application_id: &'static str, state: ApplicationState) -> Rule {
+        mock!(EmrServerlessClient::get_application)
+            .match_requests(move |req| req.application_id() == Some(application_id))
+            .then_output(move || {
+                GetApplicationOutput::builder()
+                    .application(
+                        Application::builder()
+                            .application_id(application_id)
+                            .arn(format!("{APPLICATION_ARN_PREFIX}{application_id}"))
+                            .release_label("emr-7.0.0")
+                            .r#type("SPARK")
+                            .state(state.clone())
+                            .created_at(DateTime::from_secs(0))
+                            .updated_at(DateTime::from_secs(0))
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+            })
+    }
+
+    fn scheduler(emr: EmrServerlessClient, tagging: TaggingClient) -> EmrServerlessScheduler {
+        EmrServerlessScheduler {
+            emr,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_skips_applications_never_started() {
+        let arns = get_resources_rule(
+            "emr-serverless:applications",
+            &[
+                &format!("{APPLICATION_ARN_PREFIX}00fabcd1234"),
+                &format!("{APPLICATION_ARN_PREFIX}00fefgh5678"),
+            ],
+        );
+        let started = get_application_rule("00fabcd1234", ApplicationState::Started);
+        let created = get_application_rule("00fefgh5678", ApplicationState::Created);
+        let stop = mock!(EmrServerlessClient::stop_application)
+            .match_requests(|req| req.application_id() == Some("00fabcd1234"))
+            .then_output(|| StopApplicationOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_emrserverless,
+                RuleMode::MatchAny,
+                [&started, &created, &stop]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(stop.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_starts_stopped_applications() {
+        let arns = get_resources_rule(
+            "emr-serverless:applications",
+            &[&format!("{APPLICATION_ARN_PREFIX}00fabcd1234")],
+        );
+        let stopped = get_application_rule("00fabcd1234", ApplicationState::Stopped);
+        let start = mock!(EmrServerlessClient::start_application)
+            .match_requests(|req| req.application_id() == Some("00fabcd1234"))
+            .then_output(|| StartApplicationOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_emrserverless,
+                RuleMode::MatchAny,
+                [&stopped, &start]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(start.num_calls(), 1);
+    }
+}
@@ -0,0 +1,285 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_eventbridge::types::RuleState;
+use aws_sdk_eventbridge::Client as EventBridgeClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info};
+
+use crate::arn::{self, Arn};
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Disable/enable handler for EventBridge rules in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover rules matching a tag,
+/// then disables (stop) or enables (start) each one, so that scheduled
+/// targets do not keep firing against stopped resources.
+pub struct EventBridgeScheduler {
+    events: EventBridgeClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl EventBridgeScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            events: EventBridgeClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Check whether a rule is currently enabled.
+    async fn is_enabled(&self, bus: Option<&str>, rule: &str) -> Result<bool> {
+        let resp = self
+            .events
+            .describe_rule()
+            .name(rule)
+            .set_event_bus_name(bus.map(str::to_string))
+            .send()
+            .await?;
+
+        Ok(!matches!(resp.state(), Some(RuleState::Disabled) | None))
+    }
+
+    async fn disable_rule(&self, bus: Option<&str>, rule: &str) -> Result<Outcome> {
+        if !self.is_enabled(bus, rule).await? {
+            info!(rule = %rule, "EventBridge rule already disabled, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(rule = %rule, "Dry run: would disable EventBridge rule");
+            return Ok(Outcome::Applied);
+        }
+        info!(rule = %rule, "Disabling EventBridge rule");
+        self.events
+            .disable_rule()
+            .name(rule)
+            .set_event_bus_name(bus.map(str::to_string))
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn enable_rule(&self, bus: Option<&str>, rule: &str) -> Result<Outcome> {
+        if self.is_enabled(bus, rule).await? {
+            info!(rule = %rule, "EventBridge rule already enabled, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(rule = %rule, "Dry run: would enable EventBridge rule");
+            return Ok(Outcome::Applied);
+        }
+        info!(rule = %rule, "Enabling EventBridge rule");
+        self.events
+            .enable_rule()
+            .name(rule)
+            .set_event_bus_name(bus.map(str::to_string))
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for EventBridgeScheduler {
+    fn name(&self) -> &'static str {
+        "eventbridge"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "events:rule", filter).await?;
+        info!(count = arns.len(), "Found EventBridge rules to disable");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let (bus, rule) = extract_rule_names(arn);
+            let result = self.disable_rule(bus.as_deref(), &rule).await;
+            (rule, result)
+        })
+        .await;
+        for (rule, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&rule, outcome),
+                Err(e) => {
+                    counts.fail(&rule, &e);
+                    error!(rule = %rule, error = %e, "Failed to disable EventBridge rule");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "events:rule", filter).await?;
+        info!(count = arns.len(), "Found EventBridge rules to enable");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let (bus, rule) = extract_rule_names(arn);
+            let result = self.enable_rule(bus.as_deref(), &rule).await;
+            (rule, result)
+        })
+        .await;
+        for (rule, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&rule, outcome),
+                Err(e) => {
+                    counts.fail(&rule, &e);
+                    error!(rule = %rule, error = %e, "Failed to enable EventBridge rule");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the EventBridge rules matching the given filter, `enabled` or `disabled`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "events:rule", filter).await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let (bus, rule) = extract_rule_names(&arn);
+            let state = if self.is_enabled(bus.as_deref(), &rule).await? {
+                "enabled"
+            } else {
+                "disabled"
+            };
+            items.push(InventoryItem::new(arn, "events:rule", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the event bus name (`None` for the default bus) and rule name from an EventBridge rule ARN.
+///
+/// Expected ARN formats:
+/// - Default bus: `arn:partition:events:region:account:rule/rule-name`
+/// - Custom bus: `arn:partition:events:region:account:rule/bus-name/rule-name`
+fn extract_rule_names(arn: &str) -> (Option<String>, String) {
+    match Arn::parse(arn).map(|a| a.resource_segments()).as_deref() {
+        Ok([_, bus, rule]) => (Some(bus.to_string()), rule.to_string()),
+        _ => (None, arn::resource_id(arn)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_eventbridge::operation::describe_rule::DescribeRuleOutput;
+    use aws_sdk_eventbridge::operation::disable_rule::DisableRuleOutput;
+    use aws_sdk_eventbridge::operation::enable_rule::EnableRuleOutput;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const RULE_ARN_PREFIX: &str = "arn:aws:events:eu-west-1:123456789012:rule/";
+
+    /// Rule describing the EventBridge rule `rule` of `bus` in the given state.
+    fn describe_rule_rule(bus: Option<&'static str>, rule: &'static str, state: RuleState) -> Rule {
+        mock!(EventBridgeClient::describe_rule)
+            .match_requests(move |req| req.name() == Some(rule) && req.event_bus_name() == bus)
+            .then_output(move || {
+                DescribeRuleOutput::builder()
+                    .name(rule)
+                    .state(state.clone())
+                    .build()
+            })
+    }
+
+    fn scheduler(events: EventBridgeClient, tagging: TaggingClient) -> EventBridgeScheduler {
+        EventBridgeScheduler {
+            events,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_disables_rules_of_custom_buses() {
+        let arns = get_resources_rule(
+            "events:rule",
+            &[
+                &format!("{RULE_ARN_PREFIX}orders/nightly-export"),
+                &format!("{RULE_ARN_PREFIX}hourly-cleanup"),
+            ],
+        );
+        let nightly = describe_rule_rule(Some("orders"), "nightly-export", RuleState::Enabled);
+        let hourly = describe_rule_rule(None, "hourly-cleanup", RuleState::Disabled);
+        let disable = mock!(EventBridgeClient::disable_rule)
+            .match_requests(|req| {
+                req.name() == Some("nightly-export") && req.event_bus_name() == Some("orders")
+            })
+            .then_output(|| DisableRuleOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_eventbridge,
+                RuleMode::MatchAny,
+                [&nightly, &hourly, &disable]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(disable.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_enables_rules_of_the_default_bus() {
+        let arns = get_resources_rule(
+            "events:rule",
+            &[&format!("{RULE_ARN_PREFIX}hourly-cleanup")],
+        );
+        let hourly = describe_rule_rule(None, "hourly-cleanup", RuleState::Disabled);
+        let enable = mock!(EventBridgeClient::enable_rule)
+            .match_requests(|req| {
+                req.name() == Some("hourly-cleanup") && req.event_bus_name().is_none()
+            })
+            .then_output(|| EnableRuleOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_eventbridge, RuleMode::MatchAny, [&hourly, &enable]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(enable.num_calls(), 1);
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::{bail, Context, Result};
+use aws_sdk_eventbridge::types::PutEventsRequestEntry;
+use aws_sdk_eventbridge::Client as EventBridgeClient;
+use serde_json::json;
+use tracing::{error, info};
+
+use crate::config::AppConfig;
+use crate::credentials;
+use crate::summary::ExecutionSummary;
+
+/// Source of the events put on the bus.
+///
+/// EventBridge rejects custom events whose source starts with `aws.`, which is
+/// reserved for AWS services.
+pub const SOURCE: &str = "scheduler.stopstart";
+
+/// Detail type of the events put on the bus.
+const DETAIL_TYPE: &str = "Scheduled Resource Action";
+
+/// Maximum number of entries accepted by a single `PutEvents` call.
+const BATCH_SIZE: usize = 10;
+
+/// Put one event per processed resource on the given event bus (name or ARN).
+///
+/// The detail of each event holds the resource ARN or identifier, the service,
+/// region and account role, the action, the result (`succeeded`, `skipped` or
+/// `failed`) and, on failure, the error. Events are put in the first region of
+/// `AWS_REGIONS`, with the ambient credentials.
+pub async fn publish(
+    config: &AppConfig,
+    event_bus: &str,
+    summary: &ExecutionSummary,
+) -> Result<()> {
+    let entries = entries(event_bus, summary);
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let sdk_config = credentials::load_config(config, &config.aws_regions[0], None).await;
+    let eventbridge = EventBridgeClient::new(&sdk_config);
+
+    info!(event_bus = %event_bus, count = entries.len(), "Putting resource events on EventBridge");
+    let mut failed = 0;
+    for batch in entries.chunks(BATCH_SIZE) {
+        let resp = eventbridge
+            .put_events()
+            .set_entries(Some(batch.to_vec()))
+            .send()
+            .await
+            .with_context(|| format!("Failed to put events on event bus '{}'", event_bus))?;
+        for entry in resp.entries().iter().filter(|e| e.error_code().is_some()) {
+            error!(
+                error_code = entry.error_code().unwrap_or_default(),
+                error = entry.error_message().unwrap_or_default(),
+                "Failed to put event"
+            );
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        bail!(
+            "{} of {} event(s) could not be put on event bus '{}'",
+            failed,
+            entries.len(),
+            event_bus
+        );
+    }
+    Ok(())
+}
+
+/// Build the event of every processed resource of a run.
+fn entries(event_bus: &str, summary: &ExecutionSummary) -> Vec<PutEventsRequestEntry> {
+    summary
+        .services
+        .iter()
+        .flat_map(|service| {
+            service.counts.resources.iter().map(move |resource| {
+                let detail = json!({
+                    "resource": resource.resource,
+                    "service": service.service,
+                    "region": service.region,
+                    "role_arn": service.role_arn,
+                    "action": summary.action,
+                    "dry_run": summary.dry_run,
+                    "result": resource.status,
+                    "error": resource.error,
+                });
+                PutEventsRequestEntry::builder()
+                    .event_bus_name(event_bus)
+                    .source(SOURCE)
+                    .detail_type(DETAIL_TYPE)
+                    .detail(detail.to_string())
+                    .build()
+            })
+        })
+        .collect()
+}
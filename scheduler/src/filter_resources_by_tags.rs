@@ -1,24 +1,301 @@
-use anyhow::Result;
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
 use aws_sdk_resourcegroupstagging::types::TagFilter;
 use aws_sdk_resourcegroupstagging::Client;
+use chrono::Utc;
+use chrono_tz::Tz;
+use regex::Regex;
+use tracing::{info, warn};
+
+use crate::arn;
+use crate::config::{AppConfig, ScheduleAction};
+use crate::priority::Priority;
+use crate::window::ScheduleWindow;
+
+/// Criteria used to select the resources a scheduler acts on.
+#[derive(Debug, Clone)]
+pub struct ResourceFilter {
+    pub tag_key: String,
+    /// Accepted tag values; a resource matches when its tag holds any of them,
+    /// or whatever its value when empty.
+    pub tag_values: Vec<String>,
+    /// Tags a resource must also carry (any value when `None`), from `STOP_TAG_FILTER`/`START_TAG_FILTER`.
+    pub required_tags: Vec<(String, Option<String>)>,
+    /// Key of the tag holding a per-resource schedule window, if enabled.
+    pub window_tag_key: Option<String>,
+    /// Only resources whose name (the identifier in their ARN, without the resource type) fully matches it are acted upon.
+    pub name_include_regex: Option<Regex>,
+    /// Resources whose name fully matches it are skipped.
+    pub name_exclude_regex: Option<Regex>,
+    /// Resources carrying any of these tags (any value when `None`) are never acted upon.
+    pub protected_tags: Vec<(String, Option<String>)>,
+    /// Resources carrying this tag key are skipped (with `exclusion_tag_value`, only for that value).
+    pub exclusion_tag_key: Option<String>,
+    pub exclusion_tag_value: Option<String>,
+    /// Tag key that must be set to `true` on a resource for it to be terminated.
+    pub terminate_confirm_tag: Option<String>,
+    /// Maximum number of resources a service may act on in a region.
+    pub max_resources: Option<usize>,
+    /// ARNs of the resources of an applied plan, the only ones acted upon.
+    pub planned: Option<HashSet<String>>,
+    /// Key of the tag holding the priority of a resource, if enabled.
+    pub priority_tag_key: Option<String>,
+    /// Priority wave being processed: only resources with this priority are acted upon.
+    pub wave: Option<Priority>,
+    pub action: ScheduleAction,
+    pub timezone: Tz,
+}
+
+impl ResourceFilter {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            tag_key: config.tag_key.clone(),
+            tag_values: config.tag_values_for(&config.schedule_action).to_vec(),
+            required_tags: config.tag_filters_for(&config.schedule_action).to_vec(),
+            window_tag_key: config.schedule_window_tag.clone(),
+            name_include_regex: config.name_include_regex.clone(),
+            name_exclude_regex: config.name_exclude_regex.clone(),
+            protected_tags: config.protected_tags.clone(),
+            exclusion_tag_key: config.exclusion_tag_key.clone(),
+            exclusion_tag_value: config.exclusion_tag_value.clone(),
+            terminate_confirm_tag: config.terminate_confirm_tag.clone(),
+            max_resources: config.max_resources_per_service,
+            planned: config.planned_resources.clone(),
+            priority_tag_key: config.priority_tag.clone(),
+            wave: None,
+            action: config.schedule_action.clone(),
+            timezone: config.timezone,
+        }
+    }
+
+    /// Check whether a discovered resource should be acted upon, given its tags.
+    ///
+    /// While a priority wave is processed, resources of the other waves are silently left out.
+    /// Resources carrying a protected tag or the exclusion tag, or whose name does
+    /// not pass the name regular expressions, are never acted upon.
+    /// When a plan is applied, only the planned resources are acted upon.
+    /// Resources without a window tag are always acted upon. Otherwise, a resource
+    /// is stopped only while its window is closed and started only while it is open.
+    /// On list, the window is ignored.
+    /// Resources with an invalid window tag are left untouched, but still listed.
+    /// On terminate, resources must also carry the confirmation tag set to `true`.
+    pub fn allows<'a>(
+        &self,
+        arn: &str,
+        tags: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> bool {
+        let tags: Vec<(&str, &str)> = tags.into_iter().collect();
+        self.allows_named(arn, &arn::resource_id(arn), &tags)
+    }
+
+    /// Check whether a resource discovered outside of the Tagging API carries the
+    /// scheduler tag with one of the accepted values (any value when none is set)
+    /// and should be acted upon.
+    ///
+    /// `name` is the name the regular expressions apply to, for the resources
+    /// whose ARN does not end with it (Auto Scaling groups, Lightsail...).
+    pub fn matches<'a>(
+        &self,
+        arn: &str,
+        name: &str,
+        tags: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> bool {
+        let tags: Vec<(&str, &str)> = tags.into_iter().collect();
+        let tagged = tags.iter().any(|(key, value)| {
+            *key == self.tag_key
+                && (self.tag_values.is_empty() || self.tag_values.iter().any(|v| v == value))
+        });
+        tagged && self.allows_named(arn, name, &tags)
+    }
+
+    /// Refuse to act when a service discovered more resources than `MAX_RESOURCES_PER_SERVICE`,
+    /// so that a mis-scoped tag cannot stop a whole fleet. None of the resources is acted upon.
+    pub fn check_discovered(&self, discovered: usize) -> Result<()> {
+        match self.max_resources {
+            Some(max) if discovered > max => bail!(
+                "Discovered {} resources, more than MAX_RESOURCES_PER_SERVICE={}: not acting on any of them",
+                discovered,
+                max
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Same filter, restricted to the resources of a priority wave.
+    pub fn with_wave(&self, wave: Priority) -> Self {
+        Self {
+            wave: Some(wave),
+            ..self.clone()
+        }
+    }
+
+    fn allows_named(&self, arn: &str, name: &str, tags: &[(&str, &str)]) -> bool {
+        self.in_wave(tags)
+            && self.has_required_tags(arn, tags)
+            && self.is_planned(arn)
+            && !self.is_protected(arn, tags)
+            && self.name_allows(arn, name)
+            && !self.is_excluded(arn, tags)
+            && self.window_allows(arn, tags)
+            && self.termination_confirmed(arn, tags)
+    }
+
+    fn in_wave(&self, tags: &[(&str, &str)]) -> bool {
+        match (&self.wave, &self.priority_tag_key) {
+            (Some(wave), Some(key)) => Priority::of(key, tags) == *wave,
+            _ => true,
+        }
+    }
+
+    /// Resources are matched by their full ARN, so that a resource of another region or
+    /// account sharing the name of a planned one is not acted upon.
+    fn is_planned(&self, arn: &str) -> bool {
+        let Some(planned) = &self.planned else {
+            return true;
+        };
+        let planned = planned.contains(arn);
+        if !planned {
+            info!(resource = %arn, "Resource is not in the applied plan, skipping");
+        }
+        planned
+    }
+
+    fn has_required_tags(&self, resource: &str, tags: &[(&str, &str)]) -> bool {
+        let missing = self.required_tags.iter().find(|(k, v)| {
+            !tags
+                .iter()
+                .any(|(key, value)| k == key && v.as_deref().is_none_or(|v| v == *value))
+        });
+        if let Some((key, _)) = missing {
+            info!(resource = %resource, tag = %key, action = %self.action, "Resource does not carry the tags of the action filter, skipping");
+        }
+        missing.is_none()
+    }
+
+    fn is_protected(&self, resource: &str, tags: &[(&str, &str)]) -> bool {
+        let protected = tags.iter().find(|(key, value)| {
+            self.protected_tags
+                .iter()
+                .any(|(k, v)| k == key && v.as_deref().is_none_or(|v| v == *value))
+        });
+        if let Some((key, value)) = protected {
+            warn!(
+                resource = %resource,
+                tag = %format!("{}={}", key, value),
+                action = %self.action,
+                "Resource carries a protected tag (PROTECTED_TAG_FILTERS), refusing to act on it"
+            );
+        }
+        protected.is_some()
+    }
+
+    fn name_allows(&self, resource: &str, name: &str) -> bool {
+        if self
+            .name_include_regex
+            .as_ref()
+            .is_some_and(|r| !r.is_match(name))
+        {
+            info!(resource = %resource, "Resource name does not match RESOURCE_NAME_INCLUDE_REGEX, skipping");
+            return false;
+        }
+        if self
+            .name_exclude_regex
+            .as_ref()
+            .is_some_and(|r| r.is_match(name))
+        {
+            info!(resource = %resource, "Resource name matches RESOURCE_NAME_EXCLUDE_REGEX, skipping");
+            return false;
+        }
+        true
+    }
+
+    fn is_excluded(&self, resource: &str, tags: &[(&str, &str)]) -> bool {
+        let Some(exclusion_tag_key) = &self.exclusion_tag_key else {
+            return false;
+        };
+        let excluded = tags.iter().any(|(key, value)| {
+            key == exclusion_tag_key
+                && self
+                    .exclusion_tag_value
+                    .as_deref()
+                    .is_none_or(|v| v == *value)
+        });
+        if excluded {
+            info!(resource = %resource, tag = %exclusion_tag_key, "Resource carries the exclusion tag, skipping");
+        }
+        excluded
+    }
+
+    fn termination_confirmed(&self, resource: &str, tags: &[(&str, &str)]) -> bool {
+        if !matches!(self.action, ScheduleAction::Terminate) {
+            return true;
+        }
+        let Some(confirm_tag) = &self.terminate_confirm_tag else {
+            return false;
+        };
+        let confirmed = tags
+            .iter()
+            .any(|(key, value)| key == confirm_tag && value.eq_ignore_ascii_case("true"));
+        if !confirmed {
+            info!(resource = %resource, tag = %confirm_tag, "Resource does not confirm termination, skipping");
+        }
+        confirmed
+    }
+
+    fn window_allows(&self, resource: &str, tags: &[(&str, &str)]) -> bool {
+        let Some(window_tag_key) = &self.window_tag_key else {
+            return true;
+        };
+        let Some((_, value)) = tags.iter().find(|(key, _)| key == window_tag_key) else {
+            return true;
+        };
+
+        let window = match value.parse::<ScheduleWindow>() {
+            Ok(window) => window,
+            Err(e) if matches!(self.action, ScheduleAction::List) => {
+                warn!(resource = %resource, window = %value, error = %e, "Invalid schedule window tag");
+                return true;
+            }
+            Err(e) => {
+                warn!(resource = %resource, window = %value, error = %e, "Invalid schedule window tag, skipping resource");
+                return false;
+            }
+        };
+
+        let open = window.is_open(Utc::now(), self.timezone);
+        match (&self.action, open) {
+            (ScheduleAction::Stop | ScheduleAction::Terminate, true) => {
+                info!(resource = %resource, window = %value, "Schedule window is open, not stopping resource");
+                false
+            }
+            (ScheduleAction::Start, false) => {
+                info!(resource = %resource, window = %value, "Schedule window is closed, not starting resource");
+                false
+            }
+            _ => true,
+        }
+    }
+}
 
 /// Query the AWS Resource Groups Tagging API to find resources
 /// matching the given type and tag filter.
 ///
 /// Handles pagination automatically to retrieve all results.
+/// Excluded resources and resources whose schedule window tag does not allow
+/// the action are left out.
 ///
 /// Returns the list of ARNs of matching resources.
 pub async fn get_resources(
     client: &Client,
     resource_type: &str,
-    tag_key: &str,
-    tag_value: &str,
+    filter: &ResourceFilter,
 ) -> Result<Vec<String>> {
     let mut arns = Vec::new();
 
     let tag_filter = TagFilter::builder()
-        .key(tag_key)
-        .values(tag_value)
+        .key(&filter.tag_key)
+        .set_values((!filter.tag_values.is_empty()).then(|| filter.tag_values.clone()))
         .build();
 
     let mut pagination_token: Option<String> = None;
@@ -37,7 +314,10 @@ pub async fn get_resources(
 
         for mapping in response.resource_tag_mapping_list() {
             if let Some(arn) = mapping.resource_arn() {
-                arns.push(arn.to_string());
+                let tags = mapping.tags().iter().map(|t| (t.key(), t.value()));
+                if filter.allows(arn, tags) {
+                    arns.push(arn.to_string());
+                }
             }
         }
 
@@ -51,3 +331,343 @@ pub async fn get_resources(
 
     Ok(arns)
 }
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_resourcegroupstagging::error::ErrorMetadata;
+    use aws_sdk_resourcegroupstagging::operation::get_resources::{
+        GetResourcesError, GetResourcesOutput,
+    };
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+    use crate::test_util::{filter, tagged, TAG_KEY, TAG_VALUE};
+
+    #[tokio::test]
+    async fn get_resources_follows_pagination_tokens() {
+        let first_page = mock!(Client::get_resources)
+            .match_requests(|req| req.pagination_token().is_none())
+            .then_output(|| {
+                GetResourcesOutput::builder()
+                    .resource_tag_mapping_list(tagged(
+                        "arn:aws:ec2:eu-west-1:123456789012:instance/i-1",
+                        &[],
+                    ))
+                    .pagination_token("page-2")
+                    .build()
+            });
+        let second_page = mock!(Client::get_resources)
+            .match_requests(|req| req.pagination_token() == Some("page-2"))
+            .then_output(|| {
+                GetResourcesOutput::builder()
+                    .resource_tag_mapping_list(tagged(
+                        "arn:aws:ec2:eu-west-1:123456789012:instance/i-2",
+                        &[],
+                    ))
+                    .pagination_token("")
+                    .build()
+            });
+        let client = mock_client!(
+            aws_sdk_resourcegroupstagging,
+            RuleMode::MatchAny,
+            [&first_page, &second_page]
+        );
+
+        let arns = get_resources(&client, "ec2:instance", &filter(ScheduleAction::Stop))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            arns,
+            [
+                "arn:aws:ec2:eu-west-1:123456789012:instance/i-1",
+                "arn:aws:ec2:eu-west-1:123456789012:instance/i-2",
+            ]
+        );
+        assert_eq!(first_page.num_calls(), 1);
+        assert_eq!(second_page.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_resources_skips_excluded_resources() {
+        let rule = mock!(Client::get_resources).then_output(|| {
+            GetResourcesOutput::builder()
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:rds:eu-west-1:123456789012:db:kept",
+                    &[],
+                ))
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:rds:eu-west-1:123456789012:db:excluded",
+                    &[("scheduler:exclude", "true")],
+                ))
+                .build()
+        });
+        let client = mock_client!(aws_sdk_resourcegroupstagging, [&rule]);
+        let filter = ResourceFilter {
+            exclusion_tag_key: Some("scheduler:exclude".to_string()),
+            ..filter(ScheduleAction::Stop)
+        };
+
+        let arns = get_resources(&client, "rds:db", &filter).await.unwrap();
+
+        assert_eq!(arns, ["arn:aws:rds:eu-west-1:123456789012:db:kept"]);
+    }
+
+    #[tokio::test]
+    async fn get_resources_skips_protected_resources() {
+        let rule = mock!(Client::get_resources).then_output(|| {
+            GetResourcesOutput::builder()
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:rds:eu-west-1:123456789012:db:dev",
+                    &[("env", "dev")],
+                ))
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:rds:eu-west-1:123456789012:db:prod",
+                    &[("env", "prod")],
+                ))
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:rds:eu-west-1:123456789012:db:pci",
+                    &[("compliance", "pci")],
+                ))
+                .build()
+        });
+        let client = mock_client!(aws_sdk_resourcegroupstagging, [&rule]);
+        let filter = ResourceFilter {
+            protected_tags: vec![
+                ("env".to_string(), Some("prod".to_string())),
+                ("compliance".to_string(), None),
+            ],
+            ..filter(ScheduleAction::Stop)
+        };
+
+        let arns = get_resources(&client, "rds:db", &filter).await.unwrap();
+
+        assert_eq!(arns, ["arn:aws:rds:eu-west-1:123456789012:db:dev"]);
+    }
+
+    #[tokio::test]
+    async fn get_resources_requires_the_tags_of_the_action_filter() {
+        let rule = mock!(Client::get_resources).then_output(|| {
+            GetResourcesOutput::builder()
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:ec2:eu-west-1:123456789012:instance/i-autostart",
+                    &[("autostart", "true")],
+                ))
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:ec2:eu-west-1:123456789012:instance/i-manual",
+                    &[("autostart", "false")],
+                ))
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:ec2:eu-west-1:123456789012:instance/i-untagged",
+                    &[],
+                ))
+                .build()
+        });
+        let client = mock_client!(aws_sdk_resourcegroupstagging, [&rule]);
+        let filter = ResourceFilter {
+            required_tags: vec![("autostart".to_string(), Some("true".to_string()))],
+            ..filter(ScheduleAction::Start)
+        };
+
+        let arns = get_resources(&client, "ec2:instance", &filter)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            arns,
+            ["arn:aws:ec2:eu-west-1:123456789012:instance/i-autostart"]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_resources_keeps_the_resources_of_the_priority_wave() {
+        let output = || {
+            GetResourcesOutput::builder()
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:ec2:eu-west-1:123456789012:instance/i-consul",
+                    &[("scheduler:priority", "1")],
+                ))
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:ec2:eu-west-1:123456789012:instance/i-app",
+                    &[],
+                ))
+                .build()
+        };
+        let first_wave = mock!(Client::get_resources).then_output(output);
+        let last_wave = mock!(Client::get_resources).then_output(output);
+        let client = mock_client!(aws_sdk_resourcegroupstagging, [&first_wave, &last_wave]);
+        let filter = ResourceFilter {
+            priority_tag_key: Some("scheduler:priority".to_string()),
+            ..filter(ScheduleAction::Start)
+        };
+
+        let first = get_resources(
+            &client,
+            "ec2:instance",
+            &filter.with_wave(Priority::Value(1)),
+        )
+        .await
+        .unwrap();
+        let last = get_resources(&client, "ec2:instance", &filter.with_wave(Priority::Unset))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            first,
+            ["arn:aws:ec2:eu-west-1:123456789012:instance/i-consul"]
+        );
+        assert_eq!(last, ["arn:aws:ec2:eu-west-1:123456789012:instance/i-app"]);
+    }
+
+    #[tokio::test]
+    async fn get_resources_skips_resources_matching_the_name_exclude_regex() {
+        let rule = mock!(Client::get_resources).then_output(|| {
+            GetResourcesOutput::builder()
+                .resource_tag_mapping_list(tagged("arn:aws:rds:eu-west-1:123456789012:db:app", &[]))
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:rds:eu-west-1:123456789012:db:app-bastion",
+                    &[],
+                ))
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:rds:eu-west-1:123456789012:db:app-bastion-old",
+                    &[],
+                ))
+                .build()
+        });
+        let client = mock_client!(aws_sdk_resourcegroupstagging, [&rule]);
+        let filter = ResourceFilter {
+            name_exclude_regex: Some(Regex::new("^(?:.*-bastion)$").unwrap()),
+            ..filter(ScheduleAction::Stop)
+        };
+
+        let arns = get_resources(&client, "rds:db", &filter).await.unwrap();
+
+        assert_eq!(
+            arns,
+            [
+                "arn:aws:rds:eu-west-1:123456789012:db:app",
+                "arn:aws:rds:eu-west-1:123456789012:db:app-bastion-old",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_resources_lists_resources_with_an_invalid_window_tag() {
+        let rule = mock!(Client::get_resources).then_output(|| {
+            GetResourcesOutput::builder()
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:rds:eu-west-1:123456789012:db:app",
+                    &[("scheduler:window", "every day")],
+                ))
+                .build()
+        });
+        let client = mock_client!(aws_sdk_resourcegroupstagging, RuleMode::MatchAny, [&rule]);
+        let window_filter = |action| ResourceFilter {
+            window_tag_key: Some("scheduler:window".to_string()),
+            ..filter(action)
+        };
+
+        let stopped = get_resources(&client, "rds:db", &window_filter(ScheduleAction::Stop))
+            .await
+            .unwrap();
+        let listed = get_resources(&client, "rds:db", &window_filter(ScheduleAction::List))
+            .await
+            .unwrap();
+
+        assert!(stopped.is_empty());
+        assert_eq!(listed, ["arn:aws:rds:eu-west-1:123456789012:db:app"]);
+    }
+
+    #[tokio::test]
+    async fn get_resources_skips_unconfirmed_resources_on_terminate() {
+        let rule = mock!(Client::get_resources).then_output(|| {
+            GetResourcesOutput::builder()
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:ec2:eu-west-1:123456789012:instance/i-confirmed",
+                    &[("scheduler:terminate", "TRUE")],
+                ))
+                .resource_tag_mapping_list(tagged(
+                    "arn:aws:ec2:eu-west-1:123456789012:instance/i-unconfirmed",
+                    &[],
+                ))
+                .build()
+        });
+        let client = mock_client!(aws_sdk_resourcegroupstagging, [&rule]);
+        let filter = ResourceFilter {
+            terminate_confirm_tag: Some("scheduler:terminate".to_string()),
+            ..filter(ScheduleAction::Terminate)
+        };
+
+        let arns = get_resources(&client, "ec2:instance", &filter)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            arns,
+            ["arn:aws:ec2:eu-west-1:123456789012:instance/i-confirmed"]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_resources_matches_any_value_without_tag_values() {
+        let rule = mock!(Client::get_resources)
+            .match_requests(|req| {
+                req.tag_filters()
+                    .iter()
+                    .all(|f| f.key() == Some(TAG_KEY) && f.values().is_empty())
+            })
+            .then_output(|| {
+                GetResourcesOutput::builder()
+                    .resource_tag_mapping_list(tagged(
+                        "arn:aws:ec2:eu-west-1:123456789012:instance/i-1",
+                        &[],
+                    ))
+                    .build()
+            });
+        let client = mock_client!(aws_sdk_resourcegroupstagging, [&rule]);
+        let filter = ResourceFilter {
+            tag_values: Vec::new(),
+            ..filter(ScheduleAction::Stop)
+        };
+
+        let arns = get_resources(&client, "ec2:instance", &filter)
+            .await
+            .unwrap();
+
+        assert_eq!(arns, ["arn:aws:ec2:eu-west-1:123456789012:instance/i-1"]);
+        assert_eq!(rule.num_calls(), 1);
+    }
+
+    #[test]
+    fn matches_only_the_planned_resource_of_a_name_shared_across_regions() {
+        let planned = "arn:aws:autoscaling:eu-west-1:123456789012:autoScalingGroup:1a2b:autoScalingGroupName/web";
+        let other_region = "arn:aws:autoscaling:us-east-1:123456789012:autoScalingGroup:3c4d:autoScalingGroupName/web";
+        let other_account = "arn:aws:autoscaling:eu-west-1:210987654321:autoScalingGroup:5e6f:autoScalingGroupName/web";
+        let filter = ResourceFilter {
+            planned: Some(HashSet::from([planned.to_string()])),
+            ..filter(ScheduleAction::Stop)
+        };
+        let tags = [(TAG_KEY, TAG_VALUE)];
+
+        assert!(filter.matches(planned, "web", tags));
+        assert!(!filter.matches(other_region, "web", tags));
+        assert!(!filter.matches(other_account, "web", tags));
+        assert!(!filter.matches("web", "web", tags));
+    }
+
+    #[tokio::test]
+    async fn get_resources_returns_api_errors() {
+        let rule = mock!(Client::get_resources).then_error(|| {
+            GetResourcesError::generic(
+                ErrorMetadata::builder()
+                    .code("AccessDeniedException")
+                    .build(),
+            )
+        });
+        let client = mock_client!(aws_sdk_resourcegroupstagging, [&rule]);
+
+        let result = get_resources(&client, "ec2:instance", &filter(ScheduleAction::Start)).await;
+
+        assert!(result.is_err());
+    }
+}
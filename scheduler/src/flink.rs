@@ -0,0 +1,383 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_kinesisanalyticsv2::types::{
+    ApplicationDetail, ApplicationRestoreConfiguration, ApplicationRestoreType, ApplicationStatus,
+    RunConfiguration,
+};
+use aws_sdk_kinesisanalyticsv2::Client as KinesisAnalyticsClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Stop/start handler for Managed Service for Apache Flink (Kinesis Data Analytics)
+/// applications in a given AWS region.
+///
+/// On stop: stops each application gracefully, which takes a snapshot when
+/// snapshots are enabled for the application.
+/// On start: restarts each application from its latest snapshot, so that its
+/// state is preserved across the night.
+pub struct FlinkScheduler {
+    kinesisanalytics: KinesisAnalyticsClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl FlinkScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            kinesisanalytics: KinesisAnalyticsClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    async fn describe_application(&self, application: &str) -> Result<ApplicationDetail> {
+        let resp = self
+            .kinesisanalytics
+            .describe_application()
+            .application_name(application)
+            .send()
+            .await?;
+
+        resp.application_detail()
+            .cloned()
+            .with_context(|| format!("Flink application '{}' not found", application))
+    }
+
+    async fn stop_application(&self, application: &str) -> Result<Outcome> {
+        let detail = self.describe_application(application).await?;
+        let status = detail.application_status();
+        if !matches!(status, ApplicationStatus::Running) {
+            info!(application = %application, status = %status.as_str(), "Flink application not running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if !snapshots_enabled(&detail) {
+            warn!(application = %application, "Snapshots are disabled, the Flink application state will not be preserved");
+        }
+        if self.dry_run {
+            info!(application = %application, "Dry run: would stop Flink application");
+            return Ok(Outcome::Applied);
+        }
+        info!(application = %application, "Stopping Flink application");
+        self.kinesisanalytics
+            .stop_application()
+            .application_name(application)
+            .force(false)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Start an application from its latest snapshot, or without state when snapshots are disabled.
+    async fn start_application(&self, application: &str) -> Result<Outcome> {
+        let detail = self.describe_application(application).await?;
+        let status = detail.application_status();
+        if !matches!(status, ApplicationStatus::Ready) {
+            info!(application = %application, status = %status.as_str(), "Flink application not stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let restore_type = if snapshots_enabled(&detail) {
+            ApplicationRestoreType::RestoreFromLatestSnapshot
+        } else {
+            ApplicationRestoreType::SkipRestoreFromSnapshot
+        };
+        if self.dry_run {
+            info!(application = %application, restore = %restore_type.as_str(), "Dry run: would start Flink application");
+            return Ok(Outcome::Applied);
+        }
+        info!(application = %application, restore = %restore_type.as_str(), "Starting Flink application");
+        self.kinesisanalytics
+            .start_application()
+            .application_name(application)
+            .run_configuration(
+                RunConfiguration::builder()
+                    .application_restore_configuration(
+                        ApplicationRestoreConfiguration::builder()
+                            .application_restore_type(restore_type)
+                            .build()?,
+                    )
+                    .build(),
+            )
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for FlinkScheduler {
+    fn name(&self) -> &'static str {
+        "flink"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "kinesisanalytics:application",
+            filter,
+        )
+        .await?;
+        info!(count = arns.len(), "Found Flink applications to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let application = extract_application_name(arn);
+            let result = self.stop_application(&application).await;
+            (application, result)
+        })
+        .await;
+        for (application, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&application, outcome),
+                Err(e) => {
+                    counts.fail(&application, &e);
+                    error!(application = %application, error = %e, "Failed to stop Flink application");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "kinesisanalytics:application",
+            filter,
+        )
+        .await?;
+        info!(count = arns.len(), "Found Flink applications to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let application = extract_application_name(arn);
+            let result = self.start_application(&application).await;
+            (application, result)
+        })
+        .await;
+        for (application, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&application, outcome),
+                Err(e) => {
+                    counts.fail(&application, &e);
+                    error!(application = %application, error = %e, "Failed to start Flink application");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the Flink applications matching the given filter with their current status.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "kinesisanalytics:application",
+            filter,
+        )
+        .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let detail = self
+                .describe_application(&extract_application_name(&arn))
+                .await?;
+            items.push(InventoryItem::new(
+                arn,
+                "kinesisanalytics:application",
+                detail.application_status().as_str(),
+            ));
+        }
+        Ok(items)
+    }
+}
+
+/// Whether snapshots are enabled for an application.
+fn snapshots_enabled(detail: &ApplicationDetail) -> bool {
+    detail
+        .application_configuration_description()
+        .and_then(|c| c.application_snapshot_configuration_description())
+        .is_some_and(|s| s.snapshots_enabled())
+}
+
+/// Extract the application name from a Flink application ARN.
+///
+/// Expected ARN format: `arn:partition:kinesisanalytics:region:account:application/application-name`
+fn extract_application_name(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_kinesisanalyticsv2::operation::describe_application::DescribeApplicationOutput;
+    use aws_sdk_kinesisanalyticsv2::operation::start_application::{
+        StartApplicationInput, StartApplicationOutput,
+    };
+    use aws_sdk_kinesisanalyticsv2::operation::stop_application::StopApplicationOutput;
+    use aws_sdk_kinesisanalyticsv2::types::{
+        ApplicationConfigurationDescription, ApplicationSnapshotConfigurationDescription,
+        RuntimeEnvironment,
+    };
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const APPLICATION_ARN_PREFIX: &str =
+        "arn:aws:kinesisanalytics:eu-west-1:123456789012:application/";
+
+    /// Rule describing `application` with the given status, snapshots enabled or not.
+    fn describe_application_rule(
+        application: &'static str,
+        status: ApplicationStatus,
+        snapshots_enabled: bool,
+    ) -> Rule {
+        mock!(KinesisAnalyticsClient::describe_application)
+            .match_requests(move |req| req.application_name() == Some(application))
+            .then_output(move || {
+                let configuration = ApplicationConfigurationDescription::builder()
+                    .application_snapshot_configuration_description(
+                        ApplicationSnapshotConfigurationDescription::builder()
+                            .snapshots_enabled(snapshots_enabled)
+                            .build()
+                            .unwrap(),
+                    )
+                    .build();
+                DescribeApplicationOutput::builder()
+                    .application_detail(
+                        ApplicationDetail::builder()
+                            .application_arn(format!("{APPLICATION_ARN_PREFIX}{application}"))
+                            .application_name(application)
+                            .runtime_environment(RuntimeEnvironment::Flink118)
+                            .application_status(status.clone())
+                            .application_version_id(1)
+                            .application_configuration_description(configuration)
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+            })
+    }
+
+    /// Restore type requested when starting an application.
+    fn restore_type(req: &StartApplicationInput) -> Option<ApplicationRestoreType> {
+        req.run_configuration()
+            .and_then(|c| c.application_restore_configuration())
+            .map(|c| c.application_restore_type().clone())
+    }
+
+    fn scheduler(
+        kinesisanalytics: KinesisAnalyticsClient,
+        tagging: TaggingClient,
+    ) -> FlinkScheduler {
+        FlinkScheduler {
+            kinesisanalytics,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_stops_running_applications_without_forcing() {
+        let arns = get_resources_rule(
+            "kinesisanalytics:application",
+            &[
+                &format!("{APPLICATION_ARN_PREFIX}clickstream"),
+                &format!("{APPLICATION_ARN_PREFIX}billing"),
+            ],
+        );
+        let clickstream =
+            describe_application_rule("clickstream", ApplicationStatus::Running, true);
+        let billing = describe_application_rule("billing", ApplicationStatus::Ready, true);
+        let stop = mock!(KinesisAnalyticsClient::stop_application)
+            .match_requests(|req| {
+                req.application_name() == Some("clickstream") && req.force() == Some(false)
+            })
+            .then_output(|| StopApplicationOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_kinesisanalyticsv2,
+                RuleMode::MatchAny,
+                [&clickstream, &billing, &stop]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(stop.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_restores_from_the_latest_snapshot_only_when_enabled() {
+        let arns = get_resources_rule(
+            "kinesisanalytics:application",
+            &[
+                &format!("{APPLICATION_ARN_PREFIX}clickstream"),
+                &format!("{APPLICATION_ARN_PREFIX}billing"),
+            ],
+        );
+        let clickstream = describe_application_rule("clickstream", ApplicationStatus::Ready, true);
+        let billing = describe_application_rule("billing", ApplicationStatus::Ready, false);
+        let start_clickstream = mock!(KinesisAnalyticsClient::start_application)
+            .match_requests(|req| {
+                req.application_name() == Some("clickstream")
+                    && restore_type(req) == Some(ApplicationRestoreType::RestoreFromLatestSnapshot)
+            })
+            .then_output(|| StartApplicationOutput::builder().build());
+        let start_billing = mock!(KinesisAnalyticsClient::start_application)
+            .match_requests(|req| {
+                req.application_name() == Some("billing")
+                    && restore_type(req) == Some(ApplicationRestoreType::SkipRestoreFromSnapshot)
+            })
+            .then_output(|| StartApplicationOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_kinesisanalyticsv2,
+                RuleMode::MatchAny,
+                [&clickstream, &billing, &start_clickstream, &start_billing]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 2, 0, 0)
+        );
+        assert_eq!(start_clickstream.num_calls(), 1);
+        assert_eq!(start_billing.num_calls(), 1);
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_gamelift::types::{Ec2InstanceCounts, Tag};
+use aws_sdk_gamelift::Client as GameLiftClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Tags used to remember the capacity of a fleet before it was stopped.
+const PREVIOUS_MIN_SIZE_TAG: &str = "scheduler:previous-min-size";
+const PREVIOUS_DESIRED_SIZE_TAG: &str = "scheduler:previous-desired-size";
+
+/// Stop/start handler for GameLift fleets in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover fleets matching a tag.
+/// On stop: records the minimum and desired instance counts of the fleet's home
+/// location in tags, then scales it to 0 instances (the maximum is kept).
+/// On start: restores the recorded instance counts.
+pub struct GameLiftScheduler {
+    gamelift: GameLiftClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl GameLiftScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            gamelift: GameLiftClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Record the capacity of a fleet in tags, then scale it to 0 instances.
+    /// A fleet with no minimum and no desired instances is skipped, so its tags keep
+    /// the instance counts from before it was scaled to 0.
+    async fn scale_to_zero(&self, arn: &str, fleet_id: &str) -> Result<Outcome> {
+        let capacity = self.instance_counts(fleet_id).await?;
+        let min_size = capacity.minimum().unwrap_or_default();
+        let desired_size = capacity.desired().unwrap_or_default();
+
+        if min_size == 0 && desired_size == 0 {
+            info!(fleet = %fleet_id, "GameLift fleet already scaled to 0, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(fleet = %fleet_id, min_size, desired_size, "Dry run: would scale GameLift fleet to 0");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(fleet = %fleet_id, min_size, desired_size, "Recording previous fleet capacity");
+        self.gamelift
+            .tag_resource()
+            .resource_arn(arn)
+            .tags(gamelift_tag(PREVIOUS_MIN_SIZE_TAG, &min_size.to_string()))
+            .tags(gamelift_tag(
+                PREVIOUS_DESIRED_SIZE_TAG,
+                &desired_size.to_string(),
+            ))
+            .send()
+            .await?;
+
+        info!(fleet = %fleet_id, "Scaling GameLift fleet to 0");
+        self.update_capacity(fleet_id, 0, 0).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the capacity recorded on stop.
+    ///
+    /// Fleets with a non-zero desired instance count, or without a recorded
+    /// capacity, are left untouched.
+    async fn restore_capacity(&self, arn: &str, fleet_id: &str) -> Result<Outcome> {
+        let desired = self
+            .instance_counts(fleet_id)
+            .await?
+            .desired()
+            .unwrap_or_default();
+        if desired > 0 {
+            info!(fleet = %fleet_id, desired_size = desired, "GameLift fleet already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let tags = self.list_tags(arn).await?;
+        let (Some(min_size), Some(desired_size)) = (
+            tags.get(PREVIOUS_MIN_SIZE_TAG)
+                .and_then(|v| v.parse::<i32>().ok()),
+            tags.get(PREVIOUS_DESIRED_SIZE_TAG)
+                .and_then(|v| v.parse::<i32>().ok()),
+        ) else {
+            warn!(fleet = %fleet_id, "No recorded fleet capacity found, skipping GameLift fleet");
+            return Ok(Outcome::Skipped);
+        };
+
+        if self.dry_run {
+            info!(fleet = %fleet_id, min_size, desired_size, "Dry run: would restore GameLift fleet capacity");
+            return Ok(Outcome::Applied);
+        }
+        info!(fleet = %fleet_id, min_size, desired_size, "Restoring GameLift fleet capacity");
+        self.update_capacity(fleet_id, min_size, desired_size)
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Get the instance counts of a fleet in its home location.
+    async fn instance_counts(&self, fleet_id: &str) -> Result<Ec2InstanceCounts> {
+        let resp = self
+            .gamelift
+            .describe_fleet_capacity()
+            .fleet_ids(fleet_id)
+            .send()
+            .await?;
+
+        resp.fleet_capacity()
+            .first()
+            .and_then(|c| c.instance_counts())
+            .cloned()
+            .with_context(|| format!("GameLift fleet '{}' has no capacity information", fleet_id))
+    }
+
+    async fn list_tags(&self, arn: &str) -> Result<HashMap<String, String>> {
+        let resp = self
+            .gamelift
+            .list_tags_for_resource()
+            .resource_arn(arn)
+            .send()
+            .await?;
+
+        Ok(resp
+            .tags()
+            .iter()
+            .filter_map(|t| {
+                Some((
+                    t.key()?.to_string(),
+                    t.value().unwrap_or_default().to_string(),
+                ))
+            })
+            .collect())
+    }
+
+    async fn update_capacity(
+        &self,
+        fleet_id: &str,
+        min_size: i32,
+        desired_size: i32,
+    ) -> Result<()> {
+        self.gamelift
+            .update_fleet_capacity()
+            .fleet_id(fleet_id)
+            .min_size(min_size)
+            .desired_instances(desired_size)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for GameLiftScheduler {
+    fn name(&self) -> &'static str {
+        "gamelift"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(&self.tagging, "gamelift:fleet", filter)
+            .await?;
+        info!(count = arns.len(), "Found GameLift fleets to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let fleet_id = extract_fleet_id(arn);
+            let result = self.scale_to_zero(arn, &fleet_id).await;
+            (fleet_id, result)
+        })
+        .await;
+        for (fleet_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&fleet_id, outcome),
+                Err(e) => {
+                    counts.fail(&fleet_id, &e);
+                    error!(fleet = %fleet_id, error = %e, "Failed to stop GameLift fleet");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(&self.tagging, "gamelift:fleet", filter)
+            .await?;
+        info!(count = arns.len(), "Found GameLift fleets to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let fleet_id = extract_fleet_id(arn);
+            let result = self.restore_capacity(arn, &fleet_id).await;
+            (fleet_id, result)
+        })
+        .await;
+        for (fleet_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&fleet_id, outcome),
+                Err(e) => {
+                    counts.fail(&fleet_id, &e);
+                    error!(fleet = %fleet_id, error = %e, "Failed to start GameLift fleet");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the GameLift fleets matching the given filter, `scaled-to-zero` when their
+    /// desired instance count is 0 and `running` otherwise.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns = filter_resources_by_tags::get_resources(&self.tagging, "gamelift:fleet", filter)
+            .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let desired = self
+                .instance_counts(&extract_fleet_id(&arn))
+                .await?
+                .desired()
+                .unwrap_or_default();
+            let state = if desired == 0 {
+                "scaled-to-zero"
+            } else {
+                "running"
+            };
+            items.push(InventoryItem::new(arn, "gamelift:fleet", state));
+        }
+        Ok(items)
+    }
+}
+
+fn gamelift_tag(key: &str, value: &str) -> Tag {
+    Tag::builder().key(key).value(value).build()
+}
+
+/// Extract the fleet ID from a GameLift fleet ARN.
+///
+/// Expected ARN format: `arn:partition:gamelift:region:account:fleet/fleet-id`
+fn extract_fleet_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_gamelift::operation::describe_fleet_capacity::DescribeFleetCapacityOutput;
+    use aws_sdk_gamelift::operation::list_tags_for_resource::ListTagsForResourceOutput;
+    use aws_sdk_gamelift::operation::tag_resource::TagResourceOutput;
+    use aws_sdk_gamelift::operation::update_fleet_capacity::UpdateFleetCapacityOutput;
+    use aws_sdk_gamelift::types::FleetCapacity;
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const FLEET_ARN: &str =
+        "arn:aws:gamelift:eu-west-1:123456789012:fleet/fleet-2222bbbb-33cc-44dd-55ee-666666ffffff";
+
+    #[tokio::test]
+    async fn start_restores_the_tagged_instance_counts() {
+        let fleets = get_resources_rule("gamelift:fleet", &[FLEET_ARN]);
+        let capacity = mock!(GameLiftClient::describe_fleet_capacity).then_output(|| {
+            DescribeFleetCapacityOutput::builder()
+                .fleet_capacity(
+                    FleetCapacity::builder()
+                        .instance_counts(
+                            Ec2InstanceCounts::builder()
+                                .minimum(0)
+                                .desired(0)
+                                .maximum(4)
+                                .build(),
+                        )
+                        .build(),
+                )
+                .build()
+        });
+        let tags = mock!(GameLiftClient::list_tags_for_resource)
+            .match_requests(|req| req.resource_arn() == Some(FLEET_ARN))
+            .then_output(|| {
+                ListTagsForResourceOutput::builder()
+                    .tags(gamelift_tag(PREVIOUS_MIN_SIZE_TAG, "1"))
+                    .tags(gamelift_tag(PREVIOUS_DESIRED_SIZE_TAG, "3"))
+                    .build()
+            });
+        let update = mock!(GameLiftClient::update_fleet_capacity)
+            .match_requests(|req| req.min_size() == Some(1) && req.desired_instances() == Some(3))
+            .then_output(|| UpdateFleetCapacityOutput::builder().build());
+        let scheduler = GameLiftScheduler {
+            gamelift: mock_client!(
+                aws_sdk_gamelift,
+                RuleMode::MatchAny,
+                [&capacity, &tags, &update]
+            ),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&fleets]),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_records_the_instance_counts_then_scales_to_zero() {
+        let fleets = get_resources_rule("gamelift:fleet", &[FLEET_ARN]);
+        let capacity = mock!(GameLiftClient::describe_fleet_capacity).then_output(|| {
+            DescribeFleetCapacityOutput::builder()
+                .fleet_capacity(
+                    FleetCapacity::builder()
+                        .instance_counts(
+                            Ec2InstanceCounts::builder()
+                                .minimum(1)
+                                .desired(3)
+                                .maximum(4)
+                                .build(),
+                        )
+                        .build(),
+                )
+                .build()
+        });
+        let record = mock!(GameLiftClient::tag_resource)
+            .match_requests(|req| {
+                let tags: Vec<(&str, &str)> = req
+                    .tags()
+                    .iter()
+                    .map(|t| (t.key().unwrap_or_default(), t.value().unwrap_or_default()))
+                    .collect();
+                req.resource_arn() == Some(FLEET_ARN)
+                    && tags
+                        == [
+                            (PREVIOUS_MIN_SIZE_TAG, "1"),
+                            (PREVIOUS_DESIRED_SIZE_TAG, "3"),
+                        ]
+            })
+            .then_output(|| TagResourceOutput::builder().build());
+        let update = mock!(GameLiftClient::update_fleet_capacity)
+            .match_requests(|req| req.min_size() == Some(0) && req.desired_instances() == Some(0))
+            .then_output(|| UpdateFleetCapacityOutput::builder().build());
+        let scheduler = GameLiftScheduler {
+            gamelift: mock_client!(
+                aws_sdk_gamelift,
+                RuleMode::MatchAny,
+                [&capacity, &record, &update]
+            ),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&fleets]),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(record.num_calls(), 1);
+        assert_eq!(update.num_calls(), 1);
+    }
+}
@@ -0,0 +1,398 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use aws_config::SdkConfig;
+use aws_sdk_ssm::types::{AutomationExecutionStatus, CommandStatus, Target};
+use aws_sdk_ssm::Client as SsmClient;
+use serde_json::json;
+use tokio::time::Instant;
+use tracing::info;
+
+use crate::config::{AppConfig, Hook};
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Time between two checks of the SSM execution of a hook.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Moment a hook runs at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    PreStop,
+    PostStart,
+}
+
+impl HookEvent {
+    /// Name of the hook, as reported in the execution summary.
+    pub fn service(self) -> &'static str {
+        match self {
+            HookEvent::PreStop => "pre_stop_hook",
+            HookEvent::PostStart => "post_start_hook",
+        }
+    }
+}
+
+impl std::fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookEvent::PreStop => write!(f, "pre-stop"),
+            HookEvent::PostStart => write!(f, "post-start"),
+        }
+    }
+}
+
+/// Run a hook in a region and wait for it to complete, up to `HOOK_TIMEOUT_SECONDS`.
+///
+/// Webhooks receive a POST of the event, the action, the region and the tag filter,
+/// and succeed on a 2xx response. SSM Automation runbooks and Run Command documents
+/// succeed once their execution succeeded. The hook is recorded as a single resource.
+pub async fn run(
+    hook: &Hook,
+    event: HookEvent,
+    config: &AppConfig,
+    sdk_config: &SdkConfig,
+    region: &str,
+) -> Result<ResourceCounts> {
+    let mut counts = ResourceCounts::new(1);
+    if config.dry_run {
+        info!(hook = %hook, event = %event, region = %region, "Dry run: would run hook");
+        counts.record(&hook.to_string(), Outcome::Applied);
+        return Ok(counts);
+    }
+
+    info!(hook = %hook, event = %event, region = %region, "Running hook");
+    let timeout = Duration::from_secs(config.hook_timeout_seconds);
+    match hook {
+        Hook::Webhook(url) => post(url, event, config, region, timeout).await?,
+        Hook::Automation(document) => {
+            run_automation(&SsmClient::new(sdk_config), document, timeout).await?
+        }
+        Hook::RunCommand(document) => {
+            run_command(&SsmClient::new(sdk_config), document, config, timeout).await?
+        }
+    }
+    counts.record(&hook.to_string(), Outcome::Applied);
+    Ok(counts)
+}
+
+async fn post(
+    url: &str,
+    event: HookEvent,
+    config: &AppConfig,
+    region: &str,
+    timeout: Duration,
+) -> Result<()> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()?
+        .post(url)
+        .json(&json!({
+            "event": event.to_string(),
+            "action": config.schedule_action.to_string(),
+            "region": region,
+            "tag_key": config.tag_key,
+            "tag_values": config.tag_values_for(&config.schedule_action),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .context("Failed to post hook event to webhook")?;
+    Ok(())
+}
+
+/// Start an SSM Automation runbook and wait for its execution to succeed.
+async fn run_automation(ssm: &SsmClient, document: &str, timeout: Duration) -> Result<()> {
+    let resp = ssm
+        .start_automation_execution()
+        .document_name(document)
+        .send()
+        .await
+        .with_context(|| format!("Failed to start SSM Automation '{}'", document))?;
+    let execution_id = resp
+        .automation_execution_id()
+        .context("SSM Automation returned no execution ID")?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        let resp = ssm
+            .get_automation_execution()
+            .automation_execution_id(execution_id)
+            .send()
+            .await?;
+        let execution = resp.automation_execution();
+        match execution.and_then(|e| e.automation_execution_status()) {
+            Some(
+                AutomationExecutionStatus::Success
+                | AutomationExecutionStatus::CompletedWithSuccess,
+            ) => return Ok(()),
+            Some(
+                status @ (AutomationExecutionStatus::Failed
+                | AutomationExecutionStatus::Cancelled
+                | AutomationExecutionStatus::Timedout
+                | AutomationExecutionStatus::CompletedWithFailure
+                | AutomationExecutionStatus::Rejected
+                | AutomationExecutionStatus::Exited),
+            ) => bail!(
+                "SSM Automation '{}' ({}) ended with status {}: {}",
+                document,
+                execution_id,
+                status.as_str(),
+                execution
+                    .and_then(|e| e.failure_message())
+                    .unwrap_or_default()
+            ),
+            _ => {}
+        }
+        if Instant::now() + POLL_INTERVAL > deadline {
+            bail!(
+                "SSM Automation '{}' ({}) still running after {}s (HOOK_TIMEOUT_SECONDS)",
+                document,
+                execution_id,
+                timeout.as_secs()
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Send an SSM Run Command document to the instances carrying the scheduler tag
+/// and wait for the command to succeed on all of them.
+async fn run_command(
+    ssm: &SsmClient,
+    document: &str,
+    config: &AppConfig,
+    timeout: Duration,
+) -> Result<()> {
+    let tag_values = config.tag_values_for(&config.schedule_action);
+    let target = if tag_values.is_empty() {
+        Target::builder()
+            .key("tag-key")
+            .values(&config.tag_key)
+            .build()
+    } else {
+        Target::builder()
+            .key(format!("tag:{}", config.tag_key))
+            .set_values(Some(tag_values.to_vec()))
+            .build()
+    };
+    let resp = ssm
+        .send_command()
+        .document_name(document)
+        .targets(target)
+        .send()
+        .await
+        .with_context(|| format!("Failed to send SSM command '{}'", document))?;
+    let command_id = resp
+        .command()
+        .and_then(|c| c.command_id())
+        .context("SSM Run Command returned no command ID")?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        let resp = ssm.list_commands().command_id(command_id).send().await?;
+        let command = resp.commands().first();
+        match command.and_then(|c| c.status()) {
+            Some(CommandStatus::Success) => {
+                info!(
+                    command_id = %command_id,
+                    instances = command.map(|c| c.target_count()).unwrap_or_default(),
+                    "SSM command succeeded"
+                );
+                return Ok(());
+            }
+            Some(
+                status @ (CommandStatus::Failed
+                | CommandStatus::Cancelled
+                | CommandStatus::TimedOut),
+            ) => bail!(
+                "SSM command '{}' ({}) ended with status {} on {} of {} instance(s)",
+                document,
+                command_id,
+                status.as_str(),
+                command.map(|c| c.error_count()).unwrap_or_default(),
+                command.map(|c| c.target_count()).unwrap_or_default()
+            ),
+            _ => {}
+        }
+        if Instant::now() + POLL_INTERVAL > deadline {
+            bail!(
+                "SSM command '{}' ({}) still running after {}s (HOOK_TIMEOUT_SECONDS)",
+                document,
+                command_id,
+                timeout.as_secs()
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    use aws_sdk_ssm::operation::get_automation_execution::GetAutomationExecutionOutput;
+    use aws_sdk_ssm::operation::list_commands::ListCommandsOutput;
+    use aws_sdk_ssm::operation::send_command::SendCommandOutput;
+    use aws_sdk_ssm::operation::start_automation_execution::StartAutomationExecutionOutput;
+    use aws_sdk_ssm::types::{AutomationExecution, Command};
+    use aws_smithy_mocks::{mock, mock_client};
+
+    use super::*;
+    use crate::test_util::app_config;
+
+    /// Serve a single webhook request with the given status line, returning the URL to post to.
+    fn serve(status: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            // Read the whole request before answering, so that the client gets the status.
+            loop {
+                let read = stream.read(&mut buf).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..read]);
+                let text = String::from_utf8_lossy(&request);
+                let Some((headers, body)) = text.split_once("\r\n\r\n") else {
+                    continue;
+                };
+                let length = headers
+                    .lines()
+                    .find_map(|line| {
+                        line.to_ascii_lowercase()
+                            .strip_prefix("content-length:")
+                            .map(|value| value.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or_default();
+                if body.len() >= length {
+                    break;
+                }
+            }
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                status
+            )
+            .unwrap();
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn post_fails_on_an_error_status() {
+        let config = app_config(&[]);
+        for (status, succeeds) in [
+            ("200 OK", true),
+            ("404 Not Found", false),
+            ("500 Internal Server Error", false),
+        ] {
+            let result = post(
+                &serve(status),
+                HookEvent::PreStop,
+                &config,
+                "eu-west-1",
+                Duration::from_secs(10),
+            )
+            .await;
+            assert_eq!(result.is_ok(), succeeds, "{}", status);
+        }
+    }
+
+    #[tokio::test]
+    async fn run_automation_fails_unless_the_execution_succeeds() {
+        for (status, succeeds) in [
+            (AutomationExecutionStatus::Success, true),
+            (AutomationExecutionStatus::CompletedWithSuccess, true),
+            (AutomationExecutionStatus::Failed, false),
+            (AutomationExecutionStatus::Timedout, false),
+            (AutomationExecutionStatus::Cancelled, false),
+            (AutomationExecutionStatus::CompletedWithFailure, false),
+            (AutomationExecutionStatus::Rejected, false),
+            (AutomationExecutionStatus::Exited, false),
+        ] {
+            let start = mock!(SsmClient::start_automation_execution)
+                .match_requests(|req| req.document_name() == Some("Restart-App"))
+                .then_output(|| {
+                    StartAutomationExecutionOutput::builder()
+                        .automation_execution_id("exec-1")
+                        .build()
+                });
+            let execution_status = status.clone();
+            let get = mock!(SsmClient::get_automation_execution)
+                .match_requests(|req| req.automation_execution_id() == Some("exec-1"))
+                .then_output(move || {
+                    GetAutomationExecutionOutput::builder()
+                        .automation_execution(
+                            AutomationExecution::builder()
+                                .automation_execution_status(execution_status.clone())
+                                .failure_message("Step restart failed")
+                                .build(),
+                        )
+                        .build()
+                });
+            let ssm = mock_client!(aws_sdk_ssm, [&start, &get]);
+
+            let result = run_automation(&ssm, "Restart-App", Duration::from_secs(60)).await;
+            assert_eq!(result.is_ok(), succeeds, "{}", status.as_str());
+            if let Err(e) = result {
+                assert!(e.to_string().contains(status.as_str()), "{}", e);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn run_command_fails_on_a_timeout() {
+        let cases = [
+            (CommandStatus::Success, Duration::from_secs(60), None),
+            (
+                CommandStatus::TimedOut,
+                Duration::from_secs(60),
+                Some("ended with status TimedOut on 1 of 3 instance(s)"),
+            ),
+            (
+                CommandStatus::InProgress,
+                Duration::ZERO,
+                Some("still running after 0s"),
+            ),
+        ];
+        let config = app_config(&[]);
+        for (status, timeout, error) in cases {
+            let send = mock!(SsmClient::send_command)
+                .match_requests(|req| req.document_name() == Some("Flush-Caches"))
+                .then_output(|| {
+                    SendCommandOutput::builder()
+                        .command(Command::builder().command_id("cmd-1").build())
+                        .build()
+                });
+            let command_status = status.clone();
+            let list = mock!(SsmClient::list_commands)
+                .match_requests(|req| req.command_id() == Some("cmd-1"))
+                .then_output(move || {
+                    ListCommandsOutput::builder()
+                        .commands(
+                            Command::builder()
+                                .command_id("cmd-1")
+                                .status(command_status.clone())
+                                .target_count(3)
+                                .error_count(1)
+                                .build(),
+                        )
+                        .build()
+                });
+            let ssm = mock_client!(aws_sdk_ssm, [&send, &list]);
+
+            let result = run_command(&ssm, "Flush-Caches", &config, timeout).await;
+            match error {
+                None => assert!(result.is_ok(), "{}", status.as_str()),
+                Some(error) => {
+                    let e = result.unwrap_err().to_string();
+                    assert!(e.contains(error), "{}", e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// A resource matched by the tag filter, with its current state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryItem {
+    pub arn: String,
+    /// Resource type, in the `service:resource` format of the Resource Groups Tagging API.
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    /// Region the resource was discovered in, filled in by [`Inventory::add`].
+    pub region: String,
+    /// Current state of the resource, as reported by the service API.
+    pub state: String,
+}
+
+impl InventoryItem {
+    pub fn new(arn: impl Into<String>, resource_type: &str, state: impl Into<String>) -> Self {
+        Self {
+            arn: arn.into(),
+            resource_type: resource_type.to_string(),
+            region: String::new(),
+            state: state.into(),
+        }
+    }
+}
+
+/// A service whose resources could not be listed in one region (and account).
+#[derive(Debug, Clone, Serialize)]
+pub struct InventoryError {
+    pub service: String,
+    pub region: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role_arn: Option<String>,
+    pub error: String,
+}
+
+/// Machine-readable inventory of the resources matched by the filters,
+/// printed as JSON by `SCHEDULE_ACTION=list`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Inventory {
+    pub resources: Vec<InventoryItem>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<InventoryError>,
+}
+
+impl Inventory {
+    /// Record the resources listed for one service in one region, or the error that prevented it.
+    pub fn add(
+        &mut self,
+        service: &str,
+        region: &str,
+        role_arn: Option<&str>,
+        result: Result<Vec<InventoryItem>>,
+    ) {
+        match result {
+            Ok(items) => self
+                .resources
+                .extend(items.into_iter().map(|item| InventoryItem {
+                    region: region.to_string(),
+                    ..item
+                })),
+            Err(e) => self.errors.push(InventoryError {
+                service: service.to_string(),
+                region: region.to_string(),
+                role_arn: role_arn.map(str::to_string),
+                error: format!("{:#}", e),
+            }),
+        }
+    }
+
+    /// Merge the inventory of another region into this one.
+    pub fn merge(&mut self, other: Inventory) {
+        self.resources.extend(other.resources);
+        self.errors.extend(other.errors);
+    }
+}
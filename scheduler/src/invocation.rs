@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+
+use crate::config::AppConfig;
+
+/// Services that can be selected by name, after their `<NAME>_SCHEDULE` variable.
+const SERVICES: [&str; 36] = [
+    "ec2",
+    "apprunner",
+    "autoscaling",
+    "cloudwatch_alarm",
+    "documentdb",
+    "ecs",
+    "rds",
+    "redshift",
+    "transfer",
+    "lambda",
+    "sagemaker",
+    "eks",
+    "redshift_serverless",
+    "workspaces",
+    "opensearch",
+    "elasticache",
+    "emr_serverless",
+    "mwaa",
+    "eventbridge",
+    "dms",
+    "nat_gateway",
+    "appstream",
+    "lightsail",
+    "flink",
+    "cloud9",
+    "client_vpn",
+    "dynamodb",
+    "codepipeline",
+    "gamelift",
+    "vpc_endpoints",
+    "ec2_fleet",
+    "cloudfront",
+    "route53_healthcheck",
+    "comprehend",
+    "ssm_mw",
+    "dlm",
+];
+
+/// Payload of a Lambda invocation, e.g. the input of an EventBridge schedule:
+///
+/// ```json
+/// {"action": "stop", "tag_key": "env", "tag_values": ["staging"], "services": ["ec2", "rds"]}
+/// ```
+///
+/// Every field is optional and overrides the matching environment variable, so
+/// that one function can serve several schedules. Unknown fields are ignored,
+/// which lets a plain scheduled event run with the environment configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Invocation {
+    /// Overrides `SCHEDULE_ACTION`.
+    pub action: Option<String>,
+    /// Overrides `TAG_KEY`.
+    pub tag_key: Option<String>,
+    /// Overrides `TAG_VALUE`.
+    pub tag_values: Option<Vec<String>>,
+    /// Overrides `AWS_REGIONS`.
+    pub regions: Option<Vec<String>>,
+    /// Services to process, named after their `<NAME>_SCHEDULE` variable
+    /// (e.g. `ec2`, `cloudwatch_alarm`); every other service is disabled.
+    pub services: Option<Vec<String>>,
+    /// Overrides `DRY_RUN`.
+    pub dry_run: Option<bool>,
+}
+
+impl Invocation {
+    /// Build the configuration of this invocation from the environment, `CONFIG_FILE`,
+    /// `CONFIG_SSM_PREFIX` and the payload.
+    ///
+    /// The action is always performed once: `RUN_MODE` is ignored.
+    pub async fn config(&self) -> Result<AppConfig> {
+        AppConfig::load_with(self.overrides()?).await
+    }
+
+    /// Translate the payload into configuration variables.
+    fn overrides(&self) -> Result<HashMap<String, String>> {
+        let mut vars = HashMap::from([("RUN_MODE".to_string(), "once".to_string())]);
+        if let Some(action) = &self.action {
+            vars.insert("SCHEDULE_ACTION".to_string(), action.clone());
+        }
+        if let Some(tag_key) = &self.tag_key {
+            vars.insert("TAG_KEY".to_string(), tag_key.clone());
+        }
+        if let Some(tag_values) = &self.tag_values {
+            vars.insert("TAG_VALUE".to_string(), tag_values.join(","));
+        }
+        if let Some(regions) = &self.regions {
+            vars.insert("AWS_REGIONS".to_string(), regions.join(","));
+        }
+        if let Some(dry_run) = self.dry_run {
+            vars.insert("DRY_RUN".to_string(), dry_run.to_string());
+        }
+
+        if let Some(services) = &self.services {
+            let services: Vec<String> = services.iter().map(|s| s.trim().to_lowercase()).collect();
+            if let Some(unknown) = services.iter().find(|s| !SERVICES.contains(&s.as_str())) {
+                bail!(
+                    "Unknown service '{}': must be one of {}",
+                    unknown,
+                    SERVICES.join(", ")
+                );
+            }
+            for service in SERVICES {
+                let enabled = services.iter().any(|s| s == service);
+                vars.insert(
+                    format!("{}_SCHEDULE", service.to_uppercase()),
+                    enabled.to_string(),
+                );
+            }
+        }
+
+        Ok(vars)
+    }
+}
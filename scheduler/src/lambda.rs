@@ -0,0 +1,392 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_lambda::Client as LambdaClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info};
+
+use crate::arn::Arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Stop/start handler for AWS Lambda event source mappings in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover functions matching a tag,
+/// then disables (stop) or enables (start) every event source mapping
+/// (SQS, Kinesis, DynamoDB streams, ...) attached to each function.
+pub struct LambdaScheduler {
+    lambda: LambdaClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl LambdaScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            lambda: LambdaClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Enable or disable every event source mapping of a function.
+    ///
+    /// Mappings already in the target state are left untouched; the function is
+    /// reported as skipped when none of its mappings needed a change.
+    /// Errors on individual mappings are logged without interrupting the processing,
+    /// and reported as a single error once every mapping has been processed.
+    async fn update_mappings(
+        &self,
+        function_arn: &str,
+        function_name: &str,
+        enabled: bool,
+    ) -> Result<Outcome> {
+        let mappings = self.list_mappings(function_arn).await?;
+        info!(function = %function_name, count = mappings.len(), "Found event source mappings");
+
+        let target_states: &[&str] = if enabled {
+            &["Enabled", "Enabling"]
+        } else {
+            &["Disabled", "Disabling"]
+        };
+
+        let mut updated = 0;
+        let mut failed = 0;
+        for (uuid, state) in &mappings {
+            if target_states.contains(&state.as_str()) {
+                info!(function = %function_name, mapping = %uuid, state = %state, "Event source mapping already in target state, skipping");
+                continue;
+            }
+            match self.update_mapping(function_name, uuid, enabled).await {
+                Ok(()) => updated += 1,
+                Err(e) => {
+                    error!(function = %function_name, mapping = %uuid, error = %e, "Failed to update event source mapping");
+                    failed += 1;
+                }
+            }
+        }
+
+        if failed > 0 {
+            bail!(
+                "{} of {} event source mapping(s) could not be updated",
+                failed,
+                mappings.len()
+            );
+        }
+
+        Ok(if updated > 0 {
+            Outcome::Applied
+        } else {
+            Outcome::Skipped
+        })
+    }
+
+    /// List the UUID and state of all event source mappings attached to a function.
+    async fn list_mappings(&self, function_arn: &str) -> Result<Vec<(String, String)>> {
+        let mut mappings = Vec::new();
+        let mut marker: Option<String> = None;
+
+        loop {
+            let mut req = self
+                .lambda
+                .list_event_source_mappings()
+                .function_name(function_arn);
+            if let Some(ref token) = marker {
+                req = req.marker(token);
+            }
+
+            let resp = req.send().await?;
+
+            for mapping in resp.event_source_mappings() {
+                if let Some(uuid) = mapping.uuid() {
+                    let state = mapping.state().unwrap_or_default();
+                    mappings.push((uuid.to_string(), state.to_string()));
+                }
+            }
+
+            match resp.next_marker() {
+                Some(token) if !token.is_empty() => marker = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        Ok(mappings)
+    }
+
+    async fn update_mapping(&self, function_name: &str, uuid: &str, enabled: bool) -> Result<()> {
+        if self.dry_run {
+            let action = if enabled { "enable" } else { "disable" };
+            info!(function = %function_name, mapping = %uuid, "Dry run: would {action} event source mapping");
+            return Ok(());
+        }
+        let action = if enabled { "Enabling" } else { "Disabling" };
+        info!(function = %function_name, mapping = %uuid, "{action} event source mapping");
+        self.lambda
+            .update_event_source_mapping()
+            .uuid(uuid)
+            .enabled(enabled)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for LambdaScheduler {
+    fn name(&self) -> &'static str {
+        "lambda"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "lambda:function", filter)
+                .await?;
+        info!(
+            count = arns.len(),
+            "Found Lambda functions to disable event source mappings"
+        );
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let function_name = extract_function_name(arn);
+            let result = self.update_mappings(arn, &function_name, false).await;
+            (function_name, result)
+        })
+        .await;
+        for (function_name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&function_name, outcome),
+                Err(e) => {
+                    counts.fail(&function_name, &e);
+                    error!(function = %function_name, error = %e, "Failed to disable event source mappings");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "lambda:function", filter)
+                .await?;
+        info!(
+            count = arns.len(),
+            "Found Lambda functions to enable event source mappings"
+        );
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let function_name = extract_function_name(arn);
+            let result = self.update_mappings(arn, &function_name, true).await;
+            (function_name, result)
+        })
+        .await;
+        for (function_name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&function_name, outcome),
+                Err(e) => {
+                    counts.fail(&function_name, &e);
+                    error!(function = %function_name, error = %e, "Failed to enable event source mappings");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the Lambda functions matching the given filter with the state of their event
+    /// source mappings (`mappings-enabled`, `mappings-disabled`, `mappings-mixed` or `no-mappings`).
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "lambda:function", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let mappings = self.list_mappings(&arn).await?;
+            let state = if mappings.is_empty() {
+                "no-mappings"
+            } else if mappings.iter().all(|(_, state)| state == "Enabled") {
+                "mappings-enabled"
+            } else if mappings.iter().all(|(_, state)| state == "Disabled") {
+                "mappings-disabled"
+            } else {
+                "mappings-mixed"
+            };
+            items.push(InventoryItem::new(arn, "lambda:function", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the function name from a Lambda function ARN.
+///
+/// Expected ARN format: `arn:partition:lambda:region:account:function:name`
+fn extract_function_name(arn: &str) -> String {
+    match Arn::parse(arn) {
+        Ok(parsed) => parsed
+            .resource_segments()
+            .get(1)
+            .copied()
+            .unwrap_or(arn)
+            .to_string(),
+        Err(_) => arn.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_lambda::error::ErrorMetadata;
+    use aws_sdk_lambda::operation::list_event_source_mappings::ListEventSourceMappingsOutput;
+    use aws_sdk_lambda::operation::update_event_source_mapping::{
+        UpdateEventSourceMappingError, UpdateEventSourceMappingOutput,
+    };
+    use aws_sdk_lambda::types::EventSourceMappingConfiguration;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const FUNCTION_ARN: &str = "arn:aws:lambda:eu-west-1:123456789012:function:orders-consumer";
+
+    /// Rule answering a page of the mappings of the function, as `(uuid, state)`,
+    /// requested with `marker` and pointing to `next_marker`.
+    fn list_mappings_rule(
+        marker: Option<&'static str>,
+        mappings: &'static [(&'static str, &'static str)],
+        next_marker: Option<&'static str>,
+    ) -> Rule {
+        mock!(LambdaClient::list_event_source_mappings)
+            .match_requests(move |req| {
+                req.function_name() == Some(FUNCTION_ARN) && req.marker() == marker
+            })
+            .then_output(move || {
+                let mappings = mappings.iter().map(|(uuid, state)| {
+                    EventSourceMappingConfiguration::builder()
+                        .uuid(*uuid)
+                        .state(*state)
+                        .build()
+                });
+                ListEventSourceMappingsOutput::builder()
+                    .set_event_source_mappings(Some(mappings.collect()))
+                    .set_next_marker(next_marker.map(str::to_string))
+                    .build()
+            })
+    }
+
+    /// Rule enabling or disabling the mapping `uuid`.
+    fn update_mapping_rule(uuid: &'static str, enabled: bool) -> Rule {
+        mock!(LambdaClient::update_event_source_mapping)
+            .match_requests(move |req| req.uuid() == Some(uuid) && req.enabled() == Some(enabled))
+            .then_output(|| UpdateEventSourceMappingOutput::builder().build())
+    }
+
+    fn scheduler(lambda: LambdaClient, tagging: TaggingClient) -> LambdaScheduler {
+        LambdaScheduler {
+            lambda,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_disables_the_mappings_of_every_page() {
+        let arns = get_resources_rule("lambda:function", &[FUNCTION_ARN]);
+        let first_page = list_mappings_rule(
+            None,
+            &[
+                ("6d9bce8e-kinesis", "Enabled"),
+                ("1f2a3b4c-sqs", "Disabled"),
+            ],
+            Some("page-2"),
+        );
+        let second_page =
+            list_mappings_rule(Some("page-2"), &[("9e8d7c6b-dynamodb", "Enabled")], None);
+        let kinesis = update_mapping_rule("6d9bce8e-kinesis", false);
+        let dynamodb = update_mapping_rule("9e8d7c6b-dynamodb", false);
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_lambda,
+                RuleMode::MatchAny,
+                [&first_page, &second_page, &kinesis, &dynamodb]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(counts.resources[0].resource, "orders-consumer");
+        assert_eq!(kinesis.num_calls(), 1);
+        assert_eq!(dynamodb.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_fails_the_function_when_a_mapping_cannot_be_enabled() {
+        let arns = get_resources_rule("lambda:function", &[FUNCTION_ARN]);
+        let mappings = list_mappings_rule(
+            None,
+            &[
+                ("6d9bce8e-kinesis", "Disabled"),
+                ("1f2a3b4c-sqs", "Disabled"),
+            ],
+            None,
+        );
+        let kinesis = update_mapping_rule("6d9bce8e-kinesis", true);
+        let sqs = mock!(LambdaClient::update_event_source_mapping)
+            .match_requests(|req| req.uuid() == Some("1f2a3b4c-sqs"))
+            .then_error(|| {
+                UpdateEventSourceMappingError::generic(
+                    ErrorMetadata::builder()
+                        .code("ResourceInUseException")
+                        .build(),
+                )
+            });
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_lambda,
+                RuleMode::MatchAny,
+                [&mappings, &kinesis, &sqs]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 0, 0, 1)
+        );
+        assert_eq!(
+            counts.resources[0].error.as_deref(),
+            Some("1 of 2 event source mapping(s) could not be updated")
+        );
+        assert_eq!(kinesis.num_calls(), 1);
+    }
+}
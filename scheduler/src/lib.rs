@@ -0,0 +1,928 @@
+//! Stop and start AWS resources on a schedule, based on their tags.
+//!
+//! Each supported service has its own [`ResourceScheduler`] (e.g. [`ec2::Ec2Scheduler`],
+//! [`rds::RdsScheduler`]) that discovers resources through a [`ResourceFilter`]
+//! and stops or starts them. [`run`] drives every scheduler enabled in an
+//! [`AppConfig`] across the configured accounts and regions, as the container does.
+
+mod app_autoscaling;
+mod appconfig;
+pub mod apprunner;
+pub mod appstream;
+pub mod arn;
+mod audit;
+pub mod autoscaling;
+pub mod calendar;
+pub mod clientvpn;
+pub mod cloud9;
+pub mod cloudfront;
+pub mod cloudwatch;
+mod cloudwatch_metrics;
+pub mod codepipeline;
+pub mod comprehend;
+mod concurrency;
+pub mod config;
+mod credentials;
+mod daemon;
+pub mod dlm;
+pub mod dms;
+pub mod documentdb;
+pub mod dynamodb;
+pub mod ec2;
+mod ec2_batch;
+mod ec2_shutdown;
+pub mod ec2fleet;
+pub mod ecs;
+pub mod eks;
+pub mod elasticache;
+mod email;
+pub mod emr_serverless;
+pub mod eventbridge;
+mod events;
+pub mod filter_resources_by_tags;
+pub mod flink;
+pub mod gamelift;
+mod hooks;
+pub mod inventory;
+pub mod invocation;
+pub mod lambda;
+pub mod lightsail;
+mod lock;
+mod metrics;
+pub mod mwaa;
+pub mod natgateway;
+pub mod opensearch;
+pub mod plan;
+pub mod priority;
+pub mod rds;
+pub mod redshift;
+pub mod redshift_serverless;
+mod regions;
+pub mod registry;
+pub mod route53;
+pub mod sagemaker;
+mod savings;
+mod server;
+mod shutdown;
+mod sns;
+pub mod ssm;
+pub mod state;
+pub mod status;
+pub mod summary;
+pub mod telemetry;
+#[cfg(test)]
+mod test_util;
+pub mod transfer;
+pub mod vpcendpoints;
+mod waiters;
+mod webhook;
+mod window;
+pub mod workspaces;
+
+pub use registry::{Registry, ResourceScheduler};
+
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use aws_config::SdkConfig;
+use chrono::{Datelike, Utc, Weekday};
+use chrono_tz::Tz;
+use tokio::task::{JoinError, JoinSet};
+use tokio::time::Instant;
+use tracing::{error, info, info_span, Instrument};
+
+use calendar::DateSpec;
+use config::{AppConfig, Hook, RunMode, ScheduleAction};
+use filter_resources_by_tags::ResourceFilter;
+use hooks::HookEvent;
+use inventory::{Inventory, InventoryError};
+use lock::RunLock;
+use plan::Plan;
+use status::StatusReport;
+use summary::{ExecutionSummary, ResourceCounts, ServiceSummary};
+use waiters::Waiter;
+
+/// Run the scheduler with the given configuration.
+///
+/// Performs the configured action on the AWS resources matching the configured tag,
+/// either once or on a cron schedule in daemon mode. With `SCHEDULE_ACTION=list`
+/// or `status`, resources are only listed or checked.
+///
+/// On SIGTERM/SIGINT, no new operation is started and the operations in flight
+/// are completed before returning.
+pub async fn run(config: &AppConfig) -> Result<()> {
+    shutdown::listen();
+    let config = &prepare(config).await?;
+    info!(
+        mode = %config.run_mode,
+        action = %config.schedule_action,
+        regions = ?config.aws_regions,
+        service_regions = ?config.service_regions,
+        accounts = ?config.assume_role_arns,
+        max_concurrent_regions = config.max_concurrent_regions,
+        execution_timeout_seconds = ?config.execution_timeout_seconds,
+        service_timeout_seconds = ?config.service_timeout_seconds,
+        retry_max_attempts = config.retry_max_attempts,
+        lock_table = ?config.lock_table,
+        tag = %match config.tag_values.is_empty() {
+            true => format!("{}=*", config.tag_key),
+            false => format!("{}={}", config.tag_key, config.tag_values.join(",")),
+        },
+        stop_tag_values = ?config.stop_tag_values,
+        start_tag_values = ?config.start_tag_values,
+        stop_tag_filters = ?config.stop_tag_filters,
+        start_tag_filters = ?config.start_tag_filters,
+        schedule_window_tag = ?config.schedule_window_tag,
+        priority_tag = ?config.priority_tag,
+        exclusion_tag = ?config.exclusion_tag_key.as_ref().map(|k| match &config.exclusion_tag_value {
+            Some(v) => format!("{}={}", k, v),
+            None => k.clone(),
+        }),
+        terminate_confirm_tag = ?config.terminate_confirm_tag,
+        ec2 = config.ec2_schedule,
+        ec2_stop_mode = %config.ec2_stop_mode,
+        ec2_graceful_shutdown = config.ec2_graceful_shutdown,
+        apprunner = config.apprunner_schedule,
+        autoscaling = config.autoscaling_schedule,
+        autoscaling_mode = %config.autoscaling_mode,
+        autoscaling_delete_on_terminate = config.autoscaling_delete_on_terminate,
+        autoscaling_scheduled_actions = %config.autoscaling_scheduled_actions,
+        cloudwatch = config.cloudwatch_alarm_schedule,
+        documentdb = config.documentdb_schedule,
+        ecs = config.ecs_schedule,
+        ecs_drain_container_instances = config.ecs_drain_container_instances,
+        rds = config.rds_schedule,
+        rds_serverless_v2_mode = %config.rds_serverless_v2_mode,
+        redshift = config.redshift_schedule,
+        transfer = config.transfer_schedule,
+        lambda = config.lambda_schedule,
+        sagemaker = config.sagemaker_schedule,
+        eks = config.eks_schedule,
+        redshift_serverless = config.redshift_serverless_schedule,
+        workspaces = config.workspaces_schedule,
+        opensearch = config.opensearch_schedule,
+        elasticache = config.elasticache_schedule,
+        emr_serverless = config.emr_serverless_schedule,
+        mwaa = config.mwaa_schedule,
+        eventbridge = config.eventbridge_schedule,
+        dms = config.dms_schedule,
+        nat_gateway = config.nat_gateway_schedule,
+        appstream = config.appstream_schedule,
+        lightsail = config.lightsail_schedule,
+        flink = config.flink_schedule,
+        cloud9 = config.cloud9_schedule,
+        client_vpn = config.client_vpn_schedule,
+        dynamodb = config.dynamodb_schedule,
+        codepipeline = config.codepipeline_schedule,
+        gamelift = config.gamelift_schedule,
+        vpc_endpoints = config.vpc_endpoints_schedule,
+        ec2_fleet = config.ec2_fleet_schedule,
+        cloudfront = config.cloudfront_schedule,
+        route53_healthcheck = config.route53_healthcheck_schedule,
+        comprehend = config.comprehend_schedule,
+        ssm_maintenance_window = config.ssm_mw_schedule,
+        dlm = config.dlm_schedule,
+        dry_run = config.dry_run,
+        appconfig = ?config.appconfig.as_ref().map(|s| s.to_string()),
+        "Scheduler initialized"
+    );
+
+    match config.run_mode {
+        RunMode::Once if matches!(config.schedule_action, ScheduleAction::List) => {
+            list_inventory(config).await
+        }
+        RunMode::Once if matches!(config.schedule_action, ScheduleAction::Status) => {
+            check_status(config).await
+        }
+        RunMode::Once => execute(config).await,
+        RunMode::Daemon => daemon::run(config).await,
+        RunMode::Server => server::run(config).await,
+    }
+}
+
+/// Complete the configuration with what is looked up at startup: the iCal calendar
+/// of `EXCLUDED_DATES_ICAL_URL` is fetched, and the regions of `AWS_REGIONS=all`
+/// are enumerated.
+async fn prepare(config: &AppConfig) -> Result<AppConfig> {
+    let mut config = config.clone();
+    if let Some(url) = &config.excluded_dates_ical_url {
+        let dates = calendar::fetch_ical(url)
+            .await
+            .context("Failed to load EXCLUDED_DATES_ICAL_URL")?;
+        config.excluded_dates.extend(dates);
+    }
+    if let Some(discovery) = config.region_discovery {
+        config.aws_regions = regions::discover(&config, discovery).await?;
+    }
+    Ok(config)
+}
+
+/// Plan the configured stop/start action and write the plan as JSON to `path`.
+///
+/// The plan lists the resources matched across all configured accounts and
+/// regions that the action would change, with their current state.
+pub async fn plan(config: &AppConfig, path: &Path) -> Result<()> {
+    let config = prepare(config).await?;
+    let plan = Plan::new(&config.schedule_action, collect_inventory(&config).await)?;
+    fs::write(path, serde_json::to_string_pretty(&plan)?)
+        .with_context(|| format!("Failed to write plan to '{}'", path.display()))?;
+
+    info!(action = %plan.action, count = plan.resources.len(), path = %path.display(), "Plan written");
+    Ok(())
+}
+
+/// Apply a plan written by [`plan`]: perform its action on its resources only.
+///
+/// The resources are listed again first, and the plan is refused if any of them
+/// is no longer matched or changed state since the plan was made. `config` must
+/// hold the action of the plan.
+pub async fn apply(config: &AppConfig, plan: &Plan) -> Result<()> {
+    shutdown::listen();
+    let mut config = prepare(config).await?;
+    info!(action = %plan.action, count = plan.resources.len(), created_at = %plan.created_at, "Applying plan");
+    plan.verify(&collect_inventory(&config).await)?;
+    if plan.resources.is_empty() {
+        info!("Nothing to apply");
+        return Ok(());
+    }
+
+    config.planned_resources = Some(plan.arns());
+    execute(&config).await
+}
+
+/// Check whether today's date in the given timezone is in the exclusion list.
+fn is_date_excluded(excluded_dates: &[DateSpec], timezone: Tz) -> bool {
+    let today = Utc::now().with_timezone(&timezone).date_naive();
+    excluded_dates.iter().any(|d| d.matches(today))
+}
+
+/// Check whether today's date in the given timezone is one of the active days
+/// and within one of the active date ranges, when they are set.
+fn is_date_active(active_days: &[Weekday], active_date_ranges: &[DateSpec], timezone: Tz) -> bool {
+    let today = Utc::now().with_timezone(&timezone).date_naive();
+    (active_days.is_empty() || active_days.contains(&today.weekday()))
+        && (active_date_ranges.is_empty() || active_date_ranges.iter().any(|r| r.matches(today)))
+}
+
+/// Execute the stop/start action across all configured accounts and regions.
+///
+/// Once every region has been processed, a JSON summary is printed to stdout,
+/// and an error is returned if any service or resource failed.
+async fn execute(config: &AppConfig) -> Result<()> {
+    let Some(summary) = perform(config).await? else {
+        return Ok(());
+    };
+    println!("{}", serde_json::to_string(&summary)?);
+
+    if summary.interrupted {
+        bail!("Execution interrupted by a shutdown signal");
+    }
+    let failed = summary.failed_services();
+    if !failed.is_empty() {
+        let names: Vec<String> = failed
+            .iter()
+            .map(|s| format!("{}/{}", s.region, s.service))
+            .collect();
+        bail!("Execution completed with failures: {}", names.join(", "));
+    }
+
+    info!("Execution completed");
+    Ok(())
+}
+
+/// Perform the stop/start action across all configured accounts and regions,
+/// and return the summary of the run (also published to SNS, posted to a webhook,
+/// sent by email, put on EventBridge, written to the audit store and recorded in the metrics when configured).
+///
+/// Skips execution, returning `None`, if today is an excluded date or outside
+/// the active days and date ranges or, when
+/// `LOCK_TABLE` is set, if another run of the same action holds the run lock.
+/// Returns an error if the run lock cannot be acquired.
+async fn perform(config: &AppConfig) -> Result<Option<ExecutionSummary>> {
+    if is_date_excluded(&config.excluded_dates, config.timezone) {
+        info!(
+            date = %Utc::now().with_timezone(&config.timezone).format("%Y-%m-%d (%a)"),
+            timezone = %config.timezone,
+            "Today is an excluded date, skipping execution"
+        );
+        return Ok(None);
+    }
+    if !is_date_active(
+        &config.active_days,
+        &config.active_date_ranges,
+        config.timezone,
+    ) {
+        info!(
+            date = %Utc::now().with_timezone(&config.timezone).format("%Y-%m-%d (%a)"),
+            timezone = %config.timezone,
+            "Today is outside the active days, skipping execution"
+        );
+        return Ok(None);
+    }
+
+    let lock = match &config.lock_table {
+        Some(table) => match RunLock::acquire(config, table).await? {
+            Some(lock) => Some(lock),
+            None => {
+                info!("Another run is in progress, skipping execution");
+                return Ok(None);
+            }
+        },
+        None => None,
+    };
+
+    let summary = perform_regions(config)
+        .instrument(info_span!("run", action = %config.schedule_action))
+        .await;
+    if let Some(lock) = lock {
+        lock.release().await;
+    }
+
+    metrics::record(&summary);
+    if let Some(url) = &config.pushgateway_url {
+        if let Err(e) = metrics::push(url).await {
+            error!(error = %e, "Failed to push metrics");
+        }
+    }
+    if let Some(namespace) = &config.emf_namespace {
+        for record in cloudwatch_metrics::emf_records(namespace, &summary) {
+            println!("{}", record);
+        }
+    }
+    if let Some(namespace) = &config.cloudwatch_metrics_namespace {
+        if let Err(e) = cloudwatch_metrics::publish(config, namespace, &summary).await {
+            error!(namespace = %namespace, error = %e, "Failed to publish metrics to CloudWatch");
+        }
+    }
+    Ok(Some(summary))
+}
+
+/// Perform the stop/start action across all configured accounts and regions.
+///
+/// Each account × region pair is processed concurrently, at most
+/// `max_concurrent_regions` at a time. Without `assume_role_arns`, only the
+/// account of the ambient credentials is processed.
+/// Errors on individual regions are logged without interrupting the processing of others.
+/// Once a shutdown is requested, no new region is started.
+/// Services still running when `EXECUTION_TIMEOUT_SECONDS` elapses are aborted.
+async fn perform_regions(config: &AppConfig) -> ExecutionSummary {
+    let started_at = Utc::now();
+    let deadline = config
+        .execution_timeout_seconds
+        .map(|s| Instant::now() + Duration::from_secs(s));
+    let role_arns: Vec<Option<String>> = if config.assume_role_arns.is_empty() {
+        vec![None]
+    } else {
+        config.assume_role_arns.iter().cloned().map(Some).collect()
+    };
+
+    let config = Arc::new(config.clone());
+    let mut tasks = JoinSet::new();
+    let mut services: Vec<ServiceSummary> = Vec::new();
+
+    'roles: for role_arn in &role_arns {
+        for region in &config.all_regions() {
+            if tasks.len() >= config.max_concurrent_regions {
+                if let Some(joined) = tasks.join_next().await {
+                    collect_region_result(joined, &mut services);
+                }
+            }
+            if shutdown::requested() {
+                break 'roles;
+            }
+
+            let config = Arc::clone(&config);
+            let role_arn = role_arn.clone();
+            let region = region.clone();
+            let span = info_span!("region", region = %region, role = role_arn.as_deref().unwrap_or("default"));
+            tasks.spawn(
+                async move { process_region(&config, role_arn.as_deref(), &region, deadline).await }.instrument(span),
+            );
+        }
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        collect_region_result(joined, &mut services);
+    }
+
+    let mut summary = ExecutionSummary::new(
+        config.schedule_action.to_string(),
+        config.dry_run,
+        started_at,
+        services,
+        shutdown::requested(),
+    );
+    if config.estimate_savings && matches!(config.schedule_action, ScheduleAction::Stop) {
+        summary.estimated_savings = Some(savings::estimate(&config, &summary).await);
+    }
+
+    if let Some(topic_arn) = &config.sns_topic_arn {
+        if let Err(e) = sns::publish_summary(&config, topic_arn, &summary).await {
+            error!(topic = %topic_arn, error = %e, "Failed to publish execution summary to SNS");
+        }
+    }
+    if let Some(url) = &config.webhook_url {
+        if let Err(e) = webhook::post_summary(url, config.webhook_format, &summary).await {
+            error!(error = %e, "Failed to post execution summary to webhook");
+        }
+    }
+    if let Some(from) = &config.report_email_from {
+        if !config.report_email_to.is_empty() {
+            if let Err(e) =
+                email::send_report(&config, &config.report_email_to, from, &summary).await
+            {
+                error!(error = %e, "Failed to send execution report by email");
+            }
+        }
+    }
+    if let Some(event_bus) = &config.event_bus_name {
+        if let Err(e) = events::publish(&config, event_bus, &summary).await {
+            error!(event_bus = %event_bus, error = %e, "Failed to put resource events on EventBridge");
+        }
+    }
+    if let Some(store) = &config.audit_store {
+        if let Err(e) = audit::record(&config, store, &summary).await {
+            error!(store = %store, error = %e, "Failed to write audit records");
+        }
+    }
+
+    summary
+}
+
+/// List the resources matched across all configured accounts and regions.
+///
+/// The inventory is printed as JSON to stdout, and an error is returned
+/// if any service could not be listed.
+async fn list_inventory(config: &AppConfig) -> Result<()> {
+    let inventory = collect_inventory(config).await;
+    println!("{}", serde_json::to_string(&inventory)?);
+
+    if !inventory.errors.is_empty() {
+        let names: Vec<String> = inventory
+            .errors
+            .iter()
+            .map(|e| format!("{}/{}", e.region, e.service))
+            .collect();
+        bail!("Listing completed with failures: {}", names.join(", "));
+    }
+
+    info!(count = inventory.resources.len(), "Listing completed");
+    Ok(())
+}
+
+/// Check that the resources matched across all configured accounts and regions
+/// are in the state implied by the most recent run of `STOP_CRON`/`START_CRON`.
+///
+/// Resources are filtered as that run would have filtered them, so that resources
+/// kept running by their schedule window are not reported. The report is printed
+/// as JSON to stdout, and an error is returned if any resource drifted or any
+/// service could not be listed.
+async fn check_status(config: &AppConfig) -> Result<()> {
+    let now = Utc::now().with_timezone(&config.timezone);
+    let Some((last_run_at, last_action)) = daemon::last_run(config, &now) else {
+        bail!("STOP_CRON/START_CRON have no occurrence before now");
+    };
+    info!(action = %last_action, at = %last_run_at, "Checking state against the most recent scheduled run");
+
+    let run_config = AppConfig {
+        schedule_action: last_action.clone(),
+        ..config.clone()
+    };
+    let inventory = collect_inventory(&run_config).await;
+    let report = StatusReport::new(&last_action, last_run_at.with_timezone(&Utc), inventory);
+    println!("{}", serde_json::to_string(&report)?);
+
+    if !report.errors.is_empty() {
+        let names: Vec<String> = report
+            .errors
+            .iter()
+            .map(|e| format!("{}/{}", e.region, e.service))
+            .collect();
+        bail!("Status check completed with failures: {}", names.join(", "));
+    }
+    if !report.drift.is_empty() {
+        let arns: Vec<&str> = report.drift.iter().map(|item| item.arn.as_str()).collect();
+        bail!(
+            "{} resource(s) not in the state expected after the last {}: {}",
+            arns.len(),
+            last_action,
+            arns.join(", ")
+        );
+    }
+
+    info!(checked = report.checked, "No drift detected");
+    Ok(())
+}
+
+/// List the resources matched across all configured accounts and regions.
+///
+/// Regions are processed concurrently like in [`execute`], but no resource is
+/// changed and excluded dates and active days do not apply.
+async fn collect_inventory(config: &AppConfig) -> Inventory {
+    let role_arns: Vec<Option<String>> = if config.assume_role_arns.is_empty() {
+        vec![None]
+    } else {
+        config.assume_role_arns.iter().cloned().map(Some).collect()
+    };
+
+    let config = Arc::new(config.clone());
+    let mut tasks = JoinSet::new();
+    let mut inventory = Inventory::default();
+
+    for role_arn in &role_arns {
+        for region in &config.all_regions() {
+            if tasks.len() >= config.max_concurrent_regions {
+                if let Some(joined) = tasks.join_next().await {
+                    collect_region_inventory(joined, &mut inventory);
+                }
+            }
+
+            let config = Arc::clone(&config);
+            let role_arn = role_arn.clone();
+            let region = region.clone();
+            let span = info_span!("region", region = %region, role = role_arn.as_deref().unwrap_or("default"));
+            tasks.spawn(
+                async move { list_region(&config, role_arn.as_deref(), &region).await }
+                    .instrument(span),
+            );
+        }
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        collect_region_inventory(joined, &mut inventory);
+    }
+
+    inventory
+}
+
+/// Merge the inventory of a region task.
+///
+/// A task that panicked is recorded as an error.
+fn collect_region_inventory(joined: Result<Inventory, JoinError>, inventory: &mut Inventory) {
+    match joined {
+        Ok(region_inventory) => inventory.merge(region_inventory),
+        Err(e) => {
+            error!(error = %e, "Region task panicked or was cancelled");
+            inventory.errors.push(InventoryError {
+                service: "unknown".to_string(),
+                region: "unknown".to_string(),
+                role_arn: None,
+                error: format!("Region task panicked or was cancelled: {}", e),
+            });
+        }
+    }
+}
+
+/// Collect the service summaries of a region task.
+///
+/// A task that panicked is recorded as a failed service.
+fn collect_region_result(
+    joined: Result<Vec<ServiceSummary>, JoinError>,
+    services: &mut Vec<ServiceSummary>,
+) {
+    match joined {
+        Ok(summaries) => services.extend(summaries),
+        Err(e) => {
+            error!(error = %e, "Region task panicked or was cancelled");
+            services.push(ServiceSummary::new(
+                "unknown",
+                "unknown",
+                None,
+                Err(anyhow!("Region task panicked or was cancelled: {}", e)),
+            ));
+        }
+    }
+}
+
+/// Terminate the EC2 instances and Auto Scaling Groups of a single region,
+/// with the region and credentials of `sdk_config`.
+///
+/// Only these services support the terminate action; the others are left untouched.
+async fn terminate_region(
+    config: &AppConfig,
+    sdk_config: &SdkConfig,
+    role_arn: Option<&str>,
+    region: &str,
+    filter: &ResourceFilter,
+    deadline: Option<Instant>,
+) -> Vec<ServiceSummary> {
+    let mut services: Vec<ServiceSummary> = Vec::new();
+
+    if config.ec2_schedule && config.service_regions("ec2").iter().any(|r| r == region) {
+        info!(region = %region, "Terminating EC2 instances");
+        let scheduler = ec2::Ec2Scheduler::new(
+            sdk_config,
+            config.ec2_stop_mode,
+            config.dry_run,
+            config.max_concurrent_operations,
+        );
+        let started = Instant::now();
+        let result = with_timeout(config, "ec2", deadline, scheduler.terminate(filter))
+            .instrument(info_span!(
+                "service",
+                service = "ec2",
+                region = %region,
+                action = %config.schedule_action
+            ))
+            .await;
+        if let Err(e) = &result {
+            error!(region = %region, error = %e, "Failed to terminate EC2 instances");
+        }
+        services.push(
+            ServiceSummary::new("ec2", region, role_arn, result).with_duration(started.elapsed()),
+        );
+    }
+
+    if config.autoscaling_schedule
+        && config
+            .service_regions("autoscaling")
+            .iter()
+            .any(|r| r == region)
+    {
+        info!(region = %region, "Terminating Auto Scaling groups");
+        let scheduler = autoscaling::AutoScalingScheduler::new(
+            sdk_config,
+            state::from_config(config, sdk_config),
+            config.autoscaling_mode,
+            config.autoscaling_scheduled_actions,
+            config.dry_run,
+            config.max_concurrent_operations,
+            Waiter::from_config(config),
+        );
+        let started = Instant::now();
+        let result = with_timeout(
+            config,
+            "autoscaling",
+            deadline,
+            scheduler.terminate(filter, config.autoscaling_delete_on_terminate),
+        )
+        .instrument(info_span!(
+            "service",
+            service = "autoscaling",
+            region = %region,
+            action = %config.schedule_action
+        ))
+        .await;
+        if let Err(e) = &result {
+            error!(region = %region, error = %e, "Failed to terminate Auto Scaling groups");
+        }
+        services.push(
+            ServiceSummary::new("autoscaling", region, role_arn, result)
+                .with_duration(started.elapsed()),
+        );
+    }
+
+    services
+}
+
+/// Perform the stop/start action on every enabled service in a single region,
+/// assuming `role_arn` first when set.
+///
+/// The SDK configuration (and assumed role credentials) is loaded once and
+/// shared by the schedulers of every service.
+/// Errors on individual services are logged without interrupting the processing of others.
+/// Once a shutdown is requested, no new service is started.
+/// Services are processed in the groups of `SERVICE_ORDER`: the services of a group
+/// followed by a barrier are waited for as with `WAIT_FOR_COMPLETION`.
+/// With `PRIORITY_TAG`, each service processes its resources in priority waves, each
+/// wave followed by another one being waited for before the next is processed.
+/// Each service is bounded by its `SERVICE_TIMEOUT_SECONDS` and by the `deadline` of the run,
+/// waiting for its resources to reach the target state included (`WAIT_FOR_COMPLETION`).
+/// The `PRE_STOP_HOOK` runs before the services are stopped, and the `POST_START_HOOK`
+/// after they are started; with `ABORT_ON_HOOK_FAILURE`, a failed pre-stop hook skips the stop.
+/// Returns the summary of every processed service, hooks included.
+async fn process_region(
+    config: &AppConfig,
+    role_arn: Option<&str>,
+    region: &str,
+    deadline: Option<Instant>,
+) -> Vec<ServiceSummary> {
+    let filter = ResourceFilter::from_config(config);
+    let sdk_config = credentials::load_config(config, region, role_arn).await;
+    if matches!(config.schedule_action, ScheduleAction::Terminate) {
+        return terminate_region(config, &sdk_config, role_arn, region, &filter, deadline).await;
+    }
+    let mut services: Vec<ServiceSummary> = Vec::new();
+
+    if let (ScheduleAction::Stop, Some(hook)) = (&config.schedule_action, &config.pre_stop_hook) {
+        let summary = run_hook(
+            hook,
+            HookEvent::PreStop,
+            config,
+            &sdk_config,
+            role_arn,
+            region,
+            deadline,
+        )
+        .await;
+        let failed = summary.error.is_some();
+        services.push(summary);
+        if failed && config.abort_on_hook_failure {
+            error!(region = %region, "Pre-stop hook failed, skipping the stop (ABORT_ON_HOOK_FAILURE)");
+            return services;
+        }
+    }
+
+    let registry = Registry::from_config(config, &sdk_config);
+    let groups = registry.groups(&config.service_order, &config.schedule_action);
+    let waves = wave_filters(config, &sdk_config, &filter, region).await;
+    let schedulers: Vec<(usize, &dyn ResourceScheduler)> = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(index, group)| group.iter().map(move |scheduler| (index, *scheduler)))
+        .collect();
+    for (index, scheduler) in schedulers {
+        if shutdown::requested() {
+            info!(region = %region, "Shutdown requested, skipping remaining services");
+            break;
+        }
+        let service = scheduler.name();
+        let barrier = index + 1 < groups.len();
+        info!(region = %region, service, action = %config.schedule_action, "Processing service");
+        let started = Instant::now();
+        let operation = async {
+            let mut counts = ResourceCounts::default();
+            for (wave, filter) in waves.iter().enumerate() {
+                let mut wave_counts = match config.schedule_action {
+                    ScheduleAction::Stop => scheduler.stop(filter).await?,
+                    ScheduleAction::Start => scheduler.start(filter).await?,
+                    _ => unreachable!("only stop and start are handled by process_region"),
+                };
+                let next_wave = wave + 1 < waves.len();
+                if (config.wait_for_completion || barrier || next_wave) && !config.dry_run {
+                    waiters::wait_for_target_state(
+                        scheduler,
+                        filter,
+                        &config.schedule_action,
+                        &mut wave_counts,
+                        config.wait_timeout(service),
+                        Duration::from_secs(config.wait_poll_interval_seconds),
+                    )
+                    .await;
+                }
+                counts.merge(wave_counts);
+            }
+            Ok::<_, anyhow::Error>(counts)
+        };
+        let result = with_timeout(config, service, deadline, operation)
+            .instrument(
+                info_span!("service", service, region = %region, action = %config.schedule_action),
+            )
+            .await;
+        if let Err(e) = &result {
+            error!(region = %region, service, error = %e, "Failed to process service");
+        }
+        services.push(
+            ServiceSummary::new(service, region, role_arn, result).with_duration(started.elapsed()),
+        );
+    }
+
+    if let (ScheduleAction::Start, Some(hook)) = (&config.schedule_action, &config.post_start_hook)
+    {
+        services.push(
+            run_hook(
+                hook,
+                HookEvent::PostStart,
+                config,
+                &sdk_config,
+                role_arn,
+                region,
+                deadline,
+            )
+            .await,
+        );
+    }
+
+    services
+}
+
+/// Run a hook of a region, bounded by the `deadline` of the run (and by a
+/// `SERVICE_TIMEOUT_SECONDS` of `pre_stop_hook` or `post_start_hook`), and return its summary.
+async fn run_hook(
+    hook: &Hook,
+    event: HookEvent,
+    config: &AppConfig,
+    sdk_config: &SdkConfig,
+    role_arn: Option<&str>,
+    region: &str,
+    deadline: Option<Instant>,
+) -> ServiceSummary {
+    let service = event.service();
+    let started = Instant::now();
+    let result = with_timeout(
+        config,
+        service,
+        deadline,
+        hooks::run(hook, event, config, sdk_config, region),
+    )
+    .instrument(info_span!("hook", event = %event, region = %region))
+    .await;
+    if let Err(e) = &result {
+        error!(region = %region, hook = %hook, event = %event, error = %e, "Hook failed");
+    }
+    ServiceSummary::new(service, region, role_arn, result).with_duration(started.elapsed())
+}
+
+/// Filters of the priority waves of a region, in processing order (`PRIORITY_TAG`).
+///
+/// A single filter selects every resource when priorities are disabled, or when
+/// they cannot be read: the resources are then processed without ordering.
+async fn wave_filters(
+    config: &AppConfig,
+    sdk_config: &SdkConfig,
+    filter: &ResourceFilter,
+    region: &str,
+) -> Vec<ResourceFilter> {
+    let Some(priority_tag) = &config.priority_tag else {
+        return vec![filter.clone()];
+    };
+    let client = aws_sdk_resourcegroupstagging::Client::new(sdk_config);
+    match priority::waves(&client, filter, priority_tag).await {
+        Ok(waves) => {
+            let order: Vec<String> = waves.iter().map(|w| w.to_string()).collect();
+            info!(region = %region, waves = %order.join(","), "Processing resources in priority waves");
+            waves
+                .into_iter()
+                .map(|wave| filter.with_wave(wave))
+                .collect()
+        }
+        Err(e) => {
+            error!(region = %region, error = %e, "Failed to read resource priorities, processing resources without ordering");
+            vec![filter.clone()]
+        }
+    }
+}
+
+/// Run the processing of a service, bounded by its `SERVICE_TIMEOUT_SECONDS` and by
+/// the `deadline` of the run. A service that times out is aborted and reported as failed.
+async fn with_timeout(
+    config: &AppConfig,
+    service: &str,
+    deadline: Option<Instant>,
+    operation: impl Future<Output = Result<ResourceCounts>>,
+) -> Result<ResourceCounts> {
+    let service_timeout = config.service_timeout(service);
+    let service_deadline = service_timeout.map(|t| Instant::now() + t);
+    let limit = match (deadline, service_deadline) {
+        (Some(deadline), Some(service_deadline)) => Some(deadline.min(service_deadline)),
+        (deadline, service_deadline) => deadline.or(service_deadline),
+    };
+    let Some(limit) = limit else {
+        return operation.await;
+    };
+
+    match tokio::time::timeout_at(limit, operation).await {
+        Ok(result) => result,
+        Err(_) if service_deadline == Some(limit) => Err(anyhow!(
+            "Timed out after {}s (SERVICE_TIMEOUT_SECONDS)",
+            service_timeout.unwrap_or_default().as_secs()
+        )),
+        Err(_) => Err(anyhow!("Execution timed out (EXECUTION_TIMEOUT_SECONDS)")),
+    }
+}
+
+/// List the resources matched on every enabled service in a single region,
+/// assuming `role_arn` first when set, with their current state.
+///
+/// Services whose resources cannot be listed are recorded as errors in the inventory.
+async fn list_region(config: &AppConfig, role_arn: Option<&str>, region: &str) -> Inventory {
+    let filter = ResourceFilter::from_config(config);
+    let sdk_config = credentials::load_config(config, region, role_arn).await;
+    let mut inventory = Inventory::default();
+
+    for scheduler in Registry::from_config(config, &sdk_config).iter() {
+        info!(region = %region, service = scheduler.name(), "Listing resources");
+        let result = scheduler
+            .list(&filter)
+            .instrument(info_span!("service", service = scheduler.name(), region = %region, action = "list"))
+            .await;
+        inventory.add(scheduler.name(), region, role_arn, result);
+    }
+
+    inventory
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::app_config;
+
+    #[tokio::test]
+    async fn process_region_skips_the_stop_when_the_pre_stop_hook_fails() {
+        // Nothing listens on port 1: the webhook fails without any AWS call.
+        let config = app_config(&[
+            ("PRE_STOP_HOOK", "https://127.0.0.1:1/hook"),
+            ("ABORT_ON_HOOK_FAILURE", "true"),
+        ]);
+
+        let services = process_region(&config, None, "eu-west-1", None).await;
+
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].service, "pre_stop_hook");
+        assert!(services[0].error.is_some());
+    }
+}
@@ -0,0 +1,488 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_lightsail::types::Tag;
+use aws_sdk_lightsail::Client as LightsailClient;
+use tracing::{error, info};
+
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::ResourceFilter;
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Stop/start handler for Lightsail instances and relational databases in a given AWS region.
+///
+/// Lightsail resources are not fully covered by the Resource Groups Tagging API,
+/// so they are discovered by listing them with the Lightsail API and matching
+/// their tags directly.
+pub struct LightsailScheduler {
+    lightsail: LightsailClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+/// A discovered Lightsail resource with its current state.
+struct LightsailResource {
+    arn: String,
+    name: String,
+    state: String,
+}
+
+impl LightsailScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            lightsail: LightsailClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// List the Lightsail instances whose tags match the filter.
+    async fn list_instances(&self, filter: &ResourceFilter) -> Result<Vec<LightsailResource>> {
+        let mut instances = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut req = self.lightsail.get_instances();
+            if let Some(ref token) = page_token {
+                req = req.page_token(token);
+            }
+
+            let resp = req.send().await?;
+            for instance in resp.instances() {
+                let Some(name) = instance.name() else {
+                    continue;
+                };
+                let arn = instance.arn().unwrap_or_default();
+                if matches_filter(filter, arn, name, instance.tags()) {
+                    instances.push(LightsailResource {
+                        arn: arn.to_string(),
+                        name: name.to_string(),
+                        state: instance
+                            .state()
+                            .and_then(|s| s.name())
+                            .unwrap_or_default()
+                            .to_string(),
+                    });
+                }
+            }
+
+            match resp.next_page_token() {
+                Some(token) if !token.is_empty() => page_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        Ok(instances)
+    }
+
+    /// List the Lightsail relational databases whose tags match the filter.
+    async fn list_databases(&self, filter: &ResourceFilter) -> Result<Vec<LightsailResource>> {
+        let mut databases = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut req = self.lightsail.get_relational_databases();
+            if let Some(ref token) = page_token {
+                req = req.page_token(token);
+            }
+
+            let resp = req.send().await?;
+            for database in resp.relational_databases() {
+                let Some(name) = database.name() else {
+                    continue;
+                };
+                let arn = database.arn().unwrap_or_default();
+                if matches_filter(filter, arn, name, database.tags()) {
+                    databases.push(LightsailResource {
+                        arn: arn.to_string(),
+                        name: name.to_string(),
+                        state: database.state().unwrap_or_default().to_string(),
+                    });
+                }
+            }
+
+            match resp.next_page_token() {
+                Some(token) if !token.is_empty() => page_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        Ok(databases)
+    }
+
+    async fn stop_instance(&self, instance: &LightsailResource) -> Result<Outcome> {
+        if instance.state != "running" {
+            info!(instance = %instance.name, state = %instance.state, "Lightsail instance not running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(instance = %instance.name, "Dry run: would stop Lightsail instance");
+            return Ok(Outcome::Applied);
+        }
+        info!(instance = %instance.name, "Stopping Lightsail instance");
+        self.lightsail
+            .stop_instance()
+            .instance_name(&instance.name)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn start_instance(&self, instance: &LightsailResource) -> Result<Outcome> {
+        if instance.state != "stopped" {
+            info!(instance = %instance.name, state = %instance.state, "Lightsail instance not stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(instance = %instance.name, "Dry run: would start Lightsail instance");
+            return Ok(Outcome::Applied);
+        }
+        info!(instance = %instance.name, "Starting Lightsail instance");
+        self.lightsail
+            .start_instance()
+            .instance_name(&instance.name)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn stop_database(&self, database: &LightsailResource) -> Result<Outcome> {
+        if database.state != "available" {
+            info!(database = %database.name, state = %database.state, "Lightsail database not available, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(database = %database.name, "Dry run: would stop Lightsail database");
+            return Ok(Outcome::Applied);
+        }
+        info!(database = %database.name, "Stopping Lightsail database");
+        self.lightsail
+            .stop_relational_database()
+            .relational_database_name(&database.name)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn start_database(&self, database: &LightsailResource) -> Result<Outcome> {
+        if database.state != "stopped" {
+            info!(database = %database.name, state = %database.state, "Lightsail database not stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(database = %database.name, "Dry run: would start Lightsail database");
+            return Ok(Outcome::Applied);
+        }
+        info!(database = %database.name, "Starting Lightsail database");
+        self.lightsail
+            .start_relational_database()
+            .relational_database_name(&database.name)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for LightsailScheduler {
+    fn name(&self) -> &'static str {
+        "lightsail"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let instances = self.list_instances(filter).await?;
+        let databases = self.list_databases(filter).await?;
+
+        info!(
+            instances = instances.len(),
+            databases = databases.len(),
+            "Found Lightsail resources to stop"
+        );
+
+        filter.check_discovered(instances.len() + databases.len())?;
+        let mut counts = ResourceCounts::new(instances.len() + databases.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &instances,
+            |instance| async move {
+                let result = self.stop_instance(instance).await;
+                (instance, result)
+            },
+        )
+        .await;
+        for (instance, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&instance.name, outcome),
+                Err(e) => {
+                    counts.fail(&instance.name, &e);
+                    error!(instance = %instance.name, error = %e, "Failed to stop Lightsail instance");
+                }
+            }
+        }
+
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &databases,
+            |database| async move {
+                let result = self.stop_database(database).await;
+                (database, result)
+            },
+        )
+        .await;
+        for (database, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&database.name, outcome),
+                Err(e) => {
+                    counts.fail(&database.name, &e);
+                    error!(database = %database.name, error = %e, "Failed to stop Lightsail database");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let instances = self.list_instances(filter).await?;
+        let databases = self.list_databases(filter).await?;
+
+        info!(
+            instances = instances.len(),
+            databases = databases.len(),
+            "Found Lightsail resources to start"
+        );
+
+        filter.check_discovered(instances.len() + databases.len())?;
+        let mut counts = ResourceCounts::new(instances.len() + databases.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &databases,
+            |database| async move {
+                let result = self.start_database(database).await;
+                (database, result)
+            },
+        )
+        .await;
+        for (database, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&database.name, outcome),
+                Err(e) => {
+                    counts.fail(&database.name, &e);
+                    error!(database = %database.name, error = %e, "Failed to start Lightsail database");
+                }
+            }
+        }
+
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &instances,
+            |instance| async move {
+                let result = self.start_instance(instance).await;
+                (instance, result)
+            },
+        )
+        .await;
+        for (instance, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&instance.name, outcome),
+                Err(e) => {
+                    counts.fail(&instance.name, &e);
+                    error!(instance = %instance.name, error = %e, "Failed to start Lightsail instance");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the Lightsail instances and relational databases matching the given filter with their current state.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let instances = self.list_instances(filter).await?;
+        let databases = self.list_databases(filter).await?;
+
+        Ok(instances
+            .into_iter()
+            .map(|i| InventoryItem::new(i.arn, "lightsail:instance", i.state))
+            .chain(
+                databases
+                    .into_iter()
+                    .map(|d| InventoryItem::new(d.arn, "lightsail:relationaldatabase", d.state)),
+            )
+            .collect())
+    }
+}
+
+/// Check whether a Lightsail resource carries the scheduler tag and passes the filter.
+fn matches_filter(filter: &ResourceFilter, arn: &str, name: &str, tags: &[Tag]) -> bool {
+    filter.matches(
+        arn,
+        name,
+        tags.iter()
+            .map(|t| (t.key().unwrap_or_default(), t.value().unwrap_or_default())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_lightsail::operation::get_instances::GetInstancesOutput;
+    use aws_sdk_lightsail::operation::get_relational_databases::GetRelationalDatabasesOutput;
+    use aws_sdk_lightsail::operation::start_instance::StartInstanceOutput;
+    use aws_sdk_lightsail::operation::start_relational_database::StartRelationalDatabaseOutput;
+    use aws_sdk_lightsail::operation::stop_instance::StopInstanceOutput;
+    use aws_sdk_lightsail::operation::stop_relational_database::StopRelationalDatabaseOutput;
+    use aws_sdk_lightsail::types::{Instance, InstanceState, RelationalDatabase};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, TAG_KEY, TAG_VALUE};
+
+    const ARN_PREFIX: &str = "arn:aws:lightsail:eu-west-1:123456789012:";
+
+    /// Lightsail tags of a resource, `env=staging` when `scheduled`.
+    fn tags(scheduled: bool) -> Vec<Tag> {
+        let value = if scheduled { TAG_VALUE } else { "production" };
+        vec![Tag::builder().key(TAG_KEY).value(value).build()]
+    }
+
+    fn instance(name: &str, state: &str, scheduled: bool) -> Instance {
+        Instance::builder()
+            .name(name)
+            .arn(format!("{ARN_PREFIX}Instance/{name}"))
+            .state(InstanceState::builder().name(state).build())
+            .set_tags(Some(tags(scheduled)))
+            .build()
+    }
+
+    /// Rule answering a page of instances, requested with `page_token` and pointing
+    /// to `next_page_token`.
+    fn instances_rule(
+        page_token: Option<&'static str>,
+        instances: fn() -> Vec<Instance>,
+        next_page_token: Option<&'static str>,
+    ) -> Rule {
+        mock!(LightsailClient::get_instances)
+            .match_requests(move |req| req.page_token() == page_token)
+            .then_output(move || {
+                GetInstancesOutput::builder()
+                    .set_instances(Some(instances()))
+                    .set_next_page_token(next_page_token.map(str::to_string))
+                    .build()
+            })
+    }
+
+    /// Rule answering a single database, in the given state.
+    fn databases_rule(name: &'static str, state: &'static str) -> Rule {
+        mock!(LightsailClient::get_relational_databases).then_output(move || {
+            GetRelationalDatabasesOutput::builder()
+                .relational_databases(
+                    RelationalDatabase::builder()
+                        .name(name)
+                        .arn(format!("{ARN_PREFIX}RelationalDatabase/{name}"))
+                        .state(state)
+                        .set_tags(Some(tags(true)))
+                        .build(),
+                )
+                .build()
+        })
+    }
+
+    fn scheduler(lightsail: LightsailClient) -> LightsailScheduler {
+        LightsailScheduler {
+            lightsail,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_stops_the_running_tagged_resources_of_every_page() {
+        let first_page = instances_rule(
+            None,
+            || {
+                vec![
+                    instance("web", "running", true),
+                    instance("build", "running", false),
+                ]
+            },
+            Some("page-2"),
+        );
+        let second_page = instances_rule(
+            Some("page-2"),
+            || vec![instance("worker", "stopped", true)],
+            None,
+        );
+        let databases = databases_rule("app-db", "available");
+        let stop_instance = mock!(LightsailClient::stop_instance)
+            .match_requests(|req| req.instance_name() == Some("web"))
+            .then_output(|| StopInstanceOutput::builder().build());
+        let stop_database = mock!(LightsailClient::stop_relational_database)
+            .match_requests(|req| req.relational_database_name() == Some("app-db"))
+            .then_output(|| StopRelationalDatabaseOutput::builder().build());
+        let scheduler = scheduler(mock_client!(
+            aws_sdk_lightsail,
+            RuleMode::MatchAny,
+            [
+                &first_page,
+                &second_page,
+                &databases,
+                &stop_instance,
+                &stop_database
+            ]
+        ));
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (3, 2, 1, 0)
+        );
+        assert_eq!(stop_instance.num_calls(), 1);
+        assert_eq!(stop_database.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_starts_databases_before_instances() {
+        let instances = instances_rule(None, || vec![instance("web", "stopped", true)], None);
+        let databases = databases_rule("app-db", "stopped");
+        let start_instance = mock!(LightsailClient::start_instance)
+            .match_requests(|req| req.instance_name() == Some("web"))
+            .then_output(|| StartInstanceOutput::builder().build());
+        let start_database = mock!(LightsailClient::start_relational_database)
+            .match_requests(|req| req.relational_database_name() == Some("app-db"))
+            .then_output(|| StartRelationalDatabaseOutput::builder().build());
+        let scheduler = scheduler(mock_client!(
+            aws_sdk_lightsail,
+            RuleMode::MatchAny,
+            [&instances, &databases, &start_instance, &start_database]
+        ));
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 2, 0, 0)
+        );
+        let order: Vec<&str> = counts
+            .resources
+            .iter()
+            .map(|r| r.resource.as_str())
+            .collect();
+        assert_eq!(order, ["app-db", "web"]);
+    }
+}
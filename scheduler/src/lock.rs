@@ -0,0 +1,214 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::Client as DynamoDbClient;
+use chrono::Utc;
+use tracing::{info, warn};
+
+use crate::config::AppConfig;
+use crate::credentials;
+
+/// Delay between two attempts to acquire a lock held by another run.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Lock preventing two runs of the same action on the same resources from overlapping,
+/// e.g. when a CronJob is retried while the previous run is still going.
+///
+/// The lock is an item of the `LOCK_TABLE` DynamoDB table, whose partition key is
+/// the string attribute `lock_id`, keyed by action and tag filter. The item
+/// expires after `LOCK_TTL_SECONDS`, so that a run killed before releasing it
+/// does not block the following ones; enable DynamoDB TTL on the `expires_at`
+/// attribute to have expired items removed.
+///
+/// The table is looked up in the first region of `AWS_REGIONS`, with the ambient credentials.
+pub struct RunLock {
+    dynamodb: DynamoDbClient,
+    table: String,
+    lock_id: String,
+    owner: String,
+}
+
+impl RunLock {
+    /// Acquire the lock of the configured action and tag filter.
+    ///
+    /// When another run holds the lock, retries for up to `LOCK_WAIT_SECONDS`,
+    /// then returns `None` so that the run is skipped.
+    pub async fn acquire(config: &AppConfig, table: &str) -> Result<Option<Self>> {
+        let sdk_config = credentials::load_config(config, &config.aws_regions[0], None).await;
+        let lock = Self {
+            dynamodb: DynamoDbClient::new(&sdk_config),
+            table: table.to_string(),
+            lock_id: format!(
+                "{}:{}={}",
+                config.schedule_action,
+                config.tag_key,
+                config.tag_values_for(&config.schedule_action).join(",")
+            ),
+            owner: format!(
+                "{}:{}:{}",
+                std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string()),
+                std::process::id(),
+                Utc::now().timestamp_millis()
+            ),
+        };
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(config.lock_wait_seconds);
+        loop {
+            if lock.try_acquire(config.lock_ttl_seconds).await? {
+                info!(table = %lock.table, lock = %lock.lock_id, "Run lock acquired");
+                return Ok(Some(lock));
+            }
+            if tokio::time::Instant::now() >= deadline {
+                warn!(table = %lock.table, lock = %lock.lock_id, "Run lock held by another run");
+                return Ok(None);
+            }
+            info!(table = %lock.table, lock = %lock.lock_id, "Run lock held by another run, waiting");
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Write the lock item, unless another run holds an unexpired lock.
+    async fn try_acquire(&self, ttl_seconds: u64) -> Result<bool> {
+        let now = Utc::now().timestamp();
+        let expires_at = now + i64::try_from(ttl_seconds).unwrap_or(i64::MAX - now);
+        let result = self
+            .dynamodb
+            .put_item()
+            .table_name(&self.table)
+            .item("lock_id", AttributeValue::S(self.lock_id.clone()))
+            .item("owner", AttributeValue::S(self.owner.clone()))
+            .item("expires_at", AttributeValue::N(expires_at.to_string()))
+            .condition_expression("attribute_not_exists(lock_id) OR expires_at < :now")
+            .expression_attribute_values(":now", AttributeValue::N(now.to_string()))
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_conditional_check_failed_exception()) =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e)
+                .with_context(|| format!("Failed to acquire run lock in table '{}'", self.table)),
+        }
+    }
+
+    /// Release the lock, if this run still holds it.
+    ///
+    /// Errors are logged only: the lock expires anyway.
+    pub async fn release(self) {
+        let result = self
+            .dynamodb
+            .delete_item()
+            .table_name(&self.table)
+            .key("lock_id", AttributeValue::S(self.lock_id.clone()))
+            .condition_expression("#owner = :owner")
+            .expression_attribute_names("#owner", "owner")
+            .expression_attribute_values(":owner", AttributeValue::S(self.owner.clone()))
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => info!(table = %self.table, lock = %self.lock_id, "Run lock released"),
+            Err(e) => {
+                warn!(table = %self.table, lock = %self.lock_id, error = %e, "Failed to release run lock")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::operation::delete_item::DeleteItemOutput;
+    use aws_sdk_dynamodb::operation::put_item::{PutItemError, PutItemOutput};
+    use aws_sdk_dynamodb::types::error::ConditionalCheckFailedException;
+    use aws_smithy_mocks::{mock, mock_client};
+
+    use super::*;
+
+    fn lock(dynamodb: DynamoDbClient) -> RunLock {
+        RunLock {
+            dynamodb,
+            table: "locks".to_string(),
+            lock_id: "stop:env=staging".to_string(),
+            owner: "host:1:0".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn try_acquire_writes_the_lock_until_it_expires() {
+        let now = Utc::now().timestamp();
+        let rule = mock!(DynamoDbClient::put_item)
+            .match_requests(move |req| {
+                let item = req.item().unwrap();
+                let expires_at = match item.get("expires_at") {
+                    Some(AttributeValue::N(n)) => n.parse::<i64>().unwrap(),
+                    _ => return false,
+                };
+                item.get("lock_id") == Some(&AttributeValue::S("stop:env=staging".to_string()))
+                    && item.get("owner") == Some(&AttributeValue::S("host:1:0".to_string()))
+                    && (now + 600..=now + 601).contains(&expires_at)
+                    && req.condition_expression()
+                        == Some("attribute_not_exists(lock_id) OR expires_at < :now")
+            })
+            .then_output(|| PutItemOutput::builder().build());
+        let lock = lock(mock_client!(aws_sdk_dynamodb, [&rule]));
+
+        assert!(lock.try_acquire(600).await.unwrap());
+        assert_eq!(rule.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn try_acquire_reports_a_lock_held_by_another_run() {
+        let rule = mock!(DynamoDbClient::put_item).then_error(|| {
+            PutItemError::ConditionalCheckFailedException(
+                ConditionalCheckFailedException::builder().build(),
+            )
+        });
+        let lock = lock(mock_client!(aws_sdk_dynamodb, [&rule]));
+
+        assert!(!lock.try_acquire(600).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn try_acquire_never_overflows_the_expiry() {
+        let rule = mock!(DynamoDbClient::put_item)
+            .match_requests(|req| {
+                req.item().and_then(|item| item.get("expires_at"))
+                    == Some(&AttributeValue::N(i64::MAX.to_string()))
+            })
+            .then_output(|| PutItemOutput::builder().build());
+        let lock = lock(mock_client!(aws_sdk_dynamodb, [&rule]));
+
+        assert!(lock.try_acquire(u64::MAX).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn release_deletes_the_lock_only_when_still_owned() {
+        let rule = mock!(DynamoDbClient::delete_item)
+            .match_requests(|req| {
+                req.key().and_then(|key| key.get("lock_id"))
+                    == Some(&AttributeValue::S("stop:env=staging".to_string()))
+                    && req.condition_expression() == Some("#owner = :owner")
+                    && req
+                        .expression_attribute_names()
+                        .and_then(|names| names.get("#owner"))
+                        .map(String::as_str)
+                        == Some("owner")
+                    && req
+                        .expression_attribute_values()
+                        .and_then(|values| values.get(":owner"))
+                        == Some(&AttributeValue::S("host:1:0".to_string()))
+            })
+            .then_output(|| DeleteItemOutput::builder().build());
+        let lock = lock(mock_client!(aws_sdk_dynamodb, [&rule]));
+
+        lock.release().await;
+
+        assert_eq!(rule.num_calls(), 1);
+    }
+}
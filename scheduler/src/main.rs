@@ -1,182 +1,64 @@
-mod apprunner;
-mod autoscaling;
-mod cloudwatch;
-mod config;
-mod documentdb;
-mod ec2;
-mod ecs;
-mod filter_resources_by_tags;
-mod rds;
-mod redshift;
-mod transfer;
+use std::collections::HashMap;
+use std::path::PathBuf;
 
-use anyhow::Result;
-use chrono::Utc;
-use tracing::{error, info};
+use anyhow::{bail, Context, Result};
 
-use config::{AppConfig, ScheduleAction};
+use scheduler::config::AppConfig;
+use scheduler::plan::Plan;
+
+/// Default path of the plan written by `scheduler plan`.
+const DEFAULT_PLAN_PATH: &str = "plan.json";
 
 /// Application entry point.
 ///
-/// Loads configuration from environment variables, then performs
-/// the stop/start action on AWS resources matching the configured tag.
+/// Loads configuration from environment variables (and `CONFIG_FILE`, `CONFIG_SSM_PREFIX`), then runs
+/// the scheduler, either once or on a cron schedule in daemon mode.
+///
+/// `scheduler plan [--out plan.json]` writes the plan of the configured action
+/// instead, and `scheduler apply --plan plan.json` executes a plan.
 #[tokio::main]
 async fn main() -> Result<()> {
-    let log_level = std::env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string());
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::new(&log_level))
-        .init();
-
-    let config = AppConfig::from_env()?;
-
-    info!(
-        action = %config.schedule_action,
-        regions = ?config.aws_regions,
-        tag = %format!("{}={}", config.tag_key, config.tag_value),
-        ec2 = config.ec2_schedule,
-        apprunner = config.apprunner_schedule,
-        autoscaling = config.autoscaling_schedule,
-        cloudwatch = config.cloudwatch_alarm_schedule,
-        documentdb = config.documentdb_schedule,
-        ecs = config.ecs_schedule,
-        rds = config.rds_schedule,
-        redshift = config.redshift_schedule,
-        transfer = config.transfer_schedule,
-        "Scheduler initialized"
-    );
-
-    execute(&config).await
-}
-
-/// Check whether today's date (`MM-DD` format) is in the exclusion list.
-fn is_date_excluded(excluded_dates: &[String]) -> bool {
-    let today = Utc::now().format("%m-%d").to_string();
-    excluded_dates.iter().any(|d| d == &today)
-}
-
-/// Execute the stop/start action across all configured regions.
-///
-/// Skips execution if today is an excluded date.
-/// Errors on individual regions are logged without interrupting the processing of others.
-async fn execute(config: &AppConfig) -> Result<()> {
-    if is_date_excluded(&config.excluded_dates) {
-        info!(
-            date = %Utc::now().format("%m-%d"),
-            "Today is an excluded date, skipping execution"
-        );
-        return Ok(());
-    }
+    let _telemetry = scheduler::telemetry::init(false)?;
 
-    for region in &config.aws_regions {
-        if config.ec2_schedule {
-            info!(region = %region, action = %config.schedule_action, "Processing EC2 instances");
-            let scheduler = ec2::Ec2Scheduler::new(region).await;
-            let result = match config.schedule_action {
-                ScheduleAction::Stop => scheduler.stop(&config.tag_key, &config.tag_value).await,
-                ScheduleAction::Start => scheduler.start(&config.tag_key, &config.tag_value).await,
-            };
-            if let Err(e) = result {
-                error!(region = %region, error = %e, "Failed to process EC2 instances");
-            }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => {
+            let config = AppConfig::load().await?;
+            scheduler::run(&config).await
         }
-
-        if config.autoscaling_schedule {
-            info!(region = %region, action = %config.schedule_action, "Processing Auto Scaling groups");
-            let scheduler = autoscaling::AutoScalingScheduler::new(region).await;
-            let result = match config.schedule_action {
-                ScheduleAction::Stop => scheduler.stop(&config.tag_key, &config.tag_value).await,
-                ScheduleAction::Start => scheduler.start(&config.tag_key, &config.tag_value).await,
-            };
-            if let Err(e) = result {
-                error!(region = %region, error = %e, "Failed to process Auto Scaling groups");
-            }
+        Some("plan") => {
+            let path =
+                option(&args[1..], "--out")?.unwrap_or_else(|| PathBuf::from(DEFAULT_PLAN_PATH));
+            let config = AppConfig::load().await?;
+            scheduler::plan(&config, &path).await
         }
-
-        if config.apprunner_schedule {
-            info!(region = %region, action = %config.schedule_action, "Processing App Runner services");
-            let scheduler = apprunner::AppRunnerScheduler::new(region).await;
-            let result = match config.schedule_action {
-                ScheduleAction::Stop => scheduler.stop(&config.tag_key, &config.tag_value).await,
-                ScheduleAction::Start => scheduler.start(&config.tag_key, &config.tag_value).await,
-            };
-            if let Err(e) = result {
-                error!(region = %region, error = %e, "Failed to process App Runner services");
-            }
-        }
-
-        if config.cloudwatch_alarm_schedule {
-            info!(region = %region, action = %config.schedule_action, "Processing CloudWatch alarms");
-            let scheduler = cloudwatch::CloudWatchScheduler::new(region).await;
-            let result = match config.schedule_action {
-                ScheduleAction::Stop => scheduler.stop(&config.tag_key, &config.tag_value).await,
-                ScheduleAction::Start => scheduler.start(&config.tag_key, &config.tag_value).await,
-            };
-            if let Err(e) = result {
-                error!(region = %region, error = %e, "Failed to process CloudWatch alarms");
-            }
-        }
-
-        if config.documentdb_schedule {
-            info!(region = %region, action = %config.schedule_action, "Processing DocumentDB clusters");
-            let scheduler = documentdb::DocumentDbScheduler::new(region).await;
-            let result = match config.schedule_action {
-                ScheduleAction::Stop => scheduler.stop(&config.tag_key, &config.tag_value).await,
-                ScheduleAction::Start => scheduler.start(&config.tag_key, &config.tag_value).await,
-            };
-            if let Err(e) = result {
-                error!(region = %region, error = %e, "Failed to process DocumentDB clusters");
-            }
-        }
-
-        if config.ecs_schedule {
-            info!(region = %region, action = %config.schedule_action, "Processing ECS services");
-            let scheduler = ecs::EcsScheduler::new(region).await;
-            let result = match config.schedule_action {
-                ScheduleAction::Stop => scheduler.stop(&config.tag_key, &config.tag_value).await,
-                ScheduleAction::Start => scheduler.start(&config.tag_key, &config.tag_value).await,
-            };
-            if let Err(e) = result {
-                error!(region = %region, error = %e, "Failed to process ECS services");
-            }
-        }
-
-        if config.rds_schedule {
-            info!(region = %region, action = %config.schedule_action, "Processing RDS resources");
-            let scheduler = rds::RdsScheduler::new(region).await;
-            let result = match config.schedule_action {
-                ScheduleAction::Stop => scheduler.stop(&config.tag_key, &config.tag_value).await,
-                ScheduleAction::Start => scheduler.start(&config.tag_key, &config.tag_value).await,
-            };
-            if let Err(e) = result {
-                error!(region = %region, error = %e, "Failed to process RDS resources");
-            }
-        }
-
-        if config.redshift_schedule {
-            info!(region = %region, action = %config.schedule_action, "Processing Redshift clusters");
-            let scheduler = redshift::RedshiftScheduler::new(region).await;
-            let result = match config.schedule_action {
-                ScheduleAction::Stop => scheduler.stop(&config.tag_key, &config.tag_value).await,
-                ScheduleAction::Start => scheduler.start(&config.tag_key, &config.tag_value).await,
-            };
-            if let Err(e) = result {
-                error!(region = %region, error = %e, "Failed to process Redshift clusters");
-            }
-        }
-
-        if config.transfer_schedule {
-            info!(region = %region, action = %config.schedule_action, "Processing Transfer servers");
-            let scheduler = transfer::TransferScheduler::new(region).await;
-            let result = match config.schedule_action {
-                ScheduleAction::Stop => scheduler.stop(&config.tag_key, &config.tag_value).await,
-                ScheduleAction::Start => scheduler.start(&config.tag_key, &config.tag_value).await,
-            };
-            if let Err(e) = result {
-                error!(region = %region, error = %e, "Failed to process Transfer servers");
-            }
+        Some("apply") => {
+            let path = option(&args[1..], "--plan")?.context("apply requires --plan <file>")?;
+            let plan: Plan = serde_json::from_str(
+                &std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read plan '{}'", path.display()))?,
+            )
+            .with_context(|| format!("Invalid plan '{}'", path.display()))?;
+            let config = AppConfig::load_with(HashMap::from([(
+                "SCHEDULE_ACTION".to_string(),
+                plan.action.clone(),
+            )]))
+            .await?;
+            scheduler::apply(&config, &plan).await
         }
+        Some(command) => bail!("Unknown command '{}': expected 'plan' or 'apply'", command),
     }
+}
 
-    info!("Execution completed");
-    Ok(())
+/// Read the value of a `--name value` option.
+fn option(args: &[String], name: &str) -> Result<Option<PathBuf>> {
+    match args {
+        [] => Ok(None),
+        [flag, value] if flag == name => Ok(Some(PathBuf::from(value))),
+        _ => bail!(
+            "Invalid arguments '{}': expected '{} <file>'",
+            args.join(" "),
+            name
+        ),
+    }
 }
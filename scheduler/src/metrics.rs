@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{LazyLock, Mutex};
+
+use anyhow::{Context, Result};
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use chrono::Utc;
+use tracing::info;
+
+use crate::shutdown;
+use crate::summary::ExecutionSummary;
+
+/// Job name under which metrics are pushed to a Pushgateway.
+const PUSHGATEWAY_JOB: &str = "aws-scheduler-stop-start";
+
+/// Content type of the Prometheus text exposition format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Exported metrics: name, type and help text.
+const METRICS: [(&str, &str, &str); 7] = [
+    (
+        "scheduler_runs_total",
+        "counter",
+        "Number of stop/start runs, by action and status.",
+    ),
+    (
+        "scheduler_last_run_timestamp_seconds",
+        "gauge",
+        "Unix time at which the last run of the action completed.",
+    ),
+    (
+        "scheduler_last_run_success",
+        "gauge",
+        "Whether the last run of the action completed without failure (1) or not (0).",
+    ),
+    (
+        "scheduler_run_duration_seconds",
+        "gauge",
+        "Duration of the last run of the action.",
+    ),
+    (
+        "scheduler_resources_total",
+        "counter",
+        "Number of resources by action, service, region and outcome.",
+    ),
+    (
+        "scheduler_service_errors_total",
+        "counter",
+        "Number of times a service failed as a whole (discovery error, timeout, ...).",
+    ),
+    (
+        "scheduler_service_duration_seconds",
+        "gauge",
+        "Time spent processing the service in the region during the last run of the action.",
+    ),
+];
+
+/// Value of every series, keyed by metric name and rendered labels.
+static SERIES: LazyLock<Mutex<BTreeMap<(&'static str, String), f64>>> =
+    LazyLock::new(Default::default);
+
+/// Record the outcome of a run.
+///
+/// Metrics are kept in memory for the lifetime of the process, so that they
+/// can be scraped on `/metrics` in daemon and server modes.
+pub fn record(summary: &ExecutionSummary) {
+    let action = summary.action.as_str();
+    let success = summary.failed_services().is_empty() && !summary.interrupted;
+    let mut series = SERIES.lock().expect("metrics lock poisoned");
+
+    let status = if success { "success" } else { "failure" };
+    *series
+        .entry((
+            "scheduler_runs_total",
+            labels(&[("action", action), ("status", status)]),
+        ))
+        .or_default() += 1.0;
+    let action_labels = labels(&[("action", action)]);
+    series.insert(
+        (
+            "scheduler_last_run_timestamp_seconds",
+            action_labels.clone(),
+        ),
+        Utc::now().timestamp() as f64,
+    );
+    series.insert(
+        ("scheduler_last_run_success", action_labels.clone()),
+        if success { 1.0 } else { 0.0 },
+    );
+    series.insert(
+        ("scheduler_run_duration_seconds", action_labels),
+        summary.duration_seconds,
+    );
+
+    for service in &summary.services {
+        let service_labels = [
+            ("action", action),
+            ("service", service.service.as_str()),
+            ("region", service.region.as_str()),
+        ];
+        let counts = &service.counts;
+        for (outcome, count) in [
+            ("discovered", counts.discovered),
+            ("succeeded", counts.succeeded),
+            ("failed", counts.failed),
+            ("skipped", counts.skipped),
+        ] {
+            let mut outcome_labels = service_labels.to_vec();
+            outcome_labels.push(("outcome", outcome));
+            *series
+                .entry(("scheduler_resources_total", labels(&outcome_labels)))
+                .or_default() += count as f64;
+        }
+        let error = series
+            .entry(("scheduler_service_errors_total", labels(&service_labels)))
+            .or_default();
+        if service.error.is_some() {
+            *error += 1.0;
+        }
+        series.insert(
+            (
+                "scheduler_service_duration_seconds",
+                labels(&service_labels),
+            ),
+            service.duration_seconds,
+        );
+    }
+}
+
+/// Render every metric in the Prometheus text exposition format.
+pub fn render() -> String {
+    let series = SERIES.lock().expect("metrics lock poisoned");
+    let mut output = String::new();
+    for (name, kind, help) in METRICS {
+        let _ = writeln!(output, "# HELP {} {}", name, help);
+        let _ = writeln!(output, "# TYPE {} {}", name, kind);
+        for ((_, labels), value) in series
+            .range((name, String::new())..)
+            .take_while(|((n, _), _)| *n == name)
+        {
+            let _ = writeln!(output, "{}{} {}", name, labels, value);
+        }
+    }
+    output
+}
+
+/// Push every metric to a Prometheus Pushgateway, replacing the previously pushed ones.
+pub async fn push(pushgateway_url: &str) -> Result<()> {
+    let url = format!(
+        "{}/metrics/job/{}",
+        pushgateway_url.trim_end_matches('/'),
+        PUSHGATEWAY_JOB
+    );
+    info!(url = %url, "Pushing metrics to Pushgateway");
+    reqwest::Client::new()
+        .put(&url)
+        .header(header::CONTENT_TYPE, CONTENT_TYPE)
+        .body(render())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .with_context(|| format!("Failed to push metrics to '{}'", url))?;
+    Ok(())
+}
+
+/// Serve `GET /metrics` on the given port until a shutdown is requested.
+pub async fn serve(port: u16) -> Result<()> {
+    let app = Router::new().route("/metrics", get(handler));
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    info!(addr = %addr, "Metrics listener started");
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown::wait())
+        .await
+        .context("Metrics listener failed")
+}
+
+/// Handler of `GET /metrics`.
+pub async fn handler() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], render())
+}
+
+/// Render a label set, e.g. `{action="stop",service="ec2"}`.
+fn labels(pairs: &[(&str, &str)]) -> String {
+    let pairs: Vec<String> = pairs
+        .iter()
+        .map(|(key, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", key, value)
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
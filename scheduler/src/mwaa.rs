@@ -0,0 +1,346 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_mwaa::types::{Environment, EnvironmentStatus};
+use aws_sdk_mwaa::Client as MwaaClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Tags used to remember the worker counts of an environment before it was scaled down.
+const PREVIOUS_MIN_WORKERS_TAG: &str = "scheduler:previous-min-workers";
+const PREVIOUS_MAX_WORKERS_TAG: &str = "scheduler:previous-max-workers";
+
+/// Stop/start handler for MWAA (Managed Workflows for Apache Airflow) environments
+/// in a given AWS region.
+///
+/// The MWAA API offers no way to pause an environment, so it is scaled down instead.
+/// On stop: records the min/max worker counts in tags, then lowers both to 1.
+/// On start: restores the recorded worker counts.
+pub struct MwaaScheduler {
+    mwaa: MwaaClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl MwaaScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            mwaa: MwaaClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Record the worker counts of an environment in tags, then lower them to 1.
+    ///
+    /// Environments already down to a single worker are skipped, otherwise the tags
+    /// would be rewritten with 1 worker as the counts to restore.
+    async fn scale_down(&self, arn: &str, name: &str) -> Result<Outcome> {
+        let environment = self.get_environment(name).await?;
+        let min_workers = environment.min_workers().unwrap_or(1);
+        let max_workers = environment.max_workers().unwrap_or(1);
+
+        if min_workers <= 1 && max_workers <= 1 {
+            info!(environment = %name, "MWAA environment already scaled down, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if !is_available(&environment) {
+            info!(environment = %name, status = ?environment.status(), "MWAA environment not available, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(environment = %name, min_workers, max_workers, "Dry run: would scale down MWAA environment");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(environment = %name, min_workers, max_workers, "Recording previous worker counts");
+        self.mwaa
+            .tag_resource()
+            .resource_arn(arn)
+            .tags(PREVIOUS_MIN_WORKERS_TAG, min_workers.to_string())
+            .tags(PREVIOUS_MAX_WORKERS_TAG, max_workers.to_string())
+            .send()
+            .await?;
+
+        info!(environment = %name, "Scaling down MWAA environment");
+        self.update_workers(name, 1, 1).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the worker counts recorded on stop.
+    async fn restore(&self, name: &str) -> Result<Outcome> {
+        let environment = self.get_environment(name).await?;
+
+        let (Some(min_workers), Some(max_workers)) = (
+            read_workers_tag(&environment, PREVIOUS_MIN_WORKERS_TAG),
+            read_workers_tag(&environment, PREVIOUS_MAX_WORKERS_TAG),
+        ) else {
+            warn!(environment = %name, "No recorded worker counts found, skipping MWAA environment");
+            return Ok(Outcome::Skipped);
+        };
+
+        if environment.min_workers() == Some(min_workers)
+            && environment.max_workers() == Some(max_workers)
+        {
+            info!(environment = %name, "MWAA environment already restored, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if !is_available(&environment) {
+            info!(environment = %name, status = ?environment.status(), "MWAA environment not available, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(environment = %name, min_workers, max_workers, "Dry run: would restore MWAA environment");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(environment = %name, min_workers, max_workers, "Restoring MWAA environment");
+        self.update_workers(name, min_workers, max_workers).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Describe a single MWAA environment, including its tags.
+    async fn get_environment(&self, name: &str) -> Result<Environment> {
+        let resp = self.mwaa.get_environment().name(name).send().await?;
+
+        resp.environment()
+            .cloned()
+            .with_context(|| format!("MWAA environment '{}' not found", name))
+    }
+
+    async fn update_workers(&self, name: &str, min_workers: i32, max_workers: i32) -> Result<()> {
+        self.mwaa
+            .update_environment()
+            .name(name)
+            .min_workers(min_workers)
+            .max_workers(max_workers)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for MwaaScheduler {
+    fn name(&self) -> &'static str {
+        "mwaa"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "airflow:environment", filter)
+                .await?;
+        info!(count = arns.len(), "Found MWAA environments to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let name = extract_environment_name(arn);
+            let result = self.scale_down(arn, &name).await;
+            (name, result)
+        })
+        .await;
+        for (name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&name, outcome),
+                Err(e) => {
+                    counts.fail(&name, &e);
+                    error!(environment = %name, error = %e, "Failed to stop MWAA environment");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "airflow:environment", filter)
+                .await?;
+        info!(count = arns.len(), "Found MWAA environments to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let name = extract_environment_name(arn);
+            let result = self.restore(&name).await;
+            (name, result)
+        })
+        .await;
+        for (name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&name, outcome),
+                Err(e) => {
+                    counts.fail(&name, &e);
+                    error!(environment = %name, error = %e, "Failed to start MWAA environment");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the MWAA environments matching the given filter with their current status,
+    /// or `scaled-down` when they run a single worker.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "airflow:environment", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let environment = self
+                .get_environment(&extract_environment_name(&arn))
+                .await?;
+            let state = if environment.min_workers().unwrap_or(1) <= 1
+                && environment.max_workers().unwrap_or(1) <= 1
+            {
+                "scaled-down".to_string()
+            } else {
+                environment
+                    .status()
+                    .map(|s| s.as_str())
+                    .unwrap_or("UNKNOWN")
+                    .to_string()
+            };
+            items.push(InventoryItem::new(arn, "airflow:environment", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Whether an environment accepts updates (updates take several minutes to complete).
+fn is_available(environment: &Environment) -> bool {
+    environment.status() == Some(&EnvironmentStatus::Available)
+}
+
+/// Read a worker count recorded in the tags of an environment.
+fn read_workers_tag(environment: &Environment, key: &str) -> Option<i32> {
+    environment
+        .tags()
+        .and_then(|tags| tags.get(key))
+        .and_then(|v| v.parse::<i32>().ok())
+}
+
+/// Extract the environment name from an MWAA environment ARN.
+///
+/// Expected ARN format: `arn:partition:airflow:region:account:environment/name`
+fn extract_environment_name(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_mwaa::operation::get_environment::GetEnvironmentOutput;
+    use aws_sdk_mwaa::operation::tag_resource::TagResourceOutput;
+    use aws_sdk_mwaa::operation::update_environment::UpdateEnvironmentOutput;
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const ENVIRONMENT_ARN: &str = "arn:aws:airflow:eu-west-1:123456789012:environment/pipelines";
+
+    #[tokio::test]
+    async fn stop_skips_environments_already_scaled_down() {
+        let environments = get_resources_rule("airflow:environment", &[ENVIRONMENT_ARN]);
+        let get = mock!(MwaaClient::get_environment)
+            .match_requests(|req| req.name() == Some("pipelines"))
+            .then_output(|| {
+                GetEnvironmentOutput::builder()
+                    .environment(
+                        Environment::builder()
+                            .name("pipelines")
+                            .status(EnvironmentStatus::Available)
+                            .min_workers(1)
+                            .max_workers(1)
+                            .tags(PREVIOUS_MIN_WORKERS_TAG, "2")
+                            .tags(PREVIOUS_MAX_WORKERS_TAG, "10")
+                            .build(),
+                    )
+                    .build()
+            });
+        let record =
+            mock!(MwaaClient::tag_resource).then_output(|| TagResourceOutput::builder().build());
+        let update = mock!(MwaaClient::update_environment)
+            .then_output(|| UpdateEnvironmentOutput::builder().build());
+        let scheduler = MwaaScheduler {
+            mwaa: mock_client!(aws_sdk_mwaa, RuleMode::MatchAny, [&get, &record, &update]),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&environments]),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 0, 1, 0)
+        );
+        assert_eq!(record.num_calls(), 0);
+        assert_eq!(update.num_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn start_restores_the_recorded_worker_counts() {
+        let environments = get_resources_rule("airflow:environment", &[ENVIRONMENT_ARN]);
+        let get = mock!(MwaaClient::get_environment).then_output(|| {
+            GetEnvironmentOutput::builder()
+                .environment(
+                    Environment::builder()
+                        .name("pipelines")
+                        .status(EnvironmentStatus::Available)
+                        .min_workers(1)
+                        .max_workers(1)
+                        .tags(PREVIOUS_MIN_WORKERS_TAG, "2")
+                        .tags(PREVIOUS_MAX_WORKERS_TAG, "10")
+                        .build(),
+                )
+                .build()
+        });
+        let update = mock!(MwaaClient::update_environment)
+            .match_requests(|req| {
+                req.name() == Some("pipelines")
+                    && req.min_workers() == Some(2)
+                    && req.max_workers() == Some(10)
+            })
+            .then_output(|| UpdateEnvironmentOutput::builder().build());
+        let scheduler = MwaaScheduler {
+            mwaa: mock_client!(aws_sdk_mwaa, RuleMode::MatchAny, [&get, &update]),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&environments]),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+}
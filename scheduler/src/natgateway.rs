@@ -0,0 +1,826 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_ec2::types::{
+    Address, ConnectivityType, Filter, NatGateway, NatGatewayState, ResourceType, Tag,
+    TagSpecification,
+};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{debug, error, info, warn};
+
+use crate::arn::{self, Arn};
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::state::{State, StateStore};
+use crate::summary::{Outcome, ResourceCounts};
+use crate::waiters::Waiter;
+
+/// Elastic IP tag holding the subnet of a deleted NAT gateway, to re-create it.
+const SUBNET_ID_TAG: &str = "scheduler:nat-subnet-id";
+/// Prefix of the Elastic IP tags holding the tags of the deleted NAT gateway.
+const GATEWAY_TAG_PREFIX: &str = "scheduler:nat-tag:";
+
+/// State keys of the routes targeting a deleted NAT gateway: their number, then one
+/// `route-table-id=destination` entry per route, so that each fits in a tag value.
+const ROUTE_COUNT: &str = "nat-routes";
+const ROUTE_PREFIX: &str = "nat-route-";
+
+/// Stop/start handler for public NAT gateways in a given AWS region.
+///
+/// NAT gateways cannot be stopped, so they are deleted instead.
+/// On stop: records the subnet and the gateway's tags on its primary Elastic IP
+/// (which is kept) and the routes targeting the gateway in the state store, under
+/// the Elastic IP, then deletes the gateway.
+/// On start: discovers these Elastic IPs through the same tag filter, re-creates
+/// each gateway with its Elastic IP and points the recorded routes back to it.
+pub struct NatGatewayScheduler {
+    ec2: Ec2Client,
+    tagging: TaggingClient,
+    state: Arc<dyn StateStore>,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+    /// Waiter for a re-created gateway to be available before its routes are repaired.
+    waiter: Waiter,
+}
+
+/// A route targeting a NAT gateway.
+#[derive(Debug, PartialEq, Eq)]
+struct GatewayRoute {
+    route_table_id: String,
+    /// IPv4 or IPv6 CIDR block, or prefix list ID.
+    destination: String,
+}
+
+impl NatGatewayScheduler {
+    pub fn new(
+        config: &SdkConfig,
+        state: Arc<dyn StateStore>,
+        dry_run: bool,
+        max_concurrent_operations: usize,
+        waiter: Waiter,
+    ) -> Self {
+        Self {
+            ec2: Ec2Client::new(config),
+            tagging: TaggingClient::new(config),
+            state,
+            dry_run,
+            max_concurrent_operations,
+            waiter,
+        }
+    }
+
+    /// Record the settings of a NAT gateway on its primary Elastic IP, then delete it.
+    ///
+    /// The tags the filter relies on (tag key and schedule window) are copied as-is
+    /// so that the Elastic IP is discovered on start.
+    async fn record_and_delete(&self, arn: &str, filter: &ResourceFilter) -> Result<Outcome> {
+        let gateway_id = extract_resource_id(arn);
+        let gateway_id = gateway_id.as_str();
+        let gateway = self.describe_gateway(gateway_id).await?;
+        if gateway.state() != Some(&NatGatewayState::Available) {
+            info!(nat_gateway = %gateway_id, state = ?gateway.state(), "NAT gateway not available, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if gateway.connectivity_type() == Some(&ConnectivityType::Private) {
+            warn!(nat_gateway = %gateway_id, "Private NAT gateways have no Elastic IP to record their settings, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let subnet_id = gateway
+            .subnet_id()
+            .with_context(|| format!("NAT gateway '{}' has no subnet", gateway_id))?;
+        let allocation_id = gateway
+            .nat_gateway_addresses()
+            .iter()
+            .find(|a| a.is_primary().unwrap_or(true))
+            .and_then(|a| a.allocation_id())
+            .with_context(|| format!("NAT gateway '{}' has no Elastic IP", gateway_id))?;
+        let routes = self.gateway_routes(gateway_id).await?;
+
+        if self.dry_run {
+            info!(nat_gateway = %gateway_id, routes = routes.len(), "Dry run: would delete NAT gateway");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(nat_gateway = %gateway_id, allocation = %allocation_id, routes = routes.len(), "Recording NAT gateway routes");
+        self.state
+            .save(&elastic_ip_arn(arn, allocation_id)?, &route_state(&routes))
+            .await?;
+
+        let mut tags = vec![ec2_tag(SUBNET_ID_TAG, subnet_id)];
+        for tag in gateway.tags() {
+            let (Some(key), value) = (tag.key(), tag.value().unwrap_or_default()) else {
+                continue;
+            };
+            if key.starts_with("aws:") {
+                continue;
+            }
+            tags.push(ec2_tag(&format!("{}{}", GATEWAY_TAG_PREFIX, key), value));
+            if key == filter.tag_key || filter.window_tag_key.as_deref() == Some(key) {
+                tags.push(ec2_tag(key, value));
+            }
+        }
+
+        info!(nat_gateway = %gateway_id, allocation = %allocation_id, "Recording NAT gateway settings on its Elastic IP");
+        self.ec2
+            .create_tags()
+            .resources(allocation_id)
+            .set_tags(Some(tags))
+            .send()
+            .await?;
+
+        info!(nat_gateway = %gateway_id, "Deleting NAT gateway");
+        self.ec2
+            .delete_nat_gateway()
+            .nat_gateway_id(gateway_id)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Re-create a NAT gateway from the settings recorded on its Elastic IP and repair its routes.
+    ///
+    /// Elastic IPs still associated (e.g. the old gateway is still being deleted) are left untouched.
+    async fn recreate(&self, arn: &str, address: &Address) -> Result<Outcome> {
+        let allocation_id = address.allocation_id().unwrap_or_default();
+        if let Some(association) = address.association_id() {
+            info!(allocation = %allocation_id, association = %association, "Elastic IP still associated, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let recorded: HashMap<&str, &str> = address
+            .tags()
+            .iter()
+            .filter_map(|t| Some((t.key()?, t.value().unwrap_or_default())))
+            .collect();
+        let subnet_id = recorded.get(SUBNET_ID_TAG).with_context(|| {
+            format!(
+                "Elastic IP '{}' has no {} tag",
+                allocation_id, SUBNET_ID_TAG
+            )
+        })?;
+        let routes = parse_routes(&self.state.load(arn).await?).with_context(|| {
+            format!(
+                "Invalid NAT gateway routes recorded for Elastic IP '{}'",
+                allocation_id
+            )
+        })?;
+        let gateway_tags: Vec<Tag> = recorded
+            .iter()
+            .filter_map(|(k, v)| Some(ec2_tag(k.strip_prefix(GATEWAY_TAG_PREFIX)?, v)))
+            .collect();
+
+        if self.dry_run {
+            info!(allocation = %allocation_id, subnet = %subnet_id, routes = routes.len(), "Dry run: would re-create NAT gateway");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(allocation = %allocation_id, subnet = %subnet_id, "Re-creating NAT gateway");
+        let resp = self
+            .ec2
+            .create_nat_gateway()
+            .subnet_id(*subnet_id)
+            .allocation_id(allocation_id)
+            .tag_specifications(
+                TagSpecification::builder()
+                    .resource_type(ResourceType::Natgateway)
+                    .set_tags(Some(gateway_tags))
+                    .build(),
+            )
+            .send()
+            .await?;
+        let gateway_id = resp
+            .nat_gateway()
+            .and_then(|g| g.nat_gateway_id())
+            .context("CreateNatGateway returned no NAT gateway ID")?;
+        self.wait_gateway_available(gateway_id).await?;
+
+        for route in &routes {
+            info!(nat_gateway = %gateway_id, route_table = %route.route_table_id, destination = %route.destination, "Pointing route to the NAT gateway");
+            self.restore_route(gateway_id, route).await?;
+        }
+
+        let recorded_keys: Vec<Tag> = recorded
+            .keys()
+            .filter(|k| k.starts_with("scheduler:nat-"))
+            .map(|k| Tag::builder().key(*k).build())
+            .collect();
+        self.ec2
+            .delete_tags()
+            .resources(allocation_id)
+            .set_tags(Some(recorded_keys))
+            .send()
+            .await?;
+
+        Ok(Outcome::Applied)
+    }
+
+    async fn describe_gateway(&self, gateway_id: &str) -> Result<NatGateway> {
+        let resp = self
+            .ec2
+            .describe_nat_gateways()
+            .nat_gateway_ids(gateway_id)
+            .send()
+            .await?;
+
+        resp.nat_gateways()
+            .first()
+            .cloned()
+            .with_context(|| format!("NAT gateway '{}' not found", gateway_id))
+    }
+
+    async fn describe_addresses(&self, allocation_ids: &[String]) -> Result<Vec<Address>> {
+        if allocation_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let resp = self
+            .ec2
+            .describe_addresses()
+            .set_allocation_ids(Some(allocation_ids.to_vec()))
+            .send()
+            .await?;
+
+        Ok(resp.addresses().to_vec())
+    }
+
+    /// List the routes, across all route tables, that target a NAT gateway.
+    async fn gateway_routes(&self, gateway_id: &str) -> Result<Vec<GatewayRoute>> {
+        let resp = self
+            .ec2
+            .describe_route_tables()
+            .filters(
+                Filter::builder()
+                    .name("route.nat-gateway-id")
+                    .values(gateway_id)
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        let mut routes = Vec::new();
+        for table in resp.route_tables() {
+            let Some(route_table_id) = table.route_table_id() else {
+                continue;
+            };
+            for route in table
+                .routes()
+                .iter()
+                .filter(|r| r.nat_gateway_id() == Some(gateway_id))
+            {
+                let destination = route
+                    .destination_cidr_block()
+                    .or(route.destination_ipv6_cidr_block())
+                    .or(route.destination_prefix_list_id());
+                if let Some(destination) = destination {
+                    routes.push(GatewayRoute {
+                        route_table_id: route_table_id.to_string(),
+                        destination: destination.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(routes)
+    }
+
+    /// Point a route to a NAT gateway, replacing the blackhole route left by the deleted one.
+    async fn restore_route(&self, gateway_id: &str, route: &GatewayRoute) -> Result<()> {
+        let replaced = self
+            .ec2
+            .replace_route()
+            .route_table_id(&route.route_table_id)
+            .set_destination_cidr_block(route.ipv4_cidr())
+            .set_destination_ipv6_cidr_block(route.ipv6_cidr())
+            .set_destination_prefix_list_id(route.prefix_list_id())
+            .nat_gateway_id(gateway_id)
+            .send()
+            .await;
+        if replaced.is_ok() {
+            return Ok(());
+        }
+
+        // The route was removed while the gateway was deleted: create it again.
+        self.ec2
+            .create_route()
+            .route_table_id(&route.route_table_id)
+            .set_destination_cidr_block(route.ipv4_cidr())
+            .set_destination_ipv6_cidr_block(route.ipv6_cidr())
+            .set_destination_prefix_list_id(route.prefix_list_id())
+            .nat_gateway_id(gateway_id)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Poll a NAT gateway until it reaches the `available` state.
+    async fn wait_gateway_available(&self, gateway_id: &str) -> Result<()> {
+        let pending = self
+            .waiter
+            .wait("NAT gateway to become available", move || async move {
+                match self.describe_gateway(gateway_id).await?.state() {
+                    Some(NatGatewayState::Available) => Ok(Vec::new()),
+                    Some(NatGatewayState::Failed) => {
+                        bail!("NAT gateway '{}' failed to create", gateway_id)
+                    }
+                    _ => Ok(vec![gateway_id.to_string()]),
+                }
+            })
+            .await?;
+        if !pending.is_empty() {
+            bail!(
+                "Timed out waiting for NAT gateway '{}' to become available",
+                gateway_id
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for NatGatewayScheduler {
+    fn name(&self) -> &'static str {
+        "nat_gateway"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(&self.tagging, "ec2:natgateway", filter)
+            .await?;
+        info!(count = arns.len(), "Found NAT gateways to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let result = self.record_and_delete(arn, filter).await;
+            (extract_resource_id(arn), result)
+        })
+        .await;
+        for (gateway_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&gateway_id, outcome),
+                Err(e) => {
+                    counts.fail(&gateway_id, &e);
+                    error!(nat_gateway = %gateway_id, error = %e, "Failed to stop NAT gateway");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(&self.tagging, "ec2:elastic-ip", filter)
+            .await?;
+        let allocation_ids: Vec<String> = arns.iter().map(|arn| extract_resource_id(arn)).collect();
+        let addresses: Vec<(&String, Address)> = self
+            .describe_addresses(&allocation_ids)
+            .await?
+            .into_iter()
+            .filter(|a| {
+                let recorded = a.tags().iter().any(|t| t.key() == Some(SUBNET_ID_TAG));
+                if !recorded {
+                    debug!(allocation = ?a.allocation_id(), "Elastic IP holds no NAT gateway record, ignoring");
+                }
+                recorded
+            })
+            .filter_map(|a| {
+                let arn = arns
+                    .iter()
+                    .find(|arn| Some(extract_resource_id(arn).as_str()) == a.allocation_id())?;
+                Some((arn, a))
+            })
+            .collect();
+        info!(count = addresses.len(), "Found NAT gateways to re-create");
+
+        filter.check_discovered(addresses.len())?;
+        let mut counts = ResourceCounts::new(addresses.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &addresses,
+            |(arn, address)| async move {
+                let allocation_id = address.allocation_id().unwrap_or_default();
+                let result = self.recreate(arn, address).await;
+                (allocation_id, result)
+            },
+        )
+        .await;
+        for (allocation_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(allocation_id, outcome),
+                Err(e) => {
+                    counts.fail(allocation_id, &e);
+                    error!(allocation = %allocation_id, error = %e, "Failed to re-create NAT gateway");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the NAT gateways matching the given filter with their current state, along with
+    /// the Elastic IPs recording a gateway deleted on stop, reported as `nat-gateway-deleted`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns = filter_resources_by_tags::get_resources(&self.tagging, "ec2:natgateway", filter)
+            .await?;
+        let address_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ec2:elastic-ip", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let gateway = self.describe_gateway(&extract_resource_id(&arn)).await?;
+            let state = gateway
+                .state()
+                .map(|s| s.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            items.push(InventoryItem::new(arn, "ec2:natgateway", state));
+        }
+
+        let allocation_ids: Vec<String> = address_arns
+            .iter()
+            .map(|arn| extract_resource_id(arn))
+            .collect();
+        for address in self.describe_addresses(&allocation_ids).await? {
+            if !address
+                .tags()
+                .iter()
+                .any(|t| t.key() == Some(SUBNET_ID_TAG))
+            {
+                continue;
+            }
+            let Some(arn) = address_arns
+                .iter()
+                .find(|arn| Some(extract_resource_id(arn).as_str()) == address.allocation_id())
+            else {
+                continue;
+            };
+            items.push(InventoryItem::new(
+                arn.as_str(),
+                "ec2:elastic-ip",
+                "nat-gateway-deleted",
+            ));
+        }
+        Ok(items)
+    }
+}
+
+impl GatewayRoute {
+    fn ipv4_cidr(&self) -> Option<String> {
+        (!self.destination.starts_with("pl-") && !self.destination.contains(':'))
+            .then(|| self.destination.clone())
+    }
+
+    fn ipv6_cidr(&self) -> Option<String> {
+        self.destination
+            .contains(':')
+            .then(|| self.destination.clone())
+    }
+
+    fn prefix_list_id(&self) -> Option<String> {
+        self.destination
+            .starts_with("pl-")
+            .then(|| self.destination.clone())
+    }
+}
+
+/// State recording the routes of a NAT gateway, one `route-table-id=destination` entry per key.
+fn route_state(routes: &[GatewayRoute]) -> State {
+    let entries = routes.iter().enumerate().map(|(i, r)| {
+        (
+            format!("{}{}", ROUTE_PREFIX, i),
+            format!("{}={}", r.route_table_id, r.destination),
+        )
+    });
+    std::iter::once((ROUTE_COUNT.to_string(), routes.len().to_string()))
+        .chain(entries)
+        .collect()
+}
+
+/// Parse the routes recorded by [`route_state`]; none when no route was recorded.
+///
+/// Only the recorded number of entries is read, so that the routes left over
+/// from an earlier stop with more routes are ignored.
+fn parse_routes(state: &State) -> Result<Vec<GatewayRoute>> {
+    let Some(count) = state.get(ROUTE_COUNT) else {
+        return Ok(Vec::new());
+    };
+    let count: usize = count
+        .parse()
+        .with_context(|| format!("invalid route count '{}'", count))?;
+    (0..count)
+        .map(|i| {
+            let key = format!("{}{}", ROUTE_PREFIX, i);
+            let entry = state
+                .get(&key)
+                .with_context(|| format!("missing route '{}'", key))?;
+            let (route_table_id, destination) = entry
+                .split_once('=')
+                .with_context(|| format!("invalid route '{}'", entry))?;
+            Ok(GatewayRoute {
+                route_table_id: route_table_id.to_string(),
+                destination: destination.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// ARN of the Elastic IP `allocation_id`, in the partition, region and account of `arn`.
+fn elastic_ip_arn(arn: &str, allocation_id: &str) -> Result<String> {
+    let arn = Arn::parse(arn)?;
+    Ok(format!(
+        "arn:{}:ec2:{}:{}:elastic-ip/{}",
+        arn.partition, arn.region, arn.account, allocation_id
+    ))
+}
+
+fn ec2_tag(key: &str, value: &str) -> Tag {
+    Tag::builder().key(key).value(value).build()
+}
+
+/// Extract the resource ID from an EC2 ARN.
+///
+/// Expected ARN formats:
+/// - NAT gateway: `arn:partition:ec2:region:account:natgateway/nat-0123456789abcdef0`
+/// - Elastic IP: `arn:partition:ec2:region:account:elastic-ip/eipalloc-0123456789abcdef0`
+fn extract_resource_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use aws_sdk_ec2::error::ErrorMetadata;
+    use aws_sdk_ec2::operation::create_nat_gateway::CreateNatGatewayOutput;
+    use aws_sdk_ec2::operation::create_route::CreateRouteOutput;
+    use aws_sdk_ec2::operation::create_tags::CreateTagsOutput;
+    use aws_sdk_ec2::operation::delete_nat_gateway::DeleteNatGatewayOutput;
+    use aws_sdk_ec2::operation::delete_tags::DeleteTagsOutput;
+    use aws_sdk_ec2::operation::describe_addresses::DescribeAddressesOutput;
+    use aws_sdk_ec2::operation::describe_nat_gateways::DescribeNatGatewaysOutput;
+    use aws_sdk_ec2::operation::describe_route_tables::DescribeRouteTablesOutput;
+    use aws_sdk_ec2::operation::replace_route::{ReplaceRouteError, ReplaceRouteOutput};
+    use aws_sdk_ec2::types::{NatGatewayAddress, Route, RouteTable};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule, MemoryStore, TAG_KEY, TAG_VALUE};
+
+    const GATEWAY_ARN: &str = "arn:aws:ec2:eu-west-1:123456789012:natgateway/nat-1";
+    const ADDRESS_ARN: &str = "arn:aws:ec2:eu-west-1:123456789012:elastic-ip/eipalloc-1";
+
+    fn scheduler(
+        ec2: Ec2Client,
+        tagging: TaggingClient,
+        state: Arc<MemoryStore>,
+    ) -> NatGatewayScheduler {
+        NatGatewayScheduler {
+            ec2,
+            tagging,
+            state,
+            dry_run: false,
+            max_concurrent_operations: 10,
+            waiter: Waiter {
+                max_attempts: 1,
+                delay: Duration::ZERO,
+            },
+        }
+    }
+
+    /// Rule describing an available NAT gateway `gateway_id` in `subnet-1`.
+    fn describe_gateway_rule(gateway_id: &'static str) -> Rule {
+        mock!(Ec2Client::describe_nat_gateways)
+            .match_requests(move |req| req.nat_gateway_ids() == [gateway_id])
+            .then_output(move || {
+                let gateway = NatGateway::builder()
+                    .nat_gateway_id(gateway_id)
+                    .state(NatGatewayState::Available)
+                    .connectivity_type(ConnectivityType::Public)
+                    .subnet_id("subnet-1")
+                    .nat_gateway_addresses(
+                        NatGatewayAddress::builder()
+                            .allocation_id("eipalloc-1")
+                            .is_primary(true)
+                            .build(),
+                    )
+                    .tags(ec2_tag(TAG_KEY, TAG_VALUE))
+                    .tags(ec2_tag("Name", "web"))
+                    .build();
+                DescribeNatGatewaysOutput::builder()
+                    .nat_gateways(gateway)
+                    .build()
+            })
+    }
+
+    /// Rule describing `eipalloc-1`, recording a deleted gateway, with the given association.
+    fn describe_address_rule(association_id: Option<&'static str>) -> Rule {
+        mock!(Ec2Client::describe_addresses).then_output(move || {
+            let address = Address::builder()
+                .allocation_id("eipalloc-1")
+                .set_association_id(association_id.map(str::to_string))
+                .tags(ec2_tag(TAG_KEY, TAG_VALUE))
+                .tags(ec2_tag(SUBNET_ID_TAG, "subnet-1"))
+                .tags(ec2_tag(&format!("{}Name", GATEWAY_TAG_PREFIX), "web"))
+                .build();
+            DescribeAddressesOutput::builder()
+                .addresses(address)
+                .build()
+        })
+    }
+
+    fn route(route_table_id: &str, destination: &str) -> GatewayRoute {
+        GatewayRoute {
+            route_table_id: route_table_id.to_string(),
+            destination: destination.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_records_the_routes_and_deletes_the_gateway() {
+        let tagging = get_resources_rule("ec2:natgateway", &[GATEWAY_ARN]);
+        let describe = describe_gateway_rule("nat-1");
+        let route_tables = mock!(Ec2Client::describe_route_tables).then_output(|| {
+            DescribeRouteTablesOutput::builder()
+                .route_tables(
+                    RouteTable::builder()
+                        .route_table_id("rtb-1")
+                        .routes(
+                            Route::builder()
+                                .destination_cidr_block("0.0.0.0/0")
+                                .nat_gateway_id("nat-1")
+                                .build(),
+                        )
+                        .routes(
+                            Route::builder()
+                                .destination_cidr_block("10.0.0.0/16")
+                                .gateway_id("local")
+                                .build(),
+                        )
+                        .build(),
+                )
+                .route_tables(
+                    RouteTable::builder()
+                        .route_table_id("rtb-2")
+                        .routes(
+                            Route::builder()
+                                .destination_prefix_list_id("pl-1")
+                                .nat_gateway_id("nat-1")
+                                .build(),
+                        )
+                        .build(),
+                )
+                .build()
+        });
+        let record = mock!(Ec2Client::create_tags)
+            .match_requests(|req| {
+                let tag = |key: &str| req.tags().iter().find(|t| t.key() == Some(key));
+                req.resources() == ["eipalloc-1"]
+                    && tag(SUBNET_ID_TAG).and_then(|t| t.value()) == Some("subnet-1")
+                    && tag(TAG_KEY).and_then(|t| t.value()) == Some(TAG_VALUE)
+            })
+            .then_output(|| CreateTagsOutput::builder().build());
+        let delete = mock!(Ec2Client::delete_nat_gateway)
+            .match_requests(|req| req.nat_gateway_id() == Some("nat-1"))
+            .then_output(|| DeleteNatGatewayOutput::builder().build());
+        let state = Arc::new(MemoryStore::default());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_ec2,
+                RuleMode::MatchAny,
+                [&describe, &route_tables, &record, &delete]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            state.clone(),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(counts.succeeded, 1);
+        assert_eq!(delete.num_calls(), 1);
+        assert_eq!(
+            parse_routes(&state.state(ADDRESS_ARN).unwrap()).unwrap(),
+            [route("rtb-1", "0.0.0.0/0"), route("rtb-2", "pl-1")]
+        );
+    }
+
+    #[tokio::test]
+    async fn start_skips_elastic_ips_still_associated() {
+        let tagging = get_resources_rule("ec2:elastic-ip", &[ADDRESS_ARN]);
+        let describe = describe_address_rule(Some("eipassoc-1"));
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_ec2, RuleMode::MatchAny, [&describe]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            Arc::new(MemoryStore::with(ADDRESS_ARN, &[(ROUTE_COUNT, "0")])),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!((counts.discovered, counts.skipped), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn start_recreates_the_gateway_and_repairs_its_routes() {
+        let tagging = get_resources_rule("ec2:elastic-ip", &[ADDRESS_ARN]);
+        let describe_address = describe_address_rule(None);
+        let create = mock!(Ec2Client::create_nat_gateway)
+            .match_requests(|req| {
+                let tags = req.tag_specifications().first().map(|s| s.tags());
+                req.subnet_id() == Some("subnet-1")
+                    && req.allocation_id() == Some("eipalloc-1")
+                    && tags == Some(&[ec2_tag("Name", "web")][..])
+            })
+            .then_output(|| {
+                CreateNatGatewayOutput::builder()
+                    .nat_gateway(NatGateway::builder().nat_gateway_id("nat-2").build())
+                    .build()
+            });
+        let describe_gateway = describe_gateway_rule("nat-2");
+        let replace = mock!(Ec2Client::replace_route)
+            .match_requests(|req| {
+                req.route_table_id() == Some("rtb-1")
+                    && req.destination_cidr_block() == Some("0.0.0.0/0")
+                    && req.nat_gateway_id() == Some("nat-2")
+            })
+            .then_output(|| ReplaceRouteOutput::builder().build());
+        let replace_removed = mock!(Ec2Client::replace_route)
+            .match_requests(|req| req.route_table_id() == Some("rtb-2"))
+            .then_error(|| {
+                ReplaceRouteError::generic(
+                    ErrorMetadata::builder()
+                        .code("InvalidRoute.NotFound")
+                        .build(),
+                )
+            });
+        let create_route = mock!(Ec2Client::create_route)
+            .match_requests(|req| {
+                req.route_table_id() == Some("rtb-2")
+                    && req.destination_prefix_list_id() == Some("pl-1")
+                    && req.nat_gateway_id() == Some("nat-2")
+            })
+            .then_output(|| CreateRouteOutput::builder().build());
+        let cleanup =
+            mock!(Ec2Client::delete_tags).then_output(|| DeleteTagsOutput::builder().build());
+        let state = MemoryStore::with(
+            ADDRESS_ARN,
+            &[
+                (ROUTE_COUNT, "2"),
+                ("nat-route-0", "rtb-1=0.0.0.0/0"),
+                ("nat-route-1", "rtb-2=pl-1"),
+            ],
+        );
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_ec2,
+                RuleMode::MatchAny,
+                [
+                    &describe_address,
+                    &create,
+                    &describe_gateway,
+                    &replace,
+                    &replace_removed,
+                    &create_route,
+                    &cleanup
+                ]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&tagging]),
+            Arc::new(state),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!((counts.succeeded, counts.failed), (1, 0));
+        assert_eq!(create.num_calls(), 1);
+        assert_eq!(replace.num_calls(), 1);
+        assert_eq!(create_route.num_calls(), 1);
+        assert_eq!(cleanup.num_calls(), 1);
+    }
+
+    #[test]
+    fn parse_routes_reads_the_recorded_number_of_routes() {
+        let routes = [route("rtb-1", "0.0.0.0/0"), route("rtb-1", "::/0")];
+        let mut state = route_state(&routes);
+        assert_eq!(parse_routes(&state).unwrap(), routes);
+
+        state.insert("nat-route-2".to_string(), "rtb-old=10.0.0.0/8".to_string());
+        assert_eq!(parse_routes(&state).unwrap(), routes);
+
+        assert!(parse_routes(&State::new()).unwrap().is_empty());
+        state.insert("nat-route-1".to_string(), "rtb-1".to_string());
+        assert!(parse_routes(&state).is_err());
+        state.insert(ROUTE_COUNT.to_string(), "3x".to_string());
+        assert!(parse_routes(&state).is_err());
+    }
+}
@@ -0,0 +1,443 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_opensearch::types::{
+    ClusterConfig, OpenSearchPartitionInstanceType, ZoneAwarenessConfig,
+};
+use aws_sdk_opensearch::Client as OpenSearchClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::state::{State, StateStore};
+use crate::summary::{Outcome, ResourceCounts};
+
+/// State keys used to remember the data node configuration of a domain before it was scaled down.
+const PREVIOUS_INSTANCE_TYPE: &str = "previous-instance-type";
+const PREVIOUS_INSTANCE_COUNT: &str = "previous-instance-count";
+const PREVIOUS_AZ_COUNT: &str = "previous-az-count";
+
+/// Stop/start handler for OpenSearch domains in a given AWS region.
+///
+/// Domains cannot be stopped, so they are scaled down instead.
+/// On stop: records the data node type, count and zone awareness in the state
+/// store (by default, `scheduler:previous-*` tags), then
+/// scales the domain down to a single data node of the configured instance type.
+/// On start: restores the recorded data node configuration.
+pub struct OpenSearchScheduler {
+    opensearch: OpenSearchClient,
+    tagging: TaggingClient,
+    state: Arc<dyn StateStore>,
+    stop_instance_type: String,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+/// Data node configuration of a domain.
+#[derive(Debug, PartialEq)]
+struct DataNodes {
+    instance_type: String,
+    instance_count: i32,
+    /// Number of availability zones, `None` when zone awareness is disabled.
+    az_count: Option<i32>,
+}
+
+impl OpenSearchScheduler {
+    pub fn new(
+        config: &SdkConfig,
+        state: Arc<dyn StateStore>,
+        stop_instance_type: &str,
+        dry_run: bool,
+        max_concurrent_operations: usize,
+    ) -> Self {
+        Self {
+            opensearch: OpenSearchClient::new(config),
+            tagging: TaggingClient::new(config),
+            state,
+            stop_instance_type: stop_instance_type.to_string(),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Record the data node configuration of a domain in the state store, then scale it down to one node.
+    ///
+    /// A domain already running a single data node of the stop instance type is
+    /// skipped, so the state store keeps the data nodes it ran during the day.
+    async fn scale_down(&self, arn: &str, domain: &str) -> Result<Outcome> {
+        let current = self.data_nodes(domain).await?;
+        let target = DataNodes {
+            instance_type: self.stop_instance_type.clone(),
+            instance_count: 1,
+            az_count: None,
+        };
+
+        if current == target {
+            info!(domain = %domain, "OpenSearch domain already scaled down, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(domain = %domain, current = ?current, "Dry run: would scale down OpenSearch domain");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(domain = %domain, current = ?current, "Recording previous data node configuration");
+        let mut state = State::from([
+            (
+                PREVIOUS_INSTANCE_TYPE.to_string(),
+                current.instance_type.clone(),
+            ),
+            (
+                PREVIOUS_INSTANCE_COUNT.to_string(),
+                current.instance_count.to_string(),
+            ),
+        ]);
+        if let Some(az_count) = current.az_count {
+            state.insert(PREVIOUS_AZ_COUNT.to_string(), az_count.to_string());
+        }
+        self.state.save(arn, &state).await?;
+
+        info!(domain = %domain, instance_type = %target.instance_type, "Scaling down OpenSearch domain to a single data node");
+        self.update_data_nodes(domain, &target).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the data node configuration recorded on stop.
+    async fn restore(&self, arn: &str, domain: &str) -> Result<Outcome> {
+        let state = self.state.load(arn).await?;
+        let (Some(instance_type), Some(instance_count)) = (
+            state.get(PREVIOUS_INSTANCE_TYPE),
+            state
+                .get(PREVIOUS_INSTANCE_COUNT)
+                .and_then(|v| v.parse::<i32>().ok()),
+        ) else {
+            warn!(domain = %domain, "No recorded data node configuration found, skipping OpenSearch domain");
+            return Ok(Outcome::Skipped);
+        };
+        let previous = DataNodes {
+            instance_type: instance_type.clone(),
+            instance_count,
+            az_count: state
+                .get(PREVIOUS_AZ_COUNT)
+                .and_then(|v| v.parse::<i32>().ok()),
+        };
+
+        if self.data_nodes(domain).await? == previous {
+            info!(domain = %domain, "OpenSearch domain already restored, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(domain = %domain, previous = ?previous, "Dry run: would restore OpenSearch domain");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(domain = %domain, previous = ?previous, "Restoring OpenSearch domain");
+        self.update_data_nodes(domain, &previous).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Get the current data node configuration of a domain.
+    async fn data_nodes(&self, domain: &str) -> Result<DataNodes> {
+        let resp = self
+            .opensearch
+            .describe_domain()
+            .domain_name(domain)
+            .send()
+            .await?;
+
+        let cluster = resp
+            .domain_status()
+            .and_then(|s| s.cluster_config())
+            .with_context(|| {
+                format!(
+                    "OpenSearch domain '{}' has no cluster configuration",
+                    domain
+                )
+            })?;
+        let az_count = if cluster.zone_awareness_enabled().unwrap_or_default() {
+            Some(
+                cluster
+                    .zone_awareness_config()
+                    .and_then(|z| z.availability_zone_count())
+                    .unwrap_or(2),
+            )
+        } else {
+            None
+        };
+
+        Ok(DataNodes {
+            instance_type: cluster
+                .instance_type()
+                .map(|t| t.as_str().to_string())
+                .unwrap_or_default(),
+            instance_count: cluster.instance_count().unwrap_or_default(),
+            az_count,
+        })
+    }
+
+    async fn update_data_nodes(&self, domain: &str, nodes: &DataNodes) -> Result<()> {
+        let mut cluster = ClusterConfig::builder()
+            .instance_type(OpenSearchPartitionInstanceType::from(
+                nodes.instance_type.as_str(),
+            ))
+            .instance_count(nodes.instance_count)
+            .zone_awareness_enabled(nodes.az_count.is_some());
+        if let Some(az_count) = nodes.az_count {
+            cluster = cluster.zone_awareness_config(
+                ZoneAwarenessConfig::builder()
+                    .availability_zone_count(az_count)
+                    .build(),
+            );
+        }
+
+        self.opensearch
+            .update_domain_config()
+            .domain_name(domain)
+            .cluster_config(cluster.build())
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for OpenSearchScheduler {
+    fn name(&self) -> &'static str {
+        "opensearch"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "es:domain", filter).await?;
+        info!(count = arns.len(), "Found OpenSearch domains to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let domain = extract_domain_name(arn);
+            let result = self.scale_down(arn, &domain).await;
+            (domain, result)
+        })
+        .await;
+        for (domain, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&domain, outcome),
+                Err(e) => {
+                    counts.fail(&domain, &e);
+                    error!(domain = %domain, error = %e, "Failed to stop OpenSearch domain");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "es:domain", filter).await?;
+        info!(count = arns.len(), "Found OpenSearch domains to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let domain = extract_domain_name(arn);
+            let result = self.restore(arn, &domain).await;
+            (domain, result)
+        })
+        .await;
+        for (domain, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&domain, outcome),
+                Err(e) => {
+                    counts.fail(&domain, &e);
+                    error!(domain = %domain, error = %e, "Failed to start OpenSearch domain");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the OpenSearch domains matching the given filter, `scaled-down` when they run
+    /// a single data node of the stop instance type and `running` otherwise.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "es:domain", filter).await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let current = self.data_nodes(&extract_domain_name(&arn)).await?;
+            let scaled_down =
+                current.instance_type == self.stop_instance_type && current.instance_count == 1;
+            let state = if scaled_down {
+                "scaled-down"
+            } else {
+                "running"
+            };
+            items.push(InventoryItem::new(arn, "es:domain", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the domain name from an OpenSearch domain ARN.
+///
+/// Expected ARN format: `arn:partition:es:region:account:domain/domain-name`
+fn extract_domain_name(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_opensearch::operation::describe_domain::DescribeDomainOutput;
+    use aws_sdk_opensearch::operation::update_domain_config::UpdateDomainConfigOutput;
+    use aws_sdk_opensearch::types::DomainStatus;
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule, MemoryStore};
+
+    const DOMAIN_ARN: &str = "arn:aws:es:eu-west-1:123456789012:domain/search";
+    const STOP_INSTANCE_TYPE: &str = "t3.small.search";
+
+    #[tokio::test]
+    async fn stop_skips_domains_already_scaled_down() {
+        let domains = get_resources_rule("es:domain", &[DOMAIN_ARN]);
+        let describe = mock!(OpenSearchClient::describe_domain)
+            .match_requests(|req| req.domain_name() == Some("search"))
+            .then_output(|| {
+                DescribeDomainOutput::builder()
+                    .domain_status(
+                        DomainStatus::builder()
+                            .domain_id("123456789012/search")
+                            .domain_name("search")
+                            .arn(DOMAIN_ARN)
+                            .cluster_config(
+                                ClusterConfig::builder()
+                                    .instance_type(OpenSearchPartitionInstanceType::from(
+                                        STOP_INSTANCE_TYPE,
+                                    ))
+                                    .instance_count(1)
+                                    .zone_awareness_enabled(false)
+                                    .build(),
+                            )
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+            });
+        let update = mock!(OpenSearchClient::update_domain_config)
+            .then_output(|| UpdateDomainConfigOutput::builder().build());
+        let state = Arc::new(MemoryStore::with(
+            DOMAIN_ARN,
+            &[
+                (PREVIOUS_INSTANCE_TYPE, "r6g.large.search"),
+                (PREVIOUS_INSTANCE_COUNT, "3"),
+            ],
+        ));
+        let scheduler = OpenSearchScheduler {
+            opensearch: mock_client!(aws_sdk_opensearch, RuleMode::MatchAny, [&describe, &update]),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&domains]),
+            state: state.clone(),
+            stop_instance_type: STOP_INSTANCE_TYPE.to_string(),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 0, 1, 0)
+        );
+        assert_eq!(update.num_calls(), 0);
+        assert_eq!(
+            state
+                .state(DOMAIN_ARN)
+                .and_then(|s| s.get(PREVIOUS_INSTANCE_TYPE).cloned()),
+            Some("r6g.large.search".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn start_restores_the_recorded_data_nodes() {
+        let domains = get_resources_rule("es:domain", &[DOMAIN_ARN]);
+        let describe = mock!(OpenSearchClient::describe_domain).then_output(|| {
+            DescribeDomainOutput::builder()
+                .domain_status(
+                    DomainStatus::builder()
+                        .domain_id("123456789012/search")
+                        .domain_name("search")
+                        .arn(DOMAIN_ARN)
+                        .cluster_config(
+                            ClusterConfig::builder()
+                                .instance_type(OpenSearchPartitionInstanceType::from(
+                                    STOP_INSTANCE_TYPE,
+                                ))
+                                .instance_count(1)
+                                .zone_awareness_enabled(false)
+                                .build(),
+                        )
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+        });
+        let update = mock!(OpenSearchClient::update_domain_config)
+            .match_requests(|req| {
+                let cluster = req.cluster_config().unwrap();
+                req.domain_name() == Some("search")
+                    && cluster.instance_type().map(|t| t.as_str()) == Some("r6g.large.search")
+                    && cluster.instance_count() == Some(3)
+                    && cluster.zone_awareness_enabled() == Some(true)
+                    && cluster
+                        .zone_awareness_config()
+                        .and_then(|z| z.availability_zone_count())
+                        == Some(3)
+            })
+            .then_output(|| UpdateDomainConfigOutput::builder().build());
+        let scheduler = OpenSearchScheduler {
+            opensearch: mock_client!(aws_sdk_opensearch, RuleMode::MatchAny, [&describe, &update]),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&domains]),
+            state: Arc::new(MemoryStore::with(
+                DOMAIN_ARN,
+                &[
+                    (PREVIOUS_INSTANCE_TYPE, "r6g.large.search"),
+                    (PREVIOUS_INSTANCE_COUNT, "3"),
+                    (PREVIOUS_AZ_COUNT, "3"),
+                ],
+            )),
+            stop_instance_type: STOP_INSTANCE_TYPE.to_string(),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+}
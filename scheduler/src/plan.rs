@@ -0,0 +1,101 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ScheduleAction;
+use crate::inventory::{Inventory, InventoryItem};
+use crate::status::{classify, ObservedState};
+
+/// Reviewable list of the resources a stop or start run would act on, written
+/// by `scheduler plan` and executed as is by `scheduler apply --plan`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plan {
+    /// Planned action (`stop` or `start`).
+    pub action: String,
+    pub created_at: String,
+    /// Resources the action would change, with their state when the plan was made.
+    pub resources: Vec<InventoryItem>,
+}
+
+impl Plan {
+    /// Plan `action` on the resources of an inventory that are not already in the
+    /// state it leads to. Resources in a state the scheduler leaves untouched, or
+    /// in an unknown state, are left out.
+    pub fn new(action: &ScheduleAction, inventory: Inventory) -> Result<Self> {
+        let target = match action {
+            ScheduleAction::Stop => ObservedState::Running,
+            ScheduleAction::Start => ObservedState::Stopped,
+            _ => bail!("Only stop and start can be planned, not '{}'", action),
+        };
+        if !inventory.errors.is_empty() {
+            bail!(
+                "Cannot plan: listing failed for {}",
+                failed_services(&inventory)
+            );
+        }
+
+        Ok(Self {
+            action: action.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            resources: inventory
+                .resources
+                .into_iter()
+                .filter(|item| classify(&item.resource_type, &item.state) == target)
+                .collect(),
+        })
+    }
+
+    /// Check that every planned resource is still matched by the filters and
+    /// still in the state it was in when the plan was made.
+    pub fn verify(&self, inventory: &Inventory) -> Result<()> {
+        if !inventory.errors.is_empty() {
+            bail!(
+                "Cannot verify the plan: listing failed for {}",
+                failed_services(inventory)
+            );
+        }
+
+        let changed: Vec<String> = self
+            .resources
+            .iter()
+            .filter_map(|planned| {
+                let current = inventory
+                    .resources
+                    .iter()
+                    .find(|item| item.arn == planned.arn && item.region == planned.region);
+                match current {
+                    Some(item) if item.state == planned.state => None,
+                    Some(item) => Some(format!(
+                        "{} ({} -> {})",
+                        planned.arn, planned.state, item.state
+                    )),
+                    None => Some(format!("{} (no longer matched)", planned.arn)),
+                }
+            })
+            .collect();
+        if !changed.is_empty() {
+            bail!(
+                "{} planned resource(s) changed since the plan was made: {}",
+                changed.len(),
+                changed.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// ARNs of the planned resources.
+    pub fn arns(&self) -> HashSet<String> {
+        self.resources.iter().map(|item| item.arn.clone()).collect()
+    }
+}
+
+/// Services that could not be listed, as `region/service`.
+fn failed_services(inventory: &Inventory) -> String {
+    let names: Vec<String> = inventory
+        .errors
+        .iter()
+        .map(|e| format!("{}/{}", e.region, e.service))
+        .collect();
+    names.join(", ")
+}
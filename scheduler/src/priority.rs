@@ -0,0 +1,100 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use aws_sdk_resourcegroupstagging::types::TagFilter;
+use aws_sdk_resourcegroupstagging::Client;
+use tracing::warn;
+
+use crate::config::ScheduleAction;
+use crate::filter_resources_by_tags::ResourceFilter;
+
+/// Priority of a resource, read from its `PRIORITY_TAG` (e.g. `scheduler:priority=1`).
+///
+/// Resources are started in waves of ascending priority, those without a priority
+/// last, and stopped in the reverse order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Value(i64),
+    Unset,
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Priority::Value(value) => write!(f, "{}", value),
+            Priority::Unset => write!(f, "none"),
+        }
+    }
+}
+
+impl Priority {
+    /// Priority carried by the `key` tag of a resource. A resource without the tag,
+    /// or with a value that is not an integer, has no priority.
+    pub fn of(key: &str, tags: &[(&str, &str)]) -> Self {
+        tags.iter()
+            .find(|(k, _)| *k == key)
+            .and_then(|(_, value)| value.trim().parse::<i64>().ok())
+            .map_or(Priority::Unset, Priority::Value)
+    }
+}
+
+/// Priority waves of the resources matching the scheduler tag in a region, in the
+/// order of `action`: ascending on start, descending on stop. The resources without
+/// a priority always make up a wave of their own.
+///
+/// The priorities are collected from every resource type carrying both the scheduler
+/// tag and the `priority_tag`, in a single pass of the Resource Groups Tagging API.
+pub async fn waves(
+    client: &Client,
+    filter: &ResourceFilter,
+    priority_tag: &str,
+) -> Result<Vec<Priority>> {
+    let mut waves = BTreeSet::from([Priority::Unset]);
+
+    let tag_filters = [
+        TagFilter::builder()
+            .key(&filter.tag_key)
+            .set_values((!filter.tag_values.is_empty()).then(|| filter.tag_values.clone()))
+            .build(),
+        TagFilter::builder().key(priority_tag).build(),
+    ];
+    let mut pagination_token: Option<String> = None;
+    loop {
+        let mut request = client
+            .get_resources()
+            .set_tag_filters(Some(tag_filters.to_vec()));
+        if let Some(ref token) = pagination_token {
+            request = request.pagination_token(token);
+        }
+        let response = request
+            .send()
+            .await
+            .context("Failed to look up resource priorities")?;
+
+        for mapping in response.resource_tag_mapping_list() {
+            let tags: Vec<(&str, &str)> = mapping
+                .tags()
+                .iter()
+                .map(|t| (t.key(), t.value()))
+                .collect();
+            let priority = Priority::of(priority_tag, &tags);
+            if priority == Priority::Unset {
+                warn!(
+                    resource = mapping.resource_arn().unwrap_or_default(),
+                    "Invalid priority tag, processing resource last"
+                );
+            }
+            waves.insert(priority);
+        }
+
+        match response.pagination_token() {
+            Some(token) if !token.is_empty() => pagination_token = Some(token.to_string()),
+            _ => break,
+        }
+    }
+
+    Ok(match filter.action {
+        ScheduleAction::Stop => waves.into_iter().rev().collect(),
+        _ => waves.into_iter().collect(),
+    })
+}
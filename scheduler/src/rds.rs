@@ -1,128 +1,930 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_rds::types::{DbCluster, DbInstance, ServerlessV2ScalingConfiguration, Tag};
 use aws_sdk_rds::Client as RdsClient;
 use aws_sdk_resourcegroupstagging::Client as TaggingClient;
-use tracing::{error, info};
+use tracing::{debug, error, info};
 
-use crate::filter_resources_by_tags;
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::config::ServerlessV2Mode;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+use crate::waiters::{self, Waiter};
+
+/// Tags used to remember the Serverless v2 capacity of a cluster before it was scaled down.
+const PREVIOUS_MIN_ACU_TAG: &str = "scheduler:previous-min-acu";
+const PREVIOUS_MAX_ACU_TAG: &str = "scheduler:previous-max-acu";
+
+/// Serverless v2 capacity applied on stop in `scale-down` mode.
+const SCALED_DOWN_MIN_ACU: f64 = 0.5;
+const SCALED_DOWN_MAX_ACU: f64 = 1.0;
 
 /// Stop/start handler for RDS instances and Aurora clusters in a given AWS region.
 ///
 /// Uses the Resource Groups Tagging API to discover RDS clusters (`rds:cluster`)
 /// and RDS instances (`rds:db`) matching a tag, then performs the requested action.
+/// Clusters running another engine (DocumentDB, Neptune) are ignored.
+/// In `scale-down` mode, Aurora Serverless v2 clusters are not stopped: their min/max
+/// ACU are recorded in tags and lowered to 0.5/1 on stop, then restored on start.
+/// With a start waiter, the started clusters and instances are waited for until they are available.
 pub struct RdsScheduler {
     rds: RdsClient,
     tagging: TaggingClient,
+    serverless_v2_mode: ServerlessV2Mode,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+    start_waiter: Option<Waiter>,
 }
 
 impl RdsScheduler {
-    pub async fn new(region: &str) -> Self {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
+    pub fn new(
+        config: &SdkConfig,
+        serverless_v2_mode: ServerlessV2Mode,
+        dry_run: bool,
+        max_concurrent_operations: usize,
+    ) -> Self {
+        Self::from_clients(
+            RdsClient::new(config),
+            TaggingClient::new(config),
+            serverless_v2_mode,
+            dry_run,
+            max_concurrent_operations,
+        )
+    }
 
+    /// Create a new RDS scheduler from existing clients, e.g. clients with mocked responses.
+    pub fn from_clients(
+        rds: RdsClient,
+        tagging: TaggingClient,
+        serverless_v2_mode: ServerlessV2Mode,
+        dry_run: bool,
+        max_concurrent_operations: usize,
+    ) -> Self {
         Self {
-            rds: RdsClient::new(&config),
-            tagging: TaggingClient::new(&config),
+            rds,
+            tagging,
+            serverless_v2_mode,
+            dry_run,
+            max_concurrent_operations,
+            start_waiter: None,
         }
     }
 
-    pub async fn stop(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let cluster_arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "rds:cluster", tag_key, tag_value).await?;
-        let instance_arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "rds:db", tag_key, tag_value).await?;
-
-        info!(clusters = cluster_arns.len(), instances = instance_arns.len(), "Found RDS resources to stop");
+    /// Wait for the started clusters and instances to be available, recording
+    /// those that are still not available once `waiter` gives up as failed.
+    pub fn with_start_waiter(mut self, waiter: Waiter) -> Self {
+        self.start_waiter = Some(waiter);
+        self
+    }
 
-        for arn in &cluster_arns {
+    /// Keep only the Aurora clusters among the `rds:cluster` resources.
+    ///
+    /// The Tagging API reports DocumentDB and Neptune clusters under the same
+    /// resource type, so the engine of each cluster is checked.
+    async fn aurora_cluster_ids(&self, arns: &[String]) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for arn in arns {
             let cluster_id = extract_rds_id(arn);
-            if let Err(e) = self.stop_cluster(&cluster_id).await {
-                error!(cluster = %cluster_id, error = %e, "Failed to stop RDS cluster");
+            let cluster = self.describe_cluster(&cluster_id).await?;
+            match cluster.engine() {
+                Some(engine) if engine.starts_with("aurora") => ids.push(cluster_id),
+                engine => {
+                    debug!(cluster = %cluster_id, engine = ?engine, "Not an Aurora cluster, ignoring")
+                }
             }
         }
+        Ok(ids)
+    }
 
-        for arn in &instance_arns {
-            let db_id = extract_rds_id(arn);
-            if let Err(e) = self.stop_instance(&db_id).await {
-                error!(instance = %db_id, error = %e, "Failed to stop RDS instance");
-            }
-        }
+    /// Describe a single RDS cluster, including its tags.
+    async fn describe_cluster(&self, cluster_id: &str) -> Result<DbCluster> {
+        let resp = self
+            .rds
+            .describe_db_clusters()
+            .db_cluster_identifier(cluster_id)
+            .send()
+            .await?;
 
-        Ok(())
+        resp.db_clusters()
+            .first()
+            .cloned()
+            .with_context(|| format!("RDS cluster '{}' not found", cluster_id))
     }
 
-    pub async fn start(&self, tag_key: &str, tag_value: &str) -> Result<()> {
-        let cluster_arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "rds:cluster", tag_key, tag_value).await?;
-        let instance_arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "rds:db", tag_key, tag_value).await?;
+    /// Describe a single RDS instance.
+    async fn describe_instance(&self, db_id: &str) -> Result<DbInstance> {
+        let resp = self
+            .rds
+            .describe_db_instances()
+            .db_instance_identifier(db_id)
+            .send()
+            .await?;
 
-        info!(clusters = cluster_arns.len(), instances = instance_arns.len(), "Found RDS resources to start");
+        resp.db_instances()
+            .first()
+            .cloned()
+            .with_context(|| format!("RDS instance '{}' not found", db_id))
+    }
 
-        for arn in &cluster_arns {
-            let cluster_id = extract_rds_id(arn);
-            if let Err(e) = self.start_cluster(&cluster_id).await {
-                error!(cluster = %cluster_id, error = %e, "Failed to start RDS cluster");
+    async fn stop_cluster(&self, cluster_id: &str) -> Result<Outcome> {
+        let cluster = self.describe_cluster(cluster_id).await?;
+        if self.serverless_v2_mode == ServerlessV2Mode::ScaleDown {
+            if let Some(scaling) = cluster.serverless_v2_scaling_configuration() {
+                let (Some(min_acu), Some(max_acu)) =
+                    (scaling.min_capacity(), scaling.max_capacity())
+                else {
+                    bail!(
+                        "RDS cluster '{}' has an incomplete Serverless v2 scaling configuration",
+                        cluster_id
+                    );
+                };
+                return self
+                    .scale_down_cluster(&cluster, cluster_id, min_acu, max_acu)
+                    .await;
             }
         }
 
-        for arn in &instance_arns {
-            let db_id = extract_rds_id(arn);
-            if let Err(e) = self.start_instance(&db_id).await {
-                error!(instance = %db_id, error = %e, "Failed to start RDS instance");
-            }
+        let status = cluster.status().unwrap_or_default();
+        if matches!(status, "stopped" | "stopping") {
+            info!(cluster = %cluster_id, status = %status, "RDS cluster already stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(cluster = %cluster_id, "Dry run: would stop RDS cluster");
+            return Ok(Outcome::Applied);
         }
-
-        Ok(())
-    }
-
-    async fn stop_cluster(&self, cluster_id: &str) -> Result<()> {
         info!(cluster = %cluster_id, "Stopping RDS cluster");
         self.rds
             .stop_db_cluster()
             .db_cluster_identifier(cluster_id)
             .send()
             .await?;
-        Ok(())
+        Ok(Outcome::Applied)
     }
 
-    async fn start_cluster(&self, cluster_id: &str) -> Result<()> {
+    async fn start_cluster(&self, cluster_id: &str) -> Result<Outcome> {
+        let cluster = self.describe_cluster(cluster_id).await?;
+        if self.serverless_v2_mode == ServerlessV2Mode::ScaleDown {
+            if let Some((min_acu, max_acu)) = recorded_capacity(&cluster) {
+                return self
+                    .restore_cluster_capacity(&cluster, cluster_id, min_acu, max_acu)
+                    .await;
+            }
+        }
+
+        let status = cluster.status().unwrap_or_default();
+        if matches!(status, "available" | "starting") {
+            info!(cluster = %cluster_id, status = %status, "RDS cluster already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(cluster = %cluster_id, "Dry run: would start RDS cluster");
+            return Ok(Outcome::Applied);
+        }
         info!(cluster = %cluster_id, "Starting RDS cluster");
         self.rds
             .start_db_cluster()
             .db_cluster_identifier(cluster_id)
             .send()
             .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Record the Serverless v2 capacity of a cluster in tags, then lower it to the minimum.
+    /// A cluster already within the scaled-down ACU range is skipped: its
+    /// previous-ACU tags still hold the range that the next start restores.
+    async fn scale_down_cluster(
+        &self,
+        cluster: &DbCluster,
+        cluster_id: &str,
+        min_acu: f64,
+        max_acu: f64,
+    ) -> Result<Outcome> {
+        if min_acu <= SCALED_DOWN_MIN_ACU && max_acu <= SCALED_DOWN_MAX_ACU {
+            info!(cluster = %cluster_id, min_acu, max_acu, "Aurora Serverless v2 cluster already scaled down, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(cluster = %cluster_id, min_acu, max_acu, "Dry run: would scale down Aurora Serverless v2 cluster");
+            return Ok(Outcome::Applied);
+        }
+
+        let arn = cluster
+            .db_cluster_arn()
+            .with_context(|| format!("RDS cluster '{}' has no ARN", cluster_id))?;
+        info!(cluster = %cluster_id, min_acu, max_acu, "Recording previous Serverless v2 capacity");
+        self.rds
+            .add_tags_to_resource()
+            .resource_name(arn)
+            .tags(
+                Tag::builder()
+                    .key(PREVIOUS_MIN_ACU_TAG)
+                    .value(min_acu.to_string())
+                    .build(),
+            )
+            .tags(
+                Tag::builder()
+                    .key(PREVIOUS_MAX_ACU_TAG)
+                    .value(max_acu.to_string())
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        info!(cluster = %cluster_id, "Scaling down Aurora Serverless v2 cluster");
+        self.update_cluster_capacity(cluster_id, SCALED_DOWN_MIN_ACU, SCALED_DOWN_MAX_ACU)
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the Serverless v2 capacity recorded on stop.
+    async fn restore_cluster_capacity(
+        &self,
+        cluster: &DbCluster,
+        cluster_id: &str,
+        min_acu: f64,
+        max_acu: f64,
+    ) -> Result<Outcome> {
+        let current = cluster.serverless_v2_scaling_configuration();
+        if current.and_then(|c| c.min_capacity()) == Some(min_acu)
+            && current.and_then(|c| c.max_capacity()) == Some(max_acu)
+        {
+            info!(cluster = %cluster_id, min_acu, max_acu, "Aurora Serverless v2 capacity already restored, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(cluster = %cluster_id, min_acu, max_acu, "Dry run: would restore Aurora Serverless v2 capacity");
+            return Ok(Outcome::Applied);
+        }
+        info!(cluster = %cluster_id, min_acu, max_acu, "Restoring Aurora Serverless v2 capacity");
+        self.update_cluster_capacity(cluster_id, min_acu, max_acu)
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn update_cluster_capacity(
+        &self,
+        cluster_id: &str,
+        min_acu: f64,
+        max_acu: f64,
+    ) -> Result<()> {
+        self.rds
+            .modify_db_cluster()
+            .db_cluster_identifier(cluster_id)
+            .serverless_v2_scaling_configuration(
+                ServerlessV2ScalingConfiguration::builder()
+                    .min_capacity(min_acu)
+                    .max_capacity(max_acu)
+                    .build(),
+            )
+            .apply_immediately(true)
+            .send()
+            .await?;
         Ok(())
     }
 
-    async fn stop_instance(&self, db_id: &str) -> Result<()> {
+    async fn stop_instance(&self, db_id: &str) -> Result<Outcome> {
+        let instance = self.describe_instance(db_id).await?;
+        if !is_standalone(&instance, db_id) {
+            return Ok(Outcome::Skipped);
+        }
+
+        let status = instance.db_instance_status().unwrap_or_default();
+        if matches!(status, "stopped" | "stopping") {
+            info!(instance = %db_id, status = %status, "RDS instance already stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(instance = %db_id, "Dry run: would stop RDS instance");
+            return Ok(Outcome::Applied);
+        }
         info!(instance = %db_id, "Stopping RDS instance");
         self.rds
             .stop_db_instance()
             .db_instance_identifier(db_id)
             .send()
             .await?;
-        Ok(())
+        Ok(Outcome::Applied)
     }
 
-    async fn start_instance(&self, db_id: &str) -> Result<()> {
+    async fn start_instance(&self, db_id: &str) -> Result<Outcome> {
+        let instance = self.describe_instance(db_id).await?;
+        if !is_standalone(&instance, db_id) {
+            return Ok(Outcome::Skipped);
+        }
+
+        let status = instance.db_instance_status().unwrap_or_default();
+        if matches!(status, "available" | "starting") {
+            info!(instance = %db_id, status = %status, "RDS instance already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(instance = %db_id, "Dry run: would start RDS instance");
+            return Ok(Outcome::Applied);
+        }
         info!(instance = %db_id, "Starting RDS instance");
         self.rds
             .start_db_instance()
             .db_instance_identifier(db_id)
             .send()
             .await?;
-        Ok(())
+        Ok(Outcome::Applied)
+    }
+
+    /// Wait for the started clusters and instances to be available. Those still not
+    /// available once the waiter gives up are recorded as failed; a failure to poll them is only logged.
+    async fn wait_available(
+        &self,
+        cluster_ids: &[String],
+        db_ids: &[String],
+        waiter: Waiter,
+        counts: &mut ResourceCounts,
+    ) {
+        match waiters::wait_rds_available(&self.rds, cluster_ids, db_ids, waiter).await {
+            Ok(pending) => {
+                for id in pending {
+                    error!(resource = %id, "Timed out waiting for RDS resource to be available");
+                    counts.not_converged(
+                        &id,
+                        format!(
+                            "not available after {} attempts (WAITER_MAX_ATTEMPTS)",
+                            waiter.max_attempts
+                        ),
+                    );
+                }
+            }
+            Err(e) => error!(error = %e, "Error while waiting for RDS resources to be available"),
+        }
     }
 }
 
+#[async_trait]
+impl ResourceScheduler for RdsScheduler {
+    fn name(&self) -> &'static str {
+        "rds"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let cluster_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "rds:cluster", filter).await?;
+        let cluster_ids = self.aurora_cluster_ids(&cluster_arns).await?;
+        let instance_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "rds:db", filter).await?;
+
+        info!(
+            clusters = cluster_ids.len(),
+            instances = instance_arns.len(),
+            "Found RDS resources to stop"
+        );
+
+        filter.check_discovered(cluster_ids.len() + instance_arns.len())?;
+        let mut counts = ResourceCounts::new(cluster_ids.len() + instance_arns.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &cluster_ids,
+            |cluster_id| async move {
+                let result = self.stop_cluster(cluster_id).await;
+                (cluster_id, result)
+            },
+        )
+        .await;
+        for (cluster_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(cluster_id, outcome),
+                Err(e) => {
+                    counts.fail(cluster_id, &e);
+                    error!(cluster = %cluster_id, error = %e, "Failed to stop RDS cluster");
+                }
+            }
+        }
+
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &instance_arns,
+            |arn| async move {
+                let db_id = extract_rds_id(arn);
+                let result = self.stop_instance(&db_id).await;
+                (db_id, result)
+            },
+        )
+        .await;
+        for (db_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&db_id, outcome),
+                Err(e) => {
+                    counts.fail(&db_id, &e);
+                    error!(instance = %db_id, error = %e, "Failed to stop RDS instance");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let cluster_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "rds:cluster", filter).await?;
+        let cluster_ids = self.aurora_cluster_ids(&cluster_arns).await?;
+        let instance_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "rds:db", filter).await?;
+
+        info!(
+            clusters = cluster_ids.len(),
+            instances = instance_arns.len(),
+            "Found RDS resources to start"
+        );
+
+        filter.check_discovered(cluster_ids.len() + instance_arns.len())?;
+        let mut counts = ResourceCounts::new(cluster_ids.len() + instance_arns.len());
+        let mut started_clusters: Vec<String> = Vec::new();
+        let mut started_instances: Vec<String> = Vec::new();
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &cluster_ids,
+            |cluster_id| async move {
+                let result = self.start_cluster(cluster_id).await;
+                (cluster_id, result)
+            },
+        )
+        .await;
+        for (cluster_id, result) in results {
+            match result {
+                Ok(outcome) => {
+                    counts.record(cluster_id, outcome);
+                    if outcome == Outcome::Applied {
+                        started_clusters.push(cluster_id.clone());
+                    }
+                }
+                Err(e) => {
+                    counts.fail(cluster_id, &e);
+                    error!(cluster = %cluster_id, error = %e, "Failed to start RDS cluster");
+                }
+            }
+        }
+
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &instance_arns,
+            |arn| async move {
+                let db_id = extract_rds_id(arn);
+                let result = self.start_instance(&db_id).await;
+                (db_id, result)
+            },
+        )
+        .await;
+        for (db_id, result) in results {
+            match result {
+                Ok(outcome) => {
+                    counts.record(&db_id, outcome);
+                    if outcome == Outcome::Applied {
+                        started_instances.push(db_id);
+                    }
+                }
+                Err(e) => {
+                    counts.fail(&db_id, &e);
+                    error!(instance = %db_id, error = %e, "Failed to start RDS instance");
+                }
+            }
+        }
+
+        if let Some(waiter) = self.start_waiter.filter(|_| !self.dry_run) {
+            if !started_clusters.is_empty() || !started_instances.is_empty() {
+                self.wait_available(&started_clusters, &started_instances, waiter, &mut counts)
+                    .await;
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the Aurora clusters and RDS instances matching the given filter with their current
+    /// status. In `scale-down` mode, Serverless v2 clusters with a recorded capacity are
+    /// reported as `scaled-down`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let cluster_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "rds:cluster", filter).await?;
+        let instance_arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "rds:db", filter).await?;
+
+        let mut items = Vec::with_capacity(cluster_arns.len() + instance_arns.len());
+        for arn in cluster_arns {
+            let cluster = self.describe_cluster(&extract_rds_id(&arn)).await?;
+            if !cluster.engine().is_some_and(|e| e.starts_with("aurora")) {
+                continue;
+            }
+            let state = if self.serverless_v2_mode == ServerlessV2Mode::ScaleDown
+                && recorded_capacity(&cluster).is_some()
+            {
+                "scaled-down"
+            } else {
+                cluster.status().unwrap_or("unknown")
+            };
+            items.push(InventoryItem::new(arn.as_str(), "rds:cluster", state));
+        }
+        for arn in instance_arns {
+            let instance = self.describe_instance(&extract_rds_id(&arn)).await?;
+            let status = instance
+                .db_instance_status()
+                .unwrap_or("unknown")
+                .to_string();
+            items.push(InventoryItem::new(arn, "rds:db", status));
+        }
+        Ok(items)
+    }
+}
+
+/// Whether an RDS instance can be stopped/started on its own.
+///
+/// Aurora cluster members are handled at the cluster level and read replicas
+/// cannot be stopped, so both are skipped with an informative log line.
+fn is_standalone(instance: &DbInstance, db_id: &str) -> bool {
+    if let Some(cluster) = instance.db_cluster_identifier() {
+        info!(instance = %db_id, cluster = %cluster, "RDS instance is a cluster member (managed with its cluster), skipping");
+        return false;
+    }
+    let source = instance
+        .read_replica_source_db_instance_identifier()
+        .or(instance.read_replica_source_db_cluster_identifier());
+    if let Some(source) = source {
+        info!(instance = %db_id, source = %source, "RDS instance is a read replica, skipping");
+        return false;
+    }
+    true
+}
+
+/// Read the Serverless v2 capacity recorded in the tags of a cluster.
+///
+/// Returns `None` unless both the min and max ACU tags hold a number.
+fn recorded_capacity(cluster: &DbCluster) -> Option<(f64, f64)> {
+    let read = |key: &str| {
+        cluster
+            .tag_list()
+            .iter()
+            .find(|t| t.key() == Some(key))
+            .and_then(|t| t.value())
+            .and_then(|v| v.parse::<f64>().ok())
+    };
+    Some((read(PREVIOUS_MIN_ACU_TAG)?, read(PREVIOUS_MAX_ACU_TAG)?))
+}
+
 /// Extract the resource identifier from an RDS ARN.
 ///
 /// Expected ARN formats:
-/// - Cluster: `arn:aws:rds:region:account:cluster:cluster-id`
-/// - Instance: `arn:aws:rds:region:account:db:instance-id`
+/// - Cluster: `arn:partition:rds:region:account:cluster:cluster-id`
+/// - Instance: `arn:partition:rds:region:account:db:instance-id`
 fn extract_rds_id(arn: &str) -> String {
-    arn.split(':').last().unwrap_or(arn).to_string()
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_rds::error::ErrorMetadata;
+    use aws_sdk_rds::operation::add_tags_to_resource::AddTagsToResourceOutput;
+    use aws_sdk_rds::operation::describe_db_clusters::DescribeDbClustersOutput;
+    use aws_sdk_rds::operation::describe_db_instances::DescribeDbInstancesOutput;
+    use aws_sdk_rds::operation::modify_db_cluster::ModifyDbClusterOutput;
+    use aws_sdk_rds::operation::start_db_instance::{StartDBInstanceError, StartDbInstanceOutput};
+    use aws_sdk_rds::operation::stop_db_cluster::StopDbClusterOutput;
+    use aws_sdk_rds::operation::stop_db_instance::StopDbInstanceOutput;
+    use aws_sdk_rds::types::builders::DbInstanceBuilder;
+    use aws_sdk_rds::types::ServerlessV2ScalingConfigurationInfo;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const CLUSTER_ARN_PREFIX: &str = "arn:aws:rds:eu-west-1:123456789012:cluster:";
+    const DB_ARN_PREFIX: &str = "arn:aws:rds:eu-west-1:123456789012:db:";
+
+    /// Rule describing `db_id` with the given status, built by `customize`.
+    fn describe_instance_rule(
+        db_id: &'static str,
+        status: &'static str,
+        customize: fn(DbInstanceBuilder) -> DbInstanceBuilder,
+    ) -> Rule {
+        mock!(RdsClient::describe_db_instances)
+            .match_requests(move |req| req.db_instance_identifier() == Some(db_id))
+            .then_output(move || {
+                let instance = DbInstance::builder()
+                    .db_instance_identifier(db_id)
+                    .db_instance_status(status);
+                DescribeDbInstancesOutput::builder()
+                    .db_instances(customize(instance).build())
+                    .build()
+            })
+    }
+
+    /// Rule describing `cluster_id`; clusters are described once when filtering
+    /// Aurora clusters and once when processing them.
+    fn describe_cluster_rules(cluster_id: &'static str, cluster: fn() -> DbCluster) -> [Rule; 2] {
+        [(), ()].map(|_| {
+            mock!(RdsClient::describe_db_clusters)
+                .match_requests(move |req| req.db_cluster_identifier() == Some(cluster_id))
+                .then_output(move || {
+                    DescribeDbClustersOutput::builder()
+                        .db_clusters(cluster())
+                        .build()
+                })
+        })
+    }
+
+    #[tokio::test]
+    async fn stop_skips_cluster_members_and_read_replicas() {
+        let clusters = get_resources_rule("rds:cluster", &[]);
+        let instances = get_resources_rule(
+            "rds:db",
+            &[
+                &format!("{DB_ARN_PREFIX}member"),
+                &format!("{DB_ARN_PREFIX}replica"),
+                &format!("{DB_ARN_PREFIX}standalone"),
+            ],
+        );
+        let member = describe_instance_rule("member", "available", |db| {
+            db.db_cluster_identifier("aurora")
+        });
+        let replica = describe_instance_rule("replica", "available", |db| {
+            db.read_replica_source_db_instance_identifier("primary")
+        });
+        let standalone = describe_instance_rule("standalone", "available", |db| db);
+        let stop = mock!(RdsClient::stop_db_instance)
+            .match_requests(|req| req.db_instance_identifier() == Some("standalone"))
+            .then_output(|| StopDbInstanceOutput::builder().build());
+        let scheduler = RdsScheduler::from_clients(
+            mock_client!(
+                aws_sdk_rds,
+                RuleMode::MatchAny,
+                [&member, &replica, &standalone, &stop]
+            ),
+            mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&clusters, &instances]
+            ),
+            ServerlessV2Mode::Stop,
+            false,
+            10,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (3, 1, 2, 0)
+        );
+        assert_eq!(stop.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_ignores_non_aurora_clusters() {
+        let clusters = get_resources_rule(
+            "rds:cluster",
+            &[
+                &format!("{CLUSTER_ARN_PREFIX}docdb"),
+                &format!("{CLUSTER_ARN_PREFIX}aurora"),
+            ],
+        );
+        let instances = get_resources_rule("rds:db", &[]);
+        let docdb = describe_cluster_rules("docdb", || {
+            DbCluster::builder()
+                .db_cluster_identifier("docdb")
+                .engine("docdb")
+                .status("available")
+                .build()
+        });
+        let aurora = describe_cluster_rules("aurora", || {
+            DbCluster::builder()
+                .db_cluster_identifier("aurora")
+                .engine("aurora-postgresql")
+                .status("available")
+                .build()
+        });
+        let stop = mock!(RdsClient::stop_db_cluster)
+            .match_requests(|req| req.db_cluster_identifier() == Some("aurora"))
+            .then_output(|| StopDbClusterOutput::builder().build());
+        let scheduler = RdsScheduler::from_clients(
+            mock_client!(
+                aws_sdk_rds,
+                RuleMode::MatchAny,
+                [&docdb[0], &docdb[1], &aurora[0], &aurora[1], &stop]
+            ),
+            mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&clusters, &instances]
+            ),
+            ServerlessV2Mode::Stop,
+            false,
+            10,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(stop.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_scales_down_serverless_v2_clusters() {
+        let clusters =
+            get_resources_rule("rds:cluster", &[&format!("{CLUSTER_ARN_PREFIX}serverless")]);
+        let instances = get_resources_rule("rds:db", &[]);
+        let describe = describe_cluster_rules("serverless", || {
+            DbCluster::builder()
+                .db_cluster_identifier("serverless")
+                .db_cluster_arn(format!("{CLUSTER_ARN_PREFIX}serverless"))
+                .engine("aurora-mysql")
+                .status("available")
+                .serverless_v2_scaling_configuration(
+                    ServerlessV2ScalingConfigurationInfo::builder()
+                        .min_capacity(2.0)
+                        .max_capacity(8.0)
+                        .build(),
+                )
+                .build()
+        });
+        let record = mock!(RdsClient::add_tags_to_resource)
+            .match_requests(|req| {
+                let tag = |key: &str| {
+                    req.tags()
+                        .iter()
+                        .find(|t| t.key() == Some(key))
+                        .and_then(|t| t.value())
+                };
+                tag(PREVIOUS_MIN_ACU_TAG) == Some("2") && tag(PREVIOUS_MAX_ACU_TAG) == Some("8")
+            })
+            .then_output(|| AddTagsToResourceOutput::builder().build());
+        let scale_down = mock!(RdsClient::modify_db_cluster)
+            .match_requests(|req| {
+                let scaling = req.serverless_v2_scaling_configuration();
+                scaling.and_then(|s| s.min_capacity()) == Some(SCALED_DOWN_MIN_ACU)
+                    && scaling.and_then(|s| s.max_capacity()) == Some(SCALED_DOWN_MAX_ACU)
+            })
+            .then_output(|| ModifyDbClusterOutput::builder().build());
+        let scheduler = RdsScheduler::from_clients(
+            mock_client!(
+                aws_sdk_rds,
+                RuleMode::MatchAny,
+                [&describe[0], &describe[1], &record, &scale_down]
+            ),
+            mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&clusters, &instances]
+            ),
+            ServerlessV2Mode::ScaleDown,
+            false,
+            10,
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(counts.succeeded, 1);
+        assert_eq!(record.num_calls(), 1);
+        assert_eq!(scale_down.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_restores_the_recorded_serverless_v2_capacity() {
+        let clusters =
+            get_resources_rule("rds:cluster", &[&format!("{CLUSTER_ARN_PREFIX}serverless")]);
+        let instances = get_resources_rule("rds:db", &[]);
+        let describe = describe_cluster_rules("serverless", || {
+            DbCluster::builder()
+                .db_cluster_identifier("serverless")
+                .db_cluster_arn(format!("{CLUSTER_ARN_PREFIX}serverless"))
+                .engine("aurora-postgresql")
+                .status("available")
+                .serverless_v2_scaling_configuration(
+                    ServerlessV2ScalingConfigurationInfo::builder()
+                        .min_capacity(SCALED_DOWN_MIN_ACU)
+                        .max_capacity(SCALED_DOWN_MAX_ACU)
+                        .build(),
+                )
+                .tag_list(Tag::builder().key(PREVIOUS_MIN_ACU_TAG).value("2").build())
+                .tag_list(Tag::builder().key(PREVIOUS_MAX_ACU_TAG).value("8").build())
+                .build()
+        });
+        let restore = mock!(RdsClient::modify_db_cluster)
+            .match_requests(|req| {
+                let scaling = req.serverless_v2_scaling_configuration();
+                scaling.and_then(|s| s.min_capacity()) == Some(2.0)
+                    && scaling.and_then(|s| s.max_capacity()) == Some(8.0)
+            })
+            .then_output(|| ModifyDbClusterOutput::builder().build());
+        let scheduler = RdsScheduler::from_clients(
+            mock_client!(
+                aws_sdk_rds,
+                RuleMode::MatchAny,
+                [&describe[0], &describe[1], &restore]
+            ),
+            mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&clusters, &instances]
+            ),
+            ServerlessV2Mode::ScaleDown,
+            false,
+            10,
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!((counts.succeeded, counts.skipped), (1, 0));
+        assert_eq!(restore.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_records_failures_without_interrupting_processing() {
+        let clusters = get_resources_rule("rds:cluster", &[]);
+        let instances = get_resources_rule(
+            "rds:db",
+            &[
+                &format!("{DB_ARN_PREFIX}failing"),
+                &format!("{DB_ARN_PREFIX}running"),
+                &format!("{DB_ARN_PREFIX}stopped"),
+            ],
+        );
+        let failing = describe_instance_rule("failing", "stopped", |db| db);
+        let running = describe_instance_rule("running", "available", |db| db);
+        let stopped = describe_instance_rule("stopped", "stopped", |db| db);
+        let start_failing = mock!(RdsClient::start_db_instance)
+            .match_requests(|req| req.db_instance_identifier() == Some("failing"))
+            .then_error(|| {
+                StartDBInstanceError::generic(
+                    ErrorMetadata::builder()
+                        .code("InsufficientDBInstanceCapacity")
+                        .build(),
+                )
+            });
+        let start_stopped = mock!(RdsClient::start_db_instance)
+            .match_requests(|req| req.db_instance_identifier() == Some("stopped"))
+            .then_output(|| StartDbInstanceOutput::builder().build());
+        let scheduler = RdsScheduler::from_clients(
+            mock_client!(
+                aws_sdk_rds,
+                RuleMode::MatchAny,
+                [&failing, &running, &stopped, &start_failing, &start_stopped]
+            ),
+            mock_client!(
+                aws_sdk_resourcegroupstagging,
+                RuleMode::MatchAny,
+                [&clusters, &instances]
+            ),
+            ServerlessV2Mode::Stop,
+            false,
+            10,
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (3, 1, 1, 1)
+        );
+        assert_eq!(start_stopped.num_calls(), 1);
+    }
+
+    #[test]
+    fn recorded_capacity_requires_both_tags() {
+        let tag = |key: &str, value: &str| Tag::builder().key(key).value(value).build();
+        let recorded = DbCluster::builder()
+            .tag_list(tag(PREVIOUS_MIN_ACU_TAG, "0.5"))
+            .tag_list(tag(PREVIOUS_MAX_ACU_TAG, "16"))
+            .build();
+        let partial = DbCluster::builder()
+            .tag_list(tag(PREVIOUS_MIN_ACU_TAG, "0.5"))
+            .build();
+
+        assert_eq!(recorded_capacity(&recorded), Some((0.5, 16.0)));
+        assert_eq!(recorded_capacity(&partial), None);
+    }
 }
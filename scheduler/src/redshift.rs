@@ -1,9 +1,16 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
 use aws_sdk_redshift::Client as RedshiftClient;
 use aws_sdk_resourcegroupstagging::Client as TaggingClient;
 use tracing::{error, info};
 
-use crate::filter_resources_by_tags;
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
 
 /// Stop/start handler for Redshift clusters in a given AWS region.
 ///
@@ -12,75 +19,270 @@ use crate::filter_resources_by_tags;
 pub struct RedshiftScheduler {
     redshift: RedshiftClient,
     tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
 }
 
 impl RedshiftScheduler {
-    pub async fn new(region: &str) -> Self {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
-
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
         Self {
-            redshift: RedshiftClient::new(&config),
-            tagging: TaggingClient::new(&config),
+            redshift: RedshiftClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
         }
     }
 
-    pub async fn stop(&self, tag_key: &str, tag_value: &str) -> Result<()> {
+    /// Get the current status of a Redshift cluster (e.g. `available`, `paused`).
+    async fn cluster_status(&self, cluster_id: &str) -> Result<String> {
+        let resp = self
+            .redshift
+            .describe_clusters()
+            .cluster_identifier(cluster_id)
+            .send()
+            .await?;
+
+        resp.clusters()
+            .first()
+            .and_then(|c| c.cluster_status())
+            .map(str::to_string)
+            .with_context(|| format!("Redshift cluster '{}' not found", cluster_id))
+    }
+
+    async fn pause_cluster(&self, cluster_id: &str) -> Result<Outcome> {
+        let status = self.cluster_status(cluster_id).await?;
+        if status == "paused" {
+            info!(cluster = %cluster_id, "Redshift cluster already paused, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(cluster = %cluster_id, "Dry run: would pause Redshift cluster");
+            return Ok(Outcome::Applied);
+        }
+        info!(cluster = %cluster_id, "Pausing Redshift cluster");
+        self.redshift
+            .pause_cluster()
+            .cluster_identifier(cluster_id)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn resume_cluster(&self, cluster_id: &str) -> Result<Outcome> {
+        let status = self.cluster_status(cluster_id).await?;
+        if status == "available" {
+            info!(cluster = %cluster_id, "Redshift cluster already available, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(cluster = %cluster_id, "Dry run: would resume Redshift cluster");
+            return Ok(Outcome::Applied);
+        }
+        info!(cluster = %cluster_id, "Resuming Redshift cluster");
+        self.redshift
+            .resume_cluster()
+            .cluster_identifier(cluster_id)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for RedshiftScheduler {
+    fn name(&self) -> &'static str {
+        "redshift"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
         let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "redshift:cluster", tag_key, tag_value).await?;
+            filter_resources_by_tags::get_resources(&self.tagging, "redshift:cluster", filter)
+                .await?;
         info!(count = arns.len(), "Found Redshift clusters to pause");
 
-        for arn in &arns {
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
             let cluster_id = extract_cluster_id(arn);
-            if let Err(e) = self.pause_cluster(&cluster_id).await {
-                error!(cluster = %cluster_id, error = %e, "Failed to pause Redshift cluster");
+            let result = self.pause_cluster(&cluster_id).await;
+            (cluster_id, result)
+        })
+        .await;
+        for (cluster_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&cluster_id, outcome),
+                Err(e) => {
+                    counts.fail(&cluster_id, &e);
+                    error!(cluster = %cluster_id, error = %e, "Failed to pause Redshift cluster");
+                }
             }
         }
 
-        Ok(())
+        Ok(counts)
     }
 
-    pub async fn start(&self, tag_key: &str, tag_value: &str) -> Result<()> {
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
         let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "redshift:cluster", tag_key, tag_value).await?;
+            filter_resources_by_tags::get_resources(&self.tagging, "redshift:cluster", filter)
+                .await?;
         info!(count = arns.len(), "Found Redshift clusters to resume");
 
-        for arn in &arns {
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
             let cluster_id = extract_cluster_id(arn);
-            if let Err(e) = self.resume_cluster(&cluster_id).await {
-                error!(cluster = %cluster_id, error = %e, "Failed to resume Redshift cluster");
+            let result = self.resume_cluster(&cluster_id).await;
+            (cluster_id, result)
+        })
+        .await;
+        for (cluster_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&cluster_id, outcome),
+                Err(e) => {
+                    counts.fail(&cluster_id, &e);
+                    error!(cluster = %cluster_id, error = %e, "Failed to resume Redshift cluster");
+                }
             }
         }
 
-        Ok(())
+        Ok(counts)
     }
 
-    async fn pause_cluster(&self, cluster_id: &str) -> Result<()> {
-        info!(cluster = %cluster_id, "Pausing Redshift cluster");
-        self.redshift
-            .pause_cluster()
-            .cluster_identifier(cluster_id)
-            .send()
-            .await?;
-        Ok(())
-    }
+    /// List the Redshift clusters matching the given tag with their current status.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "redshift:cluster", filter)
+                .await?;
 
-    async fn resume_cluster(&self, cluster_id: &str) -> Result<()> {
-        info!(cluster = %cluster_id, "Resuming Redshift cluster");
-        self.redshift
-            .resume_cluster()
-            .cluster_identifier(cluster_id)
-            .send()
-            .await?;
-        Ok(())
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let status = self.cluster_status(&extract_cluster_id(&arn)).await?;
+            items.push(InventoryItem::new(arn, "redshift:cluster", status));
+        }
+        Ok(items)
     }
 }
 
 /// Extract the cluster identifier from a Redshift cluster ARN.
 ///
-/// Expected ARN format: `arn:aws:redshift:region:account:cluster:cluster-id`
+/// Expected ARN format: `arn:partition:redshift:region:account:cluster:cluster-id`
 fn extract_cluster_id(arn: &str) -> String {
-    arn.split(':').last().unwrap_or(arn).to_string()
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_redshift::error::ErrorMetadata;
+    use aws_sdk_redshift::operation::describe_clusters::DescribeClustersOutput;
+    use aws_sdk_redshift::operation::pause_cluster::PauseClusterOutput;
+    use aws_sdk_redshift::operation::resume_cluster::ResumeClusterError;
+    use aws_sdk_redshift::types::Cluster;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const CLUSTER_ARN_PREFIX: &str = "arn:aws:redshift:eu-west-1:123456789012:cluster:";
+
+    /// Rule describing `cluster_id` with the given status.
+    fn describe_cluster_rule(cluster_id: &'static str, status: &'static str) -> Rule {
+        mock!(RedshiftClient::describe_clusters)
+            .match_requests(move |req| req.cluster_identifier() == Some(cluster_id))
+            .then_output(move || {
+                DescribeClustersOutput::builder()
+                    .clusters(
+                        Cluster::builder()
+                            .cluster_identifier(cluster_id)
+                            .cluster_status(status)
+                            .build(),
+                    )
+                    .build()
+            })
+    }
+
+    fn scheduler(redshift: RedshiftClient, tagging: TaggingClient) -> RedshiftScheduler {
+        RedshiftScheduler {
+            redshift,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_pauses_available_clusters_only() {
+        let arns = get_resources_rule(
+            "redshift:cluster",
+            &[
+                &format!("{CLUSTER_ARN_PREFIX}analytics"),
+                &format!("{CLUSTER_ARN_PREFIX}archive"),
+            ],
+        );
+        let analytics = describe_cluster_rule("analytics", "available");
+        let archive = describe_cluster_rule("archive", "paused");
+        let pause = mock!(RedshiftClient::pause_cluster)
+            .match_requests(|req| req.cluster_identifier() == Some("analytics"))
+            .then_output(|| PauseClusterOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_redshift,
+                RuleMode::MatchAny,
+                [&analytics, &archive, &pause]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(pause.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_reports_clusters_failing_to_resume() {
+        let arns = get_resources_rule(
+            "redshift:cluster",
+            &[&format!("{CLUSTER_ARN_PREFIX}analytics")],
+        );
+        let analytics = describe_cluster_rule("analytics", "paused");
+        let resume = mock!(RedshiftClient::resume_cluster)
+            .match_requests(|req| req.cluster_identifier() == Some("analytics"))
+            .then_error(|| {
+                ResumeClusterError::generic(
+                    ErrorMetadata::builder()
+                        .code("InsufficientClusterCapacity")
+                        .build(),
+                )
+            });
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_redshift, RuleMode::MatchAny, [&analytics, &resume]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 0, 0, 1)
+        );
+        assert_eq!(counts.resources[0].resource, "analytics");
+        assert_eq!(resume.num_calls(), 1);
+    }
 }
@@ -0,0 +1,387 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_redshiftserverless::types::{Tag, Workgroup};
+use aws_sdk_redshiftserverless::Client as RedshiftServerlessClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info, warn};
+
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Tag used to remember the base capacity of a workgroup before it was scaled down.
+const PREVIOUS_BASE_CAPACITY_TAG: &str = "scheduler:previous-base-capacity";
+
+/// Lowest base capacity (in RPUs) accepted by Redshift Serverless.
+const MIN_BASE_CAPACITY: i32 = 8;
+
+/// Stop/start handler for Redshift Serverless workgroups in a given AWS region.
+///
+/// Workgroups cannot be paused, so they are scaled down instead.
+/// On stop: records the base capacity in the `scheduler:previous-base-capacity` tag,
+/// then lowers it to the minimum (8 RPUs).
+/// On start: restores the recorded base capacity.
+pub struct RedshiftServerlessScheduler {
+    redshift: RedshiftServerlessClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl RedshiftServerlessScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            redshift: RedshiftServerlessClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Record the base capacity of a workgroup in a tag, then lower it to the minimum.
+    ///
+    /// Workgroups at 8 RPUs or less are skipped, as the tag would then hold the
+    /// minimum instead of the base capacity to restore.
+    async fn scale_down(&self, arn: &str, workgroups: &[Workgroup]) -> Result<Outcome> {
+        let (name, base_capacity) = find_workgroup(workgroups, arn)?;
+
+        if base_capacity <= MIN_BASE_CAPACITY {
+            info!(workgroup = %name, base_capacity, "Redshift Serverless workgroup already scaled down, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(workgroup = %name, base_capacity, "Dry run: would scale down Redshift Serverless workgroup");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(workgroup = %name, base_capacity, "Recording previous base capacity");
+        self.redshift
+            .tag_resource()
+            .resource_arn(arn)
+            .tags(
+                Tag::builder()
+                    .key(PREVIOUS_BASE_CAPACITY_TAG)
+                    .value(base_capacity.to_string())
+                    .build()?,
+            )
+            .send()
+            .await?;
+
+        info!(workgroup = %name, base_capacity = MIN_BASE_CAPACITY, "Scaling down Redshift Serverless workgroup");
+        self.update_base_capacity(name, MIN_BASE_CAPACITY).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// Restore the base capacity recorded on stop.
+    async fn restore(&self, arn: &str, workgroups: &[Workgroup]) -> Result<Outcome> {
+        let (name, base_capacity) = find_workgroup(workgroups, arn)?;
+
+        let Some(previous) = self.previous_base_capacity(arn).await? else {
+            warn!(workgroup = %name, "No recorded base capacity found, skipping Redshift Serverless workgroup");
+            return Ok(Outcome::Skipped);
+        };
+
+        if base_capacity == previous {
+            info!(workgroup = %name, base_capacity, "Redshift Serverless workgroup already restored, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(workgroup = %name, base_capacity = previous, "Dry run: would restore Redshift Serverless workgroup");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(workgroup = %name, base_capacity = previous, "Restoring Redshift Serverless workgroup");
+        self.update_base_capacity(name, previous).await?;
+        Ok(Outcome::Applied)
+    }
+
+    /// List all workgroups of the region.
+    ///
+    /// Workgroup ARNs hold the workgroup ID, while the API expects its name,
+    /// so ARNs returned by the Tagging API are resolved against this list.
+    async fn list_workgroups(&self) -> Result<Vec<Workgroup>> {
+        let mut workgroups = Vec::new();
+        let mut next_token: Option<String> = None;
+
+        loop {
+            let mut req = self.redshift.list_workgroups();
+            if let Some(ref token) = next_token {
+                req = req.next_token(token);
+            }
+
+            let resp = req.send().await?;
+            workgroups.extend(resp.workgroups().iter().cloned());
+
+            match resp.next_token() {
+                Some(token) if !token.is_empty() => next_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        Ok(workgroups)
+    }
+
+    /// Read the base capacity recorded in the `scheduler:previous-base-capacity` tag.
+    async fn previous_base_capacity(&self, arn: &str) -> Result<Option<i32>> {
+        let resp = self
+            .redshift
+            .list_tags_for_resource()
+            .resource_arn(arn)
+            .send()
+            .await?;
+
+        Ok(resp
+            .tags()
+            .iter()
+            .find(|t| t.key() == PREVIOUS_BASE_CAPACITY_TAG)
+            .and_then(|t| t.value().parse::<i32>().ok()))
+    }
+
+    async fn update_base_capacity(&self, name: &str, base_capacity: i32) -> Result<()> {
+        self.redshift
+            .update_workgroup()
+            .workgroup_name(name)
+            .base_capacity(base_capacity)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for RedshiftServerlessScheduler {
+    fn name(&self) -> &'static str {
+        "redshift_serverless"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "redshift-serverless:workgroup",
+            filter,
+        )
+        .await?;
+        info!(
+            count = arns.len(),
+            "Found Redshift Serverless workgroups to stop"
+        );
+
+        let workgroups = &self.list_workgroups().await?;
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let result = self.scale_down(arn, workgroups).await;
+            (arn, result)
+        })
+        .await;
+        for (arn, result) in results {
+            match result {
+                Ok(outcome) => counts.record(arn, outcome),
+                Err(e) => {
+                    counts.fail(arn, &e);
+                    error!(workgroup = %arn, error = %e, "Failed to stop Redshift Serverless workgroup");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "redshift-serverless:workgroup",
+            filter,
+        )
+        .await?;
+        info!(
+            count = arns.len(),
+            "Found Redshift Serverless workgroups to start"
+        );
+
+        let workgroups = &self.list_workgroups().await?;
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let result = self.restore(arn, workgroups).await;
+            (arn, result)
+        })
+        .await;
+        for (arn, result) in results {
+            match result {
+                Ok(outcome) => counts.record(arn, outcome),
+                Err(e) => {
+                    counts.fail(arn, &e);
+                    error!(workgroup = %arn, error = %e, "Failed to start Redshift Serverless workgroup");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the Redshift Serverless workgroups matching the given tag.
+    ///
+    /// Workgroups at the minimum base capacity are reported as `scaled-down`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "redshift-serverless:workgroup",
+            filter,
+        )
+        .await?;
+
+        let workgroups = self.list_workgroups().await?;
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let (_, base_capacity) = find_workgroup(&workgroups, &arn)?;
+            let state = if base_capacity <= MIN_BASE_CAPACITY {
+                "scaled-down"
+            } else {
+                "running"
+            };
+            items.push(InventoryItem::new(
+                arn,
+                "redshift-serverless:workgroup",
+                state,
+            ));
+        }
+        Ok(items)
+    }
+}
+
+/// Find the name and current base capacity of the workgroup with the given ARN.
+fn find_workgroup<'a>(workgroups: &'a [Workgroup], arn: &str) -> Result<(&'a str, i32)> {
+    let workgroup = workgroups
+        .iter()
+        .find(|w| w.workgroup_arn() == Some(arn))
+        .with_context(|| format!("Redshift Serverless workgroup '{}' not found", arn))?;
+    let name = workgroup
+        .workgroup_name()
+        .with_context(|| format!("Redshift Serverless workgroup '{}' has no name", arn))?;
+    Ok((name, workgroup.base_capacity().unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_redshiftserverless::operation::list_tags_for_resource::ListTagsForResourceOutput;
+    use aws_sdk_redshiftserverless::operation::list_workgroups::ListWorkgroupsOutput;
+    use aws_sdk_redshiftserverless::operation::tag_resource::TagResourceOutput;
+    use aws_sdk_redshiftserverless::operation::update_workgroup::UpdateWorkgroupOutput;
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const WORKGROUP_ARN: &str = "arn:aws:redshift-serverless:eu-west-1:123456789012:workgroup/3f9d2c1a-8b7e-4d6f-a5c4-b3a2918e7d6c";
+
+    #[tokio::test]
+    async fn stop_skips_workgroups_already_at_the_minimum() {
+        let arns = get_resources_rule("redshift-serverless:workgroup", &[WORKGROUP_ARN]);
+        let workgroups = mock!(RedshiftServerlessClient::list_workgroups).then_output(|| {
+            ListWorkgroupsOutput::builder()
+                .workgroups(
+                    Workgroup::builder()
+                        .workgroup_arn(WORKGROUP_ARN)
+                        .workgroup_name("analytics")
+                        .base_capacity(MIN_BASE_CAPACITY)
+                        .build(),
+                )
+                .build()
+                .unwrap()
+        });
+        let record = mock!(RedshiftServerlessClient::tag_resource)
+            .then_output(|| TagResourceOutput::builder().build());
+        let update = mock!(RedshiftServerlessClient::update_workgroup)
+            .then_output(|| UpdateWorkgroupOutput::builder().build());
+        let scheduler = RedshiftServerlessScheduler {
+            redshift: mock_client!(
+                aws_sdk_redshiftserverless,
+                RuleMode::MatchAny,
+                [&workgroups, &record, &update]
+            ),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 0, 1, 0)
+        );
+        assert_eq!(record.num_calls(), 0);
+        assert_eq!(update.num_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn start_restores_the_recorded_base_capacity() {
+        let arns = get_resources_rule("redshift-serverless:workgroup", &[WORKGROUP_ARN]);
+        let workgroups = mock!(RedshiftServerlessClient::list_workgroups).then_output(|| {
+            ListWorkgroupsOutput::builder()
+                .workgroups(
+                    Workgroup::builder()
+                        .workgroup_arn(WORKGROUP_ARN)
+                        .workgroup_name("analytics")
+                        .base_capacity(MIN_BASE_CAPACITY)
+                        .build(),
+                )
+                .build()
+                .unwrap()
+        });
+        let tags = mock!(RedshiftServerlessClient::list_tags_for_resource)
+            .match_requests(|req| req.resource_arn() == Some(WORKGROUP_ARN))
+            .then_output(|| {
+                ListTagsForResourceOutput::builder()
+                    .tags(
+                        Tag::builder()
+                            .key(PREVIOUS_BASE_CAPACITY_TAG)
+                            .value("64")
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+            });
+        let update = mock!(RedshiftServerlessClient::update_workgroup)
+            .match_requests(|req| {
+                req.workgroup_name() == Some("analytics") && req.base_capacity() == Some(64)
+            })
+            .then_output(|| UpdateWorkgroupOutput::builder().build());
+        let scheduler = RedshiftServerlessScheduler {
+            redshift: mock_client!(
+                aws_sdk_redshiftserverless,
+                RuleMode::MatchAny,
+                [&workgroups, &tags, &update]
+            ),
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+            dry_run: false,
+            max_concurrent_operations: 10,
+        };
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+}
@@ -0,0 +1,60 @@
+use anyhow::{bail, Context, Result};
+use aws_config::default_provider::region;
+use aws_sdk_ec2::Client as Ec2Client;
+use tracing::info;
+
+use crate::config::{AppConfig, RegionDiscovery};
+use crate::credentials;
+
+/// Region `DescribeRegions` is called in when the environment does not set one.
+const FALLBACK_REGION: &str = "us-east-1";
+
+/// Enumerate the regions enabled in the account of the ambient credentials.
+///
+/// `DescribeRegions` is called in the region of the environment (`AWS_REGION`,
+/// `AWS_PROFILE`), or in `us-east-1`. That region comes first when it is enabled, so
+/// that the resources looked up in the first region of `AWS_REGIONS` (lock
+/// table, audit store...) stay where the scheduler runs; the others are sorted.
+pub async fn discover(config: &AppConfig, discovery: RegionDiscovery) -> Result<Vec<String>> {
+    let mut provider = region::Builder::default();
+    if let Some(profile) = &config.aws_profile {
+        provider = provider.profile_name(profile);
+    }
+    let home = provider
+        .build()
+        .region()
+        .await
+        .map(|r| r.to_string())
+        .unwrap_or_else(|| FALLBACK_REGION.to_string());
+    let sdk_config = credentials::load_config(config, &home, None).await;
+
+    let resp = Ec2Client::new(&sdk_config)
+        .describe_regions()
+        .all_regions(true)
+        .send()
+        .await
+        .context("Failed to describe EC2 regions")?;
+
+    let mut regions: Vec<String> = resp
+        .regions()
+        .iter()
+        .filter(|r| {
+            matches!(
+                (discovery, r.opt_in_status()),
+                (_, Some("opt-in-not-required")) | (RegionDiscovery::AllOptedIn, Some("opted-in"))
+            )
+        })
+        .filter_map(|r| r.region_name().map(str::to_string))
+        .collect();
+    if regions.is_empty() {
+        bail!("No enabled region found for AWS_REGIONS={}", discovery);
+    }
+    regions.sort();
+    if let Some(i) = regions.iter().position(|r| r == &home) {
+        let home = regions.remove(i);
+        regions.insert(0, home);
+    }
+
+    info!(discovery = %discovery, regions = ?regions, "Discovered AWS regions");
+    Ok(regions)
+}
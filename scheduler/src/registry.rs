@@ -0,0 +1,390 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+
+use crate::config::{AppConfig, ScheduleAction, ServiceOrder};
+use crate::ec2_shutdown::GracefulShutdown;
+use crate::filter_resources_by_tags::ResourceFilter;
+use crate::inventory::InventoryItem;
+use crate::state;
+use crate::summary::ResourceCounts;
+use crate::waiters::Waiter;
+use crate::{
+    apprunner, appstream, autoscaling, clientvpn, cloud9, cloudfront, cloudwatch, codepipeline,
+    comprehend, dlm, dms, documentdb, dynamodb, ec2, ec2fleet, ecs, eks, elasticache,
+    emr_serverless, eventbridge, flink, gamelift, lambda, lightsail, mwaa, natgateway, opensearch,
+    rds, redshift, redshift_serverless, route53, sagemaker, ssm, transfer, vpcendpoints,
+    workspaces,
+};
+
+/// Stop/start handler for the resources of one AWS service.
+///
+/// Every scheduler discovers the resources matching a [`ResourceFilter`] and
+/// records the outcome of each one in [`ResourceCounts`]. Resources are processed
+/// concurrently, up to `MAX_CONCURRENT_OPERATIONS` at a time, and errors on
+/// individual resources are logged without interrupting the processing of the others.
+#[async_trait]
+pub trait ResourceScheduler: Send + Sync {
+    /// Name of the service, as reported in the execution summary (e.g. `ec2`).
+    fn name(&self) -> &'static str;
+
+    /// Stop the resources matching the filter.
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts>;
+
+    /// Start the resources matching the filter.
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts>;
+
+    /// List the resources matching the filter with their current state.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>>;
+}
+
+/// Schedulers of the services enabled in the configuration, for one region (and account).
+#[derive(Default)]
+pub struct Registry {
+    schedulers: Vec<Box<dyn ResourceScheduler>>,
+}
+
+impl Registry {
+    /// Build the schedulers of every enabled service, sharing the region and
+    /// credentials of `sdk_config`.
+    ///
+    /// Schedulers are processed in registration order: ECS comes first so that
+    /// container instances are drained before their EC2 hosts are stopped.
+    /// Services whose `<SERVICE>_REGIONS` does not include the region are left out.
+    pub fn from_config(config: &AppConfig, sdk_config: &SdkConfig) -> Self {
+        let dry_run = config.dry_run;
+        let max_concurrent_operations = config.max_concurrent_operations;
+        let state = state::from_config(config, sdk_config);
+        let waiter = Waiter::from_config(config);
+        let mut registry = Self::default();
+
+        registry.register(config.ecs_schedule, || {
+            ecs::EcsScheduler::new(
+                sdk_config,
+                state.clone(),
+                config.ecs_drain_container_instances,
+                dry_run,
+                max_concurrent_operations,
+            )
+        });
+        registry.register(config.ec2_schedule, || {
+            let mut scheduler = ec2::Ec2Scheduler::new(
+                sdk_config,
+                config.ec2_stop_mode,
+                dry_run,
+                max_concurrent_operations,
+            );
+            if config.wait_for_completion {
+                scheduler = scheduler.with_start_waiter(waiter);
+            }
+            if config.ec2_graceful_shutdown {
+                scheduler = scheduler.with_graceful_shutdown(
+                    aws_sdk_ssm::Client::new(sdk_config),
+                    GracefulShutdown::from_config(config),
+                );
+            }
+            scheduler
+        });
+        registry.register(config.autoscaling_schedule, || {
+            autoscaling::AutoScalingScheduler::new(
+                sdk_config,
+                state.clone(),
+                config.autoscaling_mode,
+                config.autoscaling_scheduled_actions,
+                dry_run,
+                max_concurrent_operations,
+                waiter,
+            )
+        });
+        registry.register(config.apprunner_schedule, || {
+            apprunner::AppRunnerScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.cloudwatch_alarm_schedule, || {
+            cloudwatch::CloudWatchScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.documentdb_schedule, || {
+            documentdb::DocumentDbScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.rds_schedule, || {
+            let scheduler = rds::RdsScheduler::new(
+                sdk_config,
+                config.rds_serverless_v2_mode,
+                dry_run,
+                max_concurrent_operations,
+            );
+            if config.wait_for_completion {
+                scheduler.with_start_waiter(waiter)
+            } else {
+                scheduler
+            }
+        });
+        registry.register(config.redshift_schedule, || {
+            redshift::RedshiftScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.transfer_schedule, || {
+            transfer::TransferScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.lambda_schedule, || {
+            lambda::LambdaScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.sagemaker_schedule, || {
+            sagemaker::SageMakerScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.eks_schedule, || {
+            eks::EksScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.redshift_serverless_schedule, || {
+            redshift_serverless::RedshiftServerlessScheduler::new(
+                sdk_config,
+                dry_run,
+                max_concurrent_operations,
+            )
+        });
+        registry.register(config.workspaces_schedule, || {
+            workspaces::WorkSpacesScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.opensearch_schedule, || {
+            opensearch::OpenSearchScheduler::new(
+                sdk_config,
+                state.clone(),
+                &config.opensearch_stop_instance_type,
+                dry_run,
+                max_concurrent_operations,
+            )
+        });
+        registry.register(config.elasticache_schedule, || {
+            elasticache::ElastiCacheScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.emr_serverless_schedule, || {
+            emr_serverless::EmrServerlessScheduler::new(
+                sdk_config,
+                dry_run,
+                max_concurrent_operations,
+            )
+        });
+        registry.register(config.mwaa_schedule, || {
+            mwaa::MwaaScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.eventbridge_schedule, || {
+            eventbridge::EventBridgeScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.dms_schedule, || {
+            dms::DmsScheduler::new(
+                sdk_config,
+                &config.dms_stop_instance_class,
+                dry_run,
+                max_concurrent_operations,
+            )
+        });
+        registry.register(config.nat_gateway_schedule, || {
+            natgateway::NatGatewayScheduler::new(
+                sdk_config,
+                state.clone(),
+                dry_run,
+                max_concurrent_operations,
+                waiter,
+            )
+        });
+        registry.register(config.appstream_schedule, || {
+            appstream::AppStreamScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.lightsail_schedule, || {
+            lightsail::LightsailScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.flink_schedule, || {
+            flink::FlinkScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.cloud9_schedule, || {
+            cloud9::Cloud9Scheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.client_vpn_schedule, || {
+            clientvpn::ClientVpnScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.dynamodb_schedule, || {
+            dynamodb::DynamoDbScheduler::new(
+                sdk_config,
+                state.clone(),
+                dry_run,
+                max_concurrent_operations,
+            )
+        });
+        registry.register(config.codepipeline_schedule, || {
+            codepipeline::CodePipelineScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.gamelift_schedule, || {
+            gamelift::GameLiftScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.vpc_endpoints_schedule, || {
+            vpcendpoints::VpcEndpointsScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.ec2_fleet_schedule, || {
+            ec2fleet::Ec2FleetScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.cloudfront_schedule, || {
+            cloudfront::CloudFrontScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.route53_healthcheck_schedule, || {
+            route53::Route53Scheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.comprehend_schedule, || {
+            comprehend::ComprehendScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.ssm_mw_schedule, || {
+            ssm::SsmScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+        registry.register(config.dlm_schedule, || {
+            dlm::DlmScheduler::new(sdk_config, dry_run, max_concurrent_operations)
+        });
+
+        let region = sdk_config.region().map(|r| r.as_ref()).unwrap_or_default();
+        registry
+            .schedulers
+            .retain(|s| config.service_regions(s.name()).iter().any(|r| r == region));
+        registry
+    }
+
+    /// Add a scheduler when its service is enabled.
+    ///
+    /// The scheduler is only built when `enabled` is set.
+    pub fn register<S>(&mut self, enabled: bool, build: impl FnOnce() -> S)
+    where
+        S: ResourceScheduler + 'static,
+    {
+        if enabled {
+            self.schedulers.push(Box::new(build()));
+        }
+    }
+
+    /// Iterate over the registered schedulers, in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ResourceScheduler> {
+        self.schedulers.iter().map(|s| s.as_ref())
+    }
+
+    /// Groups of the registered schedulers in the order of `action`, each group but
+    /// the last one followed by a barrier (see [`ServiceOrder`]).
+    ///
+    /// Schedulers left out of the order join the last group on start and the first one
+    /// on stop, in registration order. Ordered services that are not registered are skipped.
+    pub fn groups(
+        &self,
+        order: &ServiceOrder,
+        action: &ScheduleAction,
+    ) -> Vec<Vec<&dyn ResourceScheduler>> {
+        let ordered = order.groups(action);
+        let unordered: Vec<&dyn ResourceScheduler> = self
+            .iter()
+            .filter(|s| !ordered.iter().flatten().any(|name| *name == s.name()))
+            .collect();
+        let mut groups: Vec<Vec<&dyn ResourceScheduler>> = ordered
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .filter_map(|name| self.iter().find(|s| s.name() == *name))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|group| !group.is_empty())
+            .collect();
+        if groups.is_empty() {
+            groups.push(unordered);
+        } else if matches!(action, ScheduleAction::Stop) {
+            groups[0].splice(0..0, unordered);
+        } else if let Some(last) = groups.last_mut() {
+            last.extend(unordered);
+        }
+        groups.retain(|group| !group.is_empty());
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeScheduler(&'static str);
+
+    #[async_trait]
+    impl ResourceScheduler for FakeScheduler {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn stop(&self, _filter: &ResourceFilter) -> Result<ResourceCounts> {
+            Ok(ResourceCounts::default())
+        }
+
+        async fn start(&self, _filter: &ResourceFilter) -> Result<ResourceCounts> {
+            Ok(ResourceCounts::default())
+        }
+
+        async fn list(&self, _filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn registry(names: &[&'static str]) -> Registry {
+        let mut registry = Registry::default();
+        for name in names {
+            registry.register(true, || FakeScheduler(name));
+        }
+        registry.register(false, || FakeScheduler("eks"));
+        registry
+    }
+
+    fn names(groups: Vec<Vec<&dyn ResourceScheduler>>) -> Vec<Vec<&'static str>> {
+        groups
+            .iter()
+            .map(|group| group.iter().map(|s| s.name()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn groups_follow_the_service_order_of_the_action() {
+        let registry = registry(&["ecs", "ec2", "rds", "documentdb", "lambda"]);
+        let cases = [
+            (
+                "rds,documentdb|ec2,ecs,eks",
+                ScheduleAction::Start,
+                vec![vec!["rds", "documentdb"], vec!["ec2", "ecs", "lambda"]],
+            ),
+            (
+                "rds,documentdb|ec2,ecs,eks",
+                ScheduleAction::Stop,
+                vec![vec!["lambda", "ecs", "ec2"], vec!["documentdb", "rds"]],
+            ),
+            (
+                "rds|eks|ec2",
+                ScheduleAction::Start,
+                vec![vec!["rds"], vec!["ec2", "ecs", "documentdb", "lambda"]],
+            ),
+            (
+                "",
+                ScheduleAction::Stop,
+                vec![vec!["ecs", "ec2", "rds", "documentdb", "lambda"]],
+            ),
+            (
+                "eks|apprunner",
+                ScheduleAction::Start,
+                vec![vec!["ecs", "ec2", "rds", "documentdb", "lambda"]],
+            ),
+        ];
+        for (order, action, expected) in cases {
+            let service_order = ServiceOrder::parse(order).unwrap();
+            assert_eq!(
+                names(registry.groups(&service_order, &action)),
+                expected,
+                "{} on {:?}",
+                order,
+                action
+            );
+        }
+    }
+
+    #[test]
+    fn groups_are_empty_without_registered_schedulers() {
+        let service_order = ServiceOrder::parse("rds|ec2").unwrap();
+        assert!(registry(&[])
+            .groups(&service_order, &ScheduleAction::Stop)
+            .is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::{Region, SdkConfig};
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use aws_sdk_route53::Client as Route53Client;
+use tracing::{error, info};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Route 53 is a global service whose resources are tagged and managed in `us-east-1`.
+const ROUTE53_REGION: &str = "us-east-1";
+
+/// Stop/start handler for Route 53 health checks.
+///
+/// Uses the Resource Groups Tagging API to discover health checks matching a tag.
+/// On stop: disables the health checks, which Route 53 then reports as healthy,
+/// so that DNS failover and alarms do not fire for intentionally stopped backends.
+/// On start: re-enables them.
+///
+/// Health checks are global, so they are always looked up in `us-east-1`
+/// whatever the region being processed, and the service is only processed
+/// in one region (see [`AppConfig::service_regions`](crate::config::AppConfig::service_regions)).
+pub struct Route53Scheduler {
+    route53: Route53Client,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl Route53Scheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        let config = config
+            .to_builder()
+            .region(Region::from_static(ROUTE53_REGION))
+            .build();
+
+        Self {
+            route53: Route53Client::new(&config),
+            tagging: TaggingClient::new(&config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    async fn set_disabled(&self, health_check_id: &str, disabled: bool) -> Result<Outcome> {
+        let resp = self
+            .route53
+            .get_health_check()
+            .health_check_id(health_check_id)
+            .send()
+            .await?;
+        let health_check = resp
+            .health_check()
+            .with_context(|| format!("Route 53 health check '{}' not found", health_check_id))?;
+
+        let currently_disabled = health_check
+            .health_check_config()
+            .and_then(|c| c.disabled())
+            .unwrap_or_default();
+        if currently_disabled == disabled {
+            let state = if disabled { "disabled" } else { "enabled" };
+            info!(health_check = %health_check_id, "Route 53 health check already {state}, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            let action = if disabled { "disable" } else { "enable" };
+            info!(health_check = %health_check_id, "Dry run: would {action} Route 53 health check");
+            return Ok(Outcome::Applied);
+        }
+
+        let action = if disabled { "Disabling" } else { "Enabling" };
+        info!(health_check = %health_check_id, "{action} Route 53 health check");
+        self.route53
+            .update_health_check()
+            .health_check_id(health_check_id)
+            .health_check_version(health_check.health_check_version())
+            .disabled(disabled)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for Route53Scheduler {
+    fn name(&self) -> &'static str {
+        "route53_healthcheck"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "route53:healthcheck", filter)
+                .await?;
+        info!(count = arns.len(), "Found Route 53 health checks to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let health_check_id = extract_health_check_id(arn);
+            let result = self.set_disabled(&health_check_id, true).await;
+            (health_check_id, result)
+        })
+        .await;
+        for (health_check_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&health_check_id, outcome),
+                Err(e) => {
+                    counts.fail(&health_check_id, &e);
+                    error!(health_check = %health_check_id, error = %e, "Failed to disable Route 53 health check");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "route53:healthcheck", filter)
+                .await?;
+        info!(count = arns.len(), "Found Route 53 health checks to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let health_check_id = extract_health_check_id(arn);
+            let result = self.set_disabled(&health_check_id, false).await;
+            (health_check_id, result)
+        })
+        .await;
+        for (health_check_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&health_check_id, outcome),
+                Err(e) => {
+                    counts.fail(&health_check_id, &e);
+                    error!(health_check = %health_check_id, error = %e, "Failed to enable Route 53 health check");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the Route 53 health checks matching the given tag, as `enabled` or `disabled`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "route53:healthcheck", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let health_check_id = extract_health_check_id(&arn);
+            let resp = self
+                .route53
+                .get_health_check()
+                .health_check_id(&health_check_id)
+                .send()
+                .await?;
+            let disabled = resp
+                .health_check()
+                .with_context(|| format!("Route 53 health check '{}' not found", health_check_id))?
+                .health_check_config()
+                .and_then(|c| c.disabled())
+                .unwrap_or_default();
+            let state = if disabled { "disabled" } else { "enabled" };
+            items.push(InventoryItem::new(arn, "route53:healthcheck", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the health check ID from a Route 53 health check ARN.
+///
+/// Expected ARN format: `arn:partition:route53:::healthcheck/health-check-id`
+fn extract_health_check_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_route53::operation::get_health_check::GetHealthCheckOutput;
+    use aws_sdk_route53::operation::update_health_check::UpdateHealthCheckOutput;
+    use aws_sdk_route53::types::{HealthCheck, HealthCheckConfig, HealthCheckType};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const HEALTH_CHECK_ARN_PREFIX: &str = "arn:aws:route53:::healthcheck/";
+
+    /// Rule answering `health_check_id` at version 3, disabled or not.
+    fn get_health_check_rule(health_check_id: &'static str, disabled: bool) -> Rule {
+        mock!(Route53Client::get_health_check)
+            .match_requests(move |req| req.health_check_id() == Some(health_check_id))
+            .then_output(move || {
+                GetHealthCheckOutput::builder()
+                    .health_check(
+                        HealthCheck::builder()
+                            .id(health_check_id)
+                            .caller_reference("terraform")
+                            .health_check_version(3)
+                            .health_check_config(
+                                HealthCheckConfig::builder()
+                                    .r#type(HealthCheckType::Https)
+                                    .disabled(disabled)
+                                    .build()
+                                    .unwrap(),
+                            )
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+            })
+    }
+
+    /// Rule disabling or enabling `health_check_id`, from the version it was read at.
+    fn update_health_check_rule(health_check_id: &'static str, disabled: bool) -> Rule {
+        mock!(Route53Client::update_health_check)
+            .match_requests(move |req| {
+                req.health_check_id() == Some(health_check_id)
+                    && req.health_check_version() == Some(3)
+                    && req.disabled() == Some(disabled)
+            })
+            .then_output(|| UpdateHealthCheckOutput::builder().build())
+    }
+
+    fn scheduler(route53: Route53Client, tagging: TaggingClient) -> Route53Scheduler {
+        Route53Scheduler {
+            route53,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_disables_enabled_health_checks_only() {
+        let arns = get_resources_rule(
+            "route53:healthcheck",
+            &[
+                &format!("{HEALTH_CHECK_ARN_PREFIX}api"),
+                &format!("{HEALTH_CHECK_ARN_PREFIX}web"),
+            ],
+        );
+        let api = get_health_check_rule("api", false);
+        let web = get_health_check_rule("web", true);
+        let update = update_health_check_rule("api", true);
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_route53, RuleMode::MatchAny, [&api, &web, &update]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_enables_disabled_health_checks() {
+        let arns = get_resources_rule(
+            "route53:healthcheck",
+            &[&format!("{HEALTH_CHECK_ARN_PREFIX}web")],
+        );
+        let web = get_health_check_rule("web", true);
+        let update = update_health_check_rule("web", false);
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_route53, RuleMode::MatchAny, [&web, &update]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+}
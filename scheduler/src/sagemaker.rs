@@ -0,0 +1,310 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use aws_sdk_sagemaker::types::NotebookInstanceStatus;
+use aws_sdk_sagemaker::Client as SageMakerClient;
+use tracing::{error, info};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Stop/start handler for SageMaker notebook instances in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover notebook instances matching a tag,
+/// then performs the requested action on each one.
+pub struct SageMakerScheduler {
+    sagemaker: SageMakerClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl SageMakerScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            sagemaker: SageMakerClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Get the current status of a notebook instance (e.g. `InService`, `Stopped`).
+    async fn notebook_status(&self, notebook_name: &str) -> Result<NotebookInstanceStatus> {
+        let resp = self
+            .sagemaker
+            .describe_notebook_instance()
+            .notebook_instance_name(notebook_name)
+            .send()
+            .await?;
+
+        resp.notebook_instance_status().cloned().with_context(|| {
+            format!(
+                "SageMaker notebook instance '{}' has no status",
+                notebook_name
+            )
+        })
+    }
+
+    async fn stop_notebook(&self, notebook_name: &str) -> Result<Outcome> {
+        let status = self.notebook_status(notebook_name).await?;
+        if matches!(
+            status,
+            NotebookInstanceStatus::Stopped | NotebookInstanceStatus::Stopping
+        ) {
+            info!(notebook = %notebook_name, status = %status.as_str(), "SageMaker notebook instance already stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(notebook = %notebook_name, "Dry run: would stop SageMaker notebook instance");
+            return Ok(Outcome::Applied);
+        }
+        info!(notebook = %notebook_name, "Stopping SageMaker notebook instance");
+        self.sagemaker
+            .stop_notebook_instance()
+            .notebook_instance_name(notebook_name)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn start_notebook(&self, notebook_name: &str) -> Result<Outcome> {
+        let status = self.notebook_status(notebook_name).await?;
+        if matches!(
+            status,
+            NotebookInstanceStatus::InService | NotebookInstanceStatus::Pending
+        ) {
+            info!(notebook = %notebook_name, status = %status.as_str(), "SageMaker notebook instance already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(notebook = %notebook_name, "Dry run: would start SageMaker notebook instance");
+            return Ok(Outcome::Applied);
+        }
+        info!(notebook = %notebook_name, "Starting SageMaker notebook instance");
+        self.sagemaker
+            .start_notebook_instance()
+            .notebook_instance_name(notebook_name)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for SageMakerScheduler {
+    fn name(&self) -> &'static str {
+        "sagemaker"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "sagemaker:notebook-instance",
+            filter,
+        )
+        .await?;
+        info!(
+            count = arns.len(),
+            "Found SageMaker notebook instances to stop"
+        );
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let notebook_name = extract_notebook_name(arn);
+            let result = self.stop_notebook(&notebook_name).await;
+            (notebook_name, result)
+        })
+        .await;
+        for (notebook_name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&notebook_name, outcome),
+                Err(e) => {
+                    counts.fail(&notebook_name, &e);
+                    error!(notebook = %notebook_name, error = %e, "Failed to stop SageMaker notebook instance");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "sagemaker:notebook-instance",
+            filter,
+        )
+        .await?;
+        info!(
+            count = arns.len(),
+            "Found SageMaker notebook instances to start"
+        );
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let notebook_name = extract_notebook_name(arn);
+            let result = self.start_notebook(&notebook_name).await;
+            (notebook_name, result)
+        })
+        .await;
+        for (notebook_name, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&notebook_name, outcome),
+                Err(e) => {
+                    counts.fail(&notebook_name, &e);
+                    error!(notebook = %notebook_name, error = %e, "Failed to start SageMaker notebook instance");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the SageMaker notebook instances matching the given tag with their current status.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns = filter_resources_by_tags::get_resources(
+            &self.tagging,
+            "sagemaker:notebook-instance",
+            filter,
+        )
+        .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let status = self.notebook_status(&extract_notebook_name(&arn)).await?;
+            items.push(InventoryItem::new(
+                arn,
+                "sagemaker:notebook-instance",
+                status.as_str(),
+            ));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the notebook instance name from a SageMaker notebook instance ARN.
+///
+/// Expected ARN format: `arn:partition:sagemaker:region:account:notebook-instance/name`
+fn extract_notebook_name(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_sagemaker::operation::describe_notebook_instance::DescribeNotebookInstanceOutput;
+    use aws_sdk_sagemaker::operation::start_notebook_instance::StartNotebookInstanceOutput;
+    use aws_sdk_sagemaker::operation::stop_notebook_instance::StopNotebookInstanceOutput;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const NOTEBOOK_ARN_PREFIX: &str = "arn:aws:sagemaker:eu-west-1:123456789012:notebook-instance/";
+
+    /// Rule describing `notebook_name` with the given status.
+    fn describe_notebook_rule(notebook_name: &'static str, status: NotebookInstanceStatus) -> Rule {
+        mock!(SageMakerClient::describe_notebook_instance)
+            .match_requests(move |req| req.notebook_instance_name() == Some(notebook_name))
+            .then_output(move || {
+                DescribeNotebookInstanceOutput::builder()
+                    .notebook_instance_name(notebook_name)
+                    .notebook_instance_status(status.clone())
+                    .build()
+            })
+    }
+
+    fn scheduler(sagemaker: SageMakerClient, tagging: TaggingClient) -> SageMakerScheduler {
+        SageMakerScheduler {
+            sagemaker,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_skips_notebooks_already_stopped() {
+        let arns = get_resources_rule(
+            "sagemaker:notebook-instance",
+            &[
+                &format!("{NOTEBOOK_ARN_PREFIX}research"),
+                &format!("{NOTEBOOK_ARN_PREFIX}sandbox"),
+            ],
+        );
+        let research = describe_notebook_rule("research", NotebookInstanceStatus::InService);
+        let sandbox = describe_notebook_rule("sandbox", NotebookInstanceStatus::Stopped);
+        let stop = mock!(SageMakerClient::stop_notebook_instance)
+            .match_requests(|req| req.notebook_instance_name() == Some("research"))
+            .then_output(|| StopNotebookInstanceOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_sagemaker,
+                RuleMode::MatchAny,
+                [&research, &sandbox, &stop]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(stop.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_skips_notebooks_already_pending() {
+        let arns = get_resources_rule(
+            "sagemaker:notebook-instance",
+            &[
+                &format!("{NOTEBOOK_ARN_PREFIX}research"),
+                &format!("{NOTEBOOK_ARN_PREFIX}sandbox"),
+            ],
+        );
+        let research = describe_notebook_rule("research", NotebookInstanceStatus::Pending);
+        let sandbox = describe_notebook_rule("sandbox", NotebookInstanceStatus::Stopped);
+        let start = mock!(SageMakerClient::start_notebook_instance)
+            .match_requests(|req| req.notebook_instance_name() == Some("sandbox"))
+            .then_output(|| StartNotebookInstanceOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_sagemaker,
+                RuleMode::MatchAny,
+                [&research, &sandbox, &start]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(start.num_calls(), 1);
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_rds::types::Filter;
+use aws_sdk_rds::Client as RdsClient;
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::config::AppConfig;
+use crate::credentials;
+use crate::summary::{ExecutionSummary, ResourceStatus, Savings, ServiceSummary};
+
+/// Maximum number of identifiers looked up by a single describe call.
+const BATCH_SIZE: usize = 100;
+
+/// On-demand prices per hour in USD, by instance class.
+#[derive(Default, Deserialize)]
+struct PriceSheet {
+    ec2: HashMap<String, f64>,
+    rds: HashMap<String, f64>,
+}
+
+/// Bundled price sheet: Linux and MySQL single-AZ on-demand prices in us-east-1.
+const PRICE_SHEET: &str = include_str!("prices.json");
+
+/// Parsed bundled price sheet. Should it ever be invalid, the error is logged and
+/// no instance is priced.
+static PRICES: LazyLock<PriceSheet> = LazyLock::new(|| {
+    serde_json::from_str(PRICE_SHEET).unwrap_or_else(|e| {
+        error!(error = %e, "Invalid bundled price sheet, savings are not estimated");
+        PriceSheet::default()
+    })
+});
+
+/// Estimate the hourly cost no longer incurred by the EC2 and RDS instances
+/// stopped during a run, from the bundled price sheet.
+///
+/// Prices are us-east-1 on-demand prices, regardless of region, operating
+/// system, database engine or discounts (Savings Plans, Reserved Instances),
+/// so the estimate is an order of magnitude rather than a bill. Multi-AZ RDS
+/// instances count twice. Services whose instance types cannot be looked up
+/// are logged and left out of the estimate.
+pub async fn estimate(config: &AppConfig, summary: &ExecutionSummary) -> Savings {
+    let mut savings = Savings::default();
+    for service in &summary.services {
+        let ids = stopped(service);
+        if ids.is_empty() {
+            continue;
+        }
+        let sdk_config =
+            credentials::load_config(config, &service.region, service.role_arn.as_deref()).await;
+        let (classes, prices) = match service.service.as_str() {
+            "ec2" => (
+                ec2_types(&Ec2Client::new(&sdk_config), &ids).await,
+                &PRICES.ec2,
+            ),
+            "rds" => (
+                rds_classes(&RdsClient::new(&sdk_config), &ids).await,
+                &PRICES.rds,
+            ),
+            _ => continue,
+        };
+        let classes = match classes {
+            Ok(classes) => classes,
+            Err(e) => {
+                warn!(service = %service.service, region = %service.region, error = %e, "Failed to estimate savings");
+                continue;
+            }
+        };
+
+        for (class, weight) in classes {
+            match prices.get(&class) {
+                Some(price) => {
+                    savings.hourly_usd += price * weight;
+                    savings.priced += 1;
+                }
+                None => savings.unpriced += 1,
+            }
+        }
+    }
+
+    info!(
+        hourly_usd = savings.hourly_usd,
+        priced = savings.priced,
+        unpriced = savings.unpriced,
+        "Estimated savings"
+    );
+    savings
+}
+
+/// Identifiers of the resources the service stopped.
+fn stopped(service: &ServiceSummary) -> Vec<String> {
+    service
+        .counts
+        .resources
+        .iter()
+        .filter(|r| r.status == ResourceStatus::Succeeded)
+        .map(|r| r.resource.clone())
+        .collect()
+}
+
+/// Look up the type of the given EC2 instances.
+async fn ec2_types(ec2: &Ec2Client, instance_ids: &[String]) -> Result<Vec<(String, f64)>> {
+    let mut types = Vec::with_capacity(instance_ids.len());
+    for batch in instance_ids.chunks(BATCH_SIZE) {
+        let resp = ec2
+            .describe_instances()
+            .set_instance_ids(Some(batch.to_vec()))
+            .send()
+            .await
+            .context("Failed to describe EC2 instances")?;
+        for instance in resp.reservations().iter().flat_map(|r| r.instances()) {
+            if let Some(instance_type) = instance.instance_type() {
+                types.push((instance_type.as_str().to_string(), 1.0));
+            }
+        }
+    }
+    Ok(types)
+}
+
+/// Look up the class of the given RDS instances, and of the instances of the
+/// given Aurora clusters. RDS reports both under the same service, so every
+/// identifier is looked up as an instance and as a cluster.
+async fn rds_classes(rds: &RdsClient, ids: &[String]) -> Result<Vec<(String, f64)>> {
+    let mut classes = Vec::with_capacity(ids.len());
+    for batch in ids.chunks(BATCH_SIZE) {
+        for filter in ["db-instance-id", "db-cluster-id"] {
+            let filter = Filter::builder()
+                .name(filter)
+                .set_values(Some(batch.to_vec()))
+                .build();
+            let mut marker: Option<String> = None;
+            loop {
+                let resp = rds
+                    .describe_db_instances()
+                    .filters(filter.clone())
+                    .set_marker(marker.clone())
+                    .send()
+                    .await
+                    .context("Failed to describe RDS instances")?;
+                for instance in resp.db_instances() {
+                    if let Some(class) = instance.db_instance_class() {
+                        let weight = if instance.multi_az() == Some(true) {
+                            2.0
+                        } else {
+                            1.0
+                        };
+                        classes.push((class.to_string(), weight));
+                    }
+                }
+                match resp.marker() {
+                    Some(token) if !token.is_empty() => marker = Some(token.to_string()),
+                    _ => break,
+                }
+            }
+        }
+    }
+    Ok(classes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_price_sheet_parses() {
+        let prices: PriceSheet = serde_json::from_str(PRICE_SHEET).unwrap();
+
+        assert_eq!(prices.ec2.get("t3.micro"), Some(&0.0104));
+        assert_eq!(prices.rds.get("db.t3.micro"), Some(&0.017));
+    }
+}
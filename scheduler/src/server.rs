@@ -0,0 +1,218 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::json;
+use subtle::ConstantTimeEq;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::config::{AppConfig, ScheduleAction};
+use crate::metrics;
+use crate::shutdown;
+
+/// State shared by the request handlers.
+struct Server {
+    config: AppConfig,
+    /// Held while a stop/start action runs, so that actions never overlap.
+    action: Mutex<()>,
+}
+
+/// Serve the HTTP API.
+///
+/// - `POST /actions/stop`, `POST /actions/start`: perform the action and return its summary
+/// - `GET /inventory`: list the matched resources with their current state
+/// - `GET /metrics`: metrics of the runs, in the Prometheus text format
+/// - `GET /healthz`: liveness probe
+///
+/// When `SERVER_API_TOKEN` is set, every endpoint but `/metrics` and `/healthz` requires it as a bearer token.
+/// On SIGTERM/SIGINT, the server stops accepting connections and exits once
+/// the requests in flight are complete.
+pub async fn run(config: &AppConfig) -> Result<()> {
+    let app = router(Arc::new(Server {
+        config: config.clone(),
+        action: Mutex::new(()),
+    }));
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.server_port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    info!(addr = %addr, auth = config.server_api_token.is_some(), "Server started");
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown::wait())
+        .await
+        .context("Server failed")
+}
+
+fn router(server: Arc<Server>) -> Router {
+    Router::new()
+        .route("/actions/stop", post(stop))
+        .route("/actions/start", post(start))
+        .route("/inventory", get(inventory))
+        .route("/metrics", get(metrics::handler))
+        .route("/healthz", get(healthz))
+        .with_state(server)
+}
+
+async fn stop(State(server): State<Arc<Server>>, headers: HeaderMap) -> Response {
+    perform(&server, &headers, ScheduleAction::Stop).await
+}
+
+async fn start(State(server): State<Arc<Server>>, headers: HeaderMap) -> Response {
+    perform(&server, &headers, ScheduleAction::Start).await
+}
+
+/// Perform a stop/start action and return its summary.
+///
+/// Responds with `409 Conflict` while another action is running, with
+/// `502 Bad Gateway` when any service or resource failed, and with
+/// `500 Internal Server Error` when the run lock cannot be acquired.
+async fn perform(server: &Server, headers: &HeaderMap, action: ScheduleAction) -> Response {
+    if !authorized(server, headers) {
+        return unauthorized();
+    }
+    let Ok(_running) = server.action.try_lock() else {
+        return error(StatusCode::CONFLICT, "Another action is already running");
+    };
+
+    info!(action = %action, "Performing action on request");
+    let config = AppConfig {
+        schedule_action: action,
+        ..server.config.clone()
+    };
+    match crate::perform(&config).await {
+        Ok(Some(summary)) if summary.failed_services().is_empty() => (StatusCode::OK, Json(summary)).into_response(),
+        Ok(Some(summary)) => (StatusCode::BAD_GATEWAY, Json(summary)).into_response(),
+        Ok(None) => (
+            StatusCode::OK,
+            Json(json!({ "message": "Today is an excluded or inactive date or another run is in progress, skipping execution" })),
+        )
+            .into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+/// List the matched resources; responds with `502 Bad Gateway` when any service could not be listed.
+async fn inventory(State(server): State<Arc<Server>>, headers: HeaderMap) -> Response {
+    if !authorized(&server, &headers) {
+        return unauthorized();
+    }
+
+    let config = AppConfig {
+        schedule_action: ScheduleAction::List,
+        ..server.config.clone()
+    };
+    let inventory = crate::collect_inventory(&config).await;
+    let status = if inventory.errors.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::BAD_GATEWAY
+    };
+    (status, Json(inventory)).into_response()
+}
+
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// Check the bearer token of a request, when `SERVER_API_TOKEN` is set.
+/// The token is compared in constant time, so that response times do not leak it.
+fn authorized(server: &Server, headers: &HeaderMap) -> bool {
+    let Some(token) = &server.config.server_api_token else {
+        return true;
+    };
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|provided| provided.as_bytes().ct_eq(token.as_bytes()).into())
+}
+
+fn unauthorized() -> Response {
+    warn!("Rejected request with a missing or invalid API token");
+    error(StatusCode::UNAUTHORIZED, "Missing or invalid API token")
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::test_util::app_config;
+
+    fn server(vars: &[(&str, &str)]) -> Arc<Server> {
+        Arc::new(Server {
+            config: app_config(vars),
+            action: Mutex::new(()),
+        })
+    }
+
+    fn request(uri: &str, token: Option<&str>) -> Request<Body> {
+        let mut request = Request::post(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        request.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn actions_require_the_api_token() {
+        let server = server(&[("SERVER_API_TOKEN", "s3cr3t")]);
+
+        for token in [None, Some("s3cr3"), Some("s3cr3t-and-more")] {
+            let response = router(server.clone())
+                .oneshot(request("/actions/stop", token))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{:?}", token);
+        }
+        let response = router(server)
+            .oneshot(Request::get("/healthz").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn actions_conflict_while_another_one_runs() {
+        let server = server(&[("SERVER_API_TOKEN", "s3cr3t")]);
+        let _running = server.action.lock().await;
+
+        let response = router(server.clone())
+            .oneshot(request("/actions/start", Some("s3cr3t")))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn actions_report_failed_services_as_bad_gateway() {
+        // Nothing listens on the discard port: every AWS call fails right away.
+        std::env::set_var("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE");
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", "secret");
+        let server = server(&[
+            ("AWS_ENDPOINT_URL", "http://127.0.0.1:9"),
+            ("RETRY_MAX_ATTEMPTS", "1"),
+        ]);
+
+        let response = router(server)
+            .oneshot(request("/actions/stop", None))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::{LazyLock, Once};
+
+use tokio::sync::watch;
+use tracing::warn;
+
+/// Set once a shutdown signal has been received.
+static SHUTDOWN: LazyLock<watch::Sender<bool>> = LazyLock::new(|| watch::Sender::new(false));
+
+/// Guards against spawning several listeners, e.g. one per Lambda invocation.
+static LISTEN: Once = Once::new();
+
+/// Listen for SIGTERM and SIGINT in the background.
+///
+/// On the first signal, a shutdown is requested: no new operation is started,
+/// while the operations in flight are left to complete, so that a run is never
+/// interrupted in the middle of an API call sequence (e.g. with an Auto Scaling
+/// Group suspended but its instances still running).
+pub fn listen() {
+    LISTEN.call_once(|| {
+        tokio::spawn(async {
+            wait_for_signal().await;
+            warn!("Shutdown signal received, finishing in-flight operations");
+            SHUTDOWN.send_replace(true);
+        });
+    });
+}
+
+/// Whether a shutdown has been requested.
+pub fn requested() -> bool {
+    *SHUTDOWN.borrow()
+}
+
+/// Wait until a shutdown is requested.
+pub async fn wait() {
+    let mut receiver = SHUTDOWN.subscribe();
+    // The sender lives in a static, so it is never dropped.
+    let _ = receiver.wait_for(|requested| *requested).await;
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let Ok(mut sigterm) = signal(SignalKind::terminate()) else {
+        warn!("Failed to listen for SIGTERM");
+        let _ = tokio::signal::ctrl_c().await;
+        return;
+    };
+    tokio::select! {
+        _ = sigterm.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -0,0 +1,108 @@
+use anyhow::{Context, Result};
+use aws_sdk_sns::Client as SnsClient;
+use tracing::info;
+
+use crate::arn::Arn;
+use crate::config::AppConfig;
+use crate::credentials;
+use crate::summary::ExecutionSummary;
+
+/// Publish the summary of a run to an SNS topic.
+///
+/// The SNS client is created in the region of the topic, using the ambient credentials.
+pub async fn publish_summary(
+    config: &AppConfig,
+    topic_arn: &str,
+    summary: &ExecutionSummary,
+) -> Result<()> {
+    let region = extract_region(topic_arn)
+        .with_context(|| format!("Invalid SNS topic ARN '{}'", topic_arn))?;
+    let sdk_config = credentials::load_config(config, region, None).await;
+    let sns = SnsClient::new(&sdk_config);
+
+    info!(topic = %topic_arn, "Publishing execution summary to SNS");
+    sns.publish()
+        .topic_arn(topic_arn)
+        .subject(subject(summary))
+        .message(message(summary))
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// Build the notification subject (SNS limits subjects to 100 characters).
+pub fn subject(summary: &ExecutionSummary) -> String {
+    let status = if summary.failed_services().is_empty() {
+        "succeeded"
+    } else {
+        "FAILED"
+    };
+    let subject = format!("AWS scheduler {} {}", summary.action, status);
+    subject.chars().take(100).collect()
+}
+
+/// Build the notification body: overall counts, duration and per-service results.
+pub fn message(summary: &ExecutionSummary) -> String {
+    let mut lines = vec![
+        format!("Action: {}", summary.action),
+        format!("Dry run: {}", summary.dry_run),
+        format!("Started at: {}", summary.started_at),
+        format!("Duration: {:.1}s", summary.duration_seconds),
+        format!(
+            "Resources: {} discovered, {} succeeded, {} failed, {} skipped",
+            summary.totals.discovered,
+            summary.totals.succeeded,
+            summary.totals.failed,
+            summary.totals.skipped
+        ),
+    ];
+    if let Some(savings) = &summary.estimated_savings {
+        let mut line = format!("Estimated savings: ${:.2}/hour", savings.hourly_usd);
+        if savings.unpriced > 0 {
+            line.push_str(&format!(
+                " ({} instance(s) without a known price)",
+                savings.unpriced
+            ));
+        }
+        lines.push(line);
+    }
+    lines.push(String::new());
+
+    let failed = summary.failed_services();
+    if !failed.is_empty() {
+        lines.push("Failures:".to_string());
+        for s in &failed {
+            let detail = match &s.error {
+                Some(error) => error.clone(),
+                None => format!("{} resource(s) failed", s.counts.failed),
+            };
+            lines.push(format!("- {} in {}: {}", s.service, s.region, detail));
+        }
+        lines.push(String::new());
+    }
+
+    lines.push("Services:".to_string());
+    for s in &summary.services {
+        lines.push(format!(
+            "- {} in {}: {} discovered, {} succeeded, {} failed, {} skipped",
+            s.service,
+            s.region,
+            s.counts.discovered,
+            s.counts.succeeded,
+            s.counts.failed,
+            s.counts.skipped
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Extract the region from an SNS topic ARN.
+///
+/// Expected ARN format: `arn:partition:sns:region:account:topic-name`
+fn extract_region(arn: &str) -> Option<&str> {
+    Arn::parse(arn)
+        .ok()
+        .map(|a| a.region)
+        .filter(|r| !r.is_empty())
+}
@@ -0,0 +1,266 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use aws_sdk_ssm::Client as SsmClient;
+use tracing::{error, info};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Stop/start handler for SSM maintenance windows in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover maintenance windows matching a tag.
+/// On stop: disables the maintenance windows, so that patching and automation
+/// tasks do not run against stopped instances.
+/// On start: re-enables them.
+pub struct SsmScheduler {
+    ssm: SsmClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl SsmScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            ssm: SsmClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    async fn set_enabled(&self, window_id: &str, enabled: bool) -> Result<Outcome> {
+        let resp = self
+            .ssm
+            .get_maintenance_window()
+            .window_id(window_id)
+            .send()
+            .await?;
+        if resp.enabled() == enabled {
+            let state = if enabled { "enabled" } else { "disabled" };
+            info!(window = %window_id, "SSM maintenance window already {state}, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            let action = if enabled { "enable" } else { "disable" };
+            info!(window = %window_id, "Dry run: would {action} SSM maintenance window");
+            return Ok(Outcome::Applied);
+        }
+
+        let action = if enabled { "Enabling" } else { "Disabling" };
+        info!(window = %window_id, "{action} SSM maintenance window");
+        self.ssm
+            .update_maintenance_window()
+            .window_id(window_id)
+            .enabled(enabled)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for SsmScheduler {
+    fn name(&self) -> &'static str {
+        "ssm_maintenance_window"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ssm:maintenancewindow", filter)
+                .await?;
+        info!(count = arns.len(), "Found SSM maintenance windows to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let window_id = extract_window_id(arn);
+            let result = self.set_enabled(&window_id, false).await;
+            (window_id, result)
+        })
+        .await;
+        for (window_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&window_id, outcome),
+                Err(e) => {
+                    counts.fail(&window_id, &e);
+                    error!(window = %window_id, error = %e, "Failed to disable SSM maintenance window");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ssm:maintenancewindow", filter)
+                .await?;
+        info!(count = arns.len(), "Found SSM maintenance windows to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let window_id = extract_window_id(arn);
+            let result = self.set_enabled(&window_id, true).await;
+            (window_id, result)
+        })
+        .await;
+        for (window_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&window_id, outcome),
+                Err(e) => {
+                    counts.fail(&window_id, &e);
+                    error!(window = %window_id, error = %e, "Failed to enable SSM maintenance window");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the SSM maintenance windows matching the given tag, as `enabled` or `disabled`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ssm:maintenancewindow", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let resp = self
+                .ssm
+                .get_maintenance_window()
+                .window_id(extract_window_id(&arn))
+                .send()
+                .await?;
+            let state = if resp.enabled() {
+                "enabled"
+            } else {
+                "disabled"
+            };
+            items.push(InventoryItem::new(arn, "ssm:maintenancewindow", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the window ID from an SSM maintenance window ARN.
+///
+/// Expected ARN format: `arn:partition:ssm:region:account:maintenancewindow/mw-0123456789abcdef0`
+fn extract_window_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_ssm::operation::get_maintenance_window::GetMaintenanceWindowOutput;
+    use aws_sdk_ssm::operation::update_maintenance_window::UpdateMaintenanceWindowOutput;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const WINDOW_ARN_PREFIX: &str = "arn:aws:ssm:eu-west-1:123456789012:maintenancewindow/";
+
+    /// Rule answering that `window_id` is enabled or not.
+    fn get_window_rule(window_id: &'static str, enabled: bool) -> Rule {
+        mock!(SsmClient::get_maintenance_window)
+            .match_requests(move |req| req.window_id() == Some(window_id))
+            .then_output(move || {
+                GetMaintenanceWindowOutput::builder()
+                    .window_id(window_id)
+                    .enabled(enabled)
+                    .build()
+            })
+    }
+
+    /// Rule enabling or disabling `window_id`.
+    fn update_window_rule(window_id: &'static str, enabled: bool) -> Rule {
+        mock!(SsmClient::update_maintenance_window)
+            .match_requests(move |req| {
+                req.window_id() == Some(window_id) && req.enabled() == Some(enabled)
+            })
+            .then_output(|| UpdateMaintenanceWindowOutput::builder().build())
+    }
+
+    fn scheduler(ssm: SsmClient, tagging: TaggingClient) -> SsmScheduler {
+        SsmScheduler {
+            ssm,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_disables_enabled_windows_only() {
+        let arns = get_resources_rule(
+            "ssm:maintenancewindow",
+            &[
+                &format!("{WINDOW_ARN_PREFIX}mw-0a1b2c3d4e5f60001"),
+                &format!("{WINDOW_ARN_PREFIX}mw-0a1b2c3d4e5f60002"),
+            ],
+        );
+        let enabled = get_window_rule("mw-0a1b2c3d4e5f60001", true);
+        let disabled = get_window_rule("mw-0a1b2c3d4e5f60002", false);
+        let update = update_window_rule("mw-0a1b2c3d4e5f60001", false);
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_ssm,
+                RuleMode::MatchAny,
+                [&enabled, &disabled, &update]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_enables_disabled_windows() {
+        let arns = get_resources_rule(
+            "ssm:maintenancewindow",
+            &[&format!("{WINDOW_ARN_PREFIX}mw-0a1b2c3d4e5f60002")],
+        );
+        let disabled = get_window_rule("mw-0a1b2c3d4e5f60002", false);
+        let update = update_window_rule("mw-0a1b2c3d4e5f60002", true);
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_ssm, RuleMode::MatchAny, [&disabled, &update]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(update.num_calls(), 1);
+    }
+}
@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::{Region, SdkConfig};
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::Client as DynamoDbClient;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client as S3Client;
+use chrono::Utc;
+use tracing::debug;
+
+use crate::config::{AppConfig, StoreLocation};
+
+/// Prefix of the tags holding the state of a resource with the tag store.
+const TAG_PREFIX: &str = "scheduler:";
+
+/// Number of attempts to write an S3 state object modified concurrently by another save.
+const MAX_S3_SAVE_ATTEMPTS: u32 = 5;
+
+/// State recorded for a resource on stop (e.g. `previous-desired-count`), restored on start.
+pub type State = HashMap<String, String>;
+
+/// Store of the state a scheduler records before stopping a resource, so that
+/// the resource can be restored as it was on start.
+#[async_trait]
+pub trait StateStore: Send + Sync {
+    /// Load the state recorded for a resource; empty when none was recorded.
+    async fn load(&self, arn: &str) -> Result<State>;
+
+    /// Record the state of a resource, merged with the state already recorded.
+    async fn save(&self, arn: &str, state: &State) -> Result<()>;
+}
+
+/// Build the state store configured by `STATE_STORE`, for the account of `sdk_config`.
+///
+/// The DynamoDB table or S3 bucket is looked up in the first region of
+/// `AWS_REGIONS`, with the credentials of the account the resources belong to.
+pub fn from_config(config: &AppConfig, sdk_config: &SdkConfig) -> Arc<dyn StateStore> {
+    let central = || {
+        sdk_config
+            .to_builder()
+            .region(Region::new(config.aws_regions[0].clone()))
+            .build()
+    };
+    match &config.state_store {
+        None => Arc::new(TagStore {
+            tagging: TaggingClient::new(sdk_config),
+        }),
+        Some(StoreLocation::DynamoDb { table }) => Arc::new(DynamoDbStore {
+            dynamodb: DynamoDbClient::new(&central()),
+            table: table.clone(),
+        }),
+        Some(StoreLocation::S3 { bucket, prefix }) => Arc::new(S3Store {
+            s3: S3Client::new(&central()),
+            bucket: bucket.clone(),
+            prefix: prefix.clone(),
+        }),
+    }
+}
+
+/// State kept in `scheduler:*` tags of the resource itself, through the Resource Groups Tagging API.
+struct TagStore {
+    tagging: TaggingClient,
+}
+
+#[async_trait]
+impl StateStore for TagStore {
+    async fn load(&self, arn: &str) -> Result<State> {
+        let resp = self
+            .tagging
+            .get_resources()
+            .resource_arn_list(arn)
+            .send()
+            .await?;
+
+        Ok(resp
+            .resource_tag_mapping_list()
+            .iter()
+            .flat_map(|m| m.tags())
+            .filter_map(|t| {
+                t.key()
+                    .strip_prefix(TAG_PREFIX)
+                    .map(|key| (key.to_string(), t.value().to_string()))
+            })
+            .collect())
+    }
+
+    async fn save(&self, arn: &str, state: &State) -> Result<()> {
+        let tags = state
+            .iter()
+            .map(|(key, value)| (format!("{}{}", TAG_PREFIX, key), value.clone()))
+            .collect();
+        let resp = self
+            .tagging
+            .tag_resources()
+            .resource_arn_list(arn)
+            .set_tags(Some(tags))
+            .send()
+            .await?;
+        if let Some(failure) = resp.failed_resources_map().and_then(|m| m.get(arn)) {
+            bail!(
+                "Failed to tag '{}': {}",
+                arn,
+                failure.error_message().unwrap_or("unknown error")
+            );
+        }
+        Ok(())
+    }
+}
+
+/// State kept in a DynamoDB table whose partition key is the string attribute
+/// `resource_arn`, as a `state` map of strings.
+struct DynamoDbStore {
+    dynamodb: DynamoDbClient,
+    table: String,
+}
+
+#[async_trait]
+impl StateStore for DynamoDbStore {
+    async fn load(&self, arn: &str) -> Result<State> {
+        let resp = self
+            .dynamodb
+            .get_item()
+            .table_name(&self.table)
+            .key("resource_arn", AttributeValue::S(arn.to_string()))
+            .consistent_read(true)
+            .send()
+            .await
+            .with_context(|| {
+                format!("Failed to read state from DynamoDB table '{}'", self.table)
+            })?;
+
+        let Some(AttributeValue::M(state)) = resp.item().and_then(|item| item.get("state")) else {
+            return Ok(State::new());
+        };
+        Ok(state
+            .iter()
+            .filter_map(|(key, value)| value.as_s().ok().map(|v| (key.clone(), v.clone())))
+            .collect())
+    }
+
+    /// Each key is set on its own in the `state` map, so that the keys saved
+    /// concurrently for the same resource (e.g. from another region) are kept.
+    async fn save(&self, arn: &str, state: &State) -> Result<()> {
+        // Keys of the map can only be set once the map exists.
+        self.dynamodb
+            .update_item()
+            .table_name(&self.table)
+            .key("resource_arn", AttributeValue::S(arn.to_string()))
+            .update_expression("SET #state = if_not_exists(#state, :empty)")
+            .expression_attribute_names("#state", "state")
+            .expression_attribute_values(":empty", AttributeValue::M(HashMap::new()))
+            .send()
+            .await
+            .with_context(|| format!("Failed to write state to DynamoDB table '{}'", self.table))?;
+
+        let mut assignments = vec!["updated_at = :updated_at".to_string()];
+        let mut request = self
+            .dynamodb
+            .update_item()
+            .table_name(&self.table)
+            .key("resource_arn", AttributeValue::S(arn.to_string()))
+            .expression_attribute_names("#state", "state")
+            .expression_attribute_values(":updated_at", AttributeValue::S(Utc::now().to_rfc3339()));
+        for (i, (key, value)) in state.iter().enumerate() {
+            assignments.push(format!("#state.#k{i} = :v{i}"));
+            request = request
+                .expression_attribute_names(format!("#k{i}"), key)
+                .expression_attribute_values(format!(":v{i}"), AttributeValue::S(value.clone()));
+        }
+        request
+            .update_expression(format!("SET {}", assignments.join(", ")))
+            .send()
+            .await
+            .with_context(|| format!("Failed to write state to DynamoDB table '{}'", self.table))?;
+        Ok(())
+    }
+}
+
+/// State kept in an S3 bucket, as one JSON object per resource under `prefix/<arn>.json`.
+struct S3Store {
+    s3: S3Client,
+    bucket: String,
+    prefix: String,
+}
+
+impl S3Store {
+    fn key(&self, arn: &str) -> String {
+        if self.prefix.is_empty() {
+            format!("{}.json", arn)
+        } else {
+            format!("{}/{}.json", self.prefix, arn)
+        }
+    }
+
+    /// Read the state object of a resource along with its ETag; empty, without ETag, when missing.
+    async fn read(&self, arn: &str) -> Result<(State, Option<String>)> {
+        let key = self.key(arn);
+        let resp = match self
+            .s3
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await
+        {
+            Ok(resp) => resp,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => {
+                return Ok((State::new(), None))
+            }
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to read state from 's3://{}/{}'", self.bucket, key)
+                })
+            }
+        };
+        let e_tag = resp.e_tag().map(str::to_string);
+        let body =
+            resp.body.collect().await.with_context(|| {
+                format!("Failed to read state from 's3://{}/{}'", self.bucket, key)
+            })?;
+        let state = serde_json::from_slice(&body.into_bytes())
+            .with_context(|| format!("Invalid state in 's3://{}/{}'", self.bucket, key))?;
+        Ok((state, e_tag))
+    }
+}
+
+#[async_trait]
+impl StateStore for S3Store {
+    async fn load(&self, arn: &str) -> Result<State> {
+        Ok(self.read(arn).await?.0)
+    }
+
+    /// The merged object is only written if it was not modified since it was read,
+    /// and merged again otherwise, so that concurrent saves do not lose keys.
+    async fn save(&self, arn: &str, state: &State) -> Result<()> {
+        let key = self.key(arn);
+        for _ in 0..MAX_S3_SAVE_ATTEMPTS {
+            let (mut merged, e_tag) = self.read(arn).await?;
+            merged.extend(state.clone());
+
+            let request = self
+                .s3
+                .put_object()
+                .bucket(&self.bucket)
+                .key(&key)
+                .content_type("application/json")
+                .body(ByteStream::from(serde_json::to_vec(&merged)?));
+            let request = match e_tag {
+                Some(e_tag) => request.if_match(e_tag),
+                None => request.if_none_match("*"),
+            };
+            match request.send().await {
+                Ok(_) => return Ok(()),
+                Err(e)
+                    if matches!(
+                        e.code(),
+                        Some("PreconditionFailed" | "ConditionalRequestConflict")
+                    ) =>
+                {
+                    debug!(bucket = %self.bucket, key = %key, "State object modified concurrently, merging again");
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!("Failed to write state to 's3://{}/{}'", self.bucket, key)
+                    })
+                }
+            }
+        }
+        bail!(
+            "Failed to write state to 's3://{}/{}': modified concurrently {} times",
+            self.bucket,
+            key,
+            MAX_S3_SAVE_ATTEMPTS
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::operation::get_item::GetItemOutput;
+    use aws_sdk_dynamodb::operation::update_item::UpdateItemOutput;
+    use aws_sdk_resourcegroupstagging::operation::get_resources::GetResourcesOutput;
+    use aws_sdk_s3::error::ErrorMetadata;
+    use aws_sdk_s3::operation::get_object::{GetObjectError, GetObjectOutput};
+    use aws_sdk_s3::operation::put_object::{PutObjectError, PutObjectOutput};
+    use aws_sdk_s3::types::error::NoSuchKey;
+    use aws_smithy_mocks::{mock, mock_client, RuleMode};
+
+    use super::*;
+    use crate::test_util::tagged;
+
+    const ARN: &str = "arn:aws:ecs:eu-west-1:123456789012:service/cluster/web";
+
+    fn s3_store(s3: S3Client) -> S3Store {
+        S3Store {
+            s3,
+            bucket: "state".to_string(),
+            prefix: "scheduler".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn tag_store_loads_the_scheduler_tags_without_their_prefix() {
+        let rule = mock!(TaggingClient::get_resources)
+            .match_requests(|req| req.resource_arn_list() == [ARN])
+            .then_output(|| {
+                GetResourcesOutput::builder()
+                    .resource_tag_mapping_list(tagged(
+                        ARN,
+                        &[("scheduler:previous-desired-count", "3")],
+                    ))
+                    .build()
+            });
+        let store = TagStore {
+            tagging: mock_client!(aws_sdk_resourcegroupstagging, [&rule]),
+        };
+
+        assert_eq!(
+            store.load(ARN).await.unwrap(),
+            State::from([("previous-desired-count".to_string(), "3".to_string())])
+        );
+    }
+
+    #[tokio::test]
+    async fn dynamodb_store_loads_an_empty_state_for_a_missing_item() {
+        let rule = mock!(DynamoDbClient::get_item).then_output(|| GetItemOutput::builder().build());
+        let store = DynamoDbStore {
+            dynamodb: mock_client!(aws_sdk_dynamodb, [&rule]),
+            table: "state".to_string(),
+        };
+
+        assert!(store.load(ARN).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dynamodb_store_sets_each_key_in_the_existing_state() {
+        let create_map = mock!(DynamoDbClient::update_item)
+            .match_requests(|req| {
+                req.update_expression() == Some("SET #state = if_not_exists(#state, :empty)")
+            })
+            .then_output(|| UpdateItemOutput::builder().build());
+        let set_keys = mock!(DynamoDbClient::update_item)
+            .match_requests(|req| {
+                let names = req.expression_attribute_names().unwrap();
+                let values = req.expression_attribute_values().unwrap();
+                req.update_expression() == Some("SET updated_at = :updated_at, #state.#k0 = :v0")
+                    && names.get("#k0").map(String::as_str) == Some("previous-desired-count")
+                    && values.get(":v0") == Some(&AttributeValue::S("3".to_string()))
+            })
+            .then_output(|| UpdateItemOutput::builder().build());
+        let store = DynamoDbStore {
+            dynamodb: mock_client!(aws_sdk_dynamodb, [&create_map, &set_keys]),
+            table: "state".to_string(),
+        };
+
+        store
+            .save(
+                ARN,
+                &State::from([("previous-desired-count".to_string(), "3".to_string())]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(create_map.num_calls(), 1);
+        assert_eq!(set_keys.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn s3_store_loads_an_empty_state_for_a_missing_object() {
+        let rule = mock!(S3Client::get_object)
+            .match_requests(|req| req.key() == Some(&format!("scheduler/{}.json", ARN)[..]))
+            .then_error(|| GetObjectError::NoSuchKey(NoSuchKey::builder().build()));
+        let store = s3_store(mock_client!(aws_sdk_s3, [&rule]));
+
+        assert!(store.load(ARN).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn s3_store_merges_again_when_the_object_was_modified_concurrently() {
+        let missing = mock!(S3Client::get_object)
+            .then_error(|| GetObjectError::NoSuchKey(NoSuchKey::builder().build()));
+        let conflict = mock!(S3Client::put_object)
+            .match_requests(|req| req.if_none_match() == Some("*"))
+            .then_error(|| {
+                PutObjectError::generic(ErrorMetadata::builder().code("PreconditionFailed").build())
+            });
+        let existing = mock!(S3Client::get_object).then_output(|| {
+            GetObjectOutput::builder()
+                .e_tag("\"v1\"")
+                .body(ByteStream::from_static(br#"{"previous-min-size":"1"}"#))
+                .build()
+        });
+        let put = mock!(S3Client::put_object)
+            .match_requests(|req| {
+                let state: State = serde_json::from_slice(req.body().bytes().unwrap()).unwrap();
+                req.if_match() == Some("\"v1\"")
+                    && state
+                        == State::from([
+                            ("previous-min-size".to_string(), "1".to_string()),
+                            ("previous-max-size".to_string(), "4".to_string()),
+                        ])
+            })
+            .then_output(|| PutObjectOutput::builder().build());
+        let store = s3_store(mock_client!(
+            aws_sdk_s3,
+            RuleMode::Sequential,
+            [&missing, &conflict, &existing, &put]
+        ));
+
+        store
+            .save(
+                ARN,
+                &State::from([("previous-max-size".to_string(), "4".to_string())]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(put.num_calls(), 1);
+    }
+}
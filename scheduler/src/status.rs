@@ -0,0 +1,134 @@
+use serde::Serialize;
+
+use crate::config::ScheduleAction;
+use crate::inventory::{Inventory, InventoryError, InventoryItem};
+
+/// State of a resource as far as the scheduler is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObservedState {
+    /// Stopped, or on its way to being stopped (scaled down, disabled, deleted with a record...).
+    Stopped,
+    /// Running, or on its way to running.
+    Running,
+    /// In a state the scheduler leaves untouched (e.g. an on-demand DynamoDB table).
+    Unmanaged,
+    /// In any other state (failed, updating, partially disabled...).
+    Unknown,
+}
+
+/// Classify the state reported by `SCHEDULE_ACTION=list` for a resource.
+///
+/// States are compared case-insensitively, with `_` and `-` treated alike.
+/// Transitional states count as the state they lead to (e.g. `stopping` as stopped).
+pub fn classify(resource_type: &str, state: &str) -> ObservedState {
+    let state = state.to_lowercase().replace('_', "-");
+
+    match (resource_type, state.as_str()) {
+        ("dynamodb:table", "on-demand")
+        | ("lambda:function", "no-mappings")
+        | ("cloud9:environment", "no-instance") => ObservedState::Unmanaged,
+        // Flink applications and DMS tasks are `READY` when not running,
+        // and EMR Serverless applications `CREATED` until first started.
+        ("kinesisanalytics:application", "ready")
+        | ("dms:task", "ready")
+        | ("emr-serverless:applications", "created") => ObservedState::Stopped,
+        (
+            _,
+            "stopped"
+            | "stopping"
+            | "paused"
+            | "pausing"
+            | "disabled"
+            | "actions-disabled"
+            | "transitions-disabled"
+            | "mappings-disabled"
+            | "scaled-to-zero"
+            | "scaled-down"
+            | "suspended"
+            | "downsized"
+            | "offline"
+            | "inactive"
+            | "draining"
+            | "disassociated"
+            | "deleted"
+            | "stored"
+            | "nat-gateway-deleted",
+        ) => ObservedState::Stopped,
+        (
+            _,
+            "running"
+            | "pending"
+            | "starting"
+            | "resuming"
+            | "available"
+            | "creating"
+            | "enabled"
+            | "actions-enabled"
+            | "transitions-enabled"
+            | "mappings-enabled"
+            | "inservice"
+            | "online"
+            | "active"
+            | "started"
+            | "associated"
+            | "provisioned",
+        ) => ObservedState::Running,
+        _ => ObservedState::Unknown,
+    }
+}
+
+/// Whether a state is transitional (`stopping`, `pending`...): the resource is still
+/// on its way to the state it is classified as.
+pub fn is_transitional(state: &str) -> bool {
+    matches!(
+        state.to_lowercase().replace('_', "-").as_str(),
+        "stopping" | "pausing" | "pending" | "starting" | "resuming" | "creating"
+    )
+}
+
+/// Result of `SCHEDULE_ACTION=status`, printed as JSON.
+///
+/// Lists the resources that are not in the state implied by the most
+/// recent scheduled action.
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    /// Most recent scheduled action (`stop` or `start`).
+    pub last_action: String,
+    pub last_run_at: String,
+    /// Number of resources checked.
+    pub checked: usize,
+    pub drift: Vec<InventoryItem>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<InventoryError>,
+}
+
+impl StatusReport {
+    /// Compare the resources of an inventory to the state expected after `last_action`.
+    pub fn new(
+        last_action: &ScheduleAction,
+        last_run_at: chrono::DateTime<chrono::Utc>,
+        inventory: Inventory,
+    ) -> Self {
+        let expected = match last_action {
+            ScheduleAction::Start => ObservedState::Running,
+            _ => ObservedState::Stopped,
+        };
+        let checked = inventory.resources.len();
+        let drift = inventory
+            .resources
+            .into_iter()
+            .filter(|item| match classify(&item.resource_type, &item.state) {
+                ObservedState::Unmanaged => false,
+                observed => observed != expected,
+            })
+            .collect();
+
+        Self {
+            last_action: last_action.to_string(),
+            last_run_at: last_run_at.to_rfc3339(),
+            checked,
+            drift,
+            errors: inventory.errors,
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::Result;
+use serde::Serialize;
+
+/// Outcome of an action on a single resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The action was performed (or would have been, in dry-run mode).
+    Applied,
+    /// The resource was left untouched (already in the target state, not applicable, ...).
+    Skipped,
+}
+
+/// Result of an action on a single resource, as reported in events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceStatus {
+    Succeeded,
+    Skipped,
+    Failed,
+}
+
+impl ResourceStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResourceStatus::Succeeded => "succeeded",
+            ResourceStatus::Skipped => "skipped",
+            ResourceStatus::Failed => "failed",
+        }
+    }
+}
+
+/// Result of an action on a single resource.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceResult {
+    /// ARN or identifier of the resource, as reported by the scheduler.
+    pub resource: String,
+    pub status: ResourceStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Time at which the result was recorded.
+    pub timestamp: String,
+}
+
+/// Per-resource counters reported by a scheduler for one service in one region.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ResourceCounts {
+    pub discovered: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Result of each processed resource, left out of the printed summary.
+    #[serde(skip)]
+    pub resources: Vec<ResourceResult>,
+}
+
+impl ResourceCounts {
+    /// Create counters for the given number of discovered resources.
+    pub fn new(discovered: usize) -> Self {
+        Self {
+            discovered,
+            ..Self::default()
+        }
+    }
+
+    /// Record the outcome of a successful call on a single resource.
+    pub fn record(&mut self, resource: &str, outcome: Outcome) {
+        let status = match outcome {
+            Outcome::Applied => {
+                self.succeeded += 1;
+                ResourceStatus::Succeeded
+            }
+            Outcome::Skipped => {
+                self.skipped += 1;
+                ResourceStatus::Skipped
+            }
+        };
+        self.resources.push(ResourceResult {
+            resource: resource.to_string(),
+            status,
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    /// Record a failed call on a single resource.
+    pub fn fail(&mut self, resource: &str, error: impl std::fmt::Display) {
+        self.failed += 1;
+        self.resources.push(ResourceResult {
+            resource: resource.to_string(),
+            status: ResourceStatus::Failed,
+            error: Some(error.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    /// Add the counters of another pass over the resources of the same service,
+    /// e.g. another priority wave.
+    pub fn merge(&mut self, other: ResourceCounts) {
+        self.discovered += other.discovered;
+        self.succeeded += other.succeeded;
+        self.failed += other.failed;
+        self.skipped += other.skipped;
+        self.resources.extend(other.resources);
+    }
+
+    /// Record a resource the action was applied to, but that did not reach the
+    /// target state in time: it counts as failed instead of succeeded.
+    pub fn not_converged(&mut self, resource: &str, error: impl std::fmt::Display) {
+        self.succeeded = self.succeeded.saturating_sub(1);
+        self.fail(resource, error);
+    }
+}
+
+/// Result of processing one service in one region (and account).
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceSummary {
+    pub service: String,
+    pub region: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role_arn: Option<String>,
+    #[serde(flatten)]
+    pub counts: ResourceCounts,
+    /// Error that aborted the processing of the whole service (e.g. discovery failure).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_seconds: f64,
+}
+
+impl ServiceSummary {
+    pub fn new(
+        service: &str,
+        region: &str,
+        role_arn: Option<&str>,
+        result: Result<ResourceCounts>,
+    ) -> Self {
+        let (counts, error) = match result {
+            Ok(counts) => (counts, None),
+            Err(e) => (ResourceCounts::default(), Some(format!("{:#}", e))),
+        };
+
+        Self {
+            service: service.to_string(),
+            region: region.to_string(),
+            role_arn: role_arn.map(str::to_string),
+            counts,
+            error,
+            duration_seconds: 0.0,
+        }
+    }
+
+    /// Record the time spent processing the service.
+    pub fn with_duration(mut self, duration: std::time::Duration) -> Self {
+        self.duration_seconds = duration.as_millis() as f64 / 1000.0;
+        self
+    }
+
+    /// Whether the service failed as a whole or on at least one resource.
+    pub fn has_failures(&self) -> bool {
+        self.error.is_some() || self.counts.failed > 0
+    }
+}
+
+/// Estimated cost no longer incurred by the resources stopped during a run.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct Savings {
+    /// On-demand price per hour of the stopped instances with a known price, in USD.
+    pub hourly_usd: f64,
+    /// Number of stopped instances whose price is known.
+    pub priced: usize,
+    /// Number of stopped instances whose type is missing from the price sheet.
+    pub unpriced: usize,
+}
+
+/// Machine-readable summary of a whole execution, printed as JSON at the end of a run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionSummary {
+    pub action: String,
+    pub dry_run: bool,
+    pub started_at: String,
+    pub duration_seconds: f64,
+    pub totals: ResourceCounts,
+    pub services: Vec<ServiceSummary>,
+    /// Whether the run was cut short by a shutdown signal, leaving resources unprocessed.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub interrupted: bool,
+    /// Savings estimated for a stop run, when `ESTIMATE_SAVINGS` is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_savings: Option<Savings>,
+}
+
+impl ExecutionSummary {
+    pub fn new(
+        action: String,
+        dry_run: bool,
+        started_at: chrono::DateTime<chrono::Utc>,
+        services: Vec<ServiceSummary>,
+        interrupted: bool,
+    ) -> Self {
+        let totals = services
+            .iter()
+            .fold(ResourceCounts::default(), |acc, s| ResourceCounts {
+                discovered: acc.discovered + s.counts.discovered,
+                succeeded: acc.succeeded + s.counts.succeeded,
+                failed: acc.failed + s.counts.failed,
+                skipped: acc.skipped + s.counts.skipped,
+                resources: Vec::new(),
+            });
+        let duration = chrono::Utc::now() - started_at;
+
+        Self {
+            action,
+            dry_run,
+            started_at: started_at.to_rfc3339(),
+            duration_seconds: duration.num_milliseconds() as f64 / 1000.0,
+            totals,
+            services,
+            interrupted,
+            estimated_savings: None,
+        }
+    }
+
+    /// Services that failed as a whole or on at least one resource.
+    pub fn failed_services(&self) -> Vec<&ServiceSummary> {
+        self.services.iter().filter(|s| s.has_failures()).collect()
+    }
+}
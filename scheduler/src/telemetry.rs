@@ -0,0 +1,114 @@
+//! Logging and tracing setup shared by the binaries.
+
+use anyhow::{bail, Context, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use tracing::{warn, Level};
+use tracing_subscriber::filter::{filter_fn, EnvFilter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// Service name reported to the tracing backend, unless `OTEL_SERVICE_NAME` is set.
+const SERVICE_NAME: &str = "aws-scheduler-stop-start";
+
+/// Tracing pipeline of the process; flushes pending spans when dropped.
+pub struct Telemetry {
+    provider: Option<TracerProvider>,
+}
+
+impl Telemetry {
+    /// Export the spans not exported yet, e.g. before a Lambda execution environment is frozen.
+    pub fn flush(&self) {
+        if let Some(provider) = &self.provider {
+            for result in provider.force_flush() {
+                if let Err(e) = result {
+                    warn!(error = %e, "Failed to export spans");
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to shut down span exporter: {}", e);
+            }
+        }
+    }
+}
+
+/// Install the global tracing subscriber.
+///
+/// Logs are written to stdout at `LOG_LEVEL` (default: `info`), as text or, with
+/// `LOG_FORMAT=json`, as one JSON object per line: the fields of each event are
+/// flattened at the top level, and those of the enclosing span (`region`,
+/// `service`, `action`, ...) are nested under `span`. `lambda` drops colors and
+/// timestamps, which CloudWatch Logs already records.
+///
+/// When `OTEL_EXPORTER_OTLP_ENDPOINT` is set, spans are also exported over
+/// OTLP/HTTP (e.g. to Jaeger or Tempo): the run, region and service spans of
+/// the scheduler, along with the span of every AWS API call. The standard
+/// `OTEL_*` variables (headers, timeout, ...) are honoured.
+pub fn init(lambda: bool) -> Result<Telemetry> {
+    let log_level = std::env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string());
+    let log_format = std::env::var("LOG_FORMAT").unwrap_or_else(|_| "text".to_string());
+    let fmt_layer = tracing_subscriber::fmt::layer();
+    let fmt_layer = match (log_format.to_lowercase().as_str(), lambda) {
+        ("text", false) => fmt_layer.boxed(),
+        ("text", true) => fmt_layer.with_ansi(false).without_time().boxed(),
+        ("json", false) => fmt_layer
+            .json()
+            .flatten_event(true)
+            .with_span_list(false)
+            .boxed(),
+        ("json", true) => fmt_layer
+            .json()
+            .flatten_event(true)
+            .with_span_list(false)
+            .without_time()
+            .boxed(),
+        (other, _) => bail!("Invalid LOG_FORMAT '{}': must be 'text' or 'json'", other),
+    };
+
+    let provider = match std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        Ok(endpoint) if !endpoint.is_empty() => Some(tracer_provider()?),
+        _ => None,
+    };
+    let otel_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer("scheduler"))
+            // AWS SDK operations are recorded as debug spans.
+            .with_filter(filter_fn(|metadata| {
+                metadata.level() <= &Level::INFO
+                    || (metadata.is_span() && metadata.target().starts_with("aws_sdk_"))
+            }))
+    });
+
+    tracing_subscriber::registry()
+        .with(fmt_layer.with_filter(EnvFilter::new(&log_level)))
+        .with(otel_layer)
+        .try_init()
+        .context("Failed to install the tracing subscriber")?;
+
+    Ok(Telemetry { provider })
+}
+
+/// Build a tracer provider exporting spans in batches over OTLP/HTTP.
+fn tracer_provider() -> Result<TracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()
+        .context("Failed to create the OTLP span exporter")?;
+    let service_name =
+        std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| SERVICE_NAME.to_string());
+
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new("service.name", service_name)]))
+        .build())
+}
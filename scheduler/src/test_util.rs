@@ -0,0 +1,130 @@
+//! Helpers shared by the unit tests of the schedulers.
+//!
+//! AWS clients are built with `aws-smithy-mocks`, so that each test declares the
+//! responses of the API calls it expects instead of reaching AWS.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use aws_sdk_resourcegroupstagging::operation::get_resources::GetResourcesOutput;
+use aws_sdk_resourcegroupstagging::types::{ResourceTagMapping, Tag};
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use aws_smithy_mocks::{mock, Rule};
+
+use crate::config::{AppConfig, ScheduleAction};
+use crate::filter_resources_by_tags::ResourceFilter;
+use crate::state::{State, StateStore};
+
+pub const TAG_KEY: &str = "env";
+pub const TAG_VALUE: &str = "staging";
+
+/// Filter selecting the resources tagged `env=staging`, without window or exclusion tag.
+pub fn filter(action: ScheduleAction) -> ResourceFilter {
+    ResourceFilter {
+        tag_key: TAG_KEY.to_string(),
+        tag_values: vec![TAG_VALUE.to_string()],
+        required_tags: Vec::new(),
+        window_tag_key: None,
+        name_include_regex: None,
+        name_exclude_regex: None,
+        protected_tags: Vec::new(),
+        exclusion_tag_key: None,
+        exclusion_tag_value: None,
+        terminate_confirm_tag: None,
+        max_resources: None,
+        planned: None,
+        priority_tag_key: None,
+        wave: None,
+        action,
+        timezone: chrono_tz::UTC,
+    }
+}
+
+/// Configuration of a single run stopping the resources tagged `env=staging` in `eu-west-1`,
+/// with `vars` (keyed by environment variable name) on top.
+pub fn app_config(vars: &[(&str, &str)]) -> AppConfig {
+    let defaults = [
+        ("SCHEDULE_ACTION", "stop"),
+        ("AWS_REGIONS", "eu-west-1"),
+        ("TAG_KEY", TAG_KEY),
+        ("TAG_VALUE", TAG_VALUE),
+    ];
+    let overrides = defaults
+        .iter()
+        .chain(vars)
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    AppConfig::from_env_with(overrides).expect("valid test configuration")
+}
+
+/// Tagging API mapping of a resource carrying `env=staging` and the given extra tags.
+pub fn tagged(arn: &str, extra_tags: &[(&str, &str)]) -> ResourceTagMapping {
+    let tags = std::iter::once((TAG_KEY, TAG_VALUE))
+        .chain(extra_tags.iter().copied())
+        .map(|(key, value)| {
+            Tag::builder()
+                .key(key)
+                .value(value)
+                .build()
+                .expect("tag has a key and a value")
+        });
+    ResourceTagMapping::builder()
+        .resource_arn(arn)
+        .set_tags(Some(tags.collect()))
+        .build()
+}
+
+/// Rule answering the Tagging API lookup of `resource_type` with the given ARNs, on a single page.
+pub fn get_resources_rule(resource_type: &'static str, arns: &[&str]) -> Rule {
+    let mappings: Vec<ResourceTagMapping> = arns.iter().map(|arn| tagged(arn, &[])).collect();
+    mock!(TaggingClient::get_resources)
+        .match_requests(move |req| req.resource_type_filters() == [resource_type])
+        .then_output(move || {
+            GetResourcesOutput::builder()
+                .set_resource_tag_mapping_list(Some(mappings.clone()))
+                .build()
+        })
+}
+
+/// In-memory state store, recording every state saved by a scheduler.
+#[derive(Default)]
+pub struct MemoryStore {
+    states: Mutex<HashMap<String, State>>,
+}
+
+impl MemoryStore {
+    /// Store already holding the state of the resource `arn`.
+    pub fn with(arn: &str, state: &[(&str, &str)]) -> Self {
+        let state = state
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        Self {
+            states: Mutex::new(HashMap::from([(arn.to_string(), state)])),
+        }
+    }
+
+    /// State recorded for the resource `arn`, if any.
+    pub fn state(&self, arn: &str) -> Option<State> {
+        self.states.lock().unwrap().get(arn).cloned()
+    }
+}
+
+#[async_trait]
+impl StateStore for MemoryStore {
+    async fn load(&self, arn: &str) -> Result<State> {
+        Ok(self.state(arn).unwrap_or_default())
+    }
+
+    async fn save(&self, arn: &str, state: &State) -> Result<()> {
+        self.states
+            .lock()
+            .unwrap()
+            .entry(arn.to_string())
+            .or_default()
+            .extend(state.clone());
+        Ok(())
+    }
+}
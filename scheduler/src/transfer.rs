@@ -1,9 +1,17 @@
-use anyhow::Result;
-use aws_sdk_transfer::Client as TransferClient;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
 use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use aws_sdk_transfer::types::State;
+use aws_sdk_transfer::Client as TransferClient;
 use tracing::{error, info};
 
-use crate::filter_resources_by_tags;
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
 
 /// Stop/start handler for AWS Transfer Family servers in a given AWS region.
 ///
@@ -12,75 +20,271 @@ use crate::filter_resources_by_tags;
 pub struct TransferScheduler {
     transfer: TransferClient,
     tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
 }
 
 impl TransferScheduler {
-    pub async fn new(region: &str) -> Self {
-        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(aws_config::Region::new(region.to_string()))
-            .load()
-            .await;
-
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
         Self {
-            transfer: TransferClient::new(&config),
-            tagging: TaggingClient::new(&config),
+            transfer: TransferClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Get the current state of a Transfer server (e.g. `ONLINE`, `OFFLINE`).
+    async fn server_state(&self, server_id: &str) -> Result<State> {
+        let resp = self
+            .transfer
+            .describe_server()
+            .server_id(server_id)
+            .send()
+            .await?;
+
+        resp.server()
+            .and_then(|s| s.state())
+            .cloned()
+            .with_context(|| format!("Transfer server '{}' has no state", server_id))
+    }
+
+    async fn stop_server(&self, server_id: &str) -> Result<Outcome> {
+        let state = self.server_state(server_id).await?;
+        if matches!(state, State::Offline | State::Stopping) {
+            info!(server = %server_id, state = %state.as_str(), "Transfer server already stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(server = %server_id, "Dry run: would stop Transfer server");
+            return Ok(Outcome::Applied);
+        }
+        info!(server = %server_id, "Stopping Transfer server");
+        self.transfer
+            .stop_server()
+            .server_id(server_id)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+
+    async fn start_server(&self, server_id: &str) -> Result<Outcome> {
+        let state = self.server_state(server_id).await?;
+        if matches!(state, State::Online | State::Starting) {
+            info!(server = %server_id, state = %state.as_str(), "Transfer server already online, skipping");
+            return Ok(Outcome::Skipped);
         }
+        if self.dry_run {
+            info!(server = %server_id, "Dry run: would start Transfer server");
+            return Ok(Outcome::Applied);
+        }
+        info!(server = %server_id, "Starting Transfer server");
+        self.transfer
+            .start_server()
+            .server_id(server_id)
+            .send()
+            .await?;
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for TransferScheduler {
+    fn name(&self) -> &'static str {
+        "transfer"
     }
 
-    pub async fn stop(&self, tag_key: &str, tag_value: &str) -> Result<()> {
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
         let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "transfer:server", tag_key, tag_value).await?;
+            filter_resources_by_tags::get_resources(&self.tagging, "transfer:server", filter)
+                .await?;
         info!(count = arns.len(), "Found Transfer servers to stop");
 
-        for arn in &arns {
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
             let server_id = extract_server_id(arn);
-            if let Err(e) = self.stop_server(&server_id).await {
-                error!(server = %server_id, error = %e, "Failed to stop Transfer server");
+            let result = self.stop_server(&server_id).await;
+            (server_id, result)
+        })
+        .await;
+        for (server_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&server_id, outcome),
+                Err(e) => {
+                    counts.fail(&server_id, &e);
+                    error!(server = %server_id, error = %e, "Failed to stop Transfer server");
+                }
             }
         }
 
-        Ok(())
+        Ok(counts)
     }
 
-    pub async fn start(&self, tag_key: &str, tag_value: &str) -> Result<()> {
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
         let arns =
-            filter_resources_by_tags::get_resources(&self.tagging, "transfer:server", tag_key, tag_value).await?;
+            filter_resources_by_tags::get_resources(&self.tagging, "transfer:server", filter)
+                .await?;
         info!(count = arns.len(), "Found Transfer servers to start");
 
-        for arn in &arns {
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
             let server_id = extract_server_id(arn);
-            if let Err(e) = self.start_server(&server_id).await {
-                error!(server = %server_id, error = %e, "Failed to start Transfer server");
+            let result = self.start_server(&server_id).await;
+            (server_id, result)
+        })
+        .await;
+        for (server_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&server_id, outcome),
+                Err(e) => {
+                    counts.fail(&server_id, &e);
+                    error!(server = %server_id, error = %e, "Failed to start Transfer server");
+                }
             }
         }
 
-        Ok(())
+        Ok(counts)
     }
 
-    async fn stop_server(&self, server_id: &str) -> Result<()> {
-        info!(server = %server_id, "Stopping Transfer server");
-        self.transfer
-            .stop_server()
-            .server_id(server_id)
-            .send()
-            .await?;
-        Ok(())
-    }
+    /// List the Transfer servers matching the given tag with their current state.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "transfer:server", filter)
+                .await?;
 
-    async fn start_server(&self, server_id: &str) -> Result<()> {
-        info!(server = %server_id, "Starting Transfer server");
-        self.transfer
-            .start_server()
-            .server_id(server_id)
-            .send()
-            .await?;
-        Ok(())
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let state = self.server_state(&extract_server_id(&arn)).await?;
+            items.push(InventoryItem::new(arn, "transfer:server", state.as_str()));
+        }
+        Ok(items)
     }
 }
 
 /// Extract the server ID from a Transfer Family server ARN.
 ///
-/// Expected ARN format: `arn:aws:transfer:region:account:server/server-id`
+/// Expected ARN format: `arn:partition:transfer:region:account:server/server-id`
 fn extract_server_id(arn: &str) -> String {
-    arn.split('/').last().unwrap_or(arn).to_string()
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_transfer::operation::describe_server::DescribeServerOutput;
+    use aws_sdk_transfer::operation::start_server::StartServerOutput;
+    use aws_sdk_transfer::operation::stop_server::StopServerOutput;
+    use aws_sdk_transfer::types::DescribedServer;
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const SERVER_ARN_PREFIX: &str = "arn:aws:transfer:eu-west-1:123456789012:server/";
+
+    /// Rule describing `server_id` in the given state.
+    fn describe_server_rule(server_id: &'static str, state: State) -> Rule {
+        mock!(TransferClient::describe_server)
+            .match_requests(move |req| req.server_id() == Some(server_id))
+            .then_output(move || {
+                DescribeServerOutput::builder()
+                    .server(
+                        DescribedServer::builder()
+                            .arn(format!("{SERVER_ARN_PREFIX}{server_id}"))
+                            .server_id(server_id)
+                            .state(state.clone())
+                            .build()
+                            .unwrap(),
+                    )
+                    .build()
+            })
+    }
+
+    fn scheduler(transfer: TransferClient, tagging: TaggingClient) -> TransferScheduler {
+        TransferScheduler {
+            transfer,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_skips_servers_already_offline() {
+        let arns = get_resources_rule(
+            "transfer:server",
+            &[
+                &format!("{SERVER_ARN_PREFIX}s-online"),
+                &format!("{SERVER_ARN_PREFIX}s-offline"),
+            ],
+        );
+        let online = describe_server_rule("s-online", State::Online);
+        let offline = describe_server_rule("s-offline", State::Offline);
+        let stop = mock!(TransferClient::stop_server)
+            .match_requests(|req| req.server_id() == Some("s-online"))
+            .then_output(|| StopServerOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_transfer,
+                RuleMode::MatchAny,
+                [&online, &offline, &stop]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(stop.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_skips_servers_already_starting() {
+        let arns = get_resources_rule(
+            "transfer:server",
+            &[
+                &format!("{SERVER_ARN_PREFIX}s-offline"),
+                &format!("{SERVER_ARN_PREFIX}s-starting"),
+            ],
+        );
+        let offline = describe_server_rule("s-offline", State::Offline);
+        let starting = describe_server_rule("s-starting", State::Starting);
+        let start = mock!(TransferClient::start_server)
+            .match_requests(|req| req.server_id() == Some("s-offline"))
+            .then_output(|| StartServerOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_transfer,
+                RuleMode::MatchAny,
+                [&offline, &starting, &start]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(start.num_calls(), 1);
+    }
 }
@@ -0,0 +1,655 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_ec2::types::{ResourceType, Tag, TagSpecification, Vpc, VpcEndpoint, VpcEndpointType};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use tracing::{error, info};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Prefix of the VPC tags recording a deleted endpoint, followed by the endpoint ID.
+const ENDPOINT_TAG_PREFIX: &str = "scheduler:vpc-endpoint:";
+/// Infix of the VPC tags holding the tags of a deleted endpoint, followed by the tag key.
+const ENDPOINT_TAG_INFIX: &str = ":tag:";
+
+/// Maximum length of an EC2 tag value.
+const MAX_TAG_VALUE_LENGTH: usize = 256;
+
+/// Stop/start handler for VPC interface endpoints in a given AWS region.
+///
+/// Interface endpoints cannot be stopped and are billed per AZ, so they are deleted instead.
+/// On stop: records the service name, subnets, security groups and tags of each
+/// endpoint in `scheduler:vpc-endpoint:*` tags on its VPC, then deletes it.
+/// On start: re-creates the endpoints recorded on the VPCs in the region whose
+/// recorded tags match the filter. Custom endpoint policies are not preserved.
+pub struct VpcEndpointsScheduler {
+    ec2: Ec2Client,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+/// Settings of a deleted interface endpoint, as recorded on its VPC.
+#[derive(Debug)]
+struct EndpointRecord {
+    endpoint_id: String,
+    vpc_id: String,
+    /// Account owning the VPC, used to rebuild the ARN of the endpoint.
+    owner_id: String,
+    service_name: String,
+    subnet_ids: Vec<String>,
+    security_group_ids: Vec<String>,
+    private_dns: bool,
+    tags: HashMap<String, String>,
+}
+
+impl VpcEndpointsScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            ec2: Ec2Client::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Record the settings of an interface endpoint on its VPC, then delete it.
+    ///
+    /// Gateway endpoints are free and are left untouched.
+    async fn record_and_delete(&self, endpoint_id: &str) -> Result<Outcome> {
+        let endpoint = self.describe_endpoint(endpoint_id).await?;
+        if endpoint.vpc_endpoint_type() != Some(&VpcEndpointType::Interface) {
+            info!(endpoint = %endpoint_id, endpoint_type = ?endpoint.vpc_endpoint_type(), "Not an interface VPC endpoint, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        // The API reports endpoint states in lower case, which the SDK does not map to `State::Available`.
+        if !endpoint
+            .state()
+            .is_some_and(|s| s.as_str().eq_ignore_ascii_case("available"))
+        {
+            info!(endpoint = %endpoint_id, state = ?endpoint.state(), "VPC endpoint not available, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let record = EndpointRecord::from_endpoint(&endpoint, endpoint_id)?;
+        let tags = record.vpc_tags()?;
+        if self.dry_run {
+            info!(endpoint = %endpoint_id, service = %record.service_name, "Dry run: would delete VPC endpoint");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(endpoint = %endpoint_id, vpc = %record.vpc_id, service = %record.service_name, "Recording VPC endpoint settings on its VPC");
+        self.ec2
+            .create_tags()
+            .resources(&record.vpc_id)
+            .set_tags(Some(tags))
+            .send()
+            .await?;
+
+        info!(endpoint = %endpoint_id, "Deleting VPC endpoint");
+        let resp = self
+            .ec2
+            .delete_vpc_endpoints()
+            .vpc_endpoint_ids(endpoint_id)
+            .send()
+            .await?;
+        if let Some(failure) = resp.unsuccessful().first() {
+            bail!(
+                "EC2 rejected the deletion: {}",
+                failure
+                    .error()
+                    .and_then(|e| e.message())
+                    .unwrap_or("unknown reason")
+            );
+        }
+        Ok(Outcome::Applied)
+    }
+
+    /// Re-create an interface endpoint from its record, then remove the record from the VPC.
+    async fn recreate(&self, record: &EndpointRecord) -> Result<Outcome> {
+        if self.dry_run {
+            info!(endpoint = %record.endpoint_id, vpc = %record.vpc_id, service = %record.service_name, "Dry run: would re-create VPC endpoint");
+            return Ok(Outcome::Applied);
+        }
+
+        info!(endpoint = %record.endpoint_id, vpc = %record.vpc_id, service = %record.service_name, "Re-creating VPC endpoint");
+        let resp = self
+            .ec2
+            .create_vpc_endpoint()
+            .vpc_id(&record.vpc_id)
+            .vpc_endpoint_type(VpcEndpointType::Interface)
+            .service_name(&record.service_name)
+            .set_subnet_ids(Some(record.subnet_ids.clone()))
+            .set_security_group_ids(Some(record.security_group_ids.clone()))
+            .private_dns_enabled(record.private_dns)
+            .tag_specifications(
+                TagSpecification::builder()
+                    .resource_type(ResourceType::VpcEndpoint)
+                    .set_tags(Some(
+                        record.tags.iter().map(|(k, v)| ec2_tag(k, v)).collect(),
+                    ))
+                    .build(),
+            )
+            .send()
+            .await?;
+        let new_id = resp
+            .vpc_endpoint()
+            .and_then(|e| e.vpc_endpoint_id())
+            .unwrap_or_default();
+        info!(endpoint = %record.endpoint_id, new_endpoint = %new_id, "VPC endpoint re-created");
+
+        let record_keys: Vec<Tag> = std::iter::once(record.key())
+            .chain(record.tags.keys().map(|k| record.tag_key(k)))
+            .map(|k| Tag::builder().key(k).build())
+            .collect();
+        self.ec2
+            .delete_tags()
+            .resources(&record.vpc_id)
+            .set_tags(Some(record_keys))
+            .send()
+            .await?;
+
+        Ok(Outcome::Applied)
+    }
+
+    async fn describe_endpoint(&self, endpoint_id: &str) -> Result<VpcEndpoint> {
+        let resp = self
+            .ec2
+            .describe_vpc_endpoints()
+            .vpc_endpoint_ids(endpoint_id)
+            .send()
+            .await?;
+
+        resp.vpc_endpoints()
+            .first()
+            .cloned()
+            .with_context(|| format!("VPC endpoint '{}' not found", endpoint_id))
+    }
+
+    /// Region of the client, used to rebuild the ARN of deleted endpoints.
+    fn region(&self) -> String {
+        self.ec2
+            .config()
+            .region()
+            .map(|r| r.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Read the endpoint records from the tags of every VPC in the region.
+    async fn recorded_endpoints(&self) -> Result<Vec<EndpointRecord>> {
+        let mut records = Vec::new();
+        let mut next_token: Option<String> = None;
+
+        loop {
+            let mut req = self.ec2.describe_vpcs();
+            if let Some(ref token) = next_token {
+                req = req.next_token(token);
+            }
+
+            let resp = req.send().await?;
+            for vpc in resp.vpcs() {
+                records.extend(EndpointRecord::from_vpc(vpc));
+            }
+
+            match resp.next_token() {
+                Some(token) if !token.is_empty() => next_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        Ok(records)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for VpcEndpointsScheduler {
+    fn name(&self) -> &'static str {
+        "vpc_endpoints"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ec2:vpc-endpoint", filter)
+                .await?;
+        info!(count = arns.len(), "Found VPC endpoints to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let endpoint_id = extract_endpoint_id(arn);
+            let result = self.record_and_delete(&endpoint_id).await;
+            (endpoint_id, result)
+        })
+        .await;
+        for (endpoint_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&endpoint_id, outcome),
+                Err(e) => {
+                    counts.fail(&endpoint_id, &e);
+                    error!(endpoint = %endpoint_id, error = %e, "Failed to stop VPC endpoint");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let region = self.region();
+        let records: Vec<EndpointRecord> = self
+            .recorded_endpoints()
+            .await?
+            .into_iter()
+            .filter(|r| {
+                filter.matches(
+                    &r.endpoint_arn(&region),
+                    &r.endpoint_id,
+                    r.tags.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                )
+            })
+            .collect();
+        info!(count = records.len(), "Found VPC endpoints to re-create");
+
+        filter.check_discovered(records.len())?;
+        let mut counts = ResourceCounts::new(records.len());
+        let results = run_bounded(
+            self.max_concurrent_operations,
+            &records,
+            |record| async move {
+                let result = self.recreate(record).await;
+                (record, result)
+            },
+        )
+        .await;
+        for (record, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&record.endpoint_id, outcome),
+                Err(e) => {
+                    counts.fail(&record.endpoint_id, &e);
+                    error!(endpoint = %record.endpoint_id, error = %e, "Failed to re-create VPC endpoint");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the VPC endpoints matching the given tag with their current state.
+    ///
+    /// Endpoints deleted by the scheduler and recorded on their VPC are listed as `deleted`.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "ec2:vpc-endpoint", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let endpoint = self.describe_endpoint(&extract_endpoint_id(&arn)).await?;
+            let state = endpoint
+                .state()
+                .map(|s| s.as_str())
+                .unwrap_or("unknown")
+                .to_lowercase();
+            items.push(InventoryItem::new(arn, "ec2:vpc-endpoint", state));
+        }
+
+        let region = self.region();
+        for record in self.recorded_endpoints().await? {
+            if filter.matches(
+                &record.endpoint_arn(&region),
+                &record.endpoint_id,
+                record.tags.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+            ) {
+                items.push(InventoryItem::new(
+                    record.endpoint_arn(&region),
+                    "ec2:vpc-endpoint",
+                    "deleted",
+                ));
+            }
+        }
+        Ok(items)
+    }
+}
+
+impl EndpointRecord {
+    fn from_endpoint(endpoint: &VpcEndpoint, endpoint_id: &str) -> Result<Self> {
+        Ok(Self {
+            endpoint_id: endpoint_id.to_string(),
+            vpc_id: endpoint
+                .vpc_id()
+                .with_context(|| format!("VPC endpoint '{}' has no VPC", endpoint_id))?
+                .to_string(),
+            owner_id: endpoint.owner_id().unwrap_or_default().to_string(),
+            service_name: endpoint
+                .service_name()
+                .with_context(|| format!("VPC endpoint '{}' has no service name", endpoint_id))?
+                .to_string(),
+            subnet_ids: endpoint.subnet_ids().to_vec(),
+            security_group_ids: endpoint
+                .groups()
+                .iter()
+                .filter_map(|g| g.group_id().map(str::to_string))
+                .collect(),
+            private_dns: endpoint.private_dns_enabled().unwrap_or_default(),
+            tags: endpoint
+                .tags()
+                .iter()
+                .filter_map(|t| {
+                    Some((
+                        t.key()?.to_string(),
+                        t.value().unwrap_or_default().to_string(),
+                    ))
+                })
+                .filter(|(k, _)| !k.starts_with("aws:"))
+                .collect(),
+        })
+    }
+
+    /// Read the endpoint records from the tags of a VPC.
+    ///
+    /// Settings are recorded as `service=... subnets=a,b groups=c,d private-dns=true`
+    /// under `scheduler:vpc-endpoint:<endpoint-id>`, and the endpoint tags under
+    /// `scheduler:vpc-endpoint:<endpoint-id>:tag:<key>`.
+    fn from_vpc(vpc: &Vpc) -> Vec<Self> {
+        let Some(vpc_id) = vpc.vpc_id() else {
+            return Vec::new();
+        };
+        let tags: HashMap<&str, &str> = vpc
+            .tags()
+            .iter()
+            .filter_map(|t| Some((t.key()?, t.value().unwrap_or_default())))
+            .collect();
+
+        let mut records: Vec<Self> = tags
+            .iter()
+            .filter_map(|(key, value)| {
+                let endpoint_id = key.strip_prefix(ENDPOINT_TAG_PREFIX)?;
+                if endpoint_id.contains(ENDPOINT_TAG_INFIX) {
+                    return None;
+                }
+                let settings: HashMap<&str, &str> = value
+                    .split_whitespace()
+                    .filter_map(|s| s.split_once('='))
+                    .collect();
+                let list = |name: &str| -> Vec<String> {
+                    settings
+                        .get(name)
+                        .map(|v| {
+                            v.split(',')
+                                .filter(|s| !s.is_empty())
+                                .map(str::to_string)
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+                Some(Self {
+                    endpoint_id: endpoint_id.to_string(),
+                    vpc_id: vpc_id.to_string(),
+                    owner_id: vpc.owner_id().unwrap_or_default().to_string(),
+                    service_name: settings.get("service")?.to_string(),
+                    subnet_ids: list("subnets"),
+                    security_group_ids: list("groups"),
+                    private_dns: settings.get("private-dns") == Some(&"true"),
+                    tags: HashMap::new(),
+                })
+            })
+            .collect();
+
+        for record in &mut records {
+            let prefix = record.tag_key("");
+            record.tags = tags
+                .iter()
+                .filter_map(|(k, v)| Some((k.strip_prefix(&prefix)?.to_string(), v.to_string())))
+                .collect();
+        }
+        records
+    }
+
+    /// ARN of the deleted endpoint in the given region.
+    ///
+    /// e.g. `arn:aws:ec2:region:account:vpc-endpoint/vpce-0123456789abcdef0`, in the
+    /// partition of the region.
+    fn endpoint_arn(&self, region: &str) -> String {
+        format!(
+            "arn:{}:ec2:{}:{}:vpc-endpoint/{}",
+            arn::partition(region),
+            region,
+            self.owner_id,
+            self.endpoint_id
+        )
+    }
+
+    /// Key of the VPC tag holding the settings of the endpoint.
+    fn key(&self) -> String {
+        format!("{}{}", ENDPOINT_TAG_PREFIX, self.endpoint_id)
+    }
+
+    /// Key of the VPC tag holding one of the endpoint's own tags.
+    fn tag_key(&self, key: &str) -> String {
+        format!("{}{}{}", self.key(), ENDPOINT_TAG_INFIX, key)
+    }
+
+    /// Build the VPC tags recording the endpoint.
+    fn vpc_tags(&self) -> Result<Vec<Tag>> {
+        let settings = format!(
+            "service={} subnets={} groups={} private-dns={}",
+            self.service_name,
+            self.subnet_ids.join(","),
+            self.security_group_ids.join(","),
+            self.private_dns
+        );
+        if settings.len() > MAX_TAG_VALUE_LENGTH {
+            bail!(
+                "VPC endpoint '{}' has too many subnets or security groups to be recorded in a tag",
+                self.endpoint_id
+            );
+        }
+
+        let mut tags = vec![ec2_tag(&self.key(), &settings)];
+        tags.extend(self.tags.iter().map(|(k, v)| ec2_tag(&self.tag_key(k), v)));
+        Ok(tags)
+    }
+}
+
+fn ec2_tag(key: &str, value: &str) -> Tag {
+    Tag::builder().key(key).value(value).build()
+}
+
+/// Extract the endpoint ID from a VPC endpoint ARN.
+///
+/// Expected ARN format: `arn:partition:ec2:region:account:vpc-endpoint/vpce-0123456789abcdef0`
+fn extract_endpoint_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_ec2::operation::create_tags::CreateTagsOutput;
+    use aws_sdk_ec2::operation::create_vpc_endpoint::CreateVpcEndpointOutput;
+    use aws_sdk_ec2::operation::delete_tags::DeleteTagsOutput;
+    use aws_sdk_ec2::operation::delete_vpc_endpoints::DeleteVpcEndpointsOutput;
+    use aws_sdk_ec2::operation::describe_vpc_endpoints::DescribeVpcEndpointsOutput;
+    use aws_sdk_ec2::operation::describe_vpcs::DescribeVpcsOutput;
+    use aws_sdk_ec2::types::{SecurityGroupIdentifier, State};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule, TAG_KEY, TAG_VALUE};
+
+    const ENDPOINT_ARN_PREFIX: &str = "arn:aws:ec2:eu-west-1:123456789012:vpc-endpoint/";
+    const SETTINGS: &str = "service=com.amazonaws.eu-west-1.sqs subnets=subnet-0a1b,subnet-2c3d groups=sg-0a1b private-dns=true";
+
+    /// Rule describing the available endpoint `endpoint_id` of the given type.
+    fn describe_endpoint_rule(endpoint_id: &'static str, endpoint_type: VpcEndpointType) -> Rule {
+        mock!(Ec2Client::describe_vpc_endpoints)
+            .match_requests(move |req| req.vpc_endpoint_ids() == [endpoint_id.to_string()])
+            .then_output(move || {
+                DescribeVpcEndpointsOutput::builder()
+                    .vpc_endpoints(
+                        VpcEndpoint::builder()
+                            .vpc_endpoint_id(endpoint_id)
+                            .vpc_endpoint_type(endpoint_type.clone())
+                            .state(State::from("available"))
+                            .vpc_id("vpc-0a1b")
+                            .owner_id("123456789012")
+                            .service_name("com.amazonaws.eu-west-1.sqs")
+                            .subnet_ids("subnet-0a1b")
+                            .subnet_ids("subnet-2c3d")
+                            .groups(
+                                SecurityGroupIdentifier::builder()
+                                    .group_id("sg-0a1b")
+                                    .build(),
+                            )
+                            .private_dns_enabled(true)
+                            .tags(ec2_tag(TAG_KEY, TAG_VALUE))
+                            .tags(ec2_tag("team", "payments"))
+                            .tags(ec2_tag("aws:cloudformation:stack-name", "network"))
+                            .build(),
+                    )
+                    .build()
+            })
+    }
+
+    fn scheduler(ec2: Ec2Client, tagging: TaggingClient) -> VpcEndpointsScheduler {
+        VpcEndpointsScheduler {
+            ec2,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_records_interface_endpoints_on_their_vpc_then_deletes_them() {
+        let arns = get_resources_rule(
+            "ec2:vpc-endpoint",
+            &[
+                &format!("{ENDPOINT_ARN_PREFIX}vpce-0a1b"),
+                &format!("{ENDPOINT_ARN_PREFIX}vpce-2c3d"),
+            ],
+        );
+        let interface = describe_endpoint_rule("vpce-0a1b", VpcEndpointType::Interface);
+        let gateway = describe_endpoint_rule("vpce-2c3d", VpcEndpointType::Gateway);
+        let record = mock!(Ec2Client::create_tags)
+            .match_requests(|req| {
+                let tags: HashMap<&str, &str> = req
+                    .tags()
+                    .iter()
+                    .filter_map(|t| Some((t.key()?, t.value()?)))
+                    .collect();
+                req.resources() == ["vpc-0a1b".to_string()]
+                    && tags
+                        == HashMap::from([
+                            ("scheduler:vpc-endpoint:vpce-0a1b", SETTINGS),
+                            ("scheduler:vpc-endpoint:vpce-0a1b:tag:env", TAG_VALUE),
+                            ("scheduler:vpc-endpoint:vpce-0a1b:tag:team", "payments"),
+                        ])
+            })
+            .then_output(|| CreateTagsOutput::builder().build());
+        let delete = mock!(Ec2Client::delete_vpc_endpoints)
+            .match_requests(|req| req.vpc_endpoint_ids() == ["vpce-0a1b".to_string()])
+            .then_output(|| DeleteVpcEndpointsOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_ec2,
+                RuleMode::MatchAny,
+                [&interface, &gateway, &record, &delete]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(record.num_calls(), 1);
+        assert_eq!(delete.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_recreates_the_endpoints_recorded_on_vpcs() {
+        let vpcs = mock!(Ec2Client::describe_vpcs).then_output(|| {
+            DescribeVpcsOutput::builder()
+                .vpcs(
+                    Vpc::builder()
+                        .vpc_id("vpc-0a1b")
+                        .owner_id("123456789012")
+                        .tags(ec2_tag("Name", "main"))
+                        .tags(ec2_tag("scheduler:vpc-endpoint:vpce-0a1b", SETTINGS))
+                        .tags(ec2_tag(
+                            "scheduler:vpc-endpoint:vpce-0a1b:tag:env",
+                            TAG_VALUE,
+                        ))
+                        .tags(ec2_tag(
+                            "scheduler:vpc-endpoint:vpce-0a1b:tag:team",
+                            "payments",
+                        ))
+                        .build(),
+                )
+                .build()
+        });
+        let create = mock!(Ec2Client::create_vpc_endpoint)
+            .match_requests(|req| {
+                let tags: HashMap<&str, &str> = req
+                    .tag_specifications()
+                    .iter()
+                    .flat_map(|s| s.tags())
+                    .filter_map(|t| Some((t.key()?, t.value()?)))
+                    .collect();
+                req.vpc_id() == Some("vpc-0a1b")
+                    && req.vpc_endpoint_type() == Some(&VpcEndpointType::Interface)
+                    && req.service_name() == Some("com.amazonaws.eu-west-1.sqs")
+                    && req.subnet_ids() == ["subnet-0a1b".to_string(), "subnet-2c3d".to_string()]
+                    && req.security_group_ids() == ["sg-0a1b".to_string()]
+                    && req.private_dns_enabled() == Some(true)
+                    && tags == HashMap::from([(TAG_KEY, TAG_VALUE), ("team", "payments")])
+            })
+            .then_output(|| {
+                CreateVpcEndpointOutput::builder()
+                    .vpc_endpoint(VpcEndpoint::builder().vpc_endpoint_id("vpce-4e5f").build())
+                    .build()
+            });
+        let cleanup = mock!(Ec2Client::delete_tags)
+            .match_requests(|req| {
+                req.resources() == ["vpc-0a1b".to_string()] && req.tags().len() == 3
+            })
+            .then_output(|| DeleteTagsOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_ec2, RuleMode::MatchAny, [&vpcs, &create, &cleanup]),
+            mock_client!(aws_sdk_resourcegroupstagging, Vec::<&Rule>::new()),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 1, 0, 0)
+        );
+        assert_eq!(create.num_calls(), 1);
+        assert_eq!(cleanup.num_calls(), 1);
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use aws_sdk_ec2::types::InstanceStateName;
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_rds::Client as RdsClient;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::arn;
+use crate::config::{AppConfig, ScheduleAction};
+use crate::ec2_batch;
+use crate::filter_resources_by_tags::ResourceFilter;
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::status::{classify, is_transitional, ObservedState};
+use crate::summary::{ResourceCounts, ResourceStatus};
+
+/// Polling settings of the waiters of individual services (`WAITER_MAX_ATTEMPTS`,
+/// `WAITER_DELAY_SECONDS`), e.g. for the instances of an Auto Scaling Group to be running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Waiter {
+    pub max_attempts: u32,
+    /// Time between two attempts.
+    pub delay: Duration,
+}
+
+impl Waiter {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            max_attempts: config.waiter_max_attempts,
+            delay: Duration::from_secs(config.waiter_delay_seconds),
+        }
+    }
+
+    /// Call `pending` until it reports no resource left to wait for, up to
+    /// `max_attempts` times. Returns the resources still pending after the last attempt.
+    pub async fn wait<F, Fut>(&self, what: &str, mut pending: F) -> Result<Vec<String>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Vec<String>>>,
+    {
+        let mut left = Vec::new();
+        for attempt in 1..=self.max_attempts {
+            left = pending().await?;
+            if left.is_empty() {
+                info!("All {} reached", what);
+                break;
+            }
+            info!(attempt, pending = left.len(), "Waiting for {}...", what);
+            if attempt < self.max_attempts {
+                tokio::time::sleep(self.delay).await;
+            }
+        }
+        Ok(left)
+    }
+}
+
+/// Poll EC2 until the given instances are in the `running` state, in batches of
+/// [`ec2_batch::BATCH_SIZE`] instances.
+/// Returns the instances still not running once the waiter gives up.
+pub async fn wait_instances_running(
+    ec2: &Ec2Client,
+    instance_ids: &[String],
+    waiter: Waiter,
+) -> Result<Vec<String>> {
+    info!(
+        count = instance_ids.len(),
+        "Waiting for instances to reach running state"
+    );
+    waiter
+        .wait("instances to reach running state", move || async move {
+            let mut running: HashSet<String> = HashSet::new();
+            for batch in instance_ids.chunks(ec2_batch::BATCH_SIZE) {
+                let resp = ec2
+                    .describe_instance_status()
+                    .include_all_instances(true)
+                    .set_instance_ids(Some(batch.to_vec()))
+                    .send()
+                    .await?;
+                running.extend(
+                    resp.instance_statuses()
+                        .iter()
+                        .filter(|s| {
+                            s.instance_state().and_then(|st| st.name())
+                                == Some(&InstanceStateName::Running)
+                        })
+                        .filter_map(|s| s.instance_id())
+                        .map(String::from),
+                );
+            }
+            Ok(instance_ids
+                .iter()
+                .filter(|id| !running.contains(*id))
+                .cloned()
+                .collect())
+        })
+        .await
+}
+
+/// Poll RDS until the given Aurora clusters and DB instances are `available`.
+/// Returns the clusters and instances still not available once the waiter gives up.
+pub async fn wait_rds_available(
+    rds: &RdsClient,
+    cluster_ids: &[String],
+    db_ids: &[String],
+    waiter: Waiter,
+) -> Result<Vec<String>> {
+    info!(
+        clusters = cluster_ids.len(),
+        instances = db_ids.len(),
+        "Waiting for RDS resources to be available"
+    );
+    waiter
+        .wait("RDS resources to be available", move || async move {
+            let mut pending = Vec::new();
+            for cluster_id in cluster_ids {
+                let resp = rds
+                    .describe_db_clusters()
+                    .db_cluster_identifier(cluster_id)
+                    .send()
+                    .await?;
+                if resp.db_clusters().first().and_then(|c| c.status()) != Some("available") {
+                    pending.push(cluster_id.clone());
+                }
+            }
+            for db_id in db_ids {
+                let resp = rds
+                    .describe_db_instances()
+                    .db_instance_identifier(db_id)
+                    .send()
+                    .await?;
+                if resp
+                    .db_instances()
+                    .first()
+                    .and_then(|i| i.db_instance_status())
+                    != Some("available")
+                {
+                    pending.push(db_id.clone());
+                }
+            }
+            Ok(pending)
+        })
+        .await
+}
+
+/// Wait for the resources of a service to reach the state `action` leads to
+/// (`WAIT_FOR_COMPLETION`).
+///
+/// The resources are listed every `interval` until each of them is settled in
+/// that state, not in a transitional one (`stopping`, `pending`...). Resources
+/// the action failed on are not waited for. Those still not settled after
+/// `timeout` are recorded in `counts` as failed instead of succeeded.
+///
+/// A listing failure ends the wait with a warning, leaving `counts` untouched.
+pub async fn wait_for_target_state(
+    scheduler: &dyn ResourceScheduler,
+    filter: &ResourceFilter,
+    action: &ScheduleAction,
+    counts: &mut ResourceCounts,
+    timeout: Duration,
+    interval: Duration,
+) {
+    let target = match action {
+        ScheduleAction::Stop => ObservedState::Stopped,
+        ScheduleAction::Start => ObservedState::Running,
+        _ => return,
+    };
+    if counts.succeeded == 0 {
+        return;
+    }
+
+    let service = scheduler.name();
+    let deadline = Instant::now() + timeout;
+    loop {
+        let items = match scheduler.list(filter).await {
+            Ok(items) => items,
+            Err(e) => {
+                warn!(service, error = %e, "Failed to list resources, no longer waiting for the target state");
+                return;
+            }
+        };
+        let pending: Vec<InventoryItem> = items
+            .into_iter()
+            .filter(|item| !settled(item, target) && !failed(counts, item))
+            .collect();
+        if pending.is_empty() {
+            info!(service, "Resources reached the target state");
+            return;
+        }
+
+        if Instant::now() + interval > deadline {
+            for item in &pending {
+                warn!(
+                    service,
+                    resource = %item.arn,
+                    state = %item.state,
+                    "Resource did not reach the target state in time"
+                );
+                counts.not_converged(
+                    &item.arn,
+                    format!(
+                        "still {} after {}s (WAIT_TIMEOUT_SECONDS)",
+                        item.state,
+                        timeout.as_secs()
+                    ),
+                );
+            }
+            return;
+        }
+        info!(
+            service,
+            pending = pending.len(),
+            "Waiting for resources to reach the target state"
+        );
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Whether a resource is settled in the target state, or in a state the scheduler leaves untouched.
+fn settled(item: &InventoryItem, target: ObservedState) -> bool {
+    match classify(&item.resource_type, &item.state) {
+        ObservedState::Unmanaged => true,
+        state => state == target && !is_transitional(&item.state),
+    }
+}
+
+/// Whether the action already failed on a resource, reported by ARN or by identifier.
+fn failed(counts: &ResourceCounts, item: &InventoryItem) -> bool {
+    let id = arn::resource_id(&item.arn);
+    counts.resources.iter().any(|r| {
+        r.status == ResourceStatus::Failed
+            && (r.resource == item.arn || arn::resource_id(&r.resource) == id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_matches_the_exact_identifier() {
+        let mut counts = ResourceCounts::new(2);
+        counts.fail("prod-db-1", "boom");
+        counts.fail("arn:aws:ec2:eu-west-1:123456789012:instance/i-2", "boom");
+
+        let item = |arn: &str| InventoryItem::new(arn, "rds:db", "available");
+        assert!(failed(
+            &counts,
+            &item("arn:aws:rds:eu-west-1:123456789012:db:prod-db-1")
+        ));
+        assert!(!failed(
+            &counts,
+            &item("arn:aws:rds:eu-west-1:123456789012:db:db-1")
+        ));
+        assert!(failed(
+            &counts,
+            &item("arn:aws:ec2:eu-west-1:123456789012:instance/i-2")
+        ));
+    }
+}
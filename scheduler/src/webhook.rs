@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::config::WebhookFormat;
+use crate::sns;
+use crate::summary::ExecutionSummary;
+
+/// Color of the notification of a successful run.
+const SUCCESS_COLOR: &str = "2EB886";
+/// Color of the notification of a run with failures.
+const FAILURE_COLOR: &str = "D9534F";
+
+/// Post the summary of a run to a webhook.
+///
+/// Slack and Microsoft Teams incoming webhooks receive a message colored after
+/// the outcome of the run, with the failures listed first. Generic webhooks
+/// receive the summary as JSON, as printed to stdout.
+pub async fn post_summary(
+    url: &str,
+    format: WebhookFormat,
+    summary: &ExecutionSummary,
+) -> Result<()> {
+    info!(format = %format, "Posting execution summary to webhook");
+    reqwest::Client::new()
+        .post(url)
+        .json(&payload(format, summary))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .context("Failed to post execution summary to webhook")?;
+    Ok(())
+}
+
+/// Build the webhook payload of a run.
+fn payload(format: WebhookFormat, summary: &ExecutionSummary) -> Value {
+    let color = if summary.failed_services().is_empty() {
+        SUCCESS_COLOR
+    } else {
+        FAILURE_COLOR
+    };
+    let title = sns::subject(summary);
+    let message = sns::message(summary);
+
+    match format {
+        WebhookFormat::Slack => json!({
+            "text": title,
+            "attachments": [{
+                "color": format!("#{}", color),
+                "text": format!("```{}```", message),
+            }],
+        }),
+        WebhookFormat::Teams => json!({
+            "@type": "MessageCard",
+            "@context": "https://schema.org/extensions",
+            "themeColor": color,
+            "summary": title,
+            "title": title,
+            "text": format!("<pre>{}</pre>", message),
+        }),
+        WebhookFormat::Generic => json!(summary),
+    }
+}
@@ -0,0 +1,232 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use std::str::FromStr;
+
+/// Weekly time window during which a resource is expected to be running.
+///
+/// Parsed from a tag value such as `Mon-Fri 08:00-19:00 Europe/Paris`:
+/// - days: optional, comma-separated days or day ranges (`Mon-Fri`, `Sat,Sun`, `Fri-Mon`);
+///   every day when omitted
+/// - hours: `HH:MM-HH:MM`, the end may be before the start for overnight windows
+/// - timezone: optional IANA timezone, `SCHEDULER_TIMEZONE` when omitted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleWindow {
+    days: Vec<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+    timezone: Option<Tz>,
+}
+
+impl ScheduleWindow {
+    /// Whether the window is open at `now`, evaluated in the window timezone
+    /// (or `default_tz` when the window does not specify one).
+    ///
+    /// Overnight windows belong to the day they open on.
+    pub fn is_open(&self, now: DateTime<Utc>, default_tz: Tz) -> bool {
+        let local = now.with_timezone(&self.timezone.unwrap_or(default_tz));
+        let time = local.time();
+        let today = local.weekday();
+
+        if self.start < self.end {
+            self.days.contains(&today) && time >= self.start && time < self.end
+        } else {
+            (self.days.contains(&today) && time >= self.start)
+                || (self.days.contains(&today.pred()) && time < self.end)
+        }
+    }
+}
+
+impl FromStr for ScheduleWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut tokens: Vec<&str> = s.split_whitespace().collect();
+        if tokens.is_empty() || tokens.len() > 3 {
+            bail!("expected '[days] HH:MM-HH:MM [timezone]'");
+        }
+
+        let days = if tokens[0].contains(':') {
+            ALL_DAYS.to_vec()
+        } else {
+            parse_days(tokens.remove(0))?
+        };
+
+        let hours = tokens.first().context("missing HH:MM-HH:MM hours")?;
+        let (start, end) = hours
+            .split_once('-')
+            .with_context(|| format!("invalid hours '{}': expected HH:MM-HH:MM", hours))?;
+        let start = parse_time(start)?;
+        let end = parse_time(end)?;
+        if start == end {
+            bail!("invalid hours '{}': start and end must differ", hours);
+        }
+
+        let timezone = match tokens.get(1) {
+            Some(name) => Some(
+                name.parse::<Tz>()
+                    .map_err(|_| anyhow!("unknown IANA timezone '{}'", name))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            days,
+            start,
+            end,
+            timezone,
+        })
+    }
+}
+
+const ALL_DAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Parse a comma-separated list of days and day ranges (`Mon-Fri,Sun`).
+///
+/// Ranges wrap around the end of the week (`Fri-Mon`).
+pub(crate) fn parse_days(spec: &str) -> Result<Vec<Weekday>> {
+    let mut days = Vec::new();
+
+    for part in spec.split(',') {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let mut day = parse_day(from)?;
+                let to = parse_day(to)?;
+                loop {
+                    days.push(day);
+                    if day == to {
+                        break;
+                    }
+                    day = day.succ();
+                }
+            }
+            None => days.push(parse_day(part)?),
+        }
+    }
+
+    Ok(days)
+}
+
+fn parse_day(value: &str) -> Result<Weekday> {
+    value
+        .trim()
+        .parse::<Weekday>()
+        .map_err(|_| anyhow!("invalid day '{}'", value))
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .with_context(|| format!("invalid time '{}': expected HH:MM", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn is_open_within_the_days_and_hours_of_the_window() {
+        // 2025-01-03 is a Friday.
+        let cases = [
+            ("Mon-Fri 08:00-19:00", "2025-01-03T08:00:00Z", Tz::UTC, true),
+            (
+                "Mon-Fri 08:00-19:00",
+                "2025-01-03T19:00:00Z",
+                Tz::UTC,
+                false,
+            ),
+            (
+                "Mon-Fri 08:00-19:00",
+                "2025-01-04T10:00:00Z",
+                Tz::UTC,
+                false,
+            ),
+            ("22:00-06:00", "2025-01-03T23:00:00Z", Tz::UTC, true),
+            ("22:00-06:00", "2025-01-04T05:59:00Z", Tz::UTC, true),
+            ("22:00-06:00", "2025-01-03T12:00:00Z", Tz::UTC, false),
+            ("Fri 22:00-06:00", "2025-01-04T02:00:00Z", Tz::UTC, true),
+            ("Fri 22:00-06:00", "2025-01-03T02:00:00Z", Tz::UTC, false),
+            ("Fri 22:00-06:00", "2025-01-04T23:00:00Z", Tz::UTC, false),
+            ("Fri-Mon 08:00-18:00", "2025-01-05T10:00:00Z", Tz::UTC, true),
+            ("Fri-Mon 08:00-18:00", "2025-01-06T10:00:00Z", Tz::UTC, true),
+            (
+                "Fri-Mon 08:00-18:00",
+                "2025-01-07T10:00:00Z",
+                Tz::UTC,
+                false,
+            ),
+            (
+                "Mon-Fri 08:00-19:00 Europe/Paris",
+                "2025-01-03T07:30:00Z",
+                Tz::UTC,
+                true,
+            ),
+            (
+                "Mon-Fri 08:00-19:00 Europe/Paris",
+                "2025-01-03T18:30:00Z",
+                Tz::UTC,
+                false,
+            ),
+            (
+                "08:00-19:00",
+                "2025-01-03T12:30:00Z",
+                Tz::America__New_York,
+                false,
+            ),
+            (
+                "08:00-19:00",
+                "2025-01-03T13:30:00Z",
+                Tz::America__New_York,
+                true,
+            ),
+        ];
+        for (window, now, default_tz, expected) in cases {
+            let schedule: ScheduleWindow = window.parse().unwrap();
+            assert_eq!(
+                schedule.is_open(at(now), default_tz),
+                expected,
+                "{} at {}",
+                window,
+                now
+            );
+        }
+    }
+
+    #[test]
+    fn parse_days_wraps_around_the_end_of_the_week() {
+        assert_eq!(
+            parse_days("Fri-Mon").unwrap(),
+            [Weekday::Fri, Weekday::Sat, Weekday::Sun, Weekday::Mon]
+        );
+        assert_eq!(
+            parse_days("Mon,Wed-Thu").unwrap(),
+            [Weekday::Mon, Weekday::Wed, Weekday::Thu]
+        );
+    }
+
+    #[test]
+    fn from_str_rejects_invalid_windows() {
+        for window in [
+            "",
+            "Mon-Fri",
+            "Mon-Fri 08:00-08:00",
+            "Mon-Fri 8h-19h",
+            "Funday 08:00-19:00",
+            "Mon-Fri 08:00-19:00 Mars/Olympus",
+            "Mon-Fri 08:00-19:00 UTC extra",
+        ] {
+            assert!(window.parse::<ScheduleWindow>().is_err(), "{}", window);
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use aws_sdk_workspaces::types::{
+    RunningMode, StartRequest, StopRequest, Workspace, WorkspaceState,
+};
+use aws_sdk_workspaces::Client as WorkSpacesClient;
+use tracing::{error, info};
+
+use crate::arn;
+use crate::concurrency::run_bounded;
+use crate::filter_resources_by_tags::{self, ResourceFilter};
+use crate::inventory::InventoryItem;
+use crate::registry::ResourceScheduler;
+use crate::summary::{Outcome, ResourceCounts};
+
+/// Stop/start handler for Amazon WorkSpaces in a given AWS region.
+///
+/// Uses the Resource Groups Tagging API to discover WorkSpaces matching a tag,
+/// then performs the requested action on each one.
+/// WorkSpaces in `ALWAYS_ON` running mode are billed monthly and cannot be stopped,
+/// so they are skipped.
+pub struct WorkSpacesScheduler {
+    workspaces: WorkSpacesClient,
+    tagging: TaggingClient,
+    dry_run: bool,
+    max_concurrent_operations: usize,
+}
+
+impl WorkSpacesScheduler {
+    pub fn new(config: &SdkConfig, dry_run: bool, max_concurrent_operations: usize) -> Self {
+        Self {
+            workspaces: WorkSpacesClient::new(config),
+            tagging: TaggingClient::new(config),
+            dry_run,
+            max_concurrent_operations,
+        }
+    }
+
+    /// Describe a single WorkSpace.
+    async fn describe_workspace(&self, workspace_id: &str) -> Result<Workspace> {
+        let resp = self
+            .workspaces
+            .describe_workspaces()
+            .workspace_ids(workspace_id)
+            .send()
+            .await?;
+
+        resp.workspaces()
+            .first()
+            .cloned()
+            .with_context(|| format!("WorkSpace '{}' not found", workspace_id))
+    }
+
+    async fn stop_workspace(&self, workspace_id: &str) -> Result<Outcome> {
+        let workspace = self.describe_workspace(workspace_id).await?;
+
+        let running_mode = workspace
+            .workspace_properties()
+            .and_then(|p| p.running_mode());
+        if running_mode == Some(&RunningMode::AlwaysOn) {
+            info!(workspace = %workspace_id, "WorkSpace runs in ALWAYS_ON mode and cannot be stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+
+        let state = workspace.state();
+        if matches!(
+            state,
+            Some(WorkspaceState::Stopped | WorkspaceState::Stopping)
+        ) {
+            info!(workspace = %workspace_id, state = ?state, "WorkSpace already stopped, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(workspace = %workspace_id, "Dry run: would stop WorkSpace");
+            return Ok(Outcome::Applied);
+        }
+        info!(workspace = %workspace_id, "Stopping WorkSpace");
+        let resp = self
+            .workspaces
+            .stop_workspaces()
+            .stop_workspace_requests(StopRequest::builder().workspace_id(workspace_id).build())
+            .send()
+            .await?;
+
+        if let Some(failed) = resp.failed_requests().first() {
+            bail!(
+                "{}: {}",
+                failed.error_code().unwrap_or("unknown error"),
+                failed.error_message().unwrap_or_default()
+            );
+        }
+        Ok(Outcome::Applied)
+    }
+
+    async fn start_workspace(&self, workspace_id: &str) -> Result<Outcome> {
+        let workspace = self.describe_workspace(workspace_id).await?;
+
+        let state = workspace.state();
+        if matches!(
+            state,
+            Some(WorkspaceState::Available | WorkspaceState::Starting)
+        ) {
+            info!(workspace = %workspace_id, state = ?state, "WorkSpace already running, skipping");
+            return Ok(Outcome::Skipped);
+        }
+        if self.dry_run {
+            info!(workspace = %workspace_id, "Dry run: would start WorkSpace");
+            return Ok(Outcome::Applied);
+        }
+        info!(workspace = %workspace_id, "Starting WorkSpace");
+        let resp = self
+            .workspaces
+            .start_workspaces()
+            .start_workspace_requests(StartRequest::builder().workspace_id(workspace_id).build())
+            .send()
+            .await?;
+
+        if let Some(failed) = resp.failed_requests().first() {
+            bail!(
+                "{}: {}",
+                failed.error_code().unwrap_or("unknown error"),
+                failed.error_message().unwrap_or_default()
+            );
+        }
+        Ok(Outcome::Applied)
+    }
+}
+
+#[async_trait]
+impl ResourceScheduler for WorkSpacesScheduler {
+    fn name(&self) -> &'static str {
+        "workspaces"
+    }
+
+    async fn stop(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "workspaces:workspace", filter)
+                .await?;
+        info!(count = arns.len(), "Found WorkSpaces to stop");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let workspace_id = extract_workspace_id(arn);
+            let result = self.stop_workspace(&workspace_id).await;
+            (workspace_id, result)
+        })
+        .await;
+        for (workspace_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&workspace_id, outcome),
+                Err(e) => {
+                    counts.fail(&workspace_id, &e);
+                    error!(workspace = %workspace_id, error = %e, "Failed to stop WorkSpace");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn start(&self, filter: &ResourceFilter) -> Result<ResourceCounts> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "workspaces:workspace", filter)
+                .await?;
+        info!(count = arns.len(), "Found WorkSpaces to start");
+
+        filter.check_discovered(arns.len())?;
+        let mut counts = ResourceCounts::new(arns.len());
+        let results = run_bounded(self.max_concurrent_operations, &arns, |arn| async move {
+            let workspace_id = extract_workspace_id(arn);
+            let result = self.start_workspace(&workspace_id).await;
+            (workspace_id, result)
+        })
+        .await;
+        for (workspace_id, result) in results {
+            match result {
+                Ok(outcome) => counts.record(&workspace_id, outcome),
+                Err(e) => {
+                    counts.fail(&workspace_id, &e);
+                    error!(workspace = %workspace_id, error = %e, "Failed to start WorkSpace");
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// List the WorkSpaces matching the given tag with their current state.
+    async fn list(&self, filter: &ResourceFilter) -> Result<Vec<InventoryItem>> {
+        let arns =
+            filter_resources_by_tags::get_resources(&self.tagging, "workspaces:workspace", filter)
+                .await?;
+
+        let mut items = Vec::with_capacity(arns.len());
+        for arn in arns {
+            let workspace = self.describe_workspace(&extract_workspace_id(&arn)).await?;
+            let state = workspace
+                .state()
+                .map(|s| s.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            items.push(InventoryItem::new(arn, "workspaces:workspace", state));
+        }
+        Ok(items)
+    }
+}
+
+/// Extract the WorkSpace ID from a WorkSpace ARN.
+///
+/// Expected ARN format: `arn:partition:workspaces:region:account:workspace/ws-xxxxx`
+fn extract_workspace_id(arn: &str) -> String {
+    arn::resource_id(arn)
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_workspaces::operation::describe_workspaces::DescribeWorkspacesOutput;
+    use aws_sdk_workspaces::operation::start_workspaces::StartWorkspacesOutput;
+    use aws_sdk_workspaces::operation::stop_workspaces::StopWorkspacesOutput;
+    use aws_sdk_workspaces::types::{FailedWorkspaceChangeRequest, WorkspaceProperties};
+    use aws_smithy_mocks::{mock, mock_client, Rule, RuleMode};
+
+    use super::*;
+    use crate::config::ScheduleAction;
+    use crate::test_util::{filter, get_resources_rule};
+
+    const WORKSPACE_ARN_PREFIX: &str = "arn:aws:workspaces:eu-west-1:123456789012:workspace/";
+
+    /// Rule describing `workspace_id` in the given state and running mode.
+    fn describe_workspace_rule(
+        workspace_id: &'static str,
+        state: WorkspaceState,
+        running_mode: RunningMode,
+    ) -> Rule {
+        mock!(WorkSpacesClient::describe_workspaces)
+            .match_requests(move |req| req.workspace_ids() == [workspace_id.to_string()])
+            .then_output(move || {
+                DescribeWorkspacesOutput::builder()
+                    .workspaces(
+                        Workspace::builder()
+                            .workspace_id(workspace_id)
+                            .state(state.clone())
+                            .workspace_properties(
+                                WorkspaceProperties::builder()
+                                    .running_mode(running_mode.clone())
+                                    .build(),
+                            )
+                            .build(),
+                    )
+                    .build()
+            })
+    }
+
+    fn scheduler(workspaces: WorkSpacesClient, tagging: TaggingClient) -> WorkSpacesScheduler {
+        WorkSpacesScheduler {
+            workspaces,
+            tagging,
+            dry_run: false,
+            max_concurrent_operations: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_skips_always_on_workspaces() {
+        let arns = get_resources_rule(
+            "workspaces:workspace",
+            &[
+                &format!("{WORKSPACE_ARN_PREFIX}ws-auto0001"),
+                &format!("{WORKSPACE_ARN_PREFIX}ws-always001"),
+            ],
+        );
+        let auto_stop = describe_workspace_rule(
+            "ws-auto0001",
+            WorkspaceState::Available,
+            RunningMode::AutoStop,
+        );
+        let always_on = describe_workspace_rule(
+            "ws-always001",
+            WorkspaceState::Available,
+            RunningMode::AlwaysOn,
+        );
+        let stop = mock!(WorkSpacesClient::stop_workspaces)
+            .match_requests(|req| {
+                req.stop_workspace_requests()
+                    .iter()
+                    .map(|r| r.workspace_id())
+                    .eq([Some("ws-auto0001")])
+            })
+            .then_output(|| StopWorkspacesOutput::builder().build());
+        let scheduler = scheduler(
+            mock_client!(
+                aws_sdk_workspaces,
+                RuleMode::MatchAny,
+                [&auto_stop, &always_on, &stop]
+            ),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler.stop(&filter(ScheduleAction::Stop)).await.unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (2, 1, 1, 0)
+        );
+        assert_eq!(stop.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn start_reports_requests_rejected_by_workspaces() {
+        let arns = get_resources_rule(
+            "workspaces:workspace",
+            &[&format!("{WORKSPACE_ARN_PREFIX}ws-auto0001")],
+        );
+        let stopped = describe_workspace_rule(
+            "ws-auto0001",
+            WorkspaceState::Stopped,
+            RunningMode::AutoStop,
+        );
+        let start = mock!(WorkSpacesClient::start_workspaces).then_output(|| {
+            StartWorkspacesOutput::builder()
+                .failed_requests(
+                    FailedWorkspaceChangeRequest::builder()
+                        .workspace_id("ws-auto0001")
+                        .error_code("InvalidResourceState.Maintenance")
+                        .error_message("The WorkSpace is under maintenance")
+                        .build(),
+                )
+                .build()
+        });
+        let scheduler = scheduler(
+            mock_client!(aws_sdk_workspaces, RuleMode::MatchAny, [&stopped, &start]),
+            mock_client!(aws_sdk_resourcegroupstagging, [&arns]),
+        );
+
+        let counts = scheduler
+            .start(&filter(ScheduleAction::Start))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (
+                counts.discovered,
+                counts.succeeded,
+                counts.skipped,
+                counts.failed
+            ),
+            (1, 0, 0, 1)
+        );
+        assert_eq!(
+            counts.resources[0].error.as_deref(),
+            Some("InvalidResourceState.Maintenance: The WorkSpace is under maintenance")
+        );
+    }
+}
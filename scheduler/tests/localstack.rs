@@ -0,0 +1,236 @@
+//! Integration tests running discovery and stop/start flows against LocalStack.
+//!
+//! Start LocalStack, then run them with:
+//!
+//! ```bash
+//! docker run -d -p 4566:4566 localstack/localstack
+//! cargo test --features localstack --test localstack
+//! ```
+//!
+//! `AWS_ENDPOINT_URL` overrides the default `http://localhost:4566` endpoint.
+//! Each test tags the resources it creates with its own value, so tests do
+//! not see each other's resources.
+
+#![cfg(feature = "localstack")]
+
+use aws_config::{BehaviorVersion, Region, SdkConfig};
+use aws_sdk_ec2::types::{InstanceStateName, ResourceType, Tag, TagSpecification};
+use aws_sdk_ec2::Client as Ec2Client;
+use aws_sdk_resourcegroupstagging::Client as TaggingClient;
+use scheduler::config::{Ec2StopMode, ScheduleAction};
+use scheduler::ec2::Ec2Scheduler;
+use scheduler::filter_resources_by_tags::{self, ResourceFilter};
+use scheduler::ResourceScheduler;
+
+const DEFAULT_ENDPOINT_URL: &str = "http://localhost:4566";
+const REGION: &str = "us-east-1";
+const TAG_KEY: &str = "scheduler-it";
+
+async fn sdk_config() -> SdkConfig {
+    let endpoint_url =
+        std::env::var("AWS_ENDPOINT_URL").unwrap_or_else(|_| DEFAULT_ENDPOINT_URL.to_string());
+    aws_config::defaults(BehaviorVersion::latest())
+        .region(Region::from_static(REGION))
+        .endpoint_url(endpoint_url)
+        .test_credentials()
+        .load()
+        .await
+}
+
+/// Filter selecting the resources created by one test.
+fn filter(tag_value: &str, action: ScheduleAction) -> ResourceFilter {
+    ResourceFilter {
+        tag_key: TAG_KEY.to_string(),
+        tag_values: vec![tag_value.to_string()],
+        required_tags: Vec::new(),
+        window_tag_key: None,
+        name_include_regex: None,
+        name_exclude_regex: None,
+        protected_tags: Vec::new(),
+        exclusion_tag_key: None,
+        exclusion_tag_value: None,
+        terminate_confirm_tag: None,
+        max_resources: None,
+        planned: None,
+        priority_tag_key: None,
+        wave: None,
+        action,
+        timezone: chrono_tz::UTC,
+    }
+}
+
+/// Tag value unique to a test and a test run.
+fn tag_value(test: &str) -> String {
+    format!("{}-{}", test, std::process::id())
+}
+
+/// Launch `count` running instances tagged with `tag_value` and return their IDs.
+async fn launch_instances(ec2: &Ec2Client, tag_value: &str, count: i32) -> Vec<String> {
+    let images = ec2.describe_images().owners("amazon").send().await.unwrap();
+    let image_id = images
+        .images()
+        .first()
+        .and_then(|i| i.image_id())
+        .expect("LocalStack provides AMIs");
+
+    let resp = ec2
+        .run_instances()
+        .image_id(image_id)
+        .instance_type("t3.micro".into())
+        .min_count(count)
+        .max_count(count)
+        .tag_specifications(
+            TagSpecification::builder()
+                .resource_type(ResourceType::Instance)
+                .tags(Tag::builder().key(TAG_KEY).value(tag_value).build())
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+
+    resp.instances()
+        .iter()
+        .filter_map(|i| i.instance_id())
+        .map(str::to_string)
+        .collect()
+}
+
+async fn instance_states(ec2: &Ec2Client, instance_ids: &[String]) -> Vec<InstanceStateName> {
+    let resp = ec2
+        .describe_instances()
+        .set_instance_ids(Some(instance_ids.to_vec()))
+        .send()
+        .await
+        .unwrap();
+
+    resp.reservations()
+        .iter()
+        .flat_map(|r| r.instances())
+        .filter_map(|i| i.state().and_then(|s| s.name()).cloned())
+        .collect()
+}
+
+#[tokio::test]
+async fn discovery_follows_pagination() {
+    let config = sdk_config().await;
+    let tag_value = tag_value("pagination");
+    // More instances than fit on a single page of the Tagging API.
+    let mut instance_ids = launch_instances(&Ec2Client::new(&config), &tag_value, 120).await;
+
+    let arns = filter_resources_by_tags::get_resources(
+        &TaggingClient::new(&config),
+        "ec2:instance",
+        &filter(&tag_value, ScheduleAction::Stop),
+    )
+    .await
+    .unwrap();
+
+    let mut discovered: Vec<String> = arns
+        .iter()
+        .map(|arn| arn.rsplit('/').next().unwrap().to_string())
+        .collect();
+    discovered.sort();
+    instance_ids.sort();
+    assert_eq!(discovered, instance_ids);
+}
+
+#[tokio::test]
+async fn ec2_instances_are_stopped_and_started() {
+    let config = sdk_config().await;
+    let ec2 = Ec2Client::new(&config);
+    let tag_value = tag_value("stop-start");
+    let instance_ids = launch_instances(&ec2, &tag_value, 2).await;
+    let scheduler = Ec2Scheduler::new(&config, Ec2StopMode::Stop, false, 10);
+
+    let counts = scheduler
+        .stop(&filter(&tag_value, ScheduleAction::Stop))
+        .await
+        .unwrap();
+    assert_eq!(
+        (counts.discovered, counts.succeeded, counts.failed),
+        (2, 2, 0)
+    );
+    for state in instance_states(&ec2, &instance_ids).await {
+        assert!(matches!(
+            state,
+            InstanceStateName::Stopped | InstanceStateName::Stopping
+        ));
+    }
+
+    let counts = scheduler
+        .stop(&filter(&tag_value, ScheduleAction::Stop))
+        .await
+        .unwrap();
+    assert_eq!((counts.succeeded, counts.skipped), (0, 2));
+
+    let counts = scheduler
+        .start(&filter(&tag_value, ScheduleAction::Start))
+        .await
+        .unwrap();
+    assert_eq!(
+        (counts.discovered, counts.succeeded, counts.failed),
+        (2, 2, 0)
+    );
+    for state in instance_states(&ec2, &instance_ids).await {
+        assert!(matches!(
+            state,
+            InstanceStateName::Running | InstanceStateName::Pending
+        ));
+    }
+}
+
+#[tokio::test]
+async fn ec2_list_reports_instance_arns_and_states() {
+    let config = sdk_config().await;
+    let tag_value = tag_value("list");
+    let instance_ids = launch_instances(&Ec2Client::new(&config), &tag_value, 1).await;
+    let scheduler = Ec2Scheduler::new(&config, Ec2StopMode::Stop, false, 10);
+
+    let items = scheduler
+        .list(&filter(&tag_value, ScheduleAction::List))
+        .await
+        .unwrap();
+
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].resource_type, "ec2:instance");
+    assert!(items[0]
+        .arn
+        .starts_with(&format!("arn:aws:ec2:{}:", REGION)));
+    assert!(items[0]
+        .arn
+        .ends_with(&format!("instance/{}", instance_ids[0])));
+    assert!(matches!(items[0].state.as_str(), "running" | "pending"));
+}
+
+#[tokio::test]
+async fn excluded_instances_are_left_running() {
+    let config = sdk_config().await;
+    let ec2 = Ec2Client::new(&config);
+    let tag_value = tag_value("exclusion");
+    let instance_ids = launch_instances(&ec2, &tag_value, 1).await;
+    ec2.create_tags()
+        .resources(&instance_ids[0])
+        .tags(
+            Tag::builder()
+                .key("scheduler:exclude")
+                .value("true")
+                .build(),
+        )
+        .send()
+        .await
+        .unwrap();
+    let scheduler = Ec2Scheduler::new(&config, Ec2StopMode::Stop, false, 10);
+    let filter = ResourceFilter {
+        exclusion_tag_key: Some("scheduler:exclude".to_string()),
+        ..filter(&tag_value, ScheduleAction::Stop)
+    };
+
+    let counts = scheduler.stop(&filter).await.unwrap();
+
+    assert_eq!(counts.discovered, 0);
+    assert_eq!(
+        instance_states(&ec2, &instance_ids).await,
+        [InstanceStateName::Running]
+    );
+}